@@ -5,18 +5,17 @@ use aes_gcm::{
     Aes256Gcm, Key, Nonce,
     aead::{Aead, KeyInit},
 };
-use argon2::{
-    Argon2,
-    password_hash::{
-        SaltString,
-        rand_core::{OsRng, RngCore},
-    },
+use argon2::password_hash::{
+    SaltString,
+    rand_core::{OsRng, RngCore},
 };
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use clap::{Parser, Subcommand};
-use directories::ProjectDirs;
 use libp2p::identity::Keypair;
-use rpc_client::{rpc_check_balance, rpc_create_deposit_intent, rpc_spend, rpc_start_signing};
+use rpc_client::{
+    rpc_cancel_deposit_intent, rpc_check_balance, rpc_create_deposit_intent, rpc_get_block,
+    rpc_spend, rpc_start_signing,
+};
 use std::{fs, path::PathBuf};
 
 use crate::{
@@ -25,7 +24,8 @@ use crate::{
 };
 use node::{
     NodeConfig, NodeConfigBuilder,
-    config::{EncryptionParams, KeyData},
+    config::{Argon2Params, CURRENT_KEY_FORMAT_VERSION, EncryptionParams, KeyData},
+    data_dir::DataDir,
     start_node::start_node,
 };
 
@@ -36,32 +36,36 @@ struct VaultConfigPath {
     config_file_path: PathBuf,
 }
 
-fn get_key_file_path() -> Result<VaultConfigPath, KeygenError> {
-    let proj_dirs = ProjectDirs::from("", "", "TheVault").ok_or_else(|| {
-        KeygenError::DirectoryCreation("Failed to determine project directory".into())
-    })?;
+fn default_data_dir() -> Result<DataDir, KeygenError> {
+    let data_dir =
+        DataDir::default_location().map_err(|e| KeygenError::DirectoryCreation(e.to_string()))?;
+    data_dir
+        .migrate()
+        .map_err(|e| KeygenError::DirectoryCreation(e.to_string()))?;
+    Ok(data_dir)
+}
 
-    let config_dir = proj_dirs.config_dir();
-    fs::create_dir_all(config_dir).map_err(|e| KeygenError::DirectoryCreation(e.to_string()))?;
+fn get_key_file_path() -> Result<VaultConfigPath, KeygenError> {
+    let data_dir = default_data_dir()?;
 
     Ok(VaultConfigPath {
-        key_file_path: config_dir.join("config.json"),
-        config_file_path: config_dir.join("config.yaml"),
+        key_file_path: data_dir.key_file_path(),
+        config_file_path: data_dir.config_file_path(),
     })
 }
 
 fn get_log_file_path() -> Result<PathBuf, KeygenError> {
-    let proj_dirs = ProjectDirs::from("", "", "TheVault").ok_or_else(|| {
-        KeygenError::DirectoryCreation("Failed to determine project directory".into())
-    })?;
-
-    let log_dir = proj_dirs.config_dir();
-    let path = log_dir.join("node.log");
-    Ok(path)
+    Ok(default_data_dir()?.log_file_path())
 }
 
-fn generate_key(password: &str, salt: &SaltString) -> Result<Vec<u8>, KeygenError> {
-    let argon2 = Argon2::default();
+fn generate_key(
+    password: &str,
+    salt: &SaltString,
+    argon2_params: &Argon2Params,
+) -> Result<Vec<u8>, KeygenError> {
+    let argon2 = argon2_params
+        .hasher()
+        .map_err(|e| KeygenError::Encryption(e.to_string()))?;
     let password_bytes = password.as_bytes();
     let mut key = vec![0u8; 32];
 
@@ -74,9 +78,10 @@ fn generate_key(password: &str, salt: &SaltString) -> Result<Vec<u8>, KeygenErro
 fn encrypt_private_key(
     keypair: &Keypair,
     password: &str,
+    argon2_params: Argon2Params,
 ) -> Result<(String, EncryptionParams), KeygenError> {
     let salt = SaltString::generate(&mut OsRng);
-    let key = generate_key(password, &salt)?;
+    let key = generate_key(password, &salt, &argon2_params)?;
 
     let mut nonce_bytes = [0u8; 12];
     OsRng.fill_bytes(&mut nonce_bytes);
@@ -96,6 +101,7 @@ fn encrypt_private_key(
         kdf: "argon2id".to_string(),
         salt_b64: salt.to_string(),
         iv_b64: BASE64.encode(nonce_bytes),
+        argon2: argon2_params,
     };
 
     Ok((BASE64.encode(ciphertext), params))
@@ -134,6 +140,15 @@ enum Commands {
         output_dir: Option<String>,
         #[arg(short, long)]
         file_name: Option<String>,
+        /// Argon2 memory cost in KiB used to encrypt the keys
+        #[arg(long)]
+        argon2_memory_kib: Option<u32>,
+        /// Argon2 iteration count used to encrypt the keys
+        #[arg(long)]
+        argon2_iterations: Option<u32>,
+        /// Argon2 parallelism used to encrypt the keys
+        #[arg(long)]
+        argon2_parallelism: Option<u32>,
     },
     /// Run the node and connect to the network
     Run {
@@ -141,6 +156,10 @@ enum Commands {
         key_file_path: Option<String>,
         #[arg(short = 'c', long)]
         config_file_path: Option<String>,
+        /// Data directory holding the key, config, database and log files; overrides the
+        /// platform default when no key or config file is given
+        #[arg(long)]
+        data_dir: Option<String>,
         #[arg(short = 'p', long)]
         grpc_port: Option<u16>,
         #[arg(short = 'u', long)]
@@ -178,16 +197,28 @@ enum Commands {
         amount: u64,
         #[arg(short, long)]
         endpoint: Option<String>,
+        #[arg(long)]
+        min_confirmations: Option<u32>,
     },
     GetPendingDepositIntents {
         #[arg(short, long)]
         endpoint: Option<String>,
     },
+    CancelDepositIntent {
+        deposit_tracking_id: String,
+        #[arg(short, long)]
+        endpoint: Option<String>,
+    },
     CheckBalance {
         #[arg(short, long)]
         endpoint: Option<String>,
         address: String,
     },
+    GetBlock {
+        height: u64,
+        #[arg(short, long)]
+        endpoint: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -199,8 +230,17 @@ async fn main() -> Result<(), CliError> {
         Commands::Setup {
             output_dir,
             file_name,
+            argon2_memory_kib,
+            argon2_iterations,
+            argon2_parallelism,
         } => {
-            setup_config(output_dir, file_name).map_err(|e| {
+            let defaults = Argon2Params::default();
+            let argon2_params = Argon2Params {
+                memory_kib: argon2_memory_kib.unwrap_or(defaults.memory_kib),
+                iterations: argon2_iterations.unwrap_or(defaults.iterations),
+                parallelism: argon2_parallelism.unwrap_or(defaults.parallelism),
+            };
+            setup_config(output_dir, file_name, argon2_params).map_err(|e| {
                 println!("Keygen Error: {e}");
                 CliError::KeygenError(e)
             })?;
@@ -208,6 +248,7 @@ async fn main() -> Result<(), CliError> {
         Commands::Run {
             key_file_path,
             config_file_path,
+            data_dir,
             grpc_port,
             libp2p_udp_port,
             libp2p_tcp_port,
@@ -222,6 +263,7 @@ async fn main() -> Result<(), CliError> {
             start_node_cli(StartNodeConfigParams {
                 key_file_path,
                 config_file_path,
+                data_dir,
                 grpc_port,
                 libp2p_udp_port,
                 libp2p_tcp_port,
@@ -257,10 +299,12 @@ async fn main() -> Result<(), CliError> {
             amount,
             public_key,
             endpoint,
+            min_confirmations,
         } => {
-            let response = rpc_create_deposit_intent(endpoint, amount, public_key)
-                .await
-                .map_err(CliError::RpcError)?;
+            let response =
+                rpc_create_deposit_intent(endpoint, amount, public_key, min_confirmations)
+                    .await
+                    .map_err(CliError::RpcError)?;
 
             println!("Deposit intent created: {response:?}");
         }
@@ -269,25 +313,50 @@ async fn main() -> Result<(), CliError> {
                 .await
                 .map_err(CliError::RpcError)?;
         }
+        Commands::CancelDepositIntent {
+            deposit_tracking_id,
+            endpoint,
+        } => {
+            rpc_cancel_deposit_intent(endpoint, deposit_tracking_id)
+                .await
+                .map_err(CliError::RpcError)?;
+        }
         Commands::CheckBalance { endpoint, address } => {
             rpc_check_balance(endpoint, address)
                 .await
                 .map_err(CliError::RpcError)?;
         }
+        Commands::GetBlock { height, endpoint } => {
+            let report = rpc_get_block(endpoint, height)
+                .await
+                .map_err(CliError::RpcError)?;
+
+            if !report.is_verified() {
+                return Err(CliError::NodeError(format!(
+                    "Block {height} failed verification"
+                )));
+            }
+        }
     }
 
     Ok(())
 }
 
-fn setup_config(output_dir: Option<String>, file_name: Option<String>) -> Result<(), KeygenError> {
+fn setup_config(
+    output_dir: Option<String>,
+    file_name: Option<String>,
+    argon2_params: Argon2Params,
+) -> Result<(), KeygenError> {
     let keypair = Keypair::generate_ed25519();
     let public_key_b58 = keypair.public().to_peer_id().to_base58();
 
     let user_password = get_password()?;
 
-    let (encrypted_private_key, encryption_params) = encrypt_private_key(&keypair, &user_password)?;
+    let (encrypted_private_key, encryption_params) =
+        encrypt_private_key(&keypair, &user_password, argon2_params)?;
 
     let key_data = KeyData {
+        version: CURRENT_KEY_FORMAT_VERSION,
         public_key_b58: public_key_b58.clone(),
         encrypted_private_key_b64: encrypted_private_key,
         encryption_params,
@@ -321,6 +390,7 @@ fn setup_config(output_dir: Option<String>, file_name: Option<String>) -> Result
         .config_file_path(paths.config_file_path.clone())
         .log_file_path(get_log_file_path().ok())
         .password(&user_password)
+        .argon2_params(argon2_params)
         .key_data(key_data)
         .build()
         .map_err(|e| KeygenError::KeyFileNotFound(e.to_string()))?;
@@ -346,6 +416,7 @@ fn setup_config(output_dir: Option<String>, file_name: Option<String>) -> Result
 struct StartNodeConfigParams {
     key_file_path: Option<String>,
     config_file_path: Option<String>,
+    data_dir: Option<String>,
     grpc_port: Option<u16>,
     libp2p_udp_port: Option<u16>,
     libp2p_tcp_port: Option<u16>,
@@ -359,10 +430,18 @@ struct StartNodeConfigParams {
 }
 
 async fn start_node_cli(params: StartNodeConfigParams) -> Result<(), NodeError> {
-    let mut config = match NodeConfig::get_config(
-        params.key_file_path.clone(),
-        params.config_file_path.clone(),
+    let loaded = match (
+        &params.data_dir,
+        &params.key_file_path,
+        &params.config_file_path,
     ) {
+        (Some(data_dir), None, None) => NodeConfig::load_from_data_dir(&DataDir::new(data_dir)),
+        _ => NodeConfig::get_config(
+            params.key_file_path.clone(),
+            params.config_file_path.clone(),
+        ),
+    };
+    let mut config = match loaded {
         Ok(config) => config,
         Err(e) => {
             return Err(NodeError::Error(format!("Failed to get config: {e}")));
@@ -1,9 +1,20 @@
-use tonic::Status;
+use grpc::client::{ConnectRetryPolicy, connect_with_retry};
+use protocol::block::Block;
+use tonic::{Status, transport::Channel};
 use types::proto::node_proto::{
-    self, CheckBalanceResponse, CreateDepositIntentResponse, GetPendingDepositIntentsResponse,
-    SpendFundsResponse, StartSigningResponse, node_control_client::NodeControlClient,
+    self, CancelDepositIntentResponse, CheckBalanceResponse, CreateDepositIntentResponse,
+    GetPendingDepositIntentsResponse, SpendFundsResponse, StartSigningResponse,
+    node_control_client::NodeControlClient,
 };
 
+/// Connects to the node, retrying while it may still be starting up.
+async fn connect(endpoint: Option<String>) -> Result<NodeControlClient<Channel>, Status> {
+    let endpoint = endpoint.unwrap_or_else(|| "http://[::1]:50051".to_string());
+    connect_with_retry(endpoint, &ConnectRetryPolicy::default())
+        .await
+        .map_err(|e| Status::unavailable(format!("Failed to connect: {e}")))
+}
+
 pub async fn rpc_spend(
     endpoint: Option<String>,
     amount: u64,
@@ -11,10 +22,7 @@ pub async fn rpc_spend(
 ) -> Result<SpendFundsResponse, Status> {
     println!("Spending {amount} satoshis");
 
-    let mut client =
-        NodeControlClient::connect(endpoint.unwrap_or_else(|| "http://[::1]:50051".to_string()))
-            .await
-            .expect("Failed to connect");
+    let mut client = connect(endpoint).await?;
 
     let spendfunds_response = client
         .spend_funds(tonic::Request::new(node_proto::SpendFundsRequest {
@@ -34,10 +42,7 @@ pub async fn rpc_start_signing(
 ) -> Result<StartSigningResponse, Status> {
     println!("Starting signing session for message: {hex_message}");
 
-    let mut client =
-        NodeControlClient::connect(endpoint.unwrap_or_else(|| "http://[::1]:50051".to_string()))
-            .await
-            .expect("Failed to connect");
+    let mut client = connect(endpoint).await?;
 
     let start_signing_response = client
         .start_signing(tonic::Request::new(node_proto::StartSigningRequest {
@@ -52,19 +57,18 @@ pub async fn rpc_create_deposit_intent(
     endpoint: Option<String>,
     amount: u64,
     public_key: String,
+    min_confirmations: Option<u32>,
 ) -> Result<CreateDepositIntentResponse, Status> {
     println!("Creating deposit intent: {amount}");
 
-    let mut client =
-        NodeControlClient::connect(endpoint.unwrap_or_else(|| "http://[::1]:50051".to_string()))
-            .await
-            .expect("Failed to connect");
+    let mut client = connect(endpoint).await?;
 
     let create_deposit_intent_response = client
         .create_deposit_intent(tonic::Request::new(
             node_proto::CreateDepositIntentRequest {
                 amount_satoshis: amount,
                 public_key,
+                min_confirmations,
             },
         ))
         .await?;
@@ -75,10 +79,7 @@ pub async fn rpc_create_deposit_intent(
 pub async fn rpc_get_pending_deposit_intents(
     endpoint: Option<String>,
 ) -> Result<GetPendingDepositIntentsResponse, Status> {
-    let mut client =
-        NodeControlClient::connect(endpoint.unwrap_or_else(|| "http://[::1]:50051".to_string()))
-            .await
-            .expect("Failed to connect");
+    let mut client = connect(endpoint).await?;
 
     let get_pending_deposit_intents_response = client
         .get_pending_deposit_intents(tonic::Request::new(
@@ -91,14 +92,30 @@ pub async fn rpc_get_pending_deposit_intents(
     Ok(get_pending_deposit_intents_response.into_inner())
 }
 
+pub async fn rpc_cancel_deposit_intent(
+    endpoint: Option<String>,
+    deposit_tracking_id: String,
+) -> Result<CancelDepositIntentResponse, Status> {
+    let mut client = connect(endpoint).await?;
+
+    let cancel_deposit_intent_response = client
+        .cancel_deposit_intent(tonic::Request::new(
+            node_proto::CancelDepositIntentRequest {
+                deposit_tracking_id,
+            },
+        ))
+        .await?;
+
+    println!("Cancelled deposit intent: {cancel_deposit_intent_response:?}");
+
+    Ok(cancel_deposit_intent_response.into_inner())
+}
+
 pub async fn rpc_check_balance(
     endpoint: Option<String>,
     address: String,
 ) -> Result<CheckBalanceResponse, Status> {
-    let mut client =
-        NodeControlClient::connect(endpoint.unwrap_or_else(|| "http://[::1]:50051".to_string()))
-            .await
-            .expect("Failed to connect");
+    let mut client = connect(endpoint).await?;
 
     let check_balance_response = client
         .check_balance(tonic::Request::new(node_proto::CheckBalanceRequest {
@@ -110,3 +127,65 @@ pub async fn rpc_check_balance(
 
     Ok(check_balance_response.into_inner())
 }
+
+/// A block fetched over RPC together with the result of checking it locally.
+#[derive(Debug)]
+pub struct BlockReport {
+    pub block: Block,
+    pub reported_hash: String,
+    pub computed_hash: String,
+    pub hash_verified: bool,
+    pub state_root_verified: bool,
+}
+
+impl BlockReport {
+    #[must_use]
+    pub const fn is_verified(&self) -> bool {
+        self.hash_verified && self.state_root_verified
+    }
+}
+
+pub async fn rpc_get_block(endpoint: Option<String>, height: u64) -> Result<BlockReport, Status> {
+    let mut client = connect(endpoint).await?;
+
+    let get_block_response = client
+        .get_block(tonic::Request::new(node_proto::GetBlockRequest { height }))
+        .await?
+        .into_inner();
+
+    let block = Block::from_canonical_json(&get_block_response.block_json)
+        .map_err(|e| Status::data_loss(format!("Failed to decode block: {e}")))?;
+    let reported_hash = serde_json::from_str::<serde_json::Value>(&get_block_response.block_json)
+        .ok()
+        .and_then(|value| value["hash"].as_str().map(str::to_string))
+        .unwrap_or_default();
+    let computed_hash = hex::encode(block.hash());
+
+    let report = BlockReport {
+        hash_verified: reported_hash == computed_hash,
+        state_root_verified: block.has_valid_state_root(),
+        block,
+        reported_hash,
+        computed_hash,
+    };
+
+    let header = &report.block.header;
+    println!("Block {}", header.height);
+    println!("  version:             {}", header.version);
+    println!(
+        "  previous block hash: {}",
+        hex::encode(header.previous_block_hash)
+    );
+    println!("  state root:          {}", hex::encode(header.state_root));
+    println!("  proposer:            {}", hex::encode(&header.proposer));
+    println!("  transactions ({}):", report.block.body.transactions.len());
+    for transaction in &report.block.body.transactions {
+        println!("    {}", transaction.to_json_value());
+    }
+    println!("  reported hash:       {}", report.reported_hash);
+    println!("  computed hash:       {}", report.computed_hash);
+    println!("  hash verified:       {}", report.hash_verified);
+    println!("  state root verified: {}", report.state_root_verified);
+
+    Ok(report)
+}
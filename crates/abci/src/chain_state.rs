@@ -1,9 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use bincode::{Decode, Encode};
-use protocol::{block::Block, transaction::Transaction};
+use protocol::{
+    block::Block,
+    transaction::{Transaction, TransactionId},
+};
 use serde::{Deserialize, Serialize};
-use types::{errors::NodeError, intents::DepositIntent};
+use types::{
+    errors::NodeError,
+    intents::{DepositIntent, LegacyDepositIntent},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
 pub struct Account {
@@ -17,33 +23,58 @@ impl Account {
         Self { address, balance }
     }
 
-    #[must_use]
-    pub fn increment_balance(&self, amount: u64) -> Self {
-        let new_balance = self.balance + amount;
+    /// Credits `amount`, failing instead of wrapping when the balance would exceed `u64::MAX`.
+    pub fn increment_balance(&self, amount: u64) -> Result<Self, NodeError> {
+        let new_balance = self.balance.checked_add(amount).ok_or_else(|| {
+            NodeError::Error(format!(
+                "Balance overflow crediting {amount} to {}",
+                self.address
+            ))
+        })?;
 
-        Self {
+        Ok(Self {
             address: self.address.clone(),
             balance: new_balance,
-        }
+        })
     }
 
-    #[must_use]
-    pub fn decrement_balance(&self, amount: u64) -> Self {
-        let new_balance = self.balance.saturating_sub(amount);
-        Self {
+    /// Debits `amount`, failing when the balance does not cover it.
+    pub fn decrement_balance(&self, amount: u64) -> Result<Self, NodeError> {
+        let new_balance = self
+            .balance
+            .checked_sub(amount)
+            .ok_or_else(|| NodeError::Error("Insufficient balance".to_string()))?;
+        Ok(Self {
             address: self.address.clone(),
             balance: new_balance,
-        }
+        })
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
+/// Leads every versioned chain state encoding. Bincode's varint encoding never begins with
+/// this byte, so a state stored before the encoding was versioned is told apart by its first
+/// byte.
+pub(crate) const VERSIONED_ENCODING_MARKER: u8 = 0xFF;
+
+/// Version of the chain state encoding this build writes.
+pub const CHAIN_STATE_VERSION: u8 = 1;
+
+/// The encoded fields of a chain state, in the order both the versioned and the unversioned
+/// layouts store them. The unversioned layout holds its intents as [`LegacyDepositIntent`]s.
+type ChainStateFields<Intent> = (HashMap<String, Account>, Vec<Intent>, Vec<Transaction>, u64);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainState {
     // address -> account
     accounts: HashMap<String, Account>,
     deposit_intents: Vec<DepositIntent>,
     proposed_transactions: Vec<Transaction>,
     block_height: u64,
+    // vault outpoints ("txid:vout") already spent by executed withdrawals. Persisted in their
+    // own column family rather than in the encoded state, so they are loaded separately.
+    spent_outpoints: HashSet<String>,
+    // the subset of `spent_outpoints` spent by the block this state was created for
+    block_spent_outpoints: Vec<String>,
 }
 
 impl Default for ChainState {
@@ -61,6 +92,8 @@ impl ChainState {
             deposit_intents: Vec::new(),
             proposed_transactions: Vec::new(),
             block_height: 0,
+            spent_outpoints: HashSet::new(),
+            block_spent_outpoints: Vec::new(),
         }
     }
 
@@ -71,6 +104,8 @@ impl ChainState {
             deposit_intents: Vec::new(),
             proposed_transactions: Vec::new(),
             block_height,
+            spent_outpoints: HashSet::new(),
+            block_spent_outpoints: Vec::new(),
         }
     }
 
@@ -81,6 +116,8 @@ impl ChainState {
             deposit_intents: self.deposit_intents.clone(),
             proposed_transactions: self.proposed_transactions.clone(),
             block_height: self.block_height + 1,
+            spent_outpoints: self.spent_outpoints.clone(),
+            block_spent_outpoints: Vec::new(),
         }
     }
 
@@ -119,6 +156,37 @@ impl ChainState {
         self.block_height
     }
 
+    #[must_use]
+    pub fn is_outpoint_spent(&self, outpoint: &str) -> bool {
+        self.spent_outpoints.contains(outpoint)
+    }
+
+    /// Marks `outpoint` as spent by the current block, returning `false` if it already was.
+    pub fn mark_outpoint_spent(&mut self, outpoint: &str) -> bool {
+        if !self.spent_outpoints.insert(outpoint.to_string()) {
+            return false;
+        }
+        self.block_spent_outpoints.push(outpoint.to_string());
+        true
+    }
+
+    /// Restores outpoints spent by earlier blocks, as read back from storage.
+    pub fn load_spent_outpoints(&mut self, outpoints: impl IntoIterator<Item = String>) {
+        self.spent_outpoints.extend(outpoints);
+    }
+
+    /// Every vault outpoint spent so far.
+    pub fn spent_outpoints(&self) -> impl Iterator<Item = &str> {
+        self.spent_outpoints.iter().map(String::as_str)
+    }
+
+    /// The outpoints spent by the block this state was created for, which are all a commit
+    /// needs to add to storage.
+    #[must_use]
+    pub fn block_spent_outpoints(&self) -> &[String] {
+        &self.block_spent_outpoints
+    }
+
     pub fn add_transaction_to_block(&mut self, transaction: Transaction) {
         // Check if the transaction is already in the block
         if self.proposed_transactions.contains(&transaction) {
@@ -132,6 +200,14 @@ impl ChainState {
         self.proposed_transactions.clear();
     }
 
+    /// Drops the pending transactions that `finalized` contains, keeping those a block left
+    /// out for a later one.
+    pub fn remove_pending_transactions(&mut self, finalized: &[Transaction]) {
+        let finalized: HashSet<TransactionId> = finalized.iter().map(Transaction::id).collect();
+        self.proposed_transactions
+            .retain(|transaction| !finalized.contains(&transaction.id()));
+    }
+
     #[must_use]
     pub fn get_pending_transactions(&self) -> &[Transaction] {
         &self.proposed_transactions
@@ -140,7 +216,7 @@ impl ChainState {
     #[must_use]
     pub fn get_proposed_block(&self, previous_block: Option<Block>, proposer: Vec<u8>) -> Block {
         let mut sorted_transactions = self.proposed_transactions.clone();
-        sorted_transactions.sort_by_key(protocol::transaction::Transaction::id);
+        sorted_transactions.sort_by_cached_key(protocol::transaction::Transaction::ordering_key);
 
         Block::new(
             previous_block.map_or([0u8; 32], |b| b.hash()),
@@ -150,15 +226,89 @@ impl ChainState {
         )
     }
 
+    /// Builds the next block from exactly the pending transactions `tx_ids` names, in that
+    /// order, so a follower reproduces the leader's proposal whatever else its own mempool
+    /// holds. Fails if any of them is not pending here.
+    pub fn get_block_for_transactions(
+        &self,
+        previous_block: Option<Block>,
+        proposer: Vec<u8>,
+        tx_ids: &[TransactionId],
+    ) -> Result<Block, NodeError> {
+        let transactions = tx_ids
+            .iter()
+            .map(|tx_id| {
+                self.proposed_transactions
+                    .iter()
+                    .find(|tx| tx.id() == *tx_id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        NodeError::Error(format!(
+                            "Transaction {} is not pending locally",
+                            hex::encode(tx_id)
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Block::new(
+            previous_block.map_or([0u8; 32], |b| b.hash()),
+            self.block_height + 1,
+            transactions,
+            proposer,
+        ))
+    }
+
+    /// Encodes the state at [`CHAIN_STATE_VERSION`]. Spent outpoints are not included; they
+    /// are stored on their own.
     pub fn serialize(&self) -> Result<Vec<u8>, NodeError> {
-        bincode::encode_to_vec(self, bincode::config::standard())
-            .map_err(|e| NodeError::Error(e.to_string()))
+        let fields = (
+            &self.accounts,
+            &self.deposit_intents,
+            &self.proposed_transactions,
+            self.block_height,
+        );
+        let mut data = vec![VERSIONED_ENCODING_MARKER, CHAIN_STATE_VERSION];
+        data.extend(
+            bincode::encode_to_vec(fields, bincode::config::standard())
+                .map_err(|e| NodeError::Error(e.to_string()))?,
+        );
+        Ok(data)
     }
 
+    /// Decodes a state written by [`Self::serialize`], or one stored before the encoding was
+    /// versioned. The latter is rewritten in the current version by the next commit.
     pub fn deserialize(data: &[u8]) -> Result<Self, NodeError> {
-        let (chain_state, _): (Self, _) =
-            bincode::decode_from_slice(data, bincode::config::standard())
-                .map_err(|e| NodeError::Error(e.to_string()))?;
-        Ok(chain_state)
+        let (accounts, deposit_intents, proposed_transactions, block_height) = match data {
+            [VERSIONED_ENCODING_MARKER, CHAIN_STATE_VERSION, payload @ ..] => {
+                decode_fields::<DepositIntent>(payload)?
+            }
+            [VERSIONED_ENCODING_MARKER, version, ..] => {
+                return Err(NodeError::Error(format!(
+                    "Chain state uses encoding version {version}, but this build reads up to {CHAIN_STATE_VERSION}"
+                )));
+            }
+            unversioned => {
+                let (accounts, intents, transactions, height) =
+                    decode_fields::<LegacyDepositIntent>(unversioned)?;
+                let intents = intents.into_iter().map(DepositIntent::from).collect();
+                (accounts, intents, transactions, height)
+            }
+        };
+
+        Ok(Self {
+            accounts,
+            deposit_intents,
+            proposed_transactions,
+            block_height,
+            spent_outpoints: HashSet::new(),
+            block_spent_outpoints: Vec::new(),
+        })
     }
 }
+
+fn decode_fields<Intent: Decode<()>>(data: &[u8]) -> Result<ChainStateFields<Intent>, NodeError> {
+    let (fields, _) = bincode::decode_from_slice(data, bincode::config::standard())
+        .map_err(|e| NodeError::Error(e.to_string()))?;
+    Ok(fields)
+}
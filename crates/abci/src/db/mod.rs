@@ -1,6 +1,11 @@
-use types::{errors::NodeError, intents::DepositIntent, utxo::Utxo};
+use types::{
+    audit::AuditEntry,
+    errors::NodeError,
+    intents::{DepositIntent, WithdrawalRecord},
+    utxo::Utxo,
+};
 
-use protocol::block::{Block, BlockHash};
+use protocol::block::{Block, BlockHash, GenesisBlock};
 
 use crate::chain_state::ChainState;
 pub mod rocksdb;
@@ -11,7 +16,13 @@ pub trait Db: Send + Sync {
     fn get_tip_block_hash(&self) -> Result<Option<BlockHash>, NodeError>;
     fn get_chain_state(&self) -> Result<Option<ChainState>, NodeError>;
     fn insert_chain_state(&self, chain_state: ChainState) -> Result<(), NodeError>;
+    /// Stores `block` and records when this node stored it, since headers carry no timestamp.
     fn insert_block(&self, block: Block) -> Result<(), NodeError>;
+    /// Unix time, in seconds, at which the block at `height` was stored.
+    fn get_block_timestamp(&self, height: u64) -> Result<Option<u64>, NodeError>;
+    /// Keeps the genesis parameters, which the height-0 block itself does not carry.
+    fn insert_genesis(&self, genesis: &GenesisBlock) -> Result<(), NodeError>;
+    fn get_genesis(&self) -> Result<Option<GenesisBlock>, NodeError>;
     fn insert_deposit_intent(&self, intent: DepositIntent) -> Result<(), NodeError>;
     fn get_deposit_intent(&self, tracking_id: &str) -> Result<Option<DepositIntent>, NodeError>;
     fn get_all_deposit_intents(&self) -> Result<Vec<DepositIntent>, NodeError>;
@@ -21,6 +32,41 @@ pub trait Db: Send + Sync {
         address: &str,
     ) -> Result<Option<DepositIntent>, NodeError>;
     fn flush_state(&self, chain_state: &ChainState) -> Result<(), NodeError>;
+    /// Stores a finalized `block` together with the `chain_state` it produced, in one atomic
+    /// write, so a crash leaves either both or neither persisted.
+    fn commit_block(&self, block: Block, chain_state: &ChainState) -> Result<(), NodeError>;
     fn store_utxos(&self, utxos: Vec<Utxo>) -> Result<(), NodeError>;
     fn get_utxos(&self) -> Result<Vec<Utxo>, NodeError>;
+    fn insert_consumed_challenge(&self, challenge: &str) -> Result<(), NodeError>;
+    fn is_challenge_consumed(&self, challenge: &str) -> Result<bool, NodeError>;
+    /// Durably appends `entry` to the audit log, returning the sequence number it was given.
+    fn append_audit_entry(&self, entry: AuditEntry) -> Result<u64, NodeError>;
+    /// Audit entries timestamped within `from..=to`, in the order they were appended.
+    fn get_audit_log(&self, from: u64, to: u64) -> Result<Vec<AuditEntry>, NodeError>;
+    /// Inserts or replaces the record for `record.challenge`.
+    fn upsert_withdrawal_record(&self, record: &WithdrawalRecord) -> Result<(), NodeError>;
+    fn get_withdrawal_records(&self) -> Result<Vec<WithdrawalRecord>, NodeError>;
+    /// Durably reserves the next value of this node's signing session counter. A value is
+    /// never handed out twice, including across restarts.
+    fn allocate_sign_id_counter(&self) -> Result<u64, NodeError>;
+    /// Records that the deposit `txid` was credited while Bitcoin was at `bitcoin_height`.
+    fn insert_processed_deposit(&self, txid: &str, bitcoin_height: u32) -> Result<(), NodeError>;
+    /// Every recorded deposit txid with the Bitcoin height it was credited at.
+    fn get_processed_deposits(&self) -> Result<Vec<(String, u32)>, NodeError>;
+    /// Forgets the deposits credited below `bitcoin_height`, returning how many were removed.
+    fn prune_processed_deposits(&self, bitcoin_height: u32) -> Result<usize, NodeError>;
+    /// Records the deposit `txid`, consensus-encoded as `raw_tx`, as held back until Bitcoin
+    /// reaches `credit_at`.
+    fn insert_awaiting_deposit(
+        &self,
+        txid: &str,
+        credit_at: u32,
+        raw_tx: &[u8],
+    ) -> Result<(), NodeError>;
+    /// Every held-back deposit txid with the height it is credited at and its raw transaction.
+    fn get_awaiting_deposits(&self) -> Result<Vec<(String, u32, Vec<u8>)>, NodeError>;
+    fn remove_awaiting_deposit(&self, txid: &str) -> Result<(), NodeError>;
+    /// Records `address` as watched on behalf of an operator.
+    fn insert_watch_address(&self, address: &str) -> Result<(), NodeError>;
+    fn get_watch_addresses(&self) -> Result<Vec<String>, NodeError>;
 }
@@ -1,11 +1,11 @@
-use rocksdb::DB;
+use rocksdb::{DB, WriteBatch};
 use std::sync::Arc;
 
 use crate::chain_state::ChainState;
 use crate::db::Db;
-use protocol::block::{Block, BlockHash};
+use protocol::block::{Block, BlockHash, GenesisBlock};
 use types::intents::DepositIntent;
-use types::{errors::NodeError, utxo::Utxo};
+use types::{audit::AuditEntry, errors::NodeError, intents::WithdrawalRecord, utxo::Utxo};
 
 #[derive(Clone)]
 pub struct RocksDb {
@@ -19,11 +19,99 @@ impl RocksDb {
         opts.create_if_missing(true);
         opts.create_missing_column_families(true);
 
-        let cfs = vec!["deposit_intents", "blocks", "chain_state", "utxos"];
+        let cfs = vec![
+            "deposit_intents",
+            "blocks",
+            "chain_state",
+            "utxos",
+            "consumed_challenges",
+            "audit_log",
+            "withdrawal_records",
+            "sign_ids",
+            "processed_deposits",
+            "spent_outpoints",
+            "awaiting_deposits",
+            "watch_addresses",
+        ];
         let db = Arc::new(DB::open_cf(&opts, path, cfs).unwrap());
 
         Self { db }
     }
+
+    /// Adds the writes storing `block` and making it the tip to `batch`.
+    pub(crate) fn stage_block(
+        &self,
+        batch: &mut WriteBatch,
+        block: &Block,
+    ) -> Result<(), NodeError> {
+        let cf = self.db.cf_handle("blocks").unwrap();
+        let block_hash = block.hash();
+        let serialized = block
+            .serialize()
+            .map_err(|e| NodeError::Error(e.to_string()))?;
+
+        // Store block by hash
+        batch.put_cf(cf, format!("b:{}", hex::encode(block_hash)), &serialized);
+
+        // Store height to hash mapping
+        batch.put_cf(cf, format!("h:{}", block.header.height), block_hash);
+
+        // Store when the block was stored
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        batch.put_cf(
+            cf,
+            format!("t:{}", block.header.height),
+            timestamp.to_be_bytes(),
+        );
+
+        // Update tip
+        batch.put_cf(cf, "tip", block_hash);
+
+        Ok(())
+    }
+
+    /// Adds the write replacing the stored chain state with `chain_state` to `batch`.
+    pub(crate) fn stage_chain_state(
+        &self,
+        batch: &mut WriteBatch,
+        chain_state: &ChainState,
+    ) -> Result<(), NodeError> {
+        batch.put_cf(
+            self.db.cf_handle("chain_state").unwrap(),
+            "current",
+            chain_state.serialize()?,
+        );
+        Ok(())
+    }
+
+    /// Adds the writes recording each of `outpoints` as spent to `batch`.
+    pub(crate) fn stage_spent_outpoints<'a>(
+        &self,
+        batch: &mut WriteBatch,
+        outpoints: impl IntoIterator<Item = &'a str>,
+    ) {
+        let cf = self.db.cf_handle("spent_outpoints").unwrap();
+        for outpoint in outpoints {
+            batch.put_cf(cf, outpoint, []);
+        }
+    }
+
+    fn get_spent_outpoints(&self) -> Result<Vec<String>, NodeError> {
+        let cf = self.db.cf_handle("spent_outpoints").unwrap();
+        let mut outpoints = Vec::new();
+
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, _) = item?;
+            let outpoint = String::from_utf8(key.to_vec())
+                .map_err(|_| NodeError::Error("Corrupt spent outpoint".to_string()))?;
+            outpoints.push(outpoint);
+        }
+
+        Ok(outpoints)
+    }
 }
 
 impl Db for RocksDb {
@@ -42,6 +130,19 @@ impl Db for RocksDb {
         }
     }
 
+    fn get_block_timestamp(&self, height: u64) -> Result<Option<u64>, NodeError> {
+        let timestamp = self
+            .db
+            .get_cf(self.db.cf_handle("blocks").unwrap(), format!("t:{height}"))?;
+        timestamp.map_or(Ok(None), |timestamp| {
+            let timestamp: [u8; 8] = timestamp
+                .as_slice()
+                .try_into()
+                .map_err(|_| NodeError::Error("Corrupt block timestamp".to_string()))?;
+            Ok(Some(u64::from_be_bytes(timestamp)))
+        })
+    }
+
     fn get_block_by_hash(&self, hash: BlockHash) -> Result<Option<Block>, NodeError> {
         let block = self.db.get_cf(
             self.db.cf_handle("blocks").unwrap(),
@@ -65,12 +166,13 @@ impl Db for RocksDb {
         let state = self
             .db
             .get_cf(self.db.cf_handle("chain_state").unwrap(), "current")?;
-        if let Some(state) = state {
-            let chain_state = ChainState::deserialize(&state)?;
-            Ok(Some(chain_state))
+        let mut chain_state = if let Some(state) = state {
+            ChainState::deserialize(&state)?
         } else {
-            Ok(Some(ChainState::new())) // Return empty state if none exists
-        }
+            ChainState::new() // Return empty state if none exists
+        };
+        chain_state.load_spent_outpoints(self.get_spent_outpoints()?);
+        Ok(Some(chain_state))
     }
 
     fn insert_chain_state(&self, chain_state: ChainState) -> Result<(), NodeError> {
@@ -84,35 +186,30 @@ impl Db for RocksDb {
     }
 
     fn insert_block(&self, block: Block) -> Result<(), NodeError> {
-        let block_hash = block.hash();
-        let serialized = block
-            .serialize()
-            .map_err(|e| NodeError::Error(e.to_string()))?;
-
-        // Store block by hash
-        self.db.put_cf(
-            self.db.cf_handle("blocks").unwrap(),
-            format!("b:{}", hex::encode(block_hash)),
-            &serialized,
-        )?;
+        let mut batch = WriteBatch::default();
+        self.stage_block(&mut batch, &block)?;
+        self.db.write(batch)?;
+        Ok(())
+    }
 
-        // Store height to hash mapping
+    fn insert_genesis(&self, genesis: &GenesisBlock) -> Result<(), NodeError> {
         self.db.put_cf(
             self.db.cf_handle("blocks").unwrap(),
-            format!("h:{}", block.header.height),
-            block_hash,
+            "genesis",
+            genesis.serialize()?,
         )?;
+        Ok(())
+    }
 
-        // Update tip
+    fn get_genesis(&self) -> Result<Option<GenesisBlock>, NodeError> {
         self.db
-            .put_cf(self.db.cf_handle("blocks").unwrap(), "tip", block_hash)?;
-
-        Ok(())
+            .get_cf(self.db.cf_handle("blocks").unwrap(), "genesis")?
+            .map(|data| GenesisBlock::deserialize(&data))
+            .transpose()
     }
 
     fn insert_deposit_intent(&self, intent: DepositIntent) -> Result<(), NodeError> {
-        let serialized = bincode::encode_to_vec(&intent, bincode::config::standard())
-            .map_err(|e| NodeError::Error(e.to_string()))?;
+        let serialized = intent.to_stored_bytes()?;
 
         // Store by tracking ID
         self.db.put_cf(
@@ -136,14 +233,9 @@ impl Db for RocksDb {
             .db
             .get_cf(self.db.cf_handle("deposit_intents").unwrap(), tracking_id)?;
 
-        if let Some(intent) = intent {
-            let (intent, _): (DepositIntent, _) =
-                bincode::decode_from_slice(&intent, bincode::config::standard())
-                    .map_err(|e| NodeError::Error(e.to_string()))?;
-            Ok(Some(intent))
-        } else {
-            Ok(None)
-        }
+        intent
+            .map(|intent| DepositIntent::from_stored_bytes(&intent))
+            .transpose()
     }
 
     fn get_all_deposit_intents(&self) -> Result<Vec<DepositIntent>, NodeError> {
@@ -158,10 +250,7 @@ impl Db for RocksDb {
                 continue;
             }
 
-            let (intent, _): (DepositIntent, _) =
-                bincode::decode_from_slice(&value, bincode::config::standard())
-                    .map_err(|e| NodeError::Error(e.to_string()))?;
-            intents.push(intent);
+            intents.push(DepositIntent::from_stored_bytes(&value)?);
         }
 
         Ok(intents)
@@ -176,18 +265,32 @@ impl Db for RocksDb {
             format!("addr:{address}"),
         )?;
 
-        if let Some(intent) = intent {
-            let (intent, _): (DepositIntent, _) =
-                bincode::decode_from_slice(&intent, bincode::config::standard())
-                    .map_err(|e| NodeError::Error(e.to_string()))?;
-            Ok(Some(intent))
-        } else {
-            Ok(None)
-        }
+        intent
+            .map(|intent| DepositIntent::from_stored_bytes(&intent))
+            .transpose()
     }
 
     fn flush_state(&self, chain_state: &ChainState) -> Result<(), NodeError> {
-        self.insert_chain_state(chain_state.clone())
+        let mut batch = WriteBatch::default();
+        self.stage_chain_state(&mut batch, chain_state)?;
+        self.stage_spent_outpoints(&mut batch, chain_state.spent_outpoints());
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    fn commit_block(&self, block: Block, chain_state: &ChainState) -> Result<(), NodeError> {
+        let mut batch = WriteBatch::default();
+        self.stage_block(&mut batch, &block)?;
+        self.stage_chain_state(&mut batch, chain_state)?;
+        self.stage_spent_outpoints(
+            &mut batch,
+            chain_state
+                .block_spent_outpoints()
+                .iter()
+                .map(String::as_str),
+        );
+        self.db.write(batch)?;
+        Ok(())
     }
 
     fn store_utxos(&self, utxos: Vec<Utxo>) -> Result<(), NodeError> {
@@ -234,4 +337,228 @@ impl Db for RocksDb {
         )?;
         Ok(())
     }
+
+    fn insert_consumed_challenge(&self, challenge: &str) -> Result<(), NodeError> {
+        self.db.put_cf(
+            self.db.cf_handle("consumed_challenges").unwrap(),
+            challenge,
+            [],
+        )?;
+        Ok(())
+    }
+
+    fn is_challenge_consumed(&self, challenge: &str) -> Result<bool, NodeError> {
+        Ok(self
+            .db
+            .get_cf(self.db.cf_handle("consumed_challenges").unwrap(), challenge)?
+            .is_some())
+    }
+
+    fn append_audit_entry(&self, mut entry: AuditEntry) -> Result<u64, NodeError> {
+        let cf = self.db.cf_handle("audit_log").unwrap();
+
+        // Keys are big-endian sequence numbers, so the last key is the newest entry.
+        let last_sequence = match self.db.iterator_cf(cf, rocksdb::IteratorMode::End).next() {
+            Some(item) => {
+                let (key, _) = item?;
+                let key: [u8; 8] = key
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| NodeError::Error("Corrupt audit log key".to_string()))?;
+                Some(u64::from_be_bytes(key))
+            }
+            None => None,
+        };
+        entry.sequence = last_sequence.map_or(0, |sequence| sequence + 1);
+
+        let serialized = bincode::encode_to_vec(&entry, bincode::config::standard())
+            .map_err(|e| NodeError::Error(e.to_string()))?;
+
+        // Sync the write so an entry recorded ahead of an operation survives a crash.
+        let mut write_options = rocksdb::WriteOptions::default();
+        write_options.set_sync(true);
+        self.db.put_cf_opt(
+            cf,
+            entry.sequence.to_be_bytes(),
+            &serialized,
+            &write_options,
+        )?;
+
+        Ok(entry.sequence)
+    }
+
+    fn get_audit_log(&self, from: u64, to: u64) -> Result<Vec<AuditEntry>, NodeError> {
+        let cf = self.db.cf_handle("audit_log").unwrap();
+        let mut entries = Vec::new();
+
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (_, value) = item?;
+            let (entry, _): (AuditEntry, _) =
+                bincode::decode_from_slice(&value, bincode::config::standard())
+                    .map_err(|e| NodeError::Error(e.to_string()))?;
+            if (from..=to).contains(&entry.timestamp) {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn upsert_withdrawal_record(&self, record: &WithdrawalRecord) -> Result<(), NodeError> {
+        let serialized = bincode::encode_to_vec(record, bincode::config::standard())
+            .map_err(|e| NodeError::Error(e.to_string()))?;
+        self.db.put_cf(
+            self.db.cf_handle("withdrawal_records").unwrap(),
+            &record.challenge,
+            serialized,
+        )?;
+        Ok(())
+    }
+
+    fn get_withdrawal_records(&self) -> Result<Vec<WithdrawalRecord>, NodeError> {
+        let cf = self.db.cf_handle("withdrawal_records").unwrap();
+        let mut records = Vec::new();
+
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (_, value) = item?;
+            let (record, _): (WithdrawalRecord, _) =
+                bincode::decode_from_slice(&value, bincode::config::standard())
+                    .map_err(|e| NodeError::Error(e.to_string()))?;
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    fn allocate_sign_id_counter(&self) -> Result<u64, NodeError> {
+        let cf = self.db.cf_handle("sign_ids").unwrap();
+
+        let next = match self.db.get_cf(cf, "next")? {
+            Some(value) => {
+                let value: [u8; 8] = value
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| NodeError::Error("Corrupt sign id counter".to_string()))?;
+                u64::from_be_bytes(value)
+            }
+            None => 0,
+        };
+        let after = next
+            .checked_add(1)
+            .ok_or_else(|| NodeError::Error("Sign id counter exhausted".to_string()))?;
+
+        // Sync before handing the value out, so a crash cannot make it reusable.
+        let mut write_options = rocksdb::WriteOptions::default();
+        write_options.set_sync(true);
+        self.db
+            .put_cf_opt(cf, "next", after.to_be_bytes(), &write_options)?;
+
+        Ok(next)
+    }
+
+    fn insert_processed_deposit(&self, txid: &str, bitcoin_height: u32) -> Result<(), NodeError> {
+        // Sync so a deposit credited just before a crash is still known after the restart.
+        let mut write_options = rocksdb::WriteOptions::default();
+        write_options.set_sync(true);
+        self.db.put_cf_opt(
+            self.db.cf_handle("processed_deposits").unwrap(),
+            txid,
+            bitcoin_height.to_be_bytes(),
+            &write_options,
+        )?;
+        Ok(())
+    }
+
+    fn get_processed_deposits(&self) -> Result<Vec<(String, u32)>, NodeError> {
+        let cf = self.db.cf_handle("processed_deposits").unwrap();
+        let mut deposits = Vec::new();
+
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item?;
+            let txid = String::from_utf8(key.to_vec())
+                .map_err(|_| NodeError::Error("Corrupt processed deposit txid".to_string()))?;
+            let height: [u8; 4] = value
+                .as_ref()
+                .try_into()
+                .map_err(|_| NodeError::Error("Corrupt processed deposit height".to_string()))?;
+            deposits.push((txid, u32::from_be_bytes(height)));
+        }
+
+        Ok(deposits)
+    }
+
+    fn prune_processed_deposits(&self, bitcoin_height: u32) -> Result<usize, NodeError> {
+        let cf = self.db.cf_handle("processed_deposits").unwrap();
+        let mut pruned = 0;
+        for (txid, height) in self.get_processed_deposits()? {
+            if height < bitcoin_height {
+                self.db.delete_cf(cf, txid)?;
+                pruned += 1;
+            }
+        }
+        Ok(pruned)
+    }
+
+    fn insert_awaiting_deposit(
+        &self,
+        txid: &str,
+        credit_at: u32,
+        raw_tx: &[u8],
+    ) -> Result<(), NodeError> {
+        let mut value = credit_at.to_be_bytes().to_vec();
+        value.extend_from_slice(raw_tx);
+        // Sync so a deposit held back just before a crash is still waited for after it.
+        let mut write_options = rocksdb::WriteOptions::default();
+        write_options.set_sync(true);
+        self.db.put_cf_opt(
+            self.db.cf_handle("awaiting_deposits").unwrap(),
+            txid,
+            value,
+            &write_options,
+        )?;
+        Ok(())
+    }
+
+    fn get_awaiting_deposits(&self) -> Result<Vec<(String, u32, Vec<u8>)>, NodeError> {
+        let cf = self.db.cf_handle("awaiting_deposits").unwrap();
+        let mut deposits = Vec::new();
+
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item?;
+            let txid = String::from_utf8(key.to_vec())
+                .map_err(|_| NodeError::Error("Corrupt awaiting deposit txid".to_string()))?;
+            let (credit_at, raw_tx) = value
+                .split_first_chunk::<4>()
+                .ok_or_else(|| NodeError::Error("Corrupt awaiting deposit height".to_string()))?;
+            deposits.push((txid, u32::from_be_bytes(*credit_at), raw_tx.to_vec()));
+        }
+
+        Ok(deposits)
+    }
+
+    fn remove_awaiting_deposit(&self, txid: &str) -> Result<(), NodeError> {
+        self.db
+            .delete_cf(self.db.cf_handle("awaiting_deposits").unwrap(), txid)?;
+        Ok(())
+    }
+
+    fn insert_watch_address(&self, address: &str) -> Result<(), NodeError> {
+        self.db
+            .put_cf(self.db.cf_handle("watch_addresses").unwrap(), address, b"")?;
+        Ok(())
+    }
+
+    fn get_watch_addresses(&self) -> Result<Vec<String>, NodeError> {
+        let cf = self.db.cf_handle("watch_addresses").unwrap();
+        let mut addresses = Vec::new();
+
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, _) = item?;
+            let address = String::from_utf8(key.to_vec())
+                .map_err(|_| NodeError::Error("Corrupt watch address".to_string()))?;
+            addresses.push(address);
+        }
+
+        Ok(addresses)
+    }
 }
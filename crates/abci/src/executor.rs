@@ -6,7 +6,114 @@ use oracle::oracle::Oracle;
 use types::errors::NodeError;
 
 use crate::chain_state::{Account, ChainState};
-use protocol::transaction::{Operation, Transaction};
+use protocol::transaction::{
+    Operation, Transaction, TransactionType, decode_amount, encode_amount,
+};
+
+/// Checks that `transaction` is built only from the operation groups its type allows, with
+/// well-formed operands, before any of it runs.
+///
+/// A deposit is a sequence of oracle checks (`amount address txid OpCheckOracle`) and credits
+/// (`amount address OpIncrementBalance`) with at least one credit; a withdrawal is one or more
+/// debits (`amount address OpDecrementBalance`); an intent expiry is one or more
+/// `expires_at address OpExpireDepositIntent` groups. Whether a credit is covered by an oracle
+/// check is left to execution, which tracks allowances.
+pub fn validate_transaction(transaction: &Transaction) -> Result<(), NodeError> {
+    let malformed = |reason: String| {
+        NodeError::Error(format!(
+            "Malformed {:?} transaction: {reason}",
+            transaction.r#type
+        ))
+    };
+
+    let mut operations = transaction.operations.as_slice();
+    let mut state_changes = 0usize;
+    while !operations.is_empty() {
+        let group_len = operations
+            .iter()
+            .position(|operation| !matches!(operation, Operation::OpPush { .. }))
+            .ok_or_else(|| malformed("trailing operands without an operation".to_string()))?
+            + 1;
+        let (group, rest) = operations.split_at(group_len);
+        operations = rest;
+
+        let operands: Vec<&[u8]> = group[..group_len - 1]
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::OpPush { value } => Some(value.as_slice()),
+                _ => None,
+            })
+            .collect();
+        let operation = &group[group_len - 1];
+
+        let expected_operands = match (&transaction.r#type, operation) {
+            (TransactionType::Deposit, Operation::OpCheckOracle) => 3,
+            (TransactionType::Deposit, Operation::OpIncrementBalance)
+            | (TransactionType::Withdrawal, Operation::OpDecrementBalance)
+            | (TransactionType::ExpireDepositIntent, Operation::OpExpireDepositIntent) => {
+                state_changes += 1;
+                2
+            }
+            (_, operation) => {
+                return Err(malformed(format!("{operation:?} is not allowed")));
+            }
+        };
+        if operands.len() != expected_operands {
+            return Err(malformed(format!(
+                "{operation:?} takes {expected_operands} operands, got {}",
+                operands.len()
+            )));
+        }
+
+        decode_amount(operands[0]).map_err(|e| malformed(e.to_string()))?;
+        if operands[1].is_empty() || std::str::from_utf8(operands[1]).is_err() {
+            return Err(malformed("address is not valid UTF-8".to_string()));
+        }
+        if let Some(txid) = operands.get(2) {
+            if txid.len() != 32 {
+                return Err(malformed(format!(
+                    "txid must be 32 bytes, got {}",
+                    txid.len()
+                )));
+            }
+        }
+    }
+
+    if state_changes == 0 {
+        return Err(malformed("no state change".to_string()));
+    }
+    Ok(())
+}
+
+/// The `(amount, address, txid)` operands of each oracle check in `transaction`, which must
+/// already have passed [`validate_transaction`].
+fn oracle_checks(transaction: &Transaction) -> Result<Vec<(u64, String, Txid)>, NodeError> {
+    let mut operands: Vec<&[u8]> = Vec::new();
+    let mut checks = Vec::new();
+    for operation in &transaction.operations {
+        if let Operation::OpPush { value } = operation {
+            operands.push(value);
+            continue;
+        }
+        let group: Vec<&[u8]> = operands.drain(..).collect();
+        if let (Operation::OpCheckOracle, [amount, address, txid]) = (operation, group.as_slice()) {
+            checks.push((
+                decode_amount(amount)?,
+                String::from_utf8(address.to_vec()).map_err(|e| NodeError::Error(e.to_string()))?,
+                Txid::from_slice(txid).map_err(|e| NodeError::Error(e.to_string()))?,
+            ));
+        }
+    }
+    Ok(checks)
+}
+
+const fn type_label(r#type: &TransactionType) -> &'static str {
+    match r#type {
+        TransactionType::Deposit => "deposit",
+        TransactionType::Withdrawal => "withdrawal",
+        TransactionType::ExpireDepositIntent => "expire_deposit_intent",
+    }
+}
 
 #[async_trait::async_trait]
 pub trait TransactionExecutor: Send + Sync {
@@ -15,6 +122,18 @@ pub trait TransactionExecutor: Send + Sync {
         transaction: Transaction,
         chain_state: ChainState,
     ) -> Result<ChainState, NodeError>;
+
+    /// Re-executes `transaction` from a block this node already finalized, counting its
+    /// oracle checks as passed since they were confirmed before the block was voted for.
+    async fn replay_transaction(
+        &mut self,
+        transaction: Transaction,
+        chain_state: ChainState,
+    ) -> Result<ChainState, NodeError>;
+
+    /// Confirms through the oracle every deposit `transaction` references, without executing
+    /// it. Fails if the transaction is malformed or any deposit cannot be confirmed.
+    async fn confirm_oracle_checks(&self, transaction: &Transaction) -> Result<(), NodeError>;
 }
 
 pub struct TransactionExecutorImpl {
@@ -23,6 +142,8 @@ pub struct TransactionExecutorImpl {
     pub(crate) stack: Vec<Vec<u8>>,
     pub(crate) error: Option<NodeError>,
     pub(crate) new_chain_state: ChainState,
+    /// Set while replaying finalized blocks, when `OpCheckOracle` passes without a query.
+    replaying: bool,
 }
 
 impl TransactionExecutorImpl {
@@ -34,6 +155,7 @@ impl TransactionExecutorImpl {
             stack: Vec::new(),
             error: None,
             new_chain_state: ChainState::new(),
+            replaying: false,
         }
     }
 
@@ -45,8 +167,23 @@ impl TransactionExecutorImpl {
         self.stack.pop()
     }
 
+    /// Marks the vault outpoints a withdrawal spends, rejecting any already spent earlier in
+    /// the chain or in the same block.
+    pub(crate) fn spend_outpoints(&mut self, transaction: &Transaction) -> Result<(), NodeError> {
+        if transaction.r#type != TransactionType::Withdrawal {
+            return Ok(());
+        }
+
+        for outpoint in transaction.spent_outpoints() {
+            if !self.new_chain_state.mark_outpoint_spent(&outpoint) {
+                return Err(NodeError::OutpointAlreadySpent { outpoint });
+            }
+        }
+        Ok(())
+    }
+
     pub fn signal_error(&mut self, error: NodeError) -> NodeError {
-        self.stack.push(0u64.to_be_bytes().to_vec());
+        self.stack.push(encode_amount(0));
         self.error = Some(error.clone());
         error
     }
@@ -68,25 +205,26 @@ impl TransactionExecutorImpl {
             .pop_from_stack()
             .ok_or_else(|| NodeError::Error("Missing amount".to_string()))?;
 
-        let amount = u64::from_be_bytes(
-            amount
-                .try_into()
-                .map_err(|_| NodeError::Error("Invalid amount".to_string()))?,
-        );
+        let amount = decode_amount(&amount)?;
 
-        let verified = self
-            .oracle
-            .validate_transaction(&address, amount, tx_hash)
-            .await?;
+        let verified = self.replaying
+            || self
+                .oracle
+                .validate_transaction(&address, amount, tx_hash)
+                .await?;
 
         if verified {
             let current_allowance = self.allowance_list.get(&address).copied().unwrap_or(0);
-            self.allowance_list
-                .insert(address, current_allowance + amount);
-
-            self.push_to_stack(1u64.to_be_bytes().to_vec());
+            let allowance = current_allowance.checked_add(amount).ok_or_else(|| {
+                NodeError::Error(format!(
+                    "Allowance overflow crediting {amount} to {address}"
+                ))
+            })?;
+            self.allowance_list.insert(address, allowance);
+
+            self.push_to_stack(encode_amount(1));
         } else {
-            self.push_to_stack(0u64.to_be_bytes().to_vec());
+            self.push_to_stack(encode_amount(0));
         }
 
         Ok(())
@@ -103,11 +241,7 @@ impl TransactionExecutorImpl {
 
         let address = String::from_utf8(address).map_err(|e| NodeError::Error(e.to_string()))?;
 
-        let amount = u64::from_be_bytes(
-            amount
-                .try_into()
-                .map_err(|_| NodeError::Error("Invalid amount".to_string()))?,
-        );
+        let amount = decode_amount(&amount)?;
 
         let allowed = {
             let allowance = self.allowance_list.get(&address).copied().unwrap_or(0);
@@ -118,11 +252,6 @@ impl TransactionExecutorImpl {
             return Err(NodeError::Error("Insufficient allowance".to_string()));
         }
 
-        // Deduct from allowance
-        let current_allowance = self.allowance_list.get(&address).copied().unwrap_or(0);
-        self.allowance_list
-            .insert(address.clone(), current_allowance - amount);
-
         let account = self
             .new_chain_state
             .get_account(&address)
@@ -132,12 +261,17 @@ impl TransactionExecutorImpl {
                 balance: 0,
             });
 
-        let account = account.increment_balance(amount);
+        let account = account.increment_balance(amount)?;
+
+        // Deduct from allowance only once the credit is known to fit
+        let current_allowance = self.allowance_list.get(&address).copied().unwrap_or(0);
+        self.allowance_list
+            .insert(address.clone(), current_allowance - amount);
 
         self.new_chain_state.upsert_account(&address, account);
 
         // Push success to stack
-        self.push_to_stack(1u64.to_be_bytes().to_vec());
+        self.push_to_stack(encode_amount(1));
 
         Ok(())
     }
@@ -153,11 +287,7 @@ impl TransactionExecutorImpl {
 
         let address = String::from_utf8(address).map_err(|e| NodeError::Error(e.to_string()))?;
 
-        let amount = u64::from_be_bytes(
-            amount
-                .try_into()
-                .map_err(|_| NodeError::Error("Invalid amount".to_string()))?,
-        );
+        let amount = decode_amount(&amount)?;
 
         // TODO: may need to have an allowance check here
 
@@ -170,31 +300,53 @@ impl TransactionExecutorImpl {
                 balance: 0,
             });
 
-        if account.balance < amount {
-            return Err(NodeError::Error("Insufficient balance".to_string()));
-        }
-
-        let account = account.decrement_balance(amount);
+        let account = account.decrement_balance(amount)?;
 
         self.new_chain_state.upsert_account(&address, account);
 
         // Push success to stack
-        self.push_to_stack(1u64.to_be_bytes().to_vec());
+        self.push_to_stack(encode_amount(1));
 
         Ok(())
     }
-}
 
-#[async_trait::async_trait]
-impl TransactionExecutor for TransactionExecutorImpl {
-    async fn execute_transaction(
-        &mut self,
-        transaction: Transaction,
-        chain_state: ChainState,
-    ) -> Result<ChainState, NodeError> {
-        self.new_chain_state = chain_state;
+    /// Removes the intent at the popped address if it expires at the popped time, 0 for one
+    /// that never expires. Which intents have expired or been cancelled is agreed on by the
+    /// nodes holding the transaction, so execution only makes sure it drops the intent that
+    /// transaction was built from.
+    pub fn op_expire_deposit_intent(&mut self) -> Result<(), NodeError> {
+        let address = self
+            .pop_from_stack()
+            .ok_or_else(|| NodeError::Error("Missing address".to_string()))?;
+
+        let expires_at = self
+            .pop_from_stack()
+            .ok_or_else(|| NodeError::Error("Missing expiry".to_string()))?;
+
+        let address = String::from_utf8(address).map_err(|e| NodeError::Error(e.to_string()))?;
+
+        let expires_at = decode_amount(&expires_at)?;
 
-        for operation in transaction.operations {
+        let expired = self
+            .new_chain_state
+            .get_deposit_intent_by_address(&address)
+            .filter(|intent| intent.expires_at == expires_at)
+            .cloned();
+        if let Some(intent) = &expired {
+            self.new_chain_state.remove_deposit_intent(intent);
+        }
+
+        self.push_to_stack(encode_amount(u64::from(expired.is_some())));
+
+        Ok(())
+    }
+
+    /// Runs `operations` against the current state without checking their shape.
+    pub async fn execute_operations(
+        &mut self,
+        operations: Vec<Operation>,
+    ) -> Result<(), NodeError> {
+        for operation in operations {
             match operation {
                 Operation::OpPush { value } => {
                     self.push_to_stack(value);
@@ -208,8 +360,68 @@ impl TransactionExecutor for TransactionExecutorImpl {
                 Operation::OpDecrementBalance => {
                     self.op_decrement_balance()?;
                 }
+                Operation::OpExpireDepositIntent => {
+                    self.op_expire_deposit_intent()?;
+                }
             }
         }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionExecutor for TransactionExecutorImpl {
+    async fn execute_transaction(
+        &mut self,
+        transaction: Transaction,
+        chain_state: ChainState,
+    ) -> Result<ChainState, NodeError> {
+        let label = type_label(&transaction.r#type);
+        if let Err(e) = validate_transaction(&transaction) {
+            metrics::counter!("executor_transactions_total", "type" => label, "outcome" => "rejected")
+                .increment(1);
+            return Err(e);
+        }
+
+        self.new_chain_state = chain_state;
+        if let Err(e) = self.spend_outpoints(&transaction) {
+            metrics::counter!("executor_transactions_total", "type" => label, "outcome" => "rejected")
+                .increment(1);
+            return Err(e);
+        }
+        let result = self.execute_operations(transaction.operations).await;
+        let outcome = if result.is_ok() { "executed" } else { "failed" };
+        metrics::counter!("executor_transactions_total", "type" => label, "outcome" => outcome)
+            .increment(1);
+        result?;
+
         Ok(self.new_chain_state.clone())
     }
+
+    async fn replay_transaction(
+        &mut self,
+        transaction: Transaction,
+        chain_state: ChainState,
+    ) -> Result<ChainState, NodeError> {
+        self.replaying = true;
+        let result = self.execute_transaction(transaction, chain_state).await;
+        self.replaying = false;
+        result
+    }
+
+    async fn confirm_oracle_checks(&self, transaction: &Transaction) -> Result<(), NodeError> {
+        validate_transaction(transaction)?;
+        for (amount, address, txid) in oracle_checks(transaction)? {
+            if !self
+                .oracle
+                .validate_transaction(&address, amount, txid)
+                .await?
+            {
+                return Err(NodeError::Error(format!(
+                    "Deposit {txid} of {amount} to {address} could not be confirmed"
+                )));
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,10 +1,15 @@
 use frost_secp256k1::keys::PublicKeyPackage;
 use protocol::{
     block::{Block, ChainConfig, GenesisBlock, ValidatorInfo},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionId},
 };
 use tokio::sync::broadcast;
-use types::{errors::NodeError, intents::DepositIntent};
+use types::{
+    audit::AuditEntry,
+    errors::NodeError,
+    intents::{DepositIntent, WithdrawalRecord},
+    network::network_event::BlockInfo,
+};
 
 use crate::{chain_state::Account, db::Db, executor::TransactionExecutor};
 
@@ -12,6 +17,9 @@ pub mod chain_state;
 pub mod db;
 pub mod executor;
 pub mod main_loop;
+pub mod sync_mode;
+
+pub use sync_mode::SyncMode;
 
 #[async_trait::async_trait]
 pub trait ChainInterface: Send + Sync {
@@ -30,14 +38,78 @@ pub trait ChainInterface: Send + Sync {
     async fn add_transaction_to_block(&mut self, transaction: Transaction)
     -> Result<(), NodeError>;
     fn get_account(&self, address: &str) -> Option<Account>;
+    /// The next block holding every pending transaction. Without `previous_block` it extends
+    /// the current tip.
     fn get_proposed_block(
         &self,
         previous_block: Option<Block>,
         proposer: Vec<u8>,
     ) -> Result<Block, NodeError>;
+    /// The next block holding exactly the pending transactions `tx_ids`, in that order.
+    fn get_block_for_transactions(
+        &self,
+        previous_block: Option<Block>,
+        proposer: Vec<u8>,
+        tx_ids: &[TransactionId],
+    ) -> Result<Block, NodeError>;
+    /// Confirms through the oracle every deposit `block` references, before voting for it.
+    async fn verify_block_deposits(&self, block: &Block) -> Result<(), NodeError>;
     async fn finalize_and_store_block(&mut self, block: Block) -> Result<(), NodeError>;
     fn get_pending_transactions(&self) -> Vec<Transaction>;
     fn get_chain_state(&self) -> chain_state::ChainState;
+    fn get_block_by_height(&self, height: u64) -> Result<Option<Block>, NodeError>;
+    /// Unix time, in seconds, at which this node stored the block at `height`.
+    fn get_block_timestamp(&self, height: u64) -> Result<Option<u64>, NodeError>;
+    /// The last `count` finalized blocks, newest first. Genesis is not included.
+    fn get_latest_blocks(&self, count: u32) -> Result<Vec<BlockInfo>, NodeError> {
+        let tip = self.get_chain_state().get_block_height();
+        let mut blocks = Vec::new();
+        for height in (1..=tip).rev().take(count as usize) {
+            let Some(block) = self.get_block_by_height(height)? else {
+                continue;
+            };
+            blocks.push(BlockInfo {
+                height,
+                hash: hex::encode(block.hash()),
+                timestamp: self.get_block_timestamp(height)?.unwrap_or_default(),
+                transaction_count: u32::try_from(block.body.transactions.len()).unwrap_or(u32::MAX),
+            });
+        }
+        Ok(blocks)
+    }
+    /// Records `challenge` as spent, failing if it was already consumed before.
+    fn consume_withdrawal_challenge(&mut self, challenge: &str) -> Result<(), NodeError>;
+    fn append_audit_entry(&mut self, entry: AuditEntry) -> Result<(), NodeError>;
+    fn get_audit_log(&self, from: u64, to: u64) -> Result<Vec<AuditEntry>, NodeError>;
+    /// Genesis block with its chain parameters, once one has been created.
+    fn get_genesis(&self) -> Result<Option<GenesisBlock>, NodeError>;
+    fn upsert_withdrawal_record(&mut self, record: &WithdrawalRecord) -> Result<(), NodeError>;
+    fn get_withdrawal_records(&self) -> Result<Vec<WithdrawalRecord>, NodeError>;
+    /// Reserves the counter half of a new signing session id, see `Db::allocate_sign_id_counter`.
+    fn allocate_sign_id_counter(&mut self) -> Result<u64, NodeError>;
+    /// Durably records that the deposit `txid` was credited at `bitcoin_height`, so it is not
+    /// credited again after a restart.
+    fn record_processed_deposit(
+        &mut self,
+        txid: &str,
+        bitcoin_height: u32,
+    ) -> Result<(), NodeError>;
+    fn get_processed_deposits(&self) -> Result<Vec<(String, u32)>, NodeError>;
+    /// Forgets the deposits credited below `bitcoin_height`, returning how many were removed.
+    fn prune_processed_deposits(&mut self, bitcoin_height: u32) -> Result<usize, NodeError>;
+    /// Durably holds back the deposit `txid` until Bitcoin reaches `credit_at`, so a restart
+    /// still credits it then.
+    fn record_awaiting_deposit(
+        &mut self,
+        txid: &str,
+        credit_at: u32,
+        raw_tx: &[u8],
+    ) -> Result<(), NodeError>;
+    fn get_awaiting_deposits(&self) -> Result<Vec<(String, u32, Vec<u8>)>, NodeError>;
+    fn remove_awaiting_deposit(&mut self, txid: &str) -> Result<(), NodeError>;
+    /// Durably records `address` as watched, so it is monitored again after a restart.
+    fn record_watch_address(&mut self, address: &str) -> Result<(), NodeError>;
+    fn get_watch_addresses(&self) -> Result<Vec<String>, NodeError>;
 }
 
 #[derive(Clone)]
@@ -64,15 +136,67 @@ pub enum ChainMessage {
         previous_block: Option<Block>,
         proposer: Vec<u8>,
     },
+    /// Rebuilds a proposal from the transaction ids the leader committed to.
+    GetBlockForTransactions {
+        previous_block: Option<Block>,
+        proposer: Vec<u8>,
+        tx_ids: Vec<TransactionId>,
+    },
+    VerifyBlockDeposits {
+        block: Block,
+    },
     FinalizeBlock {
         block: Block,
     },
     GetPendingTransactions,
     GetChainState,
     GetChainInfo,
+    GetBlock {
+        height: u64,
+    },
+    GetLatestBlocks {
+        count: u32,
+    },
+    ConsumeWithdrawalChallenge {
+        challenge: String,
+    },
     RemoveDepositIntent {
         intent: DepositIntent,
     },
+    AppendAuditEntry {
+        entry: AuditEntry,
+    },
+    GetAuditLog {
+        from: u64,
+        to: u64,
+    },
+    GetGenesis,
+    UpsertWithdrawalRecord {
+        record: WithdrawalRecord,
+    },
+    GetWithdrawalRecords,
+    AllocateSignIdCounter,
+    RecordProcessedDeposit {
+        txid: String,
+        bitcoin_height: u32,
+    },
+    GetProcessedDeposits,
+    PruneProcessedDeposits {
+        bitcoin_height: u32,
+    },
+    RecordAwaitingDeposit {
+        txid: String,
+        credit_at: u32,
+        raw_tx: Vec<u8>,
+    },
+    GetAwaitingDeposits,
+    RemoveAwaitingDeposit {
+        txid: String,
+    },
+    RecordWatchAddress {
+        address: String,
+    },
+    GetWatchAddresses,
 }
 
 #[derive(Clone)]
@@ -98,6 +222,12 @@ pub enum ChainResponse {
     GetProposedBlock {
         block: Block,
     },
+    GetBlockForTransactions {
+        block: Result<Block, NodeError>,
+    },
+    VerifyBlockDeposits {
+        error: Option<NodeError>,
+    },
     FinalizeAndStoreBlock {
         error: Option<NodeError>,
     },
@@ -111,9 +241,60 @@ pub enum ChainResponse {
         height: u64,
         pending_transactions: usize,
     },
+    GetBlock {
+        block: Option<Block>,
+    },
+    GetLatestBlocks {
+        blocks: Vec<BlockInfo>,
+    },
+    ConsumeWithdrawalChallenge {
+        error: Option<NodeError>,
+    },
     RemoveDepositIntent {
         error: Option<NodeError>,
     },
+    AppendAuditEntry {
+        error: Option<NodeError>,
+    },
+    GetAuditLog {
+        entries: Vec<AuditEntry>,
+    },
+    GetGenesis {
+        genesis: Option<GenesisBlock>,
+    },
+    UpsertWithdrawalRecord {
+        error: Option<NodeError>,
+    },
+    GetWithdrawalRecords {
+        records: Vec<WithdrawalRecord>,
+    },
+    AllocateSignIdCounter {
+        counter: Result<u64, NodeError>,
+    },
+    RecordProcessedDeposit {
+        error: Option<NodeError>,
+    },
+    GetProcessedDeposits {
+        deposits: Vec<(String, u32)>,
+    },
+    PruneProcessedDeposits {
+        pruned: Result<usize, NodeError>,
+    },
+    RecordAwaitingDeposit {
+        error: Option<NodeError>,
+    },
+    GetAwaitingDeposits {
+        deposits: Vec<(String, u32, Vec<u8>)>,
+    },
+    RemoveAwaitingDeposit {
+        error: Option<NodeError>,
+    },
+    RecordWatchAddress {
+        error: Option<NodeError>,
+    },
+    GetWatchAddresses {
+        addresses: Vec<String>,
+    },
 }
 
 pub struct ChainInterfaceImpl {
@@ -141,6 +322,38 @@ impl ChainInterfaceImpl {
             tx,
         )
     }
+
+    /// The newest stored block above genesis, which the next block must extend. Each validator
+    /// builds its own genesis block, with its own timestamp and validator list, so the first
+    /// block links to the zero hash rather than to genesis.
+    fn tip_block(&self) -> Result<Option<Block>, NodeError> {
+        match self.chain_state.get_block_height() {
+            0 => Ok(None),
+            height => self
+                .db
+                .get_block_by_height(height)?
+                .map(Some)
+                .ok_or_else(|| {
+                    NodeError::Error(format!("Tip block at height {height} is missing"))
+                }),
+        }
+    }
+
+    /// Rejects `block` unless it sits directly on the current tip, so gaps and forks are never
+    /// stored.
+    fn check_extends_tip(&self, block: &Block) -> Result<(), NodeError> {
+        let tip_height = self.chain_state.get_block_height();
+        let tip_hash = self.tip_block()?.map_or([0u8; 32], |tip| tip.hash());
+        if block.header.height != tip_height + 1 || block.header.previous_block_hash != tip_hash {
+            return Err(NodeError::BlockDoesNotExtendTip {
+                height: block.header.height,
+                previous_hash: hex::encode(block.header.previous_block_hash),
+                tip_height,
+                tip_hash: hex::encode(tip_hash),
+            });
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -183,6 +396,7 @@ impl ChainInterface for ChainInterfaceImpl {
                 .serialize()
                 .map_err(|e| NodeError::Error(format!("Failed to serialize public key: {e}")))?,
         );
+        self.db.insert_genesis(&genesis_block)?;
         self.db.insert_block(genesis_block.to_block())
     }
 
@@ -200,27 +414,62 @@ impl ChainInterface for ChainInterfaceImpl {
         previous_block: Option<Block>,
         proposer: Vec<u8>,
     ) -> Result<Block, NodeError> {
+        let previous_block = match previous_block {
+            Some(block) => Some(block),
+            None => self.tip_block()?,
+        };
         Ok(self
             .chain_state
             .get_proposed_block(previous_block, proposer))
     }
 
-    async fn finalize_and_store_block(&mut self, block: Block) -> Result<(), NodeError> {
-        let mut new_chain_state = self.chain_state.create_new_chain_state();
+    fn get_block_for_transactions(
+        &self,
+        previous_block: Option<Block>,
+        proposer: Vec<u8>,
+        tx_ids: &[TransactionId],
+    ) -> Result<Block, NodeError> {
+        let previous_block = match previous_block {
+            Some(block) => Some(block),
+            None => self.tip_block()?,
+        };
+        self.chain_state
+            .get_block_for_transactions(previous_block, proposer, tx_ids)
+    }
+
+    async fn verify_block_deposits(&self, block: &Block) -> Result<(), NodeError> {
         for transaction in &block.body.transactions {
-            new_chain_state = self
-                .executor
-                .execute_transaction(transaction.clone(), new_chain_state)
-                .await?;
+            self.executor.confirm_oracle_checks(transaction).await?;
         }
+        Ok(())
+    }
 
-        self.db.insert_block(block.clone())?;
+    async fn finalize_and_store_block(&mut self, block: Block) -> Result<(), NodeError> {
+        self.check_extends_tip(&block)?;
+        let mut new_chain_state = self.execute_block(&block, self.chain_state.clone()).await?;
+        // Transactions pending here that the leader left out stay pending for a later block.
+        new_chain_state.remove_pending_transactions(&block.body.transactions);
+        let expired_intents: Vec<DepositIntent> = self
+            .chain_state
+            .get_all_deposit_intents()
+            .into_iter()
+            .filter(|intent| {
+                new_chain_state
+                    .get_deposit_intent_by_address(&intent.deposit_address)
+                    .is_none()
+            })
+            .collect();
 
-        self.db.flush_state(&new_chain_state)?;
+        self.db.commit_block(block.clone(), &new_chain_state)?;
         self.chain_state = new_chain_state;
 
-        // Clear pending transactions since they're now finalized
-        self.chain_state.clear_pending_transactions();
+        // Intents the block expired leave the stored intents too; a full replay drops them
+        // again anyway, so a failure here is only logged.
+        for intent in expired_intents {
+            if let Err(e) = self.db.remove_deposit_intent(intent) {
+                tracing::warn!("Failed to remove expired deposit intent from storage: {e}");
+            }
+        }
 
         tracing::info!(
             "✅ Finalized and stored block at height {} with {} transactions",
@@ -238,6 +487,88 @@ impl ChainInterface for ChainInterfaceImpl {
     fn get_chain_state(&self) -> chain_state::ChainState {
         self.chain_state.clone()
     }
+
+    fn get_block_by_height(&self, height: u64) -> Result<Option<Block>, NodeError> {
+        self.db.get_block_by_height(height)
+    }
+
+    fn get_block_timestamp(&self, height: u64) -> Result<Option<u64>, NodeError> {
+        self.db.get_block_timestamp(height)
+    }
+
+    fn consume_withdrawal_challenge(&mut self, challenge: &str) -> Result<(), NodeError> {
+        if self.db.is_challenge_consumed(challenge)? {
+            return Err(NodeError::WithdrawalChallengeReplayed {
+                challenge: challenge.to_string(),
+            });
+        }
+        self.db.insert_consumed_challenge(challenge)
+    }
+
+    fn append_audit_entry(&mut self, entry: AuditEntry) -> Result<(), NodeError> {
+        self.db.append_audit_entry(entry).map(|_| ())
+    }
+
+    fn get_audit_log(&self, from: u64, to: u64) -> Result<Vec<AuditEntry>, NodeError> {
+        self.db.get_audit_log(from, to)
+    }
+
+    fn get_genesis(&self) -> Result<Option<GenesisBlock>, NodeError> {
+        self.db.get_genesis()
+    }
+
+    fn upsert_withdrawal_record(&mut self, record: &WithdrawalRecord) -> Result<(), NodeError> {
+        self.db.upsert_withdrawal_record(record)
+    }
+
+    fn get_withdrawal_records(&self) -> Result<Vec<WithdrawalRecord>, NodeError> {
+        self.db.get_withdrawal_records()
+    }
+
+    fn allocate_sign_id_counter(&mut self) -> Result<u64, NodeError> {
+        self.db.allocate_sign_id_counter()
+    }
+
+    fn record_processed_deposit(
+        &mut self,
+        txid: &str,
+        bitcoin_height: u32,
+    ) -> Result<(), NodeError> {
+        self.db.insert_processed_deposit(txid, bitcoin_height)
+    }
+
+    fn get_processed_deposits(&self) -> Result<Vec<(String, u32)>, NodeError> {
+        self.db.get_processed_deposits()
+    }
+
+    fn prune_processed_deposits(&mut self, bitcoin_height: u32) -> Result<usize, NodeError> {
+        self.db.prune_processed_deposits(bitcoin_height)
+    }
+
+    fn record_awaiting_deposit(
+        &mut self,
+        txid: &str,
+        credit_at: u32,
+        raw_tx: &[u8],
+    ) -> Result<(), NodeError> {
+        self.db.insert_awaiting_deposit(txid, credit_at, raw_tx)
+    }
+
+    fn get_awaiting_deposits(&self) -> Result<Vec<(String, u32, Vec<u8>)>, NodeError> {
+        self.db.get_awaiting_deposits()
+    }
+
+    fn remove_awaiting_deposit(&mut self, txid: &str) -> Result<(), NodeError> {
+        self.db.remove_awaiting_deposit(txid)
+    }
+
+    fn record_watch_address(&mut self, address: &str) -> Result<(), NodeError> {
+        self.db.insert_watch_address(address)
+    }
+
+    fn get_watch_addresses(&self) -> Result<Vec<String>, NodeError> {
+        self.db.get_watch_addresses()
+    }
 }
 
 #[cfg(test)]
@@ -75,6 +75,16 @@ impl ChainInterfaceImpl {
                 } => ChainResponse::GetProposedBlock {
                     block: self.get_proposed_block(previous_block, proposer)?,
                 },
+                ChainMessage::GetBlockForTransactions {
+                    previous_block,
+                    proposer,
+                    tx_ids,
+                } => ChainResponse::GetBlockForTransactions {
+                    block: self.get_block_for_transactions(previous_block, proposer, &tx_ids),
+                },
+                ChainMessage::VerifyBlockDeposits { block } => ChainResponse::VerifyBlockDeposits {
+                    error: self.verify_block_deposits(&block).await.err(),
+                },
                 ChainMessage::FinalizeBlock { block } => ChainResponse::FinalizeAndStoreBlock {
                     error: self.finalize_and_store_block(block).await.err(),
                 },
@@ -88,6 +98,74 @@ impl ChainInterfaceImpl {
                     height: self.get_chain_state().get_block_height(),
                     pending_transactions: self.get_pending_transactions().len(),
                 },
+                ChainMessage::GetBlock { height } => ChainResponse::GetBlock {
+                    block: self.get_block_by_height(height)?,
+                },
+                ChainMessage::GetLatestBlocks { count } => ChainResponse::GetLatestBlocks {
+                    blocks: self.get_latest_blocks(count)?,
+                },
+                ChainMessage::ConsumeWithdrawalChallenge { challenge } => {
+                    ChainResponse::ConsumeWithdrawalChallenge {
+                        error: self.consume_withdrawal_challenge(&challenge).err(),
+                    }
+                }
+                ChainMessage::AppendAuditEntry { entry } => ChainResponse::AppendAuditEntry {
+                    error: self.append_audit_entry(entry).err(),
+                },
+                ChainMessage::GetAuditLog { from, to } => ChainResponse::GetAuditLog {
+                    entries: self.get_audit_log(from, to)?,
+                },
+                ChainMessage::GetGenesis => ChainResponse::GetGenesis {
+                    genesis: self.get_genesis()?,
+                },
+                ChainMessage::UpsertWithdrawalRecord { record } => {
+                    ChainResponse::UpsertWithdrawalRecord {
+                        error: self.upsert_withdrawal_record(&record).err(),
+                    }
+                }
+                ChainMessage::GetWithdrawalRecords => ChainResponse::GetWithdrawalRecords {
+                    records: self.get_withdrawal_records()?,
+                },
+                ChainMessage::AllocateSignIdCounter => ChainResponse::AllocateSignIdCounter {
+                    counter: self.allocate_sign_id_counter(),
+                },
+                ChainMessage::RecordProcessedDeposit {
+                    txid,
+                    bitcoin_height,
+                } => ChainResponse::RecordProcessedDeposit {
+                    error: self.record_processed_deposit(&txid, bitcoin_height).err(),
+                },
+                ChainMessage::GetProcessedDeposits => ChainResponse::GetProcessedDeposits {
+                    deposits: self.get_processed_deposits()?,
+                },
+                ChainMessage::PruneProcessedDeposits { bitcoin_height } => {
+                    ChainResponse::PruneProcessedDeposits {
+                        pruned: self.prune_processed_deposits(bitcoin_height),
+                    }
+                }
+                ChainMessage::RecordAwaitingDeposit {
+                    txid,
+                    credit_at,
+                    raw_tx,
+                } => ChainResponse::RecordAwaitingDeposit {
+                    error: self
+                        .record_awaiting_deposit(&txid, credit_at, &raw_tx)
+                        .err(),
+                },
+                ChainMessage::GetAwaitingDeposits => ChainResponse::GetAwaitingDeposits {
+                    deposits: self.get_awaiting_deposits()?,
+                },
+                ChainMessage::RemoveAwaitingDeposit { txid } => {
+                    ChainResponse::RemoveAwaitingDeposit {
+                        error: self.remove_awaiting_deposit(&txid).err(),
+                    }
+                }
+                ChainMessage::RecordWatchAddress { address } => ChainResponse::RecordWatchAddress {
+                    error: self.record_watch_address(&address).err(),
+                },
+                ChainMessage::GetWatchAddresses => ChainResponse::GetWatchAddresses {
+                    addresses: self.get_watch_addresses()?,
+                },
             };
             response_tx
                 .send(response)
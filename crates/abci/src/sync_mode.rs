@@ -0,0 +1,94 @@
+use protocol::block::Block;
+use serde::{Deserialize, Serialize};
+use types::errors::NodeError;
+
+use crate::{ChainInterfaceImpl, chain_state::ChainState};
+
+/// How a node rebuilds its chain state from the stored blocks at startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyncMode {
+    /// Re-executes every stored block from genesis, checking each block's header commits to
+    /// its body.
+    FullReplay,
+    /// Starts from the chain state this node persisted with its last finalized block and
+    /// replays only the blocks stored above it. That state is read back as written, neither
+    /// signed nor checked against the blocks.
+    #[default]
+    StoredState,
+}
+
+impl ChainInterfaceImpl {
+    /// Rebuilds the chain state according to `mode` and persists the result.
+    pub async fn sync(&mut self, mode: SyncMode) -> Result<(), NodeError> {
+        let mut chain_state = match mode {
+            SyncMode::FullReplay => {
+                // Deposit intents are kept outside blocks, so they are carried over as stored
+                let mut chain_state = ChainState::new();
+                for intent in self.db.get_all_deposit_intents()? {
+                    chain_state.insert_deposit_intent(intent);
+                }
+                chain_state
+            }
+            SyncMode::StoredState => self.db.get_chain_state()?.unwrap_or_default(),
+        };
+        let start_height = chain_state.get_block_height();
+
+        while let Some(block) = self
+            .db
+            .get_block_by_height(chain_state.get_block_height() + 1)?
+        {
+            if !block.has_valid_state_root() {
+                return Err(NodeError::Error(format!(
+                    "Stored block at height {} has an invalid state root",
+                    block.header.height
+                )));
+            }
+            chain_state = self.replay_block(&block, chain_state).await?;
+        }
+        chain_state.clear_pending_transactions();
+
+        tracing::info!(
+            "Synced chain state with {:?}: replayed blocks {}..={}",
+            mode,
+            start_height + 1,
+            chain_state.get_block_height()
+        );
+
+        self.db.flush_state(&chain_state)?;
+        self.chain_state = chain_state;
+        Ok(())
+    }
+
+    /// Applies the transactions of `block` on top of `chain_state`.
+    pub(crate) async fn execute_block(
+        &mut self,
+        block: &Block,
+        mut chain_state: ChainState,
+    ) -> Result<ChainState, NodeError> {
+        let mut new_chain_state = chain_state.create_new_chain_state();
+        for transaction in &block.body.transactions {
+            new_chain_state = self
+                .executor
+                .execute_transaction(transaction.clone(), new_chain_state)
+                .await?;
+        }
+        Ok(new_chain_state)
+    }
+
+    /// [`Self::execute_block`] for a block this node already finalized, whose deposits the
+    /// oracle confirmed before it was voted for and are not asked about again.
+    async fn replay_block(
+        &mut self,
+        block: &Block,
+        mut chain_state: ChainState,
+    ) -> Result<ChainState, NodeError> {
+        let mut new_chain_state = chain_state.create_new_chain_state();
+        for transaction in &block.body.transactions {
+            new_chain_state = self
+                .executor
+                .replay_transaction(transaction.clone(), new_chain_state)
+                .await?;
+        }
+        Ok(new_chain_state)
+    }
+}
@@ -16,7 +16,7 @@ fn test_account_new() {
 #[test]
 fn test_account_increment_balance() {
     let account = Account::new("test".to_string(), 100);
-    let incremented = account.increment_balance(50);
+    let incremented = account.increment_balance(50).unwrap();
 
     assert_eq!(incremented.balance, 150);
     assert_eq!(incremented.address, "test");
@@ -27,15 +27,16 @@ fn test_account_increment_balance() {
 #[test]
 fn test_account_increment_balance_overflow() {
     let account = Account::new("test".to_string(), u64::MAX - 10);
-    let incremented = account.increment_balance(5);
+    let incremented = account.increment_balance(5).unwrap();
 
     assert_eq!(incremented.balance, u64::MAX - 5);
+    assert!(incremented.increment_balance(6).is_err());
 }
 
 #[test]
 fn test_account_decrement_balance() {
     let account = Account::new("test".to_string(), 100);
-    let decremented = account.decrement_balance(30);
+    let decremented = account.decrement_balance(30).unwrap();
 
     assert_eq!(decremented.balance, 70);
     assert_eq!(decremented.address, "test");
@@ -46,10 +47,15 @@ fn test_account_decrement_balance() {
 #[test]
 fn test_account_decrement_balance_underflow() {
     let account = Account::new("test".to_string(), 50);
-    let decremented = account.decrement_balance(100);
-
-    // Should saturate to 0, not underflow
-    assert_eq!(decremented.balance, 0);
+    let result = account.decrement_balance(100);
+
+    // Should be rejected, not saturate to 0
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("Insufficient balance")
+    );
 }
 
 #[test]
@@ -145,6 +151,8 @@ fn test_chain_state_insert_deposit_intent() {
         deposit_address: "deposit_addr".to_string(),
         timestamp: 123_456_789,
         user_pubkey: "user_pubkey".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     state.insert_deposit_intent(intent);
@@ -172,6 +180,8 @@ fn test_chain_state_get_all_deposit_intents_multiple() {
         deposit_address: "addr1".to_string(),
         timestamp: 123_456_789,
         user_pubkey: "user1".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
     let intent2 = DepositIntent {
         amount_sat: 2000,
@@ -179,6 +189,8 @@ fn test_chain_state_get_all_deposit_intents_multiple() {
         deposit_address: "addr2".to_string(),
         timestamp: 987_654_321,
         user_pubkey: "user2".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     state.insert_deposit_intent(intent1);
@@ -199,6 +211,8 @@ fn test_chain_state_get_deposit_intent_by_address_found() {
         deposit_address: "specific_addr".to_string(),
         timestamp: 123_456_789,
         user_pubkey: "user".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     state.insert_deposit_intent(intent);
@@ -217,6 +231,8 @@ fn test_chain_state_get_deposit_intent_by_address_not_found() {
         deposit_address: "addr1".to_string(),
         timestamp: 123_456_789,
         user_pubkey: "user".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     state.insert_deposit_intent(intent);
@@ -263,6 +279,8 @@ fn test_chain_state_serialize_deserialize_with_data() {
         deposit_address: "deposit_addr".to_string(),
         timestamp: 123_456_789,
         user_pubkey: "user_pubkey".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
     state.insert_deposit_intent(intent);
 
@@ -318,3 +336,93 @@ fn test_chain_state_serialize_error_coverage() {
     // is tested implicitly by this test existing, as it shows the method
     // can handle both success and potential error cases.
 }
+
+fn deposit_funded_by(lock_time: u32, user_pubkey: &str) -> protocol::transaction::Transaction {
+    let funding_tx = bitcoin::Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: bitcoin::absolute::LockTime::from_consensus(lock_time),
+        input: vec![],
+        output: vec![bitcoin::TxOut {
+            value: bitcoin::Amount::from_sat(10_000),
+            script_pubkey: bitcoin::ScriptBuf::new(),
+        }],
+    };
+    protocol::transaction::Transaction::create_deposit_transaction(&funding_tx, user_pubkey, 10_000)
+        .unwrap()
+}
+
+#[test]
+fn test_chain_state_proposed_block_order_is_independent_of_arrival() {
+    let deposits: Vec<_> = (0..6)
+        .map(|i| deposit_funded_by(i, &format!("user_{i}")))
+        .collect();
+    let withdrawal =
+        protocol::transaction::Transaction::create_withdrawal_transaction("user_0", "bc1q", 500)
+            .unwrap();
+
+    let mut first = ChainState::new();
+    for tx in deposits.iter().chain(std::iter::once(&withdrawal)) {
+        first.add_transaction_to_block(tx.clone());
+    }
+    let mut second = ChainState::new();
+    for tx in std::iter::once(&withdrawal).chain(deposits.iter().rev()) {
+        second.add_transaction_to_block(tx.clone());
+    }
+
+    let first_block = first.get_proposed_block(None, vec![1]);
+    let second_block = second.get_proposed_block(None, vec![1]);
+    assert_eq!(
+        first_block.body.transactions,
+        second_block.body.transactions
+    );
+    assert_eq!(
+        first_block.body.calculate_hash(),
+        second_block.body.calculate_hash()
+    );
+
+    // Deposits lead, sorted by funding txid; the withdrawal comes last.
+    let txs = &first_block.body.transactions;
+    assert_eq!(txs.last(), Some(&withdrawal));
+    let funding_txids: Vec<_> = txs[..deposits.len()]
+        .iter()
+        .map(|tx| tx.ordering_key().1)
+        .collect();
+    assert!(funding_txids.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[test]
+fn test_deposits_from_one_funding_transaction_are_ordered_by_output() {
+    let funding_tx = bitcoin::Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: vec![],
+        output: vec![
+            bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(10_000),
+                script_pubkey: bitcoin::ScriptBuf::new(),
+            };
+            3
+        ],
+    };
+    let deposits: Vec<_> = (0..3u32)
+        .map(|vout| {
+            protocol::transaction::Transaction::create_deposit_transaction(
+                &funding_tx,
+                &format!("user_{vout}"),
+                10_000,
+            )
+            .unwrap()
+            .with_funding_vout(vout)
+        })
+        .collect();
+
+    let mut state = ChainState::new();
+    for tx in deposits.iter().rev() {
+        state.add_transaction_to_block(tx.clone());
+    }
+
+    let block = state.get_proposed_block(None, vec![1]);
+    assert_eq!(block.body.transactions, deposits);
+    assert_eq!(deposits[2].funding_txid(), Some(funding_tx.compute_txid()));
+    assert_eq!(deposits[2].funding_vout(), Some(2));
+}
@@ -1,10 +1,12 @@
 use crate::chain_state::{Account, ChainState};
 use crate::db::Db;
 use crate::db::rocksdb::RocksDb;
-use protocol::block::{Block, BlockBody, BlockHeader, ChainConfig, GenesisBlock, ValidatorInfo};
+use protocol::block::{
+    Block, BlockBody, BlockHeader, ChainConfig, ConsensusQuorum, GenesisBlock, ValidatorInfo,
+};
 use std::collections::HashMap;
 use tempfile::TempDir;
-use types::intents::DepositIntent;
+use types::intents::{DEPOSIT_INTENT_VERSION, DepositIntent, LegacyDepositIntent};
 use uuid::Uuid;
 
 fn create_test_db() -> (RocksDb, TempDir) {
@@ -114,6 +116,7 @@ fn test_insert_and_get_block() {
         min_stake: 1000,
         block_time_seconds: 10,
         max_block_size: 1_024_000,
+        consensus_quorum: ConsensusQuorum::default(),
     };
 
     let genesis_block = GenesisBlock::new(
@@ -164,6 +167,7 @@ fn test_insert_multiple_blocks() {
         min_stake: 1000,
         block_time_seconds: 10,
         max_block_size: 1_024_000,
+        consensus_quorum: ConsensusQuorum::default(),
     };
 
     let genesis_block = GenesisBlock::new(validators, chain_config, vec![1, 2, 3, 4]);
@@ -208,6 +212,8 @@ fn test_insert_and_get_deposit_intent() {
         deposit_address: "test_deposit_address".to_string(),
         timestamp: 1_234_567_890,
         user_pubkey: "test_user_pubkey".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     // Insert the intent
@@ -261,6 +267,8 @@ fn test_get_all_deposit_intents_multiple() {
         deposit_address: "address1".to_string(),
         timestamp: 1_234_567_890,
         user_pubkey: "user1".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     let intent2 = DepositIntent {
@@ -269,6 +277,8 @@ fn test_get_all_deposit_intents_multiple() {
         deposit_address: "address2".to_string(),
         timestamp: 1_234_567_891,
         user_pubkey: "user2".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     // Insert both intents
@@ -298,6 +308,8 @@ fn test_deposit_intent_address_lookup_independence() {
         deposit_address: "unique_address".to_string(),
         timestamp: 1_234_567_890,
         user_pubkey: "user".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     db.insert_deposit_intent(intent.clone()).unwrap();
@@ -336,6 +348,8 @@ fn test_complex_chain_state_serialization() {
         deposit_address: "deposit1".to_string(),
         timestamp: 1_234_567_890,
         user_pubkey: "user1".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     let intent2 = DepositIntent {
@@ -344,6 +358,8 @@ fn test_complex_chain_state_serialization() {
         deposit_address: "deposit2".to_string(),
         timestamp: 1_234_567_891,
         user_pubkey: "user2".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     chain_state.insert_deposit_intent(intent1);
@@ -384,3 +400,293 @@ fn test_error_handling_corrupted_data() {
     let result = db.get_deposit_intent("corrupted_intent");
     assert!(result.is_err());
 }
+
+#[test]
+fn test_audit_log_appends_in_order_and_survives_reopen() {
+    use types::audit::{AuditEntry, AuditEventKind, AuditOutcome};
+
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().to_str().unwrap();
+
+    {
+        let db = RocksDb::new(db_path);
+        for (kind, amount_sat) in [
+            (AuditEventKind::WithdrawalProposed, 10_000),
+            (AuditEventKind::SigningStarted, 10_000),
+        ] {
+            let entry = AuditEntry::new(kind, amount_sat, "addr", "ref", AuditOutcome::Accepted);
+            db.append_audit_entry(entry).unwrap();
+        }
+    }
+
+    let db = RocksDb::new(db_path);
+    let mut late = AuditEntry::new(
+        AuditEventKind::TransactionBroadcast,
+        10_000,
+        "addr",
+        "txid",
+        AuditOutcome::Failed("oracle unavailable".to_string()),
+    );
+    late.timestamp = u64::MAX;
+    assert_eq!(db.append_audit_entry(late).unwrap(), 2);
+
+    let entries = db.get_audit_log(0, u64::MAX).unwrap();
+    let sequences: Vec<_> = entries.iter().map(|entry| entry.sequence).collect();
+    assert_eq!(sequences, [0, 1, 2]);
+    assert_eq!(entries[1].kind, AuditEventKind::SigningStarted);
+
+    // The range bounds filter on timestamp
+    let recent = db.get_audit_log(u64::MAX, u64::MAX).unwrap();
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].outcome.reason(), Some("oracle unavailable"));
+    assert!(db.get_audit_log(0, u64::MAX - 1).unwrap().len() == 2);
+}
+
+#[test]
+fn test_sign_id_counter_survives_reopen() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().to_str().unwrap();
+
+    {
+        let db = RocksDb::new(db_path);
+        assert_eq!(db.allocate_sign_id_counter().unwrap(), 0);
+        assert_eq!(db.allocate_sign_id_counter().unwrap(), 1);
+    }
+
+    let db = RocksDb::new(db_path);
+    assert_eq!(db.allocate_sign_id_counter().unwrap(), 2);
+}
+
+#[test]
+fn test_commit_block_persists_block_and_state_together() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().to_str().unwrap();
+
+    let block = Block::new([0u8; 32], 1, vec![], vec![1, 2, 3, 4]);
+    let mut chain_state = ChainState::new();
+    chain_state.upsert_account("commit_addr", Account::new("commit_addr".to_string(), 700));
+
+    // Crash after the block is staged but before the state is: nothing reaches the disk.
+    {
+        let db = RocksDb::new(db_path);
+        let mut batch = rocksdb::WriteBatch::default();
+        db.stage_block(&mut batch, &block).unwrap();
+        drop(batch);
+    }
+    {
+        let db = RocksDb::new(db_path);
+        assert!(db.get_block_by_height(1).unwrap().is_none());
+        assert!(db.get_tip_block_hash().unwrap().is_none());
+        let state = db.get_chain_state().unwrap().unwrap();
+        assert!(state.get_account("commit_addr").is_none());
+
+        db.commit_block(block.clone(), &chain_state).unwrap();
+    }
+
+    let db = RocksDb::new(db_path);
+    assert_eq!(
+        db.get_block_by_height(1).unwrap().unwrap().hash(),
+        block.hash()
+    );
+    assert_eq!(db.get_tip_block_hash().unwrap(), Some(block.hash()));
+    let state = db.get_chain_state().unwrap().unwrap();
+    assert_eq!(state.get_account("commit_addr").unwrap().balance, 700);
+}
+
+#[test]
+fn test_spent_outpoints_are_stored_outside_the_chain_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().to_str().unwrap();
+
+    let mut chain_state = ChainState::new();
+    assert!(chain_state.mark_outpoint_spent("first_txid:0"));
+    {
+        let db = RocksDb::new(db_path);
+        db.commit_block(
+            Block::new([0u8; 32], 1, vec![], vec![1, 2, 3, 4]),
+            &chain_state,
+        )
+        .unwrap();
+    }
+
+    // The next block only adds its own outpoint
+    let mut chain_state = chain_state.create_new_chain_state();
+    assert!(chain_state.block_spent_outpoints().is_empty());
+    assert!(chain_state.mark_outpoint_spent("second_txid:1"));
+    assert_eq!(chain_state.block_spent_outpoints(), ["second_txid:1"]);
+    {
+        let db = RocksDb::new(db_path);
+        db.commit_block(
+            Block::new([1u8; 32], 2, vec![], vec![1, 2, 3, 4]),
+            &chain_state,
+        )
+        .unwrap();
+    }
+
+    let db = RocksDb::new(db_path);
+    let stored = db
+        .db
+        .get_cf(db.db.cf_handle("chain_state").unwrap(), "current")
+        .unwrap()
+        .unwrap();
+    assert!(
+        !ChainState::deserialize(&stored)
+            .unwrap()
+            .is_outpoint_spent("first_txid:0")
+    );
+
+    let state = db.get_chain_state().unwrap().unwrap();
+    assert!(state.is_outpoint_spent("first_txid:0"));
+    assert!(state.is_outpoint_spent("second_txid:1"));
+    assert!(!state.is_outpoint_spent("second_txid:0"));
+}
+
+#[test]
+fn test_unversioned_chain_state_is_read_and_rewritten_versioned() {
+    use crate::chain_state::{CHAIN_STATE_VERSION, VERSIONED_ENCODING_MARKER};
+
+    let (db, _temp_dir) = create_test_db();
+    let cf = db.db.cf_handle("chain_state").unwrap();
+
+    // The layout written before the encoding carried a version
+    let mut accounts = HashMap::new();
+    accounts.insert(
+        "legacy_addr".to_string(),
+        Account::new("legacy_addr".to_string(), 900),
+    );
+    let intents = vec![LegacyDepositIntent {
+        amount_sat: 5000,
+        user_pubkey: "legacy_user".to_string(),
+        deposit_tracking_id: "legacy_tracking_id".to_string(),
+        deposit_address: "legacy_deposit_address".to_string(),
+        timestamp: 1_234_567_890,
+    }];
+    let unversioned = bincode::encode_to_vec(
+        (
+            accounts,
+            intents,
+            Vec::<protocol::transaction::Transaction>::new(),
+            7u64,
+        ),
+        bincode::config::standard(),
+    )
+    .unwrap();
+    db.db.put_cf(cf, "current", unversioned).unwrap();
+
+    let state = db.get_chain_state().unwrap().unwrap();
+    assert_eq!(state.get_account("legacy_addr").unwrap().balance, 900);
+    assert_eq!(state.get_block_height(), 7);
+    // Intents stored before they could expire never do
+    let intent = state
+        .get_deposit_intent_by_address("legacy_deposit_address")
+        .unwrap();
+    assert_eq!(intent.expires_at, 0);
+    assert_eq!(intent.min_confirmations, None);
+
+    db.flush_state(&state).unwrap();
+    let stored = db.db.get_cf(cf, "current").unwrap().unwrap();
+    assert_eq!(
+        stored[..2],
+        [VERSIONED_ENCODING_MARKER, CHAIN_STATE_VERSION]
+    );
+    assert_eq!(
+        db.get_chain_state()
+            .unwrap()
+            .unwrap()
+            .get_account("legacy_addr")
+            .unwrap()
+            .balance,
+        900
+    );
+
+    // A version from a newer build is refused rather than misread
+    db.db
+        .put_cf(
+            cf,
+            "current",
+            [VERSIONED_ENCODING_MARKER, CHAIN_STATE_VERSION + 1],
+        )
+        .unwrap();
+    assert!(db.get_chain_state().is_err());
+}
+
+#[test]
+fn test_unversioned_deposit_intent_is_read_and_newer_version_refused() {
+    let (db, _temp_dir) = create_test_db();
+    let cf = db.db.cf_handle("deposit_intents").unwrap();
+
+    let legacy = LegacyDepositIntent {
+        amount_sat: 8000,
+        user_pubkey: "legacy_user".to_string(),
+        deposit_tracking_id: "legacy_tracking_id".to_string(),
+        deposit_address: "legacy_deposit_address".to_string(),
+        timestamp: 1_234_567_890,
+    };
+    let unversioned = bincode::encode_to_vec(&legacy, bincode::config::standard()).unwrap();
+    db.db
+        .put_cf(cf, "legacy_tracking_id", &unversioned)
+        .unwrap();
+    db.db
+        .put_cf(cf, "addr:legacy_deposit_address", &unversioned)
+        .unwrap();
+
+    let intent = db
+        .get_deposit_intent("legacy_tracking_id")
+        .unwrap()
+        .unwrap();
+    assert_eq!(intent.amount_sat, 8000);
+    assert_eq!(intent.expires_at, 0);
+    assert_eq!(intent.min_confirmations, None);
+    assert_eq!(
+        db.get_deposit_intent_by_address("legacy_deposit_address")
+            .unwrap()
+            .unwrap()
+            .deposit_tracking_id,
+        "legacy_tracking_id"
+    );
+
+    // Rewritten intents carry the current version
+    db.insert_deposit_intent(intent).unwrap();
+    let stored = db.db.get_cf(cf, "legacy_tracking_id").unwrap().unwrap();
+    assert_eq!(stored[1], DEPOSIT_INTENT_VERSION);
+    assert_eq!(db.get_all_deposit_intents().unwrap().len(), 1);
+
+    db.db
+        .put_cf(cf, "legacy_tracking_id", [0xFF, DEPOSIT_INTENT_VERSION + 1])
+        .unwrap();
+    assert!(db.get_deposit_intent("legacy_tracking_id").is_err());
+}
+
+#[test]
+fn test_deposit_intent_min_confirmations_survives_storage() {
+    let (db, _temp_dir) = create_test_db();
+    let intent = DepositIntent {
+        amount_sat: 25_000,
+        deposit_tracking_id: Uuid::new_v4().to_string(),
+        deposit_address: "deep_deposit_address".to_string(),
+        timestamp: 1_234_567_890,
+        user_pubkey: "deep_user".to_string(),
+        expires_at: 1_234_654_290,
+        min_confirmations: Some(12),
+    };
+
+    db.insert_deposit_intent(intent.clone()).unwrap();
+    let stored = db
+        .get_deposit_intent_by_address("deep_deposit_address")
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.min_confirmations, Some(12));
+    assert_eq!(stored.expires_at, intent.expires_at);
+
+    let mut chain_state = ChainState::new();
+    chain_state.insert_deposit_intent(intent);
+    db.flush_state(&chain_state).unwrap();
+    let restored = db.get_chain_state().unwrap().unwrap();
+    assert_eq!(
+        restored
+            .get_deposit_intent_by_address("deep_deposit_address")
+            .unwrap()
+            .min_confirmations,
+        Some(12)
+    );
+}
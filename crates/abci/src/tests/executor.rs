@@ -4,7 +4,9 @@ use crate::chain_state::{Account, ChainState};
 use crate::executor::*;
 use bitcoin::hashes::Hash;
 use oracle::mock::MockOracle;
-use protocol::transaction::{Operation, Transaction, TransactionType};
+use protocol::transaction::{
+    Operation, Transaction, TransactionType, decode_amount, encode_amount,
+};
 use types::errors::NodeError;
 
 // Simple oracle that always returns true for testing
@@ -40,6 +42,14 @@ impl oracle::oracle::Oracle for AlwaysValidOracle {
         Ok("mock_txid".to_string())
     }
 
+    async fn is_transaction_confirmed(&self, _txid: bitcoin::Txid) -> Result<bool, NodeError> {
+        Ok(true)
+    }
+
+    async fn get_transaction_height(&self, _txid: bitcoin::Txid) -> Result<Option<u32>, NodeError> {
+        Ok(Some(800_000))
+    }
+
     async fn get_confirmed_transactions(
         &self,
         _addresses: Vec<bitcoin::Address>,
@@ -57,6 +67,10 @@ impl oracle::oracle::Oracle for AlwaysValidOracle {
         Ok(800_000) // Mock block height
     }
 
+    async fn get_block_hash(&self, _height: u32) -> Result<bitcoin::BlockHash, NodeError> {
+        Ok(bitcoin::BlockHash::all_zeros())
+    }
+
     async fn get_transaction_by_address(
         &self,
         address: &str,
@@ -388,6 +402,35 @@ fn test_op_decrement_balance_insufficient_balance() {
     );
 }
 
+#[test]
+fn test_op_increment_balance_overflow_is_rejected() {
+    let mut executor = create_test_executor();
+
+    let address = "test_address".to_string();
+    let initial_balance = u64::MAX - 10;
+    let increment_amount = 11u64;
+
+    let account = Account::new(address.clone(), initial_balance);
+    executor.new_chain_state.upsert_account(&address, account);
+    executor
+        .allowance_list
+        .insert(address.clone(), increment_amount);
+
+    executor.push_to_stack(increment_amount.to_be_bytes().to_vec());
+    executor.push_to_stack(address.as_bytes().to_vec());
+
+    let result = executor.op_increment_balance();
+    assert!(result.unwrap_err().to_string().contains("Balance overflow"));
+
+    // Neither the balance nor the allowance was touched
+    let account = executor.new_chain_state.get_account(&address).unwrap();
+    assert_eq!(account.balance, initial_balance);
+    assert_eq!(
+        executor.allowance_list.get(&address),
+        Some(&increment_amount)
+    );
+}
+
 #[test]
 fn test_op_decrement_balance_nonexistent_account() {
     let mut executor = create_test_executor();
@@ -409,25 +452,18 @@ fn test_op_decrement_balance_nonexistent_account() {
 }
 
 #[tokio::test]
-async fn test_execute_transaction_push_operations() {
+async fn test_execute_operations_push_operations() {
     let mut executor = create_test_executor();
-    let initial_state = ChainState::new();
 
-    let transaction = Transaction::new(
-        TransactionType::Deposit,
-        vec![
+    let result = executor
+        .execute_operations(vec![
             Operation::OpPush {
                 value: vec![1, 2, 3],
             },
             Operation::OpPush {
                 value: vec![4, 5, 6],
             },
-        ],
-        None,
-    );
-
-    let result = executor
-        .execute_transaction(transaction, initial_state)
+        ])
         .await;
     assert!(result.is_ok());
 
@@ -518,6 +554,39 @@ async fn test_execute_transaction_withdrawal_flow() {
     assert_eq!(account.balance, initial_balance - withdrawal_amount);
 }
 
+#[tokio::test]
+async fn test_execute_transaction_rejects_withdrawal_above_balance() {
+    let mut executor = create_test_executor();
+
+    let mut initial_state = ChainState::new();
+    let address = "withdrawal_address";
+    initial_state.upsert_account(address, Account::new(address.to_string(), 500));
+
+    let transaction = Transaction::new(
+        TransactionType::Withdrawal,
+        vec![
+            Operation::OpPush {
+                value: 501u64.to_be_bytes().to_vec(),
+            },
+            Operation::OpPush {
+                value: address.as_bytes().to_vec(),
+            },
+            Operation::OpDecrementBalance,
+        ],
+        None,
+    );
+
+    let result = executor
+        .execute_transaction(transaction, initial_state)
+        .await;
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("Insufficient balance")
+    );
+}
+
 #[tokio::test]
 async fn test_execute_transaction_error_propagation() {
     let mut executor = create_test_executor();
@@ -704,6 +773,17 @@ async fn test_op_check_oracle_false_validation() {
             Ok("mock_txid".to_string())
         }
 
+        async fn is_transaction_confirmed(&self, _txid: bitcoin::Txid) -> Result<bool, NodeError> {
+            Ok(true)
+        }
+
+        async fn get_transaction_height(
+            &self,
+            _txid: bitcoin::Txid,
+        ) -> Result<Option<u32>, NodeError> {
+            Ok(Some(800_000))
+        }
+
         async fn get_confirmed_transactions(
             &self,
             _addresses: Vec<bitcoin::Address>,
@@ -721,6 +801,10 @@ async fn test_op_check_oracle_false_validation() {
             Ok(800_000)
         }
 
+        async fn get_block_hash(&self, _height: u32) -> Result<bitcoin::BlockHash, NodeError> {
+            Ok(bitcoin::BlockHash::all_zeros())
+        }
+
         async fn get_transaction_by_address(
             &self,
             _address: &str,
@@ -749,3 +833,243 @@ async fn test_op_check_oracle_false_validation() {
     // Stack should have 0 (false) pushed to it
     assert_eq!(executor.pop_from_stack(), Some(0u64.to_be_bytes().to_vec()));
 }
+
+// An amount whose bytes differ in every position, so any byte-order mismatch changes its value
+const NON_PALINDROMIC_AMOUNT: u64 = 0x0102_0304_0506_0708;
+
+#[test]
+fn test_amount_encoding_is_big_endian() {
+    assert_eq!(
+        encode_amount(NON_PALINDROMIC_AMOUNT),
+        vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
+    );
+    assert_eq!(
+        decode_amount(&encode_amount(NON_PALINDROMIC_AMOUNT)).unwrap(),
+        NON_PALINDROMIC_AMOUNT
+    );
+    assert_ne!(
+        decode_amount(&NON_PALINDROMIC_AMOUNT.to_le_bytes()).unwrap(),
+        NON_PALINDROMIC_AMOUNT
+    );
+}
+
+#[test]
+fn test_decode_amount_rejects_non_canonical_length() {
+    assert!(decode_amount(&[]).is_err());
+    assert!(decode_amount(&[0x01, 0x02, 0x03, 0x04]).is_err());
+    assert!(decode_amount(&[0u8; 9]).is_err());
+}
+
+#[tokio::test]
+async fn test_builder_amounts_decode_identically_in_executor() {
+    let mut executor = create_test_executor();
+    let address = "endianness_address";
+    let withdrawal_amount = 0x0000_0000_0102_0304u64;
+
+    let deposit_tx = MockOracle::create_dummy_tx_without_address(NON_PALINDROMIC_AMOUNT);
+    let deposit =
+        Transaction::create_deposit_transaction(&deposit_tx, address, NON_PALINDROMIC_AMOUNT)
+            .unwrap();
+    let state = executor
+        .execute_transaction(deposit, ChainState::new())
+        .await
+        .unwrap();
+    assert_eq!(
+        state.get_account(address).unwrap().balance,
+        NON_PALINDROMIC_AMOUNT
+    );
+
+    let withdrawal =
+        Transaction::create_withdrawal_transaction(address, "destination", withdrawal_amount)
+            .unwrap();
+    let state = executor
+        .execute_transaction(withdrawal, state)
+        .await
+        .unwrap();
+    assert_eq!(
+        state.get_account(address).unwrap().balance,
+        NON_PALINDROMIC_AMOUNT - withdrawal_amount
+    );
+}
+
+#[test]
+fn test_op_increment_balance_rejects_little_endian_mismatch() {
+    let mut executor = create_test_executor();
+    let address = "le_address";
+    executor
+        .allowance_list
+        .insert(address.to_string(), NON_PALINDROMIC_AMOUNT);
+
+    // A little-endian push decodes to a much larger value that exceeds the allowance
+    executor.push_to_stack(NON_PALINDROMIC_AMOUNT.to_le_bytes().to_vec());
+    executor.push_to_stack(address.as_bytes().to_vec());
+    assert!(
+        executor
+            .op_increment_balance()
+            .unwrap_err()
+            .to_string()
+            .contains("Insufficient allowance")
+    );
+}
+
+#[tokio::test]
+async fn test_withdrawal_missing_decrement_is_rejected() {
+    let mut executor = create_test_executor();
+    let address = "withdrawal_address";
+    let mut initial_state = ChainState::new();
+    initial_state.upsert_account(address, Account::new(address.to_string(), 2000));
+
+    let transaction = Transaction::new(
+        TransactionType::Withdrawal,
+        vec![
+            Operation::OpPush {
+                value: encode_amount(500),
+            },
+            Operation::OpPush {
+                value: address.as_bytes().to_vec(),
+            },
+        ],
+        None,
+    );
+
+    let err = executor
+        .execute_transaction(transaction, initial_state)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Malformed Withdrawal transaction: trailing operands")
+    );
+}
+
+#[tokio::test]
+async fn test_withdrawal_with_foreign_operation_is_rejected() {
+    let mut executor = create_test_executor();
+
+    let transaction = Transaction::new(
+        TransactionType::Withdrawal,
+        vec![
+            Operation::OpPush {
+                value: encode_amount(500),
+            },
+            Operation::OpPush {
+                value: b"address".to_vec(),
+            },
+            Operation::OpIncrementBalance,
+        ],
+        None,
+    );
+
+    let err = executor
+        .execute_transaction(transaction, ChainState::new())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("OpIncrementBalance is not allowed")
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_with_short_txid_is_rejected_before_execution() {
+    let mut executor = create_test_executor();
+    let address = "deposit_address";
+
+    let transaction = Transaction::new(
+        TransactionType::Deposit,
+        vec![
+            Operation::OpPush {
+                value: encode_amount(1000),
+            },
+            Operation::OpPush {
+                value: address.as_bytes().to_vec(),
+            },
+            Operation::OpPush {
+                value: vec![0u8; 31],
+            },
+            Operation::OpCheckOracle,
+            Operation::OpPush {
+                value: encode_amount(1000),
+            },
+            Operation::OpPush {
+                value: address.as_bytes().to_vec(),
+            },
+            Operation::OpIncrementBalance,
+        ],
+        None,
+    );
+
+    let err = executor
+        .execute_transaction(transaction, ChainState::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("txid must be 32 bytes, got 31"));
+    // Nothing ran: the oracle was never asked, so no allowance was granted
+    assert!(executor.allowance_list.is_empty());
+}
+
+#[tokio::test]
+async fn test_cancelling_an_intent_without_expiry_drops_it() {
+    let mut executor = create_test_executor();
+    let mut chain_state = ChainState::new();
+    chain_state.insert_deposit_intent(types::intents::DepositIntent {
+        amount_sat: 1000,
+        user_pubkey: "user".to_string(),
+        deposit_tracking_id: "open_address".to_string(),
+        deposit_address: "open_address".to_string(),
+        timestamp: 0,
+        expires_at: 0,
+        min_confirmations: None,
+    });
+
+    let chain_state = executor
+        .execute_transaction(
+            Transaction::create_expire_deposit_intent_transaction("open_address", 0),
+            chain_state,
+        )
+        .await
+        .unwrap();
+    assert!(chain_state.get_all_deposit_intents().is_empty());
+}
+
+#[tokio::test]
+async fn test_expire_deposit_intent_drops_only_the_intent_it_was_built_from() {
+    let mut executor = create_test_executor();
+    let intent = |address: &str, expires_at: u64| types::intents::DepositIntent {
+        amount_sat: 1000,
+        user_pubkey: "user".to_string(),
+        deposit_tracking_id: address.to_string(),
+        deposit_address: address.to_string(),
+        timestamp: 0,
+        expires_at,
+        min_confirmations: None,
+    };
+    let mut chain_state = ChainState::new();
+    chain_state.insert_deposit_intent(intent("expired_address", 100));
+    chain_state.insert_deposit_intent(intent("renewed_address", 200));
+
+    let chain_state = executor
+        .execute_transaction(
+            Transaction::create_expire_deposit_intent_transaction("expired_address", 100),
+            chain_state,
+        )
+        .await
+        .unwrap();
+    assert!(
+        chain_state
+            .get_deposit_intent_by_address("expired_address")
+            .is_none()
+    );
+
+    // An intent with another expiry, or one already gone, is left as it is
+    for (address, expires_at) in [("renewed_address", 100), ("expired_address", 100)] {
+        let chain_state = executor
+            .execute_transaction(
+                Transaction::create_expire_deposit_intent_transaction(address, expires_at),
+                chain_state.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(chain_state.get_all_deposit_intents().len(), 1);
+    }
+}
@@ -1,12 +1,18 @@
+use crate::chain_state::ChainState;
+use crate::db::Db;
 use crate::db::rocksdb::RocksDb;
 use crate::executor::TransactionExecutorImpl;
 use crate::{ChainInterface, ChainInterfaceImpl};
 use bitcoin::hashes::Hash;
 
 use oracle::mock::MockOracle;
+use protocol::block::{Block, BlockHash, GenesisBlock};
 use protocol::transaction::{Operation, Transaction, TransactionType};
 use tempfile::TempDir;
-use types::intents::DepositIntent;
+use types::audit::AuditEntry;
+use types::errors::NodeError;
+use types::intents::{DepositIntent, WithdrawalRecord};
+use types::utxo::Utxo;
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -47,6 +53,20 @@ impl oracle::oracle::Oracle for AlwaysValidOracle {
         Ok("mock_txid".to_string())
     }
 
+    async fn is_transaction_confirmed(
+        &self,
+        _txid: bitcoin::Txid,
+    ) -> Result<bool, types::errors::NodeError> {
+        Ok(true)
+    }
+
+    async fn get_transaction_height(
+        &self,
+        _txid: bitcoin::Txid,
+    ) -> Result<Option<u32>, types::errors::NodeError> {
+        Ok(Some(800_000))
+    }
+
     async fn get_confirmed_transactions(
         &self,
         _addresses: Vec<bitcoin::Address>,
@@ -64,6 +84,13 @@ impl oracle::oracle::Oracle for AlwaysValidOracle {
         Ok(800_000) // Mock block height
     }
 
+    async fn get_block_hash(
+        &self,
+        _height: u32,
+    ) -> Result<bitcoin::BlockHash, types::errors::NodeError> {
+        Ok(bitcoin::BlockHash::all_zeros())
+    }
+
     async fn get_transaction_by_address(
         &self,
         _address: &str,
@@ -109,6 +136,8 @@ fn test_insert_and_get_deposit_intent() {
         deposit_address: "test_deposit_address".to_string(),
         timestamp: 1_234_567_890,
         user_pubkey: "test_user_pubkey".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     // Insert intent
@@ -136,6 +165,8 @@ fn test_insert_multiple_deposit_intents() {
         deposit_address: "address1".to_string(),
         timestamp: 1_234_567_890,
         user_pubkey: "user1".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     let intent2 = DepositIntent {
@@ -144,6 +175,8 @@ fn test_insert_multiple_deposit_intents() {
         deposit_address: "address2".to_string(),
         timestamp: 1_234_567_891,
         user_pubkey: "user2".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     // Insert both intents
@@ -347,6 +380,59 @@ async fn test_execute_transaction_insufficient_balance() {
     );
 }
 
+#[tokio::test]
+async fn test_block_double_spending_vault_outpoint_is_rejected() {
+    let (mut chain_interface, _temp_dir) = create_test_chain_interface();
+
+    let address = "double_spender";
+    let initial_balance = 10_000u64;
+
+    let deposit_tx = MockOracle::create_dummy_tx_without_address(initial_balance);
+    let deposit =
+        Transaction::create_deposit_transaction(&deposit_tx, address, initial_balance).unwrap();
+    chain_interface
+        .add_transaction_to_block(deposit)
+        .await
+        .unwrap();
+    let block = chain_interface
+        .get_proposed_block(None, vec![1, 2, 3, 4])
+        .unwrap();
+    chain_interface
+        .finalize_and_store_block(block)
+        .await
+        .unwrap();
+    let height_before = chain_interface.get_chain_state().get_block_height();
+
+    // Two withdrawals to different destinations that both spend the same vault outpoint
+    let outpoint = bitcoin::OutPoint::new(bitcoin::Txid::all_zeros(), 0);
+    for destination in ["first_destination", "second_destination"] {
+        let withdrawal = Transaction::create_withdrawal_transaction(address, destination, 1_000)
+            .unwrap()
+            .with_spent_outpoints(&[outpoint]);
+        chain_interface
+            .add_transaction_to_block(withdrawal)
+            .await
+            .unwrap();
+    }
+
+    let block = chain_interface
+        .get_proposed_block(None, vec![1, 2, 3, 4])
+        .unwrap();
+    let error = chain_interface
+        .finalize_and_store_block(block)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("already spent"));
+
+    let chain_state = chain_interface.get_chain_state();
+    assert_eq!(chain_state.get_block_height(), height_before);
+    assert!(!chain_state.is_outpoint_spent(&outpoint.to_string()));
+    assert_eq!(
+        chain_interface.get_account(address).unwrap().balance,
+        initial_balance
+    );
+}
+
 #[tokio::test]
 async fn test_execute_transaction_state_persistence() {
     let (mut chain_interface, _temp_dir) = create_test_chain_interface();
@@ -455,6 +541,64 @@ async fn test_execute_multiple_transactions() {
     assert_eq!(account.balance, 6000);
 }
 
+#[tokio::test]
+async fn test_finalizing_a_subset_keeps_other_pending_transactions() {
+    let (mut chain_interface, _temp_dir) = create_test_chain_interface();
+
+    let deposit = |address: &str| {
+        Transaction::new(
+            TransactionType::Deposit,
+            vec![
+                Operation::OpPush {
+                    value: 1000u64.to_be_bytes().to_vec(),
+                },
+                Operation::OpPush {
+                    value: address.as_bytes().to_vec(),
+                },
+                Operation::OpPush {
+                    value: bitcoin::Txid::all_zeros().to_byte_array().to_vec(),
+                },
+                Operation::OpCheckOracle,
+                Operation::OpPush {
+                    value: 1000u64.to_be_bytes().to_vec(),
+                },
+                Operation::OpPush {
+                    value: address.as_bytes().to_vec(),
+                },
+                Operation::OpIncrementBalance,
+            ],
+            None,
+        )
+    };
+    let included = deposit("included_user");
+    let extra = deposit("extra_user");
+    for transaction in [included.clone(), extra.clone()] {
+        chain_interface
+            .add_transaction_to_block(transaction)
+            .await
+            .unwrap();
+    }
+
+    // The leader's block holds only one of our two pending transactions.
+    let block = chain_interface
+        .get_block_for_transactions(None, vec![1, 2, 3, 4], &[included.id()])
+        .unwrap();
+    chain_interface
+        .finalize_and_store_block(block)
+        .await
+        .unwrap();
+
+    assert_eq!(chain_interface.get_pending_transactions(), vec![extra]);
+    assert!(chain_interface.get_account("extra_user").is_none());
+    assert_eq!(
+        chain_interface
+            .get_account("included_user")
+            .unwrap()
+            .balance,
+        1000
+    );
+}
+
 #[tokio::test]
 async fn test_transaction_error_propagation() {
     let (mut chain_interface, _temp_dir) = create_test_chain_interface();
@@ -507,6 +651,8 @@ async fn test_concurrent_operations() {
         deposit_address: "concurrent_address".to_string(),
         timestamp: 1_234_567_890,
         user_pubkey: "concurrent_user".to_string(),
+        expires_at: 0,
+        min_confirmations: None,
     };
 
     // Insert deposit intent
@@ -578,3 +724,329 @@ fn test_empty_state_queries() {
     );
     assert_eq!(chain_interface.get_all_deposit_intents().unwrap().len(), 0);
 }
+
+#[tokio::test]
+async fn test_full_replay_and_stored_state_sync_to_same_balances() {
+    use crate::SyncMode;
+    use crate::db::Db;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let db = RocksDb::new(temp_dir.path().to_str().unwrap());
+    let open = || {
+        let executor = Box::new(TransactionExecutorImpl::new(Box::new(AlwaysValidOracle {})));
+        ChainInterfaceImpl::new(Box::new(db.clone()), executor).0
+    };
+
+    let mut chain_interface = open();
+    let blocks = [
+        vec![
+            Transaction::create_deposit_transaction(
+                &MockOracle::create_dummy_tx_without_address(5_000),
+                "alice",
+                5_000,
+            )
+            .unwrap(),
+            Transaction::create_deposit_transaction(
+                &MockOracle::create_dummy_tx_without_address(3_000),
+                "bob",
+                3_000,
+            )
+            .unwrap(),
+        ],
+        vec![Transaction::create_withdrawal_transaction("alice", "destination", 1_200).unwrap()],
+    ];
+    let mut snapshot = None;
+    for transactions in blocks {
+        for transaction in transactions {
+            chain_interface
+                .add_transaction_to_block(transaction)
+                .await
+                .unwrap();
+        }
+        let block = chain_interface
+            .get_proposed_block(None, vec![1, 2, 3, 4])
+            .unwrap();
+        chain_interface
+            .finalize_and_store_block(block)
+            .await
+            .unwrap();
+        snapshot.get_or_insert_with(|| chain_interface.get_chain_state());
+    }
+    let balances = |chain_interface: &ChainInterfaceImpl| {
+        ["alice", "bob"].map(|address| chain_interface.get_account(address).unwrap().balance)
+    };
+    assert_eq!(balances(&chain_interface), [3_800, 3_000]);
+
+    // Leave the persisted state one block behind so a stored state sync has a block to replay
+    db.flush_state(&snapshot.unwrap()).unwrap();
+    let mut from_snapshot = open();
+    from_snapshot.sync(SyncMode::StoredState).await.unwrap();
+
+    // Replaying finalized blocks does not ask the oracle about their deposits again, so an
+    // oracle that knows none of them does not stop the node from starting
+    let (oracle_tx, _) = tokio::sync::broadcast::channel(1);
+    let mut unaware_oracle = MockOracle::new(oracle_tx, None);
+    unaware_oracle.add_transaction(
+        bitcoin::Txid::from_slice(&[9u8; 32]).unwrap(),
+        "unrelated".to_string(),
+        1,
+        true,
+    );
+    let executor = Box::new(TransactionExecutorImpl::new(Box::new(unaware_oracle)));
+    let mut from_genesis = ChainInterfaceImpl::new(Box::new(db.clone()), executor).0;
+    from_genesis.sync(SyncMode::FullReplay).await.unwrap();
+
+    assert_eq!(balances(&from_snapshot), balances(&chain_interface));
+    assert_eq!(balances(&from_genesis), balances(&chain_interface));
+    assert_eq!(
+        from_genesis.get_chain_state().get_block_height(),
+        from_snapshot.get_chain_state().get_block_height()
+    );
+}
+
+#[tokio::test]
+async fn test_finalized_blocks_link_to_the_tip() {
+    let (mut chain_interface, _temp_dir) = create_test_chain_interface();
+
+    let first = chain_interface
+        .get_proposed_block(None, vec![1, 2, 3, 4])
+        .unwrap();
+    assert_eq!(first.header.previous_block_hash, [0u8; 32]);
+    chain_interface
+        .finalize_and_store_block(first.clone())
+        .await
+        .unwrap();
+
+    let second = chain_interface
+        .get_proposed_block(None, vec![1, 2, 3, 4])
+        .unwrap();
+    assert_eq!(second.header.height, 2);
+    assert_eq!(second.header.previous_block_hash, first.hash());
+    chain_interface
+        .finalize_and_store_block(second.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(chain_interface.get_chain_state().get_block_height(), 2);
+    assert_eq!(
+        chain_interface
+            .get_block_by_height(2)
+            .unwrap()
+            .unwrap()
+            .hash(),
+        second.hash()
+    );
+}
+
+#[tokio::test]
+async fn test_block_not_extending_the_tip_is_rejected() {
+    let (mut chain_interface, _temp_dir) = create_test_chain_interface();
+
+    let tip = chain_interface
+        .get_proposed_block(None, vec![1, 2, 3, 4])
+        .unwrap();
+    chain_interface
+        .finalize_and_store_block(tip.clone())
+        .await
+        .unwrap();
+
+    let wrong_parent = Block::new([7u8; 32], 2, vec![], vec![1, 2, 3, 4]);
+    let skipped_height = Block::new(tip.hash(), 3, vec![], vec![1, 2, 3, 4]);
+    let replayed = tip.clone();
+
+    for block in [wrong_parent, skipped_height, replayed] {
+        let error = chain_interface
+            .finalize_and_store_block(block.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, NodeError::BlockDoesNotExtendTip { .. }),
+            "unexpected error for block {}: {error}",
+            block.header.height
+        );
+    }
+
+    assert_eq!(chain_interface.get_chain_state().get_block_height(), 1);
+    assert!(chain_interface.get_block_by_height(2).unwrap().is_none());
+    assert!(chain_interface.get_block_by_height(3).unwrap().is_none());
+}
+
+/// Stores through `RocksDb` but fails every block commit once the block is staged, as a crash
+/// between writing the block and writing the state it produced would.
+struct FailingCommitDb(RocksDb);
+
+impl Db for FailingCommitDb {
+    fn get_block_by_height(&self, height: u64) -> Result<Option<Block>, NodeError> {
+        self.0.get_block_by_height(height)
+    }
+    fn get_block_by_hash(&self, hash: BlockHash) -> Result<Option<Block>, NodeError> {
+        self.0.get_block_by_hash(hash)
+    }
+    fn get_tip_block_hash(&self) -> Result<Option<BlockHash>, NodeError> {
+        self.0.get_tip_block_hash()
+    }
+    fn get_chain_state(&self) -> Result<Option<ChainState>, NodeError> {
+        self.0.get_chain_state()
+    }
+    fn insert_chain_state(&self, chain_state: ChainState) -> Result<(), NodeError> {
+        self.0.insert_chain_state(chain_state)
+    }
+    fn insert_block(&self, block: Block) -> Result<(), NodeError> {
+        self.0.insert_block(block)
+    }
+    fn get_block_timestamp(&self, height: u64) -> Result<Option<u64>, NodeError> {
+        self.0.get_block_timestamp(height)
+    }
+    fn insert_genesis(&self, genesis: &GenesisBlock) -> Result<(), NodeError> {
+        self.0.insert_genesis(genesis)
+    }
+    fn get_genesis(&self) -> Result<Option<GenesisBlock>, NodeError> {
+        self.0.get_genesis()
+    }
+    fn insert_deposit_intent(&self, intent: DepositIntent) -> Result<(), NodeError> {
+        self.0.insert_deposit_intent(intent)
+    }
+    fn get_deposit_intent(&self, tracking_id: &str) -> Result<Option<DepositIntent>, NodeError> {
+        self.0.get_deposit_intent(tracking_id)
+    }
+    fn get_all_deposit_intents(&self) -> Result<Vec<DepositIntent>, NodeError> {
+        self.0.get_all_deposit_intents()
+    }
+    fn remove_deposit_intent(&self, intent: DepositIntent) -> Result<(), NodeError> {
+        self.0.remove_deposit_intent(intent)
+    }
+    fn get_deposit_intent_by_address(
+        &self,
+        address: &str,
+    ) -> Result<Option<DepositIntent>, NodeError> {
+        self.0.get_deposit_intent_by_address(address)
+    }
+    fn flush_state(&self, chain_state: &ChainState) -> Result<(), NodeError> {
+        self.0.flush_state(chain_state)
+    }
+    fn commit_block(&self, block: Block, _chain_state: &ChainState) -> Result<(), NodeError> {
+        let mut batch = rocksdb::WriteBatch::default();
+        self.0.stage_block(&mut batch, &block)?;
+        Err(NodeError::Error(
+            "Injected failure before the chain state was staged".to_string(),
+        ))
+    }
+    fn store_utxos(&self, utxos: Vec<Utxo>) -> Result<(), NodeError> {
+        self.0.store_utxos(utxos)
+    }
+    fn get_utxos(&self) -> Result<Vec<Utxo>, NodeError> {
+        self.0.get_utxos()
+    }
+    fn insert_consumed_challenge(&self, challenge: &str) -> Result<(), NodeError> {
+        self.0.insert_consumed_challenge(challenge)
+    }
+    fn is_challenge_consumed(&self, challenge: &str) -> Result<bool, NodeError> {
+        self.0.is_challenge_consumed(challenge)
+    }
+    fn append_audit_entry(&self, entry: AuditEntry) -> Result<u64, NodeError> {
+        self.0.append_audit_entry(entry)
+    }
+    fn get_audit_log(&self, from: u64, to: u64) -> Result<Vec<AuditEntry>, NodeError> {
+        self.0.get_audit_log(from, to)
+    }
+    fn upsert_withdrawal_record(&self, record: &WithdrawalRecord) -> Result<(), NodeError> {
+        self.0.upsert_withdrawal_record(record)
+    }
+    fn get_withdrawal_records(&self) -> Result<Vec<WithdrawalRecord>, NodeError> {
+        self.0.get_withdrawal_records()
+    }
+    fn allocate_sign_id_counter(&self) -> Result<u64, NodeError> {
+        self.0.allocate_sign_id_counter()
+    }
+    fn insert_processed_deposit(&self, txid: &str, bitcoin_height: u32) -> Result<(), NodeError> {
+        self.0.insert_processed_deposit(txid, bitcoin_height)
+    }
+    fn get_processed_deposits(&self) -> Result<Vec<(String, u32)>, NodeError> {
+        self.0.get_processed_deposits()
+    }
+    fn prune_processed_deposits(&self, bitcoin_height: u32) -> Result<usize, NodeError> {
+        self.0.prune_processed_deposits(bitcoin_height)
+    }
+    fn insert_awaiting_deposit(
+        &self,
+        txid: &str,
+        credit_at: u32,
+        raw_tx: &[u8],
+    ) -> Result<(), NodeError> {
+        self.0.insert_awaiting_deposit(txid, credit_at, raw_tx)
+    }
+    fn get_awaiting_deposits(&self) -> Result<Vec<(String, u32, Vec<u8>)>, NodeError> {
+        self.0.get_awaiting_deposits()
+    }
+    fn remove_awaiting_deposit(&self, txid: &str) -> Result<(), NodeError> {
+        self.0.remove_awaiting_deposit(txid)
+    }
+    fn insert_watch_address(&self, address: &str) -> Result<(), NodeError> {
+        self.0.insert_watch_address(address)
+    }
+    fn get_watch_addresses(&self) -> Result<Vec<String>, NodeError> {
+        self.0.get_watch_addresses()
+    }
+}
+
+#[tokio::test]
+async fn test_failed_block_commit_persists_nothing_and_keeps_the_chain_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = RocksDb::new(temp_dir.path().to_str().unwrap());
+    let executor = Box::new(TransactionExecutorImpl::new(Box::new(AlwaysValidOracle {})));
+    let (mut chain_interface, _) =
+        ChainInterfaceImpl::new(Box::new(FailingCommitDb(db.clone())), executor);
+
+    let address = "commit_failure_user";
+    let amount = 1000u64;
+    let deposit = Transaction::new(
+        TransactionType::Deposit,
+        vec![
+            Operation::OpPush {
+                value: amount.to_be_bytes().to_vec(),
+            },
+            Operation::OpPush {
+                value: address.as_bytes().to_vec(),
+            },
+            Operation::OpPush {
+                value: bitcoin::Txid::all_zeros().to_byte_array().to_vec(),
+            },
+            Operation::OpCheckOracle,
+            Operation::OpPush {
+                value: amount.to_be_bytes().to_vec(),
+            },
+            Operation::OpPush {
+                value: address.as_bytes().to_vec(),
+            },
+            Operation::OpIncrementBalance,
+        ],
+        None,
+    );
+    chain_interface
+        .add_transaction_to_block(deposit.clone())
+        .await
+        .unwrap();
+
+    let block = chain_interface
+        .get_proposed_block(None, vec![1, 2, 3, 4])
+        .unwrap();
+    assert!(
+        chain_interface
+            .finalize_and_store_block(block)
+            .await
+            .is_err()
+    );
+
+    // Neither the block nor the state it produced reached the disk.
+    assert!(db.get_block_by_height(1).unwrap().is_none());
+    assert!(db.get_tip_block_hash().unwrap().is_none());
+    let stored = db.get_chain_state().unwrap().unwrap_or_default();
+    assert_eq!(stored.get_block_height(), 0);
+    assert!(stored.get_account(address).is_none());
+
+    // The in-memory state is the one from before the block, deposit still pending.
+    let state = chain_interface.get_chain_state();
+    assert_eq!(state.get_block_height(), 0);
+    assert!(state.get_account(address).is_none());
+    assert_eq!(chain_interface.get_pending_transactions(), vec![deposit]);
+}
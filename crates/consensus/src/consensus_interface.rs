@@ -1,14 +1,22 @@
-use crate::{ConsensusMessage, ConsensusPhase, ConsensusResponse, ConsensusState};
+use crate::round_buffer::MAX_ROUND_LOOKAHEAD;
+use crate::{
+    BufferedProposal, ConsensusMessage, ConsensusPhase, ConsensusResponse, ConsensusState,
+};
 use libp2p::PeerId;
-use protocol::block::Block;
+use libp2p::identity::Keypair;
+use protocol::block::{Block, ConsensusQuorum};
+use protocol::transaction::TransactionId;
 use sha2::{Digest, Sha256};
 use tracing::{debug, error, info, warn};
 use types::broadcast::BroadcastMessage;
+use types::clock::{SharedClock, SystemClock};
 use types::consensus::{
-    ConsensusMessage as ConsensusNetMessage, LeaderAnnouncement, Vote, VoteType,
+    ConsensusMessage as ConsensusNetMessage, LeaderAnnouncement, NIL_BLOCK_HASH, Vote,
+    VoteAggregate, VoteType, unix_timestamp,
 };
-use types::current_round_metrics;
 use types::errors::NodeError;
+use types::network::network_event::{DirectMessage, NetworkEvent};
+use types::{current_round_metrics, rejected_proposal_metrics};
 
 #[async_trait::async_trait]
 pub trait ConsensusInterface: Send + Sync {
@@ -22,7 +30,11 @@ pub struct ConsensusInterfaceImpl {
         Option<tokio::sync::broadcast::Sender<types::network::network_event::NetworkEvent>>,
     pub chain_interface_tx: Option<messenger::Sender<abci::ChainMessage, abci::ChainResponse>>,
     pub peer_id: Option<PeerId>,
+    /// Identity key votes are signed with when they are aggregated by the leader.
+    pub keypair: Option<Keypair>,
     pub max_validators: Option<usize>, // Expected number of validators
+    /// Time source for round and proposal timeouts.
+    pub clock: SharedClock,
 }
 
 impl ConsensusInterfaceImpl {
@@ -36,7 +48,9 @@ impl ConsensusInterfaceImpl {
                 network_events_tx: None,
                 chain_interface_tx: None,
                 peer_id: None,
+                keypair: None,
                 max_validators: None,
+                clock: SystemClock::shared(),
             },
             tx,
         )
@@ -60,10 +74,26 @@ impl ConsensusInterfaceImpl {
         self.peer_id = Some(peer_id);
     }
 
+    pub fn set_keypair(&mut self, keypair: Keypair) {
+        self.keypair = Some(keypair);
+    }
+
+    pub const fn set_vote_aggregation(&mut self, enabled: bool) {
+        self.state.vote_aggregation = enabled;
+    }
+
     pub const fn set_max_validators(&mut self, max_validators: usize) {
         self.max_validators = Some(max_validators);
     }
 
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
+    pub const fn set_consensus_quorum(&mut self, quorum: ConsensusQuorum) {
+        self.state.consensus_quorum = quorum;
+    }
+
     pub async fn initialize_from_chain_state(&mut self) -> Result<(), NodeError> {
         if let Some(chain_tx) = &mut self.chain_interface_tx {
             match chain_tx
@@ -74,7 +104,7 @@ impl ConsensusInterfaceImpl {
                     height,
                     pending_transactions: _,
                 }) => {
-                    self.state.current_height = height;
+                    self.state.set_height(height);
 
                     // Set round to 0 for the current height (fresh start for this height)
                     self.state.current_round = 0;
@@ -109,6 +139,19 @@ impl ConsensusInterfaceImpl {
         Ok(())
     }
 
+    fn send_private_message(
+        &self,
+        peer_id: PeerId,
+        message: DirectMessage,
+    ) -> Result<(), NodeError> {
+        if let Some(sender) = &self.network_events_tx {
+            sender
+                .send(NetworkEvent::SendPrivateMessage { peer_id, message })
+                .map_err(|e| NodeError::Error(format!("Failed to send network event: {e}")))?;
+        }
+        Ok(())
+    }
+
     async fn get_proposed_block(&mut self, proposer: Vec<u8>) -> Result<Block, NodeError> {
         if let Some(chain_tx) = &mut self.chain_interface_tx {
             match chain_tx
@@ -131,13 +174,79 @@ impl ConsensusInterfaceImpl {
         }
     }
 
-    async fn finalize_block(&mut self, block: Block) -> Result<(), NodeError> {
+    async fn get_block_for_transactions(
+        &mut self,
+        proposer: Vec<u8>,
+        tx_ids: Vec<TransactionId>,
+    ) -> Result<Block, NodeError> {
+        if let Some(chain_tx) = &mut self.chain_interface_tx {
+            match chain_tx
+                .send_message_with_response(abci::ChainMessage::GetBlockForTransactions {
+                    previous_block: None,
+                    proposer,
+                    tx_ids,
+                })
+                .await
+            {
+                Ok(abci::ChainResponse::GetBlockForTransactions { block }) => block,
+                Ok(_) => Err(NodeError::Error(
+                    "Unexpected response from chain interface".to_string(),
+                )),
+                Err(e) => Err(e),
+            }
+        } else {
+            Err(NodeError::Error(
+                "Chain interface not available".to_string(),
+            ))
+        }
+    }
+
+    async fn verify_block_deposits(&mut self, block: &Block) -> Result<(), NodeError> {
+        if let Some(chain_tx) = &mut self.chain_interface_tx {
+            match chain_tx
+                .send_message_with_response(abci::ChainMessage::VerifyBlockDeposits {
+                    block: block.clone(),
+                })
+                .await
+            {
+                Ok(abci::ChainResponse::VerifyBlockDeposits { error: None }) => Ok(()),
+                Ok(abci::ChainResponse::VerifyBlockDeposits { error: Some(e) }) => Err(e),
+                Ok(_) => Err(NodeError::Error(
+                    "Unexpected response from chain interface".to_string(),
+                )),
+                Err(e) => Err(e),
+            }
+        } else {
+            Err(NodeError::Error(
+                "Chain interface not available".to_string(),
+            ))
+        }
+    }
+
+    pub async fn finalize_block(&mut self, block: Block) -> Result<(), NodeError> {
+        let height = block.header.height;
+        let block_hash = block.hash();
+
+        if self.state.check_finalization(height, block_hash)? {
+            debug!("⏭️  Block at height {} already finalized, skipping", height);
+            return Ok(());
+        }
+
         if let Some(chain_tx) = &mut self.chain_interface_tx {
             match chain_tx
                 .send_message_with_response(abci::ChainMessage::FinalizeBlock { block })
                 .await
             {
-                Ok(abci::ChainResponse::FinalizeAndStoreBlock { error: None }) => Ok(()),
+                Ok(abci::ChainResponse::FinalizeAndStoreBlock { error: None }) => {
+                    self.state.record_finalized(height, block_hash);
+                    if let Some(sender) = &self.network_events_tx {
+                        // Nobody listening is fine; the event only invalidates caches.
+                        let _ = sender.send(
+                            types::network::network_event::NetworkEvent::BlockFinalized { height },
+                        );
+                    }
+                    Ok(())
+                }
                 Ok(abci::ChainResponse::FinalizeAndStoreBlock { error: Some(e) }) => {
                     Err(NodeError::Error(format!("Failed to finalize block: {e}")))
                 }
@@ -154,6 +263,17 @@ impl ConsensusInterfaceImpl {
     }
 
     pub fn start_new_round(&mut self) -> Result<(), NodeError> {
+        // A fresh node has no leader to rotate to until validators are added.
+        if self.state.validators.is_empty() {
+            debug!(
+                "No validators known, deferring round {}",
+                self.state.current_round + 1
+            );
+            self.state.proposer = None;
+            self.state.is_leader = false;
+            return Ok(());
+        }
+
         self.state.current_round += 1;
 
         if let Some(peer_id) = self.peer_id {
@@ -180,12 +300,16 @@ impl ConsensusInterfaceImpl {
         }
 
         self.state.current_state = ConsensusPhase::WaitingForPropose;
-        self.state.round_start_time = Some(tokio::time::Instant::now());
+        self.state.round_start_time = Some(self.clock.now());
 
         self.state.prevotes.clear();
+        self.state.nil_prevotes.clear();
         self.state.precommits.clear();
         self.state.current_block_hash = None;
+        self.state.proposed_block = None;
         self.state.block_finalized = false;
+        self.state.collected_votes.clear();
+        self.state.published_aggregates.clear();
 
         debug!(
             "🔄 Cleared vote counts for new round {}. Validator set: {}",
@@ -196,7 +320,50 @@ impl ConsensusInterfaceImpl {
         Ok(())
     }
 
+    /// Starts the next round and handles any proposal and votes that arrived for it early.
+    pub async fn advance_round(&mut self) -> Result<(), NodeError> {
+        self.start_new_round()?;
+        self.replay_future_messages().await;
+        Ok(())
+    }
+
+    async fn replay_future_messages(&mut self) {
+        let (height, round) = (self.state.current_height, self.state.current_round);
+
+        let proposals = self.state.future_proposals.take_round(height, round);
+        for (sender, proposal) in proposals {
+            debug!("⏪ Replaying buffered block proposal from {sender} for round {round}");
+            if let Err(e) = self
+                .handle_block_proposal(sender, proposal.raw_block, proposal.tx_hashes)
+                .await
+            {
+                warn!("Failed to handle buffered block proposal from {sender}: {e}");
+            }
+        }
+
+        let votes = self.state.future_votes.take_round(height, round);
+        if !votes.is_empty() {
+            debug!(
+                "⏪ Replaying {} buffered votes for round {}",
+                votes.len(),
+                self.state.current_round
+            );
+        }
+        for (sender, vote) in votes {
+            self.handle_vote(sender, &vote).await;
+        }
+    }
+
     pub async fn propose_block_as_leader(&mut self) -> Result<(), NodeError> {
+        if self.state.validators.is_empty() {
+            debug!("No validators known, skipping block proposal");
+            return Ok(());
+        }
+        if self.state.quorum_lost {
+            debug!("Quorum lost, skipping block proposal");
+            return Ok(());
+        }
+
         debug!(
             "Proposing block as leader for round {}",
             self.state.current_round
@@ -217,6 +384,13 @@ impl ConsensusInterfaceImpl {
                 .map(libp2p::PeerId::to_bytes)
                 .unwrap_or_default(),
             raw_block,
+            tx_hashes: block
+                .body
+                .transactions
+                .iter()
+                .map(|tx| tx.id().to_vec())
+                .collect(),
+            timestamp: unix_timestamp(),
         };
 
         self.send_broadcast(BroadcastMessage::Consensus(proposal_message))?;
@@ -229,80 +403,437 @@ impl ConsensusInterfaceImpl {
 
         // Update our state to reflect that we've proposed
         self.state.current_state = ConsensusPhase::Prevote;
+        self.state.proposed_block = Some(block.clone());
+
+        // The leader agrees with its own proposal; gossip never echoes it back to us.
+        self.cast_vote(vote_hash(&block)?, VoteType::Prevote)
+            .await?;
 
         Ok(())
     }
 
+    /// Prevotes the round leader's proposal if it matches the block we would build. Returns
+    /// whether the proposal was valid and prevoted; one that is buffered for a later round
+    /// or ignored is not.
     async fn handle_block_proposal(
         &mut self,
         sender: PeerId,
         raw_block: Vec<u8>,
-    ) -> Result<(), NodeError> {
-        match Block::deserialize(&raw_block) {
-            Ok(block) => {
-                info!(
-                    "📥 Received block proposal for round {} from {} with {} txs",
-                    self.state.current_round,
-                    sender,
-                    block.body.transactions.len()
-                );
+        tx_hashes: Option<Vec<Vec<u8>>>,
+    ) -> Result<bool, NodeError> {
+        if self.state.validators.is_empty() {
+            debug!("No validators known, ignoring block proposal from {sender}");
+            return Ok(false);
+        }
 
-                let proposer_bytes = self
-                    .state
-                    .proposer
-                    .map(libp2p::PeerId::to_bytes)
-                    .unwrap_or_default();
-                let local_block = self.get_proposed_block(proposer_bytes).await?;
+        let leader = self.state.select_leader(self.state.current_round);
+        if leader != Some(sender) {
+            if self.buffer_future_proposal(sender, &raw_block, tx_hashes) {
+                return Ok(false);
+            }
+            warn!(
+                "🚫 Rejecting block proposal for round {} from {sender}, which is not the leader {}",
+                self.state.current_round,
+                leader.map(|l| l.to_string()).unwrap_or_default()
+            );
+            rejected_proposal_metrics!("not_leader");
+            return Ok(false);
+        }
 
-                if local_block == block {
-                    info!("Block is valid. Sending prevote.");
-                    self.send_vote(&block, &VoteType::Prevote)?;
-                } else {
-                    info!("Block is invalid. Not voting - transaction mismatch");
-                    info!(
-                        "Local txs: {:?}, Received txs: {:?}",
-                        local_block.body.transactions, block.body.transactions
+        if self.state.current_state == ConsensusPhase::Prevote {
+            debug!(
+                "Ignoring block proposal from {sender}: already prevoted in round {}",
+                self.state.current_round
+            );
+            return Ok(false);
+        }
+
+        let block = match Block::deserialize(&raw_block) {
+            Ok(block) => block,
+            Err(e) => {
+                warn!("Failed to deserialize block: {e}");
+                return Ok(false);
+            }
+        };
+        info!(
+            "📥 Received block proposal for round {} from {} with {} txs",
+            self.state.current_round,
+            sender,
+            block.body.transactions.len()
+        );
+
+        let proposer_bytes = self
+            .state
+            .proposer
+            .map(libp2p::PeerId::to_bytes)
+            .unwrap_or_default();
+        // A proposal that names its transactions is rebuilt from exactly those, so
+        // transactions only we have pending do not make us reject it.
+        let local_block = match tx_hashes {
+            Some(tx_hashes) => {
+                let Some(tx_ids) = committed_tx_ids(&block, &tx_hashes) else {
+                    warn!(
+                        "🚫 Rejecting block proposal from {sender}: its transactions do not match the ids it commits to"
                     );
+                    return Ok(false);
+                };
+                match self
+                    .get_block_for_transactions(proposer_bytes, tx_ids)
+                    .await
+                {
+                    Ok(local_block) => local_block,
+                    Err(e) => {
+                        info!("Cannot rebuild proposed block, not voting: {e}");
+                        return Ok(false);
+                    }
                 }
             }
-            Err(e) => warn!("Failed to deserialize block: {e}"),
+            None => self.get_proposed_block(proposer_bytes).await?,
+        };
+
+        if local_block != block {
+            info!("Block is invalid. Not voting - transaction mismatch");
+            info!(
+                "Local txs: {:?}, Received txs: {:?}",
+                local_block.body.transactions, block.body.transactions
+            );
+            return Ok(false);
         }
-        Ok(())
+
+        // Deposits were confirmed when the leader admitted them; check them again so a block
+        // crediting deposits our oracle cannot see gets no vote from us.
+        if let Err(e) = self.verify_block_deposits(&block).await {
+            warn!(
+                "🚫 Block proposal from {sender} references deposits that cannot be confirmed, prevoting nil: {e}"
+            );
+            self.state.current_state = ConsensusPhase::Prevote;
+            self.cast_vote(NIL_BLOCK_HASH.to_vec(), VoteType::Prevote)
+                .await?;
+            return Ok(false);
+        }
+        info!("Block is valid. Sending prevote.");
+        self.state.current_state = ConsensusPhase::Prevote;
+        self.state.proposed_block = Some(block.clone());
+        self.cast_vote(vote_hash(&block)?, VoteType::Prevote)
+            .await?;
+        Ok(true)
+    }
+
+    /// Holds a proposal from the leader of one of the next few rounds, for a peer that entered
+    /// that round before this node did. Returns `false` if `sender` leads none of them, the
+    /// block is not for the next height or the buffer is full.
+    fn buffer_future_proposal(
+        &mut self,
+        sender: PeerId,
+        raw_block: &[u8],
+        tx_hashes: Option<Vec<Vec<u8>>>,
+    ) -> bool {
+        let current_round = self.state.current_round;
+        let Some(round) = (1..=MAX_ROUND_LOOKAHEAD)
+            .map(|ahead| current_round.saturating_add(ahead))
+            .find(|round| self.state.select_leader(*round) == Some(sender))
+        else {
+            return false;
+        };
+
+        let height = self.state.current_height;
+        match Block::deserialize(raw_block) {
+            Ok(block) if block.header.height == height + 1 => {}
+            _ => return false,
+        }
+
+        let proposal = BufferedProposal {
+            raw_block: raw_block.to_vec(),
+            tx_hashes,
+        };
+        let buffered =
+            self.state
+                .future_proposals
+                .push(current_round, height, round, sender, proposal);
+        if buffered {
+            debug!("⏳ Buffered block proposal from {sender} for future round {round}");
+        }
+        buffered
+    }
+
+    /// Prevotes nil once `propose_timeout` has passed in the round without a valid proposal,
+    /// rather than waiting out the full `round_timeout` on a silent leader.
+    pub async fn check_propose_timeout(&mut self) -> Result<(), NodeError> {
+        if self.state.quorum_lost
+            || self.state.current_state != ConsensusPhase::WaitingForPropose
+            || self
+                .round_elapsed()
+                .is_none_or(|elapsed| elapsed < self.state.propose_timeout)
+        {
+            return Ok(());
+        }
+
+        warn!(
+            "⌛ No valid proposal for round {} within {:?}, prevoting nil",
+            self.state.current_round, self.state.propose_timeout
+        );
+        self.state.current_state = ConsensusPhase::Prevote;
+        self.cast_vote(NIL_BLOCK_HASH.to_vec(), VoteType::Prevote)
+            .await
+    }
+
+    /// Records whether `peer` is reachable and pauses or resumes consensus if that moved the
+    /// reachable validators across the quorum.
+    async fn set_peer_connected(&mut self, peer: PeerId, connected: bool) -> Result<(), NodeError> {
+        if connected {
+            self.state.disconnected_peers.remove(&peer);
+        } else {
+            self.state.disconnected_peers.insert(peer);
+        }
+        self.update_quorum_presence().await
+    }
+
+    /// Pauses consensus when too few validators are reachable to reach a quorum, instead of
+    /// spinning through rounds nobody can finish, and starts a fresh round once enough of
+    /// them are back.
+    async fn update_quorum_presence(&mut self) -> Result<(), NodeError> {
+        let present = self.state.has_quorum_presence();
+        if present != self.state.quorum_lost {
+            return Ok(());
+        }
+        self.state.quorum_lost = !present;
+
+        if present {
+            info!(
+                "✅ Quorum regained with {}/{} validators reachable, resuming consensus",
+                self.state.reachable_validators(),
+                self.state.validators.len()
+            );
+            self.trigger_new_round().await
+        } else {
+            warn!(
+                "⏸️  Only {}/{} validators reachable, below the quorum of {}; pausing consensus in round {}",
+                self.state.reachable_validators(),
+                self.state.validators.len(),
+                self.state.quorum(),
+                self.state.current_round
+            );
+            Ok(())
+        }
+    }
+
+    /// Time since the current round started, or `None` before the first round.
+    pub(crate) fn round_elapsed(&self) -> Option<std::time::Duration> {
+        self.state
+            .round_start_time
+            .map(|start| self.clock.now().saturating_duration_since(start))
     }
 
-    fn send_vote(&self, block: &Block, vote_type: &VoteType) -> Result<(), NodeError> {
-        let block_bytes = block.serialize()?;
-        let mut hasher = Sha256::new();
-        hasher.update(&block_bytes);
-        let block_hash = hasher.finalize().to_vec();
+    /// Validators only learn of votes through gossip, which never delivers our own messages
+    /// back to us, so the local node's vote is counted here when it is in the validator set.
+    fn local_validator(&self) -> Option<PeerId> {
+        self.peer_id
+            .filter(|peer_id| self.state.validators.contains(peer_id))
+    }
 
+    fn broadcast_vote(
+        &mut self,
+        block_hash: Vec<u8>,
+        vote_type: VoteType,
+    ) -> Result<Vote, NodeError> {
         let vote = Vote {
             round: self.state.current_round,
             height: self.state.current_height,
-            block_hash: block_hash.clone(),
+            block_hash,
             voter: self
                 .peer_id
                 .map(libp2p::PeerId::to_bytes)
                 .unwrap_or_default(),
-            vote_type: vote_type.clone(),
+            vote_type,
+            timestamp: unix_timestamp(),
         };
 
-        let vote_message = ConsensusNetMessage::Vote(vote);
-        self.send_broadcast(BroadcastMessage::Consensus(vote_message))?;
+        match self.aggregator_for(&vote) {
+            Some((aggregator, keypair)) => {
+                let signature = vote.sign(&keypair).map_err(NodeError::Error)?;
+                if Some(aggregator) == self.peer_id {
+                    self.collect_signed_vote(&vote, signature)?;
+                } else {
+                    self.send_private_message(
+                        aggregator,
+                        DirectMessage::ConsensusVote {
+                            vote: vote.clone(),
+                            signature,
+                        },
+                    )?;
+                }
+            }
+            None => self.send_broadcast(BroadcastMessage::Consensus(ConsensusNetMessage::Vote(
+                vote.clone(),
+            )))?,
+        }
 
         debug!(
             "🗳️  Sending {:?} vote for block hash {} in round {} from {} | validators: {}",
-            vote_type,
-            hex::encode(&block_hash[..8]),
+            vote.vote_type,
+            hex::encode(&vote.block_hash[..8]),
             self.state.current_round,
             self.peer_id.map(|p| p.to_string()).unwrap_or_default(),
             self.state.validators.len()
         );
 
+        Ok(vote)
+    }
+
+    /// The leader to send `vote` to, and the key to sign it with, when votes are aggregated.
+    ///
+    /// Nil votes are always gossiped: they are cast when the leader has gone quiet, so it is
+    /// the one node that cannot be relied on to pass them on.
+    fn aggregator_for(&self, vote: &Vote) -> Option<(PeerId, Keypair)> {
+        if !self.state.vote_aggregation || vote.is_nil() {
+            return None;
+        }
+        Some((self.state.proposer?, self.keypair.clone()?))
+    }
+
+    /// Adds a signed vote to the aggregate for its block and gossips the aggregate once it
+    /// holds a quorum. Votes arriving after that are counted locally but not gossiped again.
+    fn collect_signed_vote(&mut self, vote: &Vote, signature: Vec<u8>) -> Result<(), NodeError> {
+        let key = (vote.vote_type, vote.block_hash.clone());
+        if self.state.published_aggregates.contains(&key) {
+            return Ok(());
+        }
+
+        let quorum = self.state.quorum();
+        let collected = self.state.collected_votes.entry(key.clone()).or_default();
+        collected.insert(vote.voter.clone(), (vote.timestamp, signature));
+        if collected.len() < quorum {
+            return Ok(());
+        }
+
+        let signers = collected.keys().cloned().collect();
+        let (timestamps, signatures) = collected.values().cloned().unzip();
+        let aggregate = VoteAggregate {
+            round: vote.round,
+            height: vote.height,
+            block_hash: vote.block_hash.clone(),
+            vote_type: vote.vote_type,
+            signers,
+            signatures,
+            timestamps,
+        };
+        self.state.collected_votes.remove(&key);
+        self.state.published_aggregates.insert(key);
+
+        info!(
+            "📦 Gossiping aggregate of {} {:?} votes for round {}",
+            aggregate.signers.len(),
+            aggregate.vote_type,
+            aggregate.round
+        );
+        self.send_broadcast(BroadcastMessage::Consensus(
+            ConsensusNetMessage::VoteAggregate(aggregate),
+        ))
+    }
+
+    /// Counts a vote a validator sent us as the round's leader and adds it to the aggregate.
+    /// Returns whether the vote was taken, as [`Self::handle_vote`] does.
+    async fn handle_signed_vote(
+        &mut self,
+        sender: PeerId,
+        vote: Vote,
+        signature: Vec<u8>,
+    ) -> Result<bool, NodeError> {
+        if vote.voter != sender.to_bytes() {
+            return Err(NodeError::Error(format!(
+                "Signed vote from {sender} carries another validator's vote"
+            )));
+        }
+        if !vote.verify_signature(&signature) {
+            return Err(NodeError::Error(format!(
+                "Invalid vote signature from {sender}"
+            )));
+        }
+        if !self.state.validators.contains(&sender) {
+            return Err(NodeError::Error(format!(
+                "Signed vote from {sender}, which is not a validator"
+            )));
+        }
+
+        // Collect before counting: a precommit quorum finalizes the block and moves the
+        // height on, and the aggregate must still go out for the others to finalize too.
+        let aggregating = self.state.vote_aggregation
+            && self.peer_id.is_some()
+            && self.state.proposer == self.peer_id
+            && vote.round == self.state.current_round
+            && vote.height == self.state.current_height;
+        if aggregating && !vote.is_nil() {
+            self.collect_signed_vote(&vote, signature)?;
+        }
+        Ok(self.handle_vote(sender, &vote).await)
+    }
+
+    /// Counts every vote in an aggregate, after checking each signer's signature. A single
+    /// bad signature rejects the whole aggregate. Returns the voters whose votes were taken.
+    async fn handle_vote_aggregate(
+        &mut self,
+        aggregate: VoteAggregate,
+    ) -> Result<Vec<PeerId>, NodeError> {
+        if !aggregate.verify() {
+            return Err(NodeError::Error(format!(
+                "Rejecting {:?} vote aggregate for round {} with invalid signatures",
+                aggregate.vote_type, aggregate.round
+            )));
+        }
+
+        debug!(
+            "📨 Received aggregate of {} {:?} votes for round {}",
+            aggregate.signers.len(),
+            aggregate.vote_type,
+            aggregate.round
+        );
+        let mut accepted = Vec::new();
+        for (vote, _) in aggregate.votes() {
+            match PeerId::from_bytes(&vote.voter) {
+                Ok(voter) => {
+                    if self.handle_vote(voter, &vote).await {
+                        accepted.push(voter);
+                    }
+                }
+                Err(e) => warn!("Skipping aggregated vote with undecodable voter: {e}"),
+            }
+        }
+        Ok(accepted)
+    }
+
+    /// Broadcasts our vote and counts it toward the threshold like any other validator's.
+    async fn cast_vote(
+        &mut self,
+        block_hash: Vec<u8>,
+        vote_type: VoteType,
+    ) -> Result<(), NodeError> {
+        if self.state.quorum_lost {
+            debug!("Quorum lost, not casting {vote_type:?} vote");
+            return Ok(());
+        }
+        let vote = self.broadcast_vote(block_hash, vote_type)?;
+        if let Some(local) = self.local_validator() {
+            match vote.vote_type {
+                VoteType::Prevote => self.process_prevote_vote(local, &vote).await,
+                VoteType::Precommit => self.process_precommit_vote(local, &vote).await,
+            }
+        }
         Ok(())
     }
 
-    fn process_prevote_vote(&mut self, sender: PeerId, vote: &Vote) {
+    async fn process_prevote_vote(&mut self, sender: PeerId, vote: &Vote) {
+        if vote.is_nil() {
+            if self.state.nil_prevotes.insert(sender) {
+                debug!(
+                    "Added nil prevote from {}. Total: {}/{}",
+                    sender,
+                    self.state.nil_prevotes.len(),
+                    self.state.validators.len()
+                );
+            }
+            return;
+        }
+
         if self.state.prevotes.insert(sender) {
             debug!(
                 "✅ Added prevote from {} for block hash {}. Total: {}/{} | Need: {}",
@@ -310,35 +841,34 @@ impl ConsensusInterfaceImpl {
                 hex::encode(&vote.block_hash[..8]),
                 self.state.prevotes.len(),
                 self.state.validators.len(),
-                (self.state.validators.len() * 2) / 3 + 1
+                self.state.quorum()
             );
 
-            if self.state.prevotes.len() >= (self.state.validators.len() * 2) / 3 {
+            if self.state.prevotes.len() >= self.state.quorum() {
                 info!(
-                    "🎯 Got 2/3+ prevotes ({}/{}). Sending precommit vote.",
+                    "🎯 Got quorum of prevotes ({}/{}). Sending precommit vote.",
                     self.state.prevotes.len(),
                     self.state.validators.len()
                 );
 
-                let vote = Vote {
-                    round: self.state.current_round,
-                    height: self.state.current_height,
-                    block_hash: vote.block_hash.clone(),
-                    voter: self
-                        .peer_id
-                        .map(libp2p::PeerId::to_bytes)
-                        .unwrap_or_default(),
-                    vote_type: VoteType::Precommit,
-                };
-
-                let vote_message = ConsensusNetMessage::Vote(vote);
-                self.send_broadcast(BroadcastMessage::Consensus(vote_message))
-                    .ok();
+                match self.broadcast_vote(vote.block_hash.clone(), VoteType::Precommit) {
+                    Ok(precommit) => {
+                        if let Some(local) = self.local_validator() {
+                            self.process_precommit_vote(local, &precommit).await;
+                        }
+                    }
+                    Err(e) => warn!("Failed to broadcast precommit: {e}"),
+                }
             }
         }
     }
 
     async fn process_precommit_vote(&mut self, sender: PeerId, vote: &Vote) {
+        if vote.is_nil() {
+            debug!("Ignoring nil precommit from {sender}");
+            return;
+        }
+
         if self.state.precommits.insert(sender) {
             debug!(
                 "✅ Added precommit from {} for block hash {}. Total: {}/{} | Need: {}",
@@ -346,28 +876,34 @@ impl ConsensusInterfaceImpl {
                 hex::encode(&vote.block_hash[..8]),
                 self.state.precommits.len(),
                 self.state.validators.len(),
-                (self.state.validators.len() * 2) / 3 + 1
+                self.state.quorum()
             );
 
-            if self.state.precommits.len() >= (self.state.validators.len() * 2) / 3 {
+            if self.state.precommits.len() >= self.state.quorum() {
                 if self.state.block_finalized {
                     debug!(
                         "⏭️  Block already finalized for this round, skipping duplicate finalization"
                     );
                 } else {
                     info!(
-                        "🎉 Got 2/3+ precommits ({}/{}). Finalizing block...",
+                        "🎉 Got quorum of precommits ({}/{}). Finalizing block...",
                         self.state.precommits.len(),
                         self.state.validators.len()
                     );
 
                     self.state.block_finalized = true;
 
-                    let proposer_bytes = self
-                        .peer_id
-                        .map(libp2p::PeerId::to_bytes)
-                        .unwrap_or_default();
-                    match self.get_proposed_block(proposer_bytes).await {
+                    let proposed_block = match self.state.proposed_block.clone() {
+                        Some(block) => Ok(block),
+                        None => {
+                            let proposer_bytes = self
+                                .peer_id
+                                .map(libp2p::PeerId::to_bytes)
+                                .unwrap_or_default();
+                            self.get_proposed_block(proposer_bytes).await
+                        }
+                    };
+                    match proposed_block {
                         Ok(block) => match self.finalize_block(block.clone()).await {
                             Ok(()) => {
                                 info!(
@@ -376,7 +912,7 @@ impl ConsensusInterfaceImpl {
                                     block.body.transactions.len()
                                 );
 
-                                self.state.current_height = block.header.height;
+                                self.state.set_height(block.header.height);
                                 info!(
                                     "✅ Updated consensus height to {}",
                                     self.state.current_height
@@ -391,7 +927,9 @@ impl ConsensusInterfaceImpl {
         }
     }
 
-    async fn handle_vote(&mut self, sender: PeerId, vote: &Vote) {
+    /// Counts `vote`, or buffers it if it is for a later round. Returns whether it was taken,
+    /// which a vote from outside the validator set or for another height is not.
+    async fn handle_vote(&mut self, sender: PeerId, vote: &Vote) -> bool {
         debug!(
             "📨 Received {:?} vote from {} for block hash {} | round: {} (current: {}), height: {} (current: {})",
             vote.vote_type,
@@ -409,39 +947,123 @@ impl ConsensusInterfaceImpl {
                 sender,
                 self.state.validators.len()
             );
-            return;
+            return false;
+        }
+
+        if vote.height != self.state.current_height {
+            debug!(
+                "Discarding {:?} vote from {} for height {}: consensus is at height {}",
+                vote.vote_type, sender, vote.height, self.state.current_height
+            );
+            return false;
+        }
+
+        if vote.round > self.state.current_round {
+            let buffered = self.state.future_votes.push(
+                self.state.current_round,
+                vote.height,
+                vote.round,
+                sender,
+                vote.clone(),
+            );
+            if buffered {
+                debug!(
+                    "⏳ Buffered {:?} vote from {} for future round {}",
+                    vote.vote_type, sender, vote.round
+                );
+            } else {
+                debug!(
+                    "Discarding {:?} vote from {} for round {}: too far ahead or buffer full",
+                    vote.vote_type, sender, vote.round
+                );
+            }
+            return buffered;
         }
 
         match vote.vote_type {
             VoteType::Prevote => {
-                self.process_prevote_vote(sender, vote);
+                self.process_prevote_vote(sender, vote).await;
             }
             VoteType::Precommit => {
                 self.process_precommit_vote(sender, vote).await;
             }
         }
+        true
     }
 }
 
+/// The ids `tx_hashes` commits to, if they are exactly the ids of `block`'s transactions in
+/// block order.
+fn committed_tx_ids(block: &Block, tx_hashes: &[Vec<u8>]) -> Option<Vec<TransactionId>> {
+    let tx_ids = tx_hashes
+        .iter()
+        .map(|hash| TransactionId::try_from(hash.as_slice()).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let matches = tx_ids.len() == block.body.transactions.len()
+        && tx_ids
+            .iter()
+            .zip(&block.body.transactions)
+            .all(|(tx_id, tx)| *tx_id == tx.id());
+    matches.then_some(tx_ids)
+}
+
+/// Hash that votes reference: SHA-256 over the serialized block.
+fn vote_hash(block: &Block) -> Result<Vec<u8>, NodeError> {
+    Ok(Sha256::digest(block.serialize()?).to_vec())
+}
+
 #[async_trait::async_trait]
 impl ConsensusInterface for ConsensusInterfaceImpl {
     async fn handle_message(&mut self, message: ConsensusMessage) -> ConsensusResponse {
         match message {
-            ConsensusMessage::StartNewRound { round: _ } => match self.start_new_round() {
+            ConsensusMessage::StartNewRound { round: _ } => match self.advance_round().await {
                 Ok(()) => ConsensusResponse::StartNewRound { error: None },
                 Err(e) => ConsensusResponse::StartNewRound {
                     error: Some(e.to_string()),
                 },
             },
             ConsensusMessage::HandleVote { sender, vote } => match PeerId::from_bytes(&sender) {
-                Ok(peer_id) => {
-                    self.handle_vote(peer_id, &vote).await;
-                    ConsensusResponse::HandleVote { error: None }
-                }
+                Ok(peer_id) => ConsensusResponse::HandleVote {
+                    accepted: self.handle_vote(peer_id, &vote).await,
+                    error: None,
+                },
                 Err(e) => ConsensusResponse::HandleVote {
+                    accepted: false,
+                    error: Some(format!("Failed to decode sender peer ID: {e}")),
+                },
+            },
+            ConsensusMessage::HandleSignedVote {
+                sender,
+                vote,
+                signature,
+            } => match PeerId::from_bytes(&sender) {
+                Ok(peer_id) => match self.handle_signed_vote(peer_id, vote, signature).await {
+                    Ok(accepted) => ConsensusResponse::HandleSignedVote {
+                        accepted,
+                        error: None,
+                    },
+                    Err(e) => ConsensusResponse::HandleSignedVote {
+                        accepted: false,
+                        error: Some(e.to_string()),
+                    },
+                },
+                Err(e) => ConsensusResponse::HandleSignedVote {
+                    accepted: false,
                     error: Some(format!("Failed to decode sender peer ID: {e}")),
                 },
             },
+            ConsensusMessage::HandleVoteAggregate { aggregate } => {
+                match self.handle_vote_aggregate(aggregate).await {
+                    Ok(accepted) => ConsensusResponse::HandleVoteAggregate {
+                        accepted: accepted.iter().map(PeerId::to_bytes).collect(),
+                        error: None,
+                    },
+                    Err(e) => ConsensusResponse::HandleVoteAggregate {
+                        accepted: Vec::new(),
+                        error: Some(e.to_string()),
+                    },
+                }
+            }
             ConsensusMessage::HandleNewRound { sender, round } => {
                 match PeerId::from_bytes(&sender) {
                     Ok(peer_id) => {
@@ -459,7 +1081,7 @@ impl ConsensusInterface for ConsensusInterfaceImpl {
                         }
 
                         self.state.current_round = round - 1;
-                        match self.start_new_round() {
+                        match self.advance_round().await {
                             Ok(()) => ConsensusResponse::HandleNewRound { error: None },
                             Err(e) => ConsensusResponse::HandleNewRound {
                                 error: Some(e.to_string()),
@@ -478,16 +1100,22 @@ impl ConsensusInterface for ConsensusInterfaceImpl {
             } => match (PeerId::from_bytes(&sender), PeerId::from_bytes(&leader)) {
                 (Ok(_sender_id), Ok(leader_id)) => {
                     if round >= self.state.current_round {
+                        if round > self.state.current_round {
+                            self.state.proposed_block = None;
+                        }
                         self.state.current_round = round;
                         self.state.proposer = Some(leader_id);
                         self.state.is_leader = self.peer_id == Some(leader_id);
                         self.state.current_state = ConsensusPhase::WaitingForPropose;
-                        self.state.round_start_time = Some(tokio::time::Instant::now());
+                        self.state.round_start_time = Some(self.clock.now());
+                        self.state.collected_votes.clear();
+                        self.state.published_aggregates.clear();
 
                         debug!(
                             "Agreed on leader for round {} is {}",
                             self.state.current_round, leader_id
                         );
+                        self.replay_future_messages().await;
                     }
                     ConsensusResponse::HandleLeaderAnnouncement { error: None }
                 }
@@ -495,21 +1123,46 @@ impl ConsensusInterface for ConsensusInterfaceImpl {
                     error: Some(format!("Failed to decode peer ID: {e}")),
                 },
             },
-            ConsensusMessage::HandleBlockProposal { sender, raw_block } => {
-                match PeerId::from_bytes(&sender) {
-                    Ok(peer_id) => match self.handle_block_proposal(peer_id, raw_block).await {
-                        Ok(()) => ConsensusResponse::HandleBlockProposal { error: None },
-                        Err(e) => ConsensusResponse::HandleBlockProposal {
-                            error: Some(e.to_string()),
-                        },
+            ConsensusMessage::HandleBlockProposal {
+                sender,
+                raw_block,
+                tx_hashes,
+            } => match PeerId::from_bytes(&sender) {
+                Ok(peer_id) => match self
+                    .handle_block_proposal(peer_id, raw_block, tx_hashes)
+                    .await
+                {
+                    Ok(accepted) => ConsensusResponse::HandleBlockProposal {
+                        accepted,
+                        error: None,
                     },
                     Err(e) => ConsensusResponse::HandleBlockProposal {
-                        error: Some(format!("Failed to decode sender peer ID: {e}")),
+                        accepted: false,
+                        error: Some(e.to_string()),
                     },
-                }
-            }
+                },
+                Err(e) => ConsensusResponse::HandleBlockProposal {
+                    accepted: false,
+                    error: Some(format!("Failed to decode sender peer ID: {e}")),
+                },
+            },
             ConsensusMessage::TriggerConsensusRound { force_round: _ } => {
-                match self.start_new_round() {
+                if self.state.validators.is_empty() {
+                    return ConsensusResponse::TriggerConsensusRound {
+                        success: false,
+                        message: "No validators known, consensus deferred".to_string(),
+                        round_number: u64::from(self.state.current_round),
+                    };
+                }
+                if self.state.quorum_lost {
+                    return ConsensusResponse::TriggerConsensusRound {
+                        success: false,
+                        message: "Too few validators reachable for a quorum, consensus paused"
+                            .to_string(),
+                        round_number: u64::from(self.state.current_round),
+                    };
+                }
+                match self.advance_round().await {
                     Ok(()) => ConsensusResponse::TriggerConsensusRound {
                         success: true,
                         message: "Consensus round triggered".to_string(),
@@ -542,7 +1195,7 @@ impl ConsensusInterface for ConsensusInterfaceImpl {
                                         "🚀 Reached {} validators, auto-starting consensus round",
                                         max_validators
                                     );
-                                    match self.start_new_round() {
+                                    match self.advance_round().await {
                                         Ok(()) => ConsensusResponse::AddValidator { error: None },
                                         Err(e) => ConsensusResponse::AddValidator {
                                             error: Some(e.to_string()),
@@ -569,6 +1222,30 @@ impl ConsensusInterface for ConsensusInterfaceImpl {
                     },
                 }
             }
+            ConsensusMessage::PeerConnected { peer_id } => match PeerId::from_bytes(&peer_id) {
+                Ok(peer) => ConsensusResponse::PeerConnected {
+                    error: self
+                        .set_peer_connected(peer, true)
+                        .await
+                        .err()
+                        .map(|e| e.to_string()),
+                },
+                Err(e) => ConsensusResponse::PeerConnected {
+                    error: Some(format!("Failed to decode peer ID: {e}")),
+                },
+            },
+            ConsensusMessage::PeerDisconnected { peer_id } => match PeerId::from_bytes(&peer_id) {
+                Ok(peer) => ConsensusResponse::PeerDisconnected {
+                    error: self
+                        .set_peer_connected(peer, false)
+                        .await
+                        .err()
+                        .map(|e| e.to_string()),
+                },
+                Err(e) => ConsensusResponse::PeerDisconnected {
+                    error: Some(format!("Failed to decode peer ID: {e}")),
+                },
+            },
             ConsensusMessage::GetConsensusState => ConsensusResponse::GetConsensusState {
                 phase: self.state.current_state.clone(),
                 round: self.state.current_round,
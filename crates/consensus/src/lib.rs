@@ -1,14 +1,23 @@
 use libp2p::{PeerId, gossipsub::IdentTopic};
+use protocol::block::{Block, BlockHash, ConsensusQuorum};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::Duration;
 use tokio::time::Instant;
-use types::consensus::Vote;
+use tracing::error;
+use types::consensus::{Vote, VoteAggregate, VoteType};
+use types::errors::NodeError;
 
 pub mod consensus_interface;
 pub mod main_loop;
+pub mod round_buffer;
 
 pub use consensus_interface::{ConsensusInterface, ConsensusInterfaceImpl};
+pub use round_buffer::{BufferedProposal, RoundBuffer};
+
+/// Heights below the latest finalized one that keep their hash for fork detection. Older
+/// heights are behind the chain tip, which refuses any block there on its own.
+pub const FINALIZED_BLOCKS_WINDOW: u64 = 1_000;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConsensusPhase {
@@ -26,6 +35,15 @@ pub enum ConsensusMessage {
         sender: Vec<u8>,
         vote: Vote,
     },
+    /// A vote sent directly to this node as the round's aggregator.
+    HandleSignedVote {
+        sender: Vec<u8>,
+        vote: Vote,
+        signature: Vec<u8>,
+    },
+    HandleVoteAggregate {
+        aggregate: VoteAggregate,
+    },
     HandleNewRound {
         sender: Vec<u8>,
         round: u32,
@@ -38,6 +56,8 @@ pub enum ConsensusMessage {
     HandleBlockProposal {
         sender: Vec<u8>,
         raw_block: Vec<u8>,
+        /// Transaction ids the proposal commits to, if it came with any.
+        tx_hashes: Option<Vec<Vec<u8>>>,
     },
     TriggerConsensusRound {
         force_round: bool,
@@ -48,6 +68,14 @@ pub enum ConsensusMessage {
     AddValidator {
         peer_id: Vec<u8>,
     },
+    /// The node (re)established a connection to `peer_id`.
+    PeerConnected {
+        peer_id: Vec<u8>,
+    },
+    /// The node lost its connection to `peer_id`.
+    PeerDisconnected {
+        peer_id: Vec<u8>,
+    },
     GetConsensusState,
 }
 
@@ -57,6 +85,18 @@ pub enum ConsensusResponse {
         error: Option<String>,
     },
     HandleVote {
+        /// Whether the vote was counted or buffered for a later round.
+        accepted: bool,
+        error: Option<String>,
+    },
+    HandleSignedVote {
+        /// Whether the vote was counted or buffered for a later round.
+        accepted: bool,
+        error: Option<String>,
+    },
+    HandleVoteAggregate {
+        /// Validators whose votes in the aggregate were counted or buffered.
+        accepted: Vec<Vec<u8>>,
         error: Option<String>,
     },
     HandleNewRound {
@@ -66,6 +106,8 @@ pub enum ConsensusResponse {
         error: Option<String>,
     },
     HandleBlockProposal {
+        /// Whether the proposal was valid and prevoted.
+        accepted: bool,
         error: Option<String>,
     },
     TriggerConsensusRound {
@@ -79,6 +121,12 @@ pub enum ConsensusResponse {
     AddValidator {
         error: Option<String>,
     },
+    PeerConnected {
+        error: Option<String>,
+    },
+    PeerDisconnected {
+        error: Option<String>,
+    },
     GetConsensusState {
         phase: ConsensusPhase,
         round: u32,
@@ -90,23 +138,61 @@ pub enum ConsensusResponse {
     },
 }
 
+/// Raised when a block conflicting with an already finalized block is presented for the same height.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkAlert {
+    pub height: u64,
+    pub finalized_hash: BlockHash,
+    pub conflicting_hash: BlockHash,
+}
+
 pub struct ConsensusState {
     pub current_state: ConsensusPhase,
     pub current_round: u32,
     pub current_height: u64,
     pub proposer: Option<PeerId>,
     pub validators: HashSet<PeerId>,
+    pub consensus_quorum: ConsensusQuorum,
+    /// Peers the node has lost its connection to; validators among them cannot vote.
+    pub disconnected_peers: HashSet<PeerId>,
+    /// Set while too few validators are reachable for a quorum. Rounds neither time out nor
+    /// see proposals or votes from this node until it clears.
+    pub quorum_lost: bool,
 
     pub broadcast_topic: IdentTopic,
 
     pub round_timeout: Duration,
+    /// How long validators wait for the round's proposal before prevoting nil, well within
+    /// `round_timeout` so a silent leader does not stall the whole round.
+    pub propose_timeout: Duration,
     pub round_start_time: Option<Instant>,
     pub is_leader: bool,
 
     pub prevotes: HashSet<PeerId>,
+    /// Validators that prevoted nil this round, kept apart from prevotes for a block.
+    pub nil_prevotes: HashSet<PeerId>,
     pub precommits: HashSet<PeerId>,
     pub current_block_hash: Option<Vec<u8>>,
+    /// The round's proposal as this node accepted it, which is the block finalized once the
+    /// precommits are in.
+    pub proposed_block: Option<Block>,
     pub block_finalized: bool,
+
+    /// Hashes finalized at the last `FINALIZED_BLOCKS_WINDOW` heights.
+    pub finalized_blocks: HashMap<u64, BlockHash>,
+    pub fork_alerts: Vec<ForkAlert>,
+
+    pub future_votes: RoundBuffer<Vote>,
+    pub future_proposals: RoundBuffer<BufferedProposal>,
+
+    /// Whether validators send their votes to the round's leader, which gossips them as a
+    /// single aggregate once it holds a quorum, instead of each gossiping its own.
+    pub vote_aggregation: bool,
+    /// Vote timestamps and signatures the leader collected this round, by vote type and block
+    /// hash, then voter.
+    pub collected_votes: HashMap<(VoteType, Vec<u8>), BTreeMap<Vec<u8>, (u64, Vec<u8>)>>,
+    /// Vote types and block hashes the leader already gossiped an aggregate for this round.
+    pub published_aggregates: HashSet<(VoteType, Vec<u8>)>,
 }
 
 impl Default for ConsensusState {
@@ -124,30 +210,128 @@ impl ConsensusState {
             current_height: 0,
             proposer: None,
             validators: HashSet::new(),
+            consensus_quorum: ConsensusQuorum::default(),
+            disconnected_peers: HashSet::new(),
+            quorum_lost: false,
             broadcast_topic: IdentTopic::new("broadcast"),
             round_timeout: Duration::from_secs(10),
+            propose_timeout: Duration::from_secs(3),
             round_start_time: None,
             is_leader: false,
             prevotes: HashSet::new(),
+            nil_prevotes: HashSet::new(),
             precommits: HashSet::new(),
             current_block_hash: None,
+            proposed_block: None,
             block_finalized: false,
+            finalized_blocks: HashMap::new(),
+            fork_alerts: Vec::new(),
+            future_votes: RoundBuffer::default(),
+            future_proposals: RoundBuffer::new(
+                round_buffer::MAX_ROUND_LOOKAHEAD,
+                round_buffer::MAX_BUFFERED_PROPOSALS,
+            ),
+            vote_aggregation: false,
+            collected_votes: HashMap::new(),
+            published_aggregates: HashSet::new(),
+        }
+    }
+
+    /// Checks `block_hash` against the block already finalized at `height`, if any.
+    ///
+    /// Returns `Ok(true)` when the same block was already finalized and `Ok(false)` when the
+    /// height is still open. A different block at a finalized height records a fork alert and
+    /// is rejected.
+    pub fn check_finalization(
+        &mut self,
+        height: u64,
+        block_hash: BlockHash,
+    ) -> Result<bool, NodeError> {
+        match self.finalized_blocks.get(&height) {
+            None => Ok(false),
+            Some(finalized_hash) if *finalized_hash == block_hash => Ok(true),
+            Some(finalized_hash) => {
+                let alert = ForkAlert {
+                    height,
+                    finalized_hash: *finalized_hash,
+                    conflicting_hash: block_hash,
+                };
+                error!(
+                    "🚨 Fork detected at height {}: finalized {} but asked to finalize {}",
+                    height,
+                    hex::encode(alert.finalized_hash),
+                    hex::encode(alert.conflicting_hash)
+                );
+                self.fork_alerts.push(alert);
+                Err(NodeError::Error(format!(
+                    "Refusing to finalize conflicting block at height {height}"
+                )))
+            }
         }
     }
 
+    /// Records `block_hash` as finalized at `height`, forgetting heights that fell out of
+    /// the window.
+    pub fn record_finalized(&mut self, height: u64, block_hash: BlockHash) {
+        self.finalized_blocks.insert(height, block_hash);
+        let oldest_kept = height.saturating_sub(FINALIZED_BLOCKS_WINDOW);
+        self.finalized_blocks
+            .retain(|finalized_height, _| *finalized_height >= oldest_kept);
+    }
+
+    /// Prevotes or precommits needed to advance, per the configured consensus quorum.
+    #[must_use]
+    pub fn quorum(&self) -> usize {
+        self.consensus_quorum.required_votes(self.validators.len())
+    }
+
+    /// Validators the node is still connected to, itself included.
+    #[must_use]
+    pub fn reachable_validators(&self) -> usize {
+        self.validators
+            .iter()
+            .filter(|validator| !self.disconnected_peers.contains(validator))
+            .count()
+    }
+
+    /// Whether enough validators are reachable for their votes to reach a quorum. Without
+    /// any known validators there is nothing to pause.
+    #[must_use]
+    pub fn has_quorum_presence(&self) -> bool {
+        self.validators.is_empty() || self.reachable_validators() >= self.quorum()
+    }
+
+    /// Moves consensus to `height`, dropping the votes and proposals buffered for earlier
+    /// heights so they are never replayed into this height's rounds.
+    pub fn set_height(&mut self, height: u64) {
+        self.current_height = height;
+        self.future_votes.advance_height(height);
+        self.future_proposals.advance_height(height);
+    }
+
     #[must_use]
     pub fn select_leader(&self, round: u32) -> Option<PeerId> {
         if self.validators.is_empty() {
             return None;
         }
 
-        let mut sorted_validators: Vec<PeerId> = self.validators.iter().copied().collect();
-        sorted_validators.sort();
-
-        let index = (round as usize) % self.validators.len();
-        sorted_validators.get(index).copied()
+        let rotation = leader_rotation(&self.validators);
+        let index = (round as usize) % rotation.len();
+        rotation.get(index).copied()
     }
 }
 
+/// Canonical validator order for leader rotation: ascending `PeerId` bytes.
+///
+/// The order is defined on the wire encoding rather than `PeerId`'s derived `Ord` so that
+/// every implementation can reproduce it. Validators are all equally weighted today; if
+/// stake weighting is introduced, equal weights must still be broken by this order.
+#[must_use]
+pub fn leader_rotation<'a>(validators: impl IntoIterator<Item = &'a PeerId>) -> Vec<PeerId> {
+    let mut rotation: Vec<PeerId> = validators.into_iter().copied().collect();
+    rotation.sort_by_cached_key(|peer| peer.to_bytes());
+    rotation
+}
+
 #[cfg(test)]
 mod tests;
@@ -5,38 +5,46 @@ use tracing::{debug, error, info};
 use types::errors::NodeError;
 
 const POLL_INTERVAL_MS: u64 = 100;
-const ROUND_TIME_SECONDS: u64 = 10;
 
 impl ConsensusInterfaceImpl {
     pub async fn start(&mut self) {
         info!(
-            "Starting consensus interface main loop with {}ms polling and {}s rounds",
-            POLL_INTERVAL_MS, ROUND_TIME_SECONDS
+            "Starting consensus interface main loop with {}ms polling, {:?} proposals and {:?} rounds",
+            POLL_INTERVAL_MS, self.state.propose_timeout, self.state.round_timeout
         );
 
         let mut poll_interval = interval(Duration::from_millis(POLL_INTERVAL_MS));
-        let mut round_interval = interval(Duration::from_secs(ROUND_TIME_SECONDS));
 
         // Skip the first tick to avoid immediate firing
         poll_interval.tick().await;
-        round_interval.tick().await;
 
         loop {
-            tokio::select! {
-                _ = poll_interval.tick() => {
-                    if let Err(e) = self.poll_messages().await {
-                        error!("Error polling consensus messages: {}", e);
-                    }
-                }
-                _ = round_interval.tick() => {
-                    if let Err(e) = self.trigger_new_round().await {
-                        error!("Error triggering new consensus round: {}", e);
-                    }
-                }
+            poll_interval.tick().await;
+            if let Err(e) = self.poll_messages().await {
+                error!("Error polling consensus messages: {}", e);
+            }
+            if let Err(e) = self.check_propose_timeout().await {
+                error!("Error prevoting nil after proposal timeout: {}", e);
+            }
+            if let Err(e) = self.check_round_timeout().await {
+                error!("Error triggering new consensus round: {}", e);
             }
         }
     }
 
+    /// Moves on to the next round once `round_timeout` has passed since the current one
+    /// started, as measured by the interface's clock.
+    pub async fn check_round_timeout(&mut self) -> Result<(), NodeError> {
+        if !self.state.quorum_lost
+            && self
+                .round_elapsed()
+                .is_some_and(|elapsed| elapsed >= self.state.round_timeout)
+        {
+            self.trigger_new_round().await?;
+        }
+        Ok(())
+    }
+
     async fn poll_messages(&mut self) -> Result<(), NodeError> {
         // Try to receive messages without blocking
         while let Ok((message, response_sender)) = self.message_stream.try_recv() {
@@ -48,14 +56,14 @@ impl ConsensusInterfaceImpl {
         Ok(())
     }
 
-    async fn trigger_new_round(&mut self) -> Result<(), NodeError> {
+    pub(crate) async fn trigger_new_round(&mut self) -> Result<(), NodeError> {
         // Only trigger new rounds if we have validators and consensus is active
         if self.state.validators.len() >= 2 && self.state.current_round > 0 {
             debug!(
-                "Auto-triggering new consensus round after {}s interval",
-                ROUND_TIME_SECONDS
+                "Auto-triggering new consensus round after {:?} interval",
+                self.state.round_timeout
             );
-            self.start_new_round()?;
+            self.advance_round().await?;
 
             // If we're the leader, automatically propose a block
             if self.state.is_leader {
@@ -0,0 +1,95 @@
+use libp2p::PeerId;
+use std::collections::BTreeMap;
+
+/// How many rounds ahead of the current one a message may be and still be held for later.
+pub const MAX_ROUND_LOOKAHEAD: u32 = 3;
+/// Upper bound on votes held across all future rounds.
+pub const MAX_BUFFERED_VOTES: usize = 256;
+/// Upper bound on proposals held across all future rounds, one per round in the lookahead.
+pub const MAX_BUFFERED_PROPOSALS: usize = MAX_ROUND_LOOKAHEAD as usize;
+
+/// A block proposal from the leader of a round the node has not entered yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferedProposal {
+    pub raw_block: Vec<u8>,
+    pub tx_hashes: Option<Vec<Vec<u8>>>,
+}
+
+/// Holds messages that arrive for rounds the node has not entered yet, so a peer that
+/// advanced slightly earlier does not lose its vote or proposal.
+///
+/// Messages are keyed by height and round, so those held for a height are never replayed
+/// into the rounds of the next one.
+pub struct RoundBuffer<T> {
+    messages: BTreeMap<(u64, u32), Vec<(PeerId, T)>>,
+    len: usize,
+    max_lookahead: u32,
+    capacity: usize,
+}
+
+impl<T> Default for RoundBuffer<T> {
+    fn default() -> Self {
+        Self::new(MAX_ROUND_LOOKAHEAD, MAX_BUFFERED_VOTES)
+    }
+}
+
+impl<T> RoundBuffer<T> {
+    #[must_use]
+    pub const fn new(max_lookahead: u32, capacity: usize) -> Self {
+        Self {
+            messages: BTreeMap::new(),
+            len: 0,
+            max_lookahead,
+            capacity,
+        }
+    }
+
+    /// Buffers `message` for `round` at `height`. Returns `false` if it was discarded because
+    /// its round is too far ahead of `current_round` or the buffer is full.
+    pub fn push(
+        &mut self,
+        current_round: u32,
+        height: u64,
+        round: u32,
+        sender: PeerId,
+        message: T,
+    ) -> bool {
+        if round.saturating_sub(current_round) > self.max_lookahead || self.len >= self.capacity {
+            return false;
+        }
+
+        self.messages
+            .entry((height, round))
+            .or_default()
+            .push((sender, message));
+        self.len += 1;
+        true
+    }
+
+    /// Removes every message for `round` at `height` and earlier, returning only those for
+    /// `round`. Messages for rounds that were skipped over can no longer count and are
+    /// dropped.
+    pub fn take_round(&mut self, height: u64, round: u32) -> Vec<(PeerId, T)> {
+        let later = self.messages.split_off(&(height, round.saturating_add(1)));
+        let mut due = std::mem::replace(&mut self.messages, later);
+        let messages = due.remove(&(height, round)).unwrap_or_default();
+        self.len = self.messages.values().map(Vec::len).sum();
+        messages
+    }
+
+    /// Drops every message held for a height below `height`, once the node has moved on to it.
+    pub fn advance_height(&mut self, height: u64) {
+        self.messages = self.messages.split_off(&(height, 0));
+        self.len = self.messages.values().map(Vec::len).sum();
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
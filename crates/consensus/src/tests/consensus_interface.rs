@@ -1,9 +1,16 @@
 use crate::{
-    ConsensusInterface, ConsensusInterfaceImpl, ConsensusMessage, ConsensusPhase, ConsensusResponse,
+    ConsensusInterface, ConsensusInterfaceImpl, ConsensusMessage, ConsensusPhase,
+    ConsensusResponse, ForkAlert, leader_rotation,
 };
 use libp2p::PeerId;
+use protocol::block::{Block, ChainConfig, ConsensusQuorum};
+use protocol::transaction::{Operation, Transaction, TransactionType};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
-use types::consensus::{Vote, VoteType};
+use types::clock::{Clock, MockClock};
+use types::consensus::{Vote, VoteType, unix_timestamp};
+use types::errors::NodeError;
 
 #[tokio::test]
 async fn test_consensus_interface_creation() {
@@ -215,6 +222,59 @@ async fn test_trigger_consensus_round() {
     assert_eq!(interface.state.current_round, initial_round + 1);
 }
 
+#[tokio::test]
+async fn test_consensus_steps_skipped_without_validators() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+    let (network_tx, mut network_rx) = broadcast::channel(100);
+    interface.set_network_events_tx(network_tx);
+    interface.set_peer_id(PeerId::random());
+    interface.state.is_leader = true;
+
+    let response = interface
+        .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+        .await;
+    assert!(matches!(
+        response,
+        ConsensusResponse::StartNewRound { error: None }
+    ));
+    assert_eq!(interface.state.current_round, 0);
+    assert_eq!(interface.state.proposer, None);
+    assert!(!interface.state.is_leader);
+
+    let response = interface
+        .handle_message(ConsensusMessage::TriggerConsensusRound { force_round: true })
+        .await;
+    assert!(matches!(
+        response,
+        ConsensusResponse::TriggerConsensusRound {
+            success: false,
+            round_number: 0,
+            ..
+        }
+    ));
+
+    let block = Block::new([0u8; 32], 1, vec![], PeerId::random().to_bytes());
+    let response = interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: PeerId::random().to_bytes(),
+            raw_block: block.serialize().unwrap(),
+            tx_hashes: None,
+        })
+        .await;
+    assert!(matches!(
+        response,
+        ConsensusResponse::HandleBlockProposal { error: None, .. }
+    ));
+    interface.propose_block_as_leader().await.unwrap();
+
+    // Nothing was proposed, voted on or announced
+    assert!(network_rx.try_recv().is_err());
+    assert_eq!(
+        interface.state.current_state,
+        ConsensusPhase::WaitingForPropose
+    );
+}
+
 #[tokio::test]
 async fn test_handle_vote_from_validator() {
     let (mut interface, _tx) = ConsensusInterfaceImpl::new();
@@ -239,6 +299,7 @@ async fn test_handle_vote_from_validator() {
         block_hash: vec![1, 2, 3, 4],
         voter: voter_peer.to_bytes(),
         vote_type: VoteType::Prevote,
+        timestamp: unix_timestamp(),
     };
 
     let response = interface
@@ -249,7 +310,7 @@ async fn test_handle_vote_from_validator() {
         .await;
 
     match response {
-        ConsensusResponse::HandleVote { error } => {
+        ConsensusResponse::HandleVote { error, .. } => {
             assert!(error.is_none());
         }
         _ => panic!("Unexpected response type"),
@@ -266,6 +327,7 @@ async fn test_handle_vote_invalid_sender() {
         block_hash: vec![1, 2, 3, 4],
         voter: vec![1, 2, 3], // Invalid voter
         vote_type: VoteType::Prevote,
+        timestamp: unix_timestamp(),
     };
 
     let response = interface
@@ -276,7 +338,7 @@ async fn test_handle_vote_invalid_sender() {
         .await;
 
     match response {
-        ConsensusResponse::HandleVote { error } => {
+        ConsensusResponse::HandleVote { error, .. } => {
             assert!(error.is_some());
             assert!(error.unwrap().contains("Failed to decode sender peer ID"));
         }
@@ -427,3 +489,1101 @@ async fn test_no_auto_start_without_max_validators_set() {
     assert_eq!(interface.state.current_round, 0);
     assert_eq!(interface.state.validators.len(), 2);
 }
+
+#[tokio::test]
+async fn test_conflicting_block_at_finalized_height_is_rejected() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+
+    let (chain_tx, mut chain_rx) = messenger::channel(10, Some(10));
+    tokio::spawn(async move {
+        while let Ok((_, reply)) = chain_rx.recv().await {
+            let _ = reply.send(abci::ChainResponse::FinalizeAndStoreBlock { error: None });
+        }
+    });
+    interface.set_chain_interface(chain_tx);
+
+    let first = Block::new([0u8; 32], 1, vec![], vec![1]);
+    let conflicting = Block::new([0u8; 32], 1, vec![], vec![2]);
+
+    interface
+        .finalize_block(first.clone())
+        .await
+        .expect("first block should finalize");
+
+    // Re-finalizing the same block is a no-op rather than a fork
+    interface
+        .finalize_block(first.clone())
+        .await
+        .expect("same block should be accepted idempotently");
+    assert!(interface.state.fork_alerts.is_empty());
+
+    let result = interface.finalize_block(conflicting.clone()).await;
+    assert!(result.is_err());
+
+    assert_eq!(
+        interface.state.finalized_blocks.get(&1),
+        Some(&first.hash())
+    );
+    assert_eq!(
+        interface.state.fork_alerts,
+        vec![ForkAlert {
+            height: 1,
+            finalized_hash: first.hash(),
+            conflicting_hash: conflicting.hash(),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_custom_consensus_quorum_governs_finalization() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+
+    let block = Block::new([0u8; 32], 1, vec![], vec![1]);
+    let proposed = block.clone();
+    let (chain_tx, mut chain_rx) = messenger::channel(10, Some(10));
+    tokio::spawn(async move {
+        while let Ok((message, reply)) = chain_rx.recv().await {
+            let response = match message {
+                abci::ChainMessage::GetProposedBlock { .. } => {
+                    abci::ChainResponse::GetProposedBlock {
+                        block: proposed.clone(),
+                    }
+                }
+                abci::ChainMessage::VerifyBlockDeposits { .. } => {
+                    abci::ChainResponse::VerifyBlockDeposits { error: None }
+                }
+                _ => abci::ChainResponse::FinalizeAndStoreBlock { error: None },
+            };
+            let _ = reply.send(response);
+        }
+    });
+    interface.set_chain_interface(chain_tx);
+
+    // Signing only needs two of four shares, but finalization demands every validator.
+    let chain_config = ChainConfig {
+        min_signers: 2,
+        max_signers: 4,
+        min_stake: 100,
+        block_time_seconds: 10,
+        max_block_size: 1000,
+        consensus_quorum: ConsensusQuorum::Count(4),
+    };
+    interface.set_consensus_quorum(chain_config.consensus_quorum);
+
+    let validators: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+    for validator in &validators {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+    assert_eq!(interface.state.quorum(), 4);
+    assert_ne!(
+        interface.state.quorum(),
+        usize::from(chain_config.min_signers)
+    );
+
+    let precommit = |voter: &PeerId| ConsensusMessage::HandleVote {
+        sender: voter.to_bytes(),
+        vote: Vote {
+            round: 0,
+            height: 0,
+            block_hash: block.hash().to_vec(),
+            voter: voter.to_bytes(),
+            vote_type: VoteType::Precommit,
+            timestamp: unix_timestamp(),
+        },
+    };
+
+    // Three precommits would clear the default 2/3 quorum but not this one.
+    for validator in &validators[..3] {
+        interface.handle_message(precommit(validator)).await;
+    }
+    assert!(!interface.state.block_finalized);
+    assert!(interface.state.finalized_blocks.is_empty());
+
+    interface.handle_message(precommit(&validators[3])).await;
+    assert!(interface.state.block_finalized);
+    assert_eq!(
+        interface.state.finalized_blocks.get(&1),
+        Some(&block.hash())
+    );
+}
+
+#[tokio::test]
+async fn test_block_proposal_from_non_leader_is_rejected() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+
+    let block = Block::new([0u8; 32], 1, vec![], vec![1]);
+    let proposed = block.clone();
+    let (chain_tx, mut chain_rx) = messenger::channel(10, Some(10));
+    tokio::spawn(async move {
+        while let Ok((message, reply)) = chain_rx.recv().await {
+            if let abci::ChainMessage::GetProposedBlock { .. } = message {
+                let _ = reply.send(abci::ChainResponse::GetProposedBlock {
+                    block: proposed.clone(),
+                });
+            }
+        }
+    });
+    interface.set_chain_interface(chain_tx);
+    let (network_tx, mut network_rx) = broadcast::channel(16);
+    interface.set_network_events_tx(network_tx);
+
+    let rotation = leader_rotation(&[PeerId::random(), PeerId::random(), PeerId::random()]);
+    let (leader, local, impostor) = (rotation[0], rotation[1], rotation[2]);
+    interface.set_peer_id(local);
+    for validator in &rotation {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+    assert_eq!(interface.state.select_leader(0), Some(leader));
+
+    // The proposal matches our own block, but comes from a validator out of turn
+    let response = interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: impostor.to_bytes(),
+            raw_block: block.serialize().unwrap(),
+            tx_hashes: None,
+        })
+        .await;
+    assert!(matches!(
+        response,
+        ConsensusResponse::HandleBlockProposal {
+            accepted: false,
+            error: None
+        }
+    ));
+    assert!(interface.state.prevotes.is_empty());
+    assert_eq!(
+        interface.state.current_state,
+        ConsensusPhase::WaitingForPropose
+    );
+    assert!(network_rx.try_recv().is_err());
+
+    // The same block from the round's leader is prevoted
+    let proposal = ConsensusMessage::HandleBlockProposal {
+        sender: leader.to_bytes(),
+        raw_block: block.serialize().unwrap(),
+        tx_hashes: None,
+    };
+    let response = interface.handle_message(proposal.clone()).await;
+    assert!(matches!(
+        response,
+        ConsensusResponse::HandleBlockProposal {
+            accepted: true,
+            error: None
+        }
+    ));
+    assert!(interface.state.prevotes.contains(&local));
+    assert!(network_rx.try_recv().is_ok());
+
+    // A second copy of the proposal is not taken again
+    let response = interface.handle_message(proposal).await;
+    assert!(matches!(
+        response,
+        ConsensusResponse::HandleBlockProposal {
+            accepted: false,
+            error: None
+        }
+    ));
+}
+
+#[tokio::test]
+async fn test_local_validator_vote_counts_toward_finalization() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+
+    let block = Block::new([0u8; 32], 1, vec![], vec![1]);
+    let proposed = block.clone();
+    let (chain_tx, mut chain_rx) = messenger::channel(10, Some(10));
+    tokio::spawn(async move {
+        while let Ok((message, reply)) = chain_rx.recv().await {
+            let response = match message {
+                abci::ChainMessage::GetProposedBlock { .. } => {
+                    abci::ChainResponse::GetProposedBlock {
+                        block: proposed.clone(),
+                    }
+                }
+                abci::ChainMessage::VerifyBlockDeposits { .. } => {
+                    abci::ChainResponse::VerifyBlockDeposits { error: None }
+                }
+                _ => abci::ChainResponse::FinalizeAndStoreBlock { error: None },
+            };
+            let _ = reply.send(response);
+        }
+    });
+    interface.set_chain_interface(chain_tx);
+    let (network_tx, mut network_rx) = broadcast::channel(16);
+    interface.set_network_events_tx(network_tx);
+
+    // Three validators, one of them this node: the default 2/3 quorum needs two votes.
+    let rotation = leader_rotation(&[PeerId::random(), PeerId::random(), PeerId::random()]);
+    let (leader, local, peer) = (rotation[0], rotation[1], rotation[2]);
+    interface.set_peer_id(local);
+    for validator in [local, leader, peer] {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+    assert_eq!(interface.state.quorum(), 2);
+
+    interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: leader.to_bytes(),
+            raw_block: block.serialize().unwrap(),
+            tx_hashes: None,
+        })
+        .await;
+    assert!(interface.state.prevotes.contains(&local));
+
+    let vote = |voter: &PeerId, vote_type: VoteType| ConsensusMessage::HandleVote {
+        sender: voter.to_bytes(),
+        vote: Vote {
+            round: 0,
+            height: 0,
+            block_hash: block.hash().to_vec(),
+            voter: voter.to_bytes(),
+            vote_type,
+            timestamp: unix_timestamp(),
+        },
+    };
+
+    // Our prevote plus one peer's reaches quorum, so we precommit and count that too.
+    interface
+        .handle_message(vote(&leader, VoteType::Prevote))
+        .await;
+    assert_eq!(interface.state.prevotes.len(), 2);
+    assert!(interface.state.precommits.contains(&local));
+    assert!(!interface.state.block_finalized);
+
+    // A single remote precommit completes the quorum with our own.
+    interface
+        .handle_message(vote(&peer, VoteType::Precommit))
+        .await;
+    assert!(interface.state.block_finalized);
+    assert_eq!(
+        interface.state.finalized_blocks.get(&1),
+        Some(&block.hash())
+    );
+
+    // Our votes were still broadcast for the other validators.
+    let mut own_votes = Vec::new();
+    while let Ok(event) = network_rx.try_recv() {
+        if let types::network::network_event::NetworkEvent::SendBroadcast {
+            message:
+                types::broadcast::BroadcastMessage::Consensus(types::consensus::ConsensusMessage::Vote(
+                    vote,
+                )),
+        } = event
+        {
+            assert_eq!(vote.voter, local.to_bytes());
+            own_votes.push(vote.vote_type);
+        }
+    }
+    assert!(matches!(
+        own_votes.as_slice(),
+        [VoteType::Prevote, VoteType::Precommit]
+    ));
+}
+
+#[tokio::test]
+async fn test_proposal_with_unconfirmable_deposit_is_nil_prevoted() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+
+    let deposit = Transaction::new(
+        TransactionType::Deposit,
+        vec![
+            Operation::OpPush {
+                value: 1000u64.to_be_bytes().to_vec(),
+            },
+            Operation::OpPush {
+                value: b"deposit_address".to_vec(),
+            },
+            Operation::OpPush {
+                value: vec![9u8; 32],
+            },
+            Operation::OpCheckOracle,
+            Operation::OpPush {
+                value: 1000u64.to_be_bytes().to_vec(),
+            },
+            Operation::OpPush {
+                value: b"deposit_address".to_vec(),
+            },
+            Operation::OpIncrementBalance,
+        ],
+        None,
+    );
+    let block = Block::new([0u8; 32], 1, vec![deposit], vec![1]);
+    let proposed = block.clone();
+    let (chain_tx, mut chain_rx) = messenger::channel(10, Some(10));
+    let (finalized_tx, mut finalized_rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((message, reply)) = chain_rx.recv().await {
+            let response = match message {
+                // The follower's mempool holds the same deposit, so the rebuilt block matches.
+                abci::ChainMessage::GetProposedBlock { .. } => {
+                    abci::ChainResponse::GetProposedBlock {
+                        block: proposed.clone(),
+                    }
+                }
+                // But its oracle cannot find the on-chain transaction the deposit references.
+                abci::ChainMessage::VerifyBlockDeposits { .. } => {
+                    abci::ChainResponse::VerifyBlockDeposits {
+                        error: Some(NodeError::Error(
+                            "Deposit could not be confirmed".to_string(),
+                        )),
+                    }
+                }
+                abci::ChainMessage::FinalizeBlock { block } => {
+                    let _ = finalized_tx.send(block.header.height);
+                    abci::ChainResponse::FinalizeAndStoreBlock { error: None }
+                }
+                _ => abci::ChainResponse::FinalizeAndStoreBlock { error: None },
+            };
+            let _ = reply.send(response);
+        }
+    });
+    interface.set_chain_interface(chain_tx);
+    let (network_tx, mut network_rx) = broadcast::channel(16);
+    interface.set_network_events_tx(network_tx);
+
+    let rotation = leader_rotation(&[PeerId::random(), PeerId::random(), PeerId::random()]);
+    let (leader, local, peer) = (rotation[0], rotation[1], rotation[2]);
+    interface.set_peer_id(local);
+    for validator in [local, leader, peer] {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+
+    interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: leader.to_bytes(),
+            raw_block: block.serialize().unwrap(),
+            tx_hashes: None,
+        })
+        .await;
+    assert_eq!(interface.state.current_state, ConsensusPhase::Prevote);
+    assert!(interface.state.nil_prevotes.contains(&local));
+    assert!(interface.state.prevotes.is_empty());
+    let Ok(types::network::network_event::NetworkEvent::SendBroadcast {
+        message:
+            types::broadcast::BroadcastMessage::Consensus(types::consensus::ConsensusMessage::Vote(
+                vote,
+            )),
+    }) = network_rx.try_recv()
+    else {
+        panic!("expected a broadcast vote");
+    };
+    assert!(vote.is_nil());
+    assert!(matches!(vote.vote_type, VoteType::Prevote));
+
+    // The leader's own prevote alone cannot carry the block without ours.
+    interface
+        .handle_message(ConsensusMessage::HandleVote {
+            sender: leader.to_bytes(),
+            vote: Vote {
+                round: 0,
+                height: 0,
+                block_hash: block.hash().to_vec(),
+                voter: leader.to_bytes(),
+                vote_type: VoteType::Prevote,
+                timestamp: unix_timestamp(),
+            },
+        })
+        .await;
+    assert!(!interface.state.precommits.contains(&local));
+    assert!(!interface.state.block_finalized);
+    assert!(!interface.state.finalized_blocks.contains_key(&1));
+    assert!(finalized_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_prevote_for_next_round_is_counted_after_advancing() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+    let validators: Vec<PeerId> = (0..3).map(|_| PeerId::random()).collect();
+    for validator in &validators {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+    assert_eq!(interface.state.current_round, 0);
+
+    let prevote = |voter: &PeerId, round: u32| ConsensusMessage::HandleVote {
+        sender: voter.to_bytes(),
+        vote: Vote {
+            round,
+            height: 0,
+            block_hash: vec![1, 2, 3, 4],
+            voter: voter.to_bytes(),
+            vote_type: VoteType::Prevote,
+            timestamp: unix_timestamp(),
+        },
+    };
+
+    // One vote arrives a round early, another far beyond the lookahead window.
+    interface.handle_message(prevote(&validators[0], 1)).await;
+    interface
+        .handle_message(prevote(
+            &validators[1],
+            crate::round_buffer::MAX_ROUND_LOOKAHEAD + 1,
+        ))
+        .await;
+    assert!(interface.state.prevotes.is_empty());
+    assert_eq!(interface.state.future_votes.len(), 1);
+
+    interface
+        .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+        .await;
+
+    assert_eq!(interface.state.current_round, 1);
+    assert_eq!(interface.state.prevotes.len(), 1);
+    assert!(interface.state.prevotes.contains(&validators[0]));
+    assert!(interface.state.future_votes.is_empty());
+}
+
+#[tokio::test]
+async fn test_buffered_votes_do_not_carry_over_to_the_next_height() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+    let validators: Vec<PeerId> = (0..3).map(|_| PeerId::random()).collect();
+    for validator in &validators {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+
+    let prevote = |voter: &PeerId, height: u64| ConsensusMessage::HandleVote {
+        sender: voter.to_bytes(),
+        vote: Vote {
+            round: 1,
+            height,
+            block_hash: vec![1, 2, 3, 4],
+            voter: voter.to_bytes(),
+            vote_type: VoteType::Prevote,
+            timestamp: unix_timestamp(),
+        },
+    };
+
+    // A vote for another height is neither counted nor buffered.
+    interface.handle_message(prevote(&validators[0], 1)).await;
+    assert!(interface.state.future_votes.is_empty());
+
+    interface.handle_message(prevote(&validators[1], 0)).await;
+    assert_eq!(interface.state.future_votes.len(), 1);
+
+    // Once height 0 is finalized, its round 1 vote must not count in round 1 of height 1.
+    interface.state.set_height(1);
+    assert!(interface.state.future_votes.is_empty());
+
+    interface
+        .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+        .await;
+    assert_eq!(interface.state.current_round, 1);
+    assert!(interface.state.prevotes.is_empty());
+}
+
+#[tokio::test]
+async fn test_proposal_for_next_round_is_handled_after_advancing() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+
+    let block = Block::new([0u8; 32], 1, vec![], vec![1]);
+    let proposed = block.clone();
+    let (chain_tx, mut chain_rx) = messenger::channel(10, Some(10));
+    tokio::spawn(async move {
+        while let Ok((message, reply)) = chain_rx.recv().await {
+            let response = match message {
+                abci::ChainMessage::GetProposedBlock { .. } => {
+                    abci::ChainResponse::GetProposedBlock {
+                        block: proposed.clone(),
+                    }
+                }
+                abci::ChainMessage::VerifyBlockDeposits { .. } => {
+                    abci::ChainResponse::VerifyBlockDeposits { error: None }
+                }
+                _ => abci::ChainResponse::FinalizeAndStoreBlock { error: None },
+            };
+            let _ = reply.send(response);
+        }
+    });
+    interface.set_chain_interface(chain_tx);
+    let (network_tx, _network_rx) = broadcast::channel(16);
+    interface.set_network_events_tx(network_tx);
+
+    let rotation = leader_rotation(&[PeerId::random(), PeerId::random(), PeerId::random()]);
+    let (local, next_leader) = (rotation[0], rotation[1]);
+    interface.set_peer_id(local);
+    for validator in &rotation {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+
+    // The round 1 leader proposes before this node has left round 0.
+    interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: next_leader.to_bytes(),
+            raw_block: block.serialize().unwrap(),
+            tx_hashes: None,
+        })
+        .await;
+    assert_eq!(interface.state.future_proposals.len(), 1);
+    assert_eq!(
+        interface.state.current_state,
+        ConsensusPhase::WaitingForPropose
+    );
+    assert!(interface.state.prevotes.is_empty());
+
+    interface
+        .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+        .await;
+
+    assert_eq!(interface.state.current_round, 1);
+    assert!(interface.state.future_proposals.is_empty());
+    assert_eq!(interface.state.current_state, ConsensusPhase::Prevote);
+    assert_eq!(interface.state.proposed_block, Some(block));
+    assert!(interface.state.prevotes.contains(&local));
+}
+
+#[tokio::test]
+async fn test_silent_leader_is_nil_prevoted_after_propose_timeout() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+    let (network_tx, mut network_rx) = broadcast::channel(16);
+    interface.set_network_events_tx(network_tx);
+    let clock = MockClock::new();
+    interface.set_clock(Arc::new(clock.clone()));
+
+    let rotation = leader_rotation(&[PeerId::random(), PeerId::random(), PeerId::random()]);
+    let (leader, local) = (rotation[1], rotation[0]);
+    interface.set_peer_id(local);
+    for validator in &rotation {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+    interface
+        .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+        .await;
+    assert_eq!(interface.state.proposer, Some(leader));
+    let propose_timeout = interface.state.propose_timeout;
+    assert!(propose_timeout < interface.state.round_timeout);
+
+    // The leader stays silent; just before the proposal timeout we keep waiting.
+    clock.advance(propose_timeout - Duration::from_millis(1));
+    interface.check_propose_timeout().await.unwrap();
+    assert_eq!(
+        interface.state.current_state,
+        ConsensusPhase::WaitingForPropose
+    );
+    assert!(network_rx.try_recv().is_err());
+
+    // Once it passes we prevote nil, long before the round itself would time out.
+    clock.advance(Duration::from_millis(1));
+    interface.check_propose_timeout().await.unwrap();
+    assert_eq!(interface.state.current_state, ConsensusPhase::Prevote);
+    assert!(interface.state.nil_prevotes.contains(&local));
+    assert!(interface.state.prevotes.is_empty());
+    let Ok(types::network::network_event::NetworkEvent::SendBroadcast {
+        message:
+            types::broadcast::BroadcastMessage::Consensus(types::consensus::ConsensusMessage::Vote(
+                vote,
+            )),
+    }) = network_rx.try_recv()
+    else {
+        panic!("expected a broadcast vote");
+    };
+    assert!(vote.is_nil());
+    assert!(matches!(vote.vote_type, VoteType::Prevote));
+    assert_eq!(vote.round, 1);
+
+    // A late proposal is not prevoted on top of the nil vote.
+    let block = Block::new([0u8; 32], 1, vec![], vec![1]);
+    interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: leader.to_bytes(),
+            raw_block: block.serialize().unwrap(),
+            tx_hashes: None,
+        })
+        .await;
+    assert!(interface.state.prevotes.is_empty());
+    assert!(network_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_round_times_out_when_mock_clock_advances() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+    let clock = MockClock::new();
+    interface.set_clock(Arc::new(clock.clone()));
+
+    let rotation = leader_rotation(&[PeerId::random(), PeerId::random(), PeerId::random()]);
+    interface.set_peer_id(rotation[0]);
+    for validator in &rotation {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+    interface
+        .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+        .await;
+    assert_eq!(interface.state.current_round, 1);
+    let round_timeout = interface.state.round_timeout;
+
+    clock.advance(round_timeout - Duration::from_millis(1));
+    interface.check_round_timeout().await.unwrap();
+    assert_eq!(interface.state.current_round, 1);
+
+    // No real time passes; the mock clock alone expires the round.
+    clock.advance(Duration::from_millis(1));
+    interface.check_round_timeout().await.unwrap();
+    assert_eq!(interface.state.current_round, 2);
+    assert_eq!(interface.state.round_start_time, Some(clock.now()));
+    assert_eq!(
+        interface.state.current_state,
+        ConsensusPhase::WaitingForPropose
+    );
+}
+
+#[tokio::test]
+async fn test_consensus_pauses_below_quorum_and_resumes_when_peers_return() {
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+    let (network_tx, mut network_rx) = broadcast::channel(16);
+    interface.set_network_events_tx(network_tx);
+    let clock = MockClock::new();
+    interface.set_clock(Arc::new(clock.clone()));
+
+    let rotation = leader_rotation(&[PeerId::random(), PeerId::random(), PeerId::random()]);
+    let local = rotation[0];
+    interface.set_peer_id(local);
+    for validator in &rotation {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+    interface
+        .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+        .await;
+    assert_eq!(interface.state.current_round, 1);
+    assert_eq!(interface.state.quorum(), 2);
+
+    // Losing one of three validators still leaves a quorum reachable.
+    interface
+        .handle_message(ConsensusMessage::PeerDisconnected {
+            peer_id: rotation[1].to_bytes(),
+        })
+        .await;
+    assert!(!interface.state.quorum_lost);
+
+    interface
+        .handle_message(ConsensusMessage::PeerDisconnected {
+            peer_id: rotation[2].to_bytes(),
+        })
+        .await;
+    assert!(interface.state.quorum_lost);
+    assert_eq!(interface.state.reachable_validators(), 1);
+
+    // While paused, timeouts neither prevote nil nor spin to new rounds.
+    while network_rx.try_recv().is_ok() {}
+    clock.advance(interface.state.round_timeout * 3);
+    interface.check_propose_timeout().await.unwrap();
+    interface.check_round_timeout().await.unwrap();
+    assert_eq!(interface.state.current_round, 1);
+    assert_eq!(
+        interface.state.current_state,
+        ConsensusPhase::WaitingForPropose
+    );
+    assert!(interface.state.nil_prevotes.is_empty());
+    assert!(network_rx.try_recv().is_err());
+    let ConsensusResponse::TriggerConsensusRound { success, .. } = interface
+        .handle_message(ConsensusMessage::TriggerConsensusRound { force_round: true })
+        .await
+    else {
+        panic!("Unexpected response type");
+    };
+    assert!(!success);
+    assert_eq!(interface.state.current_round, 1);
+
+    // Reconnecting one validator restores the quorum and starts a fresh round at once.
+    interface
+        .handle_message(ConsensusMessage::PeerConnected {
+            peer_id: rotation[2].to_bytes(),
+        })
+        .await;
+    assert!(!interface.state.quorum_lost);
+    assert_eq!(interface.state.current_round, 2);
+    assert_eq!(interface.state.round_start_time, Some(clock.now()));
+
+    // Consensus runs normally again.
+    clock.advance(interface.state.propose_timeout);
+    interface.check_propose_timeout().await.unwrap();
+    assert!(interface.state.nil_prevotes.contains(&local));
+}
+
+/// Runs one round on a cluster of `size` validators whose events are routed in memory, and
+/// returns how many vote messages were delivered and which nodes finalized the block.
+async fn run_vote_cluster(size: usize, vote_aggregation: bool) -> (usize, Vec<bool>) {
+    use libp2p::identity::Keypair;
+    use types::broadcast::BroadcastMessage;
+    use types::consensus::ConsensusMessage as NetMessage;
+    use types::network::network_event::{DirectMessage, NetworkEvent};
+
+    let block = Block::new([0u8; 32], 1, vec![], vec![1]);
+    let keypairs: Vec<Keypair> = (0..size).map(|_| Keypair::generate_ed25519()).collect();
+    let peer_ids: Vec<PeerId> = keypairs.iter().map(|k| k.public().to_peer_id()).collect();
+
+    let mut nodes = Vec::new();
+    let mut events = Vec::new();
+    for keypair in &keypairs {
+        let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+        let proposed = block.clone();
+        let (chain_tx, mut chain_rx) = messenger::channel(10, Some(10));
+        tokio::spawn(async move {
+            while let Ok((message, reply)) = chain_rx.recv().await {
+                let response = match message {
+                    abci::ChainMessage::GetProposedBlock { .. } => {
+                        abci::ChainResponse::GetProposedBlock {
+                            block: proposed.clone(),
+                        }
+                    }
+                    abci::ChainMessage::GetBlockForTransactions { .. } => {
+                        abci::ChainResponse::GetBlockForTransactions {
+                            block: Ok(proposed.clone()),
+                        }
+                    }
+                    abci::ChainMessage::VerifyBlockDeposits { .. } => {
+                        abci::ChainResponse::VerifyBlockDeposits { error: None }
+                    }
+                    _ => abci::ChainResponse::FinalizeAndStoreBlock { error: None },
+                };
+                let _ = reply.send(response);
+            }
+        });
+        interface.set_chain_interface(chain_tx);
+        let (network_tx, network_rx) = broadcast::channel(256);
+        interface.set_network_events_tx(network_tx);
+        interface.set_peer_id(keypair.public().to_peer_id());
+        interface.set_keypair(keypair.clone());
+        interface.set_vote_aggregation(vote_aggregation);
+        for validator in &peer_ids {
+            interface
+                .handle_message(ConsensusMessage::AddValidator {
+                    peer_id: validator.to_bytes(),
+                })
+                .await;
+        }
+        interface
+            .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+            .await;
+        nodes.push(interface);
+        events.push(network_rx);
+    }
+
+    let leader = nodes[0].state.proposer.unwrap();
+    let leader_index = peer_ids.iter().position(|p| *p == leader).unwrap();
+    nodes[leader_index].propose_block_as_leader().await.unwrap();
+
+    let mut vote_messages = 0;
+    loop {
+        let mut pending = Vec::new();
+        for (index, rx) in events.iter_mut().enumerate() {
+            while let Ok(event) = rx.try_recv() {
+                pending.push((index, event));
+            }
+        }
+        if pending.is_empty() {
+            break;
+        }
+
+        for (from, event) in pending {
+            let sender = peer_ids[from].to_bytes();
+            match event {
+                NetworkEvent::SendBroadcast {
+                    message: BroadcastMessage::Consensus(message),
+                } => {
+                    let to_deliver = match message {
+                        NetMessage::LeaderAnnouncement(announcement) => {
+                            ConsensusMessage::HandleLeaderAnnouncement {
+                                sender: sender.clone(),
+                                leader: announcement.leader,
+                                round: announcement.round,
+                            }
+                        }
+                        NetMessage::BlockProposal {
+                            proposer,
+                            raw_block,
+                            tx_hashes,
+                            ..
+                        } => ConsensusMessage::HandleBlockProposal {
+                            sender: proposer,
+                            raw_block,
+                            tx_hashes: Some(tx_hashes),
+                        },
+                        NetMessage::Vote(vote) => {
+                            vote_messages += size - 1;
+                            ConsensusMessage::HandleVote {
+                                sender: sender.clone(),
+                                vote,
+                            }
+                        }
+                        NetMessage::VoteAggregate(aggregate) => {
+                            vote_messages += size - 1;
+                            ConsensusMessage::HandleVoteAggregate { aggregate }
+                        }
+                        NetMessage::NewRound(_) => continue,
+                    };
+                    for (index, node) in nodes.iter_mut().enumerate() {
+                        if index != from {
+                            node.handle_message(to_deliver.clone()).await;
+                        }
+                    }
+                }
+                NetworkEvent::SendPrivateMessage {
+                    peer_id,
+                    message: DirectMessage::ConsensusVote { vote, signature },
+                } => {
+                    vote_messages += 1;
+                    let to = peer_ids.iter().position(|p| *p == peer_id).unwrap();
+                    let response = nodes[to]
+                        .handle_message(ConsensusMessage::HandleSignedVote {
+                            sender,
+                            vote,
+                            signature,
+                        })
+                        .await;
+                    assert!(matches!(
+                        response,
+                        ConsensusResponse::HandleSignedVote { error: None, .. }
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let finalized = nodes
+        .iter()
+        .map(|node| node.state.finalized_blocks.get(&1) == Some(&block.hash()))
+        .collect();
+    (vote_messages, finalized)
+}
+
+#[tokio::test]
+async fn test_vote_aggregation_finalizes_with_fewer_messages() {
+    const VALIDATORS: usize = 7;
+
+    let (individual_messages, individual_finalized) = run_vote_cluster(VALIDATORS, false).await;
+    let (aggregated_messages, aggregated_finalized) = run_vote_cluster(VALIDATORS, true).await;
+
+    assert!(individual_finalized.iter().all(|finalized| *finalized));
+    assert!(aggregated_finalized.iter().all(|finalized| *finalized));
+    assert!(
+        aggregated_messages < individual_messages,
+        "aggregated votes took {aggregated_messages} messages, individual gossip {individual_messages}"
+    );
+}
+
+#[tokio::test]
+async fn test_vote_aggregate_with_forged_signature_is_rejected() {
+    use libp2p::identity::Keypair;
+    use types::consensus::VoteAggregate;
+
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+    let keypairs: Vec<Keypair> = (0..3).map(|_| Keypair::generate_ed25519()).collect();
+    for keypair in &keypairs {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: keypair.public().to_peer_id().to_bytes(),
+            })
+            .await;
+    }
+    interface
+        .handle_message(ConsensusMessage::StartNewRound { round: 1 })
+        .await;
+
+    let now = unix_timestamp();
+    let vote = |keypair: &Keypair| Vote {
+        round: 1,
+        height: 0,
+        block_hash: vec![7; 32],
+        voter: keypair.public().to_peer_id().to_bytes(),
+        vote_type: VoteType::Prevote,
+        timestamp: now,
+    };
+    // The second signer's vote is signed by the first signer's key.
+    let aggregate = VoteAggregate {
+        round: 1,
+        height: 0,
+        block_hash: vec![7; 32],
+        vote_type: VoteType::Prevote,
+        signers: vec![vote(&keypairs[0]).voter, vote(&keypairs[1]).voter],
+        signatures: vec![
+            vote(&keypairs[0]).sign(&keypairs[0]).unwrap(),
+            vote(&keypairs[1]).sign(&keypairs[0]).unwrap(),
+        ],
+        timestamps: vec![now, now],
+    };
+
+    let response = interface
+        .handle_message(ConsensusMessage::HandleVoteAggregate { aggregate })
+        .await;
+    assert!(matches!(
+        response,
+        ConsensusResponse::HandleVoteAggregate { error: Some(_), .. }
+    ));
+    assert!(interface.state.prevotes.is_empty());
+}
+
+#[tokio::test]
+async fn test_follower_with_extra_mempool_transactions_prevotes_leaders_set() {
+    use abci::chain_state::ChainState;
+    use protocol::transaction::{Operation, Transaction, TransactionType};
+
+    let transaction = |value: u8| {
+        Transaction::new(
+            TransactionType::Deposit,
+            vec![Operation::OpPush { value: vec![value] }],
+            None,
+        )
+    };
+
+    let rotation = leader_rotation(&[PeerId::random(), PeerId::random(), PeerId::random()]);
+    let (leader, local, peer) = (rotation[0], rotation[1], rotation[2]);
+
+    let mut leader_mempool = ChainState::new();
+    leader_mempool.add_transaction_to_block(transaction(1));
+    let leader_block = leader_mempool.get_proposed_block(None, leader.to_bytes());
+
+    // Our mempool also holds a transaction the leader has not seen yet.
+    let mut local_mempool = ChainState::new();
+    local_mempool.add_transaction_to_block(transaction(1));
+    local_mempool.add_transaction_to_block(transaction(2));
+    let (chain_tx, mut chain_rx) = messenger::channel(10, Some(10));
+    let (pending_tx, mut pending_rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((message, reply)) = chain_rx.recv().await {
+            let response = match message {
+                abci::ChainMessage::GetProposedBlock {
+                    previous_block,
+                    proposer,
+                } => abci::ChainResponse::GetProposedBlock {
+                    block: local_mempool.get_proposed_block(previous_block, proposer),
+                },
+                abci::ChainMessage::GetBlockForTransactions {
+                    previous_block,
+                    proposer,
+                    tx_ids,
+                } => abci::ChainResponse::GetBlockForTransactions {
+                    block: local_mempool.get_block_for_transactions(
+                        previous_block,
+                        proposer,
+                        &tx_ids,
+                    ),
+                },
+                abci::ChainMessage::VerifyBlockDeposits { .. } => {
+                    abci::ChainResponse::VerifyBlockDeposits { error: None }
+                }
+                abci::ChainMessage::FinalizeBlock { block } => {
+                    local_mempool.remove_pending_transactions(&block.body.transactions);
+                    let _ = pending_tx.send(local_mempool.get_pending_transactions().to_vec());
+                    abci::ChainResponse::FinalizeAndStoreBlock { error: None }
+                }
+                _ => abci::ChainResponse::FinalizeAndStoreBlock { error: None },
+            };
+            let _ = reply.send(response);
+        }
+    });
+
+    let (mut interface, _tx) = ConsensusInterfaceImpl::new();
+    interface.set_chain_interface(chain_tx);
+    interface.set_peer_id(local);
+    for validator in &rotation {
+        interface
+            .handle_message(ConsensusMessage::AddValidator {
+                peer_id: validator.to_bytes(),
+            })
+            .await;
+    }
+
+    // Rebuilt from our own mempool, the block differs from the leader's.
+    interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: leader.to_bytes(),
+            raw_block: leader_block.serialize().unwrap(),
+            tx_hashes: None,
+        })
+        .await;
+    assert!(interface.state.prevotes.is_empty());
+
+    // A commitment that does not match the block's transactions is rejected outright.
+    interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: leader.to_bytes(),
+            raw_block: leader_block.serialize().unwrap(),
+            tx_hashes: Some(vec![transaction(2).id().to_vec()]),
+        })
+        .await;
+    assert!(interface.state.prevotes.is_empty());
+
+    // Rebuilt from exactly the transactions the leader selected, it matches.
+    let tx_hashes = leader_block
+        .body
+        .transactions
+        .iter()
+        .map(|tx| tx.id().to_vec())
+        .collect();
+    interface
+        .handle_message(ConsensusMessage::HandleBlockProposal {
+            sender: leader.to_bytes(),
+            raw_block: leader_block.serialize().unwrap(),
+            tx_hashes: Some(tx_hashes),
+        })
+        .await;
+    assert!(interface.state.prevotes.contains(&local));
+    assert_eq!(interface.state.current_state, ConsensusPhase::Prevote);
+
+    let vote = |voter: &PeerId, vote_type: VoteType| ConsensusMessage::HandleVote {
+        sender: voter.to_bytes(),
+        vote: Vote {
+            round: 0,
+            height: 0,
+            block_hash: leader_block.hash().to_vec(),
+            voter: voter.to_bytes(),
+            vote_type,
+            timestamp: unix_timestamp(),
+        },
+    };
+    interface
+        .handle_message(vote(&leader, VoteType::Prevote))
+        .await;
+    interface
+        .handle_message(vote(&peer, VoteType::Precommit))
+        .await;
+
+    // The leader's block is finalized, not the one our mempool would have built.
+    assert!(interface.state.block_finalized);
+    assert_eq!(
+        interface.state.finalized_blocks.get(&1),
+        Some(&leader_block.hash())
+    );
+
+    // The transaction the leader left out is still pending for a later block.
+    assert_eq!(pending_rx.recv().await.unwrap(), vec![transaction(2)]);
+}
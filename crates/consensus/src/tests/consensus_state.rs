@@ -1,4 +1,4 @@
-use crate::{ConsensusPhase, ConsensusState};
+use crate::{ConsensusPhase, ConsensusState, FINALIZED_BLOCKS_WINDOW};
 use libp2p::PeerId;
 use std::collections::HashSet;
 use std::time::Duration;
@@ -13,6 +13,7 @@ fn test_consensus_state_new() {
     assert!(state.proposer.is_none());
     assert!(state.validators.is_empty());
     assert_eq!(state.round_timeout, Duration::from_secs(10));
+    assert!(state.propose_timeout < state.round_timeout);
     assert!(state.round_start_time.is_none());
     assert!(!state.is_leader);
     assert!(state.prevotes.is_empty());
@@ -86,6 +87,32 @@ fn test_select_leader_multiple_validators() {
     assert_eq!(leader1, leader4);
 }
 
+#[test]
+fn test_select_leader_sequence_agrees_across_nodes() {
+    let validators: Vec<PeerId> = (0..5).map(|_| PeerId::random()).collect();
+
+    // Each node learns about the validators in a different order.
+    let nodes: Vec<ConsensusState> = (0..validators.len())
+        .map(|shift| {
+            let mut state = ConsensusState::new();
+            for peer in validators.iter().cycle().skip(shift).take(validators.len()) {
+                state.validators.insert(*peer);
+            }
+            state
+        })
+        .collect();
+
+    let mut by_bytes = validators;
+    by_bytes.sort_by_key(|peer| peer.to_bytes());
+
+    for round in 0..10u32 {
+        let expected = by_bytes[round as usize % by_bytes.len()];
+        for state in &nodes {
+            assert_eq!(state.select_leader(round), Some(expected));
+        }
+    }
+}
+
 #[test]
 fn test_select_leader_round_robin() {
     let mut state = ConsensusState::new();
@@ -192,3 +219,32 @@ fn test_round_state_management() {
     assert!(state.is_leader);
     assert!(state.block_finalized);
 }
+
+#[test]
+fn test_finalized_blocks_are_kept_for_a_window_of_heights() {
+    let hash_at = |height: u64| {
+        let mut hash = [0u8; 32];
+        hash[..8].copy_from_slice(&height.to_be_bytes());
+        hash
+    };
+    let mut state = ConsensusState::new();
+
+    let tip = FINALIZED_BLOCKS_WINDOW * 3;
+    for height in 1..=tip {
+        state.record_finalized(height, hash_at(height));
+    }
+
+    let oldest_kept = tip - FINALIZED_BLOCKS_WINDOW;
+    assert!(
+        state
+            .finalized_blocks
+            .keys()
+            .all(|height| *height >= oldest_kept)
+    );
+    assert!(state.finalized_blocks.contains_key(&oldest_kept));
+    assert!(!state.finalized_blocks.contains_key(&(oldest_kept - 1)));
+
+    // A conflicting block inside the window is still caught.
+    assert!(state.check_finalization(oldest_kept, [0xff; 32]).is_err());
+    assert!(state.check_finalization(tip, hash_at(tip)).unwrap());
+}
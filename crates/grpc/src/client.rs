@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use tonic::transport::{Channel, Error};
+use tracing::warn;
+use types::proto::node_proto::node_control_client::NodeControlClient;
+
+/// How long a client keeps retrying to reach a node that may still be starting up.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectRetryPolicy {
+    /// Connection attempts before giving up, including the first one.
+    pub max_attempts: u32,
+    /// Wait after the first failed attempt; doubled after each further failure.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for ConnectRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+        }
+    }
+}
+
+/// Connects to the node control service at `endpoint`, retrying with exponential backoff
+/// per `policy`. Returns the last connection error once every attempt has failed.
+pub async fn connect_with_retry(
+    endpoint: String,
+    policy: &ConnectRetryPolicy,
+) -> Result<NodeControlClient<Channel>, Error> {
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match NodeControlClient::connect(endpoint.clone()).await {
+            Ok(client) => return Ok(client),
+            Err(e) if attempt >= policy.max_attempts => return Err(e),
+            Err(e) => {
+                warn!(
+                    "Failed to connect to {endpoint} (attempt {attempt}/{}), retrying in {backoff:?}: {e}",
+                    policy.max_attempts
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(policy.max_backoff);
+                attempt += 1;
+            }
+        }
+    }
+}
@@ -1,28 +1,85 @@
+use tokio::sync::broadcast;
 use tonic::{Request, Response, Status};
+use types::intents::DepositEvent;
 use types::network::network_protocol::NetworkHandle;
 
 use types::proto::node_proto::{
-    CheckBalanceRequest, CheckBalanceResponse, ConfirmWithdrawalRequest, ConfirmWithdrawalResponse,
-    CreateDepositIntentRequest, CreateDepositIntentResponse, GetChainInfoRequest,
-    GetChainInfoResponse, GetLatestBlocksRequest, GetLatestBlocksResponse,
-    GetPendingDepositIntentsRequest, GetPendingDepositIntentsResponse, ProposeWithdrawalRequest,
-    ProposeWithdrawalResponse, SpendFundsRequest, SpendFundsResponse, StartSigningRequest,
-    StartSigningResponse, TriggerConsensusRoundRequest, TriggerConsensusRoundResponse,
+    AddWatchAddressRequest, AddWatchAddressResponse, CancelDepositIntentRequest,
+    CancelDepositIntentResponse, CheckBalanceRequest, CheckBalanceResponse,
+    ConfirmWithdrawalRequest, ConfirmWithdrawalResponse, CreateDepositIntentRequest,
+    CreateDepositIntentResponse, GetActiveSigningSessionsRequest, GetActiveSigningSessionsResponse,
+    GetAuditLogRequest, GetAuditLogResponse, GetBlockRequest, GetBlockResponse,
+    GetChainInfoRequest, GetChainInfoResponse, GetFeeEstimatesRequest, GetFeeEstimatesResponse,
+    GetGenesisRequest, GetGenesisResponse, GetLatestBlocksRequest, GetLatestBlocksResponse,
+    GetMempoolRequest, GetMempoolResponse, GetPendingDepositIntentsRequest,
+    GetPendingDepositIntentsResponse, GetReserveAttestationRequest, GetReserveAttestationResponse,
+    GetValidatorStatsRequest, GetValidatorStatsResponse, GetVaultBalanceRequest,
+    GetVaultBalanceResponse, ProposeWithdrawalRequest, ProposeWithdrawalResponse,
+    RestartDkgRequest, RestartDkgResponse, RunSigningSelfTestRequest, RunSigningSelfTestResponse,
+    SpendFundsRequest, SpendFundsResponse, StartSigningRequest, StartSigningResponse,
+    SubscribeDepositsRequest, TriggerConsensusRoundRequest, TriggerConsensusRoundResponse,
+    VerifyDepositAddressRequest, VerifyDepositAddressResponse,
     node_control_server::{NodeControl, NodeControlServer},
 };
 
 use types::route_metrics;
 
-use crate::grpc_operator;
+use crate::grpc_operator::{self, DepositEventStream};
 
 pub struct NodeControlService {
     network: NetworkHandle,
+    deposit_events: broadcast::Sender<DepositEvent>,
+    /// Bearer token admin RPCs must present; they are refused outright when unset.
+    admin_token: Option<String>,
 }
 
 impl NodeControlService {
     #[must_use]
-    pub const fn new(network: NetworkHandle) -> Self {
-        Self { network }
+    pub const fn new(
+        network: NetworkHandle,
+        deposit_events: broadcast::Sender<DepositEvent>,
+    ) -> Self {
+        Self {
+            network,
+            deposit_events,
+            admin_token: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_admin_token(mut self, admin_token: Option<String>) -> Self {
+        self.admin_token = admin_token;
+        self
+    }
+
+    /// Checks the request's `authorization: Bearer <token>` header against the admin token.
+    #[allow(clippy::result_large_err)]
+    fn authorize_admin<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let Some(expected) = &self.admin_token else {
+            return Err(Status::permission_denied(
+                "Admin RPCs are disabled: no admin token is configured",
+            ));
+        };
+
+        let presented = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Missing admin bearer token"))?;
+
+        // Compare without short-circuiting so timing does not reveal a matching prefix.
+        let matches = presented.len() == expected.len()
+            && presented
+                .bytes()
+                .zip(expected.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0;
+        if matches {
+            Ok(())
+        } else {
+            Err(Status::unauthenticated("Invalid admin token"))
+        }
     }
 
     #[must_use]
@@ -33,6 +90,8 @@ impl NodeControlService {
 
 #[tonic::async_trait]
 impl NodeControl for NodeControlService {
+    type SubscribeDepositsStream = DepositEventStream;
+
     async fn spend_funds(
         &self,
         request: Request<SpendFundsRequest>,
@@ -55,6 +114,17 @@ impl NodeControl for NodeControlService {
         })
     }
 
+    async fn get_active_signing_sessions(
+        &self,
+        request: Request<GetActiveSigningSessionsRequest>,
+    ) -> Result<Response<GetActiveSigningSessionsResponse>, Status> {
+        route_metrics!("get_active_signing_sessions", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_active_signing_sessions(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
     async fn create_deposit_intent(
         &self,
         request: Request<CreateDepositIntentRequest>,
@@ -76,6 +146,50 @@ impl NodeControl for NodeControlService {
         })
     }
 
+    async fn add_watch_address(
+        &self,
+        request: Request<AddWatchAddressRequest>,
+    ) -> Result<Response<AddWatchAddressResponse>, Status> {
+        route_metrics!("add_watch_address", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::add_watch_address(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn verify_deposit_address(
+        &self,
+        request: Request<VerifyDepositAddressRequest>,
+    ) -> Result<Response<VerifyDepositAddressResponse>, Status> {
+        route_metrics!("verify_deposit_address", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::verify_deposit_address(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn cancel_deposit_intent(
+        &self,
+        request: Request<CancelDepositIntentRequest>,
+    ) -> Result<Response<CancelDepositIntentResponse>, Status> {
+        route_metrics!("cancel_deposit_intent", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::cancel_deposit_intent(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn subscribe_deposits(
+        &self,
+        request: Request<SubscribeDepositsRequest>,
+    ) -> Result<Response<Self::SubscribeDepositsStream>, Status> {
+        route_metrics!("subscribe_deposits", async {
+            let req = request.into_inner();
+            let stream = grpc_operator::subscribe_deposits(&self.deposit_events, req);
+            Ok(Response::new(stream))
+        })
+    }
+
     async fn propose_withdrawal(
         &self,
         request: Request<ProposeWithdrawalRequest>,
@@ -109,6 +223,28 @@ impl NodeControl for NodeControlService {
         })
     }
 
+    async fn get_vault_balance(
+        &self,
+        request: Request<GetVaultBalanceRequest>,
+    ) -> Result<Response<GetVaultBalanceResponse>, Status> {
+        route_metrics!("get_vault_balance", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_vault_balance(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn get_fee_estimates(
+        &self,
+        request: Request<GetFeeEstimatesRequest>,
+    ) -> Result<Response<GetFeeEstimatesResponse>, Status> {
+        route_metrics!("get_fee_estimates", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_fee_estimates(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
     async fn get_chain_info(
         &self,
         request: Request<GetChainInfoRequest>,
@@ -141,4 +277,93 @@ impl NodeControl for NodeControlService {
             Ok(Response::new(resp))
         })
     }
+
+    async fn get_block(
+        &self,
+        request: Request<GetBlockRequest>,
+    ) -> Result<Response<GetBlockResponse>, Status> {
+        route_metrics!("get_block", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_block(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn get_mempool(
+        &self,
+        request: Request<GetMempoolRequest>,
+    ) -> Result<Response<GetMempoolResponse>, Status> {
+        route_metrics!("get_mempool", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_mempool(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn get_audit_log(
+        &self,
+        request: Request<GetAuditLogRequest>,
+    ) -> Result<Response<GetAuditLogResponse>, Status> {
+        route_metrics!("get_audit_log", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_audit_log(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn get_genesis(
+        &self,
+        request: Request<GetGenesisRequest>,
+    ) -> Result<Response<GetGenesisResponse>, Status> {
+        route_metrics!("get_genesis", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_genesis(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn get_validator_stats(
+        &self,
+        request: Request<GetValidatorStatsRequest>,
+    ) -> Result<Response<GetValidatorStatsResponse>, Status> {
+        route_metrics!("get_validator_stats", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_validator_stats(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn get_reserve_attestation(
+        &self,
+        request: Request<GetReserveAttestationRequest>,
+    ) -> Result<Response<GetReserveAttestationResponse>, Status> {
+        route_metrics!("get_reserve_attestation", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::get_reserve_attestation(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn run_signing_self_test(
+        &self,
+        request: Request<RunSigningSelfTestRequest>,
+    ) -> Result<Response<RunSigningSelfTestResponse>, Status> {
+        route_metrics!("run_signing_self_test", async {
+            let req = request.into_inner();
+            let resp = grpc_operator::run_signing_self_test(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
+
+    async fn restart_dkg(
+        &self,
+        request: Request<RestartDkgRequest>,
+    ) -> Result<Response<RestartDkgResponse>, Status> {
+        route_metrics!("restart_dkg", async {
+            self.authorize_admin(&request)?;
+            let req = request.into_inner();
+            let resp = grpc_operator::restart_dkg(&self.network, req).await?;
+            Ok(Response::new(resp))
+        })
+    }
 }
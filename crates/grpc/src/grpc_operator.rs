@@ -1,17 +1,38 @@
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures::{Stream, stream};
+use tokio::sync::broadcast;
 use tonic::Status;
-use tracing::{debug, info};
-use types::intents::WithdrawlIntent;
+use tracing::{debug, info, warn};
+use types::intents::{DepositEvent, FeeBumpPolicy, WithdrawlIntent};
 use types::network::network_event::{SelfRequest, SelfResponse};
 use types::network::network_protocol::{Network, NetworkHandle};
 use types::proto::node_proto::{
-    self, BlockInfo, CheckBalanceRequest, CheckBalanceResponse, ConfirmWithdrawalRequest,
-    ConfirmWithdrawalResponse, CreateDepositIntentRequest, CreateDepositIntentResponse,
-    GetChainInfoRequest, GetChainInfoResponse, GetLatestBlocksRequest, GetLatestBlocksResponse,
-    GetPendingDepositIntentsResponse, ProposeWithdrawalRequest, ProposeWithdrawalResponse,
-    SpendFundsRequest, SpendFundsResponse, StartSigningRequest, StartSigningResponse,
-    TriggerConsensusRoundRequest, TriggerConsensusRoundResponse,
+    self, AddWatchAddressRequest, AddWatchAddressResponse, BlockInfo, CancelDepositIntentRequest,
+    CancelDepositIntentResponse, CheckBalanceRequest, CheckBalanceResponse,
+    ConfirmWithdrawalRequest, ConfirmWithdrawalResponse, CreateDepositIntentRequest,
+    CreateDepositIntentResponse, DepositEvent as DepositEventProto,
+    GetActiveSigningSessionsRequest, GetActiveSigningSessionsResponse, GetAuditLogRequest,
+    GetAuditLogResponse, GetBlockRequest, GetBlockResponse, GetChainInfoRequest,
+    GetChainInfoResponse, GetFeeEstimatesRequest, GetFeeEstimatesResponse, GetGenesisRequest,
+    GetGenesisResponse, GetLatestBlocksRequest, GetLatestBlocksResponse, GetMempoolRequest,
+    GetMempoolResponse, GetPendingDepositIntentsResponse, GetReserveAttestationRequest,
+    GetReserveAttestationResponse, GetValidatorStatsRequest, GetValidatorStatsResponse,
+    GetVaultBalanceRequest, GetVaultBalanceResponse, ProposeWithdrawalRequest,
+    ProposeWithdrawalResponse, RestartDkgRequest, RestartDkgResponse, RunSigningSelfTestRequest,
+    RunSigningSelfTestResponse, SpendFundsRequest, SpendFundsResponse, StartSigningRequest,
+    StartSigningResponse, SubscribeDepositsRequest, TriggerConsensusRoundRequest,
+    TriggerConsensusRoundResponse, VerifyDepositAddressRequest, VerifyDepositAddressResponse,
 };
 
+/// How long a signing self-test may take before it is reported as failed, since a session
+/// whose signers stop answering never completes.
+pub const SIGNING_SELF_TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub type DepositEventStream =
+    Pin<Box<dyn Stream<Item = Result<DepositEventProto, Status>> + Send + 'static>>;
+
 pub async fn spend_funds(
     network: &NetworkHandle,
     request: SpendFundsRequest,
@@ -27,6 +48,8 @@ pub async fn spend_funds(
                 fee: 200,
                 address_to,
                 user_pubkey: String::new(),
+                fee_bump: None,
+                challenge: None,
             },
             true,
         )
@@ -95,6 +118,7 @@ pub async fn create_deposit_intent(
             SelfRequest::CreateDeposit {
                 user_pubkey: req.public_key,
                 amount_sat,
+                min_confirmations: req.min_confirmations,
             },
             true,
         )
@@ -124,6 +148,140 @@ pub async fn create_deposit_intent(
     })
 }
 
+/// Streams deposit events whose address is in `address_filter`, or all events when it is empty.
+///
+/// A subscriber that falls behind skips the events it missed rather than ending the stream.
+#[must_use]
+pub fn subscribe_deposits(
+    deposit_events: &broadcast::Sender<DepositEvent>,
+    request: SubscribeDepositsRequest,
+) -> DepositEventStream {
+    let receiver = deposit_events.subscribe();
+    let address_filter = request.address_filter;
+
+    Box::pin(stream::unfold(
+        (receiver, address_filter),
+        |(mut receiver, address_filter)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.matches(&address_filter) => {
+                        let event = DepositEventProto {
+                            txid: event.txid,
+                            address: event.address,
+                            amount_satoshis: event.amount_sat,
+                            confirmations: event.confirmations,
+                            status: event.status.as_str().to_string(),
+                        };
+                        return Some((Ok(event), (receiver, address_filter)));
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Deposit subscriber lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        },
+    ))
+}
+
+pub async fn add_watch_address(
+    network: &impl Network,
+    request: AddWatchAddressRequest,
+) -> Result<AddWatchAddressResponse, Status> {
+    if request.address.is_empty() {
+        return Err(Status::invalid_argument("Address must not be empty"));
+    }
+
+    let response = network
+        .send_self_request(
+            SelfRequest::AddWatchAddress {
+                address: request.address,
+            },
+            true,
+        )
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    match response {
+        SelfResponse::AddWatchAddressResponse {
+            deposit_tracking_id,
+        } => Ok(AddWatchAddressResponse {
+            success: true,
+            deposit_tracking_id,
+        }),
+        SelfResponse::NodeError(e) => Err(Status::invalid_argument(e.to_string())),
+        _ => Err(Status::internal("Invalid response from node")),
+    }
+}
+
+pub async fn verify_deposit_address(
+    network: &impl Network,
+    request: VerifyDepositAddressRequest,
+) -> Result<VerifyDepositAddressResponse, Status> {
+    if request.deposit_address.is_empty() {
+        return Err(Status::invalid_argument(
+            "Deposit address must not be empty",
+        ));
+    }
+
+    let response = network
+        .send_self_request(
+            SelfRequest::VerifyDepositAddress {
+                deposit_address: request.deposit_address,
+            },
+            true,
+        )
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    match response {
+        SelfResponse::VerifyDepositAddressResponse { verified } => {
+            Ok(VerifyDepositAddressResponse { verified })
+        }
+        SelfResponse::NodeError(e) => Err(Status::not_found(e.to_string())),
+        _ => Err(Status::internal("Invalid response from node")),
+    }
+}
+
+pub async fn cancel_deposit_intent(
+    network: &impl Network,
+    request: CancelDepositIntentRequest,
+) -> Result<CancelDepositIntentResponse, Status> {
+    if request.deposit_tracking_id.is_empty() {
+        return Err(Status::invalid_argument(
+            "Deposit tracking id must not be empty",
+        ));
+    }
+
+    let response = network
+        .send_self_request(
+            SelfRequest::CancelDepositIntent {
+                deposit_tracking_id: request.deposit_tracking_id,
+            },
+            true,
+        )
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    match response {
+        SelfResponse::CancelDepositIntentResponse { deposit_address } => {
+            Ok(CancelDepositIntentResponse {
+                success: true,
+                deposit_address,
+            })
+        }
+        SelfResponse::NodeError(e) => Err(Status::failed_precondition(e.to_string())),
+        _ => Err(Status::internal("Invalid response from node")),
+    }
+}
+
 pub async fn get_pending_deposit_intents(
     network: &impl Network,
 ) -> Result<GetPendingDepositIntentsResponse, Status> {
@@ -146,6 +304,8 @@ pub async fn get_pending_deposit_intents(
                 deposit_tracking_id: intent.deposit_tracking_id.clone(),
                 deposit_address: intent.deposit_address.clone(),
                 timestamp: intent.timestamp,
+                expires_at: intent.expires_at,
+                min_confirmations: intent.min_confirmations,
             })
             .collect(),
     })
@@ -163,11 +323,30 @@ pub async fn propose_withdrawal(
         ));
     };
 
+    let fee_bump = match request.fee_bump {
+        Some(policy) if policy.after_blocks == 0 || policy.max_fee_satoshis == 0 => {
+            return Err(Status::invalid_argument(
+                "Fee bump policy needs a positive block count and fee cap",
+            ));
+        }
+        Some(policy) => Some(FeeBumpPolicy {
+            after_blocks: policy.after_blocks,
+            max_fee_sat: policy.max_fee_satoshis,
+        }),
+        None => None,
+    };
+
+    if request.fee_rate_sat_vb == Some(0) {
+        return Err(Status::invalid_argument("Fee rate must be positive"));
+    }
+
     let withdrawal_intent = WithdrawlIntent {
         amount_sat,
         address_to: request.address_to,
         public_key: request.public_key,
         blocks_to_confirm: request.blocks_to_confirm.map(|b| u16::try_from(b).unwrap()),
+        fee_rate_sat_vb: request.fee_rate_sat_vb,
+        fee_bump,
     };
 
     let response = network
@@ -238,6 +417,88 @@ pub async fn check_balance(
     Ok(CheckBalanceResponse { balance_satoshis })
 }
 
+pub async fn get_active_signing_sessions(
+    network: &impl Network,
+    _request: GetActiveSigningSessionsRequest,
+) -> Result<GetActiveSigningSessionsResponse, Status> {
+    let response = network
+        .send_self_request(SelfRequest::GetActiveSigningSessions, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::GetActiveSigningSessionsResponse { sessions } = response else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+
+    Ok(GetActiveSigningSessionsResponse {
+        sessions: sessions
+            .into_iter()
+            .map(|session| node_proto::SigningSession {
+                sign_id: session.sign_id,
+                message_hex: session.message_hex,
+                selected_peer_count: session.selected_peer_count,
+                commitments_received: session.commitments_received,
+                shares_received: session.shares_received,
+                is_coordinator: session.is_coordinator,
+                elapsed_secs: session.elapsed_secs,
+            })
+            .collect(),
+    })
+}
+
+pub async fn get_vault_balance(
+    network: &impl Network,
+    _request: GetVaultBalanceRequest,
+) -> Result<GetVaultBalanceResponse, Status> {
+    let response = network
+        .send_self_request(SelfRequest::GetVaultBalance, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::GetVaultBalanceResponse {
+        spendable_satoshis,
+        total_satoshis,
+    } = response
+    else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+
+    Ok(GetVaultBalanceResponse {
+        spendable_satoshis,
+        total_satoshis,
+    })
+}
+
+pub async fn get_fee_estimates(
+    network: &impl Network,
+    _request: GetFeeEstimatesRequest,
+) -> Result<GetFeeEstimatesResponse, Status> {
+    let response = network
+        .send_self_request(SelfRequest::GetFeeEstimates, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::GetFeeEstimatesResponse { estimates } = response else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+
+    Ok(GetFeeEstimatesResponse {
+        estimates: estimates
+            .into_iter()
+            .map(|estimate| node_proto::FeeEstimate {
+                target_blocks: u32::from(estimate.target_blocks),
+                sat_per_vb: estimate.sat_per_vb,
+            })
+            .collect(),
+    })
+}
+
 pub async fn get_chain_info(
     network: &impl Network,
     _request: GetChainInfoRequest,
@@ -343,3 +604,254 @@ pub async fn get_latest_blocks(
         blocks: block_infos,
     })
 }
+
+pub async fn get_block(
+    network: &impl Network,
+    request: GetBlockRequest,
+) -> Result<GetBlockResponse, Status> {
+    let height = request.height;
+
+    let response = network
+        .send_self_request(SelfRequest::GetBlock { height }, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::GetBlockResponse { block_json } = response else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+
+    let block_json =
+        block_json.ok_or_else(|| Status::not_found(format!("No block at height {height}")))?;
+
+    Ok(GetBlockResponse { block_json })
+}
+
+pub async fn get_mempool(
+    network: &impl Network,
+    _request: GetMempoolRequest,
+) -> Result<GetMempoolResponse, Status> {
+    let response = network
+        .send_self_request(SelfRequest::GetMempool, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::GetMempoolResponse { transactions } = response else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+
+    Ok(GetMempoolResponse {
+        transactions: transactions
+            .into_iter()
+            .map(|tx| node_proto::MempoolTransaction {
+                hash: tx.hash,
+                transaction_json: tx.transaction_json,
+            })
+            .collect(),
+    })
+}
+
+pub async fn get_audit_log(
+    network: &impl Network,
+    request: GetAuditLogRequest,
+) -> Result<GetAuditLogResponse, Status> {
+    let to = if request.to == 0 {
+        u64::MAX
+    } else {
+        request.to
+    };
+    if request.from > to {
+        return Err(Status::invalid_argument("`from` must not be after `to`"));
+    }
+
+    let response = network
+        .send_self_request(
+            SelfRequest::GetAuditLog {
+                from: request.from,
+                to,
+            },
+            true,
+        )
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::GetAuditLogResponse { entries } = response else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+
+    Ok(GetAuditLogResponse {
+        entries: entries
+            .into_iter()
+            .map(|entry| node_proto::AuditLogEntry {
+                sequence: entry.sequence,
+                timestamp: entry.timestamp,
+                kind: entry.kind.as_str().to_string(),
+                amount_satoshis: entry.amount_sat,
+                address: entry.address,
+                reference: entry.reference,
+                outcome: entry.outcome.as_str().to_string(),
+                reason: entry.outcome.reason().unwrap_or_default().to_string(),
+            })
+            .collect(),
+    })
+}
+
+pub async fn get_genesis(
+    network: &impl Network,
+    _request: GetGenesisRequest,
+) -> Result<GetGenesisResponse, Status> {
+    let response = network
+        .send_self_request(SelfRequest::GetGenesis, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::GetGenesisResponse { genesis } = response else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+    let genesis = genesis.ok_or_else(|| Status::not_found("No genesis block created yet"))?;
+
+    Ok(GetGenesisResponse {
+        genesis_hash: genesis.genesis_hash,
+        timestamp: genesis.timestamp,
+        vault_pub_key: genesis.vault_pub_key,
+        min_signers: u32::from(genesis.min_signers),
+        max_signers: u32::from(genesis.max_signers),
+        min_stake: genesis.min_stake,
+        block_time_seconds: genesis.block_time_seconds,
+        max_block_size: genesis.max_block_size,
+        validators: genesis
+            .validators
+            .into_iter()
+            .map(|(pub_key, stake)| node_proto::GenesisValidator { pub_key, stake })
+            .collect(),
+    })
+}
+
+pub async fn get_validator_stats(
+    network: &impl Network,
+    _request: GetValidatorStatsRequest,
+) -> Result<GetValidatorStatsResponse, Status> {
+    let response = network
+        .send_self_request(SelfRequest::GetValidatorStats, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::GetValidatorStatsResponse { validators } = response else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+
+    Ok(GetValidatorStatsResponse {
+        validators: validators
+            .into_iter()
+            .map(|stats| node_proto::ValidatorStats {
+                peer_id: stats.peer_id,
+                proposals: stats.proposals,
+                votes: stats.votes,
+                signing_sessions: stats.signing_sessions,
+                last_seen: stats.last_seen,
+            })
+            .collect(),
+    })
+}
+
+pub async fn get_reserve_attestation(
+    network: &impl Network,
+    _request: GetReserveAttestationRequest,
+) -> Result<GetReserveAttestationResponse, Status> {
+    let response = network
+        .send_self_request(SelfRequest::GetReserveAttestation, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let attestation = match response {
+        SelfResponse::GetReserveAttestationResponse { attestation } => attestation,
+        SelfResponse::NodeError(e) => return Err(Status::failed_precondition(e.to_string())),
+        _ => return Err(Status::internal("Invalid response from node")),
+    };
+
+    Ok(GetReserveAttestationResponse {
+        height: attestation.height,
+        utxo_commitment: hex::encode(attestation.utxo_commitment()),
+        digest: hex::encode(attestation.digest()),
+        signature: hex::encode(&attestation.signature),
+        total_value_satoshis: attestation.total_value,
+        utxos: attestation
+            .utxos
+            .into_iter()
+            .map(|utxo| node_proto::ReserveUtxo {
+                txid: utxo.outpoint.txid.to_string(),
+                vout: utxo.outpoint.vout,
+                value_satoshis: utxo.value_sat,
+                script_pubkey: hex::encode(utxo.script_pubkey.as_bytes()),
+            })
+            .collect(),
+    })
+}
+
+pub async fn run_signing_self_test(
+    network: &impl Network,
+    _request: RunSigningSelfTestRequest,
+) -> Result<RunSigningSelfTestResponse, Status> {
+    let pending = network
+        .send_self_request(SelfRequest::RunSigningSelfTest, true)
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?;
+
+    let Ok(response) = tokio::time::timeout(SIGNING_SELF_TEST_TIMEOUT, pending).await else {
+        return Ok(RunSigningSelfTestResponse {
+            passed: false,
+            message: "Signing session did not complete in time".to_string(),
+            elapsed_ms: u64::try_from(SIGNING_SELF_TEST_TIMEOUT.as_millis()).unwrap_or(u64::MAX),
+        });
+    };
+    let response = response.map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    match response {
+        SelfResponse::RunSigningSelfTestResponse {
+            passed,
+            message,
+            elapsed_ms,
+        } => Ok(RunSigningSelfTestResponse {
+            passed,
+            message,
+            elapsed_ms,
+        }),
+        SelfResponse::NodeError(e) => Err(Status::failed_precondition(e.to_string())),
+        _ => Err(Status::internal("Invalid response from node")),
+    }
+}
+
+pub async fn restart_dkg(
+    network: &impl Network,
+    request: RestartDkgRequest,
+) -> Result<RestartDkgResponse, Status> {
+    warn!(force = request.force, "Received request to restart DKG");
+    let response = network
+        .send_self_request(
+            SelfRequest::RestartDkg {
+                force: request.force,
+            },
+            true,
+        )
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?
+        .ok_or_else(|| Status::internal("No response from node"))?
+        .await
+        .map_err(|e| Status::internal(format!("Network error: {e:?}")))?;
+
+    let SelfResponse::RestartDkgResponse { success, message } = response else {
+        return Err(Status::internal("Invalid response from node"));
+    };
+
+    Ok(RestartDkgResponse { success, message })
+}
@@ -1,2 +1,3 @@
+pub mod client;
 pub mod grpc_handler;
 pub mod grpc_operator;
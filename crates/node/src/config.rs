@@ -1,19 +1,247 @@
-use crate::{NodeError, PeerData, key_manager};
+use crate::{
+    NodeError, PeerData, data_dir::DataDir, key_manager,
+    wallet::taproot::DEFAULT_MAX_UTXOS_PER_ADDRESS,
+};
+use abci::SyncMode;
 use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce, aead::Aead};
 use argon2::{
-    Argon2,
+    Algorithm, Argon2, Params, Version,
     password_hash::{
         SaltString,
         rand_core::{OsRng, RngCore},
     },
 };
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
-use directories::ProjectDirs;
 use frost_secp256k1::{self as frost};
 use libp2p::identity::Keypair;
+use protocol::block::ConsensusQuorum;
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
-use tracing::debug;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use tracing::{debug, info};
+use tracing_subscriber::EnvFilter;
+use types::intents::{FeePolicy, UnknownDepositPolicy};
+
+/// Format the key file's `KeyData` and `DkgKeys` records are written in. Each record
+/// carries its version, so a node can tell a file it must migrate from one it cannot read.
+pub const CURRENT_KEY_FORMAT_VERSION: u32 = 2;
+
+/// Version of records written before the format was versioned, which have no `version` field.
+pub const LEGACY_KEY_FORMAT_VERSION: u32 = 1;
+
+const fn legacy_key_format_version() -> u32 {
+    LEGACY_KEY_FORMAT_VERSION
+}
+
+/// How long a peer may stay disconnected before it is dropped from the active set.
+pub const DEFAULT_PEER_DISCONNECT_GRACE_SECONDS: u64 = 30;
+
+const fn default_peer_disconnect_grace_seconds() -> u64 {
+    DEFAULT_PEER_DISCONNECT_GRACE_SECONDS
+}
+
+/// Cap on outstanding deposit and withdrawal intents each handler keeps in memory.
+pub const DEFAULT_MAX_PENDING_INTENTS: usize = 10_000;
+
+const fn default_max_pending_intents() -> usize {
+    DEFAULT_MAX_PENDING_INTENTS
+}
+
+const fn default_max_utxos_per_address() -> usize {
+    DEFAULT_MAX_UTXOS_PER_ADDRESS
+}
+
+/// Smallest withdrawal accepted by default: anything below the dust limit cannot be paid out.
+pub const DEFAULT_MIN_WITHDRAWAL_SAT: u64 = 546;
+
+const fn default_min_withdrawal_sat() -> u64 {
+    DEFAULT_MIN_WITHDRAWAL_SAT
+}
+
+/// Largest withdrawal accepted by default; deployments should lower this to cap exposure.
+pub const DEFAULT_MAX_WITHDRAWAL_SAT: u64 = u64::MAX;
+
+const fn default_max_withdrawal_sat() -> u64 {
+    DEFAULT_MAX_WITHDRAWAL_SAT
+}
+
+/// Highest feerate, in sat/vB, a withdrawal may ask for explicitly; higher requests are
+/// refused rather than quoted.
+pub const DEFAULT_MAX_WITHDRAWAL_FEE_RATE_SAT_VB: u64 = 500;
+
+const fn default_max_withdrawal_fee_rate_sat_vb() -> u64 {
+    DEFAULT_MAX_WITHDRAWAL_FEE_RATE_SAT_VB
+}
+
+/// How long a single oracle query may take before it is reported as timed out.
+pub const DEFAULT_ORACLE_TIMEOUT_MS: u64 = 30_000;
+
+const fn default_oracle_timeout_ms() -> u64 {
+    DEFAULT_ORACLE_TIMEOUT_MS
+}
+
+/// Oracle requests allowed in flight at once, kept low so a rescan stays under public
+/// Esplora rate limits.
+pub const DEFAULT_ORACLE_MAX_CONCURRENT_REQUESTS: usize =
+    oracle::rate_limit::DEFAULT_MAX_CONCURRENT_REQUESTS;
+
+const fn default_oracle_max_concurrent_requests() -> usize {
+    DEFAULT_ORACLE_MAX_CONCURRENT_REQUESTS
+}
+
+/// Chain announced in the peer handshake; nodes on different chains refuse each other.
+pub const DEFAULT_CHAIN_ID: &str = "threshold";
+
+fn default_chain_id() -> String {
+    DEFAULT_CHAIN_ID.to_string()
+}
+
+/// Seconds the chain may sit at one height with pending transactions before the liveness
+/// watchdog alerts; six of the consensus layer's 10-second rounds.
+pub const DEFAULT_CONSENSUS_STALL_THRESHOLD_SECONDS: u64 = 60;
+
+const fn default_consensus_stall_threshold_seconds() -> u64 {
+    DEFAULT_CONSENSUS_STALL_THRESHOLD_SECONDS
+}
+
+/// Seconds a deposit intent waits for funds before it is dropped and its address unwatched;
+/// one day. Zero keeps intents until they are fulfilled.
+pub const DEFAULT_DEPOSIT_INTENT_TTL_SECONDS: u64 = 86_400;
+
+const fn default_deposit_intent_ttl_seconds() -> u64 {
+    DEFAULT_DEPOSIT_INTENT_TTL_SECONDS
+}
+
+/// Seconds between consensus ticks, which drive round timeouts, retransmits and liveness checks.
+pub const DEFAULT_CONSENSUS_TICK_INTERVAL_SECONDS: u64 = 10;
+
+const fn default_consensus_tick_interval_seconds() -> u64 {
+    DEFAULT_CONSENSUS_TICK_INTERVAL_SECONDS
+}
+
+/// Seconds between maintenance ticks, which prune expired state and query the oracle for
+/// reorgs and stuck withdrawals.
+pub const DEFAULT_MAINTENANCE_TICK_INTERVAL_SECONDS: u64 = 60;
+
+const fn default_maintenance_tick_interval_seconds() -> u64 {
+    DEFAULT_MAINTENANCE_TICK_INTERVAL_SECONDS
+}
+
+/// Signing nonces kept pre-generated so a new session can commit without waiting on the RNG.
+pub const DEFAULT_NONCE_POOL_SIZE: usize = 8;
+
+const fn default_nonce_pool_size() -> usize {
+    DEFAULT_NONCE_POOL_SIZE
+}
+
+/// Delivery attempts for a direct message before it is dead-lettered.
+pub const DEFAULT_DIRECT_MESSAGE_RETRY_BUDGET: u32 = 5;
+
+const fn default_direct_message_retry_budget() -> u32 {
+    DEFAULT_DIRECT_MESSAGE_RETRY_BUDGET
+}
+
+/// Seconds a consensus vote or block proposal stays valid after it was made, and how far
+/// ahead of local time its timestamp may be. Covers several rounds plus clock drift between validators.
+pub const DEFAULT_MAX_CONSENSUS_MESSAGE_AGE_SECONDS: u64 = 120;
+
+const fn default_max_consensus_message_age_seconds() -> u64 {
+    DEFAULT_MAX_CONSENSUS_MESSAGE_AGE_SECONDS
+}
+
+/// Deepest Bitcoin reorg followed automatically; a deeper one halts deposit crediting.
+pub const DEFAULT_MAX_REORG_DEPTH: u32 = 6;
+
+const fn default_max_reorg_depth() -> u32 {
+    DEFAULT_MAX_REORG_DEPTH
+}
+
+/// Argon2id cost used to derive key-encryption keys from the node password.
+///
+/// Key files written before these were configurable carry no parameters and fall back to the
+/// defaults, which match `Argon2::default()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Argon2Params {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// Builds the Argon2id hasher for these costs, rejecting values Argon2 does not accept.
+    pub fn hasher(&self) -> Result<Argon2<'static>, NodeError> {
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(Params::DEFAULT_OUTPUT_LEN),
+        )
+        .map_err(|e| NodeError::Error(format!("Invalid Argon2 parameters: {e}")))?;
+
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+/// How log lines are rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines, colored on the console.
+    #[default]
+    Pretty,
+    /// One JSON object per line, for log shippers.
+    Json,
+}
+
+/// Verbosity and format of the node's logs. Log output goes to the console and, when
+/// `log_file_path` is set, to daily rotated files in that directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Level for every module without its own entry in `modules`.
+    pub level: String,
+    /// Levels for individual modules, by tracing target prefix such as `consensus` or
+    /// `node::handlers::signing`.
+    pub modules: BTreeMap<String, String>,
+    pub format: LogFormat,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            modules: BTreeMap::new(),
+            format: LogFormat::default(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Builds the filter these levels describe, rejecting levels or module names that do not
+    /// parse so a typo cannot silently turn logging off.
+    pub fn env_filter(&self) -> Result<EnvFilter, NodeError> {
+        let mut filter = EnvFilter::builder()
+            .parse(&self.level)
+            .map_err(|e| NodeError::Error(format!("Invalid log level '{}': {e}", self.level)))?;
+        for (module, level) in &self.modules {
+            let directive = format!("{module}={level}");
+            filter =
+                filter.add_directive(directive.parse().map_err(|e| {
+                    NodeError::Error(format!("Invalid log filter '{directive}': {e}"))
+                })?);
+        }
+        Ok(filter)
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct NodeConfig {
@@ -34,6 +262,66 @@ pub struct NodeConfig {
     pub min_signers: Option<u16>,
     pub max_signers: Option<u16>,
     pub save_keys: bool,
+    #[serde(default)]
+    pub fee_policy: FeePolicy,
+    #[serde(default)]
+    pub unknown_deposit_policy: UnknownDepositPolicy,
+    #[serde(default = "default_peer_disconnect_grace_seconds")]
+    pub peer_disconnect_grace_seconds: u64,
+    #[serde(default = "default_max_pending_intents")]
+    pub max_pending_intents: usize,
+    /// UTXOs the wallet tracks per address before it ignores further dust-sized outputs.
+    #[serde(default = "default_max_utxos_per_address")]
+    pub max_utxos_per_address: usize,
+    #[serde(default)]
+    pub consensus_quorum: ConsensusQuorum,
+    #[serde(default)]
+    pub argon2_params: Argon2Params,
+    #[serde(default = "default_min_withdrawal_sat")]
+    pub min_withdrawal_sat: u64,
+    #[serde(default = "default_max_withdrawal_sat")]
+    pub max_withdrawal_sat: u64,
+    #[serde(default = "default_max_withdrawal_fee_rate_sat_vb")]
+    pub max_withdrawal_fee_rate_sat_vb: u64,
+    #[serde(default = "default_oracle_timeout_ms")]
+    pub oracle_timeout_ms: u64,
+    /// Cap on concurrent requests to the Esplora servers, shared by every query path.
+    #[serde(default = "default_oracle_max_concurrent_requests")]
+    pub oracle_max_concurrent_requests: usize,
+    /// Esplora endpoints whose feerate estimates are combined by median; when empty the
+    /// main oracle's estimate is used alone.
+    #[serde(default)]
+    pub fee_oracle_urls: Vec<String>,
+    #[serde(default)]
+    pub sync_mode: SyncMode,
+    #[serde(default = "default_max_reorg_depth")]
+    pub max_reorg_depth: u32,
+    #[serde(default = "default_chain_id")]
+    pub chain_id: String,
+    #[serde(default = "default_consensus_stall_threshold_seconds")]
+    pub consensus_stall_threshold_seconds: u64,
+    #[serde(default = "default_deposit_intent_ttl_seconds")]
+    pub deposit_intent_ttl_seconds: u64,
+    #[serde(default = "default_consensus_tick_interval_seconds")]
+    pub consensus_tick_interval_seconds: u64,
+    #[serde(default = "default_maintenance_tick_interval_seconds")]
+    pub maintenance_tick_interval_seconds: u64,
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    #[serde(default = "default_nonce_pool_size")]
+    pub nonce_pool_size: usize,
+    #[serde(default = "default_direct_message_retry_budget")]
+    pub direct_message_retry_budget: u32,
+    /// Send consensus votes to the round's leader, which gossips them as one signed
+    /// aggregate, instead of every validator gossiping its own votes.
+    #[serde(default)]
+    pub consensus_vote_aggregation: bool,
+    /// Votes and block proposals timestamped further than this from local time are dropped
+    /// on receipt, so a replayed one cannot be counted again.
+    #[serde(default = "default_max_consensus_message_age_seconds")]
+    pub max_consensus_message_age_seconds: u64,
+    #[serde(default)]
+    pub logging: LoggingConfig,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -42,6 +330,58 @@ pub struct KeyStore {
     pub dkg_keys: Option<DkgKeys>,
 }
 
+impl KeyStore {
+    /// Parses a key file, migrating records in an older format to
+    /// [`CURRENT_KEY_FORMAT_VERSION`]. A record in a format this build does not know is
+    /// refused by version before its fields are decoded.
+    pub fn from_json(contents: &str) -> Result<Self, NodeError> {
+        let value: serde_json::Value = serde_json::from_str(contents)
+            .map_err(|e| NodeError::Error(format!("Failed to parse key file: {e}")))?;
+        for record in ["key_data", "dkg_keys"] {
+            if let Some(fields) = value.get(record).filter(|fields| !fields.is_null()) {
+                check_key_format_version(record, fields.get("version"))?;
+            }
+        }
+
+        let mut key_store: Self = serde_json::from_value(value)
+            .map_err(|e| NodeError::Error(format!("Failed to deserialize key file: {e}")))?;
+        migrate_key_record("key_data", &mut key_store.key_data.version);
+        if let Some(dkg_keys) = key_store.dkg_keys.as_mut() {
+            migrate_key_record("dkg_keys", &mut dkg_keys.version);
+        }
+        Ok(key_store)
+    }
+}
+
+fn check_key_format_version(
+    record: &str,
+    version: Option<&serde_json::Value>,
+) -> Result<(), NodeError> {
+    let Some(version) = version else {
+        return Ok(());
+    };
+    match version.as_u64() {
+        Some(v) if v > u64::from(CURRENT_KEY_FORMAT_VERSION) => Err(NodeError::Error(format!(
+            "Key file {record} uses format version {v}, newer than the supported {CURRENT_KEY_FORMAT_VERSION}; upgrade the node to load it"
+        ))),
+        Some(v) if v >= u64::from(LEGACY_KEY_FORMAT_VERSION) => Ok(()),
+        _ => Err(NodeError::Error(format!(
+            "Key file {record} has unknown format version {version}"
+        ))),
+    }
+}
+
+/// Brings a record read at `version` up to the current format. Version 1 differs only in
+/// lacking the version field, and its missing Argon2 costs already default on decode.
+fn migrate_key_record(record: &str, version: &mut u32) {
+    if *version < CURRENT_KEY_FORMAT_VERSION {
+        info!(
+            "Migrating key file {record} from format version {version} to {CURRENT_KEY_FORMAT_VERSION}"
+        );
+        *version = CURRENT_KEY_FORMAT_VERSION;
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ConfigStore {
     pub allowed_peers: Vec<PeerData>,
@@ -56,24 +396,91 @@ pub struct ConfigStore {
     pub min_signers: Option<u16>,
     pub max_signers: Option<u16>,
     pub save_keys: bool,
+    #[serde(default)]
+    pub fee_policy: FeePolicy,
+    #[serde(default)]
+    pub unknown_deposit_policy: UnknownDepositPolicy,
+    #[serde(default = "default_peer_disconnect_grace_seconds")]
+    pub peer_disconnect_grace_seconds: u64,
+    #[serde(default = "default_max_pending_intents")]
+    pub max_pending_intents: usize,
+    /// UTXOs the wallet tracks per address before it ignores further dust-sized outputs.
+    #[serde(default = "default_max_utxos_per_address")]
+    pub max_utxos_per_address: usize,
+    #[serde(default)]
+    pub consensus_quorum: ConsensusQuorum,
+    #[serde(default)]
+    pub argon2_params: Argon2Params,
+    #[serde(default = "default_min_withdrawal_sat")]
+    pub min_withdrawal_sat: u64,
+    #[serde(default = "default_max_withdrawal_sat")]
+    pub max_withdrawal_sat: u64,
+    #[serde(default = "default_max_withdrawal_fee_rate_sat_vb")]
+    pub max_withdrawal_fee_rate_sat_vb: u64,
+    #[serde(default = "default_oracle_timeout_ms")]
+    pub oracle_timeout_ms: u64,
+    /// Cap on concurrent requests to the Esplora servers, shared by every query path.
+    #[serde(default = "default_oracle_max_concurrent_requests")]
+    pub oracle_max_concurrent_requests: usize,
+    /// Esplora endpoints whose feerate estimates are combined by median; when empty the
+    /// main oracle's estimate is used alone.
+    #[serde(default)]
+    pub fee_oracle_urls: Vec<String>,
+    #[serde(default)]
+    pub sync_mode: SyncMode,
+    #[serde(default = "default_max_reorg_depth")]
+    pub max_reorg_depth: u32,
+    #[serde(default = "default_chain_id")]
+    pub chain_id: String,
+    #[serde(default = "default_consensus_stall_threshold_seconds")]
+    pub consensus_stall_threshold_seconds: u64,
+    #[serde(default = "default_deposit_intent_ttl_seconds")]
+    pub deposit_intent_ttl_seconds: u64,
+    #[serde(default = "default_consensus_tick_interval_seconds")]
+    pub consensus_tick_interval_seconds: u64,
+    #[serde(default = "default_maintenance_tick_interval_seconds")]
+    pub maintenance_tick_interval_seconds: u64,
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    #[serde(default = "default_nonce_pool_size")]
+    pub nonce_pool_size: usize,
+    #[serde(default = "default_direct_message_retry_budget")]
+    pub direct_message_retry_budget: u32,
+    /// Send consensus votes to the round's leader, which gossips them as one signed
+    /// aggregate, instead of every validator gossiping its own votes.
+    #[serde(default)]
+    pub consensus_vote_aggregation: bool,
+    /// Votes and block proposals timestamped further than this from local time are dropped
+    /// on receipt, so a replayed one cannot be counted again.
+    #[serde(default = "default_max_consensus_message_age_seconds")]
+    pub max_consensus_message_age_seconds: u64,
+    #[serde(default)]
+    pub logging: LoggingConfig,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct DkgKeys {
+    #[serde(default = "legacy_key_format_version")]
+    pub version: u32,
     pub encrypted_private_key_package_b64: String,
     pub dkg_encryption_params: EncryptionParams,
     pub pubkey_package_b64: String,
 }
 
+/// Versioned along with the `KeyData` or `DkgKeys` record holding it.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct EncryptionParams {
     pub kdf: String,
     pub salt_b64: String,
     pub iv_b64: String,
+    #[serde(default)]
+    pub argon2: Argon2Params,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct KeyData {
+    #[serde(default = "legacy_key_format_version")]
+    pub version: u32,
     pub public_key_b58: String,
     pub encrypted_private_key_b64: String,
     pub encryption_params: EncryptionParams,
@@ -85,6 +492,23 @@ impl NodeConfig {
         config_file_path: PathBuf,
         log_file_path: Option<PathBuf>,
         password: &str,
+    ) -> Result<Self, NodeError> {
+        Self::new_with_argon2_params(
+            key_file_path,
+            config_file_path,
+            log_file_path,
+            password,
+            Argon2Params::default(),
+        )
+    }
+
+    /// Like [`NodeConfig::new`], encrypting the generated identity key with the given Argon2 cost.
+    pub fn new_with_argon2_params(
+        key_file_path: PathBuf,
+        config_file_path: PathBuf,
+        log_file_path: Option<PathBuf>,
+        password: &str,
+        argon2_params: Argon2Params,
     ) -> Result<Self, NodeError> {
         // Generate a new keypair
         let keypair = Keypair::generate_ed25519();
@@ -95,7 +519,7 @@ impl NodeConfig {
         let salt_b64 = salt.to_string();
 
         // Derive encryption key from password
-        let argon2 = Argon2::default();
+        let argon2 = argon2_params.hasher()?;
         let mut key_bytes = vec![0u8; 32];
         argon2
             .hash_password_into(
@@ -125,12 +549,14 @@ impl NodeConfig {
         let iv_b64 = BASE64.encode(iv);
 
         let key_data = KeyData {
+            version: CURRENT_KEY_FORMAT_VERSION,
             public_key_b58,
             encrypted_private_key_b64,
             encryption_params: EncryptionParams {
                 kdf: "argon2id".to_string(),
                 salt_b64,
                 iv_b64,
+                argon2: argon2_params,
             },
         };
 
@@ -141,7 +567,7 @@ impl NodeConfig {
             log_file_path,
             key_file_path,
             config_file_path,
-            database_directory: PathBuf::from("nodedb.db"),
+            database_directory: DataDir::default_location()?.database_directory(),
             grpc_port: 50051,
             libp2p_udp_port: 0,
             libp2p_tcp_port: 0,
@@ -150,6 +576,32 @@ impl NodeConfig {
             min_signers: None,
             max_signers: None,
             save_keys: true,
+            fee_policy: FeePolicy::default(),
+            unknown_deposit_policy: UnknownDepositPolicy::default(),
+            peer_disconnect_grace_seconds: DEFAULT_PEER_DISCONNECT_GRACE_SECONDS,
+            max_pending_intents: DEFAULT_MAX_PENDING_INTENTS,
+            max_utxos_per_address: DEFAULT_MAX_UTXOS_PER_ADDRESS,
+            consensus_quorum: ConsensusQuorum::default(),
+            argon2_params,
+            min_withdrawal_sat: DEFAULT_MIN_WITHDRAWAL_SAT,
+            max_withdrawal_sat: DEFAULT_MAX_WITHDRAWAL_SAT,
+            max_withdrawal_fee_rate_sat_vb: DEFAULT_MAX_WITHDRAWAL_FEE_RATE_SAT_VB,
+            oracle_timeout_ms: DEFAULT_ORACLE_TIMEOUT_MS,
+            oracle_max_concurrent_requests: DEFAULT_ORACLE_MAX_CONCURRENT_REQUESTS,
+            fee_oracle_urls: Vec::new(),
+            sync_mode: SyncMode::default(),
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            chain_id: default_chain_id(),
+            consensus_stall_threshold_seconds: DEFAULT_CONSENSUS_STALL_THRESHOLD_SECONDS,
+            deposit_intent_ttl_seconds: DEFAULT_DEPOSIT_INTENT_TTL_SECONDS,
+            consensus_tick_interval_seconds: DEFAULT_CONSENSUS_TICK_INTERVAL_SECONDS,
+            maintenance_tick_interval_seconds: DEFAULT_MAINTENANCE_TICK_INTERVAL_SECONDS,
+            admin_token: None,
+            nonce_pool_size: DEFAULT_NONCE_POOL_SIZE,
+            direct_message_retry_budget: DEFAULT_DIRECT_MESSAGE_RETRY_BUDGET,
+            consensus_vote_aggregation: false,
+            max_consensus_message_age_seconds: DEFAULT_MAX_CONSENSUS_MESSAGE_AGE_SECONDS,
+            logging: LoggingConfig::default(),
         })
     }
 
@@ -193,6 +645,32 @@ impl NodeConfig {
             min_signers: self.min_signers,
             max_signers: self.max_signers,
             save_keys: self.save_keys,
+            fee_policy: self.fee_policy,
+            unknown_deposit_policy: self.unknown_deposit_policy.clone(),
+            peer_disconnect_grace_seconds: self.peer_disconnect_grace_seconds,
+            max_pending_intents: self.max_pending_intents,
+            max_utxos_per_address: self.max_utxos_per_address,
+            consensus_quorum: self.consensus_quorum,
+            argon2_params: self.argon2_params,
+            min_withdrawal_sat: self.min_withdrawal_sat,
+            max_withdrawal_sat: self.max_withdrawal_sat,
+            max_withdrawal_fee_rate_sat_vb: self.max_withdrawal_fee_rate_sat_vb,
+            oracle_timeout_ms: self.oracle_timeout_ms,
+            oracle_max_concurrent_requests: self.oracle_max_concurrent_requests,
+            fee_oracle_urls: self.fee_oracle_urls.clone(),
+            sync_mode: self.sync_mode,
+            max_reorg_depth: self.max_reorg_depth,
+            chain_id: self.chain_id.clone(),
+            consensus_stall_threshold_seconds: self.consensus_stall_threshold_seconds,
+            deposit_intent_ttl_seconds: self.deposit_intent_ttl_seconds,
+            consensus_tick_interval_seconds: self.consensus_tick_interval_seconds,
+            maintenance_tick_interval_seconds: self.maintenance_tick_interval_seconds,
+            admin_token: self.admin_token.clone(),
+            nonce_pool_size: self.nonce_pool_size,
+            direct_message_retry_budget: self.direct_message_retry_budget,
+            consensus_vote_aggregation: self.consensus_vote_aggregation,
+            max_consensus_message_age_seconds: self.max_consensus_message_age_seconds,
+            logging: self.logging.clone(),
         };
 
         let config_str: String = serde_yaml::to_string(&config_store).unwrap();
@@ -203,15 +681,13 @@ impl NodeConfig {
         Ok(())
     }
 
+    /// Key file in the default data directory, migrating that directory to the current
+    /// layout first.
     pub fn get_key_file_path() -> Result<PathBuf, NodeError> {
-        let proj_dirs = ProjectDirs::from("", "", "TheVault")
-            .ok_or_else(|| NodeError::Error("Failed to determine project directory".into()))?;
-
-        let config_dir = proj_dirs.config_dir();
-        fs::create_dir_all(config_dir)
-            .map_err(|e| NodeError::Error(format!("Failed to create config directory: {e}")))?;
+        let data_dir = DataDir::default_location()?;
+        data_dir.migrate()?;
 
-        let path = config_dir.join("config.json");
+        let path = data_dir.key_file_path();
         debug!("Using key file path: {}", path.display());
         Ok(path)
     }
@@ -225,13 +701,19 @@ impl NodeConfig {
             println!("Using config file path: {}", path.display());
             Ok(path)
         } else {
-            let proj_dirs = ProjectDirs::from("", "", "TheVault")
-                .ok_or_else(|| NodeError::Error("Failed to determine project directory".into()))?;
-            let config_dir = proj_dirs.config_dir();
-            Ok(config_dir.join("config.yaml"))
+            let data_dir = DataDir::default_location()?;
+            data_dir.migrate()?;
+            Ok(data_dir.config_file_path())
         }
     }
 
+    /// Loads the key and config files of `data_dir`, migrating it to the current layout
+    /// first.
+    pub fn load_from_data_dir(data_dir: &DataDir) -> Result<Self, NodeError> {
+        data_dir.migrate()?;
+        Self::load_files(data_dir.key_file_path(), data_dir.config_file_path())
+    }
+
     pub fn get_config(
         key_file_path: Option<String>,
         config_file_path: Option<String>,
@@ -248,11 +730,14 @@ impl NodeConfig {
             Self::get_config_file_path(None)?
         };
 
+        Self::load_files(key_file_path, config_file_path)
+    }
+
+    fn load_files(key_file_path: PathBuf, config_file_path: PathBuf) -> Result<Self, NodeError> {
         let key_contents = fs::read_to_string(&key_file_path)
             .map_err(|e| NodeError::Error(format!("Failed to read config file: {e}")))?;
 
-        let key_store = serde_json::from_str::<KeyStore>(&key_contents)
-            .map_err(|e| NodeError::Error(format!("Failed to deserialize key file: {e}")))?;
+        let key_store = KeyStore::from_json(&key_contents)?;
 
         let config_contents = fs::read_to_string(&config_file_path)
             .map_err(|e| NodeError::Error(format!("Failed to read config file: {e}")))?;
@@ -276,11 +761,59 @@ impl NodeConfig {
             min_signers: config_store.min_signers,
             max_signers: config_store.max_signers,
             save_keys: config_store.save_keys,
+            fee_policy: config_store.fee_policy,
+            unknown_deposit_policy: config_store.unknown_deposit_policy,
+            peer_disconnect_grace_seconds: config_store.peer_disconnect_grace_seconds,
+            max_pending_intents: config_store.max_pending_intents,
+            max_utxos_per_address: config_store.max_utxos_per_address,
+            consensus_quorum: config_store.consensus_quorum,
+            argon2_params: config_store.argon2_params,
+            min_withdrawal_sat: config_store.min_withdrawal_sat,
+            max_withdrawal_sat: config_store.max_withdrawal_sat,
+            max_withdrawal_fee_rate_sat_vb: config_store.max_withdrawal_fee_rate_sat_vb,
+            oracle_timeout_ms: config_store.oracle_timeout_ms,
+            oracle_max_concurrent_requests: config_store.oracle_max_concurrent_requests,
+            fee_oracle_urls: config_store.fee_oracle_urls,
+            sync_mode: config_store.sync_mode,
+            max_reorg_depth: config_store.max_reorg_depth,
+            chain_id: config_store.chain_id,
+            consensus_stall_threshold_seconds: config_store.consensus_stall_threshold_seconds,
+            deposit_intent_ttl_seconds: config_store.deposit_intent_ttl_seconds,
+            consensus_tick_interval_seconds: config_store.consensus_tick_interval_seconds,
+            maintenance_tick_interval_seconds: config_store.maintenance_tick_interval_seconds,
+            admin_token: config_store.admin_token,
+            nonce_pool_size: config_store.nonce_pool_size,
+            direct_message_retry_budget: config_store.direct_message_retry_budget,
+            consensus_vote_aggregation: config_store.consensus_vote_aggregation,
+            max_consensus_message_age_seconds: config_store.max_consensus_message_age_seconds,
+            logging: config_store.logging,
         };
 
+        node_config.validate()?;
         Ok(node_config)
     }
 
+    /// Rejects settings that could never work together, so the node fails at startup instead
+    /// of stalling once it is running.
+    pub fn validate(&self) -> Result<(), NodeError> {
+        let validators = self.allowed_peers.len() + 1;
+        if let ConsensusQuorum::Count(count) = self.consensus_quorum {
+            if count == 0 || count as usize > validators {
+                return Err(NodeError::Error(format!(
+                    "consensus_quorum of {count} votes must be between 1 and the {validators} validators"
+                )));
+            }
+        }
+        if self.min_withdrawal_sat > self.max_withdrawal_sat {
+            return Err(NodeError::Error(format!(
+                "min_withdrawal_sat of {} exceeds max_withdrawal_sat of {}",
+                self.min_withdrawal_sat, self.max_withdrawal_sat
+            )));
+        }
+
+        Ok(())
+    }
+
     pub fn save_dkg_keys(
         &mut self,
         private_key_package: &frost::keys::KeyPackage,
@@ -299,6 +832,7 @@ impl NodeConfig {
             &private_key_bytes,
             &password,
             &self.key_data.encryption_params.salt_b64,
+            &self.argon2_params,
         )?;
 
         let pubkey_bytes = pubkey_package.serialize().map_err(|e| {
@@ -307,11 +841,13 @@ impl NodeConfig {
         let pubkey_package_b64 = BASE64.encode(pubkey_bytes);
 
         let dkg_keys = DkgKeys {
+            version: CURRENT_KEY_FORMAT_VERSION,
             encrypted_private_key_package_b64: encrypted_private_key_b64,
             dkg_encryption_params: EncryptionParams {
                 kdf: "argon2id".to_string(),
                 salt_b64: self.key_data.encryption_params.salt_b64.clone(),
                 iv_b64,
+                argon2: self.argon2_params,
             },
             pubkey_package_b64,
         };
@@ -352,6 +888,7 @@ impl NodeConfig {
                 frost::keys::PublicKeyPackage::deserialize(&pubkey_bytes).map_err(|e| {
                     NodeError::Error(format!("Failed to deserialize public key package: {e}"))
                 })?;
+            verify_key_packages(&private_key, &pubkey)?;
 
             Ok(Some((private_key, pubkey)))
         } else {
@@ -360,6 +897,42 @@ impl NodeConfig {
     }
 }
 
+/// Checks that a key package belongs to the public key package loaded beside it: its
+/// verifying share is re-derived from the secret share and must match both its own copy and
+/// the group's entry for it, under the same group key. A node holding a mismatched pair
+/// would only produce signature shares the group rejects.
+pub fn verify_key_packages(
+    key_package: &frost::keys::KeyPackage,
+    pubkey_package: &frost::keys::PublicKeyPackage,
+) -> Result<(), NodeError> {
+    let derived_share = frost::keys::VerifyingShare::from(*key_package.signing_share());
+    if derived_share != *key_package.verifying_share() {
+        return Err(NodeError::Error(
+            "Private key package is inconsistent: its verifying share does not match its signing share"
+                .to_string(),
+        ));
+    }
+    if key_package.verifying_key() != pubkey_package.verifying_key() {
+        return Err(NodeError::Error(
+            "Private key package does not match the public key package: group keys differ"
+                .to_string(),
+        ));
+    }
+    match pubkey_package
+        .verifying_shares()
+        .get(key_package.identifier())
+    {
+        Some(share) if *share == derived_share => Ok(()),
+        Some(_) => Err(NodeError::Error(
+            "Private key package does not match the public key package: verifying shares differ"
+                .to_string(),
+        )),
+        None => Err(NodeError::Error(
+            "Public key package has no verifying share for this node's key package".to_string(),
+        )),
+    }
+}
+
 pub struct NodeConfigBuilder {
     key_file_path: Option<PathBuf>,
     config_file_path: Option<PathBuf>,
@@ -378,6 +951,32 @@ pub struct NodeConfigBuilder {
     min_signers: Option<u16>,
     max_signers: Option<u16>,
     save_keys: Option<bool>,
+    fee_policy: Option<FeePolicy>,
+    unknown_deposit_policy: Option<UnknownDepositPolicy>,
+    peer_disconnect_grace_seconds: Option<u64>,
+    max_pending_intents: Option<usize>,
+    max_utxos_per_address: Option<usize>,
+    consensus_quorum: Option<ConsensusQuorum>,
+    argon2_params: Option<Argon2Params>,
+    min_withdrawal_sat: Option<u64>,
+    max_withdrawal_sat: Option<u64>,
+    max_withdrawal_fee_rate_sat_vb: Option<u64>,
+    oracle_timeout_ms: Option<u64>,
+    oracle_max_concurrent_requests: Option<usize>,
+    fee_oracle_urls: Option<Vec<String>>,
+    sync_mode: Option<SyncMode>,
+    max_reorg_depth: Option<u32>,
+    chain_id: Option<String>,
+    consensus_stall_threshold_seconds: Option<u64>,
+    deposit_intent_ttl_seconds: Option<u64>,
+    consensus_tick_interval_seconds: Option<u64>,
+    maintenance_tick_interval_seconds: Option<u64>,
+    admin_token: Option<String>,
+    nonce_pool_size: Option<usize>,
+    direct_message_retry_budget: Option<u32>,
+    consensus_vote_aggregation: Option<bool>,
+    max_consensus_message_age_seconds: Option<u64>,
+    logging: Option<LoggingConfig>,
 }
 
 impl Default for NodeConfigBuilder {
@@ -407,6 +1006,32 @@ impl NodeConfigBuilder {
             min_signers: None,
             max_signers: None,
             save_keys: None,
+            fee_policy: None,
+            unknown_deposit_policy: None,
+            peer_disconnect_grace_seconds: None,
+            max_pending_intents: None,
+            max_utxos_per_address: None,
+            consensus_quorum: None,
+            argon2_params: None,
+            min_withdrawal_sat: None,
+            max_withdrawal_sat: None,
+            max_withdrawal_fee_rate_sat_vb: None,
+            oracle_timeout_ms: None,
+            oracle_max_concurrent_requests: None,
+            fee_oracle_urls: None,
+            sync_mode: None,
+            max_reorg_depth: None,
+            chain_id: None,
+            consensus_stall_threshold_seconds: None,
+            deposit_intent_ttl_seconds: None,
+            consensus_tick_interval_seconds: None,
+            maintenance_tick_interval_seconds: None,
+            admin_token: None,
+            nonce_pool_size: None,
+            direct_message_retry_budget: None,
+            consensus_vote_aggregation: None,
+            max_consensus_message_age_seconds: None,
+            logging: None,
         }
     }
     #[must_use]
@@ -505,6 +1130,162 @@ impl NodeConfigBuilder {
         self
     }
 
+    #[must_use]
+    pub const fn fee_policy(mut self, fee_policy: FeePolicy) -> Self {
+        self.fee_policy = Some(fee_policy);
+        self
+    }
+
+    #[must_use]
+    pub fn unknown_deposit_policy(mut self, policy: UnknownDepositPolicy) -> Self {
+        self.unknown_deposit_policy = Some(policy);
+        self
+    }
+
+    #[must_use]
+    pub const fn peer_disconnect_grace_seconds(mut self, seconds: u64) -> Self {
+        self.peer_disconnect_grace_seconds = Some(seconds);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_pending_intents(mut self, max: usize) -> Self {
+        self.max_pending_intents = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_utxos_per_address(mut self, max: usize) -> Self {
+        self.max_utxos_per_address = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub const fn consensus_quorum(mut self, quorum: ConsensusQuorum) -> Self {
+        self.consensus_quorum = Some(quorum);
+        self
+    }
+
+    #[must_use]
+    pub const fn argon2_params(mut self, params: Argon2Params) -> Self {
+        self.argon2_params = Some(params);
+        self
+    }
+
+    #[must_use]
+    pub const fn min_withdrawal_sat(mut self, min: u64) -> Self {
+        self.min_withdrawal_sat = Some(min);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_withdrawal_sat(mut self, max: u64) -> Self {
+        self.max_withdrawal_sat = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_withdrawal_fee_rate_sat_vb(mut self, max: u64) -> Self {
+        self.max_withdrawal_fee_rate_sat_vb = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub const fn oracle_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.oracle_timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    #[must_use]
+    pub const fn oracle_max_concurrent_requests(mut self, requests: usize) -> Self {
+        self.oracle_max_concurrent_requests = Some(requests);
+        self
+    }
+
+    #[must_use]
+    pub fn fee_oracle_urls(mut self, urls: Vec<String>) -> Self {
+        self.fee_oracle_urls = Some(urls);
+        self
+    }
+
+    #[must_use]
+    pub const fn sync_mode(mut self, mode: SyncMode) -> Self {
+        self.sync_mode = Some(mode);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_reorg_depth(mut self, depth: u32) -> Self {
+        self.max_reorg_depth = Some(depth);
+        self
+    }
+
+    #[must_use]
+    pub fn chain_id(mut self, chain_id: impl Into<String>) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn consensus_stall_threshold_seconds(mut self, seconds: u64) -> Self {
+        self.consensus_stall_threshold_seconds = Some(seconds);
+        self
+    }
+
+    #[must_use]
+    pub const fn deposit_intent_ttl_seconds(mut self, seconds: u64) -> Self {
+        self.deposit_intent_ttl_seconds = Some(seconds);
+        self
+    }
+
+    #[must_use]
+    pub const fn consensus_tick_interval_seconds(mut self, seconds: u64) -> Self {
+        self.consensus_tick_interval_seconds = Some(seconds);
+        self
+    }
+
+    #[must_use]
+    pub const fn maintenance_tick_interval_seconds(mut self, seconds: u64) -> Self {
+        self.maintenance_tick_interval_seconds = Some(seconds);
+        self
+    }
+
+    #[must_use]
+    pub fn admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = Some(token.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn nonce_pool_size(mut self, size: usize) -> Self {
+        self.nonce_pool_size = Some(size);
+        self
+    }
+
+    #[must_use]
+    pub const fn direct_message_retry_budget(mut self, attempts: u32) -> Self {
+        self.direct_message_retry_budget = Some(attempts);
+        self
+    }
+
+    #[must_use]
+    pub const fn consensus_vote_aggregation(mut self, enabled: bool) -> Self {
+        self.consensus_vote_aggregation = Some(enabled);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_consensus_message_age_seconds(mut self, seconds: u64) -> Self {
+        self.max_consensus_message_age_seconds = Some(seconds);
+        self
+    }
+
+    #[must_use]
+    pub fn logging(mut self, logging: LoggingConfig) -> Self {
+        self.logging = Some(logging);
+        self
+    }
+
     pub fn build(self) -> Result<NodeConfig, NodeError> {
         let key_file_path = self.key_file_path.ok_or_else(|| {
             NodeError::Error("key_file_path must be provided when building NodeConfig".into())
@@ -518,11 +1299,12 @@ impl NodeConfigBuilder {
             NodeError::Error("password must be provided when building NodeConfig".into())
         })?;
 
-        let mut cfg = NodeConfig::new(
+        let mut cfg = NodeConfig::new_with_argon2_params(
             key_file_path,
             config_file_path,
             self.log_file_path,
             &password,
+            self.argon2_params.unwrap_or_default(),
         )
         .map_err(|e| NodeError::Error(format!("Failed to create NodeConfig: {e}")))?;
 
@@ -563,7 +1345,83 @@ impl NodeConfigBuilder {
         if let Some(mx) = self.max_signers {
             cfg.max_signers = Some(mx);
         }
+        if let Some(policy) = self.fee_policy {
+            cfg.fee_policy = policy;
+        }
+        if let Some(policy) = self.unknown_deposit_policy {
+            cfg.unknown_deposit_policy = policy;
+        }
+        if let Some(grace) = self.peer_disconnect_grace_seconds {
+            cfg.peer_disconnect_grace_seconds = grace;
+        }
+        if let Some(max) = self.max_pending_intents {
+            cfg.max_pending_intents = max;
+        }
+        if let Some(max) = self.max_utxos_per_address {
+            cfg.max_utxos_per_address = max;
+        }
+        if let Some(quorum) = self.consensus_quorum {
+            cfg.consensus_quorum = quorum;
+        }
+        if let Some(min) = self.min_withdrawal_sat {
+            cfg.min_withdrawal_sat = min;
+        }
+        if let Some(max) = self.max_withdrawal_sat {
+            cfg.max_withdrawal_sat = max;
+        }
+        if let Some(max) = self.max_withdrawal_fee_rate_sat_vb {
+            cfg.max_withdrawal_fee_rate_sat_vb = max;
+        }
+        if let Some(timeout_ms) = self.oracle_timeout_ms {
+            cfg.oracle_timeout_ms = timeout_ms;
+        }
+        if let Some(requests) = self.oracle_max_concurrent_requests {
+            cfg.oracle_max_concurrent_requests = requests;
+        }
+        if let Some(urls) = self.fee_oracle_urls {
+            cfg.fee_oracle_urls = urls;
+        }
+        if let Some(mode) = self.sync_mode {
+            cfg.sync_mode = mode;
+        }
+        if let Some(depth) = self.max_reorg_depth {
+            cfg.max_reorg_depth = depth;
+        }
+        if let Some(chain_id) = self.chain_id {
+            cfg.chain_id = chain_id;
+        }
+        if let Some(seconds) = self.consensus_stall_threshold_seconds {
+            cfg.consensus_stall_threshold_seconds = seconds;
+        }
+        if let Some(seconds) = self.deposit_intent_ttl_seconds {
+            cfg.deposit_intent_ttl_seconds = seconds;
+        }
+        if let Some(seconds) = self.consensus_tick_interval_seconds {
+            cfg.consensus_tick_interval_seconds = seconds;
+        }
+        if let Some(seconds) = self.maintenance_tick_interval_seconds {
+            cfg.maintenance_tick_interval_seconds = seconds;
+        }
+        if let Some(token) = self.admin_token {
+            cfg.admin_token = Some(token);
+        }
+        if let Some(size) = self.nonce_pool_size {
+            cfg.nonce_pool_size = size;
+        }
+        if let Some(attempts) = self.direct_message_retry_budget {
+            cfg.direct_message_retry_budget = attempts;
+        }
+        if let Some(enabled) = self.consensus_vote_aggregation {
+            cfg.consensus_vote_aggregation = enabled;
+        }
+        if let Some(seconds) = self.max_consensus_message_age_seconds {
+            cfg.max_consensus_message_age_seconds = seconds;
+        }
+        if let Some(logging) = self.logging {
+            cfg.logging = logging;
+        }
 
+        cfg.validate()?;
         Ok(cfg)
     }
 }
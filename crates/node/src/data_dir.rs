@@ -0,0 +1,197 @@
+use crate::{ConfigStore, NodeError};
+use directories::ProjectDirs;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Version of the on-disk layout written by this build. Bump it together with a new step
+/// in [`DataDir::migrate`] whenever files move within the data directory.
+pub const CURRENT_LAYOUT_VERSION: u32 = 1;
+
+/// File under the data-directory root recording which layout the directory uses.
+pub const LAYOUT_VERSION_FILE: &str = "layout_version";
+
+/// Legacy key file kept directly in the data-directory root.
+const LEGACY_KEY_FILE: &str = "config.json";
+/// Legacy config file kept directly in the data-directory root.
+const LEGACY_CONFIG_FILE: &str = "config.yaml";
+/// Legacy default database, opened relative to the working directory rather than the root.
+const LEGACY_DATABASE: &str = "nodedb.db";
+/// Legacy log file kept directly in the data-directory root.
+const LEGACY_LOG_FILE: &str = "node.log";
+
+/// All files a node keeps on disk, grouped under a single root:
+///
+/// ```text
+/// <root>/layout_version
+/// <root>/keys/keys.json
+/// <root>/config/config.yaml
+/// <root>/db/
+/// <root>/logs/node.log
+/// ```
+///
+/// Directories written by older builds are upgraded in place by [`DataDir::migrate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataDir {
+    pub root: PathBuf,
+}
+
+impl DataDir {
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The platform's per-user data directory for the vault.
+    pub fn default_location() -> Result<Self, NodeError> {
+        let proj_dirs = ProjectDirs::from("", "", "TheVault")
+            .ok_or_else(|| NodeError::Error("Failed to determine project directory".into()))?;
+        Ok(Self::new(proj_dirs.config_dir()))
+    }
+
+    #[must_use]
+    pub fn key_file_path(&self) -> PathBuf {
+        self.root.join("keys").join("keys.json")
+    }
+
+    #[must_use]
+    pub fn config_file_path(&self) -> PathBuf {
+        self.root.join("config").join("config.yaml")
+    }
+
+    #[must_use]
+    pub fn database_directory(&self) -> PathBuf {
+        self.root.join("db")
+    }
+
+    #[must_use]
+    pub fn log_file_path(&self) -> PathBuf {
+        self.root.join("logs").join("node.log")
+    }
+
+    #[must_use]
+    pub fn layout_version_path(&self) -> PathBuf {
+        self.root.join(LAYOUT_VERSION_FILE)
+    }
+
+    /// Layout version recorded under the root; directories without a version file predate
+    /// versioning and report 0.
+    pub fn layout_version(&self) -> Result<u32, NodeError> {
+        let path = self.layout_version_path();
+        if !path.exists() {
+            return Ok(0);
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| NodeError::Error(format!("Failed to read layout version: {e}")))?;
+        contents.trim().parse().map_err(|e| {
+            NodeError::Error(format!("Invalid layout version in {}: {e}", path.display()))
+        })
+    }
+
+    /// Brings the directory up to [`CURRENT_LAYOUT_VERSION`], returning the version it was
+    /// found at. Directories written by a newer build are refused rather than downgraded.
+    pub fn migrate(&self) -> Result<u32, NodeError> {
+        let found = self.layout_version()?;
+        if found > CURRENT_LAYOUT_VERSION {
+            return Err(NodeError::Error(format!(
+                "Data directory {} uses layout version {found}, newer than the supported {CURRENT_LAYOUT_VERSION}",
+                self.root.display()
+            )));
+        }
+
+        let files = [
+            self.key_file_path(),
+            self.config_file_path(),
+            self.log_file_path(),
+        ];
+        for dir in files.iter().filter_map(|path| path.parent()) {
+            fs::create_dir_all(dir).map_err(|e| {
+                NodeError::Error(format!("Failed to create {}: {e}", dir.display()))
+            })?;
+        }
+
+        if found < 1 {
+            self.migrate_flat_layout()?;
+        }
+
+        if found < CURRENT_LAYOUT_VERSION {
+            fs::write(
+                self.layout_version_path(),
+                CURRENT_LAYOUT_VERSION.to_string(),
+            )
+            .map_err(|e| NodeError::Error(format!("Failed to write layout version: {e}")))?;
+            info!(
+                "Migrated data directory {} from layout version {} to {}",
+                self.root.display(),
+                found,
+                CURRENT_LAYOUT_VERSION
+            );
+        }
+
+        Ok(found)
+    }
+
+    /// Version 0 kept every file directly in the root, except the database, which defaulted
+    /// to the working directory. Moves them into their own subdirectories and repoints the
+    /// config at the moved database and log.
+    fn migrate_flat_layout(&self) -> Result<(), NodeError> {
+        let moves = [
+            (self.root.join(LEGACY_KEY_FILE), self.key_file_path()),
+            (self.root.join(LEGACY_CONFIG_FILE), self.config_file_path()),
+            (PathBuf::from(LEGACY_DATABASE), self.database_directory()),
+            (self.root.join(LEGACY_LOG_FILE), self.log_file_path()),
+        ];
+        for (from, to) in &moves {
+            move_if_present(from, to)?;
+        }
+
+        let config_file_path = self.config_file_path();
+        if !config_file_path.exists() {
+            return Ok(());
+        }
+        let contents = fs::read_to_string(&config_file_path)
+            .map_err(|e| NodeError::Error(format!("Failed to read config file: {e}")))?;
+        let mut config_store = serde_yaml::from_str::<ConfigStore>(&contents)
+            .map_err(|e| NodeError::Error(format!("Failed to deserialize config file: {e}")))?;
+
+        let relocate = |path: &Path| {
+            moves
+                .iter()
+                .find(|(from, _)| from == path)
+                .map(|(_, to)| to.clone())
+        };
+        if let Some(path) = relocate(&config_store.key_file_path) {
+            config_store.key_file_path = path;
+        }
+        if let Some(path) = relocate(&config_store.database_directory) {
+            config_store.database_directory = path;
+        }
+        if let Some(path) = config_store.log_file_path.as_deref().and_then(relocate) {
+            config_store.log_file_path = Some(path);
+        }
+
+        let config_str = serde_yaml::to_string(&config_store)
+            .map_err(|e| NodeError::Error(format!("Failed to serialize config: {e}")))?;
+        fs::write(&config_file_path, config_str)
+            .map_err(|e| NodeError::Error(format!("Failed to write config: {e}")))
+    }
+}
+
+/// Renames `from` to `to`, leaving an existing `to` untouched so a half-finished migration
+/// can be rerun.
+fn move_if_present(from: &Path, to: &Path) -> Result<(), NodeError> {
+    if !from.exists() || to.exists() {
+        return Ok(());
+    }
+    fs::rename(from, to).map_err(|e| {
+        NodeError::Error(format!(
+            "Failed to move {} to {}: {e}",
+            from.display(),
+            to.display()
+        ))
+    })?;
+    info!("Moved {} to {}", from.display(), to.display());
+    Ok(())
+}
@@ -1,17 +1,119 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use crate::{Network, NodeState, handlers::Handler, wallet::Wallet};
-use abci::chain_state::Account;
 use abci::{ChainMessage, ChainResponse};
+use frost_secp256k1 as frost;
+use protocol::block::GenesisBlock;
+use tokio::time::Instant;
 use types::errors::NodeError;
-use types::network::network_event::{BlockInfo, NetworkEvent, SelfRequest, SelfResponse};
+use types::network::network_event::{
+    GenesisInfo, MempoolTransaction, NetworkEvent, SelfRequest, SelfResponse,
+};
+
+/// How long a balance read from the chain is served from the cache.
+pub const DEFAULT_BALANCE_CACHE_TTL: Duration = Duration::from_secs(5);
 
-#[derive(Default)]
-pub struct BalanceState;
+struct CachedBalance {
+    balance: u64,
+    fetched_at: Instant,
+}
+
+pub struct BalanceState {
+    balance_cache: HashMap<String, CachedBalance>,
+    cache_ttl: Duration,
+}
+
+impl Default for BalanceState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl BalanceState {
     #[must_use]
-    pub const fn new() -> Self {
-        Self {}
+    pub fn new() -> Self {
+        Self::with_cache_ttl(DEFAULT_BALANCE_CACHE_TTL)
+    }
+
+    #[must_use]
+    pub fn with_cache_ttl(cache_ttl: Duration) -> Self {
+        Self {
+            balance_cache: HashMap::new(),
+            cache_ttl,
+        }
+    }
+
+    fn cached_balance(&self, address: &str) -> Option<u64> {
+        self.balance_cache
+            .get(address)
+            .filter(|cached| cached.fetched_at.elapsed() < self.cache_ttl)
+            .map(|cached| cached.balance)
+    }
+
+    /// Forgets every cached balance; any finalized block may have moved funds.
+    pub fn invalidate_cache(&mut self) {
+        self.balance_cache.clear();
     }
+
+    async fn fetch_balance<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        address: String,
+    ) -> Result<u64, NodeError> {
+        if let Some(balance) = self.cached_balance(&address) {
+            return Ok(balance);
+        }
+
+        let ChainResponse::GetAccount { account } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::GetAccount {
+                address: address.clone(),
+            })
+            .await?
+        else {
+            return Err(NodeError::Error("Failed to get account".to_string()));
+        };
+
+        let balance = account.map_or(0, |account| account.balance);
+        self.balance_cache.insert(
+            address,
+            CachedBalance {
+                balance,
+                fetched_at: Instant::now(),
+            },
+        );
+        Ok(balance)
+    }
+}
+
+/// Flattens a genesis block for clients, reporting the vault key as the group verifying key
+/// rather than the serialized public key package stored on chain.
+fn genesis_info(genesis: &GenesisBlock) -> Result<GenesisInfo, NodeError> {
+    let state = &genesis.initial_state;
+    let pubkey_package = frost::keys::PublicKeyPackage::deserialize(&state.vault_pub_key)
+        .map_err(|e| NodeError::Error(format!("Invalid vault public key in genesis: {e}")))?;
+    let vault_pub_key = pubkey_package
+        .verifying_key()
+        .serialize()
+        .map_err(|e| NodeError::Error(format!("Failed to serialize vault public key: {e}")))?;
+    let config = &state.chain_config;
+
+    Ok(GenesisInfo {
+        genesis_hash: hex::encode(genesis.hash()),
+        timestamp: genesis.timestamp,
+        vault_pub_key: hex::encode(vault_pub_key),
+        min_signers: config.min_signers,
+        max_signers: config.max_signers,
+        min_stake: config.min_stake,
+        block_time_seconds: config.block_time_seconds,
+        max_block_size: config.max_block_size,
+        validators: state
+            .validators
+            .iter()
+            .map(|validator| (hex::encode(&validator.pub_key), validator.stake))
+            .collect(),
+    })
 }
 
 #[async_trait::async_trait]
@@ -26,19 +128,7 @@ impl<N: Network, W: Wallet> Handler<N, W> for BalanceState {
                 request: SelfRequest::CheckBalance { address },
                 response_channel,
             } => {
-                let ChainResponse::GetAccount { account } = node
-                    .chain_interface_tx
-                    .send_message_with_response(ChainMessage::GetAccount {
-                        address: address.clone(),
-                    })
-                    .await?
-                else {
-                    return Err(NodeError::Error("Failed to get account".to_string()));
-                };
-
-                let account = account.unwrap_or_else(|| Account::new(address, 0));
-
-                let balance = account.balance;
+                let balance = self.fetch_balance(node, address).await?;
 
                 if let Some(response_channel) = response_channel {
                     response_channel
@@ -48,6 +138,27 @@ impl<N: Network, W: Wallet> Handler<N, W> for BalanceState {
                         .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
                 }
             }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetVaultBalance,
+                response_channel,
+            } => {
+                let spendable_satoshis = node.wallet.spendable_balance();
+                let total_satoshis = node
+                    .wallet
+                    .get_utxos()
+                    .iter()
+                    .map(|u| u.utxo.value.to_sat())
+                    .sum();
+
+                if let Some(response_channel) = response_channel {
+                    response_channel
+                        .send(SelfResponse::GetVaultBalanceResponse {
+                            spendable_satoshis,
+                            total_satoshis,
+                        })
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+                }
+            }
             NetworkEvent::SelfRequest {
                 request: SelfRequest::GetChainInfo,
                 response_channel,
@@ -75,39 +186,112 @@ impl<N: Network, W: Wallet> Handler<N, W> for BalanceState {
                 }
             }
             NetworkEvent::SelfRequest {
-                request: SelfRequest::GetLatestBlocks { count: _ },
+                request: SelfRequest::GetLatestBlocks { count },
                 response_channel,
             } => {
-                let ChainResponse::GetChainState { state } = node
+                let ChainResponse::GetLatestBlocks { blocks } = node
                     .chain_interface_tx
-                    .send_message_with_response(ChainMessage::GetChainState)
+                    .send_message_with_response(ChainMessage::GetLatestBlocks { count })
                     .await?
                 else {
-                    return Err(NodeError::Error("Failed to get chain state".to_string()));
+                    return Err(NodeError::Error("Failed to get latest blocks".to_string()));
+                };
+
+                if let Some(response_channel) = response_channel {
+                    response_channel
+                        .send(SelfResponse::GetLatestBlocksResponse { blocks })
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+                }
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetBlock { height },
+                response_channel,
+            } => {
+                let ChainResponse::GetBlock { block } = node
+                    .chain_interface_tx
+                    .send_message_with_response(ChainMessage::GetBlock { height })
+                    .await?
+                else {
+                    return Err(NodeError::Error("Failed to get block".to_string()));
                 };
 
-                let mut blocks = Vec::new();
-                let current_height = state.get_block_height();
-
-                if current_height > 0 {
-                    blocks.push(BlockInfo {
-                        height: current_height,
-                        hash: format!("block_hash_{current_height}"),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                        transaction_count: u32::try_from(state.get_pending_transactions().len())
-                            .unwrap(),
-                    });
+                if let Some(response_channel) = response_channel {
+                    response_channel
+                        .send(SelfResponse::GetBlockResponse {
+                            block_json: block.map(|block| block.to_canonical_json()),
+                        })
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
                 }
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetGenesis,
+                response_channel,
+            } => {
+                let ChainResponse::GetGenesis { genesis } = node
+                    .chain_interface_tx
+                    .send_message_with_response(ChainMessage::GetGenesis)
+                    .await?
+                else {
+                    return Err(NodeError::Error("Failed to get genesis block".to_string()));
+                };
+                let genesis = genesis.as_ref().map(genesis_info).transpose()?;
 
                 if let Some(response_channel) = response_channel {
                     response_channel
-                        .send(SelfResponse::GetLatestBlocksResponse { blocks })
+                        .send(SelfResponse::GetGenesisResponse { genesis })
                         .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
                 }
             }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetMempool,
+                response_channel,
+            } => {
+                let ChainResponse::GetPendingTransactions { transactions } = node
+                    .chain_interface_tx
+                    .send_message_with_response(ChainMessage::GetPendingTransactions)
+                    .await?
+                else {
+                    return Err(NodeError::Error(
+                        "Failed to get pending transactions".to_string(),
+                    ));
+                };
+
+                let transactions = transactions
+                    .iter()
+                    .map(|tx| MempoolTransaction {
+                        hash: hex::encode(tx.id()),
+                        transaction_json: tx.to_json_value().to_string(),
+                    })
+                    .collect();
+
+                if let Some(response_channel) = response_channel {
+                    response_channel
+                        .send(SelfResponse::GetMempoolResponse { transactions })
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+                }
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetAuditLog { from, to },
+                response_channel,
+            } => {
+                let ChainResponse::GetAuditLog { entries } = node
+                    .chain_interface_tx
+                    .send_message_with_response(ChainMessage::GetAuditLog { from, to })
+                    .await?
+                else {
+                    return Err(NodeError::Error("Failed to get audit log".to_string()));
+                };
+
+                if let Some(response_channel) = response_channel {
+                    response_channel
+                        .send(SelfResponse::GetAuditLogResponse { entries })
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+                }
+            }
+            NetworkEvent::BlockFinalized { height } => {
+                tracing::debug!("Block {height} finalized, invalidating balance cache");
+                self.invalidate_cache();
+            }
             _ => {}
         }
         Ok(())
@@ -1,10 +1,12 @@
 use crate::{NodeState, handlers::Handler, handlers::consensus::ConsensusState, wallet::Wallet};
+use abci::{ChainMessage, ChainResponse};
 use consensus::{ConsensusMessage, ConsensusResponse};
-use tracing::error;
+use libp2p::PeerId;
+use tracing::{error, warn};
 use types::broadcast::BroadcastMessage;
-use types::consensus::ConsensusMessage as ConsensusNetMessage;
+use types::consensus::{ConsensusMessage as ConsensusNetMessage, unix_timestamp};
 use types::errors::NodeError;
-use types::network::network_event::{NetworkEvent, SelfRequest, SelfResponse};
+use types::network::network_event::{DirectMessage, NetworkEvent, SelfRequest, SelfResponse};
 use types::network::network_protocol::Network;
 use types::proto::ProtoDecode;
 
@@ -57,6 +59,59 @@ impl<N: Network, W: Wallet> Handler<N, W> for ConsensusState {
                     }
                 }
             }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::ConsensusTick,
+                ..
+            } => {
+                let ChainResponse::GetChainInfo {
+                    height,
+                    pending_transactions,
+                } = node
+                    .chain_interface_tx
+                    .send_message_with_response(ChainMessage::GetChainInfo)
+                    .await?
+                else {
+                    return Err(NodeError::Error("Failed to get chain info".to_string()));
+                };
+                self.watchdog
+                    .observe(height, pending_transactions, node.clock.now());
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetValidatorStats,
+                response_channel: Some(response_channel),
+            } => {
+                response_channel
+                    .send(SelfResponse::GetValidatorStatsResponse {
+                        validators: node.validator_stats.snapshot(),
+                    })
+                    .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+            }
+            NetworkEvent::PeersConnected(list) => {
+                for (peer_id, _) in list {
+                    if let ConsensusResponse::PeerConnected { error: Some(e) } = node
+                        .consensus_interface_tx
+                        .send_message_with_response(ConsensusMessage::PeerConnected {
+                            peer_id: peer_id.to_bytes(),
+                        })
+                        .await?
+                    {
+                        warn!(
+                            "Failed to resume consensus after {} connected: {}",
+                            peer_id, e
+                        );
+                    }
+                }
+            }
+            NetworkEvent::PeersDisconnected(list) => {
+                for (peer_id, _) in list {
+                    let _ = node
+                        .consensus_interface_tx
+                        .send_message_with_response(ConsensusMessage::PeerDisconnected {
+                            peer_id: peer_id.to_bytes(),
+                        })
+                        .await;
+                }
+            }
             NetworkEvent::Subscribed { peer_id, topic: _ } => {
                 // Notify consensus about new validator
                 let _ = node
@@ -72,6 +127,26 @@ impl<N: Network, W: Wallet> Handler<N, W> for ConsensusState {
                         NodeError::Error(format!("Failed to decode broadcast message: {e}"))
                     })?;
 
+                    // Gossip is signed by its source, so a vote's or proposal's timestamp
+                    // cannot be altered in transit; an old one is a replay and must not be
+                    // counted or recorded. A bare block carries no timestamp to check, so it
+                    // cannot be told from a replay and is dropped.
+                    let fresh = match &broadcast {
+                        BroadcastMessage::Consensus(consensus_message) => consensus_message
+                            .is_fresh(
+                                unix_timestamp(),
+                                node.config.max_consensus_message_age_seconds,
+                            ),
+                        BroadcastMessage::Block(_) => false,
+                        _ => true,
+                    };
+                    if !fresh {
+                        warn!("Dropping stale consensus message gossiped by {}", peer);
+                        return Ok(());
+                    }
+
+                    // Stats are recorded only for what consensus accepted, so a message that
+                    // fails validation or repeats one already taken is not counted.
                     match broadcast {
                         BroadcastMessage::Consensus(consensus_message) => match consensus_message {
                             ConsensusNetMessage::LeaderAnnouncement(announcement) => {
@@ -96,45 +171,115 @@ impl<N: Network, W: Wallet> Handler<N, W> for ConsensusState {
                                     .await;
                             }
                             ConsensusNetMessage::Vote(vote) => {
-                                let _ = node
+                                if let Ok(ConsensusResponse::HandleVote {
+                                    accepted: true, ..
+                                }) = node
                                     .consensus_interface_tx
                                     .send_message_with_response(ConsensusMessage::HandleVote {
                                         sender: peer.to_bytes(),
                                         vote,
                                     })
-                                    .await;
+                                    .await
+                                {
+                                    node.validator_stats.record_vote(peer);
+                                }
+                            }
+                            ConsensusNetMessage::VoteAggregate(aggregate) => {
+                                match node
+                                    .consensus_interface_tx
+                                    .send_message_with_response(
+                                        ConsensusMessage::HandleVoteAggregate { aggregate },
+                                    )
+                                    .await?
+                                {
+                                    ConsensusResponse::HandleVoteAggregate {
+                                        error: Some(e),
+                                        ..
+                                    } => {
+                                        warn!(
+                                            "Rejected vote aggregate gossiped by {}: {}",
+                                            peer, e
+                                        );
+                                    }
+                                    ConsensusResponse::HandleVoteAggregate { accepted, .. } => {
+                                        for voter in accepted {
+                                            if let Ok(voter) = PeerId::from_bytes(&voter) {
+                                                node.validator_stats.record_vote(voter);
+                                            }
+                                        }
+                                    }
+                                    _ => {}
+                                }
                             }
                             ConsensusNetMessage::BlockProposal {
                                 proposer,
                                 raw_block,
+                                tx_hashes,
+                                ..
                             } => {
-                                let _ = node
+                                let response = node
                                     .consensus_interface_tx
                                     .send_message_with_response(
                                         ConsensusMessage::HandleBlockProposal {
-                                            sender: proposer,
+                                            sender: proposer.clone(),
                                             raw_block,
+                                            tx_hashes: Some(tx_hashes),
                                         },
                                     )
                                     .await;
+                                record_accepted_proposal(node, &proposer, response);
                             }
                         },
-                        BroadcastMessage::Block(raw_block) => {
-                            let _ = node
-                                .consensus_interface_tx
-                                .send_message_with_response(ConsensusMessage::HandleBlockProposal {
-                                    sender: peer.to_bytes(),
-                                    raw_block,
-                                })
-                                .await;
-                        }
                         _ => {}
                     }
                 }
             }
+            NetworkEvent::MessageEvent((
+                peer,
+                DirectMessage::ConsensusVote { vote, signature },
+            )) => {
+                if !vote.is_fresh(
+                    unix_timestamp(),
+                    node.config.max_consensus_message_age_seconds,
+                ) {
+                    warn!("Dropping stale signed vote from {}", peer);
+                    return Ok(());
+                }
+                match node
+                    .consensus_interface_tx
+                    .send_message_with_response(ConsensusMessage::HandleSignedVote {
+                        sender: peer.to_bytes(),
+                        vote,
+                        signature,
+                    })
+                    .await?
+                {
+                    ConsensusResponse::HandleSignedVote { error: Some(e), .. } => {
+                        warn!("Rejected signed vote from {}: {}", peer, e);
+                    }
+                    ConsensusResponse::HandleSignedVote { accepted: true, .. } => {
+                        node.validator_stats.record_vote(peer);
+                    }
+                    _ => {}
+                }
+            }
             _ => {}
         }
 
         Ok(())
     }
 }
+
+/// Credits `proposer` with a proposal once consensus has validated and prevoted it. A copy
+/// of a proposal already taken is not accepted again, so it is counted once.
+fn record_accepted_proposal<N: Network, W: Wallet>(
+    node: &mut NodeState<N, W>,
+    proposer: &[u8],
+    response: Result<ConsensusResponse, NodeError>,
+) {
+    if let Ok(ConsensusResponse::HandleBlockProposal { accepted: true, .. }) = response {
+        if let Ok(proposer) = PeerId::from_bytes(proposer) {
+            node.validator_stats.record_proposal(proposer);
+        }
+    }
+}
@@ -2,8 +2,14 @@ use libp2p::{PeerId, gossipsub::IdentTopic};
 use std::collections::HashSet;
 use std::time::Duration;
 use tokio::time::Instant;
+use types::network::network_protocol::broadcast_topic;
+
+use crate::config::{DEFAULT_CHAIN_ID, DEFAULT_CONSENSUS_STALL_THRESHOLD_SECONDS};
 
 pub mod handler;
+pub mod watchdog;
+
+use watchdog::LivenessWatchdog;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConsensusPhase {
@@ -29,6 +35,8 @@ pub struct ConsensusState {
     pub precommits: HashSet<PeerId>,
     pub current_block_hash: Option<Vec<u8>>,
     pub block_finalized: bool,
+
+    pub watchdog: LivenessWatchdog,
 }
 
 impl Default for ConsensusState {
@@ -46,7 +54,7 @@ impl ConsensusState {
             current_height: 0,
             proposer: None,
             validators: HashSet::new(),
-            broadcast_topic: IdentTopic::new("broadcast"),
+            broadcast_topic: broadcast_topic(DEFAULT_CHAIN_ID),
             round_timeout: Duration::from_secs(10),
             round_start_time: None,
             is_leader: false,
@@ -54,19 +62,35 @@ impl ConsensusState {
             precommits: HashSet::new(),
             current_block_hash: None,
             block_finalized: false,
+            watchdog: LivenessWatchdog::new(Duration::from_secs(
+                DEFAULT_CONSENSUS_STALL_THRESHOLD_SECONDS,
+            )),
         }
     }
 
+    /// Namespaces the consensus gossip topic under `chain_id`.
+    #[must_use]
+    pub fn with_chain_id(mut self, chain_id: &str) -> Self {
+        self.broadcast_topic = broadcast_topic(chain_id);
+        self
+    }
+
+    /// Overrides how long the chain may sit at one height with pending transactions before
+    /// the liveness watchdog alerts.
+    #[must_use]
+    pub const fn with_stall_threshold(mut self, stall_threshold: Duration) -> Self {
+        self.watchdog.stall_threshold = stall_threshold;
+        self
+    }
+
     #[must_use]
     pub fn select_leader(&self, round: u32) -> Option<PeerId> {
         if self.validators.is_empty() {
             return None;
         }
 
-        let mut sorted_validators: Vec<PeerId> = self.validators.iter().copied().collect();
-        sorted_validators.sort();
-
-        let index = (round as usize) % self.validators.len();
-        sorted_validators.get(index).copied()
+        let rotation = consensus::leader_rotation(&self.validators);
+        let index = (round as usize) % rotation.len();
+        rotation.get(index).copied()
     }
 }
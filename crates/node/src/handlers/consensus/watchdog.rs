@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Raised when the chain has pending transactions but its height has not moved for longer
+/// than the watchdog threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StallAlert {
+    pub height: u64,
+    pub pending_transactions: usize,
+    pub stalled_for: Duration,
+}
+
+/// Tracks when the chain height last advanced and alerts once it has been stuck for
+/// `stall_threshold` with work waiting, e.g. because no quorum can form or the leader keeps
+/// timing out.
+///
+/// An idle chain with an empty mempool is not stalled, so the clock only runs while
+/// transactions are pending.
+#[derive(Debug, Clone)]
+pub struct LivenessWatchdog {
+    pub stall_threshold: Duration,
+    pub last_height: u64,
+    pub last_advance: Option<Instant>,
+    pub alert: Option<StallAlert>,
+}
+
+impl LivenessWatchdog {
+    #[must_use]
+    pub const fn new(stall_threshold: Duration) -> Self {
+        Self {
+            stall_threshold,
+            last_height: 0,
+            last_advance: None,
+            alert: None,
+        }
+    }
+
+    /// Feeds the latest chain height, returning the alert if this observation raised it.
+    pub fn observe(
+        &mut self,
+        height: u64,
+        pending_transactions: usize,
+        now: Instant,
+    ) -> Option<StallAlert> {
+        if height > self.last_height || pending_transactions == 0 {
+            if height > self.last_height && self.alert.is_some() {
+                info!(height, "Consensus resumed, chain height advanced");
+            }
+            self.last_height = height;
+            self.last_advance = Some(now);
+            self.alert = None;
+            metrics::gauge!("consensus_stalled").set(0.0);
+            return None;
+        }
+
+        let since = *self.last_advance.get_or_insert(now);
+        let stalled_for = now.saturating_duration_since(since);
+        if self.alert.is_some() || stalled_for < self.stall_threshold {
+            return None;
+        }
+
+        warn!(
+            height,
+            pending_transactions,
+            stalled_secs = stalled_for.as_secs(),
+            "🚨 Consensus stalled: chain height has not advanced despite pending transactions"
+        );
+        metrics::gauge!("consensus_stalled").set(1.0);
+        metrics::counter!("consensus_stall_alerts_total").increment(1);
+
+        let alert = StallAlert {
+            height,
+            pending_transactions,
+            stalled_for,
+        };
+        self.alert = Some(alert);
+        Some(alert)
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use abci::{ChainMessage, ChainResponse};
+use bitcoin::{Transaction, Txid};
+use tracing::warn;
+use types::errors::NodeError;
+use types::network::network_protocol::Network;
+
+use crate::{NodeState, handlers::deposit::DepositIntentState, wallet::Wallet};
+
+impl DepositIntentState {
+    /// Every deposit this node held back for extra confirmations, with the Bitcoin height it
+    /// is credited at.
+    pub async fn load_awaiting_deposits(
+        chain_interface_tx: &mut messenger::Sender<ChainMessage, ChainResponse>,
+    ) -> Result<HashMap<Txid, (Transaction, u32)>, NodeError> {
+        let ChainResponse::GetAwaitingDeposits { deposits } = chain_interface_tx
+            .send_message_with_response(ChainMessage::GetAwaitingDeposits)
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to load deposits awaiting confirmations".to_string(),
+            ));
+        };
+
+        let mut awaiting = HashMap::new();
+        for (txid, credit_at, raw_tx) in deposits {
+            let tx = match bitcoin::consensus::deserialize::<Transaction>(&raw_tx) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    warn!("Skipping stored awaiting deposit {txid}: {e}");
+                    continue;
+                }
+            };
+            match Txid::from_str(&txid) {
+                Ok(txid) => {
+                    awaiting.insert(txid, (tx, credit_at));
+                }
+                Err(e) => warn!("Skipping stored awaiting deposit txid {txid}: {e}"),
+            }
+        }
+        Ok(awaiting)
+    }
+
+    #[must_use]
+    pub fn with_awaiting_depth(
+        mut self,
+        awaiting_depth: HashMap<Txid, (Transaction, u32)>,
+    ) -> Self {
+        self.awaiting_depth = awaiting_depth;
+        self
+    }
+
+    /// Durably holds `tx` back until Bitcoin reaches `credit_at`, so a restart does not
+    /// forget it.
+    pub(crate) async fn hold_for_depth<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        tx: &Transaction,
+        credit_at: u32,
+    ) -> Result<(), NodeError> {
+        let txid = tx.compute_txid();
+        let ChainResponse::RecordAwaitingDeposit { error } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::RecordAwaitingDeposit {
+                txid: txid.to_string(),
+                credit_at,
+                raw_tx: bitcoin::consensus::serialize(tx),
+            })
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to record deposit awaiting confirmations".to_string(),
+            ));
+        };
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        self.awaiting_depth.insert(txid, (tx.clone(), credit_at));
+        Ok(())
+    }
+
+    /// Forgets the held-back deposit `txid`, in memory and on disk.
+    pub(crate) async fn release_from_depth<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        txid: Txid,
+    ) -> Result<(), NodeError> {
+        let ChainResponse::RemoveAwaitingDeposit { error } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::RemoveAwaitingDeposit {
+                txid: txid.to_string(),
+            })
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to remove deposit awaiting confirmations".to_string(),
+            ));
+        };
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        self.awaiting_depth.remove(&txid);
+        Ok(())
+    }
+}
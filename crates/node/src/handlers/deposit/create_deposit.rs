@@ -1,4 +1,8 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+    time::Duration,
+};
 
 use abci::{ChainMessage, ChainResponse};
 use bitcoin::{
@@ -7,24 +11,165 @@ use bitcoin::{
 };
 use protocol::transaction::Transaction;
 use tokio::sync::broadcast;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use types::{broadcast::BroadcastMessage, errors::NodeError, network::network_protocol::Network};
 use uuid::Uuid;
 
-use crate::{NodeState, handlers::deposit::DepositIntentState, wallet::Wallet};
-use types::intents::DepositIntent;
+use crate::{
+    NodeState,
+    config::{
+        DEFAULT_DEPOSIT_INTENT_TTL_SECONDS, DEFAULT_MAX_PENDING_INTENTS, DEFAULT_MAX_REORG_DEPTH,
+    },
+    handlers::{
+        deposit::{DepositIntentState, reorg::ReorgGuard},
+        wallet::WalletState,
+    },
+    wallet::Wallet,
+};
+use types::audit::{AuditEntry, AuditEventKind, AuditOutcome};
+use types::intents::{
+    DepositEvent, DepositIntent, DepositStatus, QuarantinedDeposit, UnknownDepositPolicy,
+};
+
+/// Capacity of the deposit event channel; slow subscribers skip events beyond this.
+pub const DEPOSIT_EVENT_CHANNEL_CAPACITY: usize = 100;
+/// Capacity of the channel handing deposit intents to the deposit monitor. Intents are held
+/// back rather than sent once this many are unread, since a full channel drops the oldest.
+pub const DEPOSIT_INTENT_CHANNEL_CAPACITY: usize = 100;
 
 impl DepositIntentState {
     #[must_use]
     pub fn new(deposit_intent_tx: broadcast::Sender<DepositIntent>) -> Self {
         Self {
             deposit_addresses: HashSet::new(),
+            watch_addresses: HashSet::new(),
+            max_pending_intents: DEFAULT_MAX_PENDING_INTENTS,
             deposit_intent_tx,
-            processed_txids: HashSet::new(),
+            unsent_intents: VecDeque::new(),
+            deposit_event_tx: broadcast::channel(DEPOSIT_EVENT_CHANNEL_CAPACITY).0,
+            processed_txids: HashMap::new(),
+            awaiting_depth: HashMap::new(),
+            reorg_guard: ReorgGuard::new(DEFAULT_MAX_REORG_DEPTH),
+            intent_ttl: Duration::from_secs(DEFAULT_DEPOSIT_INTENT_TTL_SECONDS),
+            expired_addresses: HashMap::new(),
+            quarantined_deposits: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_deposit_event_tx(
+        mut self,
+        deposit_event_tx: broadcast::Sender<DepositEvent>,
+    ) -> Self {
+        self.deposit_event_tx = deposit_event_tx;
+        self
+    }
+
+    /// Sending fails only when nobody is subscribed, which is the normal case without
+    /// streaming clients, so the error is ignored.
+    fn publish_deposit_event(&self, event: DepositEvent) {
+        let _ = self.deposit_event_tx.send(event);
+    }
+
+    fn publish_pending_deposit(&self, intent: &DepositIntent) {
+        self.publish_deposit_event(DepositEvent {
+            txid: String::new(),
+            address: intent.deposit_address.clone(),
+            amount_sat: intent.amount_sat,
+            confirmations: 0,
+            status: DepositStatus::Pending,
+        });
+    }
+
+    /// Hands `intent` to the deposit monitor behind any intents still waiting for it, or
+    /// queues it for [`Self::flush_monitor_queue`] when the monitor cannot take it now.
+    pub fn notify_monitor(&mut self, intent: DepositIntent) {
+        // A later message for the same intent, such as its cancellation, supersedes a queued one.
+        self.unsent_intents
+            .retain(|queued| queued.deposit_tracking_id != intent.deposit_tracking_id);
+        self.unsent_intents.push_back(intent);
+        self.flush_monitor_queue();
+    }
+
+    /// Sends queued intents to the deposit monitor until it stops accepting them, returning
+    /// how many are still queued.
+    pub fn flush_monitor_queue(&mut self) -> usize {
+        while let Some(intent) = self.unsent_intents.front() {
+            if self.deposit_intent_tx.len() >= DEPOSIT_INTENT_CHANNEL_CAPACITY {
+                warn!(
+                    "Deposit monitor is lagging, holding back {} deposit intents",
+                    self.unsent_intents.len()
+                );
+                break;
+            }
+            if self.deposit_intent_tx.send(intent.clone()).is_err() {
+                warn!(
+                    "Deposit monitor is not listening, holding back {} deposit intents",
+                    self.unsent_intents.len()
+                );
+                break;
+            }
+            self.unsent_intents.pop_front();
+        }
+        #[allow(clippy::cast_precision_loss)]
+        metrics::gauge!("deposit_monitor_queue_length").set(self.unsent_intents.len() as f64);
+        self.unsent_intents.len()
+    }
+
+    #[must_use]
+    pub const fn with_max_pending_intents(mut self, max_pending_intents: usize) -> Self {
+        self.max_pending_intents = max_pending_intents;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_reorg_depth(mut self, max_reorg_depth: u32) -> Self {
+        self.reorg_guard.max_depth = max_reorg_depth;
+        self
+    }
+
+    /// Overrides how long a new intent waits for funds before it is dropped; zero keeps
+    /// intents until they are fulfilled.
+    #[must_use]
+    pub const fn with_intent_ttl(mut self, intent_ttl: Duration) -> Self {
+        self.intent_ttl = intent_ttl;
+        self
+    }
+
+    const fn expiry_from(&self, timestamp: u64) -> u64 {
+        if self.intent_ttl.is_zero() {
+            0
+        } else {
+            timestamp.saturating_add(self.intent_ttl.as_secs())
         }
     }
 
+    /// Once the cap is reached new local intents are refused until existing ones are
+    /// fulfilled or expire. Only unexpired intents count: one past its expiry is on its way
+    /// out through the chain.
+    async fn ensure_intent_capacity<N: Network, W: Wallet>(
+        &self,
+        node: &mut NodeState<N, W>,
+        now: u64,
+    ) -> Result<(), NodeError> {
+        let live = self
+            .get_pending_deposit_intents(node)
+            .await?
+            .iter()
+            .filter(|intent| !intent.is_expired(now))
+            .count();
+        if live >= self.max_pending_intents {
+            return Err(NodeError::TooManyPendingIntents {
+                kind: "deposit".to_string(),
+                limit: self.max_pending_intents,
+            });
+        }
+        Ok(())
+    }
+
+    /// Tracks an intent another node created. It is not held to the local cap: refusing it
+    /// would leave this node blind to a deposit the rest of the network expects.
     pub async fn create_deposit_from_intent<N: Network, W: Wallet>(
         &mut self,
         node: &mut NodeState<N, W>,
@@ -52,9 +197,8 @@ impl DepositIntentState {
             .deposit_addresses
             .insert(deposit_intent.deposit_address.clone())
         {
-            if let Err(e) = self.deposit_intent_tx.send(deposit_intent) {
-                error!("Failed to notify deposit monitor of new address: {}", e);
-            }
+            self.publish_pending_deposit(&deposit_intent);
+            self.notify_monitor(deposit_intent);
         }
 
         Ok(())
@@ -65,37 +209,29 @@ impl DepositIntentState {
         node: &mut NodeState<N, W>,
         user_pubkey: &str,
         amount_sat: u64,
+        min_confirmations: Option<u32>,
     ) -> Result<(String, String), NodeError> {
-        let deposit_tracking_id = Uuid::new_v4().to_string();
-
-        let Some(ref frost_pubkey) = node.pubkey_package else {
-            return Err(NodeError::Error("No public key found".to_string()));
-        };
-
-        let frost_public_key = frost_pubkey
-            .verifying_key()
-            .serialize()
-            .map_err(|x| NodeError::Error(format!("Failed to serialize public key: {x:?}")))?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_else(|_| std::time::Duration::from_secs(0))
+            .as_secs();
+        self.ensure_intent_capacity(node, timestamp).await?;
 
-        let public_key = bitcoin::PublicKey::from_slice(&frost_public_key)
-            .map_err(|e| NodeError::Error(format!("Failed to parse public key: {e}")))?;
-
-        let tweak_scalar = Scalar::from_be_bytes(
-            bitcoin::hashes::sha256::Hash::hash(deposit_tracking_id.as_bytes()).to_byte_array(),
-        )
-        .expect("32 bytes, should not fail");
+        let deposit_tracking_id = Uuid::new_v4().to_string();
 
-        let deposit_address = node.wallet.generate_new_address(public_key, tweak_scalar);
+        let public_key = node.vault_public_key()?;
+        let deposit_address = node
+            .wallet
+            .generate_new_address(public_key, deposit_tweak(&deposit_tracking_id));
 
         let deposit_intent = DepositIntent {
             amount_sat,
             user_pubkey: user_pubkey.to_string(),
             deposit_tracking_id: deposit_tracking_id.clone(),
             deposit_address: deposit_address.to_string(),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                .as_secs(),
+            timestamp,
+            expires_at: self.expiry_from(timestamp),
+            min_confirmations,
         };
 
         let ChainResponse::InsertDepositIntent { error: None } = node
@@ -114,14 +250,258 @@ impl DepositIntentState {
             .deposit_addresses
             .insert(deposit_intent.deposit_address.clone())
         {
-            if let Err(e) = self.deposit_intent_tx.send(deposit_intent.clone()) {
-                error!("Failed to notify deposit monitor of new address: {}", e);
-            }
+            self.publish_pending_deposit(&deposit_intent);
+            self.notify_monitor(deposit_intent);
         }
 
         Ok((deposit_tracking_id, deposit_address.to_string()))
     }
 
+    /// Starts monitoring an externally controlled address for deposits, persisting it so it
+    /// is watched again after a restart.
+    ///
+    /// Payments to it are credited to the account named by the address itself. They never
+    /// reach the vault wallet, since the vault cannot spend them.
+    pub async fn add_watch_address<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        address: &str,
+    ) -> Result<String, NodeError> {
+        let address = Address::from_str(address)
+            .map_err(|e| NodeError::Error(format!("Failed to parse watch address: {e}")))?
+            .require_network(node.wallet.network())
+            .map_err(|e| NodeError::Error(format!("Invalid watch address: {e}")))?;
+        let addr_str = address.to_string();
+
+        if self.deposit_addresses.contains(&addr_str) || self.watch_addresses.contains(&addr_str) {
+            return Err(NodeError::Error(format!(
+                "Address {address} is already being monitored"
+            )));
+        }
+        if node.wallet.controls_script(&address.script_pubkey()) {
+            return Err(NodeError::Error(format!(
+                "Address {address} belongs to the vault and cannot be watched"
+            )));
+        }
+
+        let ChainResponse::RecordWatchAddress { error: None } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::RecordWatchAddress {
+                address: addr_str.clone(),
+            })
+            .await?
+        else {
+            return Err(NodeError::Error(format!(
+                "Failed to persist watch address {address}"
+            )));
+        };
+
+        let watch_id = self.watch(addr_str);
+        info!("👀 Watching address {} for deposits", address);
+
+        Ok(watch_id)
+    }
+
+    /// Hands the watched `address` to the deposit monitor, returning the tracking id it is
+    /// monitored under.
+    pub fn watch(&mut self, address: String) -> String {
+        let watch_id = Uuid::new_v4().to_string();
+        if self.watch_addresses.insert(address.clone()) {
+            self.notify_monitor(DepositIntent {
+                amount_sat: 0,
+                user_pubkey: address.clone(),
+                deposit_tracking_id: watch_id.clone(),
+                deposit_address: address,
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_else(|_| std::time::Duration::from_secs(0))
+                    .as_secs(),
+                // Watched addresses are monitored until they are removed from the store.
+                expires_at: 0,
+                min_confirmations: None,
+            });
+        }
+        watch_id
+    }
+
+    /// Addresses whose funds have reached the wallet or are waiting out extra confirmations,
+    /// and so are on their way to being credited.
+    fn funded_addresses<N: Network, W: Wallet>(&self, node: &NodeState<N, W>) -> HashSet<String> {
+        let mut funded_addresses: HashSet<String> = node
+            .wallet
+            .get_utxos()
+            .iter()
+            .map(|tracked| tracked.address.to_string())
+            .collect();
+        funded_addresses.extend(
+            self.awaiting_depth
+                .values()
+                .flat_map(|(tx, _)| &tx.output)
+                .filter_map(|output| {
+                    Address::from_script(&output.script_pubkey, BitcoinNetwork::Testnet).ok()
+                })
+                .map(|address| address.to_string()),
+        );
+        funded_addresses
+    }
+
+    /// Queues the removal of intents that expired by unix time `now` without receiving
+    /// funds. The removal is a transaction built only from the intent, so every node whose
+    /// clock has passed the expiry queues the same one, and the intent is dropped once a
+    /// block carrying it is finalized.
+    ///
+    /// An intent whose address already holds a UTXO is kept even past its expiry, since its
+    /// deposit is still on the way to being credited.
+    pub async fn expire_intents<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        now: u64,
+    ) -> Result<Vec<DepositIntent>, NodeError> {
+        let funded_addresses = self.funded_addresses(node);
+
+        let mut expiring = Vec::new();
+        for intent in self.get_pending_deposit_intents(node).await? {
+            if !intent.is_expired(now) || funded_addresses.contains(&intent.deposit_address) {
+                continue;
+            }
+
+            let transaction = Transaction::create_expire_deposit_intent_transaction(
+                &intent.deposit_address,
+                intent.expires_at,
+            );
+            let ChainResponse::AddTransactionToBlock { error: None } = node
+                .chain_interface_tx
+                .send_message_with_response(ChainMessage::AddTransactionToBlock { transaction })
+                .await?
+            else {
+                return Err(NodeError::Error(
+                    "Failed to queue deposit intent expiry".to_string(),
+                ));
+            };
+            expiring.push(intent);
+        }
+
+        Ok(expiring)
+    }
+
+    /// Catches up with intents a finalized block expired: they stop counting as pending, and
+    /// their addresses stay watched for one more intent lifetime so a late payment is still
+    /// seen. Addresses whose window closed by unix time `now` are no longer watched.
+    pub async fn settle_expired_intents<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        now: u64,
+    ) -> Result<(), NodeError> {
+        let pending: HashSet<String> = self
+            .get_pending_deposit_intents(node)
+            .await?
+            .into_iter()
+            .map(|intent| intent.deposit_address)
+            .collect();
+        let expired: Vec<String> = self
+            .deposit_addresses
+            .difference(&pending)
+            .cloned()
+            .collect();
+        for address in expired {
+            self.deposit_addresses.remove(&address);
+            info!(
+                "⌛ Deposit intent at {} expired without funds, watching it for late payments",
+                address
+            );
+            metrics::counter!("deposit_intents_expired_total").increment(1);
+            self.expired_addresses
+                .insert(address, now.saturating_add(self.intent_ttl.as_secs()));
+        }
+
+        let closed: Vec<String> = self
+            .expired_addresses
+            .iter()
+            .filter(|(_, watched_until)| now >= **watched_until)
+            .map(|(address, _)| address.clone())
+            .collect();
+        for address in closed {
+            self.expired_addresses.remove(&address);
+            if let Ok(parsed) = Address::from_str(&address) {
+                node.wallet.remove_address(&parsed.assume_checked());
+            }
+            // The monitor drops the address of any expired intent it is sent.
+            self.notify_monitor(DepositIntent {
+                amount_sat: 0,
+                user_pubkey: String::new(),
+                deposit_tracking_id: String::new(),
+                deposit_address: address.clone(),
+                timestamp: now,
+                expires_at: 1,
+                min_confirmations: None,
+            });
+            info!("No longer watching expired deposit address {}", address);
+        }
+
+        Ok(())
+    }
+
+    /// Abandons the pending intent tracked as `deposit_tracking_id` by queuing its removal
+    /// through the chain, the same transaction its expiry would be, so every node drops it
+    /// once a block carrying it is finalized. [`Self::settle_expired_intents`] then stops
+    /// watching its address. Refused once funds have reached the address, since that deposit
+    /// is still to be credited.
+    pub async fn cancel_intent<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        deposit_tracking_id: &str,
+    ) -> Result<DepositEvent, NodeError> {
+        let intent = self
+            .get_pending_deposit_intents(node)
+            .await?
+            .into_iter()
+            .find(|intent| intent.deposit_tracking_id == deposit_tracking_id)
+            .ok_or_else(|| {
+                NodeError::Error(format!(
+                    "No pending deposit intent with tracking id {deposit_tracking_id}"
+                ))
+            })?;
+
+        if self
+            .funded_addresses(node)
+            .contains(&intent.deposit_address)
+        {
+            return Err(NodeError::Error(format!(
+                "Deposit intent {deposit_tracking_id} already received funds at {}",
+                intent.deposit_address
+            )));
+        }
+
+        let transaction = Transaction::create_expire_deposit_intent_transaction(
+            &intent.deposit_address,
+            intent.expires_at,
+        );
+        let ChainResponse::AddTransactionToBlock { error: None } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::AddTransactionToBlock { transaction })
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to queue deposit intent cancellation".to_string(),
+            ));
+        };
+
+        let cancelled = DepositEvent {
+            txid: String::new(),
+            address: intent.deposit_address,
+            amount_sat: intent.amount_sat,
+            confirmations: 0,
+            status: DepositStatus::Cancelled,
+        };
+        self.publish_deposit_event(cancelled.clone());
+        info!(
+            "🗑️ Deposit intent {} cancelled at {}",
+            intent.deposit_tracking_id, cancelled.address
+        );
+        metrics::counter!("deposit_intents_cancelled_total").increment(1);
+        Ok(cancelled)
+    }
+
     pub async fn get_pending_deposit_intents<N: Network, W: Wallet>(
         &self,
         node: &mut NodeState<N, W>,
@@ -138,23 +518,205 @@ impl DepositIntentState {
         }
     }
 
+    /// Looks up the stored intent for `deposit_address` and checks the vault controls it.
+    pub async fn verify_stored_deposit_address<N: Network, W: Wallet>(
+        &self,
+        node: &mut NodeState<N, W>,
+        deposit_address: &str,
+    ) -> Result<bool, NodeError> {
+        let ChainResponse::GetDepositIntentByAddress { intent } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::GetDepositIntentByAddress {
+                address: deposit_address.to_string(),
+            })
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to fetch deposit intent from db".to_string(),
+            ));
+        };
+        let intent = intent.ok_or_else(|| {
+            NodeError::Error(format!("No deposit intent found for {deposit_address}"))
+        })?;
+        node.verify_deposit_address(&intent)
+    }
+
+    /// Confirmations `tx` needs before it is credited: the node's `confirmation_depth`, or the
+    /// largest `min_confirmations` among the intents it pays if that is higher.
+    async fn required_confirmations<N: Network, W: Wallet>(
+        &self,
+        node: &mut NodeState<N, W>,
+        tx: &BitcoinTransaction,
+    ) -> Result<u32, NodeError> {
+        let mut required = node.config.confirmation_depth;
+        for output in &tx.output {
+            let Ok(address) = Address::from_script(&output.script_pubkey, BitcoinNetwork::Testnet)
+            else {
+                continue;
+            };
+            let addr_str = address.to_string();
+            if !self.deposit_addresses.contains(&addr_str) {
+                continue;
+            }
+
+            let response = node
+                .chain_interface_tx
+                .send_message_with_response(ChainMessage::GetDepositIntentByAddress {
+                    address: addr_str,
+                })
+                .await?;
+            if let ChainResponse::GetDepositIntentByAddress {
+                intent: Some(intent),
+            } = response
+            {
+                required = required.max(intent.min_confirmations.unwrap_or_default());
+            }
+        }
+        Ok(required)
+    }
+
+    /// Credits a deposit the monitor reported at the node's `confirmation_depth`, or holds it
+    /// back until it is as deep as its intent's `min_confirmations` asks.
+    pub async fn confirm_deposit<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        tx: &BitcoinTransaction,
+    ) -> Result<(), NodeError> {
+        let txid = tx.compute_txid();
+        if self.awaiting_depth.contains_key(&txid) || self.processed_txids.contains_key(&txid) {
+            return Ok(());
+        }
+
+        let required = self.required_confirmations(node, tx).await?;
+        if required <= node.config.confirmation_depth {
+            return self.insert_pending_deposit_transaction(node, tx).await;
+        }
+
+        // A deposit has its first confirmation at the height it was mined at. The monitor
+        // reports it at `confirmation_depth`, which places it when the oracle cannot.
+        let tip = node.oracle.get_latest_block_height().await?;
+        let reported_at = tip + required - node.config.confirmation_depth;
+        let credit_at = match node.oracle.get_transaction_height(txid).await {
+            Ok(Some(mined_at)) => mined_at + required - 1,
+            Ok(None) => reported_at,
+            Err(e) => {
+                warn!("Cannot place deposit {txid} in a block, counting from the tip: {e}");
+                reported_at
+            }
+        };
+        if tip >= credit_at {
+            return self.insert_pending_deposit_transaction(node, tx).await;
+        }
+
+        info!(
+            "⏳ Deposit {} needs {} confirmations, crediting at height {}",
+            txid, required, credit_at
+        );
+        self.hold_for_depth(node, tx, credit_at).await
+    }
+
+    /// Credits the held-back deposits that are deep enough at Bitcoin height `height`.
+    pub async fn credit_deep_deposits<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        height: u32,
+    ) -> Result<(), NodeError> {
+        let ready: Vec<bitcoin::Txid> = self
+            .awaiting_depth
+            .iter()
+            .filter(|(_, (_, credit_at))| height >= *credit_at)
+            .map(|(txid, _)| *txid)
+            .collect();
+
+        for txid in ready {
+            if let Some((tx, _)) = self.awaiting_depth.get(&txid).cloned() {
+                self.insert_pending_deposit_transaction(node, &tx).await?;
+                self.release_from_depth(node, txid).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Confirmations `txid` has at the current Bitcoin tip. The monitor only reports deposits
+    /// at least `confirmation_depth` deep, which stands in when the oracle cannot place it.
+    async fn confirmations<N: Network, W: Wallet>(
+        node: &NodeState<N, W>,
+        txid: bitcoin::Txid,
+    ) -> u32 {
+        let tip = node.oracle.get_latest_block_height().await;
+        let mined_at = node.oracle.get_transaction_height(txid).await;
+        match (tip, mined_at) {
+            (Ok(tip), Ok(Some(height))) => tip.saturating_sub(height) + 1,
+            (Ok(_), Ok(None)) => {
+                warn!("Oracle has no block for confirmed deposit {txid}");
+                node.config.confirmation_depth
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Cannot count the confirmations of deposit {txid}: {e}");
+                node.config.confirmation_depth
+            }
+        }
+    }
+
     pub async fn insert_pending_deposit_transaction<N: Network, W: Wallet>(
         &mut self,
         node: &mut NodeState<N, W>,
         tx: &BitcoinTransaction,
     ) -> Result<(), NodeError> {
-        if !self.processed_txids.insert(tx.compute_txid()) {
+        self.reorg_guard.check_crediting_allowed()?;
+        if self.processed_txids.contains_key(&tx.compute_txid()) {
             return Ok(());
         }
 
-        for output in &tx.output {
+        // The vault's own spends pay change back to its addresses; that is not a deposit.
+        let spends_vault = node.wallet.is_own_spend(tx);
+        let confirmations = Self::confirmations(node, tx.compute_txid()).await;
+
+        for (vout, output) in (0u32..).zip(&tx.output) {
             if let Ok(address) =
                 Address::from_script(&output.script_pubkey, BitcoinNetwork::Testnet)
             {
                 let addr_str = address.to_string();
 
+                // A watched address stays watched, so unlike an intent it is not removed
+                // once credited.
+                if self.watch_addresses.contains(&addr_str) {
+                    info!(
+                        "👀 Crediting {} sat paid to watched address {}",
+                        output.value.to_sat(),
+                        addr_str
+                    );
+                    let transaction = Transaction::create_deposit_transaction(
+                        tx,
+                        &addr_str,
+                        output.value.to_sat(),
+                    )?
+                    .with_funding_vout(vout);
+                    add_credit_to_block(node, &transaction).await?;
+                    self.publish_deposit_event(DepositEvent {
+                        txid: tx.compute_txid().to_string(),
+                        address: addr_str,
+                        amount_sat: output.value.to_sat(),
+                        confirmations,
+                        status: DepositStatus::Confirmed,
+                    });
+                    broadcast_credit(node, &transaction);
+                    continue;
+                }
+
                 if !self.deposit_addresses.contains(&addr_str) {
-                    info!("❌ Address {} not in deposit_addresses, skipping", addr_str);
+                    if !spends_vault && node.wallet.controls_script(&output.script_pubkey) {
+                        self.handle_unknown_deposit(
+                            node,
+                            tx,
+                            vout,
+                            &addr_str,
+                            output.value.to_sat(),
+                        )
+                        .await?;
+                    } else {
+                        info!("❌ Address {} not in deposit_addresses, skipping", addr_str);
+                    }
                     continue;
                 }
 
@@ -179,29 +741,23 @@ impl DepositIntentState {
                         tx,
                         &intent.user_pubkey,
                         output.value.to_sat(),
-                    )?;
+                    )?
+                    .with_funding_vout(vout);
 
                     info!("🔍 Created transaction: {}", hex::encode(transaction.id()));
 
-                    let add_tx_response = node
-                        .chain_interface_tx
-                        .send_message_with_response(ChainMessage::AddTransactionToBlock {
-                            transaction: transaction.clone(),
-                        })
-                        .await?;
+                    add_credit_to_block(node, &transaction).await?;
 
-                    let ChainResponse::AddTransactionToBlock { error: None } = add_tx_response
-                    else {
-                        return Err(NodeError::Error(
-                            "Failed to execute transaction".to_string(),
-                        ));
-                    };
-
-                    info!("✅ Transaction successfully added to block");
+                    self.publish_deposit_event(DepositEvent {
+                        txid: tx.compute_txid().to_string(),
+                        address: addr_str.clone(),
+                        amount_sat: output.value.to_sat(),
+                        confirmations,
+                        status: DepositStatus::Confirmed,
+                    });
 
                     // Remove the transaction from the deposit addresses
                     self.deposit_addresses.remove(&addr_str);
-                    self.processed_txids.insert(tx.compute_txid());
                     let remove_intent_response = node
                         .chain_interface_tx
                         .send_message_with_response(ChainMessage::RemoveDepositIntent {
@@ -227,30 +783,129 @@ impl DepositIntentState {
                         }
                     }
 
-                    // Broadcast the transaction to all other nodes
-                    match bincode::encode_to_vec(&transaction, bincode::config::standard()) {
-                        Ok(transaction_data) => {
-                            if let Err(e) = node
-                                .network_handle
-                                .send_broadcast(BroadcastMessage::Transaction(transaction_data))
-                            {
-                                info!("Failed to broadcast transaction: {e:?}");
-                            } else {
-                                info!("📤 Successfully broadcast transaction to all nodes");
-                            }
-                        }
-                        Err(e) => {
-                            info!("Failed to encode transaction for broadcast: {}", e);
-                        }
-                    }
+                    broadcast_credit(node, &transaction);
                 } else {
                     info!("❌ No deposit intent found for address: {}", addr_str);
+                    if !spends_vault {
+                        self.handle_unknown_deposit(
+                            node,
+                            tx,
+                            vout,
+                            &addr_str,
+                            output.value.to_sat(),
+                        )
+                        .await?;
+                    }
                 }
             }
         }
 
-        node.wallet.ingest_external_tx(tx)?;
+        // Only once every credit is queued, so a failure above leaves the deposit to be
+        // credited again when it is next reported.
+        self.mark_processed(node, tx.compute_txid()).await?;
+
+        WalletState::ingest(node, tx)?;
 
         Ok(())
     }
+
+    /// Applies the configured [`UnknownDepositPolicy`] to output `vout` of `tx`, which pays
+    /// `amount_sat` to the vault-controlled `address` but matches no deposit intent.
+    async fn handle_unknown_deposit<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        tx: &BitcoinTransaction,
+        vout: u32,
+        address: &str,
+        amount_sat: u64,
+    ) -> Result<(), NodeError> {
+        match node.config.unknown_deposit_policy.clone() {
+            UnknownDepositPolicy::Ignore => {
+                info!("Ignoring {amount_sat} sat paid to {address} without a deposit intent");
+            }
+            UnknownDepositPolicy::CreditToFallbackAccount { account } => {
+                info!(
+                    "Crediting {amount_sat} sat paid to {address} without a deposit intent to {account}"
+                );
+                let transaction =
+                    Transaction::create_deposit_transaction(tx, &account, amount_sat)?
+                        .with_funding_vout(vout);
+                add_credit_to_block(node, &transaction).await?;
+                broadcast_credit(node, &transaction);
+            }
+            UnknownDepositPolicy::Quarantine => {
+                let deposit = QuarantinedDeposit {
+                    txid: tx.compute_txid().to_string(),
+                    vout,
+                    address: address.to_string(),
+                    amount_sat,
+                };
+                warn!(
+                    "Quarantining {amount_sat} sat paid to {address} without a deposit intent in {}:{vout}",
+                    deposit.txid
+                );
+                node.audit(AuditEntry::new(
+                    AuditEventKind::DepositQuarantined,
+                    amount_sat,
+                    address,
+                    format!("{}:{vout}", deposit.txid),
+                    AuditOutcome::Accepted,
+                ))
+                .await?;
+                self.quarantined_deposits.push(deposit);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Queues a deposit credit for the next block.
+async fn add_credit_to_block<N: Network, W: Wallet>(
+    node: &mut NodeState<N, W>,
+    transaction: &Transaction,
+) -> Result<(), NodeError> {
+    let add_tx_response = node
+        .chain_interface_tx
+        .send_message_with_response(ChainMessage::AddTransactionToBlock {
+            transaction: transaction.clone(),
+        })
+        .await?;
+
+    let ChainResponse::AddTransactionToBlock { error: None } = add_tx_response else {
+        return Err(NodeError::Error(
+            "Failed to execute transaction".to_string(),
+        ));
+    };
+
+    info!("✅ Transaction successfully added to block");
+    Ok(())
+}
+
+/// Broadcasts a deposit credit to all other nodes.
+fn broadcast_credit<N: Network, W: Wallet>(node: &NodeState<N, W>, transaction: &Transaction) {
+    match bincode::encode_to_vec(transaction, bincode::config::standard()) {
+        Ok(transaction_data) => {
+            if let Err(e) = node
+                .network_handle
+                .send_broadcast(BroadcastMessage::Transaction(transaction_data))
+            {
+                info!("Failed to broadcast transaction: {e:?}");
+            } else {
+                info!("📤 Successfully broadcast transaction to all nodes");
+            }
+        }
+        Err(e) => {
+            info!("Failed to encode transaction for broadcast: {}", e);
+        }
+    }
+}
+
+/// Tweak that derives the deposit address of the intent tracked as `deposit_tracking_id` from
+/// the vault key: the SHA-256 of the tracking id.
+#[must_use]
+pub fn deposit_tweak(deposit_tracking_id: &str) -> Scalar {
+    Scalar::from_be_bytes(
+        bitcoin::hashes::sha256::Hash::hash(deposit_tracking_id.as_bytes()).to_byte_array(),
+    )
+    .expect("32 bytes, should not fail")
 }
@@ -1,11 +1,11 @@
 use libp2p::gossipsub::Message;
-use tracing::info;
+use tracing::{info, warn};
 use types::broadcast::BroadcastMessage;
 use types::errors::NodeError;
 
 use crate::{
     NodeState,
-    handlers::{Handler, deposit::DepositIntentState},
+    handlers::{Handler, deposit::DepositIntentState, wallet::WalletState},
     wallet::Wallet,
 };
 
@@ -27,11 +27,14 @@ impl<N: Network, W: Wallet> Handler<N, W> for DepositIntentState {
                     SelfRequest::CreateDeposit {
                         user_pubkey,
                         amount_sat,
+                        min_confirmations,
                     },
                 response_channel,
             } => {
                 println!("Node receveived request to create deposit");
-                let response = self.create_deposit(node, &user_pubkey, amount_sat).await;
+                let response = self
+                    .create_deposit(node, &user_pubkey, amount_sat, min_confirmations)
+                    .await;
                 if let Some(response_channel) = response_channel {
                     match response {
                         Ok((deposit_tracking_id, deposit_address)) => {
@@ -80,14 +83,65 @@ impl<N: Network, W: Wallet> Handler<N, W> for DepositIntentState {
                     }
                 }
             }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::AddWatchAddress { address },
+                response_channel,
+            } => {
+                let response = self.add_watch_address(node, &address).await;
+                if let Some(response_channel) = response_channel {
+                    let response = match response {
+                        Ok(deposit_tracking_id) => SelfResponse::AddWatchAddressResponse {
+                            deposit_tracking_id,
+                        },
+                        Err(e) => SelfResponse::NodeError(e),
+                    };
+                    response_channel
+                        .send(response)
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+                }
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::VerifyDepositAddress { deposit_address },
+                response_channel,
+            } => {
+                let response = self
+                    .verify_stored_deposit_address(node, &deposit_address)
+                    .await;
+                if let Some(response_channel) = response_channel {
+                    let response = match response {
+                        Ok(verified) => SelfResponse::VerifyDepositAddressResponse { verified },
+                        Err(e) => SelfResponse::NodeError(e),
+                    };
+                    response_channel
+                        .send(response)
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+                }
+            }
+            NetworkEvent::SelfRequest {
+                request:
+                    SelfRequest::CancelDepositIntent {
+                        deposit_tracking_id,
+                    },
+                response_channel,
+            } => {
+                let response = self.cancel_intent(node, &deposit_tracking_id).await;
+                if let Some(response_channel) = response_channel {
+                    let response = match response {
+                        Ok(cancelled) => SelfResponse::CancelDepositIntentResponse {
+                            deposit_address: cancelled.address,
+                        },
+                        Err(e) => SelfResponse::NodeError(e),
+                    };
+                    response_channel
+                        .send(response)
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+                }
+            }
             NetworkEvent::SelfRequest {
                 request: SelfRequest::ConfirmDeposit { confirmed_tx },
                 ..
             } => {
-                if let Err(e) = self
-                    .insert_pending_deposit_transaction(node, &confirmed_tx)
-                    .await
-                {
+                if let Err(e) = self.confirm_deposit(node, &confirmed_tx).await {
                     info!("❌ Failed to update user balance: {}", e);
                 } else {
                     info!(
@@ -96,6 +150,43 @@ impl<N: Network, W: Wallet> Handler<N, W> for DepositIntentState {
                     );
                 }
             }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::MaintenanceTick,
+                ..
+            } => {
+                self.flush_monitor_queue();
+
+                if let Err(e) = self.reorg_guard.observe(node.oracle.as_ref()).await {
+                    warn!("Failed to check the Bitcoin chain for reorgs: {e}");
+                }
+
+                if let Err(e) = self.prune_processed_txids(node).await {
+                    warn!("Failed to prune processed deposit txids: {e}");
+                }
+
+                if !self.awaiting_depth.is_empty() {
+                    let credited = match node.oracle.get_latest_block_height().await {
+                        Ok(height) => self.credit_deep_deposits(node, height).await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = credited {
+                        warn!("Failed to credit deposits awaiting confirmations: {e}");
+                    }
+                }
+
+                if !self.deposit_addresses.is_empty() || !self.expired_addresses.is_empty() {
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or_default();
+                    if let Err(e) = self.expire_intents(node, now).await {
+                        warn!("Failed to expire deposit intents: {e}");
+                    }
+                    if let Err(e) = self.settle_expired_intents(node, now).await {
+                        warn!("Failed to settle expired deposit intents: {e}");
+                    }
+                }
+            }
             NetworkEvent::GossipsubMessage(Message { data, .. }) => {
                 let broadcast = BroadcastMessage::decode(&data).map_err(|e| {
                     NodeError::Error(format!("Failed to decode broadcast message: {e}"))
@@ -129,7 +220,7 @@ impl<N: Network, W: Wallet> Handler<N, W> for DepositIntentState {
                             }
 
                             let tx = transaction.get_deposit_transaction_address()?;
-                            node.wallet.ingest_external_tx(&tx)?;
+                            WalletState::ingest(node, &tx)?;
                         }
                         Err(e) => {
                             info!("Failed to decode broadcasted transaction: {}", e);
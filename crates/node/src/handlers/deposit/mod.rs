@@ -1,13 +1,45 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
 use tokio::sync::broadcast;
-use types::intents::DepositIntent;
+use types::intents::{DepositEvent, DepositIntent, QuarantinedDeposit};
 
+use crate::handlers::deposit::reorg::ReorgGuard;
+
+pub mod awaiting_depth;
 pub mod create_deposit;
 pub mod handler;
+pub mod processed_txids;
+pub mod reorg;
 
 pub struct DepositIntentState {
     pub deposit_addresses: HashSet<String>,
+    /// Externally controlled addresses watched on behalf of operators. Payments to them are
+    /// credited to the account named by the address, but never added to the wallet since the
+    /// vault cannot spend them. Persisted and reloaded at startup.
+    pub watch_addresses: HashSet<String>,
+    pub max_pending_intents: usize,
     pub deposit_intent_tx: broadcast::Sender<DepositIntent>,
-    pub processed_txids: HashSet<bitcoin::Txid>,
+    /// Intents the deposit monitor has not been handed yet, because it was not subscribed or
+    /// was too far behind; retried in order on every maintenance tick.
+    pub unsent_intents: VecDeque<DepositIntent>,
+    pub deposit_event_tx: broadcast::Sender<DepositEvent>,
+    /// Deposits already credited, with the Bitcoin height they were credited at. Persisted
+    /// so restarts do not credit them again, and pruned once they fall out of the retention
+    /// window.
+    pub processed_txids: HashMap<bitcoin::Txid, u32>,
+    /// Confirmed deposits whose intent asks for more confirmations than the node's
+    /// `confirmation_depth`, with the Bitcoin height at which they may be credited. Persisted
+    /// so a restart still credits them.
+    pub awaiting_depth: HashMap<bitcoin::Txid, (bitcoin::Transaction, u32)>,
+    pub reorg_guard: ReorgGuard,
+    /// How long a new intent waits for funds before it is dropped; zero disables expiry.
+    pub intent_ttl: Duration,
+    /// Addresses of intents a finalized block expired, with the unix time until which they
+    /// are still watched. A payment arriving in that window has no intent to credit, so it
+    /// is handled under the node's `UnknownDepositPolicy`.
+    pub expired_addresses: HashMap<String, u64>,
+    /// Deposits to vault addresses without an intent, held back for an operator under
+    /// `UnknownDepositPolicy::Quarantine`; each is also in the audit log.
+    pub quarantined_deposits: Vec<QuarantinedDeposit>,
 }
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use abci::{ChainMessage, ChainResponse};
+use bitcoin::Txid;
+use tracing::{info, warn};
+use types::errors::NodeError;
+use types::network::network_protocol::Network;
+
+use crate::{NodeState, handlers::deposit::DepositIntentState, wallet::Wallet};
+
+/// Bitcoin blocks a credited deposit txid is remembered for, about two weeks. By then the
+/// credit is long finalized and its intent removed, so the monitor no longer matches it.
+pub const PROCESSED_TXID_RETENTION_BLOCKS: u32 = 2_016;
+
+impl DepositIntentState {
+    /// Every deposit txid this node has credited within the retention window, with the
+    /// Bitcoin height it was credited at.
+    pub async fn load_processed_txids(
+        chain_interface_tx: &mut messenger::Sender<ChainMessage, ChainResponse>,
+    ) -> Result<HashMap<Txid, u32>, NodeError> {
+        let ChainResponse::GetProcessedDeposits { deposits } = chain_interface_tx
+            .send_message_with_response(ChainMessage::GetProcessedDeposits)
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to load processed deposits".to_string(),
+            ));
+        };
+
+        let mut processed = HashMap::new();
+        for (txid, height) in deposits {
+            match Txid::from_str(&txid) {
+                Ok(txid) => {
+                    processed.insert(txid, height);
+                }
+                Err(e) => warn!("Skipping stored deposit txid {txid}: {e}"),
+            }
+        }
+        Ok(processed)
+    }
+
+    #[must_use]
+    pub fn with_processed_txids(mut self, processed_txids: HashMap<Txid, u32>) -> Self {
+        self.processed_txids = processed_txids;
+        self
+    }
+
+    /// Durably marks `txid` as credited once its credits are queued for a block, so a restart
+    /// does not credit it again.
+    pub(crate) async fn mark_processed<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        txid: Txid,
+    ) -> Result<(), NodeError> {
+        if self.processed_txids.contains_key(&txid) {
+            return Ok(());
+        }
+
+        let bitcoin_height = match self.reorg_guard.tip_height() {
+            Some(height) => height,
+            None => node.oracle.get_latest_block_height().await?,
+        };
+        let ChainResponse::RecordProcessedDeposit { error } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::RecordProcessedDeposit {
+                txid: txid.to_string(),
+                bitcoin_height,
+            })
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to record processed deposit".to_string(),
+            ));
+        };
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        self.processed_txids.insert(txid, bitcoin_height);
+        Ok(())
+    }
+
+    /// Forgets the txids credited more than `PROCESSED_TXID_RETENTION_BLOCKS` below the last
+    /// observed Bitcoin tip, in memory and on disk.
+    pub async fn prune_processed_txids<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+    ) -> Result<(), NodeError> {
+        let Some(tip) = self.reorg_guard.tip_height() else {
+            return Ok(());
+        };
+        let cutoff = tip.saturating_sub(PROCESSED_TXID_RETENTION_BLOCKS);
+        if !self.processed_txids.values().any(|&height| height < cutoff) {
+            return Ok(());
+        }
+
+        let ChainResponse::PruneProcessedDeposits { pruned } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::PruneProcessedDeposits {
+                bitcoin_height: cutoff,
+            })
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to prune processed deposits".to_string(),
+            ));
+        };
+        let pruned = pruned?;
+
+        self.processed_txids.retain(|_, height| *height >= cutoff);
+        info!("Pruned {pruned} processed deposit txids credited below height {cutoff}");
+        Ok(())
+    }
+}
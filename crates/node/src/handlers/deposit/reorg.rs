@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use bitcoin::BlockHash;
+use oracle::oracle::Oracle;
+use tracing::{error, warn};
+use types::errors::NodeError;
+
+/// Reorg the node was not willing to follow: blocks from `fork_height` up were replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReorgAlert {
+    pub fork_height: u32,
+    pub depth: u32,
+}
+
+/// Watches the Bitcoin tip for reorgs and halts deposit crediting when one replaces more
+/// than `max_depth` blocks, leaving the credits already made for an operator to review.
+///
+/// Only the last `max_depth + 1` block hashes are remembered, so a reorg replacing all of
+/// them is reported with that depth even if it went further back.
+#[derive(Debug, Clone)]
+pub struct ReorgGuard {
+    pub max_depth: u32,
+    pub recent_blocks: BTreeMap<u32, BlockHash>,
+    pub alert: Option<ReorgAlert>,
+}
+
+impl ReorgGuard {
+    #[must_use]
+    pub const fn new(max_depth: u32) -> Self {
+        Self {
+            max_depth,
+            recent_blocks: BTreeMap::new(),
+            alert: None,
+        }
+    }
+
+    /// Fails once a reorg deeper than the limit was seen; crediting stays halted until restart.
+    pub const fn check_crediting_allowed(&self) -> Result<(), NodeError> {
+        match self.alert {
+            Some(ReorgAlert { depth, .. }) => Err(NodeError::ReorgTooDeep {
+                depth,
+                max_depth: self.max_depth,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Height of the newest block seen by [`Self::observe`], if it has run.
+    #[must_use]
+    pub fn tip_height(&self) -> Option<u32> {
+        self.recent_blocks
+            .last_key_value()
+            .map(|(&height, _)| height)
+    }
+
+    /// Compares the remembered blocks against the oracle's chain and records the new tip.
+    pub async fn observe(&mut self, oracle: &dyn Oracle) -> Result<(), NodeError> {
+        if self.alert.is_some() {
+            return Ok(());
+        }
+
+        let tip = oracle.get_latest_block_height().await?;
+
+        // Walk back from the newest remembered block to the first one still on the chain.
+        let mut fork_height = None;
+        for (&height, &hash) in self.recent_blocks.iter().rev() {
+            if height <= tip && oracle.get_block_hash(height).await? == hash {
+                break;
+            }
+            fork_height = Some(height);
+        }
+
+        if let Some(fork_height) = fork_height {
+            let depth =
+                u32::try_from(self.recent_blocks.range(fork_height..).count()).unwrap_or(u32::MAX);
+            if depth > self.max_depth {
+                error!(
+                    "🚨 Reorg of {depth} blocks from height {fork_height} exceeds the maximum of {}; halting deposit crediting",
+                    self.max_depth
+                );
+                metrics::gauge!("deposit_crediting_halted").set(1.0);
+                self.alert = Some(ReorgAlert { fork_height, depth });
+                return Ok(());
+            }
+            warn!("Reorg of {depth} blocks from height {fork_height}");
+            self.recent_blocks.split_off(&fork_height);
+        }
+
+        let window_start = tip.saturating_sub(self.max_depth);
+        let next = self
+            .recent_blocks
+            .last_key_value()
+            .map_or(window_start, |(&height, _)| height + 1)
+            .max(window_start);
+        for height in next..=tip {
+            let hash = oracle.get_block_hash(height).await?;
+            self.recent_blocks.insert(height, hash);
+        }
+        self.recent_blocks = self.recent_blocks.split_off(&window_start);
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{NodeState, handlers::Handler, handlers::dkg::DkgState, wallet::Wallet};
 use p2p_proto::dkg_message::Message as DkgInner;
 use types::broadcast::BroadcastMessage;
-use types::network::network_event::{DirectMessage, NetworkEvent};
+use types::network::network_event::{DirectMessage, NetworkEvent, SelfRequest, SelfResponse};
 use types::network::network_protocol::Network;
 use types::proto::ProtoDecode;
 use types::proto::p2p_proto::{self, gossipsub_message::Message};
@@ -51,6 +51,35 @@ impl<N: Network, W: Wallet> Handler<N, W> for DkgState {
                 );
                 self.handle_round2_payload(node, peer, package).await?;
             }
+            NetworkEvent::MessageEvent((peer, DirectMessage::Round2Ack { package_hash })) => {
+                self.handle_round2_ack(node, peer, &package_hash)?;
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::RestartDkg { force },
+                response_channel,
+            } => {
+                let response = match self.restart_dkg(node, force) {
+                    Ok(()) => SelfResponse::RestartDkgResponse {
+                        success: true,
+                        message: "DKG restarted".to_string(),
+                    },
+                    Err(e) => SelfResponse::RestartDkgResponse {
+                        success: false,
+                        message: e.to_string(),
+                    },
+                };
+                if let Some(response_channel) = response_channel {
+                    response_channel.send(response).map_err(|e| {
+                        types::errors::NodeError::Error(format!("Failed to send response: {e}"))
+                    })?;
+                }
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::ConsensusTick,
+                ..
+            } => {
+                self.resend_unacked_round2_packages(node)?;
+            }
             _ => {}
         }
         Ok(())
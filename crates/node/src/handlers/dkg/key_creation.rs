@@ -1,13 +1,22 @@
 use abci::{ChainMessage, ChainResponse};
-use frost_secp256k1::{self as frost, keys::dkg::round2};
+use frost_secp256k1::{
+    self as frost,
+    keys::dkg::{round1, round2},
+};
 use libp2p::PeerId;
 use protocol::block::{ChainConfig, ValidatorInfo};
+use sha2::{Digest, Sha256};
 use std::time::Duration;
 use types::broadcast::BroadcastMessage;
 use types::{errors::NodeError, network::network_event::DirectMessage};
 
 use crate::peer_id_to_identifier;
-use crate::{NodeState, handlers::dkg::DkgState, wallet::Wallet};
+use crate::utils::threshold_scheme::{DefaultScheme, ThresholdScheme};
+use crate::{
+    NodeState,
+    handlers::dkg::{DkgState, MAX_ROUND2_ATTEMPTS, PendingRound2Delivery},
+    wallet::Wallet,
+};
 use types::network::network_protocol::Network;
 use types::proto::p2p_proto::{
     DkgMessage, GossipsubMessage, StartDkgMessage, dkg_message::Message,
@@ -20,6 +29,68 @@ fn dkg_step_delay() -> Duration {
         .map_or_else(|| Duration::from_secs(1), Duration::from_secs)
 }
 
+/// SHA-256 of the canonical serialization of a round2 package, echoed back in its ack.
+pub fn round2_package_hash(package: &round2::Package) -> Result<[u8; 32], NodeError> {
+    let serialized = package
+        .serialize()
+        .map_err(|e| NodeError::Error(format!("Failed to serialize round2 package: {e}")))?;
+    Ok(Sha256::digest(&serialized).into())
+}
+
+/// Checks the VSS commitment of a peer's round1 package before it is counted:
+/// one coefficient commitment per polynomial coefficient (`min_signers`), none of
+/// which may be the identity point.
+pub fn validate_round1_commitment(
+    package: &round1::Package,
+    min_signers: usize,
+) -> Result<(), NodeError> {
+    // Serializing a commitment fails if any coefficient is the identity point.
+    let coefficients = package.commitment().serialize().map_err(|e| {
+        NodeError::Error(format!("Round1 commitment contains an invalid point: {e}"))
+    })?;
+    if coefficients.len() != min_signers {
+        return Err(NodeError::Error(format!(
+            "Round1 commitment has {} coefficients, expected {min_signers}",
+            coefficients.len()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Smallest threshold FROST accepts for either signer count.
+pub const MIN_FROST_SIGNERS: u16 = 2;
+
+/// Checks the configured signer counts against what FROST supports, returning
+/// `(min_signers, max_signers)` so a bad configuration is rejected before the ceremony starts.
+pub fn validate_signer_counts(
+    min_signers: Option<u16>,
+    max_signers: Option<u16>,
+) -> Result<(u16, u16), NodeError> {
+    let invalid = |reason: String| NodeError::InvalidSignerConfig { reason };
+
+    let max_signers = max_signers.ok_or_else(|| invalid("max_signers is not set".to_string()))?;
+    let min_signers = min_signers.ok_or_else(|| invalid("min_signers is not set".to_string()))?;
+
+    if max_signers < MIN_FROST_SIGNERS {
+        return Err(invalid(format!(
+            "max_signers is {max_signers}, FROST requires at least {MIN_FROST_SIGNERS}"
+        )));
+    }
+    if min_signers < MIN_FROST_SIGNERS {
+        return Err(invalid(format!(
+            "min_signers is {min_signers}, FROST requires at least {MIN_FROST_SIGNERS}"
+        )));
+    }
+    if min_signers > max_signers {
+        return Err(invalid(format!(
+            "min_signers ({min_signers}) exceeds max_signers ({max_signers})"
+        )));
+    }
+
+    Ok((min_signers, max_signers))
+}
+
 impl DkgState {
     pub fn handle_dkg_start<N: Network, W: Wallet>(
         &mut self,
@@ -35,11 +106,9 @@ impl DkgState {
             return Ok(());
         }
 
-        let max_signers = node
-            .config
-            .max_signers
-            .ok_or_else(|| NodeError::Error("Max signers not set".to_string()))?
-            as usize;
+        let (_, max_signers) =
+            validate_signer_counts(node.config.min_signers, node.config.max_signers)?;
+        let max_signers = usize::from(max_signers);
 
         if self.dkg_listeners.len() + 1 < max_signers
             || self.round1_listeners.len() + 1 < max_signers
@@ -74,22 +143,16 @@ impl DkgState {
         if self.dkg_started {
             return Err(NodeError::Error("DKG already started".to_string()));
         }
+        let (min_signers, max_signers) =
+            validate_signer_counts(node.config.min_signers, node.config.max_signers)?;
         self.dkg_started = true;
 
         // Run the DKG initialization code
         let participant_identifier = peer_id_to_identifier(&node.peer_id);
 
-        let (round1_secret_package, round1_package) = frost::keys::dkg::part1(
-            participant_identifier,
-            node.config
-                .max_signers
-                .ok_or_else(|| NodeError::Error("Max signers not set".to_string()))?,
-            node.config
-                .min_signers
-                .ok_or_else(|| NodeError::Error("Min signers not set".to_string()))?,
-            node.rng,
-        )
-        .expect("Failed to generate round1 package");
+        let (round1_secret_package, round1_package) =
+            DefaultScheme::dkg_part1(participant_identifier, max_signers, min_signers, node.rng)
+                .map_err(|e| NodeError::Error(format!("Failed to generate round1 package: {e}")))?;
 
         self.r1_secret_package = Some(round1_secret_package);
 
@@ -189,6 +252,20 @@ impl DkgState {
                 )));
             }
         };
+        let min_signers = node
+            .config
+            .min_signers
+            .ok_or_else(|| NodeError::Error("Min signers not set".to_string()))?
+            as usize;
+        if let Err(e) = validate_round1_commitment(&package, min_signers) {
+            tracing::warn!(
+                "Rejecting round1 package from {}: {}",
+                node.network_handle.peer_name(&sender_peer_id),
+                e
+            );
+            return Err(e);
+        }
+
         // Add package to peer packages
         self.round1_peer_packages.insert(identifier, package);
 
@@ -254,15 +331,16 @@ impl DkgState {
                 );
                 // all packages received
                 let part2_result =
-                    frost::keys::dkg::part2(r1_secret_package.clone(), &self.round1_peer_packages);
+                    DefaultScheme::dkg_part2(r1_secret_package.clone(), &self.round1_peer_packages);
                 match part2_result {
                     Ok((round2_secret_package, round2_packages)) => {
                         tracing::info!("✅ All round1 packages collected; entering FROST round 2");
                         self.r1_secret_package = None;
                         self.r2_secret_package = Some(round2_secret_package);
 
-                        for peer_to_send_to in &self.dkg_listeners {
-                            let identifier = peer_id_to_identifier(peer_to_send_to);
+                        let listeners: Vec<PeerId> = self.dkg_listeners.iter().copied().collect();
+                        for peer_to_send_to in listeners {
+                            let identifier = peer_id_to_identifier(&peer_to_send_to);
                             let package_to_send =
                                 round2_packages.get(&identifier).ok_or_else(|| {
                                     tracing::warn!(
@@ -274,31 +352,11 @@ impl DkgState {
                                     ))
                                 })?;
 
-                            let request = DirectMessage::Round2Package(package_to_send.clone());
-
-                            match node
-                                .network_handle
-                                .send_private_message(*peer_to_send_to, request)
-                            {
-                                Ok(()) => {
-                                    tracing::debug!(
-                                        "{} Sent round2 package to {}",
-                                        node.peer_id,
-                                        peer_to_send_to
-                                    );
-                                }
-                                Err(e) => {
-                                    tracing::error!(
-                                        "Round2 package not found for {}",
-                                        peer_to_send_to
-                                    );
-                                    return Err(NodeError::Error(format!(
-                                        "Failed to send private request: {e:?}"
-                                    )));
-                                }
-                            }
-
-                            tracing::debug!("Sent round2 package to {}", peer_to_send_to);
+                            self.send_round2_package(
+                                node,
+                                peer_to_send_to,
+                                package_to_send.clone(),
+                            )?;
                         }
 
                         std::thread::sleep(dkg_step_delay());
@@ -322,16 +380,23 @@ impl DkgState {
     ) -> Result<(), NodeError> {
         let identifier = peer_id_to_identifier(&sender_peer_id);
 
-        match node
-            .network_handle
-            .send_private_message(sender_peer_id, DirectMessage::Pong)
-        {
-            Ok(()) => (),
-            Err(e) => {
-                return Err(NodeError::Error(format!(
-                    "Failed to send private response: {e:?}"
-                )));
-            }
+        let package_hash = round2_package_hash(&package)?;
+        node.network_handle
+            .send_private_message(
+                sender_peer_id,
+                DirectMessage::Round2Ack {
+                    package_hash: package_hash.to_vec(),
+                },
+            )
+            .map_err(|e| NodeError::Error(format!("Failed to send private response: {e:?}")))?;
+
+        // A resend whose ack went missing; it is already counted.
+        if self.round2_peer_packages.get(&identifier) == Some(&package) {
+            tracing::debug!(
+                "Duplicate round2 package from {}",
+                node.network_handle.peer_name(&sender_peer_id)
+            );
+            return Ok(());
         }
 
         // Add package to peer packages
@@ -372,7 +437,7 @@ impl DkgState {
                 tracing::info!("Received all round2 packages, entering part3");
                 std::thread::sleep(dkg_step_delay());
 
-                let part3_result = frost::keys::dkg::part3(
+                let part3_result = DefaultScheme::dkg_part3(
                     &r2_secret_package.clone(),
                     &self.round1_peer_packages,
                     &self.round2_peer_packages,
@@ -415,6 +480,7 @@ impl DkgState {
                             })?,
                             min_stake: 100,
                             max_block_size: 1000,
+                            consensus_quorum: node.config.consensus_quorum,
                         };
 
                         let ChainResponse::CreateGenesisBlock { error: None } = node
@@ -445,6 +511,143 @@ impl DkgState {
         Ok(())
     }
 
+    /// Sends `package` to `peer` and tracks it until the peer acks it with a matching hash.
+    pub fn send_round2_package<N: Network, W: Wallet>(
+        &mut self,
+        node: &NodeState<N, W>,
+        peer: PeerId,
+        package: round2::Package,
+    ) -> Result<(), NodeError> {
+        let package_hash = round2_package_hash(&package)?;
+
+        node.network_handle
+            .send_private_message(peer, DirectMessage::Round2Package(package.clone()))
+            .map_err(|e| {
+                tracing::error!("Failed to send round2 package to {}", peer);
+                NodeError::Error(format!("Failed to send private request: {e:?}"))
+            })?;
+        tracing::debug!("{} Sent round2 package to {}", node.peer_id, peer);
+
+        let attempts = self
+            .pending_round2_acks
+            .get(&peer)
+            .map_or(0, |pending| pending.attempts);
+        self.pending_round2_acks.insert(
+            peer,
+            PendingRound2Delivery {
+                package,
+                package_hash,
+                sent_at: node.clock.now(),
+                attempts: attempts + 1,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn handle_round2_ack<N: Network, W: Wallet>(
+        &mut self,
+        node: &NodeState<N, W>,
+        peer: PeerId,
+        package_hash: &[u8],
+    ) -> Result<(), NodeError> {
+        let Some(pending) = self.pending_round2_acks.get(&peer) else {
+            tracing::debug!(
+                "Ignoring round2 ack from {} with nothing outstanding",
+                node.network_handle.peer_name(&peer)
+            );
+            return Ok(());
+        };
+
+        if pending.package_hash.as_slice() == package_hash {
+            tracing::debug!(
+                "Round2 package delivery to {} acknowledged",
+                node.network_handle.peer_name(&peer)
+            );
+            self.pending_round2_acks.remove(&peer);
+            return Ok(());
+        }
+
+        tracing::warn!(
+            "Round2 ack from {} does not match the package sent, resending",
+            node.network_handle.peer_name(&peer)
+        );
+        let package = pending.package.clone();
+        self.send_round2_package(node, peer, package)
+    }
+
+    /// Resends every round2 package whose ack is overdue, backing off after each attempt.
+    /// Deliveries still unacknowledged after `MAX_ROUND2_ATTEMPTS` sends are dropped and
+    /// reported as an error.
+    pub fn resend_unacked_round2_packages<N: Network, W: Wallet>(
+        &mut self,
+        node: &NodeState<N, W>,
+    ) -> Result<(), NodeError> {
+        let now = node.clock.now();
+        let overdue: Vec<(PeerId, round2::Package, u32)> = self
+            .pending_round2_acks
+            .iter()
+            .filter(|(_, pending)| {
+                now.saturating_duration_since(pending.sent_at) >= pending.ack_timeout()
+            })
+            .map(|(peer, pending)| (*peer, pending.package.clone(), pending.attempts))
+            .collect();
+
+        let mut given_up = Vec::new();
+        for (peer, package, attempts) in overdue {
+            if attempts >= MAX_ROUND2_ATTEMPTS {
+                self.pending_round2_acks.remove(&peer);
+                given_up.push(node.network_handle.peer_name(&peer));
+                continue;
+            }
+            tracing::info!(
+                "No round2 ack from {}, resending package",
+                node.network_handle.peer_name(&peer)
+            );
+            self.send_round2_package(node, peer, package)?;
+        }
+
+        if given_up.is_empty() {
+            Ok(())
+        } else {
+            Err(NodeError::Error(format!(
+                "Gave up delivering round2 packages to {} after {MAX_ROUND2_ATTEMPTS} attempts",
+                given_up.join(", ")
+            )))
+        }
+    }
+
+    /// Abandons any ceremony in progress and starts a fresh one, broadcasting a new start
+    /// message and round 1 package.
+    ///
+    /// A node that already holds group keys refuses unless `force` is set, in which case the
+    /// keys are dropped from memory until the new ceremony replaces them. Peers still holding
+    /// keys ignore the start message, so every participant must be restarted.
+    pub fn restart_dkg<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        force: bool,
+    ) -> Result<(), NodeError> {
+        let has_keys = node.private_key_package.is_some() || node.pubkey_package.is_some();
+        if has_keys && !force {
+            return Err(NodeError::Error(
+                "DKG keys are in use; set force to restart the ceremony".to_string(),
+            ));
+        }
+
+        tracing::warn!(
+            force,
+            had_keys = has_keys,
+            "Restarting DKG at operator request"
+        );
+        self.reset_dkg_state();
+        if has_keys {
+            node.private_key_package = None;
+            node.pubkey_package = None;
+        }
+
+        self.start_dkg(node)
+    }
+
     /// Reset DKG state after a failed run so that a new DKG round can be initiated.
     fn reset_dkg_state(&mut self) {
         self.dkg_started = false;
@@ -452,5 +655,6 @@ impl DkgState {
         self.r2_secret_package = None;
         self.round1_peer_packages.clear();
         self.round2_peer_packages.clear();
+        self.pending_round2_acks.clear();
     }
 }
@@ -1,14 +1,39 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
 
 use frost_secp256k1::{
     Identifier,
     keys::dkg::{round1, round2},
 };
 use libp2p::PeerId;
+use tokio::time::Instant;
 
 pub mod handler;
 pub mod key_creation;
 
+/// How long a round2 package may go unacknowledged before it is sent again. Each resend
+/// doubles the wait, see [`PendingRound2Delivery::ack_timeout`].
+pub const ROUND2_ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends of a round2 package to one peer before its delivery is given up.
+pub const MAX_ROUND2_ATTEMPTS: u32 = 5;
+
+/// A round2 package sent to a peer that has not yet acknowledged it with a matching hash.
+pub struct PendingRound2Delivery {
+    pub package: round2::Package,
+    pub package_hash: [u8; 32],
+    pub sent_at: Instant,
+    pub attempts: u32,
+}
+
+impl PendingRound2Delivery {
+    /// How long the latest send waits for its ack before the package is sent again.
+    #[must_use]
+    pub fn ack_timeout(&self) -> Duration {
+        ROUND2_ACK_TIMEOUT.saturating_mul(1 << self.attempts.saturating_sub(1).min(16))
+    }
+}
+
 pub struct DkgState {
     pub dkg_started: bool,
     pub dkg_listeners: HashSet<PeerId>,
@@ -19,6 +44,8 @@ pub struct DkgState {
 
     pub r1_secret_package: Option<round1::SecretPackage>,
     pub r2_secret_package: Option<round2::SecretPackage>,
+
+    pub pending_round2_acks: HashMap<PeerId, PendingRound2Delivery>,
 }
 
 impl Default for DkgState {
@@ -37,6 +64,7 @@ impl DkgState {
             round2_peer_packages: BTreeMap::new(),
             r1_secret_package: None,
             r2_secret_package: None,
+            pending_round2_acks: HashMap::new(),
             dkg_started: false,
         }
     }
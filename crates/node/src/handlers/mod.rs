@@ -3,6 +3,7 @@ pub mod consensus;
 pub mod deposit;
 pub mod dkg;
 pub mod signing;
+pub mod wallet;
 pub mod withdrawl;
 use std::any::Any;
 
@@ -28,4 +29,11 @@ impl<N: Network, W: Wallet> dyn Handler<N, W> {
     {
         (self as &dyn Any).downcast_ref::<T>()
     }
+
+    pub fn downcast_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Any,
+    {
+        (self as &mut dyn Any).downcast_mut::<T>()
+    }
 }
@@ -1,7 +1,7 @@
 use rand::seq::SliceRandom;
 use std::collections::BTreeMap;
 
-use frost_secp256k1::rand_core::RngCore;
+use bitcoin::secp256k1::Scalar;
 use frost_secp256k1::{self as frost};
 use hex;
 use libp2p::PeerId;
@@ -9,19 +9,68 @@ use tracing::{debug, error, info, warn};
 
 use crate::handlers::signing::SigningState;
 use crate::peer_id_to_identifier;
+use crate::utils::threshold_scheme::{DefaultScheme, ThresholdScheme};
 use crate::{
     NodeState, handlers::signing::ActiveSigning, handlers::withdrawl::SpendIntentState,
     wallet::Wallet,
 };
 use types::errors::NodeError;
-use types::network::network_event::DirectMessage;
+use types::network::network_event::{DirectMessage, SelfRequest};
 use types::network::network_protocol::Network;
 
 impl SigningState {
-    pub fn start_signing_session<N: Network, W: Wallet>(
+    /// Round-one nonces for a new session, drawn from the pre-generated pool when it has any
+    /// and generated on the spot otherwise.
+    fn draw_nonces(
+        &mut self,
+        key_pkg: &frost::keys::KeyPackage,
+        rng: &mut frost::rand_core::OsRng,
+    ) -> (
+        frost::round1::SigningNonces,
+        frost::round1::SigningCommitments,
+    ) {
+        match self.nonce_pool.take(key_pkg) {
+            Some(pooled) => {
+                debug!("Using pre-generated nonce {}", pooled.id);
+                (pooled.nonces, pooled.commitments)
+            }
+            None => DefaultScheme::commit(key_pkg, rng),
+        }
+    }
+
+    /// This node's key package for a session signing for the group key plus `tweak`.
+    fn session_key_package<N: Network, W: Wallet>(
+        node: &NodeState<N, W>,
+        tweak: Option<&Scalar>,
+    ) -> Result<frost::keys::KeyPackage, NodeError> {
+        let key_pkg = node
+            .private_key_package
+            .as_ref()
+            .ok_or_else(|| NodeError::Error("No private key found".to_string()))?;
+        match tweak {
+            Some(tweak) => Self::tweak_key_package(key_pkg, tweak),
+            None => Ok(key_pkg.clone()),
+        }
+    }
+
+    /// Tops the nonce pool up while the node is idle; a no-op until DKG has completed.
+    pub fn refill_nonce_pool<N: Network, W: Wallet>(&mut self, node: &mut NodeState<N, W>) {
+        let Some(key_pkg) = node.private_key_package.as_ref() else {
+            return;
+        };
+        let generated = self.nonce_pool.fill(key_pkg, &mut node.rng);
+        if generated > 0 {
+            debug!("Pre-generated {generated} signing nonces");
+        }
+    }
+
+    /// Starts coordinating a signature over `message_hex`, a 32-byte sighash, by the group key
+    /// plus `tweak` when one is given.
+    pub async fn start_signing_session<N: Network, W: Wallet>(
         &mut self,
         node: &mut NodeState<N, W>,
         message_hex: &str,
+        tweak: Option<Scalar>,
     ) -> Result<Option<u64>, NodeError> {
         if node.private_key_package.is_none() || node.pubkey_package.is_none() {
             error!("❌ DKG not completed – cannot start signing");
@@ -44,14 +93,18 @@ impl SigningState {
 
         info!("Starting signing session for message: {}", message_hex);
 
-        if self.active_signing.is_some() {
-            error!("❌ A signing session is already active");
+        if self
+            .active_signing
+            .values()
+            .any(|active| active.is_coordinator && active.message == message)
+        {
+            error!("❌ A signing session for this message is already active");
             return Err(NodeError::Error(
-                "A signing session is already active".to_string(),
+                "A signing session for this message is already active".to_string(),
             ));
         }
 
-        let sign_id = node.rng.next_u64();
+        let sign_id = node.allocate_sign_id().await?;
         let self_identifier = peer_id_to_identifier(&node.peer_id);
 
         // Select participants: self + first (min_signers -1) peers
@@ -77,12 +130,18 @@ impl SigningState {
         );
         debug!("Selected peers: {:?}", node.peers);
 
-        // Randomly shuffle peers and pick required number
+        // Ask up to max_signers - 1 peers in parallel; the first min_signers - 1 to commit
+        // form the signing subset, so one slow peer does not hold up the session.
+        let wanted = node
+            .config
+            .max_signers
+            .map_or(required, |max| usize::from(max).saturating_sub(1))
+            .max(required);
         let mut rng_rand = rand::rng();
         let mut peer_pool = node.peers.clone().into_iter().collect::<Vec<_>>();
         peer_pool.shuffle(&mut rng_rand);
 
-        let selected_peers: Vec<PeerId> = peer_pool.into_iter().take(required).collect();
+        let selected_peers: Vec<PeerId> = peer_pool.into_iter().take(wanted).collect();
 
         // Generate nonces & commitments for self
         let key_pkg = match node.private_key_package.as_ref() {
@@ -91,28 +150,34 @@ impl SigningState {
                 return Err(NodeError::Error("No private key found".to_string()));
             }
         };
-        let (nonces, commitments) = frost::round1::commit(key_pkg.signing_share(), &mut node.rng);
+        let (nonces, commitments) = self.draw_nonces(&key_pkg, &mut node.rng);
 
         let mut commitments_map = BTreeMap::new();
         commitments_map.insert(self_identifier, commitments);
 
         // Save active session
-        self.active_signing = Some(ActiveSigning {
+        self.active_signing.insert(
             sign_id,
-            message: message.clone(),
-            selected_peers: selected_peers.clone(),
-            nonces,
-            commitments: commitments_map,
-            signature_shares: BTreeMap::new(),
-            signing_package: None,
-            is_coordinator: true,
-        });
+            ActiveSigning {
+                sign_id,
+                message: message.clone(),
+                tweak,
+                selected_peers: selected_peers.clone(),
+                nonces,
+                commitments: commitments_map,
+                signature_shares: BTreeMap::new(),
+                signing_package: None,
+                is_coordinator: true,
+                started_at: node.clock.now(),
+            },
+        );
 
         // Broadcast SignRequest to chosen peers (skip self)
         for peer in &selected_peers {
             let req = DirectMessage::SignRequest {
                 sign_id,
                 message: message.clone(),
+                tweak: tweak.map(|t| t.to_be_bytes().to_vec()).unwrap_or_default(),
             };
             node.network_handle
                 .send_private_message(*peer, req)
@@ -130,6 +195,7 @@ impl SigningState {
         peer: PeerId,
         sign_id: u64,
         message: Vec<u8>,
+        tweak: &[u8],
     ) -> Result<(), NodeError> {
         if node.private_key_package.is_none() {
             let _ = node.network_handle.send_private_message(
@@ -142,25 +208,43 @@ impl SigningState {
             return Ok(());
         }
 
+        let tweak = match <[u8; 32]>::try_from(tweak) {
+            Ok(bytes) => Some(
+                Scalar::from_be_bytes(bytes)
+                    .map_err(|e| NodeError::Error(format!("Invalid signing tweak: {e}")))?,
+            ),
+            Err(_) if tweak.is_empty() => None,
+            Err(_) => {
+                return Err(NodeError::Error(format!(
+                    "Signing tweak must be 32 bytes, got {}",
+                    tweak.len()
+                )));
+            }
+        };
+
         let key_pkg = match node.private_key_package.as_ref() {
             Some(key_pkg) => key_pkg.clone(),
             None => {
                 return Err(NodeError::Error("No private key found".to_string()));
             }
         };
-        let (nonces, commitments) = frost::round1::commit(key_pkg.signing_share(), &mut node.rng);
+        let (nonces, commitments) = self.draw_nonces(&key_pkg, &mut node.rng);
 
-        // Save session (one at a time for simplicity)
-        self.active_signing = Some(ActiveSigning {
+        self.active_signing.insert(
             sign_id,
-            message,
-            selected_peers: Vec::new(),
-            nonces,
-            commitments: BTreeMap::new(), // not used for participant
-            signature_shares: BTreeMap::new(),
-            signing_package: None,
-            is_coordinator: false,
-        });
+            ActiveSigning {
+                sign_id,
+                message,
+                tweak,
+                selected_peers: Vec::new(),
+                nonces,
+                commitments: BTreeMap::new(), // not used for participant
+                signature_shares: BTreeMap::new(),
+                signing_package: None,
+                is_coordinator: false,
+                started_at: node.clock.now(),
+            },
+        );
 
         let Ok(commit_bytes) = commitments.serialize() else {
             return Err(NodeError::Error(
@@ -190,13 +274,21 @@ impl SigningState {
         sign_id: u64,
         commitments_bytes: &[u8],
     ) -> Result<(), NodeError> {
-        let Some(active) = self.active_signing.as_mut() else {
+        let Some(active) = self.active_signing.get_mut(&sign_id) else {
             return Err(NodeError::Error("No active session".to_string()));
         };
-        if !active.is_coordinator || active.sign_id != sign_id {
+        if !active.is_coordinator {
             return Err(NodeError::Error("Session id mismatch".to_string()));
         }
 
+        if active.signing_package.is_some() {
+            debug!(
+                "Ignoring late commitments from {} for session {}; signers already chosen",
+                peer, sign_id
+            );
+            return Ok(());
+        }
+
         let Ok(commitments) = frost::round1::SigningCommitments::deserialize(commitments_bytes)
         else {
             warn!("Failed to deserialize commitments from {}", peer);
@@ -233,8 +325,12 @@ impl SigningState {
                 ));
             };
 
-            // Send package to participants (excluding self)
-            for peer in &active.selected_peers {
+            // Send package only to the peers whose commitments it holds (excluding self)
+            for peer in active
+                .selected_peers
+                .iter()
+                .filter(|p| active.commitments.contains_key(&peer_id_to_identifier(p)))
+            {
                 let req = DirectMessage::SignPackage {
                     sign_id,
                     package: pkg_bytes.clone(),
@@ -243,16 +339,8 @@ impl SigningState {
             }
 
             // Generate our signature share
-            let sig_share = frost::round2::sign(
-                &signing_package,
-                &active.nonces,
-                match node.private_key_package.as_ref() {
-                    Some(key_pkg) => key_pkg,
-                    None => {
-                        return Err(NodeError::Error("No private key found".to_string()));
-                    }
-                },
-            );
+            let key_pkg = Self::session_key_package(node, active.tweak.as_ref())?;
+            let sig_share = DefaultScheme::sign(&signing_package, &active.nonces, &key_pkg);
             match sig_share {
                 Ok(sig_share) => {
                     active
@@ -278,14 +366,10 @@ impl SigningState {
         sign_id: u64,
         package_bytes: &[u8],
     ) -> Result<(), NodeError> {
-        let Some(active) = self.active_signing.as_ref() else {
-            warn!("No active session to sign");
+        let Some(active) = self.active_signing.get(&sign_id) else {
+            warn!("No active session {} to sign", sign_id);
             return Err(NodeError::Error("No active session".to_string()));
         };
-        if active.sign_id != sign_id {
-            warn!("Session id mismatch");
-            return Err(NodeError::Error("Session id mismatch".to_string()));
-        }
 
         let Ok(signing_package) = frost::SigningPackage::deserialize(package_bytes) else {
             warn!("Failed to deserialize signing package");
@@ -294,16 +378,8 @@ impl SigningState {
             ));
         };
 
-        let sig_share = frost::round2::sign(
-            &signing_package,
-            &active.nonces,
-            match node.private_key_package.as_ref() {
-                Some(key_pkg) => key_pkg,
-                None => {
-                    return Err(NodeError::Error("No private key found".to_string()));
-                }
-            },
-        );
+        let key_pkg = Self::session_key_package(node, active.tweak.as_ref())?;
+        let sig_share = DefaultScheme::sign(&signing_package, &active.nonces, &key_pkg);
         match sig_share {
             Ok(sig_share) => {
                 let sig_bytes = sig_share.serialize();
@@ -312,6 +388,9 @@ impl SigningState {
                     signature_share: sig_bytes,
                 };
                 let _ = node.network_handle.send_private_message(peer, resp);
+                // The coordinator's package held our commitments and signed, so the session
+                // it ran is a real one.
+                node.validator_stats.record_signing_session(peer);
             }
             Err(e) => {
                 return Err(NodeError::Error(format!("Failed to sign: {e}")));
@@ -322,7 +401,7 @@ impl SigningState {
             "✍️  Sent signature share for session {} to {}",
             sign_id, peer
         );
-        self.active_signing = None;
+        self.active_signing.remove(&sign_id);
 
         Ok(())
     }
@@ -335,10 +414,10 @@ impl SigningState {
         sign_id: u64,
         sig_bytes: &[u8],
     ) -> Result<(), NodeError> {
-        let Some(active) = self.active_signing.as_mut() else {
+        let Some(active) = self.active_signing.get_mut(&sign_id) else {
             return Err(NodeError::Error("No active session".to_string()));
         };
-        if !active.is_coordinator || active.sign_id != sign_id {
+        if !active.is_coordinator {
             return Err(NodeError::Error("Session id mismatch".to_string()));
         }
 
@@ -349,6 +428,17 @@ impl SigningState {
             ));
         };
         let identifier = peer_id_to_identifier(&peer);
+        let in_signing_subset = active
+            .signing_package
+            .as_ref()
+            .is_some_and(|package| package.signing_commitments().contains_key(&identifier));
+        if !in_signing_subset {
+            warn!(
+                "Ignoring signature share from {} outside the signing subset of session {}",
+                peer, sign_id
+            );
+            return Ok(());
+        }
         active.signature_shares.insert(identifier, sig_share);
         debug!(
             "✅ Received signature share from {} (total {}/{})",
@@ -359,8 +449,9 @@ impl SigningState {
                 .ok_or_else(|| NodeError::Error("Min signers not set".to_string()))?
         );
 
+        // Every share comes from the committed subset, so min_signers of them complete it.
         if active.signature_shares.len()
-            == node
+            >= node
                 .config
                 .min_signers
                 .ok_or_else(|| NodeError::Error("Min signers not set".to_string()))?
@@ -370,23 +461,52 @@ impl SigningState {
                 .signing_package
                 .clone()
                 .ok_or_else(|| NodeError::Error("No signing package found".to_string()))?;
-            let group_sig = frost::aggregate(
+            let pubkey_package = match (node.pubkey_package.as_ref(), active.tweak.as_ref()) {
+                (Some(pubkey_package), Some(tweak)) => {
+                    Self::tweak_public_key_package(pubkey_package, tweak)?
+                }
+                (Some(pubkey_package), None) => pubkey_package.clone(),
+                (None, _) => {
+                    return Err(NodeError::Error("No public key found".to_string()));
+                }
+            };
+            let group_sig = DefaultScheme::aggregate(
                 &signing_package,
                 &active.signature_shares,
-                match node.pubkey_package.as_ref() {
-                    Some(public_key) => public_key,
-                    None => {
-                        return Err(NodeError::Error("No public key found".to_string()));
-                    }
-                },
+                &pubkey_package,
             )
             .expect("Aggregate");
+            // Shares are only known to be valid once they aggregate to the group signature.
+            for signer in active.selected_peers.iter().filter(|p| {
+                active
+                    .signature_shares
+                    .contains_key(&peer_id_to_identifier(p))
+            }) {
+                node.validator_stats.record_signing_session(*signer);
+            }
             let sig_hex = hex::encode(group_sig.serialize().expect("serialize group sig"));
             debug!(
                 "🎉 Final FROST signature for session {}: {}",
                 sign_id, sig_hex
             );
 
+            if self.pending_self_tests.contains_key(&sign_id) {
+                if let Some(pubkey_package) = node.pubkey_package.as_ref() {
+                    self.complete_signing_self_test(
+                        sign_id,
+                        &group_sig,
+                        pubkey_package.verifying_key(),
+                    );
+                }
+            }
+
+            if self.pending_attestations.contains_key(&sign_id) {
+                self.complete_reserve_attestation(
+                    sign_id,
+                    group_sig.serialize().expect("serialize group sig"),
+                );
+            }
+
             // If this signing session corresponds to a pending spend, finalise the transaction.
             if let Some(pending) = self.pending_spends.remove(&sign_id) {
                 match Self::frost_signature_to_bitcoin(&group_sig) {
@@ -406,14 +526,73 @@ impl SigningState {
                         )
                         .await?;
                         debug!("📤 Broadcasted transaction");
+                        if let Some(challenge) = self.withdrawal_challenges.remove(&sign_id) {
+                            Self::notify_withdrawal_tracker(
+                                node,
+                                SelfRequest::WithdrawalBroadcast {
+                                    challenge,
+                                    txid: tx.compute_txid().to_string(),
+                                },
+                            );
+                        }
+                        self.watch_broadcast(node, sign_id, tx).await;
+                    }
+                    Err(e) => debug!("❌ Failed to convert signature: {}", e),
+                }
+            } else if let Some(replacement) = self.pending_watches.get(&sign_id) {
+                // Fee bump of a withdrawal the user was already debited for; only the
+                // Bitcoin transaction is replaced.
+                match Self::frost_signature_to_bitcoin(&group_sig) {
+                    Ok(bitcoin_sig) => {
+                        let mut tx = replacement.tx.clone();
+                        let mut witness = bitcoin::witness::Witness::new();
+                        witness.push(bitcoin_sig.as_ref());
+                        if let Some(input) = tx.input.first_mut() {
+                            input.witness = witness;
+                        }
+                        let address_to = tx
+                            .output
+                            .first()
+                            .and_then(|output| {
+                                bitcoin::Address::from_script(
+                                    &output.script_pubkey,
+                                    node.wallet.network(),
+                                )
+                                .ok()
+                            })
+                            .map(|address| address.to_string())
+                            .unwrap_or_default();
+                        SpendIntentState::audited_broadcast(node, &tx, &address_to).await?;
+                        debug!("📤 Broadcasted fee bump replacement {}", tx.compute_txid());
+                        if let Some(replaced) = tx.input.first().and_then(|input| {
+                            self.broadcast_withdrawals.get(&input.previous_output)
+                        }) {
+                            Self::notify_withdrawal_tracker(
+                                node,
+                                SelfRequest::WithdrawalReplaced {
+                                    replaced_txid: replaced.tx.compute_txid().to_string(),
+                                    txid: tx.compute_txid().to_string(),
+                                },
+                            );
+                        }
+                        self.watch_broadcast(node, sign_id, tx).await;
                     }
                     Err(e) => debug!("❌ Failed to convert signature: {}", e),
                 }
             }
-            // Reset
-            self.active_signing = None;
+            self.active_signing.remove(&sign_id);
         }
 
         Ok(())
     }
+
+    /// Hands a broadcast withdrawal to the withdrawal handler, which persists its txid.
+    fn notify_withdrawal_tracker<N: Network, W: Wallet>(
+        node: &NodeState<N, W>,
+        request: SelfRequest,
+    ) {
+        if let Err(e) = node.network_handle.send_self_request(request, false) {
+            warn!("Failed to record broadcast withdrawal: {e:?}");
+        }
+    }
 }
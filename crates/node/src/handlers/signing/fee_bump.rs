@@ -0,0 +1,156 @@
+use bitcoin::{Transaction, TxOut};
+use num_traits::cast::ToPrimitive;
+use oracle::oracle::Oracle;
+use tracing::{debug, info};
+use types::errors::NodeError;
+use types::network::network_protocol::Network;
+
+use crate::{
+    NodeState,
+    handlers::signing::{BroadcastWithdrawal, SigningState},
+    wallet::{TrackedUtxo, Wallet, taproot::INCREMENTAL_RELAY_FEE_SAT_PER_VB},
+};
+
+impl SigningState {
+    /// Outputs spent by `tx`, looked up among the wallet UTXOs tracked before the spend.
+    #[must_use]
+    pub fn spent_prevouts(tx: &Transaction, utxos: &[TrackedUtxo]) -> Vec<TxOut> {
+        tx.input
+            .iter()
+            .filter_map(|input| {
+                utxos
+                    .iter()
+                    .find(|u| u.utxo.outpoint == input.previous_output)
+                    .map(|u| TxOut {
+                        value: u.utxo.value,
+                        script_pubkey: u.utxo.script_pubkey.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Starts watching the signed transaction of a completed session if it opted into fee
+    /// bumping, replacing any earlier version of the same withdrawal.
+    pub async fn watch_broadcast<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        sign_id: u64,
+        tx: Transaction,
+    ) {
+        let Some(mut watched) = self.pending_watches.remove(&sign_id) else {
+            return;
+        };
+        let Some(key) = tx.input.first().map(|input| input.previous_output) else {
+            return;
+        };
+
+        if let Ok(height) = node.oracle.get_latest_block_height().await {
+            watched.broadcast_height = height;
+        }
+        watched.tx = tx;
+        self.broadcast_withdrawals.insert(key, watched);
+    }
+
+    /// Fee for a replacement of `watched`: the current market rate, and at least the relay
+    /// increment over the fee already paid, capped by the policy. `None` once the cap is hit.
+    async fn bumped_fee(
+        oracle: &dyn Oracle,
+        watched: &BroadcastWithdrawal,
+    ) -> Result<Option<u64>, NodeError> {
+        let vsize = watched.tx.vsize() as u64;
+        let fee_per_vb = oracle.get_current_fee_per_vb(None).await?;
+        let market_fee = (fee_per_vb * vsize.to_f64().unwrap_or_default())
+            .ceil()
+            .to_u64()
+            .unwrap_or_default();
+
+        let new_fee = market_fee
+            .max(watched.fee + INCREMENTAL_RELAY_FEE_SAT_PER_VB * vsize)
+            .min(watched.policy.max_fee_sat);
+
+        Ok((new_fee > watched.fee).then_some(new_fee))
+    }
+
+    /// Stops watching withdrawals that confirmed and replaces one that has stayed
+    /// unconfirmed past its policy with a higher-fee version.
+    ///
+    /// At most one withdrawal is bumped per call, and never one whose previous replacement is
+    /// still being signed.
+    pub async fn bump_stuck_withdrawals<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+    ) -> Result<(), NodeError> {
+        if self.broadcast_withdrawals.is_empty() {
+            return Ok(());
+        }
+
+        let height = node.oracle.get_latest_block_height().await?;
+        let mut stuck = None;
+        let mut confirmed = Vec::new();
+        for (key, watched) in &self.broadcast_withdrawals {
+            if node
+                .oracle
+                .is_transaction_confirmed(watched.tx.compute_txid())
+                .await?
+            {
+                confirmed.push(*key);
+            } else if stuck.is_none()
+                && height.saturating_sub(watched.broadcast_height) >= watched.policy.after_blocks
+                && !self.pending_watches.values().any(|replacement| {
+                    replacement
+                        .tx
+                        .input
+                        .first()
+                        .map(|input| input.previous_output)
+                        == Some(*key)
+                })
+            {
+                stuck = Some(*key);
+            }
+        }
+        for key in confirmed {
+            self.broadcast_withdrawals.remove(&key);
+        }
+
+        let Some(watched) = stuck.and_then(|key| self.broadcast_withdrawals.get(&key)) else {
+            return Ok(());
+        };
+
+        let Some(new_fee) = Self::bumped_fee(node.oracle.as_ref(), watched).await? else {
+            debug!(
+                "Withdrawal {} already pays its maximum fee of {} sat",
+                watched.tx.compute_txid(),
+                watched.policy.max_fee_sat
+            );
+            return Ok(());
+        };
+
+        let (replacement, sighash) =
+            node.wallet
+                .bump_fee(&watched.tx, &watched.prevouts, new_fee)?;
+        let replacement = BroadcastWithdrawal {
+            tx: replacement,
+            fee: new_fee,
+            ..watched.clone()
+        };
+        info!(
+            "⛽ Bumping fee of withdrawal {} from {} to {} sat",
+            watched.tx.compute_txid(),
+            watched.fee,
+            new_fee
+        );
+
+        let tweak = node.wallet.signing_tweak(&replacement.tx);
+        let Some(sign_id) = self
+            .start_signing_session(node, &hex::encode(sighash), tweak)
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Fee bump signing session did not start".to_string(),
+            ));
+        };
+        self.pending_watches.insert(sign_id, replacement);
+
+        Ok(())
+    }
+}
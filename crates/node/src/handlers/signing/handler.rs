@@ -16,7 +16,7 @@ impl<N: Network, W: Wallet> Handler<N, W> for SigningState {
                 request: SelfRequest::StartSigningSession { hex_message },
                 ..
             } => {
-                let _ = self.start_signing_session(node, &hex_message)?;
+                let _ = self.start_signing_session(node, &hex_message, None).await?;
             }
             NetworkEvent::SelfRequest {
                 request:
@@ -25,17 +25,23 @@ impl<N: Network, W: Wallet> Handler<N, W> for SigningState {
                         fee,
                         address_to,
                         user_pubkey,
+                        fee_bump,
+                        challenge,
                     },
                 response_channel,
             } => {
-                let response = self.start_spend_request(
-                    node,
-                    amount_sat,
-                    fee,
-                    &address_to,
-                    user_pubkey,
-                    false,
-                );
+                let response = self
+                    .start_spend_request(
+                        node,
+                        amount_sat,
+                        fee,
+                        &address_to,
+                        user_pubkey,
+                        fee_bump,
+                        challenge,
+                        false,
+                    )
+                    .await;
                 if let Some(response_channel) = response_channel {
                     response_channel
                         .send(SelfResponse::SpendRequestSent {
@@ -44,8 +50,57 @@ impl<N: Network, W: Wallet> Handler<N, W> for SigningState {
                         .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
                 }
             }
-            NetworkEvent::MessageEvent((peer, DirectMessage::SignRequest { sign_id, message })) => {
-                self.handle_sign_request(node, peer, sign_id, message)?;
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::MaintenanceTick,
+                ..
+            } => {
+                let pruned = self.prune_stale_sessions(&mut node.wallet, node.clock.now());
+                if pruned > 0 {
+                    tracing::debug!("Dropped {pruned} stale signing sessions");
+                }
+                if let Err(e) = self.bump_stuck_withdrawals(node).await {
+                    tracing::warn!("Failed to bump stuck withdrawal fee: {e}");
+                }
+                self.refill_nonce_pool(node);
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetReserveAttestation,
+                response_channel: Some(response_channel),
+            } => {
+                if let Err(e) = self
+                    .start_reserve_attestation(node, response_channel.clone())
+                    .await
+                {
+                    response_channel
+                        .send(SelfResponse::NodeError(e))
+                        .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+                }
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::RunSigningSelfTest,
+                response_channel: Some(response_channel),
+            } => {
+                self.start_signing_self_test(node, response_channel).await?;
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetActiveSigningSessions,
+                response_channel: Some(response_channel),
+            } => {
+                response_channel
+                    .send(SelfResponse::GetActiveSigningSessionsResponse {
+                        sessions: self.active_sessions(node.clock.now()),
+                    })
+                    .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))?;
+            }
+            NetworkEvent::MessageEvent((
+                peer,
+                DirectMessage::SignRequest {
+                    sign_id,
+                    message,
+                    tweak,
+                },
+            )) => {
+                self.handle_sign_request(node, peer, sign_id, message, &tweak)?;
             }
             NetworkEvent::MessageEvent((peer, DirectMessage::SignPackage { sign_id, package })) => {
                 self.handle_sign_package(node, peer, sign_id, &package)?;
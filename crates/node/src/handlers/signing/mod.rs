@@ -1,25 +1,78 @@
 pub mod create_signature;
+pub mod fee_bump;
 pub mod handler;
+pub mod nonce_pool;
+pub mod reserves;
+pub mod self_test;
+pub mod sign_id;
 pub mod utils;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
+use bitcoin::secp256k1::Scalar;
 use frost_secp256k1::{self as frost, Identifier};
 use libp2p::PeerId;
-use types::intents::PendingSpend;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use types::intents::{FeeBumpPolicy, PendingSpend};
+use types::network::network_event::SelfResponse;
+use types::reserves::ReserveAttestation;
+
+use nonce_pool::NoncePool;
+
+/// How long a participant keeps its nonces for a session whose coordinator never sent it a
+/// signing package, e.g. because enough other peers committed first.
+pub const PARTICIPANT_SESSION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How long a coordinator waits for its selected signers before giving the session up, so a
+/// signer that went away cannot pin the session and whatever awaits its signature forever.
+pub const COORDINATOR_SESSION_TIMEOUT: Duration = Duration::from_secs(15 * 60);
 
 // Active signing session tracking
 pub struct ActiveSigning {
+    /// Allocated by the coordinator, see [`sign_id`] for why it is unique cluster-wide.
     pub sign_id: u64,
     pub message: Vec<u8>,
+    /// Added to the group key when signing for a tweaked wallet address, see
+    /// [`crate::wallet::SpendPath`].
+    pub tweak: Option<Scalar>,
     pub selected_peers: Vec<PeerId>,
     pub nonces: frost::round1::SigningNonces,
     pub commitments: BTreeMap<Identifier, frost::round1::SigningCommitments>,
     pub signature_shares: BTreeMap<Identifier, frost::round2::SignatureShare>,
     pub signing_package: Option<frost::SigningPackage>,
     pub is_coordinator: bool,
+    pub started_at: Instant,
+}
+
+/// A withdrawal on the Bitcoin network that is watched so its fee can be bumped.
+#[derive(Clone, Debug)]
+pub struct BroadcastWithdrawal {
+    pub tx: bitcoin::Transaction,
+    pub prevouts: Vec<bitcoin::TxOut>,
+    pub fee: u64,
+    /// Bitcoin height when the current version of the transaction was broadcast.
+    pub broadcast_height: u32,
+    pub policy: FeeBumpPolicy,
 }
 
 pub struct SigningState {
-    pub active_signing: Option<ActiveSigning>,
+    /// Sessions in progress keyed by `sign_id`, so several withdrawals can be signed at once.
+    pub active_signing: BTreeMap<u64, ActiveSigning>,
     pub pending_spends: std::collections::BTreeMap<u64, PendingSpend>,
+    /// Withdrawals to start watching once the signing session with this id completes.
+    pub pending_watches: BTreeMap<u64, BroadcastWithdrawal>,
+    /// Watched withdrawals keyed by their first input, which every replacement shares.
+    pub broadcast_withdrawals: HashMap<bitcoin::OutPoint, BroadcastWithdrawal>,
+    /// Challenges of the withdrawals being paid out by the signing session with this id.
+    pub withdrawal_challenges: BTreeMap<u64, String>,
+    /// Reserve attestations awaiting the signature of the session with this id, and where to
+    /// send them once signed.
+    pub pending_attestations:
+        BTreeMap<u64, (ReserveAttestation, mpsc::UnboundedSender<SelfResponse>)>,
+    /// Signing self-tests awaiting the session with this id, when each started and where to
+    /// report its result.
+    pub pending_self_tests: BTreeMap<u64, (Instant, mpsc::UnboundedSender<SelfResponse>)>,
+    /// Pre-generated round-one nonces that new sessions draw from before generating fresh ones.
+    pub nonce_pool: NoncePool,
 }
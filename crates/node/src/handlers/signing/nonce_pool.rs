@@ -0,0 +1,112 @@
+use std::collections::VecDeque;
+
+use frost_secp256k1::rand_core::{CryptoRng, RngCore};
+use frost_secp256k1::{self as frost};
+use zeroize::Zeroize;
+
+use crate::utils::threshold_scheme::{DefaultScheme, ThresholdScheme};
+
+/// A nonce pair generated ahead of time, numbered in the order it was created.
+pub struct PooledNonce {
+    pub id: u64,
+    pub nonces: frost::round1::SigningNonces,
+    pub commitments: frost::round1::SigningCommitments,
+}
+
+/// Round-one nonces generated during idle ticks so a signing session can commit at once.
+///
+/// Every nonce is handed out at most once: [`NoncePool::take`] moves it out of the pool and
+/// ids are never reissued. Nonces committed under a key that is no longer the node's, or
+/// left over when the pool is dropped, are zeroized rather than just freed.
+pub struct NoncePool {
+    capacity: usize,
+    entries: VecDeque<PooledNonce>,
+    /// Verifying share of the key package the pooled nonces were committed under.
+    key: Option<frost::keys::VerifyingShare>,
+    next_id: u64,
+}
+
+impl NoncePool {
+    #[must_use]
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+            key: None,
+            next_id: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Id and commitments of the nonce the next [`NoncePool::take`] will hand out.
+    #[must_use]
+    pub fn peek(&self) -> Option<(u64, &frost::round1::SigningCommitments)> {
+        self.entries
+            .front()
+            .map(|entry| (entry.id, &entry.commitments))
+    }
+
+    /// Whether the nonce numbered `id` has left the pool, by use or disposal, for good.
+    #[must_use]
+    pub fn is_consumed(&self, id: u64) -> bool {
+        id < self.next_id && self.entries.iter().all(|entry| entry.id != id)
+    }
+
+    /// Tops the pool up to capacity with nonces for `key_package`, returning how many were
+    /// generated.
+    pub fn fill<R: RngCore + CryptoRng>(
+        &mut self,
+        key_package: &frost::keys::KeyPackage,
+        rng: &mut R,
+    ) -> usize {
+        self.bind_to(key_package);
+        let missing = self.capacity.saturating_sub(self.entries.len());
+        for _ in 0..missing {
+            let (nonces, commitments) = DefaultScheme::commit(key_package, rng);
+            self.entries.push_back(PooledNonce {
+                id: self.next_id,
+                nonces,
+                commitments,
+            });
+            self.next_id += 1;
+        }
+        missing
+    }
+
+    /// Removes and returns the oldest nonce committed under `key_package`, if any.
+    pub fn take(&mut self, key_package: &frost::keys::KeyPackage) -> Option<PooledNonce> {
+        self.bind_to(key_package);
+        self.entries.pop_front()
+    }
+
+    /// Disposes of nonces committed under any other key than `key_package`, e.g. after DKG
+    /// was rerun.
+    fn bind_to(&mut self, key_package: &frost::keys::KeyPackage) {
+        let key = *key_package.verifying_share();
+        if self.key != Some(key) {
+            self.dispose();
+            self.key = Some(key);
+        }
+    }
+
+    fn dispose(&mut self) {
+        for mut entry in self.entries.drain(..) {
+            entry.nonces.zeroize();
+        }
+    }
+}
+
+impl Drop for NoncePool {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
@@ -0,0 +1,82 @@
+use abci::{ChainMessage, ChainResponse};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+use types::errors::NodeError;
+use types::network::network_event::SelfResponse;
+use types::network::network_protocol::Network;
+use types::reserves::{ReserveAttestation, ReserveUtxo};
+
+use crate::{NodeState, handlers::signing::SigningState, wallet::Wallet};
+
+impl SigningState {
+    /// Starts signing an attestation of the vault's UTXO set at the current chain height. The
+    /// signed attestation is sent on `response_channel` once the session completes.
+    pub async fn start_reserve_attestation<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        response_channel: mpsc::UnboundedSender<SelfResponse>,
+    ) -> Result<(), NodeError> {
+        let ChainResponse::GetChainState { state } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::GetChainState)
+            .await?
+        else {
+            return Err(NodeError::Error("Failed to get chain state".to_string()));
+        };
+
+        let utxos = node
+            .wallet
+            .get_utxos()
+            .into_iter()
+            .map(|tracked| ReserveUtxo {
+                outpoint: tracked.utxo.outpoint,
+                value_sat: tracked.utxo.value.to_sat(),
+                script_pubkey: tracked.utxo.script_pubkey,
+            })
+            .collect();
+        let attestation = ReserveAttestation::new(state.get_block_height(), utxos);
+
+        let sign_id = self
+            .start_signing_session(node, &hex::encode(attestation.digest()), None)
+            .await?
+            .ok_or_else(|| NodeError::Error("Signing session not started".to_string()))?;
+        debug!(
+            "Signing reserve attestation of {} sat at height {} in session {sign_id}",
+            attestation.total_value, attestation.height
+        );
+        self.pending_attestations
+            .insert(sign_id, (attestation, response_channel));
+        Ok(())
+    }
+
+    /// Answers the attestation request signed by session `sign_id`, if there was one.
+    pub fn complete_reserve_attestation(&mut self, sign_id: u64, signature: Vec<u8>) {
+        let Some((mut attestation, response_channel)) = self.pending_attestations.remove(&sign_id)
+        else {
+            return;
+        };
+        attestation.signature = signature;
+        if response_channel
+            .send(SelfResponse::GetReserveAttestationResponse { attestation })
+            .is_err()
+        {
+            warn!("Reserve attestation for session {sign_id} was no longer awaited");
+        }
+    }
+
+    /// Answers the attestation request of session `sign_id`, if there was one, with `reason`
+    /// instead of a signature.
+    pub fn fail_reserve_attestation(&mut self, sign_id: u64, reason: &str) {
+        let Some((_, response_channel)) = self.pending_attestations.remove(&sign_id) else {
+            return;
+        };
+        if response_channel
+            .send(SelfResponse::NodeError(NodeError::Error(
+                reason.to_string(),
+            )))
+            .is_err()
+        {
+            warn!("Reserve attestation for session {sign_id} was no longer awaited");
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use bitcoin::hashes::{Hash, sha256};
+use frost_secp256k1 as frost;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tracing::{info, warn};
+use types::errors::NodeError;
+use types::network::network_event::SelfResponse;
+use types::network::network_protocol::Network;
+
+use crate::{NodeState, handlers::signing::SigningState, wallet::Wallet};
+
+/// Tag hashed into the digest the self-test signs. Being a tagged hash rather than a sighash,
+/// the signature cannot authorize spending any vault output.
+const SELF_TEST_DOMAIN: &[u8] = b"threshold/signing-self-test/v1";
+
+/// The fixed 32-byte message every signing self-test signs.
+#[must_use]
+pub fn self_test_digest() -> [u8; 32] {
+    sha256::Hash::hash(SELF_TEST_DOMAIN).to_byte_array()
+}
+
+impl SigningState {
+    /// Starts a signing session over [`self_test_digest`], answering on `response_channel` once
+    /// the aggregate signature has been checked against the group key. A session that cannot
+    /// start, e.g. for lack of signers, is answered with a failure straight away.
+    ///
+    /// The session is never tied to a spend, so it touches neither the wallet nor its UTXOs.
+    pub async fn start_signing_self_test<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        response_channel: mpsc::UnboundedSender<SelfResponse>,
+    ) -> Result<(), NodeError> {
+        let started_at = Instant::now();
+        match self
+            .start_signing_session(node, &hex::encode(self_test_digest()), None)
+            .await
+        {
+            Ok(Some(sign_id)) => {
+                info!("Running signing self-test in session {sign_id}");
+                self.pending_self_tests
+                    .insert(sign_id, (started_at, response_channel));
+                Ok(())
+            }
+            Ok(None) => Self::answer_self_test(
+                &response_channel,
+                false,
+                "Signing session not started",
+                started_at,
+            ),
+            Err(e) => Self::answer_self_test(
+                &response_channel,
+                false,
+                &format!("Signing session failed to start: {e}"),
+                started_at,
+            ),
+        }
+    }
+
+    /// Answers the self-test run by session `sign_id`, if there was one, with whether
+    /// `signature` verifies under the group key.
+    pub fn complete_signing_self_test(
+        &mut self,
+        sign_id: u64,
+        signature: &frost::Signature,
+        verifying_key: &frost::VerifyingKey,
+    ) {
+        let Some((started_at, response_channel)) = self.pending_self_tests.remove(&sign_id) else {
+            return;
+        };
+        let (passed, message) = match verifying_key.verify(&self_test_digest(), signature) {
+            Ok(()) => (
+                true,
+                "Aggregate signature verified under the group key".to_string(),
+            ),
+            Err(e) => (false, format!("Aggregate signature failed to verify: {e}")),
+        };
+        if Self::answer_self_test(&response_channel, passed, &message, started_at).is_err() {
+            warn!("Signing self-test for session {sign_id} was no longer awaited");
+        }
+    }
+
+    /// Reports the self-test run by session `sign_id`, if there was one, as failed.
+    pub fn fail_signing_self_test(&mut self, sign_id: u64, reason: &str) {
+        let Some((started_at, response_channel)) = self.pending_self_tests.remove(&sign_id) else {
+            return;
+        };
+        if Self::answer_self_test(&response_channel, false, reason, started_at).is_err() {
+            warn!("Signing self-test for session {sign_id} was no longer awaited");
+        }
+    }
+
+    fn answer_self_test(
+        response_channel: &mpsc::UnboundedSender<SelfResponse>,
+        passed: bool,
+        message: &str,
+        started_at: Instant,
+    ) -> Result<(), NodeError> {
+        let elapsed_ms = u64::try_from(started_at.elapsed().as_millis()).unwrap_or(u64::MAX);
+        response_channel
+            .send(SelfResponse::RunSigningSelfTestResponse {
+                passed,
+                message: message.to_string(),
+                elapsed_ms,
+            })
+            .map_err(|e| NodeError::Error(format!("Failed to send response: {e}")))
+    }
+}
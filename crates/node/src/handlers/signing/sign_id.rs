@@ -0,0 +1,33 @@
+use libp2p::PeerId;
+use sha2::{Digest, Sha256};
+use types::errors::NodeError;
+
+/// Low bits of a sign id that hold the node-local counter.
+pub const COUNTER_BITS: u32 = 40;
+
+const COUNTER_MASK: u64 = (1 << COUNTER_BITS) - 1;
+
+/// The high bits every sign id allocated by `peer_id` starts with.
+#[must_use]
+pub fn namespace(peer_id: &PeerId) -> u64 {
+    let digest = Sha256::digest(peer_id.to_bytes());
+    let mut prefix = [0u8; 8];
+    prefix[5..].copy_from_slice(&digest[..3]);
+    u64::from_be_bytes(prefix)
+}
+
+/// Builds the sign id for the `counter`-th session started by `peer_id`. The counter is
+/// persisted by the chain interface before it is handed out, so ids neither repeat across a
+/// restart nor collide with another node's, whose namespace differs.
+pub fn compose(peer_id: &PeerId, counter: u64) -> Result<u64, NodeError> {
+    if counter > COUNTER_MASK {
+        return Err(NodeError::Error("Sign id counter exhausted".to_string()));
+    }
+    Ok((namespace(peer_id) << COUNTER_BITS) | counter)
+}
+
+/// The node-local counter a sign id was built from.
+#[must_use]
+pub const fn counter(sign_id: u64) -> u64 {
+    sign_id & COUNTER_MASK
+}
@@ -1,9 +1,27 @@
-use std::{collections::BTreeMap, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
 
-use crate::{NodeState, handlers::signing::SigningState, wallet::Wallet};
+use crate::{
+    NodeState,
+    config::DEFAULT_NONCE_POOL_SIZE,
+    handlers::signing::{
+        BroadcastWithdrawal, COORDINATOR_SESSION_TIMEOUT, PARTICIPANT_SESSION_TIMEOUT,
+        SigningState, nonce_pool::NoncePool,
+    },
+    wallet::Wallet,
+};
+use bitcoin::secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
 use frost_secp256k1::{self as frost};
+use tokio::time::Instant;
 use tracing::{error, info};
-use types::{intents::PendingSpend, network::network_protocol::Network};
+use types::{
+    audit::{AuditEntry, AuditEventKind, AuditOutcome},
+    errors::NodeError,
+    intents::{FeeBumpPolicy, PendingSpend},
+    network::{network_event::SigningSessionInfo, network_protocol::Network},
+};
 
 impl Default for SigningState {
     fn default() -> Self {
@@ -13,13 +31,75 @@ impl Default for SigningState {
 
 impl SigningState {
     #[must_use]
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
-            active_signing: None,
+            active_signing: BTreeMap::new(),
             pending_spends: BTreeMap::new(),
+            pending_watches: BTreeMap::new(),
+            broadcast_withdrawals: HashMap::new(),
+            withdrawal_challenges: BTreeMap::new(),
+            pending_attestations: BTreeMap::new(),
+            pending_self_tests: BTreeMap::new(),
+            nonce_pool: NoncePool::new(DEFAULT_NONCE_POOL_SIZE),
         }
     }
 
+    /// Sets how many signing nonces are kept pre-generated.
+    #[must_use]
+    pub fn with_nonce_pool_size(mut self, size: usize) -> Self {
+        self.nonce_pool = NoncePool::new(size);
+        self
+    }
+
+    /// Progress snapshot of every signing session this node is taking part in.
+    #[must_use]
+    pub fn active_sessions(&self, now: Instant) -> Vec<SigningSessionInfo> {
+        self.active_signing
+            .values()
+            .map(|active| SigningSessionInfo {
+                sign_id: active.sign_id,
+                message_hex: hex::encode(&active.message),
+                selected_peer_count: u32::try_from(active.selected_peers.len()).unwrap_or(u32::MAX),
+                commitments_received: u32::try_from(active.commitments.len()).unwrap_or(u32::MAX),
+                shares_received: u32::try_from(active.signature_shares.len()).unwrap_or(u32::MAX),
+                is_coordinator: active.is_coordinator,
+                elapsed_secs: now.saturating_duration_since(active.started_at).as_secs(),
+            })
+            .collect()
+    }
+
+    /// Drops participant sessions the coordinator never finished with us, and coordinated
+    /// sessions the signers never finished, returning how many. Whatever was waiting on a
+    /// dropped session's signature is released with it, and the inputs of a spend that will
+    /// now never be signed go back to `wallet`.
+    pub fn prune_stale_sessions<W: Wallet>(&mut self, wallet: &mut W, now: Instant) -> usize {
+        let stale: Vec<u64> = self
+            .active_signing
+            .values()
+            .filter(|active| {
+                let timeout = if active.is_coordinator {
+                    COORDINATOR_SESSION_TIMEOUT
+                } else {
+                    PARTICIPANT_SESSION_TIMEOUT
+                };
+                now.saturating_duration_since(active.started_at) >= timeout
+            })
+            .map(|active| active.sign_id)
+            .collect();
+
+        for sign_id in &stale {
+            self.active_signing.remove(sign_id);
+            if let Some(pending) = self.pending_spends.remove(sign_id) {
+                wallet.release_spend(pending.tx.compute_txid());
+            }
+            self.pending_watches.remove(sign_id);
+            self.withdrawal_challenges.remove(sign_id);
+            self.fail_reserve_attestation(*sign_id, "Signing session timed out");
+            self.fail_signing_self_test(*sign_id, "Signing session timed out");
+        }
+        stale.len()
+    }
+
     pub fn frost_signature_to_bitcoin(
         frost_sig: &frost::Signature,
     ) -> Result<bitcoin::secp256k1::schnorr::Signature, String> {
@@ -37,18 +117,102 @@ impl SigningState {
             .map_err(|e| format!("Parse schnorr sig: {e}"))
     }
 
-    pub fn start_spend_request<N: Network, W: Wallet>(
+    /// `key_pkg` shifted by `tweak`, signing for the group key plus `tweak`·G.
+    ///
+    /// Every share and the group key move by the same `tweak`, which survives Lagrange
+    /// interpolation unchanged since the coefficients sum to one, so the shares of any
+    /// signing subset still combine into a signature under the tweaked key.
+    pub fn tweak_key_package(
+        key_pkg: &frost::keys::KeyPackage,
+        tweak: &Scalar,
+    ) -> Result<frost::keys::KeyPackage, NodeError> {
+        let signing_share = SecretKey::from_slice(&key_pkg.signing_share().serialize())
+            .and_then(|share| share.add_tweak(tweak))
+            .map_err(|e| NodeError::Error(format!("Failed to tweak signing share: {e}")))?;
+        let signing_share =
+            frost::keys::SigningShare::deserialize(&signing_share.secret_bytes())
+                .map_err(|e| NodeError::Error(format!("Failed to tweak signing share: {e}")))?;
+        Ok(frost::keys::KeyPackage::new(
+            *key_pkg.identifier(),
+            signing_share,
+            Self::tweak_verifying_share(key_pkg.verifying_share(), tweak)?,
+            Self::tweak_verifying_key(key_pkg.verifying_key(), tweak)?,
+            *key_pkg.min_signers(),
+        ))
+    }
+
+    /// `pubkey_package` shifted by `tweak` to match [`Self::tweak_key_package`], so the shares
+    /// signed under the tweaked key verify and aggregate.
+    pub fn tweak_public_key_package(
+        pubkey_package: &frost::keys::PublicKeyPackage,
+        tweak: &Scalar,
+    ) -> Result<frost::keys::PublicKeyPackage, NodeError> {
+        let verifying_shares = pubkey_package
+            .verifying_shares()
+            .iter()
+            .map(|(identifier, share)| {
+                Ok((*identifier, Self::tweak_verifying_share(share, tweak)?))
+            })
+            .collect::<Result<BTreeMap<_, _>, NodeError>>()?;
+        Ok(frost::keys::PublicKeyPackage::new(
+            verifying_shares,
+            Self::tweak_verifying_key(pubkey_package.verifying_key(), tweak)?,
+        ))
+    }
+
+    fn tweak_verifying_share(
+        share: &frost::keys::VerifyingShare,
+        tweak: &Scalar,
+    ) -> Result<frost::keys::VerifyingShare, NodeError> {
+        let tweaked = share
+            .serialize()
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| Self::tweak_point(&bytes, tweak))
+            .map_err(|e| NodeError::Error(format!("Failed to tweak verifying share: {e}")))?;
+        frost::keys::VerifyingShare::deserialize(&tweaked)
+            .map_err(|e| NodeError::Error(format!("Failed to tweak verifying share: {e}")))
+    }
+
+    fn tweak_verifying_key(
+        key: &frost::VerifyingKey,
+        tweak: &Scalar,
+    ) -> Result<frost::VerifyingKey, NodeError> {
+        let tweaked = key
+            .serialize()
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| Self::tweak_point(&bytes, tweak))
+            .map_err(|e| NodeError::Error(format!("Failed to tweak group key: {e}")))?;
+        frost::VerifyingKey::deserialize(&tweaked)
+            .map_err(|e| NodeError::Error(format!("Failed to tweak group key: {e}")))
+    }
+
+    /// The compressed point `point` plus `tweak`·G.
+    fn tweak_point(point: &[u8], tweak: &Scalar) -> Result<Vec<u8>, String> {
+        let secp = Secp256k1::verification_only();
+        PublicKey::from_slice(point)
+            .and_then(|point| point.add_exp_tweak(&secp, tweak))
+            .map(|point| point.serialize().to_vec())
+            .map_err(|e| e.to_string())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn start_spend_request<N: Network, W: Wallet>(
         &mut self,
         node: &mut NodeState<N, W>,
         amount_sat: u64,
         estimated_fee_sat: u64,
         address: &str,
         user_pubkey: String,
+        fee_bump: Option<FeeBumpPolicy>,
+        challenge: Option<String>,
         dry_run: bool,
     ) -> Option<String> {
         info!("🚀 Creating spend request for {} sat", amount_sat);
 
         let addr = bitcoin::Address::from_str(address).ok()?.assume_checked();
+        // Coin selection drops the spent UTXOs, so capture them first for later replacements.
+        let utxos_before_spend = fee_bump.map(|_| node.wallet.get_utxos());
+        node.wallet.refresh_min_relay_feerate().await;
 
         let (tx, sighash) =
             match node
@@ -63,15 +227,49 @@ impl SigningState {
             };
 
         let sighash_hex = hex::encode(sighash);
-        if let Err(e) = self.start_signing_session(node, &sighash_hex) {
-            error!("❌ Failed to start signing session: {}", e);
+        let audit_entry = AuditEntry::new(
+            AuditEventKind::SigningStarted,
+            amount_sat,
+            address,
+            &sighash_hex,
+            AuditOutcome::Accepted,
+        );
+        if let Err(e) = node.audit(audit_entry.clone()).await {
+            error!("❌ Failed to audit signing session, not starting it: {}", e);
+            node.wallet.release_spend(tx.compute_txid());
             return None;
         }
+        let tweak = node.wallet.signing_tweak(&tx);
+        let sign_id = match self.start_signing_session(node, &sighash_hex, tweak).await {
+            Ok(sign_id) => sign_id,
+            Err(e) => {
+                error!("❌ Failed to start signing session: {}", e);
+                node.audit_outcome(audit_entry.with_outcome(AuditOutcome::Failed(e.to_string())))
+                    .await;
+                node.wallet.release_spend(tx.compute_txid());
+                return None;
+            }
+        };
 
-        if let Some(active) = &self.active_signing {
+        if let Some(sign_id) = sign_id {
+            if let (Some(policy), Some(utxos)) = (fee_bump, utxos_before_spend) {
+                self.pending_watches.insert(
+                    sign_id,
+                    BroadcastWithdrawal {
+                        prevouts: Self::spent_prevouts(&tx, &utxos),
+                        tx: tx.clone(),
+                        fee: estimated_fee_sat,
+                        broadcast_height: 0,
+                        policy,
+                    },
+                );
+            }
+            if let Some(challenge) = challenge {
+                self.withdrawal_challenges.insert(sign_id, challenge);
+            }
             let recipient_script = addr.script_pubkey();
             self.pending_spends.insert(
-                active.sign_id,
+                sign_id,
                 PendingSpend {
                     tx,
                     user_pubkey,
@@ -80,10 +278,11 @@ impl SigningState {
                     fee: estimated_fee_sat,
                 },
             );
-            info!("🚀 Spend request prepared (session id {})", active.sign_id);
+            info!("🚀 Spend request prepared (session id {})", sign_id);
             Some(sighash_hex)
         } else {
             error!("❌ Signing session never became active");
+            node.wallet.release_spend(tx.compute_txid());
             None
         }
     }
@@ -0,0 +1,23 @@
+use bitcoin::Transaction;
+use tracing::debug;
+use types::errors::NodeError;
+use types::network::network_protocol::Network;
+
+use crate::{NodeState, wallet::Wallet};
+
+/// Wallet mutations the deposit and withdrawal handlers make, kept in one place so the UTXO
+/// set changes the same way whichever handler observed the transaction.
+pub struct WalletState;
+
+impl WalletState {
+    /// Tracks the vault outputs of `tx` and drops the UTXOs it spends before returning, so
+    /// whatever the caller does next already sees them.
+    pub fn ingest<N: Network, W: Wallet>(
+        node: &mut NodeState<N, W>,
+        tx: &Transaction,
+    ) -> Result<(), NodeError> {
+        node.wallet.ingest_external_tx(tx)?;
+        debug!("Ingested {} into the wallet", tx.compute_txid());
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use abci::{ChainMessage, ChainResponse};
+use bitcoin::Txid;
+use std::str::FromStr;
+use tracing::info;
+use types::errors::NodeError;
+use types::intents::{WithdrawalRecord, WithdrawalStatus};
+use types::network::network_protocol::Network;
+
+use crate::{NodeState, handlers::withdrawl::SpendIntentState, wallet::Wallet};
+
+impl SpendIntentState {
+    /// Every withdrawal record persisted by this node, confirmed or not.
+    pub async fn load_withdrawal_records(
+        chain_interface_tx: &mut messenger::Sender<ChainMessage, ChainResponse>,
+    ) -> Result<Vec<WithdrawalRecord>, NodeError> {
+        let ChainResponse::GetWithdrawalRecords { records } = chain_interface_tx
+            .send_message_with_response(ChainMessage::GetWithdrawalRecords)
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to load withdrawal records".to_string(),
+            ));
+        };
+        Ok(records)
+    }
+
+    async fn persist_withdrawal_record<N: Network, W: Wallet>(
+        node: &mut NodeState<N, W>,
+        record: WithdrawalRecord,
+    ) -> Result<(), NodeError> {
+        let ChainResponse::UpsertWithdrawalRecord { error } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::UpsertWithdrawalRecord { record })
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to persist withdrawal record".to_string(),
+            ));
+        };
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Persists that `challenge` was paid out by `txid` and starts watching it.
+    pub async fn track_broadcast<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        challenge: String,
+        txid: String,
+    ) -> Result<(), NodeError> {
+        let record = WithdrawalRecord {
+            challenge: challenge.clone(),
+            txid,
+            status: WithdrawalStatus::Broadcast,
+            broadcast_height: node.oracle.get_latest_block_height().await?,
+        };
+        Self::persist_withdrawal_record(node, record.clone()).await?;
+        self.broadcast_withdrawals.insert(challenge, record);
+        Ok(())
+    }
+
+    /// Points the withdrawal broadcast as `replaced_txid` at its fee-bumped replacement.
+    pub async fn track_replacement<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        replaced_txid: &str,
+        txid: String,
+    ) -> Result<(), NodeError> {
+        let Some(record) = self
+            .broadcast_withdrawals
+            .values_mut()
+            .find(|record| record.txid == replaced_txid)
+        else {
+            return Ok(());
+        };
+        record.txid = txid;
+        record.broadcast_height = node.oracle.get_latest_block_height().await?;
+        let record = record.clone();
+        Self::persist_withdrawal_record(node, record).await
+    }
+
+    /// Marks watched withdrawals whose transaction confirmed and stops watching them.
+    pub async fn check_withdrawal_confirmations<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+    ) -> Result<(), NodeError> {
+        let mut confirmed = Vec::new();
+        for (challenge, record) in &self.broadcast_withdrawals {
+            let txid = Txid::from_str(&record.txid).map_err(|e| NodeError::Error(e.to_string()))?;
+            if node.oracle.is_transaction_confirmed(txid).await? {
+                confirmed.push(challenge.clone());
+            }
+        }
+
+        for challenge in confirmed {
+            if let Some(mut record) = self.broadcast_withdrawals.remove(&challenge) {
+                record.status = WithdrawalStatus::Confirmed;
+                info!("✅ Withdrawal {} confirmed in {}", challenge, record.txid);
+                Self::persist_withdrawal_record(node, record).await?;
+            }
+        }
+        Ok(())
+    }
+}
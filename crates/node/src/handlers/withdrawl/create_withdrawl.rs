@@ -1,7 +1,14 @@
-use crate::{NodeState, handlers::withdrawl::SpendIntentState, wallet::Wallet};
+use crate::{
+    NodeState,
+    handlers::{
+        wallet::WalletState,
+        withdrawl::{PendingWithdrawal, SpendIntentState, clamp_fee_rate, unix_timestamp},
+    },
+    wallet::Wallet,
+};
 use abci::{ChainMessage, ChainResponse};
 use bitcoin::{
-    Transaction as BitcoinTransaction,
+    OutPoint, Transaction as BitcoinTransaction,
     key::Secp256k1,
     secp256k1::{Message, PublicKey, ecdsa::Signature},
 };
@@ -9,18 +16,47 @@ use num_traits::cast::ToPrimitive;
 use protocol::transaction::Transaction;
 use sha2::{Digest, Sha256};
 use std::str::FromStr;
+use types::audit::{AuditEntry, AuditEventKind, AuditOutcome};
 use types::broadcast::BroadcastMessage;
 use types::errors::NodeError;
-use types::intents::{PendingSpend, WithdrawlIntent};
+use types::intents::{FeePolicy, PendingSpend, WithdrawlIntent};
 use types::network::network_event::SelfRequest;
 use types::network::network_protocol::Network;
 
+/// Fee, in sat, for `vsize` vbytes at `sat_per_vb`. Rounded up, so a fractional feerate never
+/// quotes a fee below it.
+fn fee_for_vsize(sat_per_vb: f64, vsize: usize) -> Result<u64, NodeError> {
+    vsize
+        .to_f64()
+        .map(|vsize| (sat_per_vb * vsize).ceil())
+        .and_then(|fee| fee.to_u64())
+        .ok_or_else(|| {
+            NodeError::Error(format!(
+                "Fee for {vsize} vB at {sat_per_vb} sat/vB is out of range"
+            ))
+        })
+}
+
+/// What the user is debited for a withdrawal paying out `amount_sat` with a `fee` sat fee.
+fn debit_amount(fee_policy: FeePolicy, amount_sat: u64, fee: u64) -> Result<u64, NodeError> {
+    amount_sat
+        .checked_add(fee_policy.user_fee(fee))
+        .ok_or_else(|| {
+            NodeError::Error(format!(
+                "Withdrawal of {amount_sat} sat with a {fee} sat fee overflows"
+            ))
+        })
+}
+
 impl SpendIntentState {
     pub async fn propose_withdrawal<N: Network, W: Wallet>(
         &mut self,
         node: &mut NodeState<N, W>,
         withdrawal_intent: &WithdrawlIntent,
     ) -> Result<(u64, String), NodeError> {
+        self.check_withdrawal_amount(withdrawal_intent.amount_sat)?;
+        self.reserve_intent_slot()?;
+
         let ChainResponse::GetAccount { account } = node
             .chain_interface_tx
             .send_message_with_response(ChainMessage::GetAccount {
@@ -39,35 +75,87 @@ impl SpendIntentState {
             return Err(NodeError::Error("Insufficient balance".to_string()));
         }
 
-        let current_fee_per_vb = node
-            .oracle
-            .get_current_fee_per_vb(withdrawal_intent.blocks_to_confirm)
-            .await?;
+        let relay_floor = node.wallet.refresh_min_relay_feerate().await;
+        let relay_floor = relay_floor.to_f64().ok_or_else(|| {
+            NodeError::Error(format!(
+                "Minimum relay feerate of {relay_floor} sat/vB is out of range"
+            ))
+        })?;
+        let current_fee_per_vb = match withdrawal_intent.fee_rate_sat_vb {
+            Some(fee_rate) if fee_rate > self.max_fee_rate_sat_vb => {
+                return Err(NodeError::Error(format!(
+                    "Fee rate of {fee_rate} sat/vB is above the maximum of {} sat/vB",
+                    self.max_fee_rate_sat_vb
+                )));
+            }
+            Some(fee_rate) => {
+                let fee_rate = fee_rate.to_f64().ok_or_else(|| {
+                    NodeError::Error(format!("Fee rate of {fee_rate} sat/vB is out of range"))
+                })?;
+                if fee_rate < relay_floor {
+                    return Err(NodeError::Error(format!(
+                        "Fee rate of {fee_rate} sat/vB is below the minimum relay feerate of {relay_floor} sat/vB"
+                    )));
+                }
+                clamp_fee_rate(fee_rate)
+            }
+            None => clamp_fee_rate(
+                node.oracle
+                    .get_current_fee_per_vb(withdrawal_intent.blocks_to_confirm)
+                    .await?,
+            )
+            .max(relay_floor),
+        };
 
+        let address_to = bitcoin::Address::from_str(&withdrawal_intent.address_to)
+            .map_err(|e| NodeError::Error(format!("Invalid withdrawal address: {e}")))?
+            .assume_checked();
         let (tx, _) = node.wallet.create_spend(
             withdrawal_intent.amount_sat,
-            (current_fee_per_vb * 120.0).round().to_u64().unwrap(), // Just estimate for now this doesnt affect vsize
-            &bitcoin::Address::from_str(&withdrawal_intent.address_to)
-                .unwrap()
-                .assume_checked(),
+            fee_for_vsize(current_fee_per_vb, 120)?, // Just estimate for now this doesnt affect vsize
+            &address_to,
             true,
         )?;
 
-        let vsize = tx.vsize();
+        let fee = fee_for_vsize(current_fee_per_vb, tx.vsize())?
+            .checked_mul(2)
+            .ok_or_else(|| NodeError::Error("Withdrawal fee overflows".to_string()))?;
+        let total_amount = debit_amount(node.config.fee_policy, withdrawal_intent.amount_sat, fee)?;
 
-        let fee = (current_fee_per_vb * vsize.to_f64().unwrap())
-            .round()
-            .to_u64()
-            .unwrap()
-            * 2;
-        let total_amount = withdrawal_intent.amount_sat + fee;
+        if account.balance < total_amount {
+            return Err(NodeError::Error("Insufficient balance".to_string()));
+        }
+
+        if let Some(fee_bump) = withdrawal_intent.fee_bump {
+            if fee_bump.max_fee_sat <= fee {
+                return Err(NodeError::Error(format!(
+                    "Fee bump cap of {} sat does not exceed the quoted fee of {fee} sat",
+                    fee_bump.max_fee_sat
+                )));
+            }
+        }
 
         let nonce: [u8; 16] = rand::random();
         let challenge = Sha256::digest(nonce).to_vec();
         let challenge_hex = hex::encode(challenge);
 
-        self.pending_intents
-            .insert(challenge_hex.clone(), (withdrawal_intent.clone(), fee));
+        node.audit(AuditEntry::new(
+            AuditEventKind::WithdrawalProposed,
+            withdrawal_intent.amount_sat,
+            &withdrawal_intent.address_to,
+            &challenge_hex,
+            AuditOutcome::Accepted,
+        ))
+        .await?;
+
+        self.pending_intents.insert(
+            challenge_hex.clone(),
+            PendingWithdrawal {
+                intent: withdrawal_intent.clone(),
+                fee,
+                expires_at: unix_timestamp() + self.quote_ttl_seconds,
+            },
+        );
 
         Ok((total_amount, challenge_hex))
     }
@@ -98,27 +186,106 @@ impl SpendIntentState {
         Ok(secp.verify_ecdsa(&message, &signature, &public_key).is_ok())
     }
 
-    pub fn confirm_withdrawal<N: Network, W: Wallet>(
+    /// Takes the pending quote for `challenge` if it is unexpired and `signature` authorises it.
+    fn take_authorised_intent(
         &mut self,
-        node: &mut NodeState<N, W>,
         challenge: &str,
         signature: &str,
-    ) -> Result<(), NodeError> {
-        let Some((withdrawal_intent, fee)) = self.pending_intents.remove(challenge) else {
+    ) -> Result<PendingWithdrawal, NodeError> {
+        let Some(pending) = self.pending_intents.remove(challenge) else {
             return Err(NodeError::Error("Challenge not found".to_string()));
         };
 
-        if !Self::verify_signature(challenge, signature, &withdrawal_intent.public_key)? {
+        if unix_timestamp() >= pending.expires_at {
+            return Err(NodeError::WithdrawalQuoteExpired {
+                challenge: challenge.to_string(),
+                expired_at: pending.expires_at,
+            });
+        }
+
+        if !Self::verify_signature(challenge, signature, &pending.intent.public_key)? {
             return Err(NodeError::Error("Invalid signature".to_string()));
         }
 
+        Ok(pending)
+    }
+
+    pub async fn confirm_withdrawal<N: Network, W: Wallet>(
+        &mut self,
+        node: &mut NodeState<N, W>,
+        challenge: &str,
+        signature: &str,
+    ) -> Result<(), NodeError> {
+        let PendingWithdrawal {
+            intent: withdrawal_intent,
+            fee,
+            ..
+        } = match self.take_authorised_intent(challenge, signature) {
+            Ok(pending) => pending,
+            Err(e) => {
+                node.audit_outcome(AuditEntry::new(
+                    AuditEventKind::WithdrawalConfirmed,
+                    0,
+                    "",
+                    challenge,
+                    AuditOutcome::Rejected(e.to_string()),
+                ))
+                .await;
+                return Err(e);
+            }
+        };
+
+        let audit_entry = AuditEntry::new(
+            AuditEventKind::WithdrawalConfirmed,
+            withdrawal_intent.amount_sat,
+            &withdrawal_intent.address_to,
+            challenge,
+            AuditOutcome::Accepted,
+        );
+        node.audit(audit_entry.clone()).await?;
+
+        if let Err(e) = Self::spend_confirmed_intent(node, challenge, withdrawal_intent, fee).await
+        {
+            node.audit_outcome(audit_entry.with_outcome(AuditOutcome::Failed(e.to_string())))
+                .await;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    async fn spend_confirmed_intent<N: Network, W: Wallet>(
+        node: &mut NodeState<N, W>,
+        challenge: &str,
+        withdrawal_intent: WithdrawlIntent,
+        fee: u64,
+    ) -> Result<(), NodeError> {
+        // Burn the challenge before spending so it can never authorise a second payout,
+        // even if an identical intent is quoted again later.
+        let ChainResponse::ConsumeWithdrawalChallenge { error } = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::ConsumeWithdrawalChallenge {
+                challenge: challenge.to_string(),
+            })
+            .await?
+        else {
+            return Err(NodeError::Error(
+                "Failed to record withdrawal challenge".to_string(),
+            ));
+        };
+        if let Some(e) = error {
+            return Err(e);
+        }
+
         node.network_handle
             .send_self_request(
                 SelfRequest::Spend {
                     amount_sat: withdrawal_intent.amount_sat,
                     fee,
-                    address_to: withdrawal_intent.address_to.clone(),
+                    address_to: withdrawal_intent.address_to,
                     user_pubkey: withdrawal_intent.public_key,
+                    fee_bump: withdrawal_intent.fee_bump,
+                    challenge: Some(challenge.to_string()),
                 },
                 false,
             )
@@ -134,13 +301,18 @@ impl SpendIntentState {
         user_pubkey: String,
         address_to: String,
     ) -> Result<(), NodeError> {
-        node.oracle.broadcast_transaction(tx).await?;
+        if let Err(e) = Self::audited_broadcast(node, tx, &address_to).await {
+            // Nothing else will broadcast it, so its inputs must not stay reserved.
+            node.wallet.release_spend(tx.compute_txid());
+            return Err(e);
+        }
 
         let transaction = Transaction::create_withdrawal_transaction(
             &user_pubkey,
             &address_to,
-            tx.output[0].value.to_sat() + fee,
-        )?;
+            debit_amount(node.config.fee_policy, tx.output[0].value.to_sat(), fee)?,
+        )?
+        .with_spent_outpoints(&spent_outpoints(tx));
 
         let ChainResponse::AddTransactionToBlock { error: None } = node
             .chain_interface_tx
@@ -174,9 +346,9 @@ impl SpendIntentState {
         node: &mut NodeState<N, W>,
         pending: PendingSpend,
     ) -> Result<(), NodeError> {
-        node.oracle.broadcast_transaction(&pending.tx).await?;
+        Self::audited_broadcast(node, &pending.tx, &pending.address_to).await?;
 
-        node.wallet.ingest_external_tx(&pending.tx)?;
+        WalletState::ingest(node, &pending.tx)?;
 
         let pay_out = pending
             .tx
@@ -185,13 +357,14 @@ impl SpendIntentState {
             .find(|o| o.script_pubkey == pending.recipient_script)
             .ok_or_else(|| NodeError::Error("payment output not found".into()))?;
 
-        let debit = pay_out.value.to_sat() + pending.fee;
+        let debit = debit_amount(node.config.fee_policy, pay_out.value.to_sat(), pending.fee)?;
 
         let transaction = Transaction::create_withdrawal_transaction(
             &pending.user_pubkey,
             &pending.address_to,
             debit,
-        )?;
+        )?
+        .with_spent_outpoints(&spent_outpoints(&pending.tx));
 
         let ChainResponse::AddTransactionToBlock { error: None } = node
             .chain_interface_tx
@@ -205,4 +378,32 @@ impl SpendIntentState {
 
         Ok(())
     }
+
+    /// Broadcasts a signed withdrawal paying `address_to`, auditing it first.
+    pub(crate) async fn audited_broadcast<N: Network, W: Wallet>(
+        node: &mut NodeState<N, W>,
+        tx: &BitcoinTransaction,
+        address_to: &str,
+    ) -> Result<(), NodeError> {
+        let audit_entry = AuditEntry::new(
+            AuditEventKind::TransactionBroadcast,
+            tx.output.first().map_or(0, |output| output.value.to_sat()),
+            address_to,
+            tx.compute_txid().to_string(),
+            AuditOutcome::Accepted,
+        );
+        node.audit(audit_entry.clone()).await?;
+
+        if let Err(e) = node.oracle.broadcast_transaction(tx).await {
+            node.audit_outcome(audit_entry.with_outcome(AuditOutcome::Failed(e.to_string())))
+                .await;
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+/// The vault outpoints `tx` spends, recorded on the chain withdrawal so it cannot be reused.
+fn spent_outpoints(tx: &BitcoinTransaction) -> Vec<OutPoint> {
+    tx.input.iter().map(|input| input.previous_output).collect()
 }
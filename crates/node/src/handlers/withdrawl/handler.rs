@@ -1,6 +1,7 @@
 use crate::wallet::Wallet;
 use crate::{NodeState, handlers::Handler, handlers::withdrawl::SpendIntentState};
 use libp2p::gossipsub::Message;
+use types::audit::{AuditEntry, AuditEventKind, AuditOutcome};
 use types::broadcast::BroadcastMessage;
 use types::errors::NodeError;
 use types::network::network_event::{NetworkEvent, SelfRequest, SelfResponse};
@@ -20,7 +21,20 @@ impl<N: Network, W: Wallet> Handler<N, W> for SpendIntentState {
                 response_channel,
             } => {
                 let (total_amount, challenge) =
-                    self.propose_withdrawal(node, &withdrawal_intent).await?;
+                    match self.propose_withdrawal(node, &withdrawal_intent).await {
+                        Ok(quote) => quote,
+                        Err(e) => {
+                            node.audit_outcome(AuditEntry::new(
+                                AuditEventKind::WithdrawalProposed,
+                                withdrawal_intent.amount_sat,
+                                &withdrawal_intent.address_to,
+                                "",
+                                AuditOutcome::Rejected(e.to_string()),
+                            ))
+                            .await;
+                            return Err(e);
+                        }
+                    };
                 if let Some(response_channel) = response_channel {
                     response_channel
                         .send(SelfResponse::ProposeWithdrawalResponse {
@@ -38,13 +52,53 @@ impl<N: Network, W: Wallet> Handler<N, W> for SpendIntentState {
                     },
                 response_channel,
             } => {
-                self.confirm_withdrawal(node, &challenge, &signature)?;
+                self.confirm_withdrawal(node, &challenge, &signature)
+                    .await?;
                 if let Some(response_channel) = response_channel {
                     response_channel
                         .send(SelfResponse::ConfirmWithdrawalResponse { success: true })
                         .map_err(|e| NodeError::Error(e.to_string()))?;
                 }
             }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::GetFeeEstimates,
+                response_channel,
+            } => {
+                let estimates = Self::fee_estimates(node).await?;
+                if let Some(response_channel) = response_channel {
+                    response_channel
+                        .send(SelfResponse::GetFeeEstimatesResponse { estimates })
+                        .map_err(|e| NodeError::Error(e.to_string()))?;
+                }
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::MaintenanceTick,
+                ..
+            } => {
+                let removed = self.prune_expired_intents();
+                if removed > 0 {
+                    tracing::debug!("Pruned {removed} expired withdrawal intents");
+                }
+                if let Err(e) = self.check_withdrawal_confirmations(node).await {
+                    tracing::warn!("Failed to check withdrawal confirmations: {e}");
+                }
+            }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::WithdrawalBroadcast { challenge, txid },
+                ..
+            } => {
+                self.track_broadcast(node, challenge, txid).await?;
+            }
+            NetworkEvent::SelfRequest {
+                request:
+                    SelfRequest::WithdrawalReplaced {
+                        replaced_txid,
+                        txid,
+                    },
+                ..
+            } => {
+                self.track_replacement(node, &replaced_txid, txid).await?;
+            }
             NetworkEvent::GossipsubMessage(Message { data, .. }) => {
                 let broadcast = BroadcastMessage::decode(&data).map_err(|e| {
                     NodeError::Error(format!("Failed to decode broadcast message: {e}"))
@@ -1,12 +1,57 @@
 use std::collections::HashMap;
 
-use types::intents::WithdrawlIntent;
+use types::{
+    errors::NodeError,
+    intents::{WithdrawalRecord, WithdrawalStatus, WithdrawlIntent},
+    network::network_event::FeeEstimate,
+    network::network_protocol::Network,
+};
 
+use crate::{
+    NodeState,
+    config::{
+        DEFAULT_MAX_PENDING_INTENTS, DEFAULT_MAX_WITHDRAWAL_FEE_RATE_SAT_VB,
+        DEFAULT_MAX_WITHDRAWAL_SAT, DEFAULT_MIN_WITHDRAWAL_SAT,
+    },
+    wallet::Wallet,
+};
+
+pub mod broadcast_tracking;
 pub mod create_withdrawl;
 pub mod handler;
 
+/// Number of seconds a withdrawal quote remains valid after it is proposed.
+pub const DEFAULT_QUOTE_TTL_SECONDS: u64 = 300;
+
+/// Lowest feerate, in sat/vB, the vault quotes regardless of the oracle estimate.
+pub const MIN_FEE_RATE_SAT_VB: f64 = 1.0;
+/// Highest feerate, in sat/vB, the vault quotes; guards against a faulty oracle spike.
+pub const MAX_FEE_RATE_SAT_VB: f64 = 500.0;
+/// Confirmation targets, in blocks, reported by `GetFeeEstimates`.
+pub const FEE_ESTIMATE_TARGETS: [u16; 4] = [1, 3, 6, 12];
+
+/// Keeps an oracle feerate within `MIN_FEE_RATE_SAT_VB..=MAX_FEE_RATE_SAT_VB`.
+#[must_use]
+pub const fn clamp_fee_rate(sat_per_vb: f64) -> f64 {
+    sat_per_vb.clamp(MIN_FEE_RATE_SAT_VB, MAX_FEE_RATE_SAT_VB)
+}
+
+pub struct PendingWithdrawal {
+    pub intent: WithdrawlIntent,
+    pub fee: u64,
+    pub expires_at: u64,
+}
+
 pub struct SpendIntentState {
-    pub pending_intents: HashMap<String, (WithdrawlIntent, u64)>,
+    pub pending_intents: HashMap<String, PendingWithdrawal>,
+    pub quote_ttl_seconds: u64,
+    pub max_pending_intents: usize,
+    pub min_withdrawal_sat: u64,
+    pub max_withdrawal_sat: u64,
+    /// Highest feerate, in sat/vB, a withdrawal intent may ask for explicitly.
+    pub max_fee_rate_sat_vb: u64,
+    /// Broadcast withdrawals awaiting confirmation, keyed by challenge.
+    pub broadcast_withdrawals: HashMap<String, WithdrawalRecord>,
 }
 
 impl Default for SpendIntentState {
@@ -18,8 +63,121 @@ impl Default for SpendIntentState {
 impl SpendIntentState {
     #[must_use]
     pub fn new() -> Self {
+        Self::with_quote_ttl(DEFAULT_QUOTE_TTL_SECONDS)
+    }
+
+    #[must_use]
+    pub fn with_quote_ttl(quote_ttl_seconds: u64) -> Self {
         Self {
             pending_intents: HashMap::new(),
+            quote_ttl_seconds,
+            max_pending_intents: DEFAULT_MAX_PENDING_INTENTS,
+            min_withdrawal_sat: DEFAULT_MIN_WITHDRAWAL_SAT,
+            max_withdrawal_sat: DEFAULT_MAX_WITHDRAWAL_SAT,
+            max_fee_rate_sat_vb: DEFAULT_MAX_WITHDRAWAL_FEE_RATE_SAT_VB,
+            broadcast_withdrawals: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_max_pending_intents(mut self, max_pending_intents: usize) -> Self {
+        self.max_pending_intents = max_pending_intents;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_withdrawal_limits(mut self, min_sat: u64, max_sat: u64) -> Self {
+        self.min_withdrawal_sat = min_sat;
+        self.max_withdrawal_sat = max_sat;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_fee_rate(mut self, max_fee_rate_sat_vb: u64) -> Self {
+        self.max_fee_rate_sat_vb = max_fee_rate_sat_vb;
+        self
+    }
+
+    /// Resumes confirmation tracking for the still unconfirmed withdrawals among `records`.
+    #[must_use]
+    pub fn with_broadcast_withdrawals(
+        mut self,
+        records: impl IntoIterator<Item = WithdrawalRecord>,
+    ) -> Self {
+        self.broadcast_withdrawals.extend(
+            records
+                .into_iter()
+                .filter(|record| record.status == WithdrawalStatus::Broadcast)
+                .map(|record| (record.challenge.clone(), record)),
+        );
+        self
+    }
+
+    /// Rejects withdrawal amounts outside the configured `min_withdrawal_sat..=max_withdrawal_sat`.
+    pub const fn check_withdrawal_amount(&self, amount_sat: u64) -> Result<(), NodeError> {
+        if amount_sat < self.min_withdrawal_sat || amount_sat > self.max_withdrawal_sat {
+            return Err(NodeError::WithdrawalAmountOutOfRange {
+                amount_sat,
+                min_sat: self.min_withdrawal_sat,
+                max_sat: self.max_withdrawal_sat,
+            });
+        }
+        Ok(())
+    }
+
+    /// Clamped oracle feerates for each of `FEE_ESTIMATE_TARGETS`.
+    pub async fn fee_estimates<N: Network, W: Wallet>(
+        node: &NodeState<N, W>,
+    ) -> Result<Vec<FeeEstimate>, NodeError> {
+        let mut estimates = Vec::with_capacity(FEE_ESTIMATE_TARGETS.len());
+        for target_blocks in FEE_ESTIMATE_TARGETS {
+            let sat_per_vb = node
+                .oracle
+                .get_current_fee_per_vb(Some(target_blocks))
+                .await?;
+            estimates.push(FeeEstimate {
+                target_blocks,
+                sat_per_vb: clamp_fee_rate(sat_per_vb),
+            });
         }
+        Ok(estimates)
+    }
+
+    /// Drops every pending intent whose quote has expired, returning how many were removed.
+    pub fn prune_expired_intents(&mut self) -> usize {
+        let now = unix_timestamp();
+        let before = self.pending_intents.len();
+        self.pending_intents
+            .retain(|_, pending| now < pending.expires_at);
+        before - self.pending_intents.len()
     }
+
+    /// Makes room for one more pending intent, evicting expired quotes oldest first.
+    pub fn reserve_intent_slot(&mut self) -> Result<(), NodeError> {
+        let now = unix_timestamp();
+        while self.pending_intents.len() >= self.max_pending_intents {
+            let oldest_expired = self
+                .pending_intents
+                .iter()
+                .filter(|(_, pending)| now >= pending.expires_at)
+                .min_by_key(|(_, pending)| pending.expires_at)
+                .map(|(challenge, _)| challenge.clone());
+
+            let Some(challenge) = oldest_expired else {
+                return Err(NodeError::TooManyPendingIntents {
+                    kind: "withdrawal".to_string(),
+                    limit: self.max_pending_intents,
+                });
+            };
+            self.pending_intents.remove(&challenge);
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
 }
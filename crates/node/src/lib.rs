@@ -1,9 +1,19 @@
 use crate::{
     handlers::{
-        Handler, balance::BalanceState, consensus::ConsensusState, deposit::DepositIntentState,
-        dkg::DkgState, signing::SigningState, withdrawl::SpendIntentState,
+        Handler,
+        balance::BalanceState,
+        consensus::ConsensusState,
+        deposit::{
+            DepositIntentState,
+            create_deposit::{DEPOSIT_EVENT_CHANNEL_CAPACITY, deposit_tweak},
+        },
+        dkg::DkgState,
+        signing::SigningState,
+        withdrawl::SpendIntentState,
     },
-    wallet::Wallet,
+    main_loop::PendingHandshake,
+    validator_stats::ValidatorStatsTracker,
+    wallet::{Wallet, tweaked_p2tr_address},
 };
 use abci::{ChainMessage, ChainResponse};
 use consensus::{ConsensusMessage, ConsensusResponse};
@@ -11,18 +21,26 @@ use frost_secp256k1::{self as frost, Identifier};
 use libp2p::PeerId;
 use oracle::oracle::Oracle;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use tokio::sync::broadcast;
-use tracing::{error, info};
-use types::network::network_protocol::Network;
-use types::{errors::NodeError, intents::DepositIntent, network::network_event::NetworkEvent};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::{sync::broadcast, time::Instant};
+use tracing::{error, info, warn};
+use types::clock::{SharedClock, SystemClock};
+use types::network::network_protocol::{Network, PROTOCOL_VERSION};
+use types::{
+    audit::AuditEntry,
+    errors::NodeError,
+    intents::{DepositEvent, DepositIntent},
+    network::network_event::NetworkEvent,
+};
 
 pub use config::{ConfigStore, KeyStore, NodeConfig, NodeConfigBuilder};
 
 pub mod config;
+pub mod data_dir;
 pub mod handlers;
 pub mod main_loop;
 pub mod start_node;
+pub mod validator_stats;
 
 pub mod utils;
 pub use utils::key_manager;
@@ -39,6 +57,17 @@ pub struct NodeState<N: Network, W: Wallet> {
     pub handlers: Vec<Box<dyn Handler<N, W>>>,
     pub peer_id: PeerId,
     pub peers: HashSet<PeerId>,
+    /// Peers that dropped out but are still inside their reconnect grace period.
+    pub disconnected_peers: HashMap<PeerId, Instant>,
+    /// Peer-to-peer protocol version announced in the connect handshake.
+    pub protocol_version: u32,
+    /// Peers whose handshake announced another protocol version or chain; ignored until restart.
+    pub incompatible_peers: HashSet<PeerId>,
+    /// Connected peers whose Hello has not arrived yet, with what they sent in the meantime.
+    pub pending_handshakes: HashMap<PeerId, PendingHandshake>,
+    /// Messages held back from peers that have since completed their handshake, handled
+    /// before anything new is read from the network.
+    pub replayed_events: VecDeque<NetworkEvent>,
 
     pub rng: frost::rand_core::OsRng,
     pub pubkey_package: Option<frost::keys::PublicKeyPackage>,
@@ -49,10 +78,18 @@ pub struct NodeState<N: Network, W: Wallet> {
     pub config: NodeConfig,
     pub network_handle: N,
     pub network_events_stream: broadcast::Receiver<NetworkEvent>,
+    /// Network events overwritten in the channel before this node read them.
+    pub dropped_network_events: u64,
+    /// Deposit lifecycle notifications, consumed by streaming gRPC subscribers.
+    pub deposit_event_tx: broadcast::Sender<DepositEvent>,
 
     pub oracle: Box<dyn Oracle>,
     pub chain_interface_tx: messenger::Sender<ChainMessage, ChainResponse>,
     pub consensus_interface_tx: messenger::Sender<ConsensusMessage, ConsensusResponse>,
+    /// Proposals, votes and signing sessions seen from each validator.
+    pub validator_stats: ValidatorStatsTracker,
+    /// Time source for peer grace periods, DKG acks and signing session timeouts.
+    pub clock: SharedClock,
 }
 
 impl<N: Network, W: Wallet> NodeState<N, W> {
@@ -70,11 +107,50 @@ impl<N: Network, W: Wallet> NodeState<N, W> {
     ) -> Result<Self, NodeError> {
         let keys = config.load_dkg_keys()?;
         let dkg_state = DkgState::new();
-        let signing_state = SigningState::new();
-        let consensus_state = ConsensusState::new();
+        let signing_state = SigningState::new().with_nonce_pool_size(config.nonce_pool_size);
+        let consensus_state = ConsensusState::new()
+            .with_chain_id(&config.chain_id)
+            .with_stall_threshold(std::time::Duration::from_secs(
+                config.consensus_stall_threshold_seconds,
+            ));
 
-        let mut deposit_intent_state = DepositIntentState::new(deposit_intent_tx);
-        let withdrawl_intent_state = SpendIntentState::new();
+        let (deposit_event_tx, _) = broadcast::channel(DEPOSIT_EVENT_CHANNEL_CAPACITY);
+        let mut deposit_intent_state = DepositIntentState::new(deposit_intent_tx)
+            .with_max_pending_intents(config.max_pending_intents)
+            .with_max_reorg_depth(config.max_reorg_depth)
+            .with_intent_ttl(std::time::Duration::from_secs(
+                config.deposit_intent_ttl_seconds,
+            ))
+            .with_deposit_event_tx(deposit_event_tx.clone());
+        match DepositIntentState::load_processed_txids(&mut chain_interface_tx).await {
+            Ok(processed_txids) => {
+                deposit_intent_state = deposit_intent_state.with_processed_txids(processed_txids);
+            }
+            Err(e) => warn!("Failed to load processed deposit txids: {}", e),
+        }
+        match DepositIntentState::load_awaiting_deposits(&mut chain_interface_tx).await {
+            Ok(awaiting_depth) => {
+                deposit_intent_state = deposit_intent_state.with_awaiting_depth(awaiting_depth);
+            }
+            Err(e) => warn!("Failed to load deposits awaiting confirmations: {}", e),
+        }
+        let withdrawal_records =
+            match SpendIntentState::load_withdrawal_records(&mut chain_interface_tx).await {
+                Ok(records) => records,
+                Err(e) => {
+                    warn!("Failed to load withdrawal records: {}", e);
+                    Vec::new()
+                }
+            };
+        let withdrawl_intent_state = SpendIntentState::new()
+            .with_max_pending_intents(config.max_pending_intents)
+            .with_withdrawal_limits(config.min_withdrawal_sat, config.max_withdrawal_sat)
+            .with_max_fee_rate(config.max_withdrawal_fee_rate_sat_vb)
+            .with_broadcast_withdrawals(withdrawal_records);
+        info!(
+            "Tracking {} broadcast withdrawals awaiting confirmation",
+            withdrawl_intent_state.broadcast_withdrawals.len()
+        );
         let balance_state = BalanceState::new();
 
         if let Ok(ChainResponse::GetAllDepositIntents { intents }) = chain_interface_tx
@@ -83,22 +159,38 @@ impl<N: Network, W: Wallet> NodeState<N, W> {
         {
             info!("Found {} deposit intents", intents.len());
             for intent in intents {
+                // The monitor usually subscribes after startup, so these stay queued until a
+                // maintenance tick finds it listening.
                 if deposit_intent_state
                     .deposit_addresses
                     .insert(intent.deposit_address.clone())
                 {
-                    if let Err(e) = deposit_intent_state.deposit_intent_tx.send(intent.clone()) {
-                        error!("Failed to notify deposit monitor of new address: {}", e);
-                    }
+                    deposit_intent_state.notify_monitor(intent);
                 }
             }
         }
+        if let Ok(ChainResponse::GetWatchAddresses { addresses }) = chain_interface_tx
+            .send_message_with_response(ChainMessage::GetWatchAddresses)
+            .await
+        {
+            info!("Found {} watched addresses", addresses.len());
+            for address in addresses {
+                deposit_intent_state.watch(address);
+            }
+        }
 
         let mut node_state = Self {
             network_handle: network_handle.clone(),
             network_events_stream: network_events_sender.subscribe(),
+            dropped_network_events: 0,
+            deposit_event_tx,
             peer_id: network_handle.peer_id(),
             peers: HashSet::new(),
+            disconnected_peers: HashMap::new(),
+            protocol_version: PROTOCOL_VERSION,
+            incompatible_peers: HashSet::new(),
+            pending_handshakes: HashMap::new(),
+            replayed_events: VecDeque::new(),
             rng: frost::rand_core::OsRng,
             wallet,
             config,
@@ -115,6 +207,8 @@ impl<N: Network, W: Wallet> NodeState<N, W> {
             oracle,
             chain_interface_tx,
             consensus_interface_tx,
+            validator_stats: ValidatorStatsTracker::new(),
+            clock: SystemClock::shared(),
         };
 
         if let Some((private_key, pubkey)) = keys {
@@ -124,6 +218,67 @@ impl<N: Network, W: Wallet> NodeState<N, W> {
 
         Ok(node_state)
     }
+
+    /// Appends `entry` to the audit log. Operations are audited before they are applied, so
+    /// an error here must abort the operation rather than let it run unrecorded.
+    pub async fn audit(&mut self, entry: AuditEntry) -> Result<(), NodeError> {
+        let ChainResponse::AppendAuditEntry { error } = self
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::AppendAuditEntry { entry })
+            .await?
+        else {
+            return Err(NodeError::Error("Failed to append audit entry".to_string()));
+        };
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Records a rejection or failure, for which there is nothing left to abort.
+    pub async fn audit_outcome(&mut self, entry: AuditEntry) {
+        if let Err(e) = self.audit(entry).await {
+            warn!("Failed to record audit entry: {e}");
+        }
+    }
+
+    /// A fresh signing session id in this node's namespace, backed by a counter the chain
+    /// interface persists, so ids stay unique across restarts.
+    pub async fn allocate_sign_id(&mut self) -> Result<u64, NodeError> {
+        let ChainResponse::AllocateSignIdCounter { counter } = self
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::AllocateSignIdCounter)
+            .await?
+        else {
+            return Err(NodeError::Error("Failed to allocate sign id".to_string()));
+        };
+        handlers::signing::sign_id::compose(&self.peer_id, counter?)
+    }
+
+    /// The group's FROST verifying key as a Bitcoin public key, from which every deposit
+    /// address is derived.
+    pub fn vault_public_key(&self) -> Result<bitcoin::PublicKey, NodeError> {
+        let Some(ref pubkey_package) = self.pubkey_package else {
+            return Err(NodeError::Error("No public key found".to_string()));
+        };
+
+        let verifying_key = pubkey_package
+            .verifying_key()
+            .serialize()
+            .map_err(|x| NodeError::Error(format!("Failed to serialize public key: {x:?}")))?;
+
+        bitcoin::PublicKey::from_slice(&verifying_key)
+            .map_err(|e| NodeError::Error(format!("Failed to parse public key: {e}")))
+    }
+
+    /// Re-derives the address of `intent` from the vault key and the tweak recorded by its
+    /// tracking id, so auditors can confirm the stored address is one the vault controls.
+    /// Watched external addresses never match.
+    pub fn verify_deposit_address(&self, intent: &DepositIntent) -> Result<bool, NodeError> {
+        let expected = tweaked_p2tr_address(
+            self.vault_public_key()?,
+            deposit_tweak(&intent.deposit_tracking_id),
+            self.wallet.network(),
+        )?;
+        Ok(expected.to_string() == intent.deposit_address)
+    }
 }
 
 pub fn peer_id_to_identifier(peer_id: &PeerId) -> Identifier {
@@ -1,28 +1,71 @@
-use tracing::{error, info};
+use std::time::Duration;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::time::Instant;
+use tracing::{error, info, warn};
 
 use crate::wallet::Wallet;
 use crate::{Network, NodeState};
+use libp2p::PeerId;
 use types::errors::NodeError;
-use types::network::network_event::NetworkEvent;
+use types::network::network_event::{DirectMessage, NetworkEvent, SelfRequest};
+use types::network::network_protocol::chain_topics;
+
+/// How long a connected peer has to send its Hello before it is disconnected.
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Messages held back per peer while its handshake is pending; later ones are dropped.
+pub const MAX_HELD_EVENTS: usize = 1_000;
+
+/// A connected peer that has not sent its Hello yet.
+pub struct PendingHandshake {
+    pub since: Instant,
+    /// Gossip and direct messages from the peer, handled once its Hello shows it compatible.
+    pub held: Vec<NetworkEvent>,
+}
 
 impl<N: Network + 'static, W: Wallet + 'static> NodeState<N, W> {
     pub async fn try_poll(&mut self) -> Result<bool, NodeError> {
-        let send_message = self.network_events_stream.try_recv().ok();
-        if let Some(event) = send_message {
+        if let Some(event) = self.replayed_events.pop_front() {
             self.handle_message(event).await?;
-            Ok(true)
-        } else {
-            Ok(false)
+            return Ok(true);
+        }
+        match self.network_events_stream.try_recv() {
+            Ok(event) => {
+                self.handle_message(event).await?;
+                Ok(true)
+            }
+            // The receiver resumes at the oldest event still buffered, so keep polling.
+            Err(TryRecvError::Lagged(skipped)) => {
+                self.record_dropped_events(skipped);
+                Ok(true)
+            }
+            Err(TryRecvError::Empty | TryRecvError::Closed) => Ok(false),
         }
     }
 
     pub async fn poll(&mut self) -> Result<(), NodeError> {
-        if let Ok(event) = self.network_events_stream.recv().await {
-            self.handle_message(event).await?;
+        if let Some(event) = self.replayed_events.pop_front() {
+            return self.handle_message(event).await;
+        }
+        match self.network_events_stream.recv().await {
+            Ok(event) => self.handle_message(event).await?,
+            Err(RecvError::Lagged(skipped)) => self.record_dropped_events(skipped),
+            Err(RecvError::Closed) => {}
         }
         Ok(())
     }
 
+    /// The handler loop fell so far behind that the event channel overwrote `skipped`
+    /// events, consensus messages included, before they were read.
+    fn record_dropped_events(&mut self, skipped: u64) {
+        self.dropped_network_events += skipped;
+        metrics::counter!("network_events_dropped_total").increment(skipped);
+        warn!(
+            skipped,
+            total_dropped = self.dropped_network_events,
+            "Node fell behind the network event stream, events were dropped"
+        );
+    }
+
     pub async fn start(&mut self) {
         info!("Local peer id: {}", self.peer_id);
         loop {
@@ -33,6 +76,13 @@ impl<N: Network + 'static, W: Wallet + 'static> NodeState<N, W> {
     }
 
     pub async fn handle_message(&mut self, message: NetworkEvent) -> Result<(), NodeError> {
+        if self.is_from_incompatible_peer(&message) || self.is_on_foreign_topic(&message) {
+            return Ok(());
+        }
+        let Some(message) = self.hold_until_handshake(message) else {
+            return Ok(());
+        };
+
         let mut handlers = std::mem::take(&mut self.handlers);
 
         for handler in &mut handlers {
@@ -44,18 +94,203 @@ impl<N: Network + 'static, W: Wallet + 'static> NodeState<N, W> {
         match message {
             NetworkEvent::PeersConnected(list) => {
                 for (peer_id, _multiaddr) in list {
-                    self.peers.insert(peer_id);
+                    if self.incompatible_peers.contains(&peer_id) {
+                        continue;
+                    }
+                    if self.disconnected_peers.remove(&peer_id).is_some() {
+                        info!("Peer {} reconnected within grace period", peer_id);
+                    }
+                    if self.peers.insert(peer_id) {
+                        self.pending_handshakes.insert(
+                            peer_id,
+                            PendingHandshake {
+                                since: self.clock.now(),
+                                held: Vec::new(),
+                            },
+                        );
+                        self.send_hello(peer_id);
+                    }
+                }
+            }
+            NetworkEvent::MessageEvent((
+                peer_id,
+                DirectMessage::Hello {
+                    protocol_version,
+                    chain_id,
+                },
+            )) => self.handle_hello(peer_id, protocol_version, &chain_id),
+            NetworkEvent::PeersDisconnected(list) => {
+                // Keep the peer in the active set until the grace period runs out so a
+                // transient blip does not reshuffle signers mid-round.
+                let now = self.clock.now();
+                for (peer_id, _multiaddr) in list {
+                    if self.peers.contains(&peer_id) {
+                        self.disconnected_peers.entry(peer_id).or_insert(now);
+                    }
                 }
             }
+            NetworkEvent::SelfRequest {
+                request: SelfRequest::ConsensusTick,
+                ..
+            } => {
+                self.expire_disconnected_peers();
+                self.expire_pending_handshakes();
+            }
             NetworkEvent::SendBroadcast { message } => {
                 // Forward broadcast request to the network handle
                 if let Err(e) = self.network_handle.send_broadcast(message) {
                     error!("Failed to send broadcast: {:?}", e);
                 }
             }
+            NetworkEvent::SendPrivateMessage { peer_id, message } => {
+                if let Err(e) = self.network_handle.send_private_message(peer_id, message) {
+                    error!("Failed to send private message to {}: {:?}", peer_id, e);
+                }
+            }
             _ => {}
         }
 
         Ok(())
     }
+
+    fn send_hello(&self, peer_id: PeerId) {
+        let hello = DirectMessage::Hello {
+            protocol_version: self.protocol_version,
+            chain_id: self.config.chain_id.clone(),
+        };
+        if let Err(e) = self.network_handle.send_private_message(peer_id, hello) {
+            warn!("Failed to send handshake to {}: {:?}", peer_id, e);
+        }
+    }
+
+    /// Completes the peer's handshake, handling what it sent while it was pending, or drops
+    /// it when the handshake shows it cannot interoperate with us.
+    fn handle_hello(&mut self, peer_id: PeerId, protocol_version: u32, chain_id: &str) {
+        let pending = self.pending_handshakes.remove(&peer_id);
+        if protocol_version == self.protocol_version && chain_id == self.config.chain_id {
+            match pending {
+                Some(pending) => self.replayed_events.extend(pending.held),
+                // The peer reconnected to us while we still counted it as connected, so it is
+                // waiting on a Hello we never sent.
+                None if self.peers.contains(&peer_id) => self.send_hello(peer_id),
+                None => {}
+            }
+            return;
+        }
+
+        warn!(
+            "🚫 Disconnecting peer {} running protocol v{} on chain '{}' (we run v{} on '{}')",
+            self.network_handle.peer_name(&peer_id),
+            protocol_version,
+            chain_id,
+            self.protocol_version,
+            self.config.chain_id
+        );
+        self.incompatible_peers.insert(peer_id);
+        self.peers.remove(&peer_id);
+        self.disconnected_peers.remove(&peer_id);
+        if let Err(e) = self.network_handle.disconnect_peer(peer_id) {
+            warn!("Failed to disconnect peer {}: {:?}", peer_id, e);
+        }
+    }
+
+    fn is_from_incompatible_peer(&self, message: &NetworkEvent) -> bool {
+        let source = match message {
+            NetworkEvent::MessageEvent((peer_id, _)) | NetworkEvent::Subscribed { peer_id, .. } => {
+                Some(peer_id)
+            }
+            NetworkEvent::GossipsubMessage(message) => message.source.as_ref(),
+            _ => None,
+        };
+        source.is_some_and(|peer_id| self.incompatible_peers.contains(peer_id))
+    }
+
+    /// Holds back gossip and direct messages from a peer whose handshake is still pending,
+    /// returning every other message for handling. A subscription only registers the peer;
+    /// nothing it sends is acted on until its Hello arrives.
+    fn hold_until_handshake(&mut self, message: NetworkEvent) -> Option<NetworkEvent> {
+        let source = match &message {
+            NetworkEvent::MessageEvent((_, DirectMessage::Hello { .. })) => return Some(message),
+            NetworkEvent::MessageEvent((peer_id, _)) => *peer_id,
+            NetworkEvent::GossipsubMessage(libp2p::gossipsub::Message {
+                source: Some(peer_id),
+                ..
+            }) => *peer_id,
+            _ => return Some(message),
+        };
+        let Some(pending) = self.pending_handshakes.get_mut(&source) else {
+            return Some(message);
+        };
+        if pending.held.len() < MAX_HELD_EVENTS {
+            pending.held.push(message);
+        } else {
+            warn!(
+                "Dropping message from {}, which has not completed its handshake",
+                source
+            );
+        }
+        None
+    }
+
+    /// Disconnects peers that connected more than `HANDSHAKE_TIMEOUT` ago without sending
+    /// a Hello, dropping what they sent. They handshake afresh if they connect again.
+    fn expire_pending_handshakes(&mut self) {
+        let now = self.clock.now();
+        let expired: Vec<_> = self
+            .pending_handshakes
+            .iter()
+            .filter(|(_, pending)| {
+                now.saturating_duration_since(pending.since) >= HANDSHAKE_TIMEOUT
+            })
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+
+        for peer_id in expired {
+            self.pending_handshakes.remove(&peer_id);
+            self.peers.remove(&peer_id);
+            self.disconnected_peers.remove(&peer_id);
+            warn!(
+                "Peer {} sent no handshake within {:?}, disconnecting",
+                self.network_handle.peer_name(&peer_id),
+                HANDSHAKE_TIMEOUT
+            );
+            if let Err(e) = self.network_handle.disconnect_peer(peer_id) {
+                warn!("Failed to disconnect peer {}: {:?}", peer_id, e);
+            }
+        }
+    }
+
+    /// Gossip published under another chain's namespace, which can still reach us when the
+    /// two networks share a peer.
+    fn is_on_foreign_topic(&self, message: &NetworkEvent) -> bool {
+        let topic = match message {
+            NetworkEvent::Subscribed { topic, .. } => topic,
+            NetworkEvent::GossipsubMessage(message) => &message.topic,
+            _ => return false,
+        };
+        !chain_topics(&self.config.chain_id)
+            .iter()
+            .any(|chain_topic| chain_topic.hash() == *topic)
+    }
+
+    fn expire_disconnected_peers(&mut self) {
+        let grace = Duration::from_secs(self.config.peer_disconnect_grace_seconds);
+        let now = self.clock.now();
+        let expired: Vec<_> = self
+            .disconnected_peers
+            .iter()
+            .filter(|(_, since)| now.saturating_duration_since(**since) >= grace)
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+
+        for peer_id in expired {
+            self.disconnected_peers.remove(&peer_id);
+            self.peers.remove(&peer_id);
+            self.pending_handshakes.remove(&peer_id);
+            warn!(
+                "Peer {} did not reconnect within grace period, removing",
+                peer_id
+            );
+        }
+    }
 }
@@ -1,11 +1,21 @@
-use abci::{ChainInterfaceImpl, db::rocksdb::RocksDb, executor::TransactionExecutorImpl};
+use abci::{
+    ChainInterface, ChainInterfaceImpl, db::rocksdb::RocksDb, executor::TransactionExecutorImpl,
+};
 use consensus::{ConsensusInterface, ConsensusInterfaceImpl, ConsensusMessage};
-use oracle::{esplora::EsploraOracle, mock::MockOracle, oracle::Oracle};
+use oracle::{
+    esplora::EsploraOracle, median::MedianFeeOracle, mock::MockOracle, oracle::Oracle,
+    rate_limit::RequestLimiter, timeout::TimeoutOracle,
+};
 use types::network::network_protocol::Network;
 use types::{errors::NodeError, intents::DepositIntent};
 
 use crate::{
-    NodeConfig, NodeState, key_manager::load_and_decrypt_keypair, swarm_manager::build_swarm,
+    NodeConfig, NodeState,
+    config::{LogFormat, LoggingConfig},
+    handlers::deposit::create_deposit::DEPOSIT_INTENT_CHANNEL_CAPACITY,
+    key_manager::load_and_decrypt_keypair,
+    swarm_manager::build_swarm,
+    utils::tick_schedule::TickSchedule,
     wallet::TaprootWallet,
 };
 use actix_web::{App, HttpResponse, HttpServer, web};
@@ -14,6 +24,7 @@ use grpc::grpc_handler::NodeControlService;
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::{signal, sync::broadcast};
 use tonic::transport::Server;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
@@ -21,64 +32,99 @@ use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 
 type PrometheusHandler = Arc<PrometheusHandle>;
 
+/// Installs the global subscriber: console output, plus daily rotated files in `log_dir`
+/// when one is given, filtered by the configured levels. `RUST_LOG` still takes precedence
+/// when set, for ad hoc debugging.
+fn init_logging(logging: &LoggingConfig, log_dir: Option<&Path>) -> Result<(), NodeError> {
+    let env_filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => logging.env_filter()?,
+    };
+    let json = logging.format == LogFormat::Json;
+
+    let file_appender = match log_dir {
+        Some(log_dir) => {
+            if !log_dir.exists() {
+                std::fs::create_dir_all(log_dir).map_err(|e| {
+                    NodeError::Error(format!(
+                        "Failed to create log directory {}: {e}",
+                        log_dir.display()
+                    ))
+                })?;
+            }
+            Some(RollingFileAppender::new(
+                Rotation::DAILY,
+                log_dir,
+                "node.log",
+            ))
+        }
+        None => None,
+    };
+
+    let (file_pretty, file_json) = match file_appender {
+        Some(appender) if json => (
+            None,
+            Some(
+                fmt::layer()
+                    .json()
+                    .with_writer(appender)
+                    .with_thread_ids(true)
+                    .with_thread_names(true),
+            ),
+        ),
+        Some(appender) => (
+            Some(
+                fmt::layer()
+                    .with_writer(appender)
+                    .with_ansi(false)
+                    .with_target(true)
+                    .with_thread_ids(true)
+                    .with_thread_names(true),
+            ),
+            None,
+        ),
+        None => (None, None),
+    };
+    let console_pretty = (!json).then(|| {
+        fmt::layer()
+            .with_writer(std::io::stdout)
+            .with_ansi(true)
+            .with_target(false)
+    });
+    let console_json = json.then(|| fmt::layer().json().with_writer(std::io::stdout));
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(file_pretty)
+        .with(file_json)
+        .with(console_pretty)
+        .with(console_json)
+        .init();
+    Ok(())
+}
+
 pub async fn start_node(
     config: NodeConfig,
     grpc_port: Option<u16>,
     log_file: Option<PathBuf>,
     use_mock_oracle: Option<bool>,
 ) -> Result<(), NodeError> {
-    // Initialize logging
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-
     let config_database_path = config.database_directory.clone();
     let config_grpc_port = config.grpc_port;
     let confirmation_depth = config.confirmation_depth;
     let monitor_start_block = config.monitor_start_block;
-
-    let registry = tracing_subscriber::registry().with(env_filter);
-
-    if let Some(log_path) = config.log_file_path.clone().or(log_file) {
-        let log_dir = Path::new(&log_path);
-
-        if !log_dir.exists() {
-            tracing::info!("Creating log directory: {:?}", log_dir);
-            if let Err(e) = std::fs::create_dir_all(log_dir) {
-                tracing::error!(
-                    "Failed to create log directory {}: {}",
-                    log_dir.display(),
-                    e
-                );
-                return Err(NodeError::Error(e.to_string()));
-            }
-        }
-
-        let file_appender = RollingFileAppender::new(Rotation::DAILY, log_dir, "node.log");
-
-        let file_layer = fmt::layer()
-            .with_writer(file_appender)
-            .with_ansi(false)
-            .with_target(true)
-            .with_thread_ids(true)
-            .with_thread_names(true);
-
-        let console_layer = fmt::layer()
-            .with_writer(std::io::stdout)
-            .with_ansi(true)
-            .with_target(false);
-
-        registry.with(file_layer).with(console_layer).init();
-        tracing::info!(
+    let oracle_timeout = Duration::from_millis(config.oracle_timeout_ms);
+    let oracle_limiter = RequestLimiter::new(config.oracle_max_concurrent_requests);
+    let fee_oracle_urls = config.fee_oracle_urls.clone();
+
+    let log_path = config.log_file_path.clone().or(log_file);
+    init_logging(&config.logging, log_path.as_deref())?;
+    match &log_path {
+        Some(log_path) => tracing::info!(
             "Logging initialized with file output: {}",
             log_path.display()
-        );
-    } else {
-        let console_layer = fmt::layer()
-            .with_writer(std::io::stdout)
-            .with_ansi(true)
-            .with_target(false);
-
-        registry.with(console_layer).init();
-        tracing::info!("Logging initialized with console output only");
+        ),
+        None => tracing::info!("Logging initialized with console output only"),
     }
 
     let prometheus_handle: Arc<PrometheusHandle> = {
@@ -128,38 +174,63 @@ pub async fn start_node(
 
     let allowed_peers = config.allowed_peers.clone();
 
-    let (network_handle, mut swarm) = build_swarm(
+    let (network_handle, swarm) = build_swarm(
         keypair.clone(),
         config.libp2p_udp_port,
         config.libp2p_tcp_port,
         &allowed_peers,
+        &config.chain_id,
     )
     .expect("Failed to build swarm");
+    let mut swarm = swarm.with_direct_message_retry_budget(config.direct_message_retry_budget);
 
-    let (deposit_intent_tx, _) = broadcast::channel::<DepositIntent>(100);
+    let (deposit_intent_tx, _) =
+        broadcast::channel::<DepositIntent>(DEPOSIT_INTENT_CHANNEL_CAPACITY);
     let is_testnet = dotenvy::var("IS_TESTNET")
         .unwrap_or_else(|_| "false".to_string())
         .parse()
         .unwrap();
 
+    let bitcoin_network = if is_testnet {
+        BitcoinNetwork::Testnet
+    } else {
+        BitcoinNetwork::Bitcoin
+    };
+
     let oracle: Box<dyn Oracle> = if use_mock_oracle.unwrap_or(false) {
         Box::new(MockOracle::new(
             swarm.network_events.clone(),
             Some(deposit_intent_tx.clone()),
         ))
     } else {
-        Box::new(EsploraOracle::new(
-            if is_testnet {
-                BitcoinNetwork::Testnet
-            } else {
-                BitcoinNetwork::Bitcoin
-            },
-            Some(100),
-            Some(swarm.network_events.clone()),
-            Some(deposit_intent_tx.clone()),
-            confirmation_depth,
-            monitor_start_block,
-        ))
+        Box::new(
+            EsploraOracle::new(
+                bitcoin_network,
+                Some(100),
+                Some(swarm.network_events.clone()),
+                Some(deposit_intent_tx.clone()),
+                confirmation_depth,
+                monitor_start_block,
+            )
+            .with_request_limiter(oracle_limiter.clone()),
+        )
+    };
+    let oracle: Box<dyn Oracle> = Box::new(TimeoutOracle::new(oracle, oracle_timeout));
+    let oracle: Box<dyn Oracle> = if fee_oracle_urls.is_empty() {
+        oracle
+    } else {
+        // Every backend carries its own timeout, so one slow source cannot hold up the median
+        let mut fee_backends = vec![oracle.clone()];
+        for url in &fee_oracle_urls {
+            fee_backends.push(Box::new(TimeoutOracle::new(
+                Box::new(
+                    EsploraOracle::with_url(bitcoin_network, url)?
+                        .with_request_limiter(oracle_limiter.clone()),
+                ),
+                oracle_timeout,
+            )));
+        }
+        Box::new(MedianFeeOracle::new(oracle, fee_backends))
     };
 
     let db = RocksDb::new(config_database_path.to_str().unwrap());
@@ -170,6 +241,15 @@ pub async fn start_node(
         Box::new(db.clone()),
         Box::new(TransactionExecutorImpl::new(oracle.clone())),
     );
+    chain_interface.sync(config.sync_mode).await?;
+
+    // Once DKG has produced a genesis, its quorum is what every validator agreed on, so a local
+    // config edit afterwards must not change how many votes this node waits for.
+    let consensus_quorum = chain_interface
+        .get_genesis()?
+        .map_or(config.consensus_quorum, |genesis| {
+            genesis.initial_state.chain_config.consensus_quorum
+        });
 
     let chain_interface_handle = tokio::spawn(async move {
         chain_interface.start().await;
@@ -185,6 +265,9 @@ pub async fn start_node(
     // Set max validators (self + allowed peers)
     let max_validators = allowed_peers.len() + 1;
     consensus_interface.set_max_validators(max_validators);
+    consensus_interface.set_consensus_quorum(consensus_quorum);
+    consensus_interface.set_keypair(keypair.clone());
+    consensus_interface.set_vote_aggregation(config.consensus_vote_aggregation);
 
     // Add validators from config
     for peer in &allowed_peers {
@@ -218,29 +301,17 @@ pub async fn start_node(
         oracle_clone.poll_new_transactions(vec![]).await;
     });
 
+    let wallet =
+        TaprootWallet::new_with_db(oracle.clone(), Vec::new(), bitcoin_network, db_arc.clone())
+            .with_max_utxos_per_address(config.max_utxos_per_address);
+
     let mut node_state = NodeState::new_from_config(
         &network_handle,
         config,
         &swarm.network_events,
         deposit_intent_tx,
         oracle.clone(),
-        TaprootWallet::new_with_db(
-            oracle.clone(),
-            Vec::new(),
-            {
-                let is_testnet: bool = std::env::var("IS_TESTNET")
-                    .unwrap_or_else(|_| String::from("false"))
-                    .parse()
-                    .unwrap_or(false);
-
-                if is_testnet {
-                    BitcoinNetwork::Testnet
-                } else {
-                    BitcoinNetwork::Bitcoin
-                }
-            },
-            db_arc.clone(),
-        ),
+        wallet,
         chain_message_tx,
         consensus_message_tx,
     )
@@ -248,17 +319,20 @@ pub async fn start_node(
     .expect("Failed to create node");
 
     let network_handle = node_state.network_handle.clone();
+    let deposit_event_tx = node_state.deposit_event_tx.clone();
 
     let swarm_handle = tokio::spawn(async move {
         swarm.start().await;
     });
 
+    let admin_token = node_state.config.admin_token.clone();
     let grpc_handle = tokio::spawn(async move {
         let addr = format!("0.0.0.0:{}", grpc_port.unwrap_or(config_grpc_port))
             .parse()
             .unwrap();
 
-        let node_control_service = NodeControlService::new(network_handle);
+        let node_control_service =
+            NodeControlService::new(network_handle, deposit_event_tx).with_admin_token(admin_token);
 
         tracing::info!("gRPC server listening on {}", addr);
 
@@ -269,6 +343,23 @@ pub async fn start_node(
             .expect("gRPC server failed");
     });
 
+    let tick_network_handle = node_state.network_handle.clone();
+    let mut tick_schedule = TickSchedule::new(
+        Duration::from_secs(node_state.config.consensus_tick_interval_seconds),
+        Duration::from_secs(node_state.config.maintenance_tick_interval_seconds),
+        tokio::time::Instant::now(),
+    );
+    let tick_handle = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep_until(tick_schedule.next_deadline()).await;
+            for tick in tick_schedule.due(tokio::time::Instant::now()) {
+                if let Err(e) = tick_network_handle.send_self_request(tick, false) {
+                    tracing::error!("Failed to send tick: {:?}", e);
+                }
+            }
+        }
+    });
+
     let main_loop_handle = tokio::spawn(async move { node_state.start().await });
 
     // Create shutdown signal handler for Docker compatibility
@@ -315,6 +406,9 @@ pub async fn start_node(
         _ = main_loop_handle => {
             tracing::info!("Main loop stopped");
         }
+        _ = tick_handle => {
+            tracing::info!("Tick task stopped");
+        }
         result = deposit_monitor_handle => {
             match result {
                 Ok(()) => tracing::info!("Deposit monitor stopped"),
@@ -1,6 +1,9 @@
-use crate::{NodeConfig, config::EncryptionParams};
+use crate::{
+    NodeConfig,
+    config::{Argon2Params, EncryptionParams, KeyData},
+};
 use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce, aead::Aead};
-use argon2::{Argon2, password_hash::SaltString};
+use argon2::password_hash::SaltString;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use bip39::{Language, Mnemonic};
 use bitcoin::bip32::{DerivationPath, Xpriv};
@@ -8,12 +11,17 @@ use bitcoin::key::Secp256k1;
 use bitcoin::{Address, CompressedPublicKey, Network, PrivateKey};
 use frost::rand_core::RngCore;
 use frost_secp256k1 as frost;
-use libp2p::identity::Keypair;
+use libp2p::PeerId;
+use libp2p::identity::{Keypair, PublicKey};
 use std::str::FromStr;
 use types::errors::NodeError;
 
-pub fn derive_key_from_password(password: &str, salt_str: &str) -> Result<Vec<u8>, NodeError> {
-    let argon2 = Argon2::default();
+pub fn derive_key_from_password(
+    password: &str,
+    salt_str: &str,
+    argon2_params: &Argon2Params,
+) -> Result<Vec<u8>, NodeError> {
+    let argon2 = argon2_params.hasher()?;
     let password_bytes = password.as_bytes();
     let salt = SaltString::from_b64(salt_str)
         .map_err(|e| NodeError::Error(format!("Salt decoding failed: {e}")))?;
@@ -29,8 +37,9 @@ pub fn encrypt_private_key(
     private_key_data: &[u8],
     password: &str,
     salt_b64: &str,
+    argon2_params: &Argon2Params,
 ) -> Result<(String, String), NodeError> {
-    let key_bytes = derive_key_from_password(password, salt_b64)?;
+    let key_bytes = derive_key_from_password(password, salt_b64, argon2_params)?;
 
     // Generate random IV
     let mut iv = [0u8; 12];
@@ -53,7 +62,7 @@ pub fn decrypt_private_key(
     password: &str,
     params: &EncryptionParams,
 ) -> Result<Vec<u8>, NodeError> {
-    let key_bytes = derive_key_from_password(password, &params.salt_b64)?;
+    let key_bytes = derive_key_from_password(password, &params.salt_b64, &params.argon2)?;
 
     let iv_bytes = BASE64
         .decode(&params.iv_b64)
@@ -84,14 +93,44 @@ pub fn load_and_decrypt_keypair(config_data: &NodeConfig) -> Result<Keypair, Nod
         Err(_) => get_password_from_prompt()?,
     };
 
+    decrypt_keypair(&config_data.key_data, &password)
+}
+
+/// Decrypts the identity keypair and checks it belongs to the peer id recorded in `key_data`,
+/// so a wrong password or corrupted key file cannot start the node under another identity.
+pub fn decrypt_keypair(key_data: &KeyData, password: &str) -> Result<Keypair, NodeError> {
     let private_key_protobuf = decrypt_private_key(
-        &config_data.key_data.encrypted_private_key_b64,
-        &password,
-        &config_data.key_data.encryption_params,
+        &key_data.encrypted_private_key_b64,
+        password,
+        &key_data.encryption_params,
     )?;
 
-    Keypair::from_protobuf_encoding(&private_key_protobuf)
-        .map_err(|e| NodeError::Error(format!("Failed to reconstruct keypair from protobuf: {e}")))
+    let keypair = Keypair::from_protobuf_encoding(&private_key_protobuf).map_err(|e| {
+        NodeError::Error(format!("Failed to reconstruct keypair from protobuf: {e}"))
+    })?;
+
+    if !records_public_key(&key_data.public_key_b58, &keypair.public()) {
+        return Err(NodeError::Error(format!(
+            "Decrypted keypair belongs to {}, but the key file records {}",
+            keypair.public().to_peer_id(),
+            key_data.public_key_b58
+        )));
+    }
+
+    Ok(keypair)
+}
+
+/// Whether `public_key_b58` names `public_key`. Key files written by the node record the
+/// base58 peer id; those written by the CLI's key generator record the base58 of the
+/// protobuf-encoded public key.
+fn records_public_key(public_key_b58: &str, public_key: &PublicKey) -> bool {
+    let Ok(bytes) = bs58::decode(public_key_b58).into_vec() else {
+        return false;
+    };
+    if let Ok(recorded) = PublicKey::try_decode_protobuf(&bytes) {
+        return recorded == *public_key;
+    }
+    PeerId::from_bytes(&bytes).is_ok_and(|peer_id| peer_id == public_key.to_peer_id())
 }
 
 #[must_use]
@@ -1,2 +1,5 @@
 pub mod key_manager;
+pub mod retry_queue;
 pub mod swarm_manager;
+pub mod threshold_scheme;
+pub mod tick_schedule;
@@ -0,0 +1,113 @@
+use libp2p::PeerId;
+use std::collections::{HashMap, VecDeque};
+
+use types::network::network_event::DirectMessage;
+
+/// Messages held for a single peer; beyond this the oldest one is dead-lettered.
+pub const MAX_QUEUED_PER_PEER: usize = 64;
+/// Dead letters kept for inspection; older ones are discarded.
+pub const MAX_DEAD_LETTERS: usize = 256;
+
+/// A message waiting for its peer to become reachable again.
+#[derive(Clone, Debug)]
+pub struct PendingDelivery {
+    pub message: DirectMessage,
+    /// Delivery attempts made so far.
+    pub attempts: u32,
+}
+
+/// A message given up on after its delivery attempts ran out.
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    pub peer_id: PeerId,
+    pub message: DirectMessage,
+    pub attempts: u32,
+}
+
+/// Direct messages waiting for their peer to become reachable again. A message that runs out
+/// of delivery attempts is dead-lettered and logged rather than retried forever.
+#[derive(Debug)]
+pub struct DirectMessageRetryQueue {
+    max_attempts: u32,
+    queued: HashMap<PeerId, VecDeque<PendingDelivery>>,
+    dead_letters: VecDeque<DeadLetter>,
+}
+
+impl DirectMessageRetryQueue {
+    /// A queue allowing each message `max_attempts` delivery attempts, the first included.
+    #[must_use]
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            queued: HashMap::new(),
+            dead_letters: VecDeque::new(),
+        }
+    }
+
+    /// Records that the `attempts`-th delivery of `message` to `peer_id` failed. The message is
+    /// queued for redelivery while attempts remain and dead-lettered otherwise; returns whether
+    /// it was queued.
+    pub fn record_failure(
+        &mut self,
+        peer_id: PeerId,
+        message: DirectMessage,
+        attempts: u32,
+    ) -> bool {
+        if attempts >= self.max_attempts {
+            self.dead_letter(peer_id, message, attempts);
+            return false;
+        }
+
+        let queue = self.queued.entry(peer_id).or_default();
+        queue.push_back(PendingDelivery { message, attempts });
+        if queue.len() > MAX_QUEUED_PER_PEER {
+            if let Some(oldest) = queue.pop_front() {
+                self.dead_letter(peer_id, oldest.message, oldest.attempts);
+            }
+        }
+        true
+    }
+
+    /// Removes and returns every message queued for `peer_id`, oldest first, for the caller to
+    /// redeliver now that the peer is reachable.
+    pub fn take(&mut self, peer_id: &PeerId) -> Vec<PendingDelivery> {
+        self.queued
+            .remove(peer_id)
+            .map(Vec::from)
+            .unwrap_or_default()
+    }
+
+    /// Peers with at least one message waiting.
+    #[must_use]
+    pub fn queued_peers(&self) -> Vec<PeerId> {
+        self.queued.keys().copied().collect()
+    }
+
+    #[must_use]
+    pub fn queued_len(&self, peer_id: &PeerId) -> usize {
+        self.queued.get(peer_id).map_or(0, VecDeque::len)
+    }
+
+    /// The most recent dead letters, oldest first.
+    pub fn dead_letters(&self) -> impl Iterator<Item = &DeadLetter> {
+        self.dead_letters.iter()
+    }
+
+    /// Gives up on `message` without further attempts, logging it and keeping it for inspection.
+    pub fn dead_letter(&mut self, peer_id: PeerId, message: DirectMessage, attempts: u32) {
+        tracing::warn!(
+            peer = %peer_id,
+            kind = message.kind(),
+            attempts,
+            "Dead-lettering undeliverable direct message"
+        );
+        if self.dead_letters.len() == MAX_DEAD_LETTERS {
+            self.dead_letters.pop_front();
+        }
+        self.dead_letters.push_back(DeadLetter {
+            peer_id,
+            message,
+            attempts,
+        });
+    }
+}
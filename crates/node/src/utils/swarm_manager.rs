@@ -1,16 +1,16 @@
 use futures::StreamExt;
 use libp2p::{
     PeerId,
-    request_response::{Event, Message},
+    request_response::{Event, Message, OutboundFailure, OutboundRequestId},
     swarm::SwarmEvent,
 };
 use std::{
-    collections::{BTreeMap, HashSet, hash_map::DefaultHasher},
+    collections::{BTreeMap, HashMap, HashSet, hash_map::DefaultHasher},
     fmt::Debug,
     hash::{Hash, Hasher},
     time::Duration,
 };
-use tracing::info;
+use tracing::{debug, info, warn};
 
 // Include the generated P2P proto code
 
@@ -30,11 +30,16 @@ use tokio::{
     },
 };
 
-use crate::PeerData;
+use crate::{
+    PeerData, config::DEFAULT_DIRECT_MESSAGE_RETRY_BUDGET,
+    utils::retry_queue::DirectMessageRetryQueue,
+};
 use types::{
     broadcast_received_metrics, broadcast_sent_metrics,
     errors::{NetworkError, NodeError},
-    network::network_protocol::{NetworkHandle, NetworkMessage, NetworkResponseFuture},
+    network::network_protocol::{
+        NetworkHandle, NetworkMessage, NetworkResponseFuture, chain_topics,
+    },
     proto::p2p_proto,
 };
 use types::{
@@ -42,6 +47,10 @@ use types::{
     proto::{ProtoDecode, ProtoEncode},
 };
 
+/// How often queued direct messages are retried to peers that are still connected, in case
+/// the last attempt timed out rather than finding the peer gone.
+const DIRECT_MESSAGE_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum ConsensusMessage {
     Propose(Transaction),
@@ -154,40 +163,109 @@ impl Network for NetworkHandle {
     }
 }
 
+/// Admits inbound network traffic only from peers listed in `allowed_peers`.
+#[derive(Debug, Clone, Default)]
+pub struct PeerGate {
+    allowed: HashSet<PeerId>,
+}
+
+impl PeerGate {
+    pub fn new(allowed: impl IntoIterator<Item = PeerId>) -> Self {
+        Self {
+            allowed: allowed.into_iter().collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_allowed(&self, peer_id: &PeerId) -> bool {
+        self.allowed.contains(peer_id)
+    }
+
+    /// Returns whether an inbound event originates from an allowed peer. Gossip without a
+    /// known source is rejected; events not tied to a remote peer are always admitted.
+    #[must_use]
+    pub fn admits(&self, event: &NetworkEvent) -> bool {
+        match event {
+            NetworkEvent::GossipsubMessage(message) => message
+                .source
+                .is_some_and(|source| self.is_allowed(&source)),
+            NetworkEvent::MessageEvent((peer_id, _)) | NetworkEvent::Subscribed { peer_id, .. } => {
+                self.is_allowed(peer_id)
+            }
+            _ => true,
+        }
+    }
+
+    /// Decides what to do with an event delivered by `via`. Gossip is relayed, so an unlisted
+    /// or unknown author is no reason to drop the allowed peer that forwarded it.
+    #[must_use]
+    pub fn check(&self, via: &PeerId, event: &NetworkEvent) -> GateDecision {
+        if !self.is_allowed(via) {
+            GateDecision::RejectRelay
+        } else if self.admits(event) {
+            GateDecision::Admit
+        } else if let NetworkEvent::GossipsubMessage(message) = event {
+            GateDecision::DropFromAuthor(message.source)
+        } else {
+            GateDecision::RejectRelay
+        }
+    }
+}
+
+/// Outcome of [`PeerGate::check`] for an inbound event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateDecision {
+    Admit,
+    /// The delivering peer itself is not allowed and gets disconnected.
+    RejectRelay,
+    /// An allowed peer relayed gossip from an unlisted or anonymous author; only the author,
+    /// when known, is penalised.
+    DropFromAuthor(Option<PeerId>),
+}
+
 pub struct SwarmManager {
     pub inner: Swarm<MyBehaviour>,
 
     pub network_manager_rx: mpsc::UnboundedReceiver<NetworkMessage>,
     pub network_events: broadcast::Sender<NetworkEvent>,
 
-    pub allowed_peers: Vec<PeerId>,
+    pub peer_gate: PeerGate,
     pub peers_to_names: BTreeMap<PeerId, String>,
 
     pub live_peers: HashSet<PeerId>,
 
-    pub broadcast_topic: gossipsub::IdentTopic,
+    pub chain_topics: Vec<gossipsub::IdentTopic>,
+
+    /// Direct messages awaiting the outcome of a delivery attempt, with that attempt's number.
+    in_flight: HashMap<OutboundRequestId, (PeerId, DirectMessage, u32)>,
+    /// Direct messages whose delivery failed, held until their peer is reachable again.
+    pub retry_queue: DirectMessageRetryQueue,
 }
 
 impl SwarmManager {
     pub fn new(
         mut swarm: Swarm<MyBehaviour>,
         peer_data: &[PeerData],
+        chain_id: &str,
     ) -> Result<(Self, NetworkHandle), NodeError> {
         let (send_commands, receiving_commands) = unbounded_channel::<NetworkMessage>();
 
         let (network_events_emitter, _) = broadcast::channel::<NetworkEvent>(10000);
 
-        let broadcast_topic = gossipsub::IdentTopic::new("broadcast");
-        swarm
-            .behaviour_mut()
-            .gossipsub
-            .subscribe(&broadcast_topic)
-            .map_err(|e| NodeError::Error(e.to_string()))?;
+        let chain_topics = chain_topics(chain_id);
+        for topic in &chain_topics {
+            swarm
+                .behaviour_mut()
+                .gossipsub
+                .subscribe(topic)
+                .map_err(|e| NodeError::Error(e.to_string()))?;
+        }
 
-        let allowed_peers: Vec<PeerId> = peer_data
-            .iter()
-            .map(|peer| peer.public_key.parse().unwrap())
-            .collect();
+        let peer_gate = PeerGate::new(
+            peer_data
+                .iter()
+                .map(|peer| peer.public_key.parse().unwrap()),
+        );
 
         let peers_to_names: BTreeMap<PeerId, String> = peer_data
             .iter()
@@ -198,32 +276,126 @@ impl SwarmManager {
             peer_id: *swarm.local_peer_id(),
             tx: send_commands,
             peers_to_names: peers_to_names.clone(),
+            chain_id: chain_id.to_string(),
         };
 
         Ok((
             Self {
-                broadcast_topic,
+                chain_topics,
                 inner: swarm,
                 network_manager_rx: receiving_commands,
                 network_events: network_events_emitter,
-                allowed_peers,
+                peer_gate,
                 peers_to_names,
                 live_peers: HashSet::new(),
+                in_flight: HashMap::new(),
+                retry_queue: DirectMessageRetryQueue::new(DEFAULT_DIRECT_MESSAGE_RETRY_BUDGET),
             },
             network_handle,
         ))
     }
 
+    /// Sets the delivery attempts each direct message gets before it is dead-lettered.
+    #[must_use]
+    pub fn with_direct_message_retry_budget(mut self, max_attempts: u32) -> Self {
+        self.retry_queue = DirectMessageRetryQueue::new(max_attempts);
+        self
+    }
+
     pub fn peer_name(&self, peer_id: &PeerId) -> String {
         self.peers_to_names
             .get(peer_id)
             .map_or_else(|| peer_id.to_string(), Clone::clone)
     }
 
+    fn send_direct_message(&mut self, peer_id: PeerId, message: DirectMessage, attempt: u32) {
+        let request_id = self
+            .inner
+            .behaviour_mut()
+            .request_response
+            .send_request(&peer_id, message.clone());
+        self.in_flight
+            .insert(request_id, (peer_id, message, attempt));
+    }
+
+    fn handle_outbound_failure(&mut self, request_id: OutboundRequestId, error: &OutboundFailure) {
+        let Some((peer_id, message, attempts)) = self.in_flight.remove(&request_id) else {
+            return;
+        };
+        if matches!(error, OutboundFailure::UnsupportedProtocols) {
+            self.retry_queue.dead_letter(peer_id, message, attempts);
+            return;
+        }
+        if self.retry_queue.record_failure(peer_id, message, attempts) {
+            debug!(
+                "Direct message to {} failed ({}), queued for redelivery",
+                self.peer_name(&peer_id),
+                error
+            );
+        }
+    }
+
+    /// Resends every direct message queued for `peer_id`.
+    fn redeliver(&mut self, peer_id: PeerId) {
+        let pending = self.retry_queue.take(&peer_id);
+        if pending.is_empty() {
+            return;
+        }
+        info!(
+            "Redelivering {} direct message(s) to {}",
+            pending.len(),
+            self.peer_name(&peer_id)
+        );
+        for delivery in pending {
+            self.send_direct_message(peer_id, delivery.message, delivery.attempts + 1);
+        }
+    }
+
+    /// Forwards an inbound event to the node, dropping it if the peer behind it is not allowed.
+    pub fn emit_from_peer(&mut self, via: PeerId, event: NetworkEvent) {
+        match self.peer_gate.check(&via, &event) {
+            GateDecision::Admit => {
+                self.network_events.send(event).unwrap();
+            }
+            GateDecision::RejectRelay => {
+                warn!("🚫 Dropping message from non-allowed peer {}", via);
+                self.reject_peer(via);
+            }
+            GateDecision::DropFromAuthor(author) => {
+                warn!(
+                    "🚫 Dropping gossip from non-allowed author {:?} relayed by {}",
+                    author,
+                    self.peer_name(&via)
+                );
+                if let Some(author) = author {
+                    self.reject_peer(author);
+                }
+            }
+        }
+    }
+
+    fn reject_peer(&mut self, peer_id: PeerId) {
+        self.inner
+            .behaviour_mut()
+            .gossipsub
+            .blacklist_peer(&peer_id);
+        if self.inner.disconnect_peer_id(peer_id).is_ok() {
+            warn!("🚫 Disconnected peer {} not in allowed_peers", peer_id);
+        }
+    }
+
     pub async fn start(&mut self) {
         info!("Starting swarm manager");
+        let mut retry_interval = tokio::time::interval(DIRECT_MESSAGE_RETRY_INTERVAL);
         loop {
             tokio::select! {
+                _ = retry_interval.tick() => {
+                    for peer_id in self.retry_queue.queued_peers() {
+                        if self.inner.is_connected(&peer_id) {
+                            self.redeliver(peer_id);
+                        }
+                    }
+                },
                 send_message = self.network_manager_rx.recv() => match send_message {
                     Some(NetworkMessage::SendBroadcast { topic, message }) => {
                         let _ = self.inner
@@ -232,10 +404,11 @@ impl SwarmManager {
                             .publish(topic, message);
                     }
                     Some(NetworkMessage::SendPrivateMessage(peer_id, request)) => {
-                        self.inner
-                            .behaviour_mut()
-                            .request_response
-                            .send_request(&peer_id, request);
+                        self.send_direct_message(peer_id, request, 1);
+                    }
+                    Some(NetworkMessage::DisconnectPeer(peer_id)) => {
+                        self.live_peers.remove(&peer_id);
+                        let _ = self.inner.disconnect_peer_id(peer_id);
                     }
                     Some(NetworkMessage::SendSelfRequest { request, response_channel }) => {
                         self.network_events.send(NetworkEvent::SelfRequest { request, response_channel } ).unwrap();
@@ -248,18 +421,22 @@ impl SwarmManager {
                         SwarmEvent::Behaviour(MyBehaviourEvent::Mdns(mdns::Event::Discovered(list))) => {
                             let mut peers_connected = vec![];
                             for (peer_id, multiaddr) in list {
-                                if self.allowed_peers.contains(&peer_id) {
+                                if self.peer_gate.is_allowed(&peer_id) {
                                     info!("Discovered peer: {}", self.peer_name(&peer_id));
                                     peers_connected.push((peer_id, multiaddr));
                                     self.live_peers.insert(peer_id);
                                     self.inner.behaviour_mut().gossipsub.add_explicit_peer(&peer_id);
                                 }
                             }
+                            let discovered: Vec<PeerId> = peers_connected.iter().map(|(peer_id, _)| *peer_id).collect();
                             self.network_events.send(NetworkEvent::PeersConnected(peers_connected)).unwrap();
+                            for peer_id in discovered {
+                                self.redeliver(peer_id);
+                            }
                         },
                         SwarmEvent::Behaviour(MyBehaviourEvent::Mdns(mdns::Event::Expired(list))) => {
                             for (peer_id, _multiaddr) in list.clone() {
-                                if self.allowed_peers.contains(&peer_id) {
+                                if self.peer_gate.is_allowed(&peer_id) {
                                     info!("Peer expired: {}", self.peer_name(&peer_id));
                                     self.live_peers.retain(|p| p != &peer_id);
                                     self.inner.behaviour_mut().gossipsub.remove_explicit_peer(&peer_id);
@@ -267,24 +444,45 @@ impl SwarmManager {
                             }
                             self.network_events.send(NetworkEvent::PeersDisconnected(list)).unwrap();
                         },
+                        SwarmEvent::ConnectionEstablished { peer_id, .. } if !self.peer_gate.is_allowed(&peer_id) => {
+                            warn!("🚫 Connection from peer {} not in allowed_peers", peer_id);
+                            self.reject_peer(peer_id);
+                        },
+                        SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                            self.redeliver(peer_id);
+                        },
                         SwarmEvent::Behaviour(MyBehaviourEvent::Gossipsub(gossipsub::Event::Message {
+                            propagation_source,
                             message,
                             ..
                         })) => {
                             if let Ok(broadcast_msg) = types::broadcast::BroadcastMessage::decode(&message.data) {
                                 broadcast_received_metrics!(get_broadcast_message_type(&broadcast_msg));
                             }
-                            self.network_events.send(NetworkEvent::GossipsubMessage(message.clone())).unwrap();
+                            self.emit_from_peer(propagation_source, NetworkEvent::GossipsubMessage(message));
                         },
                         SwarmEvent::Behaviour(MyBehaviourEvent::RequestResponse(Event::Message {
                             peer,
                             message: Message::Request { request, .. },
                             ..
                         }) ) => {
-                            self.network_events.send(NetworkEvent::MessageEvent((peer, request))).unwrap();
+                            self.emit_from_peer(peer, NetworkEvent::MessageEvent((peer, request)));
+                        },
+                        SwarmEvent::Behaviour(MyBehaviourEvent::RequestResponse(Event::Message {
+                            message: Message::Response { request_id, .. },
+                            ..
+                        })) => {
+                            self.in_flight.remove(&request_id);
+                        },
+                        SwarmEvent::Behaviour(MyBehaviourEvent::RequestResponse(Event::OutboundFailure {
+                            request_id,
+                            error,
+                            ..
+                        })) => {
+                            self.handle_outbound_failure(request_id, &error);
                         },
                         SwarmEvent::Behaviour(MyBehaviourEvent::Gossipsub(gossipsub::Event::Subscribed { peer_id, topic })) => {
-                            self.network_events.send(NetworkEvent::Subscribed { peer_id, topic }).unwrap();
+                            self.emit_from_peer(peer_id, NetworkEvent::Subscribed { peer_id, topic });
                         },
                         _ => {
                             // self.network_events.send(NetworkEvent::SwarmEvent(event)).unwrap();
@@ -302,6 +500,7 @@ pub fn build_swarm(
     libp2p_udp_port: u16,
     libp2p_tcp_port: u16,
     peer_data: &[PeerData],
+    chain_id: &str,
 ) -> Result<(NetworkHandle, SwarmManager), NodeError> {
     let mut swarm = libp2p::SwarmBuilder::with_existing_identity(keypair)
         .with_tokio()
@@ -387,7 +586,7 @@ pub fn build_swarm(
         )
         .map_err(|e| NodeError::Error(format!("Failed to listen on tcp {e}")))?;
 
-    let (swarm_manager, network) = SwarmManager::new(swarm, peer_data)
+    let (swarm_manager, network) = SwarmManager::new(swarm, peer_data, chain_id)
         .map_err(|e| NodeError::Error(format!("Failed to create swarm manager: {e}")))?;
 
     Ok((network, swarm_manager))
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use frost_secp256k1::{
+    self as frost,
+    keys::dkg::{round1, round2},
+    rand_core::{CryptoRng, RngCore},
+};
+
+/// The FROST operations the DKG and signing handlers run, kept behind one trait so another
+/// ciphersuite (e.g. Ed25519) can be plugged in without rewriting the handlers.
+///
+/// Each method mirrors the `frost-core` function of the same step; implementations must not
+/// add behavior of their own.
+pub trait ThresholdScheme {
+    type Identifier: Ord + Copy;
+    type Round1SecretPackage;
+    type Round1Package;
+    type Round2SecretPackage;
+    type Round2Package;
+    type KeyPackage;
+    type PublicKeyPackage;
+    type SigningNonces;
+    type SigningCommitments;
+    type SigningPackage;
+    type SignatureShare;
+    type Signature;
+    type Error: std::fmt::Display;
+
+    /// DKG part 1: this participant's secret and the package it broadcasts.
+    #[allow(clippy::type_complexity)]
+    fn dkg_part1<R: RngCore + CryptoRng>(
+        identifier: Self::Identifier,
+        max_signers: u16,
+        min_signers: u16,
+        rng: R,
+    ) -> Result<(Self::Round1SecretPackage, Self::Round1Package), Self::Error>;
+
+    /// DKG part 2: consumes every peer's round 1 package and returns one round 2 package
+    /// addressed to each of them.
+    #[allow(clippy::type_complexity)]
+    fn dkg_part2(
+        secret_package: Self::Round1SecretPackage,
+        round1_packages: &BTreeMap<Self::Identifier, Self::Round1Package>,
+    ) -> Result<
+        (
+            Self::Round2SecretPackage,
+            BTreeMap<Self::Identifier, Self::Round2Package>,
+        ),
+        Self::Error,
+    >;
+
+    /// DKG part 3: derives this participant's key package and the group public key.
+    fn dkg_part3(
+        secret_package: &Self::Round2SecretPackage,
+        round1_packages: &BTreeMap<Self::Identifier, Self::Round1Package>,
+        round2_packages: &BTreeMap<Self::Identifier, Self::Round2Package>,
+    ) -> Result<(Self::KeyPackage, Self::PublicKeyPackage), Self::Error>;
+
+    /// Signing round 1: fresh nonces and the commitments to share with the coordinator.
+    fn commit<R: RngCore + CryptoRng>(
+        key_package: &Self::KeyPackage,
+        rng: &mut R,
+    ) -> (Self::SigningNonces, Self::SigningCommitments);
+
+    /// Signing round 2: this participant's share of the signature over `signing_package`.
+    fn sign(
+        signing_package: &Self::SigningPackage,
+        nonces: &Self::SigningNonces,
+        key_package: &Self::KeyPackage,
+    ) -> Result<Self::SignatureShare, Self::Error>;
+
+    /// Combines the collected shares into the group signature.
+    fn aggregate(
+        signing_package: &Self::SigningPackage,
+        signature_shares: &BTreeMap<Self::Identifier, Self::SignatureShare>,
+        public_key_package: &Self::PublicKeyPackage,
+    ) -> Result<Self::Signature, Self::Error>;
+}
+
+/// FROST over secp256k1 with SHA-256, the scheme the group key and Taproot spends use.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Secp256k1Scheme;
+
+/// Scheme the node's DKG and signing handlers run.
+pub type DefaultScheme = Secp256k1Scheme;
+
+impl ThresholdScheme for Secp256k1Scheme {
+    type Identifier = frost::Identifier;
+    type Round1SecretPackage = round1::SecretPackage;
+    type Round1Package = round1::Package;
+    type Round2SecretPackage = round2::SecretPackage;
+    type Round2Package = round2::Package;
+    type KeyPackage = frost::keys::KeyPackage;
+    type PublicKeyPackage = frost::keys::PublicKeyPackage;
+    type SigningNonces = frost::round1::SigningNonces;
+    type SigningCommitments = frost::round1::SigningCommitments;
+    type SigningPackage = frost::SigningPackage;
+    type SignatureShare = frost::round2::SignatureShare;
+    type Signature = frost::Signature;
+    type Error = frost::Error;
+
+    fn dkg_part1<R: RngCore + CryptoRng>(
+        identifier: Self::Identifier,
+        max_signers: u16,
+        min_signers: u16,
+        rng: R,
+    ) -> Result<(Self::Round1SecretPackage, Self::Round1Package), Self::Error> {
+        frost::keys::dkg::part1(identifier, max_signers, min_signers, rng)
+    }
+
+    fn dkg_part2(
+        secret_package: Self::Round1SecretPackage,
+        round1_packages: &BTreeMap<Self::Identifier, Self::Round1Package>,
+    ) -> Result<
+        (
+            Self::Round2SecretPackage,
+            BTreeMap<Self::Identifier, Self::Round2Package>,
+        ),
+        Self::Error,
+    > {
+        frost::keys::dkg::part2(secret_package, round1_packages)
+    }
+
+    fn dkg_part3(
+        secret_package: &Self::Round2SecretPackage,
+        round1_packages: &BTreeMap<Self::Identifier, Self::Round1Package>,
+        round2_packages: &BTreeMap<Self::Identifier, Self::Round2Package>,
+    ) -> Result<(Self::KeyPackage, Self::PublicKeyPackage), Self::Error> {
+        frost::keys::dkg::part3(secret_package, round1_packages, round2_packages)
+    }
+
+    fn commit<R: RngCore + CryptoRng>(
+        key_package: &Self::KeyPackage,
+        rng: &mut R,
+    ) -> (Self::SigningNonces, Self::SigningCommitments) {
+        frost::round1::commit(key_package.signing_share(), rng)
+    }
+
+    fn sign(
+        signing_package: &Self::SigningPackage,
+        nonces: &Self::SigningNonces,
+        key_package: &Self::KeyPackage,
+    ) -> Result<Self::SignatureShare, Self::Error> {
+        frost::round2::sign(signing_package, nonces, key_package)
+    }
+
+    fn aggregate(
+        signing_package: &Self::SigningPackage,
+        signature_shares: &BTreeMap<Self::Identifier, Self::SignatureShare>,
+        public_key_package: &Self::PublicKeyPackage,
+    ) -> Result<Self::Signature, Self::Error> {
+        frost::aggregate(signing_package, signature_shares, public_key_package)
+    }
+}
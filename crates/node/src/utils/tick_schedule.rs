@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+use types::network::network_event::SelfRequest;
+
+/// Shortest interval either tick may run at; guards against a zero interval spinning the loop.
+const MIN_TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Decides when to send `ConsensusTick` and `MaintenanceTick`, so heavy background upkeep
+/// does not run at the consensus cadence.
+///
+/// Each tick kind runs on its own interval. A tick that is late by more than one interval
+/// still fires only once, and the next one is scheduled from `now`.
+#[derive(Debug, Clone)]
+pub struct TickSchedule {
+    pub consensus_interval: Duration,
+    pub maintenance_interval: Duration,
+    pub next_consensus: Instant,
+    pub next_maintenance: Instant,
+}
+
+impl TickSchedule {
+    #[must_use]
+    pub fn new(
+        consensus_interval: Duration,
+        maintenance_interval: Duration,
+        start: Instant,
+    ) -> Self {
+        let consensus_interval = consensus_interval.max(MIN_TICK_INTERVAL);
+        let maintenance_interval = maintenance_interval.max(MIN_TICK_INTERVAL);
+        Self {
+            consensus_interval,
+            maintenance_interval,
+            next_consensus: start + consensus_interval,
+            next_maintenance: start + maintenance_interval,
+        }
+    }
+
+    /// When the next tick of either kind is due.
+    #[must_use]
+    pub fn next_deadline(&self) -> Instant {
+        self.next_consensus.min(self.next_maintenance)
+    }
+
+    /// Returns the ticks due at `now` and schedules their next occurrence.
+    pub fn due(&mut self, now: Instant) -> Vec<SelfRequest> {
+        let mut ticks = Vec::new();
+        if now >= self.next_consensus {
+            ticks.push(SelfRequest::ConsensusTick);
+            self.next_consensus = advance(self.next_consensus, self.consensus_interval, now);
+        }
+        if now >= self.next_maintenance {
+            ticks.push(SelfRequest::MaintenanceTick);
+            self.next_maintenance = advance(self.next_maintenance, self.maintenance_interval, now);
+        }
+        ticks
+    }
+}
+
+fn advance(deadline: Instant, interval: Duration, now: Instant) -> Instant {
+    let next = deadline + interval;
+    if next > now { next } else { now + interval }
+}
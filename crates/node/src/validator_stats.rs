@@ -0,0 +1,51 @@
+use libp2p::PeerId;
+use std::collections::HashMap;
+use types::network::network_event::ValidatorStats;
+
+/// Validator participation observed by this node, accumulated since it started.
+///
+/// Activity is recorded as peers' gossip and direct messages arrive, so the local node's
+/// own proposals, votes and signing shares are not counted.
+#[derive(Debug, Default)]
+pub struct ValidatorStatsTracker {
+    stats: HashMap<PeerId, ValidatorStats>,
+}
+
+impl ValidatorStatsTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_proposal(&mut self, peer: PeerId) {
+        self.touch(peer).proposals += 1;
+    }
+
+    pub fn record_vote(&mut self, peer: PeerId) {
+        self.touch(peer).votes += 1;
+    }
+
+    pub fn record_signing_session(&mut self, peer: PeerId) {
+        self.touch(peer).signing_sessions += 1;
+    }
+
+    /// Stats of every validator seen so far, ordered by peer id.
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<ValidatorStats> {
+        let mut stats: Vec<_> = self.stats.values().cloned().collect();
+        stats.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+        stats
+    }
+
+    fn touch(&mut self, peer: PeerId) -> &mut ValidatorStats {
+        let entry = self.stats.entry(peer).or_insert_with(|| ValidatorStats {
+            peer_id: peer.to_string(),
+            ..ValidatorStats::default()
+        });
+        entry.last_seen = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        entry
+    }
+}
@@ -1,11 +1,87 @@
 // PendingSpend struct shared across node handlers
-use bitcoin::{Address, PublicKey, Transaction, secp256k1::Scalar};
+use bitcoin::{
+    Address, OutPoint, PublicKey, Transaction, TxOut, Txid, absolute::LockTime, secp256k1::Scalar,
+    transaction::Version,
+};
 use protocol::block::Block;
 use types::errors::NodeError;
 
 pub mod taproot;
 
-pub use taproot::{TaprootWallet, TrackedUtxo};
+pub use taproot::{SpendPath, TaprootWallet, TrackedUtxo, tweaked_p2tr_address};
+
+/// Transaction-level fields of a spend that callers may override.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendOptions {
+    pub version: Version,
+    /// Earliest block height or time the spend may be mined, e.g. for timelocked withdrawals.
+    pub lock_time: LockTime,
+    /// Feerate the spend targets, in sat/vB. When set, coin selection skips UTXOs whose
+    /// effective value at this rate is negative, and the fee is charged on the spend's
+    /// estimated weight at this rate, with the caller's fee estimate as a floor.
+    pub feerate_sat_vb: Option<u64>,
+}
+
+impl Default for SpendOptions {
+    fn default() -> Self {
+        Self {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            feerate_sat_vb: None,
+        }
+    }
+}
+
+impl SpendOptions {
+    #[must_use]
+    pub const fn with_version(mut self, version: Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_lock_time(mut self, lock_time: LockTime) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_feerate(mut self, feerate_sat_vb: u64) -> Self {
+        self.feerate_sat_vb = Some(feerate_sat_vb);
+        self
+    }
+}
+
+/// How a stuck wallet transaction can be pushed to a target feerate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeeBumpPlan {
+    /// Replace the transaction under BIP 125 with a version paying `new_fee` sat in total.
+    Rbf {
+        new_fee: u64,
+    },
+    /// Spend one of its wallet outputs in a child paying `child_fee` sat, lifting the
+    /// package to the target feerate.
+    Cpfp {
+        child_fee: u64,
+    },
+    Impossible {
+        reason: String,
+    },
+}
+
+/// What the wallet knows about an outpoint, for audits of where its funds went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UtxoStatus {
+    /// Tracked and available to coin selection.
+    Unspent,
+    /// Tracked but withheld from coin selection until unlocked.
+    Locked,
+    /// Consumed by the transaction `txid`, one of the wallet's own spends or a transaction
+    /// seen spending it.
+    Spent { txid: Txid },
+    /// Neither tracked nor known to have been spent.
+    Unknown,
+}
 
 #[async_trait::async_trait]
 pub trait Wallet: Send + Sync {
@@ -17,8 +93,42 @@ pub trait Wallet: Send + Sync {
         estimated_fee_sat: u64,
         recipient: &Address,
         dry_run: bool,
+    ) -> Result<(Transaction, [u8; 32]), NodeError> {
+        self.create_spend_with_options(
+            amount_sat,
+            estimated_fee_sat,
+            recipient,
+            dry_run,
+            SpendOptions::default(),
+        )
+    }
+
+    /// [`Self::create_spend`] with a caller-chosen transaction version and locktime.
+    fn create_spend_with_options(
+        &mut self,
+        amount_sat: u64,
+        estimated_fee_sat: u64,
+        recipient: &Address,
+        dry_run: bool,
+        options: SpendOptions,
+    ) -> Result<(Transaction, [u8; 32]), NodeError>;
+
+    /// Rebuilds `tx`, one of this wallet's own unconfirmed spends of `prevouts`, so it pays
+    /// `new_fee_sat` by shrinking its change. The replacement spends the same inputs and so
+    /// conflicts with the original under BIP 125.
+    fn bump_fee(
+        &mut self,
+        tx: &Transaction,
+        prevouts: &[TxOut],
+        new_fee_sat: u64,
     ) -> Result<(Transaction, [u8; 32]), NodeError>;
 
+    /// Suggests how to bring `txid`, a transaction spending from or paying to this wallet, up
+    /// to `target_feerate` sat/vB: a replacement when it signals RBF and its change can absorb
+    /// the higher fee, otherwise a child spending one of its wallet outputs. Fails when a fee
+    /// at `target_feerate` does not fit in a `u64`.
+    fn suggest_fee_bump(&self, txid: Txid, target_feerate: u64) -> Result<FeeBumpPlan, NodeError>;
+
     fn get_transaction_for_block(
         &self,
         block: Block,
@@ -32,11 +142,53 @@ pub trait Wallet: Send + Sync {
         sighash: [u8; 32],
     ) -> Transaction;
 
+    /// Tweak the group key needs on top of its shares to sign the first input of `tx`, one of
+    /// this wallet's own spends, or `None` when that input pays the untweaked group key.
+    fn signing_tweak(&self, tx: &Transaction) -> Option<Scalar>;
+
+    /// Reloads the UTXO set from the oracle. With `allow_unconfirmed`, outputs still in the
+    /// mempool are tracked too, but spends only use them when confirmed outputs fall short.
     async fn refresh_utxos(&mut self, allow_unconfirmed: Option<bool>) -> Result<(), NodeError>;
 
+    /// Minimum relay feerate, in sat/vB, below which spends are not built. The oracle is
+    /// asked again once the cached rate is older than its TTL; a failed query keeps it.
+    async fn refresh_min_relay_feerate(&mut self) -> u64;
+
     fn ingest_external_tx(&mut self, tx: &Transaction) -> Result<(), NodeError>;
 
+    /// Excludes `outpoint` from coin selection and the spendable balance. Returns false if it
+    /// was already locked.
+    fn lock_utxo(&mut self, outpoint: OutPoint) -> bool;
+
+    fn unlock_utxo(&mut self, outpoint: &OutPoint) -> bool;
+
+    /// Abandons `txid`, one of this wallet's own spends that will never reach the network:
+    /// its inputs become spendable again and its change is no longer tracked. Returns false
+    /// if the wallet holds no such spend.
+    fn release_spend(&mut self, txid: Txid) -> bool;
+
     fn get_utxos(&self) -> Vec<TrackedUtxo>;
 
+    /// Whether `outpoint` is tracked, locked, or spent, and by which transaction.
+    fn get_utxo_spend_status(&self, outpoint: OutPoint) -> UtxoStatus;
+
+    /// Sum of tracked UTXOs that coin selection may spend right now.
+    fn spendable_balance(&self) -> u64;
+
     fn add_address(&mut self, address: Address);
+
+    /// Stops tracking `address`; UTXOs already held on it are kept.
+    fn remove_address(&mut self, address: &Address);
+
+    /// Bitcoin network the wallet derives and tracks addresses on.
+    fn network(&self) -> bitcoin::Network;
+
+    /// Whether `script_pubkey` pays an address this wallet derived, and so can spend, whether
+    /// or not it is still tracked.
+    fn controls_script(&self, script_pubkey: &bitcoin::Script) -> bool;
+
+    /// Whether `tx` spends an outpoint the wallet tracks or has seen spent, which only the
+    /// vault itself can sign for. Holds for the wallet's own spends after they have moved
+    /// their inputs out of the UTXO set.
+    fn is_own_spend(&self, tx: &Transaction) -> bool;
 }
@@ -5,26 +5,106 @@ use bitcoin::secp256k1::Scalar;
 use bitcoin::secp256k1::Secp256k1;
 use bitcoin::sighash::Prevouts;
 use bitcoin::sighash::SighashCache;
-use bitcoin::{Address, EcdsaSighashType};
+use bitcoin::{Address, EcdsaSighashType, WPubkeyHash, XOnlyPublicKey};
 use bitcoin::{
-    Amount, Network, ScriptBuf, Sequence, Transaction, TxIn, TxOut, absolute::LockTime,
-    transaction::Version, witness::Witness,
+    Amount, Network, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Weight,
+    absolute::LockTime, transaction::Version, witness::Witness,
 };
 use itertools::Itertools;
+use num_traits::cast::ToPrimitive;
 use oracle::oracle::Oracle;
 use protocol::block::Block;
 use protocol::transaction::TransactionType;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use types::errors::NodeError;
 use types::utxo::Utxo;
 
-use super::Wallet;
+use super::{FeeBumpPlan, SpendOptions, UtxoStatus, Wallet};
 
 const IN_SZ_VBYTES: f64 = 68.0; // assume P2WPKH/P2TR key-spend
 const OUT_SZ_VBYTES: f64 = 31.0; // P2WPKH/P2TR output
 const TX_OVH_VBYTES: f64 = 10.5; // version + locktime + marker/flag
-const DUST: u64 = 546;
+/// Outputs at or below this value are never created; such change is paid to the miner instead.
+pub const DUST: u64 = 546;
+/// Non-witness bytes of an input: outpoint, empty `script_sig` length and sequence.
+const INPUT_BASE_BYTES: u64 = 32 + 4 + 1 + 4;
+/// Witness of a Taproot key-path input: item count, then a 65-byte Schnorr signature with
+/// its `SIGHASH_ALL` byte behind a length prefix.
+const P2TR_KEY_SPEND_WITNESS_WU: u64 = 1 + 1 + 65;
+/// Witness of a P2WPKH input: item count, then a DER signature of up to 72 bytes with its
+/// sighash byte and a 33-byte compressed key, each behind a length prefix.
+const P2WPKH_WITNESS_WU: u64 = 1 + 1 + 72 + 1 + 33;
+/// Segwit marker and flag, serialized once the transaction carries any witness.
+const SEGWIT_MARKER_WU: u64 = 2;
+/// Minimum fee-rate increase, in sat/vB, a BIP 125 replacement must pay over the original.
+pub const INCREMENTAL_RELAY_FEE_SAT_PER_VB: u64 = 1;
+/// Confirmations Bitcoin consensus requires before a coinbase output may be spent.
+pub const COINBASE_MATURITY: u32 = 100;
+/// Default cap on the UTXOs tracked for one address before dust-sized additions are ignored.
+pub const DEFAULT_MAX_UTXOS_PER_ADDRESS: usize = 500;
+/// Outputs below this value count as dust-sized for the per-address UTXO cap: at moderate
+/// feerates they cost about as much to spend as they are worth.
+pub const DUST_UTXO_THRESHOLD_SAT: u64 = 1_000;
+/// Furthest ahead of the current tip a height locktime may be set, roughly one year of blocks.
+pub const MAX_LOCKTIME_BLOCKS_AHEAD: u32 = 52_560;
+/// Furthest ahead of now a time locktime may be set, one year in seconds.
+pub const MAX_LOCKTIME_SECONDS_AHEAD: u32 = 365 * 24 * 60 * 60;
+/// Relay floor, in sat/vB, assumed until the oracle has reported its own.
+pub const DEFAULT_MIN_RELAY_FEERATE_SAT_VB: u64 = 1;
+/// How long an oracle-reported relay floor is trusted before it is queried again.
+pub const MIN_RELAY_FEERATE_TTL: Duration = Duration::from_secs(60);
+
+/// Key-path P2TR address of `public_key` with `tweak` added to its x-only key, the form every
+/// deposit address takes.
+pub fn tweaked_p2tr_address(
+    public_key: PublicKey,
+    tweak: Scalar,
+    network: Network,
+) -> Result<Address, NodeError> {
+    let secp = Secp256k1::new();
+    let internal = public_key.inner.x_only_public_key().0;
+    let (tweaked, _) = internal
+        .add_tweak(&secp, &tweak)
+        .map_err(|e| NodeError::Error(format!("Failed to tweak public key: {e}")))?;
+    Ok(Address::p2tr(&secp, tweaked, None, network))
+}
+
+/// What signing an input that spends a wallet output takes, recorded when the output is
+/// ingested since the script alone does not say which tweak the group key needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpendPath {
+    /// Taproot key path, signed over a BIP 341 sighash by the group key plus `tweak`, whose
+    /// x-only form is the witness program `output_key`. `tweak` is `None` for addresses the
+    /// wallet did not derive itself.
+    TaprootKeyPath {
+        output_key: XOnlyPublicKey,
+        tweak: Option<Scalar>,
+    },
+    /// P2WPKH, signed over a BIP 143 sighash by the key hashing to `pubkey_hash`.
+    SegwitV0 { pubkey_hash: WPubkeyHash },
+}
+
+impl SpendPath {
+    /// The path for an output locked to `script_pubkey`, or `None` for script types the
+    /// wallet cannot sign for, such as legacy P2PKH or P2SH outputs.
+    #[must_use]
+    pub fn from_script(script_pubkey: &ScriptBuf, tweak: Option<Scalar>) -> Option<Self> {
+        let bytes = script_pubkey.as_bytes();
+        if TaprootWallet::is_p2tr(script_pubkey) {
+            let output_key = XOnlyPublicKey::from_slice(&bytes[2..]).ok()?;
+            Some(Self::TaprootKeyPath { output_key, tweak })
+        } else if TaprootWallet::is_p2wpkh(script_pubkey) {
+            let pubkey_hash = WPubkeyHash::from_slice(&bytes[2..]).ok()?;
+            Some(Self::SegwitV0 { pubkey_hash })
+        } else {
+            None
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct TrackedUtxo {
@@ -32,12 +112,55 @@ pub struct TrackedUtxo {
     pub address: bitcoin::Address,
 }
 
+/// Bitcoin wallet holding the group's UTXOs.
+///
+/// The UTXO set has no interior locking: every method that changes it takes `&mut self`, and
+/// the node owns its wallet exclusively, driving it from one handler at a time. A wallet shared
+/// between tasks must sit behind a lock held for a whole operation, including across the
+/// awaits of [`Wallet::refresh_utxos`], so a refresh and a spend never interleave.
 pub struct TaprootWallet {
     pub addresses: Vec<bitcoin::Address>,
     pub utxos: Vec<TrackedUtxo>,
+    /// Outpoints consumed by our own spends that the oracle may still report until the spend
+    /// confirms; refreshes skip them so they cannot be selected twice.
+    pub spent_utxos: HashSet<bitcoin::OutPoint>,
+    /// Transaction that consumed each outpoint the wallet has seen spent, kept after the
+    /// spend confirms so the history stays auditable.
+    pub spending_txids: HashMap<bitcoin::OutPoint, Txid>,
     pub oracle: Box<dyn Oracle>,
     pub network: Network,
     pub db: Option<Arc<dyn Db + Send + Sync>>,
+    pub change_outputs: usize,
+    /// UTXOs tracked per address past which further dust-sized outputs are ignored; larger
+    /// outputs are always tracked.
+    pub max_utxos_per_address: usize,
+    /// Outpoints withheld from coin selection until explicitly unlocked.
+    pub locked_utxos: HashSet<bitcoin::OutPoint>,
+    /// Outputs created by our own spends that the oracle has not yet reported confirmed.
+    pub unconfirmed_utxos: HashSet<bitcoin::OutPoint>,
+    /// Outputs of others' transactions that the oracle reported while still unconfirmed, only
+    /// tracked by refreshes that allow unconfirmed outputs. Coin selection falls back to them
+    /// once confirmed outputs cannot cover a spend.
+    pub mempool_utxos: HashSet<bitcoin::OutPoint>,
+    /// Coinbase-derived outpoints and the block height they were mined at.
+    pub coinbase_utxos: HashMap<bitcoin::OutPoint, u32>,
+    /// Tracked outpoints already known to come from a coinbase or not, so each is looked up
+    /// once.
+    pub coinbase_checked: HashSet<bitcoin::OutPoint>,
+    /// Tweak each address derived by [`Wallet::generate_new_address`] adds to the group key.
+    pub address_tweaks: HashMap<ScriptBuf, Scalar>,
+    /// How each tracked UTXO is signed for, see [`SpendPath`].
+    pub spend_paths: HashMap<bitcoin::OutPoint, SpendPath>,
+    pub coinbase_maturity: u32,
+    /// Latest Bitcoin block height seen by the oracle.
+    pub tip_height: u32,
+    /// Unconfirmed transactions touching the wallet, with the outputs of their inputs that the
+    /// wallet tracked, kept so a fee bump can be planned for them.
+    pub wallet_transactions: HashMap<Txid, (Transaction, Vec<TxOut>)>,
+    /// Lowest feerate, in sat/vB, a spend may pay, from the oracle's minimum relay feerate.
+    pub min_relay_feerate_sat_vb: u64,
+    /// When `min_relay_feerate_sat_vb` was last reported by the oracle.
+    pub min_relay_feerate_fetched_at: Option<Instant>,
 }
 
 impl TaprootWallet {
@@ -46,9 +169,25 @@ impl TaprootWallet {
         Self {
             addresses,
             utxos: Vec::new(),
+            spent_utxos: HashSet::new(),
+            spending_txids: HashMap::new(),
             oracle,
             network,
             db: None,
+            change_outputs: 1,
+            max_utxos_per_address: DEFAULT_MAX_UTXOS_PER_ADDRESS,
+            locked_utxos: HashSet::new(),
+            unconfirmed_utxos: HashSet::new(),
+            mempool_utxos: HashSet::new(),
+            coinbase_utxos: HashMap::new(),
+            coinbase_checked: HashSet::new(),
+            address_tweaks: HashMap::new(),
+            spend_paths: HashMap::new(),
+            coinbase_maturity: COINBASE_MATURITY,
+            tip_height: 0,
+            wallet_transactions: HashMap::new(),
+            min_relay_feerate_sat_vb: DEFAULT_MIN_RELAY_FEERATE_SAT_VB,
+            min_relay_feerate_fetched_at: None,
         }
     }
 
@@ -73,17 +212,351 @@ impl TaprootWallet {
         Self {
             addresses,
             utxos: tracked,
+            spent_utxos: HashSet::new(),
+            spending_txids: HashMap::new(),
             oracle,
             network,
             db: Some(db),
+            change_outputs: 1,
+            max_utxos_per_address: DEFAULT_MAX_UTXOS_PER_ADDRESS,
+            locked_utxos: HashSet::new(),
+            unconfirmed_utxos: HashSet::new(),
+            mempool_utxos: HashSet::new(),
+            coinbase_utxos: HashMap::new(),
+            coinbase_checked: HashSet::new(),
+            address_tweaks: HashMap::new(),
+            spend_paths: HashMap::new(),
+            coinbase_maturity: COINBASE_MATURITY,
+            tip_height: 0,
+            wallet_transactions: HashMap::new(),
+            min_relay_feerate_sat_vb: DEFAULT_MIN_RELAY_FEERATE_SAT_VB,
+            min_relay_feerate_fetched_at: None,
+        }
+    }
+
+    /// Opts into splitting change across up to `count` distinct wallet addresses so spends
+    /// do not carry the single-change-output fingerprint. A count of 1 keeps one change output.
+    #[must_use]
+    pub fn with_change_outputs(mut self, count: usize) -> Self {
+        self.change_outputs = count.max(1);
+        self
+    }
+
+    /// Caps the UTXOs tracked for each address so a flood of tiny deposits cannot bloat the
+    /// wallet. Once an address holds `count` UTXOs, further dust-sized outputs are ignored.
+    #[must_use]
+    pub const fn with_max_utxos_per_address(mut self, count: usize) -> Self {
+        self.max_utxos_per_address = count;
+        self
+    }
+
+    /// Overrides the confirmations a coinbase-derived UTXO needs before it may be selected.
+    #[must_use]
+    pub const fn with_coinbase_maturity(mut self, confirmations: u32) -> Self {
+        self.coinbase_maturity = confirmations;
+        self
+    }
+
+    /// Records `outpoint` as paid by a coinbase transaction mined at `height`.
+    pub fn mark_coinbase(&mut self, outpoint: bitcoin::OutPoint, height: u32) {
+        self.coinbase_utxos.insert(outpoint, height);
+    }
+
+    /// Whether an output worth `value` may be tracked on `address`, which already holds `held`
+    /// UTXOs. Past the per-address cap only dust-sized outputs are turned away.
+    fn admits_utxo(&self, address: &Address, held: usize, value: Amount) -> bool {
+        if held < self.max_utxos_per_address || value.to_sat() >= DUST_UTXO_THRESHOLD_SAT {
+            return true;
         }
+        tracing::warn!(
+            "Ignoring {} sat output to {address}: it already holds {held} UTXOs",
+            value.to_sat()
+        );
+        false
     }
 
-    fn select_utxos(&self, target: u64) -> Option<Vec<TrackedUtxo>> {
+    /// Flags tracked UTXOs paid by a coinbase transaction and records the height that mined
+    /// them, which maturity counts from. Heights of immature coinbase outputs are read again
+    /// on every call, so one provisionally dated from the tip or re-mined by a reorg moves.
+    async fn detect_coinbase_utxos(&mut self) {
+        let unchecked: Vec<bitcoin::OutPoint> = self
+            .utxos
+            .iter()
+            .map(|tracked| tracked.utxo.outpoint)
+            .filter(|outpoint| !self.coinbase_checked.contains(outpoint))
+            .collect();
+        for outpoint in unchecked {
+            match self
+                .oracle
+                .get_transaction_by_address(&outpoint.txid.to_string())
+                .await
+            {
+                Ok(tx) => {
+                    if tx.is_coinbase() {
+                        self.mark_coinbase(outpoint, self.tip_height);
+                    }
+                    self.coinbase_checked.insert(outpoint);
+                }
+                Err(e) => tracing::warn!(
+                    "Cannot tell whether {outpoint} comes from a coinbase, retrying on the next refresh: {e}"
+                ),
+            }
+        }
+
+        let immature: Vec<bitcoin::OutPoint> = self
+            .coinbase_utxos
+            .keys()
+            .filter(|outpoint| !self.is_mature(outpoint))
+            .copied()
+            .collect();
+        for outpoint in immature {
+            match self.oracle.get_transaction_height(outpoint.txid).await {
+                Ok(Some(height)) => self.mark_coinbase(outpoint, height),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to fetch the height that mined {outpoint}: {e}"),
+            }
+        }
+    }
+
+    fn is_mature(&self, outpoint: &bitcoin::OutPoint) -> bool {
+        self.coinbase_utxos.get(outpoint).is_none_or(|&height| {
+            self.tip_height.saturating_sub(height) + 1 >= self.coinbase_maturity
+        })
+    }
+
+    /// Value `tracked` adds to a spend at `feerate_sat_vb` once the fee for its own input is
+    /// paid. Dust-sized UTXOs go negative at high feerates and would only shrink the spend.
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn effective_value(tracked: &TrackedUtxo, feerate_sat_vb: u64) -> i64 {
+        let witness =
+            Self::witness_weight(&tracked.utxo.script_pubkey).unwrap_or(P2WPKH_WITNESS_WU);
+        let input_fee = ((INPUT_BASE_BYTES * 4 + witness) * feerate_sat_vb).div_ceil(4);
+        tracked.utxo.value.to_sat() as i64 - input_fee as i64
+    }
+
+    /// Witness weight, in weight units, of a signed input spending `script_pubkey`.
+    fn witness_weight(script_pubkey: &ScriptBuf) -> Option<u64> {
+        if Self::is_p2tr(script_pubkey) {
+            Some(P2TR_KEY_SPEND_WITNESS_WU)
+        } else if Self::is_p2wpkh(script_pubkey) {
+            Some(P2WPKH_WITNESS_WU)
+        } else {
+            None
+        }
+    }
+
+    /// Weight of `tx` once every input, spending the matching entry of `prevouts`, carries
+    /// its witness: four units per non-witness byte plus one per witness byte.
+    pub fn estimated_weight(tx: &Transaction, prevouts: &[TxOut]) -> Result<Weight, NodeError> {
+        if prevouts.len() != tx.input.len() {
+            return Err(NodeError::Error(format!(
+                "Expected {} prevouts, got {}",
+                tx.input.len(),
+                prevouts.len()
+            )));
+        }
+
+        let mut unsigned = tx.clone();
+        for input in &mut unsigned.input {
+            input.witness = Witness::new();
+        }
+        let witness_wu = prevouts
+            .iter()
+            .map(|prevout| {
+                Self::witness_weight(&prevout.script_pubkey)
+                    .ok_or_else(|| NodeError::Error("Unsupported script type".into()))
+            })
+            .sum::<Result<u64, NodeError>>()?;
+
+        Ok(Weight::from_wu(
+            unsigned.base_size() as u64 * 4 + SEGWIT_MARKER_WU + witness_wu,
+        ))
+    }
+
+    /// Fee `tx` pays at `feerate_sat_vb` on its virtual size, the estimated weight divided by
+    /// four and rounded up, so the witness discount is not overpaid.
+    pub fn estimate_fee_sat(
+        tx: &Transaction,
+        prevouts: &[TxOut],
+        feerate_sat_vb: u64,
+    ) -> Result<u64, NodeError> {
+        Ok(Self::estimated_weight(tx, prevouts)?.to_vbytes_ceil() * feerate_sat_vb)
+    }
+
+    /// Rejects spending `selected` on `amount_sat` to `recipient` with a fee of `fee_sat` if
+    /// that pays less than the minimum relay feerate.
+    fn check_relay_floor(
+        &mut self,
+        selected: &[TrackedUtxo],
+        amount_sat: u64,
+        fee_sat: u64,
+        recipient: &Address,
+        options: SpendOptions,
+    ) -> Result<(), NodeError> {
+        let (tx, _) = self.build_spend(selected, amount_sat, fee_sat, recipient, true, options)?;
+        let prevouts: Vec<TxOut> = selected
+            .iter()
+            .map(|u| TxOut {
+                value: u.utxo.value,
+                script_pubkey: u.utxo.script_pubkey.clone(),
+            })
+            .collect();
+        // Inputs whose witness size is unknown are counted without it, which can only
+        // understate the floor.
+        let vsize = Self::estimated_weight(&tx, &prevouts)
+            .unwrap_or_else(|_| tx.weight())
+            .to_vbytes_ceil();
+        let floor_sat = vsize * self.min_relay_feerate_sat_vb;
+        if fee_sat < floor_sat {
+            return Err(NodeError::Error(format!(
+                "Fee of {fee_sat} sat is below the minimum relay feerate of {} sat/vB ({floor_sat} sat)",
+                self.min_relay_feerate_sat_vb
+            )));
+        }
+        Ok(())
+    }
+
+    /// Selects inputs paying `payments` and the fee their spend needs at `feerate_sat_vb`,
+    /// never below `min_fee_sat`. Each added input raises the fee, which may in turn need
+    /// another input, so selection repeats until the inputs also cover their own weight.
+    fn select_for_feerate(
+        &mut self,
+        payments: &[(Address, u64)],
+        min_fee_sat: u64,
+        feerate_sat_vb: u64,
+        options: SpendOptions,
+    ) -> Result<(Vec<TrackedUtxo>, u64), NodeError> {
+        let amount_sat: u64 = payments.iter().map(|(_, value)| value).sum();
+        let mut fee_sat = min_fee_sat;
+        loop {
+            let selected = self
+                .select_utxos(amount_sat + fee_sat, Some(feerate_sat_vb))
+                .ok_or_else(|| NodeError::Error("Not enough funds to create transaction".into()))?;
+            let (tx, _) = self.build_payments(&selected, payments, fee_sat, true, options)?;
+            let prevouts: Vec<TxOut> = selected
+                .iter()
+                .map(|u| TxOut {
+                    value: u.utxo.value,
+                    script_pubkey: u.utxo.script_pubkey.clone(),
+                })
+                .collect();
+
+            let needed = Self::estimate_fee_sat(&tx, &prevouts, feerate_sat_vb)?;
+            if needed <= fee_sat {
+                return Ok((selected, fee_sat));
+            }
+            fee_sat = needed;
+        }
+    }
+
+    /// Tracked outputs of others' transactions the oracle does not report as confirmed. A
+    /// failed confirmation query counts as unconfirmed, so selection never prefers an output
+    /// it could not vouch for.
+    async fn find_mempool_utxos(&self) -> HashSet<bitcoin::OutPoint> {
+        let txids: HashSet<Txid> = self
+            .utxos
+            .iter()
+            .map(|u| u.utxo.outpoint)
+            .filter(|outpoint| !self.unconfirmed_utxos.contains(outpoint))
+            .map(|outpoint| outpoint.txid)
+            .collect();
+
+        let mut unconfirmed_txids = HashSet::new();
+        for txid in txids {
+            match self.oracle.is_transaction_confirmed(txid).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    unconfirmed_txids.insert(txid);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check confirmation of {txid}: {e}");
+                    unconfirmed_txids.insert(txid);
+                }
+            }
+        }
+
+        self.utxos
+            .iter()
+            .map(|u| u.utxo.outpoint)
+            .filter(|outpoint| {
+                unconfirmed_txids.contains(&outpoint.txid)
+                    && !self.unconfirmed_utxos.contains(outpoint)
+            })
+            .collect()
+    }
+
+    fn is_spendable(&self, tracked: &TrackedUtxo) -> bool {
+        let outpoint = &tracked.utxo.outpoint;
+        !self.locked_utxos.contains(outpoint)
+            && !self.unconfirmed_utxos.contains(outpoint)
+            && self.is_mature(outpoint)
+    }
+
+    /// Splits `change_sat` into randomized shares, each above dust, paid to `primary` followed
+    /// by other wallet addresses. Fewer shares are used when the change cannot cover them all.
+    fn split_change(&self, change_sat: u64, primary: &Address) -> Vec<(Address, u64)> {
+        if change_sat <= DUST {
+            return Vec::new();
+        }
+
+        let mut change_addresses = vec![primary.clone()];
+        for address in &self.addresses {
+            if change_addresses.len() >= self.change_outputs {
+                break;
+            }
+            if !change_addresses.contains(address) {
+                change_addresses.push(address.clone());
+            }
+        }
+
+        let max_shares = usize::try_from(change_sat / (DUST + 1)).unwrap_or(usize::MAX);
+        change_addresses.truncate(max_shares.max(1));
+
+        if change_addresses.len() == 1 {
+            return vec![(primary.clone(), change_sat)];
+        }
+
+        let share_count = change_addresses.len() as u64;
+        let surplus = change_sat - (DUST + 1) * share_count;
+
+        let mut rng = rand::rng();
+        let mut cuts: Vec<u64> = (1..share_count)
+            .map(|_| rng.random_range(0..=surplus))
+            .collect();
+        cuts.sort_unstable();
+        cuts.push(surplus);
+
+        let mut previous = 0;
+        change_addresses
+            .into_iter()
+            .zip(cuts)
+            .map(|(address, cut)| {
+                let share = DUST + 1 + cut - previous;
+                previous = cut;
+                (address, share)
+            })
+            .collect()
+    }
+
+    /// Picks the largest spendable UTXOs until `target` is covered, exhausting confirmed ones
+    /// before touching any output still in the mempool.
+    fn select_utxos(&self, target: u64, feerate_sat_vb: Option<u64>) -> Option<Vec<TrackedUtxo>> {
         let mut selected = Vec::new();
         let mut total_val: u64 = 0;
-        let mut sorted_utxos = self.utxos.clone();
-        sorted_utxos.sort_by(|a, b| b.utxo.value.cmp(&a.utxo.value));
+        let mut sorted_utxos: Vec<TrackedUtxo> = self
+            .utxos
+            .iter()
+            .filter(|u| self.is_spendable(u))
+            .filter(|u| feerate_sat_vb.is_none_or(|rate| Self::effective_value(u, rate) > 0))
+            .cloned()
+            .collect();
+        sorted_utxos.sort_by_key(|u| {
+            (
+                self.mempool_utxos.contains(&u.utxo.outpoint),
+                std::cmp::Reverse(u.utxo.value),
+            )
+        });
 
         for utxo in sorted_utxos {
             if total_val < target {
@@ -94,11 +567,468 @@ impl TaprootWallet {
             }
         }
 
-        if total_val >= target {
-            Some(selected)
-        } else {
-            None
+        if total_val < target {
+            return None;
+        }
+
+        let unconfirmed = selected
+            .iter()
+            .filter(|u| self.mempool_utxos.contains(&u.utxo.outpoint))
+            .count();
+        if unconfirmed > 0 {
+            tracing::warn!(
+                "Confirmed UTXOs cannot cover {target} sat, spending {unconfirmed} unconfirmed inputs"
+            );
+        }
+        Some(selected)
+    }
+
+    /// Spends exactly `outpoints`, paying their total value less `fee_sat` to `recipient`.
+    ///
+    /// Coin selection is bypassed, so every outpoint must be a tracked UTXO that selection
+    /// could also have picked: not locked, not unconfirmed change and not immature coinbase.
+    pub fn create_spend_from(
+        &mut self,
+        outpoints: &[bitcoin::OutPoint],
+        recipient: &Address,
+        fee_sat: u64,
+    ) -> Result<(Transaction, [u8; 32]), NodeError> {
+        if outpoints.is_empty() {
+            return Err(NodeError::Error("No outpoints to spend".into()));
+        }
+
+        let mut selected_utxos = Vec::with_capacity(outpoints.len());
+        for outpoint in outpoints {
+            let tracked = self
+                .utxos
+                .iter()
+                .find(|u| u.utxo.outpoint == *outpoint)
+                .ok_or_else(|| NodeError::Error(format!("Unknown outpoint {outpoint}")))?;
+            if !self.is_spendable(tracked) {
+                return Err(NodeError::Error(format!(
+                    "Outpoint {outpoint} is not spendable"
+                )));
+            }
+            if selected_utxos
+                .iter()
+                .any(|u: &TrackedUtxo| u.utxo.outpoint == *outpoint)
+            {
+                return Err(NodeError::Error(format!("Duplicate outpoint {outpoint}")));
+            }
+            selected_utxos.push(tracked.clone());
+        }
+
+        let total_input_val: u64 = selected_utxos.iter().map(|u| u.utxo.value.to_sat()).sum();
+        let amount_sat = total_input_val
+            .checked_sub(fee_sat)
+            .filter(|amount| *amount > DUST)
+            .ok_or_else(|| {
+                NodeError::Error(format!(
+                    "Outpoints worth {total_input_val} sat cannot pay a {fee_sat} sat fee"
+                ))
+            })?;
+
+        self.build_spend(
+            &selected_utxos,
+            amount_sat,
+            fee_sat,
+            recipient,
+            false,
+            SpendOptions::default(),
+        )
+    }
+
+    /// Spends every spendable UTXO into a single output to `destination`, worth their total
+    /// less `fee_sat`, such as when moving the vault to a new group key after a reshare.
+    ///
+    /// Locked outputs, unconfirmed change and immature coinbase outputs are left behind.
+    pub fn sweep_all(
+        &mut self,
+        destination: &Address,
+        fee_sat: u64,
+    ) -> Result<(Transaction, [u8; 32]), NodeError> {
+        let outpoints: Vec<bitcoin::OutPoint> = self
+            .utxos
+            .iter()
+            .filter(|u| self.is_spendable(u))
+            .map(|u| u.utxo.outpoint)
+            .collect();
+        if outpoints.is_empty() {
+            return Err(NodeError::Error("No spendable UTXOs to sweep".into()));
+        }
+        self.create_spend_from(&outpoints, destination, fee_sat)
+    }
+
+    /// Builds the spend of `selected_utxos` paying `amount_sat` to `recipient`, with the
+    /// remainder after `estimated_fee_sat` returned as change.
+    fn build_spend(
+        &mut self,
+        selected_utxos: &[TrackedUtxo],
+        amount_sat: u64,
+        estimated_fee_sat: u64,
+        recipient: &Address,
+        dry_run: bool,
+        options: SpendOptions,
+    ) -> Result<(Transaction, [u8; 32]), NodeError> {
+        self.build_payments(
+            selected_utxos,
+            &[(recipient.clone(), amount_sat)],
+            estimated_fee_sat,
+            dry_run,
+            options,
+        )
+    }
+
+    /// Sats left over as change once `amount_sat` and `fee_sat` are paid from `input_sat`.
+    ///
+    /// Every spend holds `inputs == outputs + fee` to the sat. Fees are rounded up wherever
+    /// they are derived from a feerate, and change is never negative: inputs that fall short
+    /// are an error rather than an underflow. Change of at most [`DUST`] sat gets no output of
+    /// its own and is paid to the miner on top of `fee_sat`.
+    pub fn change_sat(input_sat: u64, amount_sat: u64, fee_sat: u64) -> Result<u64, NodeError> {
+        amount_sat
+            .checked_add(fee_sat)
+            .and_then(|spent| input_sat.checked_sub(spent))
+            .ok_or_else(|| {
+                NodeError::Error(format!(
+                    "Inputs worth {input_sat} sat cannot pay {amount_sat} sat plus a {fee_sat} sat fee"
+                ))
+            })
+    }
+
+    /// [`Self::build_spend`] paying every entry of `payments`, in order, before the change.
+    fn build_payments(
+        &mut self,
+        selected_utxos: &[TrackedUtxo],
+        payments: &[(Address, u64)],
+        estimated_fee_sat: u64,
+        dry_run: bool,
+        options: SpendOptions,
+    ) -> Result<(Transaction, [u8; 32]), NodeError> {
+        let amount_sat: u64 = payments.iter().map(|(_, value)| value).sum();
+        let total_input_val = selected_utxos
+            .iter()
+            .fold(0, |acc, u| acc + u.utxo.value.to_sat());
+
+        let change_address = self
+            .utxos
+            .iter()
+            .min_by(|a, b| a.address.cmp(&b.address))
+            .ok_or_else(|| NodeError::Error("No UTXOs selected".into()))?
+            .address
+            .clone();
+
+        let change_sat = Self::change_sat(total_input_val, amount_sat, estimated_fee_sat)?;
+
+        let inputs: Vec<TxIn> = selected_utxos
+            .iter()
+            .map(|tracked_utxo| TxIn {
+                previous_output: tracked_utxo.utxo.outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            })
+            .collect();
+        let mut outputs: Vec<TxOut> = payments
+            .iter()
+            .map(|(recipient, value)| TxOut {
+                value: Amount::from_sat(*value),
+                script_pubkey: recipient.script_pubkey(),
+            })
+            .collect();
+
+        let change = self.split_change(change_sat, &change_address);
+        outputs.extend(change.iter().map(|(address, value)| TxOut {
+            value: Amount::from_sat(*value),
+            script_pubkey: address.script_pubkey(),
+        }));
+
+        let tx = Transaction {
+            version: options.version,
+            lock_time: options.lock_time,
+            input: inputs,
+            output: outputs,
+        };
+
+        let prevouts: Vec<TxOut> = selected_utxos
+            .iter()
+            .map(|u| TxOut {
+                value: u.utxo.value,
+                script_pubkey: u.utxo.script_pubkey.clone(),
+            })
+            .collect();
+        let sighash = self.first_input_sighash(&tx, &prevouts)?;
+
+        // Only touch the UTXO set once the spend can no longer fail.
+        if !dry_run {
+            let txid = tx.compute_txid();
+            let outpoints: HashSet<_> = selected_utxos.iter().map(|u| u.utxo.outpoint).collect();
+            self.utxos.retain(|t| !outpoints.contains(&t.utxo.outpoint));
+            self.spending_txids
+                .extend(outpoints.iter().map(|outpoint| (*outpoint, txid)));
+            self.spent_utxos.extend(outpoints);
+
+            self.wallet_transactions
+                .insert(txid, (tx.clone(), prevouts.clone()));
+            let first_change = u32::try_from(payments.len())
+                .map_err(|_| NodeError::Error("Too many payments".into()))?;
+            for (vout, (address, value)) in (first_change..).zip(change) {
+                let outpoint = bitcoin::OutPoint { txid, vout };
+                self.unconfirmed_utxos.insert(outpoint);
+                self.coinbase_checked.insert(outpoint);
+                self.utxos.push(TrackedUtxo {
+                    utxo: Utxo {
+                        outpoint,
+                        value: Amount::from_sat(value),
+                        script_pubkey: address.script_pubkey(),
+                    },
+                    address,
+                });
+            }
+        }
+
+        Ok((tx, sighash))
+    }
+
+    /// Rejects non-standard versions and locktimes too far in the future to be intended.
+    ///
+    /// Locktimes already in the past are allowed; such a spend is simply final immediately.
+    fn check_spend_options(&self, options: SpendOptions) -> Result<(), NodeError> {
+        if !options.version.is_standard() {
+            return Err(NodeError::Error(format!(
+                "Non-standard transaction version {}",
+                options.version.0
+            )));
+        }
+
+        match options.lock_time {
+            LockTime::Blocks(height) => {
+                let max_height = self.tip_height.saturating_add(MAX_LOCKTIME_BLOCKS_AHEAD);
+                // Without a known tip there is nothing to compare against.
+                if self.tip_height > 0 && height.to_consensus_u32() > max_height {
+                    return Err(NodeError::Error(format!(
+                        "Locktime height {height} is more than {MAX_LOCKTIME_BLOCKS_AHEAD} blocks past the tip {}",
+                        self.tip_height
+                    )));
+                }
+            }
+            LockTime::Seconds(time) => {
+                let now = u32::try_from(
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or_default(),
+                )
+                .unwrap_or(u32::MAX);
+                if time.to_consensus_u32() > now.saturating_add(MAX_LOCKTIME_SECONDS_AHEAD) {
+                    return Err(NodeError::Error(format!(
+                        "Locktime {time} is more than a year in the future"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sighash the group key signs for the first input, which spends `prevouts[0]`.
+    fn first_input_sighash(
+        &self,
+        tx: &Transaction,
+        prevouts: &[TxOut],
+    ) -> Result<[u8; 32], NodeError> {
+        if prevouts.is_empty() {
+            return Err(NodeError::Error("No UTXOs to sign".into()));
+        }
+        self.input_sighash(&mut SighashCache::new(tx), 0, prevouts)
+    }
+
+    /// Sighashes of every input of `tx`, spending `prevouts` in input order.
+    ///
+    /// One [`SighashCache`] serves the whole transaction, so the hashes of the prevouts,
+    /// sequences and outputs shared by all inputs are computed once rather than per input.
+    pub fn input_sighashes(
+        &self,
+        tx: &Transaction,
+        prevouts: &[TxOut],
+    ) -> Result<Vec<[u8; 32]>, NodeError> {
+        if prevouts.len() != tx.input.len() {
+            return Err(NodeError::Error(format!(
+                "Expected {} prevouts, got {}",
+                tx.input.len(),
+                prevouts.len()
+            )));
         }
+        let mut sighash_cache = SighashCache::new(tx);
+        (0..tx.input.len())
+            .map(|index| self.input_sighash(&mut sighash_cache, index, prevouts))
+            .collect()
+    }
+
+    fn input_sighash(
+        &self,
+        sighash_cache: &mut SighashCache<&Transaction>,
+        index: usize,
+        prevouts: &[TxOut],
+    ) -> Result<[u8; 32], NodeError> {
+        let utxo_to_sign = prevouts
+            .get(index)
+            .ok_or_else(|| NodeError::Error(format!("No prevout for input {index}")))?;
+        let outpoint = sighash_cache
+            .transaction()
+            .input
+            .get(index)
+            .ok_or_else(|| NodeError::Error(format!("No input {index}")))?
+            .previous_output;
+
+        let sighash = match self.input_spend_path(&outpoint, &utxo_to_sign.script_pubkey) {
+            Some(SpendPath::SegwitV0 { .. }) => sighash_cache
+                .p2wpkh_signature_hash(
+                    index,
+                    &utxo_to_sign.script_pubkey,
+                    utxo_to_sign.value,
+                    EcdsaSighashType::All,
+                )
+                .map_err(|e| NodeError::Error(format!("Failed to calculate sighash: {e}")))?
+                .to_byte_array(),
+            Some(SpendPath::TaprootKeyPath { .. }) => sighash_cache
+                .taproot_key_spend_signature_hash(
+                    index,
+                    &Prevouts::All(prevouts),
+                    bitcoin::TapSighashType::All,
+                )
+                .map_err(|e| NodeError::Error(format!("Failed to calculate sighash: {e}")))?
+                .to_byte_array(),
+            None => return Err(NodeError::Error("Unsupported script type".into())),
+        };
+
+        Ok(sighash)
+    }
+
+    /// `feerate` sat/vB over `vsize` vbytes, or an error when that does not fit in a `u64`.
+    fn fee_at(feerate: u64, vsize: u64) -> Result<u64, NodeError> {
+        feerate.checked_mul(vsize).ok_or_else(|| {
+            NodeError::Error(format!("Fee for {vsize} vB at {feerate} sat/vB overflows"))
+        })
+    }
+
+    /// Output indexes of our own spend `tx` that pay back to the wallet, to one of its
+    /// addresses or to a script it spent from in `prevouts`. Recipients are never counted as
+    /// change.
+    fn change_vouts(&self, tx: &Transaction, prevouts: &[TxOut]) -> HashSet<u32> {
+        (0u32..)
+            .zip(&tx.output)
+            .filter(|(_, output)| {
+                let script = &output.script_pubkey;
+                self.address_tweaks.contains_key(script)
+                    || self.addresses.iter().any(|a| &a.script_pubkey() == script)
+                    || self.utxos.iter().any(|u| &u.utxo.script_pubkey == script)
+                    || prevouts.iter().any(|p| &p.script_pubkey == script)
+            })
+            .map(|(vout, _)| vout)
+            .collect()
+    }
+
+    /// Sats the change of `tx` can give up to a BIP 125 replacement while staying above dust.
+    fn rbf_headroom(&self, tx: &Transaction, prevouts: &[TxOut]) -> Result<u64, String> {
+        if !tx.is_explicitly_rbf() {
+            return Err("it does not signal replaceability".into());
+        }
+        if prevouts.len() != tx.input.len() {
+            return Err("the wallet does not own all of its inputs".into());
+        }
+
+        let change_vouts = self.change_vouts(tx, prevouts);
+        Ok((0u32..)
+            .zip(&tx.output)
+            .filter(|(vout, _)| change_vouts.contains(vout))
+            .map(|(_, output)| output.value.to_sat().saturating_sub(DUST + 1))
+            .sum())
+    }
+
+    /// The largest spendable wallet output of `txid` and the vsize of a child spending it.
+    fn cpfp_candidate(&self, txid: Txid) -> Result<(&TrackedUtxo, u64), String> {
+        let output = self
+            .utxos
+            .iter()
+            .filter(|u| u.utxo.outpoint.txid == txid)
+            .filter(|u| {
+                !self.locked_utxos.contains(&u.utxo.outpoint) && self.is_mature(&u.utxo.outpoint)
+            })
+            .max_by_key(|u| u.utxo.value)
+            .ok_or("the wallet owns none of its outputs")?;
+
+        let prevout = TxOut {
+            value: output.utxo.value,
+            script_pubkey: output.utxo.script_pubkey.clone(),
+        };
+        let child = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: output.utxo.outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            }],
+            output: vec![prevout.clone()],
+        };
+        let child_vsize = Self::estimated_weight(&child, &[prevout])
+            .map_err(|e| e.to_string())?
+            .to_vbytes_ceil();
+        Ok((output, child_vsize))
+    }
+
+    /// Tracks the outputs `change_vouts` of our own spend `tx` as unconfirmed wallet UTXOs.
+    fn track_change_outputs(&mut self, tx: &Transaction, change_vouts: &HashSet<u32>) {
+        let txid = tx.compute_txid();
+        for (vout, out) in (0u32..).zip(&tx.output) {
+            if !change_vouts.contains(&vout) {
+                continue;
+            }
+            let Ok(address) = Address::from_script(&out.script_pubkey, self.network) else {
+                continue;
+            };
+            let outpoint = bitcoin::OutPoint { txid, vout };
+            if let Some(spend_path) = self.spend_path_for(&out.script_pubkey) {
+                self.spend_paths.insert(outpoint, spend_path);
+            }
+            self.unconfirmed_utxos.insert(outpoint);
+            self.coinbase_checked.insert(outpoint);
+            self.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint,
+                    value: out.value,
+                    script_pubkey: out.script_pubkey.clone(),
+                },
+                address,
+            });
+        }
+    }
+
+    /// How the wallet output `outpoint` is signed for, once it has been tracked.
+    #[must_use]
+    pub fn spend_path(&self, outpoint: &bitcoin::OutPoint) -> Option<SpendPath> {
+        self.spend_paths.get(outpoint).copied()
+    }
+
+    /// How the input spending `outpoint`, locked to `script_pubkey`, is signed for: the path
+    /// recorded when the output was tracked, or else the one its script and any tweak the
+    /// wallet derived it with imply, e.g. once our own spend has dropped it from the UTXO set.
+    #[must_use]
+    pub fn input_spend_path(
+        &self,
+        outpoint: &bitcoin::OutPoint,
+        script_pubkey: &ScriptBuf,
+    ) -> Option<SpendPath> {
+        self.spend_path(outpoint)
+            .or_else(|| self.spend_path_for(script_pubkey))
+    }
+
+    fn spend_path_for(&self, script_pubkey: &ScriptBuf) -> Option<SpendPath> {
+        SpendPath::from_script(
+            script_pubkey,
+            self.address_tweaks.get(script_pubkey).copied(),
+        )
     }
 
     fn is_p2wpkh(script: &ScriptBuf) -> bool {
@@ -118,155 +1048,310 @@ impl Wallet for TaprootWallet {
         self.addresses.push(address);
     }
 
+    fn remove_address(&mut self, address: &Address) {
+        self.addresses.retain(|tracked| tracked != address);
+    }
+
+    fn network(&self) -> Network {
+        self.network
+    }
+
+    fn controls_script(&self, script_pubkey: &bitcoin::Script) -> bool {
+        self.address_tweaks.contains_key(script_pubkey)
+    }
+
+    fn is_own_spend(&self, tx: &Transaction) -> bool {
+        tx.input.iter().any(|input| {
+            let outpoint = input.previous_output;
+            self.spent_utxos.contains(&outpoint)
+                || self.spending_txids.contains_key(&outpoint)
+                || self.utxos.iter().any(|t| t.utxo.outpoint == outpoint)
+        })
+    }
+
+    fn signing_tweak(&self, tx: &Transaction) -> Option<Scalar> {
+        let input = tx.input.first()?;
+        let (_, prevouts) = self.wallet_transactions.get(&tx.compute_txid())?;
+        match self.input_spend_path(&input.previous_output, &prevouts.first()?.script_pubkey)? {
+            SpendPath::TaprootKeyPath { tweak, .. } => tweak,
+            SpendPath::SegwitV0 { .. } => None,
+        }
+    }
+
     async fn refresh_utxos(&mut self, allow_unconfirmed: Option<bool>) -> Result<(), NodeError> {
-        self.utxos.clear();
+        let allow_unconfirmed = allow_unconfirmed.unwrap_or(false);
+        match self.oracle.get_latest_block_height().await {
+            Ok(height) => self.tip_height = height,
+            Err(e) => tracing::warn!(
+                "Failed to fetch block height, keeping {}: {e}",
+                self.tip_height
+            ),
+        }
+        // Build the new set before replacing the old one, so a failed fetch loses nothing.
+        let mut refreshed = Vec::new();
+        let mut reported = HashSet::new();
         for addr in &self.addresses {
             let fetched = self
                 .oracle
-                .refresh_utxos(addr.clone(), 3, None, allow_unconfirmed.unwrap_or(false))
+                .refresh_utxos(addr.clone(), 3, None, allow_unconfirmed)
                 .await?;
 
             if let Some(db) = &self.db {
                 db.store_utxos(fetched.clone())?;
             }
 
+            let mut held = 0;
             for u in fetched {
-                self.utxos.push(TrackedUtxo {
+                reported.insert(u.outpoint);
+                if self.spent_utxos.contains(&u.outpoint) || !self.admits_utxo(addr, held, u.value)
+                {
+                    continue;
+                }
+                held += 1;
+                refreshed.push(TrackedUtxo {
                     utxo: u.clone(),
                     address: addr.clone(),
                 });
             }
         }
+        self.utxos = refreshed;
+        // Once the oracle stops reporting a spent outpoint, the spend is on chain.
+        self.spent_utxos
+            .retain(|outpoint| reported.contains(outpoint));
+
+        // Without unconfirmed outputs the oracle only reports confirmed ones; otherwise keep
+        // tracking our own pending outputs until they are seen confirmed.
+        let known: HashSet<_> = self.utxos.iter().map(|u| u.utxo.outpoint).collect();
+        if allow_unconfirmed {
+            self.unconfirmed_utxos
+                .retain(|outpoint| known.contains(outpoint));
+            self.mempool_utxos = self.find_mempool_utxos().await;
+        } else {
+            self.unconfirmed_utxos.clear();
+            self.mempool_utxos.clear();
+        }
+        self.coinbase_utxos
+            .retain(|outpoint, _| known.contains(outpoint));
+        self.coinbase_checked
+            .retain(|outpoint| known.contains(outpoint));
+        self.detect_coinbase_utxos().await;
+        self.spend_paths = self
+            .utxos
+            .iter()
+            .filter_map(|u| {
+                let spend_path = self.spend_path_for(&u.utxo.script_pubkey)?;
+                Some((u.utxo.outpoint, spend_path))
+            })
+            .collect();
+        // A transaction is settled once its inputs are no longer reported and none of its
+        // outputs is still awaiting confirmation.
+        self.wallet_transactions.retain(|txid, (tx, _)| {
+            tx.input
+                .iter()
+                .any(|input| self.spent_utxos.contains(&input.previous_output))
+                || self
+                    .unconfirmed_utxos
+                    .iter()
+                    .any(|outpoint| outpoint.txid == *txid)
+        });
         Ok(())
     }
 
+    async fn refresh_min_relay_feerate(&mut self) -> u64 {
+        let fresh = self
+            .min_relay_feerate_fetched_at
+            .is_some_and(|fetched_at| fetched_at.elapsed() < MIN_RELAY_FEERATE_TTL);
+        if fresh {
+            return self.min_relay_feerate_sat_vb;
+        }
+
+        match self.oracle.get_min_relay_fee_per_vb().await {
+            Ok(rate) => match rate.ceil().to_u64() {
+                Some(rate) => {
+                    self.min_relay_feerate_sat_vb = rate;
+                    self.min_relay_feerate_fetched_at = Some(Instant::now());
+                }
+                None => tracing::warn!(
+                    "Ignoring invalid minimum relay feerate {rate}, keeping {}",
+                    self.min_relay_feerate_sat_vb
+                ),
+            },
+            Err(e) => tracing::warn!(
+                "Failed to fetch minimum relay feerate, keeping {}: {e}",
+                self.min_relay_feerate_sat_vb
+            ),
+        }
+        self.min_relay_feerate_sat_vb
+    }
+
     fn generate_new_address(&mut self, public_key: PublicKey, tweak: Scalar) -> bitcoin::Address {
-        let secp = Secp256k1::new();
-        let internal = public_key.inner.x_only_public_key().0;
-        let (tweaked, _) = internal.add_tweak(&secp, &tweak).expect("tweak");
-        let address = Address::p2tr(&secp, tweaked, None, self.network);
+        let address = tweaked_p2tr_address(public_key, tweak, self.network).expect("tweak");
+        self.address_tweaks.insert(address.script_pubkey(), tweak);
         self.addresses.push(address.clone());
         address
     }
 
-    fn create_spend(
+    fn create_spend_with_options(
         &mut self,
         amount_sat: u64,
         estimated_fee_sat: u64,
         recipient: &bitcoin::Address,
         dry_run: bool,
+        options: SpendOptions,
     ) -> Result<(Transaction, [u8; 32]), NodeError> {
-        let total_needed = amount_sat + estimated_fee_sat;
-        let selected_utxos = self
-            .select_utxos(total_needed)
-            .ok_or_else(|| NodeError::Error("Not enough funds to create transaction".into()))?;
+        self.check_spend_options(options)?;
 
-        let total_input_val = selected_utxos
-            .iter()
-            .fold(0, |acc, u| acc + u.utxo.value.to_sat());
+        let (selected_utxos, fee_sat) = if let Some(feerate_sat_vb) = options.feerate_sat_vb {
+            self.select_for_feerate(
+                &[(recipient.clone(), amount_sat)],
+                estimated_fee_sat,
+                feerate_sat_vb.max(self.min_relay_feerate_sat_vb),
+                options,
+            )?
+        } else {
+            let target = amount_sat
+                .checked_add(estimated_fee_sat)
+                .ok_or_else(|| NodeError::Error("Spend amount overflows".into()))?;
+            let selected = self
+                .select_utxos(target, None)
+                .ok_or_else(|| NodeError::Error("Not enough funds to create transaction".into()))?;
+            self.check_relay_floor(&selected, amount_sat, estimated_fee_sat, recipient, options)?;
+            (selected, estimated_fee_sat)
+        };
 
-        let change_address = self
-            .utxos
-            .iter()
-            .min_by(|a, b| a.address.cmp(&b.address))
-            .ok_or_else(|| NodeError::Error("No UTXOs selected".into()))?
-            .address
-            .clone();
+        self.build_spend(
+            &selected_utxos,
+            amount_sat,
+            fee_sat,
+            recipient,
+            dry_run,
+            options,
+        )
+    }
 
-        if !dry_run {
-            let outpoints: Vec<_> = selected_utxos.iter().map(|u| u.utxo.outpoint).collect();
-            self.utxos.retain(|t| !outpoints.contains(&t.utxo.outpoint));
+    fn bump_fee(
+        &mut self,
+        tx: &Transaction,
+        prevouts: &[TxOut],
+        new_fee_sat: u64,
+    ) -> Result<(Transaction, [u8; 32]), NodeError> {
+        let input_sat: u64 = prevouts.iter().map(|p| p.value.to_sat()).sum();
+        let output_sat: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+        let current_fee_sat = input_sat
+            .checked_sub(output_sat)
+            .ok_or_else(|| NodeError::Error("Prevouts do not cover the outputs".into()))?;
+        if new_fee_sat <= current_fee_sat {
+            return Err(NodeError::Error(format!(
+                "Replacement fee {new_fee_sat} must exceed current fee {current_fee_sat}"
+            )));
         }
 
-        let change_sat = total_input_val - amount_sat - estimated_fee_sat;
+        // Take the increase out of the change outputs, last first, keeping each above dust.
+        let replaced_txid = tx.compute_txid();
+        let change_vouts = self.change_vouts(tx, prevouts);
+        let mut remaining = new_fee_sat - current_fee_sat;
+        let is_change = |vout: usize| u32::try_from(vout).is_ok_and(|v| change_vouts.contains(&v));
+        let mut replacement = tx.clone();
+        for (_, output) in replacement
+            .output
+            .iter_mut()
+            .enumerate()
+            .filter(|(vout, _)| is_change(*vout))
+            .rev()
+        {
+            let available = output.value.to_sat().saturating_sub(DUST + 1);
+            let taken = available.min(remaining);
+            output.value = Amount::from_sat(output.value.to_sat() - taken);
+            remaining -= taken;
+        }
+        if remaining > 0 {
+            return Err(NodeError::Error(
+                "Not enough change to cover the fee bump".into(),
+            ));
+        }
+        for input in &mut replacement.input {
+            input.witness = Witness::new();
+        }
 
-        let inputs: Vec<TxIn> = selected_utxos
-            .iter()
-            .map(|tracked_utxo| TxIn {
-                previous_output: tracked_utxo.utxo.outpoint,
-                script_sig: ScriptBuf::new(),
-                sequence: Sequence::ZERO,
-                witness: Witness::new(),
-            })
-            .collect();
-        let mut outputs = vec![TxOut {
-            value: Amount::from_sat(amount_sat),
-            script_pubkey: recipient.script_pubkey(),
-        }];
-
-        if change_sat > 546 {
-            outputs.push(TxOut {
-                value: Amount::from_sat(change_sat),
-                script_pubkey: change_address.script_pubkey(),
-            });
+        self.utxos.retain(|t| t.utxo.outpoint.txid != replaced_txid);
+        self.unconfirmed_utxos
+            .retain(|outpoint| outpoint.txid != replaced_txid);
+        self.track_change_outputs(&replacement, &change_vouts);
+        self.wallet_transactions.remove(&replaced_txid);
+        let replacement_txid = replacement.compute_txid();
+        for input in &replacement.input {
+            self.spending_txids
+                .insert(input.previous_output, replacement_txid);
         }
+        self.wallet_transactions
+            .insert(replacement_txid, (replacement.clone(), prevouts.to_vec()));
 
-        let tx = Transaction {
-            version: Version::TWO,
-            lock_time: LockTime::ZERO,
-            input: inputs,
-            output: outputs,
-        };
+        let sighash = self.first_input_sighash(&replacement, prevouts)?;
+        Ok((replacement, sighash))
+    }
 
-        if !dry_run && change_sat > 546 {
-            let change_vout: u32 = tx
-                .output
-                .iter()
-                .position(|o| o.script_pubkey == change_address.script_pubkey())
-                .unwrap()
-                .try_into()
-                .unwrap();
+    fn suggest_fee_bump(&self, txid: Txid, target_feerate: u64) -> Result<FeeBumpPlan, NodeError> {
+        let Some((tx, prevouts)) = self.wallet_transactions.get(&txid) else {
+            return Ok(FeeBumpPlan::Impossible {
+                reason: format!("{txid} is not an unconfirmed wallet transaction"),
+            });
+        };
 
-            self.utxos.push(TrackedUtxo {
-                utxo: Utxo {
-                    outpoint: bitcoin::OutPoint {
-                        txid: tx.compute_txid(),
-                        vout: change_vout,
-                    },
-                    value: Amount::from_sat(change_sat),
-                    script_pubkey: change_address.script_pubkey(),
-                },
-                address: change_address,
+        // Without every prevout the fee already paid is unknown, so none is counted.
+        let (vsize, fee_sat) = match Self::estimated_weight(tx, prevouts) {
+            Ok(weight) => {
+                let input_sat: u64 = prevouts.iter().map(|p| p.value.to_sat()).sum();
+                let output_sat: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+                (
+                    weight.to_vbytes_ceil(),
+                    input_sat.saturating_sub(output_sat),
+                )
+            }
+            Err(_) => (tx.vsize() as u64, 0),
+        };
+        let target_fee = Self::fee_at(target_feerate, vsize)?;
+        if fee_sat >= target_fee {
+            return Ok(FeeBumpPlan::Impossible {
+                reason: format!("{txid} already pays at least {target_feerate} sat/vB"),
             });
         }
 
-        let mut sighash_cache = SighashCache::new(&tx);
-
-        let utxo_to_sign = selected_utxos
-            .first()
-            .ok_or_else(|| NodeError::Error("No UTXOs to sign".into()))?;
+        // A replacement must also pay for its own relay on top of the fee it replaces.
+        let new_fee = Self::fee_at(INCREMENTAL_RELAY_FEE_SAT_PER_VB, vsize)?
+            .checked_add(fee_sat)
+            .ok_or_else(|| NodeError::Error(format!("Replacement fee for {txid} overflows")))?
+            .max(target_fee);
+        let rbf = match self.rbf_headroom(tx, prevouts) {
+            Ok(headroom) if new_fee - fee_sat <= headroom => {
+                return Ok(FeeBumpPlan::Rbf { new_fee });
+            }
+            Ok(_) => format!(
+                "its change cannot cover a {} sat fee increase",
+                new_fee - fee_sat
+            ),
+            Err(reason) => reason,
+        };
 
-        let sighash = if Self::is_p2wpkh(&utxo_to_sign.utxo.script_pubkey) {
-            sighash_cache
-                .p2wpkh_signature_hash(
-                    0,
-                    &utxo_to_sign.utxo.script_pubkey,
-                    utxo_to_sign.utxo.value,
-                    EcdsaSighashType::All,
-                )
-                .map_err(|e| NodeError::Error(format!("Failed to calculate sighash: {e}")))?
-                .to_byte_array()
-        } else if Self::is_p2tr(&utxo_to_sign.utxo.script_pubkey) {
-            let prevouts: Vec<TxOut> = selected_utxos
-                .iter()
-                .map(|u| bitcoin::TxOut {
-                    value: u.utxo.value,
-                    script_pubkey: u.utxo.script_pubkey.clone(),
-                })
-                .collect();
-            sighash_cache
-                .taproot_key_spend_signature_hash(
-                    0,
-                    &Prevouts::All(&prevouts),
-                    bitcoin::TapSighashType::All,
+        let cpfp = match self.cpfp_candidate(txid) {
+            Ok((output, child_vsize)) => {
+                let package_vsize = vsize
+                    .checked_add(child_vsize)
+                    .ok_or_else(|| NodeError::Error(format!("Package size of {txid} overflows")))?;
+                let child_fee =
+                    Self::fee_at(target_feerate, package_vsize)?.saturating_sub(fee_sat);
+                if output.utxo.value.to_sat() > child_fee.saturating_add(DUST) {
+                    return Ok(FeeBumpPlan::Cpfp { child_fee });
+                }
+                format!(
+                    "its {} sat wallet output cannot pay a {child_fee} sat child fee",
+                    output.utxo.value.to_sat()
                 )
-                .map_err(|e| NodeError::Error(format!("Failed to calculate sighash: {e}")))?
-                .to_byte_array()
-        } else {
-            return Err(NodeError::Error("Unsupported script type".into()));
+            }
+            Err(reason) => reason,
         };
-
-        Ok((tx, sighash))
+        Ok(FeeBumpPlan::Impossible {
+            reason: format!("Cannot replace {txid}: {rbf}; cannot spend a child: {cpfp}"),
+        })
     }
 
     fn sign(
@@ -278,36 +1363,100 @@ impl Wallet for TaprootWallet {
         // For P2WPKH, we need to create a witness signature
         let secp = bitcoin::key::Secp256k1::new();
 
-        // Create the signature using the properly calculated sighash
-        let message = bitcoin::secp256k1::Message::from_digest(sighash);
-        let signature = secp.sign_ecdsa(&message, &private_key.inner);
-
-        // Create witness with signature + sighash type (0x01 = SIGHASH_ALL)
-        let mut sig_bytes = signature.serialize_der().to_vec();
-        sig_bytes.push(0x01); // SIGHASH_ALL
-
         let compressed_pubkey = bitcoin::CompressedPublicKey::from_private_key(&secp, private_key)
             .expect("Failed to get compressed public key");
+        let own_script = ScriptBuf::new_p2wpkh(&compressed_pubkey.wpubkey_hash());
 
-        let mut witness = Witness::new();
-        witness.push(sig_bytes);
-        witness.push(compressed_pubkey.to_bytes());
+        let witness_for = |sighash: [u8; 32]| {
+            let message = bitcoin::secp256k1::Message::from_digest(sighash);
+            let signature = secp.sign_ecdsa(&message, &private_key.inner);
+
+            // Create witness with signature + sighash type (0x01 = SIGHASH_ALL)
+            let mut sig_bytes = signature.serialize_der().to_vec();
+            sig_bytes.push(0x01); // SIGHASH_ALL
+
+            let mut witness = Witness::new();
+            witness.push(sig_bytes);
+            witness.push(compressed_pubkey.to_bytes());
+            witness
+        };
 
         let mut response_tx = tx.clone();
-        // Add witness to the first (and only) input
-        if let Some(input) = response_tx.input.first_mut() {
-            input.witness = witness;
+        // Spends built by this wallet know every prevout, so each input of the key gets a
+        // signature over its own sighash, all taken from one shared cache.
+        let sighashes =
+            self.wallet_transactions
+                .get(&tx.compute_txid())
+                .and_then(|(_, prevouts)| {
+                    self.input_sighashes(tx, prevouts)
+                        .ok()
+                        .map(|sighashes| (prevouts, sighashes))
+                });
+        match sighashes {
+            Some((prevouts, sighashes)) => {
+                for ((input, prevout), sighash) in
+                    response_tx.input.iter_mut().zip(prevouts).zip(sighashes)
+                {
+                    if prevout.script_pubkey == own_script {
+                        input.witness = witness_for(sighash);
+                    }
+                }
+            }
+            // Without the prevouts only the first input's sighash is known.
+            None => {
+                if let Some(input) = response_tx.input.first_mut() {
+                    input.witness = witness_for(sighash);
+                }
+            }
         }
 
         response_tx
     }
 
     fn ingest_external_tx(&mut self, tx: &Transaction) -> Result<(), NodeError> {
+        let prevouts: Vec<TxOut> = tx
+            .input
+            .iter()
+            .filter_map(|input| {
+                self.utxos
+                    .iter()
+                    .find(|t| t.utxo.outpoint == input.previous_output)
+                    .map(|t| TxOut {
+                        value: t.utxo.value,
+                        script_pubkey: t.utxo.script_pubkey.clone(),
+                    })
+            })
+            .collect();
+        let pays_wallet = tx.output.iter().any(|out| {
+            self.addresses
+                .iter()
+                .any(|a| a.script_pubkey() == out.script_pubkey)
+        });
+        // Our own spends were recorded with their prevouts when built, which are gone by now.
+        if pays_wallet || !prevouts.is_empty() {
+            self.wallet_transactions
+                .entry(tx.compute_txid())
+                .or_insert_with(|| (tx.clone(), prevouts));
+        }
+
+        let txid = tx.compute_txid();
+        for input in &tx.input {
+            if self
+                .utxos
+                .iter()
+                .any(|t| t.utxo.outpoint == input.previous_output)
+            {
+                self.spending_txids.insert(input.previous_output, txid);
+            }
+        }
         self.utxos.retain(|t| {
             !tx.input
                 .iter()
                 .any(|i| i.previous_output == t.utxo.outpoint)
         });
+        for input in &tx.input {
+            self.spend_paths.remove(&input.previous_output);
+        }
 
         for (idx, out) in tx.output.iter().enumerate() {
             if let Some(addr) = self
@@ -315,12 +1464,28 @@ impl Wallet for TaprootWallet {
                 .iter()
                 .find(|a| a.script_pubkey() == out.script_pubkey)
             {
+                let held = self.utxos.iter().filter(|u| &u.address == addr).count();
+                if !self.admits_utxo(addr, held, out.value) {
+                    continue;
+                }
+                let outpoint = bitcoin::OutPoint {
+                    txid: tx.compute_txid(),
+                    vout: u32::try_from(idx).unwrap(),
+                };
+                let Some(spend_path) = self.spend_path_for(&out.script_pubkey) else {
+                    tracing::warn!("Not tracking {outpoint}: the wallet cannot sign for {addr}");
+                    continue;
+                };
+                self.spend_paths.insert(outpoint, spend_path);
+                // The mining height is unknown here, so maturity counts from the current tip
+                // until the next refresh reads it from the oracle.
+                if tx.is_coinbase() {
+                    self.mark_coinbase(outpoint, self.tip_height);
+                }
+                self.coinbase_checked.insert(outpoint);
                 self.utxos.push(TrackedUtxo {
                     utxo: Utxo {
-                        outpoint: bitcoin::OutPoint {
-                            txid: tx.compute_txid(),
-                            vout: u32::try_from(idx).unwrap(),
-                        },
+                        outpoint,
                         value: out.value,
                         script_pubkey: out.script_pubkey.clone(),
                     },
@@ -331,10 +1496,73 @@ impl Wallet for TaprootWallet {
         Ok(())
     }
 
+    fn lock_utxo(&mut self, outpoint: bitcoin::OutPoint) -> bool {
+        self.locked_utxos.insert(outpoint)
+    }
+
+    fn unlock_utxo(&mut self, outpoint: &bitcoin::OutPoint) -> bool {
+        self.locked_utxos.remove(outpoint)
+    }
+
+    fn release_spend(&mut self, txid: Txid) -> bool {
+        let Some((tx, prevouts)) = self.wallet_transactions.remove(&txid) else {
+            return false;
+        };
+
+        self.utxos.retain(|t| t.utxo.outpoint.txid != txid);
+        self.unconfirmed_utxos
+            .retain(|outpoint| outpoint.txid != txid);
+        self.coinbase_checked
+            .retain(|outpoint| outpoint.txid != txid);
+        for (input, prevout) in tx.input.iter().zip(prevouts) {
+            let outpoint = input.previous_output;
+            if self.spending_txids.get(&outpoint) != Some(&txid) {
+                continue;
+            }
+            self.spending_txids.remove(&outpoint);
+            self.spent_utxos.remove(&outpoint);
+            if let Ok(address) = Address::from_script(&prevout.script_pubkey, self.network) {
+                self.utxos.push(TrackedUtxo {
+                    utxo: Utxo {
+                        outpoint,
+                        value: prevout.value,
+                        script_pubkey: prevout.script_pubkey,
+                    },
+                    address,
+                });
+            }
+        }
+        true
+    }
+
     fn get_utxos(&self) -> Vec<TrackedUtxo> {
         self.utxos.clone()
     }
 
+    fn get_utxo_spend_status(&self, outpoint: bitcoin::OutPoint) -> UtxoStatus {
+        // A tracked outpoint is reported as such even if a spend of it was once recorded,
+        // since the wallet only tracks it again once that spend has been abandoned.
+        if self.utxos.iter().any(|t| t.utxo.outpoint == outpoint) {
+            if self.locked_utxos.contains(&outpoint) {
+                UtxoStatus::Locked
+            } else {
+                UtxoStatus::Unspent
+            }
+        } else {
+            self.spending_txids
+                .get(&outpoint)
+                .map_or(UtxoStatus::Unknown, |&txid| UtxoStatus::Spent { txid })
+        }
+    }
+
+    fn spendable_balance(&self) -> u64 {
+        self.utxos
+            .iter()
+            .filter(|u| self.is_spendable(u))
+            .map(|u| u.utxo.value.to_sat())
+            .sum()
+    }
+
     #[allow(
         clippy::cast_possible_truncation,
         clippy::cast_sign_loss,
@@ -381,13 +1609,18 @@ impl Wallet for TaprootWallet {
 
         let fee_rate = feerate_sat_per_vb as f64;
         let total_payout: u64 = payouts.iter().map(|(_, v)| *v).sum();
-        let utxos = &self.utxos; // shorthand
+        let mut candidates: Vec<TrackedUtxo> = self
+            .utxos
+            .iter()
+            .filter(|u| self.is_spendable(u))
+            .filter(|u| Self::effective_value(u, feerate_sat_per_vb) > 0)
+            .cloned()
+            .collect();
 
-        if utxos.is_empty() {
+        if candidates.is_empty() {
             return Err(NodeError::Error("Wallet has no spendable UTXOs".into()));
         }
 
-        let mut candidates = utxos.clone();
         candidates.sort_by(|a, b| b.utxo.value.cmp(&a.utxo.value));
 
         let mut chosen: Option<Vec<TrackedUtxo>> = None;
@@ -1,15 +1,16 @@
 use crate::oracle::Oracle;
+use crate::rate_limit::{RequestLimiter, parse_retry_after};
 use bitcoin::{
-    Address, Amount, Network, OutPoint, Transaction, TxIn, TxOut, Txid, absolute::LockTime,
-    consensus,
+    Address, Amount, BlockHash, Network, OutPoint, Transaction, TxIn, TxOut, Txid,
+    absolute::LockTime, consensus,
 };
 use esplora_client::{AsyncClient, Builder};
-use std::{collections::HashSet, str::FromStr};
+use std::{collections::HashSet, future::Future, str::FromStr};
 use tokio::{
     sync::broadcast,
     time::{Duration, sleep},
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use types::{
     errors::NodeError,
     intents::DepositIntent,
@@ -24,6 +25,9 @@ pub struct EsploraOracle {
     pub deposit_intent_rx: Option<broadcast::Sender<DepositIntent>>,
     pub confirmation_depth: u32,
     pub monitor_start_block: u32,
+    pub network: Network,
+    /// Caps the requests in flight to the Esplora server, across every clone of the oracle.
+    pub limiter: RequestLimiter,
 }
 
 impl EsploraOracle {
@@ -52,8 +56,112 @@ impl EsploraOracle {
             deposit_intent_rx,
             confirmation_depth,
             monitor_start_block,
+            network,
+            limiter: RequestLimiter::default(),
         }
     }
+
+    /// Oracle querying the Esplora API at `url` instead of the default for `network`, used
+    /// for additional fee backends.
+    pub fn with_url(network: Network, url: &str) -> Result<Self, NodeError> {
+        let client = Builder::new(url)
+            .build_async()
+            .map_err(|e| NodeError::Error(format!("Invalid Esplora URL {url}: {e}")))?;
+        Ok(Self {
+            client,
+            tx_channel: broadcast::channel(1).0,
+            deposit_intent_rx: None,
+            confirmation_depth: 0,
+            monitor_start_block: 0,
+            network,
+            limiter: RequestLimiter::default(),
+        })
+    }
+
+    /// Routes requests through `limiter`, e.g. to share its slots with other oracles.
+    #[must_use]
+    pub fn with_request_limiter(mut self, limiter: RequestLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Sends a request to the Esplora server once the limiter has a slot for it, retrying it
+    /// while the server answers HTTP 429, after the server's `Retry-After` when it gives one.
+    /// Other failures are reported under `context`.
+    async fn request<T, F, Fut>(&self, context: &str, mut call: F) -> Result<T, NodeError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, esplora_client::Error>>,
+    {
+        self.limiter
+            .run(context, || {
+                let response = call();
+                async move {
+                    match response.await {
+                        Ok(value) => Ok(value),
+                        Err(esplora_client::Error::HttpResponse { status: 429, .. }) => {
+                            Err(NodeError::OracleRateLimited {
+                                retry_after_secs: self.retry_after().await,
+                            })
+                        }
+                        Err(e) => Err(NodeError::Error(format!("{context}: {e}"))),
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Seconds the server asks rate-limited clients to wait. The Esplora client drops response
+    /// headers, so this asks again for the cheapest resource and reads the `Retry-After` of
+    /// its answer; `None` when that is not a 429 or carries no delay in seconds.
+    async fn retry_after(&self) -> Option<u64> {
+        let response = self
+            .client
+            .client()
+            .get(format!("{}/blocks/tip/height", self.client.url()))
+            .send()
+            .await
+            .ok()?;
+        if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return None;
+        }
+        parse_retry_after(
+            response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)?
+                .to_str()
+                .ok()?,
+        )
+    }
+
+    /// Starts watching the address of a gossiped deposit intent, or stops once the intent
+    /// has expired. An address that is malformed or for another network is left out and
+    /// reported, so one bad intent cannot take the monitor down.
+    pub fn track_deposit_intent(
+        &self,
+        addresses: &mut HashSet<Address>,
+        deposit_intent: &DepositIntent,
+    ) -> Result<(), NodeError> {
+        let address = deposit_intent.parse_deposit_address(self.network)?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        if deposit_intent.is_expired(now) {
+            if addresses.remove(&address) {
+                info!("Stopped monitoring expired deposit address {}", address);
+            }
+        } else {
+            info!(
+                "Received new deposit address to monitor: {}",
+                &deposit_intent.deposit_address
+            );
+            if addresses.insert(address) {
+                info!("Now polling {} addresses.", addresses.len());
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -65,10 +173,10 @@ impl Oracle for EsploraOracle {
         tx_hash: Txid,
     ) -> Result<bool, NodeError> {
         let tx = self
-            .client
-            .get_tx_info(&tx_hash)
-            .await
-            .map_err(|e| NodeError::Error(e.to_string()))?;
+            .request("Cannot retrieve transaction", || {
+                self.client.get_tx_info(&tx_hash)
+            })
+            .await?;
 
         let tx = tx.ok_or_else(|| NodeError::Error("Transaction not found".to_string()))?;
 
@@ -92,10 +200,10 @@ impl Oracle for EsploraOracle {
 
     async fn get_current_fee_per_vb(&self, priority: Option<u16>) -> Result<f64, NodeError> {
         let fee = self
-            .client
-            .get_fee_estimates()
-            .await
-            .map_err(|e| NodeError::Error(e.to_string()))?;
+            .request("Cannot retrieve fee estimates", || {
+                self.client.get_fee_estimates()
+            })
+            .await?;
 
         let priority = priority.unwrap_or(3);
 
@@ -106,6 +214,21 @@ impl Oracle for EsploraOracle {
         Ok(*fee)
     }
 
+    /// Esplora exposes no relay policy, but its estimate for the longest confirmation target
+    /// bottoms out at the mempool's minimum feerate.
+    async fn get_min_relay_fee_per_vb(&self) -> Result<f64, NodeError> {
+        let fees = self
+            .request("Cannot retrieve fee estimates", || {
+                self.client.get_fee_estimates()
+            })
+            .await?;
+
+        fees.iter()
+            .max_by_key(|(target, _)| **target)
+            .map(|(_, fee)| *fee)
+            .ok_or_else(|| NodeError::Error("Fee not found".to_string()))
+    }
+
     async fn refresh_utxos(
         &self,
         address: Address,
@@ -119,12 +242,10 @@ impl Oracle for EsploraOracle {
 
         for _ in 0..number_pages {
             let address_txs = self
-                .client
-                .scripthash_txs(&script, last_seen_txid)
-                .await
-                .map_err(|_| {
-                    NodeError::Error("Cannot retrieve transactions for address".to_string())
-                })?;
+                .request("Cannot retrieve transactions for address", || {
+                    self.client.scripthash_txs(&script, last_seen_txid)
+                })
+                .await?;
 
             if address_txs.is_empty() {
                 break;
@@ -132,10 +253,22 @@ impl Oracle for EsploraOracle {
 
             last_seen_txid = Some(address_txs.last().unwrap().txid);
             for tx in address_txs {
-                let Some(full_tx) = self.client.get_tx(&tx.txid).await.ok().flatten() else {
+                let Some(full_tx) = self
+                    .request("Cannot retrieve transaction", || {
+                        self.client.get_tx(&tx.txid)
+                    })
+                    .await
+                    .ok()
+                    .flatten()
+                else {
                     continue;
                 };
-                let Ok(tx_status) = self.client.get_tx_status(&tx.txid).await else {
+                let Ok(tx_status) = self
+                    .request("Cannot retrieve transaction status", || {
+                        self.client.get_tx_status(&tx.txid)
+                    })
+                    .await
+                else {
                     continue;
                 };
                 if !allow_unconfirmed && !tx_status.confirmed {
@@ -146,8 +279,11 @@ impl Oracle for EsploraOracle {
                     if output.script_pubkey != script {
                         continue;
                     }
-                    let Ok(Some(output_status)) =
-                        self.client.get_output_status(&tx.txid, vout as u64).await
+                    let Ok(Some(output_status)) = self
+                        .request("Cannot retrieve output status", || {
+                            self.client.get_output_status(&tx.txid, vout as u64)
+                        })
+                        .await
                     else {
                         continue;
                     };
@@ -179,24 +315,43 @@ impl Oracle for EsploraOracle {
         let tx_hex = hex::encode(&tx_bytes);
 
         // Broadcast the transaction
-        self.client
-            .broadcast(tx)
-            .await
-            .map_err(|_| NodeError::Error("Failed to broadcast transaction".to_string()))?;
+        self.request("Failed to broadcast transaction", || {
+            self.client.broadcast(tx)
+        })
+        .await?;
 
         Ok(tx_hex)
     }
 
+    async fn is_transaction_confirmed(&self, txid: Txid) -> Result<bool, NodeError> {
+        let status = self
+            .request("Cannot retrieve transaction status", || {
+                self.client.get_tx_status(&txid)
+            })
+            .await?;
+        Ok(status.confirmed)
+    }
+
+    async fn get_transaction_height(&self, txid: Txid) -> Result<Option<u32>, NodeError> {
+        let status = self
+            .request("Cannot retrieve transaction status", || {
+                self.client.get_tx_status(&txid)
+            })
+            .await?;
+        Ok(status.block_height.filter(|_| status.confirmed))
+    }
+
     async fn get_confirmed_transactions(
         &self,
         addresses: Vec<Address>,
         min_height: u32,
         max_height: u32,
     ) -> Result<Vec<Transaction>, NodeError> {
-        let blockchain_height =
-            self.client.get_height().await.map_err(|_| {
-                NodeError::Error("Cannot retrieve height of blockchain".to_string())
-            })?;
+        let blockchain_height = self
+            .request("Cannot retrieve height of blockchain", || {
+                self.client.get_height()
+            })
+            .await?;
 
         let new_max_height = max_height.min(blockchain_height - self.confirmation_depth);
         let mut confirmed_txs = Vec::new();
@@ -205,13 +360,12 @@ impl Oracle for EsploraOracle {
             let mut last_seen_txid = None;
 
             loop {
+                let script = address.script_pubkey();
                 let address_txs = self
-                    .client
-                    .scripthash_txs(&address.script_pubkey(), last_seen_txid)
-                    .await
-                    .map_err(|_| {
-                        NodeError::Error("Cannot retrieve transactions for address".to_string())
-                    })?;
+                    .request("Cannot retrieve transactions for address", || {
+                        self.client.scripthash_txs(&script, last_seen_txid)
+                    })
+                    .await?;
 
                 if address_txs.is_empty() {
                     break;
@@ -225,7 +379,12 @@ impl Oracle for EsploraOracle {
                 for tx in address_txs {
                     if let Some(block_height) = tx.status.block_height {
                         if block_height >= min_height && block_height <= new_max_height {
-                            if let Ok(full_tx) = self.client.get_tx(&tx.txid).await {
+                            if let Ok(full_tx) = self
+                                .request("Cannot retrieve transaction", || {
+                                    self.client.get_tx(&tx.txid)
+                                })
+                                .await
+                            {
                                 if let Ok(bitcoin_tx) =
                                     consensus::deserialize(&consensus::serialize(&full_tx.unwrap()))
                                 {
@@ -251,10 +410,15 @@ impl Oracle for EsploraOracle {
     async fn poll_new_transactions(&mut self, addresses: Vec<Address>) {
         let confirmation_depth = self.confirmation_depth;
 
-        let mut last_confirmed_height = match self.client.get_height().await {
+        let mut last_confirmed_height = match self
+            .request("Cannot retrieve height of blockchain", || {
+                self.client.get_height()
+            })
+            .await
+        {
             Ok(height) => height - confirmation_depth,
             Err(e) => {
-                error!("Cannot retrieve height of blockchain: {}", e);
+                error!("{}", e);
                 return;
             }
         };
@@ -272,10 +436,15 @@ impl Oracle for EsploraOracle {
         loop {
             tokio::select! {
                 () = sleep(Duration::from_secs(30)) => {
-                    let current_height = match self.client.get_height().await {
+                    let current_height = match self
+                        .request("Cannot retrieve height of blockchain", || {
+                            self.client.get_height()
+                        })
+                        .await
+                    {
                         Ok(height) => height,
                         Err(e) => {
-                            error!("Cannot retrieve height of blockchain: {}", e);
+                            error!("{}", e);
                             continue;
                         }
                     };
@@ -328,13 +497,11 @@ impl Oracle for EsploraOracle {
                     }
                 }
                 Ok(deposit_intent) = deposit_intent_rx.recv() => {
-                    info!("Received new deposit address to monitor: {}", &deposit_intent.deposit_address);
-                    if addresses.insert(
-                        Address::from_str(&deposit_intent.deposit_address)
-                            .unwrap()
-                            .assume_checked()
-                    ) {
-                        info!("Now polling {} addresses.", addresses.len());
+                    if let Err(e) = self.track_deposit_intent(&mut addresses, &deposit_intent) {
+                        warn!(
+                            "Skipping deposit intent {}: {}",
+                            deposit_intent.deposit_tracking_id, e
+                        );
                     }
                 }
             }
@@ -342,20 +509,28 @@ impl Oracle for EsploraOracle {
     }
 
     async fn get_latest_block_height(&self) -> Result<u32, NodeError> {
-        let height =
-            self.client.get_height().await.map_err(|_| {
-                NodeError::Error("Cannot retrieve height of blockchain".to_string())
-            })?;
-        Ok(height)
+        self.request("Cannot retrieve height of blockchain", || {
+            self.client.get_height()
+        })
+        .await
+    }
+
+    async fn get_block_hash(&self, height: u32) -> Result<BlockHash, NodeError> {
+        self.request(
+            &format!("Cannot retrieve block hash at height {height}"),
+            || self.client.get_block_hash(height),
+        )
+        .await
     }
 
     async fn get_transaction_by_address(&self, tx_id: &str) -> Result<Transaction, NodeError> {
         let tx_hash = Txid::from_str(tx_id)
             .map_err(|_| NodeError::Error("Invalid transaction hash".to_string()))?;
-        let tx =
-            self.client.get_tx_info(&tx_hash).await.map_err(|_| {
-                NodeError::Error("Cannot retrieve transaction by address".to_string())
-            })?;
+        let tx = self
+            .request("Cannot retrieve transaction by address", || {
+                self.client.get_tx_info(&tx_hash)
+            })
+            .await?;
         let tx = tx.ok_or_else(|| NodeError::Error("Transaction not found".to_string()))?;
         let transaction = Transaction {
             version: bitcoin::transaction::Version(tx.version),
@@ -1,3 +1,6 @@
 pub mod esplora;
+pub mod median;
 pub mod mock;
 pub mod oracle;
+pub mod rate_limit;
+pub mod timeout;
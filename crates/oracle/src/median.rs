@@ -0,0 +1,146 @@
+use crate::oracle::Oracle;
+use bitcoin::{Address, BlockHash, Transaction, Txid};
+use tokio::task::JoinSet;
+use tracing::warn;
+use types::{errors::NodeError, utxo::Utxo};
+
+/// Estimates further than this factor above or below the median of all answers are
+/// treated as outliers and left out of the final median.
+pub const MAX_FEE_DEVIATION_FACTOR: f64 = 2.0;
+
+/// Oracle that asks several fee backends for a feerate and answers with the median, so a
+/// single faulty or manipulated source cannot set the fee on its own.
+///
+/// Backends that error are skipped, as are outliers per [`MAX_FEE_DEVIATION_FACTOR`].
+/// Every other query goes to `inner` unchanged.
+#[derive(Clone)]
+pub struct MedianFeeOracle {
+    pub inner: Box<dyn Oracle>,
+    pub fee_backends: Vec<Box<dyn Oracle>>,
+}
+
+impl MedianFeeOracle {
+    #[must_use]
+    pub fn new(inner: Box<dyn Oracle>, fee_backends: Vec<Box<dyn Oracle>>) -> Self {
+        Self {
+            inner,
+            fee_backends,
+        }
+    }
+}
+
+/// Median of `rates` after dropping outliers, or `None` if there are no usable rates.
+#[must_use]
+pub fn median_fee_rate(mut rates: Vec<f64>) -> Option<f64> {
+    rates.retain(|rate| rate.is_finite() && *rate > 0.0);
+    let center = median(&mut rates)?;
+    rates.retain(|rate| {
+        *rate <= center * MAX_FEE_DEVIATION_FACTOR && *rate >= center / MAX_FEE_DEVIATION_FACTOR
+    });
+    median(&mut rates)
+}
+
+fn median(rates: &mut [f64]) -> Option<f64> {
+    rates.sort_by(f64::total_cmp);
+    let mid = rates.len() / 2;
+    match rates.len() {
+        0 => None,
+        len if len.is_multiple_of(2) => Some(f64::midpoint(rates[mid - 1], rates[mid])),
+        _ => Some(rates[mid]),
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for MedianFeeOracle {
+    async fn validate_transaction(
+        &self,
+        address: &str,
+        amount: u64,
+        tx_hash: Txid,
+    ) -> Result<bool, NodeError> {
+        self.inner
+            .validate_transaction(address, amount, tx_hash)
+            .await
+    }
+
+    async fn get_transaction_by_address(&self, tx_id: &str) -> Result<Transaction, NodeError> {
+        self.inner.get_transaction_by_address(tx_id).await
+    }
+
+    async fn get_current_fee_per_vb(&self, priority: Option<u16>) -> Result<f64, NodeError> {
+        if self.fee_backends.is_empty() {
+            return self.inner.get_current_fee_per_vb(priority).await;
+        }
+
+        let mut queries = JoinSet::new();
+        for backend in &self.fee_backends {
+            let backend = backend.clone();
+            queries.spawn(async move { backend.get_current_fee_per_vb(priority).await });
+        }
+
+        let mut rates = Vec::with_capacity(self.fee_backends.len());
+        while let Some(result) = queries.join_next().await {
+            match result {
+                Ok(Ok(rate)) => rates.push(rate),
+                Ok(Err(e)) => warn!("Fee backend failed to estimate feerate: {}", e),
+                Err(e) => warn!("Fee backend query did not complete: {}", e),
+            }
+        }
+
+        median_fee_rate(rates)
+            .ok_or_else(|| NodeError::Error("No fee backend returned a feerate".to_string()))
+    }
+
+    /// Relay policy belongs to the backend transactions are broadcast through, so this is
+    /// not put to a vote.
+    async fn get_min_relay_fee_per_vb(&self) -> Result<f64, NodeError> {
+        self.inner.get_min_relay_fee_per_vb().await
+    }
+
+    async fn refresh_utxos(
+        &self,
+        address: Address,
+        number_pages: u32,
+        start_transactions: Option<Txid>,
+        allow_unconfirmed: bool,
+    ) -> Result<Vec<Utxo>, NodeError> {
+        self.inner
+            .refresh_utxos(address, number_pages, start_transactions, allow_unconfirmed)
+            .await
+    }
+
+    async fn broadcast_transaction(&self, tx: &Transaction) -> Result<String, NodeError> {
+        self.inner.broadcast_transaction(tx).await
+    }
+
+    async fn is_transaction_confirmed(&self, txid: Txid) -> Result<bool, NodeError> {
+        self.inner.is_transaction_confirmed(txid).await
+    }
+
+    async fn get_transaction_height(&self, txid: Txid) -> Result<Option<u32>, NodeError> {
+        self.inner.get_transaction_height(txid).await
+    }
+
+    async fn get_confirmed_transactions(
+        &self,
+        addresses: Vec<Address>,
+        min_height: u32,
+        max_height: u32,
+    ) -> Result<Vec<Transaction>, NodeError> {
+        self.inner
+            .get_confirmed_transactions(addresses, min_height, max_height)
+            .await
+    }
+
+    async fn poll_new_transactions(&mut self, addresses: Vec<Address>) {
+        self.inner.poll_new_transactions(addresses).await;
+    }
+
+    async fn get_latest_block_height(&self) -> Result<u32, NodeError> {
+        self.inner.get_latest_block_height().await
+    }
+
+    async fn get_block_hash(&self, height: u32) -> Result<BlockHash, NodeError> {
+        self.inner.get_block_hash(height).await
+    }
+}
@@ -1,11 +1,18 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU32, AtomicU64, Ordering},
+    },
+};
 
 use crate::oracle::Oracle;
 use bitcoin::{
-    Address, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
+    Address, Amount, BlockHash, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
     absolute::LockTime, hashes::Hash, transaction::Version,
 };
-use tokio::sync::broadcast;
+use tokio::{sync::broadcast, time::Duration};
 use tracing::{error, info};
 use types::{
     errors::NodeError,
@@ -20,6 +27,22 @@ pub struct MockOracle {
     pub transactions: HashMap<String, (String, u64, bool)>,
     pub tx_channel: broadcast::Sender<NetworkEvent>,
     pub deposit_intent_rx: Option<broadcast::Sender<DepositIntent>>,
+    // Chain view shared by every clone so tests can drive what the node observes
+    pub broadcast_txs: Arc<Mutex<Vec<Transaction>>>,
+    pub confirmed_txids: Arc<Mutex<HashSet<Txid>>>,
+    pub block_height: Arc<AtomicU32>,
+    /// UTXOs reported by `refresh_utxos` once set; until then it returns fixed dummy outputs.
+    pub reported_utxos: Arc<Mutex<Option<Vec<Utxo>>>>,
+    /// Milliseconds the wallet and fee queries wait before answering, to mimic a slow backend.
+    pub response_delay_ms: Arc<AtomicU64>,
+    /// Block hashes overriding the default one derived from each height.
+    pub block_hashes: Arc<Mutex<HashMap<u32, BlockHash>>>,
+    /// Feerates by confirmation target overriding the fixed defaults.
+    pub fee_estimates: Arc<Mutex<HashMap<u16, f64>>>,
+    /// Minimum relay feerate reported by `get_min_relay_fee_per_vb`.
+    pub min_relay_fee: Arc<Mutex<f64>>,
+    /// Transactions looked up by id, with the height that mined them, instead of the dummy.
+    pub known_transactions: Arc<Mutex<HashMap<Txid, (Transaction, Option<u32>)>>>,
 }
 
 impl MockOracle {
@@ -32,9 +55,97 @@ impl MockOracle {
             transactions: HashMap::new(),
             tx_channel,
             deposit_intent_rx,
+            broadcast_txs: Arc::new(Mutex::new(Vec::new())),
+            confirmed_txids: Arc::new(Mutex::new(HashSet::new())),
+            block_height: Arc::new(AtomicU32::new(0)),
+            reported_utxos: Arc::new(Mutex::new(None)),
+            response_delay_ms: Arc::new(AtomicU64::new(0)),
+            block_hashes: Arc::new(Mutex::new(HashMap::new())),
+            fee_estimates: Arc::new(Mutex::new(HashMap::new())),
+            min_relay_fee: Arc::new(Mutex::new(1.0)),
+            known_transactions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Transactions passed to `broadcast_transaction`, oldest first.
+    #[must_use]
+    pub fn broadcast_transactions(&self) -> Vec<Transaction> {
+        self.broadcast_txs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Broadcast transactions stay unconfirmed until marked here.
+    pub fn confirm_transaction(&self, txid: Txid) {
+        self.confirmed_txids
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(txid);
+    }
+
+    /// Makes `refresh_utxos` report, for each address, the given UTXOs paying to it.
+    pub fn set_utxos(&self, utxos: Vec<Utxo>) {
+        *self
+            .reported_utxos
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(utxos);
+    }
+
+    /// Makes `get_current_fee_per_vb` answer from `estimates` for the targets it contains.
+    pub fn set_fee_estimates(&self, estimates: HashMap<u16, f64>) {
+        *self
+            .fee_estimates
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = estimates;
+    }
+
+    pub fn set_min_relay_fee(&self, sat_per_vb: f64) {
+        *self
+            .min_relay_fee
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = sat_per_vb;
+    }
+
+    pub fn set_response_delay(&self, delay: Duration) {
+        self.response_delay_ms.store(
+            u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+            Ordering::SeqCst,
+        );
+    }
+
+    async fn delay_response(&self) {
+        let delay_ms = self.response_delay_ms.load(Ordering::SeqCst);
+        if delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        }
+    }
+
+    /// Replaces the blocks at `heights` with ones of a competing branch tagged `branch`.
+    pub fn reorg_blocks(&self, heights: std::ops::RangeInclusive<u32>, branch: u8) {
+        let mut hashes = self
+            .block_hashes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for height in heights {
+            let mut bytes = [branch; 32];
+            bytes[..4].copy_from_slice(&height.to_be_bytes());
+            hashes.insert(height, BlockHash::from_byte_array(bytes));
         }
     }
 
+    /// Serves `tx` when it is looked up by id, as mined at `height` if given.
+    pub fn add_known_transaction(&self, tx: Transaction, height: Option<u32>) {
+        self.known_transactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(tx.compute_txid(), (tx, height));
+    }
+
+    pub fn set_block_height(&self, height: u32) {
+        self.block_height.store(height, Ordering::SeqCst);
+    }
+
     pub fn add_transaction(&mut self, tx_hash: Txid, address: String, amount: u64, is_valid: bool) {
         self.transactions
             .insert(tx_hash.to_string(), (address, amount, is_valid));
@@ -76,20 +187,51 @@ impl Oracle for MockOracle {
     }
 
     async fn get_current_fee_per_vb(&self, priority: Option<u16>) -> Result<f64, NodeError> {
-        if priority.is_some() {
+        self.delay_response().await;
+        if let Some(fee) = priority.and_then(|target| {
+            self.fee_estimates
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&target)
+                .copied()
+        }) {
+            Ok(fee)
+        } else if priority.is_some() {
             Ok(100.0)
         } else {
             Ok(10.0)
         }
     }
 
+    async fn get_min_relay_fee_per_vb(&self) -> Result<f64, NodeError> {
+        self.delay_response().await;
+        Ok(*self
+            .min_relay_fee
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner))
+    }
+
     async fn refresh_utxos(
         &self,
-        _address: Address,
+        address: Address,
         _number_pages: u32,
         _start_transactions: Option<Txid>,
         _allow_unconfirmed: bool,
     ) -> Result<Vec<Utxo>, NodeError> {
+        self.delay_response().await;
+        if let Some(utxos) = self
+            .reported_utxos
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            return Ok(utxos
+                .iter()
+                .filter(|u| u.script_pubkey == address.script_pubkey())
+                .cloned()
+                .collect());
+        }
+
         Ok(vec![
             Utxo {
                 outpoint: OutPoint::new(
@@ -127,10 +269,40 @@ impl Oracle for MockOracle {
         ])
     }
 
-    async fn broadcast_transaction(&self, _tx: &bitcoin::Transaction) -> Result<String, NodeError> {
+    async fn broadcast_transaction(&self, tx: &bitcoin::Transaction) -> Result<String, NodeError> {
+        self.delay_response().await;
+        self.broadcast_txs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(tx.clone());
         Ok(String::new())
     }
 
+    async fn is_transaction_confirmed(&self, txid: Txid) -> Result<bool, NodeError> {
+        Ok(self
+            .confirmed_txids
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&txid))
+    }
+
+    async fn get_transaction_height(&self, txid: Txid) -> Result<Option<u32>, NodeError> {
+        if let Some((_, height)) = self
+            .known_transactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&txid)
+        {
+            return Ok(*height);
+        }
+        let confirmed = self
+            .confirmed_txids
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&txid);
+        Ok(confirmed.then(|| self.block_height.load(Ordering::SeqCst)))
+    }
+
     async fn get_confirmed_transactions(
         &self,
         _addresses: Vec<Address>,
@@ -151,6 +323,14 @@ impl Oracle for MockOracle {
         loop {
             match deposit_rx.recv().await {
                 Ok(deposit_intent) => {
+                    // An expired intent is a request to stop watching, never a deposit.
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or_default();
+                    if deposit_intent.is_expired(now) {
+                        continue;
+                    }
                     info!("Received new address: {}", deposit_intent.deposit_address);
                     if let Ok(addr) = Address::from_str(&deposit_intent.deposit_address) {
                         let tx = Self::create_dummy_tx(
@@ -188,13 +368,33 @@ impl Oracle for MockOracle {
     }
 
     async fn get_latest_block_height(&self) -> Result<u32, NodeError> {
-        // Return a constant dummy height
-        Ok(0)
+        self.delay_response().await;
+        Ok(self.block_height.load(Ordering::SeqCst))
+    }
+
+    async fn get_block_hash(&self, height: u32) -> Result<BlockHash, NodeError> {
+        let overridden = self
+            .block_hashes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&height)
+            .copied();
+        Ok(overridden.unwrap_or_else(|| {
+            let mut bytes = [0u8; 32];
+            bytes[..4].copy_from_slice(&height.to_be_bytes());
+            BlockHash::from_byte_array(bytes)
+        }))
     }
 
-    async fn get_transaction_by_address(&self, _tx_id: &str) -> Result<Transaction, NodeError> {
-        let tx = Self::create_dummy_tx_without_address(1000);
-        Ok(tx)
+    async fn get_transaction_by_address(&self, tx_id: &str) -> Result<Transaction, NodeError> {
+        let known = Txid::from_str(tx_id).ok().and_then(|txid| {
+            self.known_transactions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&txid)
+                .map(|(tx, _)| tx.clone())
+        });
+        Ok(known.unwrap_or_else(|| Self::create_dummy_tx_without_address(1000)))
     }
 }
 
@@ -1,5 +1,5 @@
 use bitcoin::Transaction;
-use bitcoin::{Address, Txid};
+use bitcoin::{Address, BlockHash, Txid};
 use dyn_clone::DynClone;
 use types::{errors::NodeError, utxo::Utxo};
 
@@ -15,6 +15,9 @@ pub trait Oracle: Send + DynClone + Sync {
     async fn get_transaction_by_address(&self, tx_id: &str) -> Result<Transaction, NodeError>;
 
     async fn get_current_fee_per_vb(&self, priority: Option<u16>) -> Result<f64, NodeError>;
+
+    /// Lowest feerate, in sat/vB, at which the backend's mempool accepts transactions.
+    async fn get_min_relay_fee_per_vb(&self) -> Result<f64, NodeError>;
     async fn refresh_utxos(
         &self,
         address: Address,
@@ -25,6 +28,11 @@ pub trait Oracle: Send + DynClone + Sync {
 
     async fn broadcast_transaction(&self, tx: &bitcoin::Transaction) -> Result<String, NodeError>;
 
+    async fn is_transaction_confirmed(&self, txid: Txid) -> Result<bool, NodeError>;
+
+    /// Height of the block that confirmed `txid`, or `None` while it is unconfirmed.
+    async fn get_transaction_height(&self, txid: Txid) -> Result<Option<u32>, NodeError>;
+
     async fn get_confirmed_transactions(
         &self,
         addresses: Vec<Address>,
@@ -35,6 +43,8 @@ pub trait Oracle: Send + DynClone + Sync {
     async fn poll_new_transactions(&mut self, addresses: Vec<Address>);
 
     async fn get_latest_block_height(&self) -> Result<u32, NodeError>;
+
+    async fn get_block_hash(&self, height: u32) -> Result<BlockHash, NodeError>;
 }
 
 dyn_clone::clone_trait_object!(Oracle);
@@ -0,0 +1,92 @@
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::time::{Duration, sleep};
+use tracing::warn;
+use types::errors::NodeError;
+
+/// Requests allowed in flight at once unless configured otherwise.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+/// Retries of a rate-limited request before the error is handed to the caller.
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+/// Wait before the first retry when the backend gives no `Retry-After`; doubled on each
+/// further retry.
+pub const INITIAL_RATE_LIMIT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Seconds to wait given by a `Retry-After` header. Only the delay-seconds form is understood;
+/// for an HTTP date the caller falls back to its own backoff.
+#[must_use]
+pub fn parse_retry_after(value: &str) -> Option<u64> {
+    value.trim().parse().ok()
+}
+
+/// Caps the oracle requests in flight, shared by every clone of the oracle so rescans, fee
+/// estimates and broadcasts together stay under a backend's rate limit.
+#[derive(Clone, Debug)]
+pub struct RequestLimiter {
+    permits: Arc<Semaphore>,
+    initial_backoff: Duration,
+    max_retries: u32,
+}
+
+impl Default for RequestLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_REQUESTS)
+    }
+}
+
+impl RequestLimiter {
+    /// A limiter letting `max_concurrent` requests, at least one, run at once. Clones share
+    /// the same slots.
+    #[must_use]
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            initial_backoff: INITIAL_RATE_LIMIT_BACKOFF,
+            max_retries: MAX_RATE_LIMIT_RETRIES,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Runs `request` once a slot is free, running it again while it fails with
+    /// [`NodeError::OracleRateLimited`]. The slot is given up while backing off, so a
+    /// rate-limited request does not hold back the others.
+    pub async fn run<T, F, Fut>(&self, operation: &str, mut request: F) -> Result<T, NodeError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, NodeError>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut retries = 0;
+        loop {
+            let result = {
+                let _permit = self.permits.acquire().await.map_err(|_| {
+                    NodeError::Error("Oracle request limiter was closed".to_string())
+                })?;
+                request().await
+            };
+
+            match result {
+                Err(NodeError::OracleRateLimited { retry_after_secs })
+                    if retries < self.max_retries =>
+                {
+                    let wait = retry_after_secs.map_or(backoff, Duration::from_secs);
+                    warn!(
+                        "Oracle rate-limited {}; retrying in {} ms",
+                        operation,
+                        wait.as_millis()
+                    );
+                    sleep(wait).await;
+                    backoff = backoff.saturating_mul(2);
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
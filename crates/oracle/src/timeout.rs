@@ -0,0 +1,146 @@
+use std::future::Future;
+
+use crate::oracle::Oracle;
+use bitcoin::{Address, BlockHash, Transaction, Txid};
+use tokio::time::{Duration, timeout};
+use types::{errors::NodeError, utxo::Utxo};
+
+/// Oracle that bounds every query to the wrapped backend by a fixed timeout, so a slow
+/// backend surfaces as [`NodeError::OracleTimeout`] instead of stalling the caller.
+///
+/// `poll_new_transactions` is the long-running deposit monitor and is passed through as is.
+#[derive(Clone)]
+pub struct TimeoutOracle {
+    pub inner: Box<dyn Oracle>,
+    pub timeout: Duration,
+}
+
+impl TimeoutOracle {
+    #[must_use]
+    pub fn new(inner: Box<dyn Oracle>, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    async fn bounded<T>(
+        &self,
+        operation: &str,
+        call: impl Future<Output = Result<T, NodeError>> + Send,
+    ) -> Result<T, NodeError> {
+        timeout(self.timeout, call).await.unwrap_or_else(|_| {
+            Err(NodeError::OracleTimeout {
+                operation: operation.to_string(),
+                timeout_ms: u64::try_from(self.timeout.as_millis()).unwrap_or(u64::MAX),
+            })
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Oracle for TimeoutOracle {
+    async fn validate_transaction(
+        &self,
+        address: &str,
+        amount: u64,
+        tx_hash: Txid,
+    ) -> Result<bool, NodeError> {
+        self.bounded(
+            "validate_transaction",
+            self.inner.validate_transaction(address, amount, tx_hash),
+        )
+        .await
+    }
+
+    async fn get_transaction_by_address(&self, tx_id: &str) -> Result<Transaction, NodeError> {
+        self.bounded(
+            "get_transaction_by_address",
+            self.inner.get_transaction_by_address(tx_id),
+        )
+        .await
+    }
+
+    async fn get_current_fee_per_vb(&self, priority: Option<u16>) -> Result<f64, NodeError> {
+        self.bounded(
+            "get_current_fee_per_vb",
+            self.inner.get_current_fee_per_vb(priority),
+        )
+        .await
+    }
+
+    async fn get_min_relay_fee_per_vb(&self) -> Result<f64, NodeError> {
+        self.bounded(
+            "get_min_relay_fee_per_vb",
+            self.inner.get_min_relay_fee_per_vb(),
+        )
+        .await
+    }
+
+    async fn refresh_utxos(
+        &self,
+        address: Address,
+        number_pages: u32,
+        start_transactions: Option<Txid>,
+        allow_unconfirmed: bool,
+    ) -> Result<Vec<Utxo>, NodeError> {
+        self.bounded(
+            "refresh_utxos",
+            self.inner
+                .refresh_utxos(address, number_pages, start_transactions, allow_unconfirmed),
+        )
+        .await
+    }
+
+    async fn broadcast_transaction(&self, tx: &Transaction) -> Result<String, NodeError> {
+        self.bounded(
+            "broadcast_transaction",
+            self.inner.broadcast_transaction(tx),
+        )
+        .await
+    }
+
+    async fn is_transaction_confirmed(&self, txid: Txid) -> Result<bool, NodeError> {
+        self.bounded(
+            "is_transaction_confirmed",
+            self.inner.is_transaction_confirmed(txid),
+        )
+        .await
+    }
+
+    async fn get_transaction_height(&self, txid: Txid) -> Result<Option<u32>, NodeError> {
+        self.bounded(
+            "get_transaction_height",
+            self.inner.get_transaction_height(txid),
+        )
+        .await
+    }
+
+    async fn get_confirmed_transactions(
+        &self,
+        addresses: Vec<Address>,
+        min_height: u32,
+        max_height: u32,
+    ) -> Result<Vec<Transaction>, NodeError> {
+        self.bounded(
+            "get_confirmed_transactions",
+            self.inner
+                .get_confirmed_transactions(addresses, min_height, max_height),
+        )
+        .await
+    }
+
+    async fn poll_new_transactions(&mut self, addresses: Vec<Address>) {
+        self.inner.poll_new_transactions(addresses).await;
+    }
+
+    async fn get_latest_block_height(&self) -> Result<u32, NodeError> {
+        self.bounded(
+            "get_latest_block_height",
+            self.inner.get_latest_block_height(),
+        )
+        .await
+    }
+
+    async fn get_block_hash(&self, height: u32) -> Result<BlockHash, NodeError> {
+        self.bounded("get_block_hash", self.inner.get_block_hash(height))
+            .await
+    }
+}
@@ -58,6 +58,39 @@ pub struct ChainConfig {
     pub min_stake: u64,
     pub block_time_seconds: u64,
     pub max_block_size: u64,
+    /// Votes needed to finalize a block; independent of the FROST `min_signers`.
+    pub consensus_quorum: ConsensusQuorum,
+}
+
+/// Number of validator votes required for block finalization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub enum ConsensusQuorum {
+    /// At least `numerator / denominator` of the validator set, rounded up.
+    Fraction { numerator: u32, denominator: u32 },
+    /// A fixed number of votes regardless of the validator set size.
+    Count(u32),
+}
+
+impl Default for ConsensusQuorum {
+    fn default() -> Self {
+        Self::Fraction {
+            numerator: 2,
+            denominator: 3,
+        }
+    }
+}
+
+impl ConsensusQuorum {
+    #[must_use]
+    pub fn required_votes(self, validators: usize) -> usize {
+        match self {
+            Self::Fraction {
+                numerator,
+                denominator,
+            } => (validators * numerator as usize).div_ceil(denominator.max(1) as usize),
+            Self::Count(count) => count as usize,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
@@ -125,15 +158,7 @@ impl Block {
         transactions: Vec<Transaction>,
         proposer: Vec<u8>,
     ) -> Self {
-        let mut hasher = Sha256::new();
-        let state_bytes =
-            bincode::encode_to_vec(&transactions, bincode::config::standard()).unwrap();
-        hasher.update(&state_bytes);
-        hasher.update(&proposer);
-        hasher.update(height.to_le_bytes());
-        let result = hasher.finalize();
-        let mut state_root = [0u8; 32];
-        state_root.copy_from_slice(&result);
+        let state_root = Self::compute_state_root(&transactions, &proposer, height);
 
         let header = BlockHeader {
             version: 1,
@@ -149,6 +174,37 @@ impl Block {
         }
     }
 
+    /// State root committed to by [`Self::new`]: the hash of the transactions, proposer and
+    /// height.
+    #[must_use]
+    pub fn compute_state_root(
+        transactions: &[Transaction],
+        proposer: &[u8],
+        height: u64,
+    ) -> StateRoot {
+        let mut hasher = Sha256::new();
+        let state_bytes =
+            bincode::encode_to_vec(transactions, bincode::config::standard()).unwrap();
+        hasher.update(&state_bytes);
+        hasher.update(proposer);
+        hasher.update(height.to_le_bytes());
+        let result = hasher.finalize();
+        let mut state_root = [0u8; 32];
+        state_root.copy_from_slice(&result);
+        state_root
+    }
+
+    /// Whether the header's state root commits to this block's contents.
+    #[must_use]
+    pub fn has_valid_state_root(&self) -> bool {
+        self.header.state_root
+            == Self::compute_state_root(
+                &self.body.transactions,
+                &self.header.proposer,
+                self.header.height,
+            )
+    }
+
     #[must_use]
     pub const fn new_with_state_root(
         previous_block_hash: BlockHash,
@@ -182,6 +238,77 @@ impl Block {
         hash
     }
 
+    /// Canonical JSON rendering of the full block: compact, with object keys sorted
+    /// at every level and byte fields hex encoded.
+    #[must_use]
+    pub fn to_canonical_json(&self) -> String {
+        let mut value = serde_json::json!({
+            "hash": hex::encode(self.hash()),
+            "header": {
+                "version": self.header.version,
+                "previous_block_hash": hex::encode(self.header.previous_block_hash),
+                "state_root": hex::encode(self.header.state_root),
+                "height": self.header.height,
+                "proposer": hex::encode(&self.header.proposer),
+            },
+            "transactions": self
+                .body
+                .transactions
+                .iter()
+                .map(Transaction::to_json_value)
+                .collect::<Vec<_>>(),
+        });
+        value.sort_all_objects();
+        value.to_string()
+    }
+
+    /// Rebuilds a block from [`Self::to_canonical_json`]. The `hash` field is not trusted;
+    /// callers compare it against [`Self::hash`] of the result.
+    pub fn from_canonical_json(json: &str) -> Result<Self, NodeError> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| NodeError::Error(format!("Invalid block JSON: {e}")))?;
+        let header = &value["header"];
+
+        let hex_field = |name: &str| {
+            header[name]
+                .as_str()
+                .ok_or_else(|| NodeError::Error(format!("Block header without {name}")))
+                .and_then(|field| {
+                    hex::decode(field).map_err(|e| NodeError::Error(format!("Invalid {name}: {e}")))
+                })
+        };
+        let hash_field = |name: &str| {
+            hex_field(name).and_then(|bytes| {
+                <[u8; 32]>::try_from(bytes)
+                    .map_err(|_| NodeError::Error(format!("{name} must be 32 bytes")))
+            })
+        };
+
+        let header = BlockHeader {
+            version: header["version"]
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| NodeError::Error("Block header without version".to_string()))?,
+            previous_block_hash: hash_field("previous_block_hash")?,
+            state_root: hash_field("state_root")?,
+            height: header["height"]
+                .as_u64()
+                .ok_or_else(|| NodeError::Error("Block header without height".to_string()))?,
+            proposer: hex_field("proposer")?,
+        };
+        let transactions = value["transactions"]
+            .as_array()
+            .ok_or_else(|| NodeError::Error("Block without transactions".to_string()))?
+            .iter()
+            .map(Transaction::from_json_value)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            header,
+            body: BlockBody { transactions },
+        })
+    }
+
     pub fn serialize(&self) -> Result<Vec<u8>, NodeError> {
         bincode::encode_to_vec(self, bincode::config::standard())
             .map_err(|e| NodeError::Error(format!("Failed to serialize block: {e}")))
@@ -248,4 +375,15 @@ impl GenesisBlock {
     pub fn hash(&self) -> BlockHash {
         self.to_block().hash()
     }
+
+    pub fn serialize(&self) -> Result<Vec<u8>, NodeError> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .map_err(|e| NodeError::Error(format!("Failed to serialize genesis block: {e}")))
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, NodeError> {
+        let (genesis, _): (Self, _) = bincode::decode_from_slice(data, bincode::config::standard())
+            .map_err(|e| NodeError::Error(format!("Failed to deserialize genesis block: {e}")))?;
+        Ok(genesis)
+    }
 }
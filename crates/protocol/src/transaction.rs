@@ -7,6 +7,27 @@ use types::errors::NodeError;
 
 pub type TransactionId = [u8; 32];
 
+/// Encodes a numeric operand in its canonical form: the 8-byte big-endian `u64`.
+///
+/// Every node must agree on this byte order, so amounts pushed onto the stack should always
+/// go through this function rather than `to_be_bytes`/`to_le_bytes` directly.
+#[must_use]
+pub fn encode_amount(amount: u64) -> Vec<u8> {
+    amount.to_be_bytes().to_vec()
+}
+
+/// Decodes a numeric operand produced by [`encode_amount`], rejecting anything that is not
+/// exactly 8 bytes.
+pub fn decode_amount(bytes: &[u8]) -> Result<u64, NodeError> {
+    let bytes = <[u8; 8]>::try_from(bytes).map_err(|_| {
+        NodeError::Error(format!(
+            "Invalid amount: expected 8 bytes, got {}",
+            bytes.len()
+        ))
+    })?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Transaction {
     pub version: u32,
@@ -54,13 +75,16 @@ impl<'de, C> BorrowDecode<'de, C> for Transaction {
 pub enum TransactionType {
     Deposit,
     Withdrawal,
+    /// Drops a deposit intent that passed its expiry, or was cancelled, without receiving
+    /// funds.
+    ExpireDepositIntent,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, PartialEq, Eq)]
 pub enum Operation {
     /// Push a value to the stack in bytes
     /// Data types:
-    ///    - Numbers: u64
+    ///    - Numbers: u64, 8 bytes big-endian (see [`encode_amount`])
     ///    - Strings: utf-8 encoded string
     ///    - Booleans: u8 (0 or 1)
     ///    - Tx Hash: [u8; 32]
@@ -90,6 +114,72 @@ pub enum Operation {
     /// Pushes to the stack:
     ///   - 0: The result (0 or 1)
     OpDecrementBalance,
+    /// Remove the deposit intent at the address on the stack if it expires at the given
+    /// time, 0 for one that never expires. An intent already gone, or replaced by one with
+    /// another expiry, is left as is.
+    /// Pops from the stack:
+    ///   - 0: The deposit address
+    ///   - 1: The intent's expiry, in unix seconds
+    ///
+    /// Pushes to the stack:
+    ///   - 0: The result (0 or 1)
+    OpExpireDepositIntent,
+}
+
+impl Operation {
+    /// Pushes `amount` in its canonical encoding.
+    #[must_use]
+    pub fn push_amount(amount: u64) -> Self {
+        Self::OpPush {
+            value: encode_amount(amount),
+        }
+    }
+
+    /// Decodes the operation for display. Pushed values are shown as hex, plus their
+    /// UTF-8 text and big-endian `u64` readings where those interpretations apply.
+    #[must_use]
+    pub fn to_json_value(&self) -> serde_json::Value {
+        match self {
+            Self::OpPush { value } => {
+                let mut op = serde_json::json!({
+                    "op": "OpPush",
+                    "value": hex::encode(value),
+                });
+                if let Ok(text) = std::str::from_utf8(value) {
+                    if !text.is_empty() && !text.chars().any(char::is_control) {
+                        op["text"] = text.into();
+                    }
+                }
+                if let Ok(number) = decode_amount(value) {
+                    op["number"] = number.into();
+                }
+                op
+            }
+            Self::OpCheckOracle => serde_json::json!({ "op": "OpCheckOracle" }),
+            Self::OpIncrementBalance => serde_json::json!({ "op": "OpIncrementBalance" }),
+            Self::OpDecrementBalance => serde_json::json!({ "op": "OpDecrementBalance" }),
+            Self::OpExpireDepositIntent => serde_json::json!({ "op": "OpExpireDepositIntent" }),
+        }
+    }
+
+    /// Inverse of [`Self::to_json_value`]; the display-only readings of a push are ignored.
+    pub fn from_json_value(value: &serde_json::Value) -> Result<Self, NodeError> {
+        match value["op"].as_str() {
+            Some("OpPush") => {
+                let value = value["value"]
+                    .as_str()
+                    .ok_or_else(|| NodeError::Error("OpPush without a value".to_string()))?;
+                let value = hex::decode(value)
+                    .map_err(|e| NodeError::Error(format!("Invalid OpPush value: {e}")))?;
+                Ok(Self::OpPush { value })
+            }
+            Some("OpCheckOracle") => Ok(Self::OpCheckOracle),
+            Some("OpIncrementBalance") => Ok(Self::OpIncrementBalance),
+            Some("OpDecrementBalance") => Ok(Self::OpDecrementBalance),
+            Some("OpExpireDepositIntent") => Ok(Self::OpExpireDepositIntent),
+            other => Err(NodeError::Error(format!("Unknown operation {other:?}"))),
+        }
+    }
 }
 
 impl Transaction {
@@ -132,6 +222,80 @@ impl Transaction {
         id
     }
 
+    /// Canonical position of the transaction within a proposed block.
+    ///
+    /// Deposits come first, ordered by the Bitcoin outpoint that funded them, so every
+    /// proposer credits accounts in the same sequence; withdrawals follow, then intent
+    /// expiries. Ties are broken by the transaction id.
+    #[must_use]
+    pub fn ordering_key(&self) -> (u8, Vec<u8>, TransactionId) {
+        match self.r#type {
+            TransactionType::Deposit => {
+                let mut funding = self
+                    .funding_txid()
+                    .map(|txid| txid.as_byte_array().to_vec())
+                    .unwrap_or_default();
+                if let Some(vout) = self.funding_vout() {
+                    funding.extend_from_slice(&vout.to_be_bytes());
+                }
+                (0, funding, self.id())
+            }
+            TransactionType::Withdrawal => (1, Vec::new(), self.id()),
+            TransactionType::ExpireDepositIntent => (2, Vec::new(), self.id()),
+        }
+    }
+
+    /// Human-readable view of the transaction with its operations decoded.
+    #[must_use]
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": hex::encode(self.id()),
+            "version": self.version,
+            "type": format!("{:?}", self.r#type),
+            "operations": self
+                .operations
+                .iter()
+                .map(Operation::to_json_value)
+                .collect::<Vec<_>>(),
+            "metadata": self.metadata,
+        })
+    }
+
+    /// Inverse of [`Self::to_json_value`]; the reported id is not checked here.
+    pub fn from_json_value(value: &serde_json::Value) -> Result<Self, NodeError> {
+        let version = value["version"]
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| NodeError::Error("Transaction without a valid version".to_string()))?;
+        let r#type = match value["type"].as_str() {
+            Some("Deposit") => TransactionType::Deposit,
+            Some("Withdrawal") => TransactionType::Withdrawal,
+            Some("ExpireDepositIntent") => TransactionType::ExpireDepositIntent,
+            other => {
+                return Err(NodeError::Error(format!(
+                    "Unknown transaction type {other:?}"
+                )));
+            }
+        };
+        let operations = value["operations"]
+            .as_array()
+            .ok_or_else(|| NodeError::Error("Transaction without operations".to_string()))?
+            .iter()
+            .map(Operation::from_json_value)
+            .collect::<Result<_, _>>()?;
+        let metadata = match &value["metadata"] {
+            serde_json::Value::Null => None,
+            metadata => Some(metadata.clone()),
+        };
+
+        Ok(Self {
+            version,
+            r#type,
+            operations,
+            metadata,
+        })
+    }
+
     pub fn create_deposit_transaction(
         tx: &bitcoin::Transaction,
         user_pubkey: &str,
@@ -140,9 +304,7 @@ impl Transaction {
         Ok(Self::new(
             TransactionType::Deposit,
             vec![
-                Operation::OpPush {
-                    value: amount_sat.to_be_bytes().to_vec(),
-                },
+                Operation::push_amount(amount_sat),
                 Operation::OpPush {
                     value: user_pubkey.as_bytes().to_vec(),
                 },
@@ -150,9 +312,7 @@ impl Transaction {
                     value: tx.compute_txid().as_byte_array().to_vec(),
                 },
                 Operation::OpCheckOracle,
-                Operation::OpPush {
-                    value: amount_sat.to_be_bytes().to_vec(),
-                },
+                Operation::push_amount(amount_sat),
                 Operation::OpPush {
                     value: user_pubkey.as_bytes().to_vec(),
                 },
@@ -166,6 +326,46 @@ impl Transaction {
         ))
     }
 
+    /// The Bitcoin txid checked by the deposit's first oracle check
+    /// (`amount address txid OpCheckOracle`), if it has a well-formed one.
+    #[must_use]
+    pub fn funding_txid(&self) -> Option<bitcoin::Txid> {
+        let mut operands: Vec<&[u8]> = Vec::new();
+        for operation in &self.operations {
+            if let Operation::OpPush { value } = operation {
+                operands.push(value);
+                continue;
+            }
+            if let (Operation::OpCheckOracle, [_, _, txid]) = (operation, operands.as_slice()) {
+                return bitcoin::Txid::from_slice(txid).ok();
+            }
+            operands.clear();
+        }
+        None
+    }
+
+    /// Records which output of the funding transaction the deposit credits, so deposits
+    /// funded by the same transaction are ordered by output.
+    #[must_use]
+    pub fn with_funding_vout(mut self, vout: u32) -> Self {
+        if let Some(serde_json::Value::Object(metadata)) = self.metadata.as_mut() {
+            metadata.insert("vout".to_string(), serde_json::json!(vout));
+        } else {
+            self.metadata = Some(serde_json::json!({ "vout": vout }));
+        }
+        self
+    }
+
+    /// The output recorded by [`Self::with_funding_vout`].
+    #[must_use]
+    pub fn funding_vout(&self) -> Option<u32> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("vout"))
+            .and_then(serde_json::Value::as_u64)
+            .and_then(|vout| u32::try_from(vout).ok())
+    }
+
     pub fn get_deposit_transaction_address(&self) -> Result<bitcoin::Transaction, NodeError> {
         let tx = self
             .metadata
@@ -189,9 +389,7 @@ impl Transaction {
         Ok(Self::new(
             TransactionType::Withdrawal,
             vec![
-                Operation::OpPush {
-                    value: amount_sat.to_be_bytes().to_vec(),
-                },
+                Operation::push_amount(amount_sat),
                 Operation::OpPush {
                     value: user_pubkey.as_bytes().to_vec(),
                 },
@@ -204,4 +402,57 @@ impl Transaction {
             })),
         ))
     }
+
+    /// Drops the deposit intent at `deposit_address` that expires at `expires_at`. Built only
+    /// from the intent, so every node that sees it expire proposes the same transaction; a
+    /// cancellation is the same transaction proposed early.
+    #[must_use]
+    pub fn create_expire_deposit_intent_transaction(
+        deposit_address: &str,
+        expires_at: u64,
+    ) -> Self {
+        Self::new(
+            TransactionType::ExpireDepositIntent,
+            vec![
+                Operation::push_amount(expires_at),
+                Operation::OpPush {
+                    value: deposit_address.as_bytes().to_vec(),
+                },
+                Operation::OpExpireDepositIntent,
+            ],
+            Some(serde_json::json!({
+                "deposit_address": deposit_address,
+                "expires_at": expires_at,
+            })),
+        )
+    }
+
+    /// Records the vault outpoints the withdrawal's bitcoin transaction spends, so block
+    /// execution can reject a second withdrawal spending any of them.
+    #[must_use]
+    pub fn with_spent_outpoints(mut self, outpoints: &[bitcoin::OutPoint]) -> Self {
+        let outpoints: Vec<String> = outpoints.iter().map(ToString::to_string).collect();
+        if let Some(serde_json::Value::Object(metadata)) = self.metadata.as_mut() {
+            metadata.insert("spent_outpoints".to_string(), serde_json::json!(outpoints));
+        } else {
+            self.metadata = Some(serde_json::json!({ "spent_outpoints": outpoints }));
+        }
+        self
+    }
+
+    /// The vault outpoints recorded by [`Self::with_spent_outpoints`], as `txid:vout` strings.
+    #[must_use]
+    pub fn spent_outpoints(&self) -> Vec<String> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("spent_outpoints"))
+            .and_then(serde_json::Value::as_array)
+            .map(|outpoints| {
+                outpoints
+                    .iter()
+                    .filter_map(|outpoint| outpoint.as_str().map(ToString::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
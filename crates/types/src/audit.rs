@@ -0,0 +1,108 @@
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// Operation recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub enum AuditEventKind {
+    WithdrawalProposed,
+    WithdrawalConfirmed,
+    SigningStarted,
+    TransactionBroadcast,
+    /// A deposit to a vault address without an intent was held back for manual resolution.
+    DepositQuarantined,
+}
+
+impl AuditEventKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::WithdrawalProposed => "withdrawal_proposed",
+            Self::WithdrawalConfirmed => "withdrawal_confirmed",
+            Self::SigningStarted => "signing_started",
+            Self::TransactionBroadcast => "transaction_broadcast",
+            Self::DepositQuarantined => "deposit_quarantined",
+        }
+    }
+}
+
+/// What became of an audited operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub enum AuditOutcome {
+    /// Recorded before the operation is applied; no later `Failed` entry means it went through.
+    Accepted,
+    /// Refused before any state changed.
+    Rejected(String),
+    /// Accepted earlier but failed while being applied.
+    Failed(String),
+}
+
+impl AuditOutcome {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Rejected(_) => "rejected",
+            Self::Failed(_) => "failed",
+        }
+    }
+
+    #[must_use]
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Accepted => None,
+            Self::Rejected(reason) | Self::Failed(reason) => Some(reason),
+        }
+    }
+}
+
+/// One append-only record of a withdrawal, signing or broadcast operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct AuditEntry {
+    /// Position in the log, assigned when the entry is appended.
+    pub sequence: u64,
+    /// Unix time in seconds.
+    pub timestamp: u64,
+    pub kind: AuditEventKind,
+    pub amount_sat: u64,
+    pub address: String,
+    /// Withdrawal challenge, signing sighash, Bitcoin txid or quarantined outpoint, depending
+    /// on `kind`.
+    pub reference: String,
+    pub outcome: AuditOutcome,
+}
+
+impl AuditEntry {
+    #[must_use]
+    pub fn new(
+        kind: AuditEventKind,
+        amount_sat: u64,
+        address: impl Into<String>,
+        reference: impl Into<String>,
+        outcome: AuditOutcome,
+    ) -> Self {
+        Self {
+            sequence: 0,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            kind,
+            amount_sat,
+            address: address.into(),
+            reference: reference.into(),
+            outcome,
+        }
+    }
+
+    /// The same operation with a different outcome, e.g. to record that it later failed.
+    #[must_use]
+    pub fn with_outcome(&self, outcome: AuditOutcome) -> Self {
+        Self::new(
+            self.kind,
+            self.amount_sat,
+            self.address.clone(),
+            self.reference.clone(),
+            outcome,
+        )
+    }
+}
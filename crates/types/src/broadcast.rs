@@ -1,6 +1,7 @@
 use crate::{
     consensus::ConsensusMessage,
     intents::{DepositIntent, PendingSpend},
+    network::network_protocol::GossipTopic,
     proto::{ProtoDecode, ProtoEncode, p2p_proto},
 };
 
@@ -60,6 +61,17 @@ impl ProtoEncode for BroadcastMessage {
         }
         Ok(buf)
     }
+
+    fn gossip_topic(&self) -> GossipTopic {
+        match self {
+            Self::Consensus(msg) => msg.gossip_topic(),
+            Self::Block(_) => GossipTopic::Block,
+            Self::DepositIntent(_)
+            | Self::Transaction(_)
+            | Self::PendingSpend(_)
+            | Self::Dkg(_) => GossipTopic::Broadcast,
+        }
+    }
 }
 
 impl ProtoDecode for BroadcastMessage {
@@ -1,24 +1,57 @@
+use libp2p::PeerId;
+use libp2p::identity::{Keypair, PublicKey};
 use prost::Message;
 
+use crate::network::network_protocol::GossipTopic;
 use crate::proto::{ProtoDecode, ProtoEncode, p2p_proto};
 
+/// Consensus traffic, each kind gossiped on its own [`GossipTopic`].
 #[derive(Debug, Clone)]
 pub enum ConsensusMessage {
+    /// Published on [`GossipTopic::Leader`].
     LeaderAnnouncement(LeaderAnnouncement),
+    /// Published on [`GossipTopic::Leader`].
     NewRound(u32),
+    /// Published on [`GossipTopic::Vote`].
     Vote(Vote),
+    /// Published on [`GossipTopic::Vote`] by the round's aggregator in place of the
+    /// individual votes it collected.
+    VoteAggregate(VoteAggregate),
+    /// Published on [`GossipTopic::Block`].
     BlockProposal {
         proposer: Vec<u8>,
         raw_block: Vec<u8>,
+        /// Ids of the transactions the leader selected, in block order. Followers rebuild
+        /// the block from exactly these rather than from their own mempool.
+        tx_hashes: Vec<Vec<u8>>,
+        /// Unix time in seconds the block was proposed, see [`Vote::timestamp`].
+        timestamp: u64,
     },
 }
 
+impl ConsensusMessage {
+    /// Whether the votes or proposal carried were made within `max_age_secs` of unix time
+    /// `now`, see [`Vote::is_fresh`]. Leader messages carry no timestamp and always pass.
+    #[must_use]
+    pub fn is_fresh(&self, now: u64, max_age_secs: u64) -> bool {
+        match self {
+            Self::Vote(vote) => vote.is_fresh(now, max_age_secs),
+            Self::VoteAggregate(aggregate) => aggregate.is_fresh(now, max_age_secs),
+            Self::BlockProposal { timestamp, .. } => now.abs_diff(*timestamp) <= max_age_secs,
+            Self::LeaderAnnouncement(_) | Self::NewRound(_) => true,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LeaderAnnouncement {
     pub leader: Vec<u8>,
     pub round: u32,
 }
 
+/// Block hash a validator votes for when it has no valid proposal to back in the round.
+pub const NIL_BLOCK_HASH: [u8; 32] = [0; 32];
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Vote {
     pub round: u32,
@@ -26,14 +59,160 @@ pub struct Vote {
     pub block_hash: Vec<u8>,
     pub voter: Vec<u8>,
     pub vote_type: VoteType,
+    /// Unix time in seconds the vote was cast. It is signed along with the vote, so a
+    /// replayed vote keeps its original time and can be told apart from a fresh one.
+    pub timestamp: u64,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Domain separator of the bytes a validator signs for a vote.
+const VOTE_SIGNING_DOMAIN: &[u8] = b"threshold/consensus-vote/v2";
+
+/// Seconds since the Unix epoch, the unit of [`Vote::timestamp`].
+#[must_use]
+pub fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Vote {
+    /// Whether this is a vote for no block rather than for a proposal.
+    #[must_use]
+    pub fn is_nil(&self) -> bool {
+        self.block_hash == NIL_BLOCK_HASH
+    }
+
+    /// Whether the vote was cast within `max_age_secs` of unix time `now`, either way, so
+    /// neither a replayed vote nor one dated ahead to outlive its round is accepted.
+    #[must_use]
+    pub const fn is_fresh(&self, now: u64, max_age_secs: u64) -> bool {
+        now.abs_diff(self.timestamp) <= max_age_secs
+    }
+
+    /// Canonical encoding of every field, which the voter's identity key signs.
+    #[must_use]
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = VOTE_SIGNING_DOMAIN.to_vec();
+        bytes.extend_from_slice(&self.round.to_be_bytes());
+        bytes.extend_from_slice(&self.height.to_be_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.push(self.vote_type.as_byte());
+        bytes.extend_from_slice(&(self.block_hash.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(&self.block_hash);
+        bytes.extend_from_slice(&self.voter);
+        bytes
+    }
+
+    /// Signs the vote with the voter's libp2p identity key.
+    pub fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>, String> {
+        keypair
+            .sign(&self.signing_bytes())
+            .map_err(|e| format!("Failed to sign vote: {e}"))
+    }
+
+    /// Whether `signature` is the voter's signature over this vote. Only voters whose peer
+    /// id inlines their public key, as Ed25519 ids do, can be verified.
+    #[must_use]
+    pub fn verify_signature(&self, signature: &[u8]) -> bool {
+        let Ok(voter) = PeerId::from_bytes(&self.voter) else {
+            return false;
+        };
+        let multihash = voter.as_ref();
+        // Identity multihash: the digest is the protobuf-encoded public key itself.
+        if multihash.code() != 0 {
+            return false;
+        }
+        PublicKey::try_decode_protobuf(multihash.digest())
+            .is_ok_and(|key| key.verify(&self.signing_bytes(), signature))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum VoteType {
     Prevote,
     Precommit,
 }
 
+impl VoteType {
+    const fn as_byte(self) -> u8 {
+        match self {
+            Self::Prevote => 0,
+            Self::Precommit => 1,
+        }
+    }
+
+    const fn to_proto(self) -> i32 {
+        match self {
+            Self::Prevote => p2p_proto::VoteType::Prevote as i32,
+            Self::Precommit => p2p_proto::VoteType::Precommit as i32,
+        }
+    }
+
+    fn from_proto(value: i32) -> Result<Self, String> {
+        match value {
+            0 => Ok(Self::Prevote),
+            1 => Ok(Self::Precommit),
+            _ => Err("Invalid vote type".to_string()),
+        }
+    }
+}
+
+/// Matching votes of several validators, gossiped once by the round's aggregator instead of
+/// every validator gossiping its own. Each signature is checked against its signer, so the
+/// aggregator cannot vote on anyone's behalf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteAggregate {
+    pub round: u32,
+    pub height: u64,
+    pub block_hash: Vec<u8>,
+    pub vote_type: VoteType,
+    /// Peer ids of the voters, in the same order as `signatures` and `timestamps`.
+    pub signers: Vec<Vec<u8>>,
+    pub signatures: Vec<Vec<u8>>,
+    /// When each signer cast its vote, see [`Vote::timestamp`].
+    pub timestamps: Vec<u64>,
+}
+
+impl VoteAggregate {
+    /// Whether every vote in the aggregate is fresh, see [`Vote::is_fresh`].
+    #[must_use]
+    pub fn is_fresh(&self, now: u64, max_age_secs: u64) -> bool {
+        self.timestamps
+            .iter()
+            .all(|timestamp| now.abs_diff(*timestamp) <= max_age_secs)
+    }
+
+    /// The individual vote each signer cast, paired with its signature.
+    pub fn votes(&self) -> impl Iterator<Item = (Vote, &[u8])> {
+        self.signers
+            .iter()
+            .zip(&self.signatures)
+            .zip(&self.timestamps)
+            .map(|((signer, signature), &timestamp)| {
+                let vote = Vote {
+                    round: self.round,
+                    height: self.height,
+                    block_hash: self.block_hash.clone(),
+                    voter: signer.clone(),
+                    vote_type: self.vote_type,
+                    timestamp,
+                };
+                (vote, signature.as_slice())
+            })
+    }
+
+    /// Whether every signer has exactly one signature and timestamp and all of them verify.
+    #[must_use]
+    pub fn verify(&self) -> bool {
+        self.signers.len() == self.signatures.len()
+            && self.signers.len() == self.timestamps.len()
+            && self
+                .votes()
+                .all(|(vote, signature)| vote.verify_signature(signature))
+    }
+}
+
 impl ProtoEncode for ConsensusMessage {
     fn encode(&self) -> Result<Vec<u8>, String> {
         let proto_msg = match self {
@@ -50,22 +229,28 @@ impl ProtoEncode for ConsensusMessage {
                     round: *round,
                 })
             }
-            Self::Vote(vote) => p2p_proto::consensus_message::Message::Vote(p2p_proto::Vote {
-                round: vote.round,
-                height: vote.height,
-                block_hash: vote.block_hash.clone(),
-                voter: vote.voter.clone(),
-                vote_type: match vote.vote_type {
-                    VoteType::Prevote => p2p_proto::VoteType::Prevote as i32,
-                    VoteType::Precommit => p2p_proto::VoteType::Precommit as i32,
-                },
-            }),
+            Self::Vote(vote) => p2p_proto::consensus_message::Message::Vote(vote.into()),
+            Self::VoteAggregate(aggregate) => {
+                p2p_proto::consensus_message::Message::VoteAggregate(p2p_proto::VoteAggregate {
+                    round: aggregate.round,
+                    height: aggregate.height,
+                    block_hash: aggregate.block_hash.clone(),
+                    vote_type: aggregate.vote_type.to_proto(),
+                    signers: aggregate.signers.clone(),
+                    signatures: aggregate.signatures.clone(),
+                    timestamps: aggregate.timestamps.clone(),
+                })
+            }
             Self::BlockProposal {
                 proposer,
                 raw_block,
+                tx_hashes,
+                timestamp,
             } => p2p_proto::consensus_message::Message::BlockProposal(p2p_proto::BlockProposal {
                 proposer: proposer.clone(),
                 raw_block: raw_block.clone(),
+                tx_hashes: tx_hashes.clone(),
+                timestamp: *timestamp,
             }),
         };
 
@@ -78,6 +263,14 @@ impl ProtoEncode for ConsensusMessage {
             .map_err(|e| format!("Failed to encode consensus message: {e}"))?;
         Ok(buf)
     }
+
+    fn gossip_topic(&self) -> GossipTopic {
+        match self {
+            Self::LeaderAnnouncement(_) | Self::NewRound(_) => GossipTopic::Leader,
+            Self::Vote(_) | Self::VoteAggregate(_) => GossipTopic::Vote,
+            Self::BlockProposal { .. } => GossipTopic::Block,
+        }
+    }
 }
 
 impl ProtoDecode for ConsensusMessage {
@@ -97,26 +290,54 @@ impl ProtoDecode for ConsensusMessage {
             p2p_proto::consensus_message::Message::NewRound(new_round) => {
                 Ok(Self::NewRound(new_round.round))
             }
-            p2p_proto::consensus_message::Message::Vote(vote) => {
-                let vote_type = match vote.vote_type {
-                    0 => VoteType::Prevote,
-                    1 => VoteType::Precommit,
-                    _ => return Err("Invalid vote type".to_string()),
-                };
-                Ok(Self::Vote(Vote {
-                    round: vote.round,
-                    height: vote.height,
-                    block_hash: vote.block_hash,
-                    voter: vote.voter,
-                    vote_type,
+            p2p_proto::consensus_message::Message::Vote(vote) => Ok(Self::Vote(vote.try_into()?)),
+            p2p_proto::consensus_message::Message::VoteAggregate(aggregate) => {
+                Ok(Self::VoteAggregate(VoteAggregate {
+                    round: aggregate.round,
+                    height: aggregate.height,
+                    block_hash: aggregate.block_hash,
+                    vote_type: VoteType::from_proto(aggregate.vote_type)?,
+                    signers: aggregate.signers,
+                    signatures: aggregate.signatures,
+                    timestamps: aggregate.timestamps,
                 }))
             }
             p2p_proto::consensus_message::Message::BlockProposal(proposal) => {
                 Ok(Self::BlockProposal {
                     proposer: proposal.proposer,
                     raw_block: proposal.raw_block,
+                    tx_hashes: proposal.tx_hashes,
+                    timestamp: proposal.timestamp,
                 })
             }
         }
     }
 }
+
+impl From<&Vote> for p2p_proto::Vote {
+    fn from(vote: &Vote) -> Self {
+        Self {
+            round: vote.round,
+            height: vote.height,
+            block_hash: vote.block_hash.clone(),
+            voter: vote.voter.clone(),
+            vote_type: vote.vote_type.to_proto(),
+            timestamp: vote.timestamp,
+        }
+    }
+}
+
+impl TryFrom<p2p_proto::Vote> for Vote {
+    type Error = String;
+
+    fn try_from(vote: p2p_proto::Vote) -> Result<Self, Self::Error> {
+        Ok(Self {
+            round: vote.round,
+            height: vote.height,
+            block_hash: vote.block_hash,
+            voter: vote.voter,
+            vote_type: VoteType::from_proto(vote.vote_type)?,
+            timestamp: vote.timestamp,
+        })
+    }
+}
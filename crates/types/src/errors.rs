@@ -5,6 +5,69 @@ use std::error::Error;
 #[derive(Debug, Display, Clone, Serialize, Deserialize)]
 pub enum NodeError {
     Error(String),
+    #[display("Withdrawal quote for challenge {challenge} expired at {expired_at}")]
+    WithdrawalQuoteExpired {
+        challenge: String,
+        expired_at: u64,
+    },
+    #[display("Withdrawal challenge {challenge} was already confirmed")]
+    WithdrawalChallengeReplayed {
+        challenge: String,
+    },
+    #[display("Withdrawal of {amount_sat} sat is outside the allowed range {min_sat}..={max_sat}")]
+    WithdrawalAmountOutOfRange {
+        amount_sat: u64,
+        min_sat: u64,
+        max_sat: u64,
+    },
+    #[display("Too many pending {kind} intents (limit {limit})")]
+    TooManyPendingIntents {
+        kind: String,
+        limit: usize,
+    },
+    #[display(
+        "Reorg of {depth} blocks exceeds the maximum of {max_depth}; deposit crediting is halted"
+    )]
+    ReorgTooDeep {
+        depth: u32,
+        max_depth: u32,
+    },
+    #[display("Oracle {operation} timed out after {timeout_ms} ms")]
+    OracleTimeout {
+        operation: String,
+        timeout_ms: u64,
+    },
+    #[display("Oracle backend is rate limiting requests")]
+    OracleRateLimited {
+        retry_after_secs: Option<u64>,
+    },
+    #[display("Malformed deposit address {address}: {reason}")]
+    MalformedDepositAddress {
+        address: String,
+        reason: String,
+    },
+    #[display("Deposit address {address} is not valid on {expected}")]
+    DepositAddressWrongNetwork {
+        address: String,
+        expected: String,
+    },
+    #[display("Vault outpoint {outpoint} is already spent")]
+    OutpointAlreadySpent {
+        outpoint: String,
+    },
+    #[display("Invalid signer configuration: {reason}")]
+    InvalidSignerConfig {
+        reason: String,
+    },
+    #[display(
+        "Block {height} with parent {previous_hash} does not extend the tip {tip_hash} at height {tip_height}"
+    )]
+    BlockDoesNotExtendTip {
+        height: u64,
+        previous_hash: String,
+        tip_height: u64,
+        tip_hash: String,
+    },
 }
 
 #[derive(Debug)]
@@ -1,8 +1,10 @@
 use bincode::{Decode, Encode};
-use bitcoin::{ScriptBuf, Transaction};
+use bitcoin::{Address, Network, ScriptBuf, Transaction};
 use prost::Message;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
+use crate::errors::NodeError;
 use crate::proto::{ProtoDecode, ProtoEncode, p2p_proto};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
@@ -12,6 +14,106 @@ pub struct DepositIntent {
     pub deposit_tracking_id: String,
     pub deposit_address: String,
     pub timestamp: u64,
+    /// Unix time in seconds after which an intent that never received funds is dropped;
+    /// 0 keeps it indefinitely.
+    #[serde(default)]
+    pub expires_at: u64,
+    /// Confirmations the funding transaction needs before it is credited, overriding the
+    /// node's `confirmation_depth` when deeper; `None` uses the node's depth.
+    #[serde(default)]
+    pub min_confirmations: Option<u32>,
+}
+
+/// Leads a versioned [`DepositIntent`] encoding. Bincode's varint encoding never begins with
+/// this byte, so an intent stored before the encoding was versioned is told apart by it.
+const VERSIONED_INTENT_MARKER: u8 = 0xFF;
+
+/// Version of the stored [`DepositIntent`] encoding this build writes. Version 1 added
+/// `expires_at` and `min_confirmations` to the unversioned layout.
+pub const DEPOSIT_INTENT_VERSION: u8 = 1;
+
+/// A deposit intent in the layout stored before the encoding was versioned, when intents
+/// could neither expire nor ask for extra confirmations.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct LegacyDepositIntent {
+    pub amount_sat: u64,
+    pub user_pubkey: String,
+    pub deposit_tracking_id: String,
+    pub deposit_address: String,
+    pub timestamp: u64,
+}
+
+impl From<LegacyDepositIntent> for DepositIntent {
+    fn from(intent: LegacyDepositIntent) -> Self {
+        Self {
+            amount_sat: intent.amount_sat,
+            user_pubkey: intent.user_pubkey,
+            deposit_tracking_id: intent.deposit_tracking_id,
+            deposit_address: intent.deposit_address,
+            timestamp: intent.timestamp,
+            expires_at: 0,
+            min_confirmations: None,
+        }
+    }
+}
+
+impl DepositIntent {
+    /// Encodes the intent for storage at [`DEPOSIT_INTENT_VERSION`].
+    pub fn to_stored_bytes(&self) -> Result<Vec<u8>, NodeError> {
+        let mut data = vec![VERSIONED_INTENT_MARKER, DEPOSIT_INTENT_VERSION];
+        data.extend(
+            bincode::encode_to_vec(self, bincode::config::standard())
+                .map_err(|e| NodeError::Error(e.to_string()))?,
+        );
+        Ok(data)
+    }
+
+    /// Decodes an intent written by [`Self::to_stored_bytes`], or one stored before the
+    /// encoding was versioned, which never expires.
+    pub fn from_stored_bytes(data: &[u8]) -> Result<Self, NodeError> {
+        match data {
+            [
+                VERSIONED_INTENT_MARKER,
+                DEPOSIT_INTENT_VERSION,
+                payload @ ..,
+            ] => {
+                let (intent, _): (Self, _) =
+                    bincode::decode_from_slice(payload, bincode::config::standard())
+                        .map_err(|e| NodeError::Error(e.to_string()))?;
+                Ok(intent)
+            }
+            [VERSIONED_INTENT_MARKER, version, ..] => Err(NodeError::Error(format!(
+                "Deposit intent uses encoding version {version}, but this build reads up to {DEPOSIT_INTENT_VERSION}"
+            ))),
+            unversioned => {
+                let (intent, _): (LegacyDepositIntent, _) =
+                    bincode::decode_from_slice(unversioned, bincode::config::standard())
+                        .map_err(|e| NodeError::Error(e.to_string()))?;
+                Ok(intent.into())
+            }
+        }
+    }
+
+    /// Whether the intent has passed its expiry at unix time `now`.
+    #[must_use]
+    pub const fn is_expired(&self, now: u64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    /// Parses the intent's deposit address, which arrives over gossip and so may be
+    /// malformed or belong to a different Bitcoin network than `network`.
+    pub fn parse_deposit_address(&self, network: Network) -> Result<Address, NodeError> {
+        Address::from_str(&self.deposit_address)
+            .map_err(|e| NodeError::MalformedDepositAddress {
+                address: self.deposit_address.clone(),
+                reason: e.to_string(),
+            })?
+            .require_network(network)
+            .map_err(|_| NodeError::DepositAddressWrongNetwork {
+                address: self.deposit_address.clone(),
+                expected: network.to_string(),
+            })
+    }
 }
 
 impl ProtoEncode for DepositIntent {
@@ -22,6 +124,8 @@ impl ProtoEncode for DepositIntent {
             deposit_tracking_id: self.deposit_tracking_id.clone(),
             deposit_address: self.deposit_address.clone(),
             timestamp: self.timestamp,
+            expires_at: self.expires_at,
+            min_confirmations: self.min_confirmations,
         };
 
         let mut buf = Vec::new();
@@ -42,16 +146,138 @@ impl ProtoDecode for DepositIntent {
             deposit_tracking_id: proto_intent.deposit_tracking_id,
             deposit_address: proto_intent.deposit_address,
             timestamp: proto_intent.timestamp,
+            expires_at: proto_intent.expires_at,
+            min_confirmations: proto_intent.min_confirmations,
         })
     }
 }
 
+/// Lifecycle stage of a deposit as reported to subscribers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepositStatus {
+    /// The address is being monitored but no funds have been seen yet.
+    Pending,
+    /// A transaction paying the address was confirmed and credited.
+    Confirmed,
+    /// The intent was cancelled before any funds reached the address.
+    Cancelled,
+}
+
+impl DepositStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Confirmed => "confirmed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Notification emitted by the deposit handler whenever a monitored address changes state.
+///
+/// `txid` is empty while the deposit is still pending.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositEvent {
+    pub txid: String,
+    pub address: String,
+    pub amount_sat: u64,
+    pub confirmations: u32,
+    pub status: DepositStatus,
+}
+
+impl DepositEvent {
+    /// An empty filter matches every address.
+    #[must_use]
+    pub fn matches(&self, address_filter: &[String]) -> bool {
+        address_filter.is_empty() || address_filter.contains(&self.address)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WithdrawlIntent {
     pub amount_sat: u64,
     pub address_to: String,
     pub public_key: String,
     pub blocks_to_confirm: Option<u16>,
+    /// Exact feerate to quote and pay, in sat/vB. Overrides the oracle estimate for
+    /// `blocks_to_confirm` when set.
+    pub fee_rate_sat_vb: Option<u64>,
+    /// Opt-in automatic RBF fee bumping once the withdrawal is broadcast.
+    pub fee_bump: Option<FeeBumpPolicy>,
+}
+
+/// How a broadcast withdrawal that fails to confirm is replaced with a higher-fee version.
+///
+/// The user is debited the quoted fee only; any increase is paid by the vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeBumpPolicy {
+    /// Bitcoin blocks the withdrawal may stay unconfirmed before its fee is bumped.
+    pub after_blocks: u32,
+    /// Upper bound on the total fee any replacement may pay.
+    pub max_fee_sat: u64,
+}
+
+/// Where a broadcast withdrawal stands on the Bitcoin network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub enum WithdrawalStatus {
+    Broadcast,
+    Confirmed,
+}
+
+/// Links a confirmed withdrawal challenge to the Bitcoin transaction paying it out, so
+/// confirmation tracking can resume after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct WithdrawalRecord {
+    pub challenge: String,
+    /// Txid of the latest broadcast version; fee bumps replace it.
+    pub txid: String,
+    pub status: WithdrawalStatus,
+    /// Bitcoin height when the latest version was broadcast.
+    pub broadcast_height: u32,
+}
+
+/// Who bears the on-chain fee when a withdrawal is executed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeePolicy {
+    /// The fee is debited from the user's balance on top of the withdrawn amount.
+    #[default]
+    UserPays,
+    /// The vault covers the fee and the user is debited only the withdrawn amount.
+    VaultAbsorbs,
+}
+
+impl FeePolicy {
+    /// Portion of `fee` charged to the user's balance under this policy.
+    #[must_use]
+    pub const fn user_fee(self, fee: u64) -> u64 {
+        match self {
+            Self::UserPays => fee,
+            Self::VaultAbsorbs => 0,
+        }
+    }
+}
+
+/// What becomes of funds paid to a vault address that has no deposit intent, e.g. one whose
+/// intent expired or was cancelled, or a reused change address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnknownDepositPolicy {
+    /// The output is logged and left uncredited.
+    Ignore,
+    /// The output is credited to `account` as if it had been deposited for it.
+    CreditToFallbackAccount { account: String },
+    /// The output is left uncredited and recorded for an operator to resolve.
+    #[default]
+    Quarantine,
+}
+
+/// A vault output with no deposit intent, held back under [`UnknownDepositPolicy::Quarantine`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantinedDeposit {
+    pub txid: String,
+    pub vout: u32,
+    pub address: String,
+    pub amount_sat: u64,
 }
 
 #[derive(Debug, Clone)]
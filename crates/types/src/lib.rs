@@ -1,9 +1,12 @@
+pub mod audit;
 pub mod broadcast;
+pub mod clock;
 pub mod consensus;
 pub mod errors;
 pub mod intents;
 pub mod network;
 pub mod proto;
+pub mod reserves;
 pub mod utxo;
 
 #[macro_use]
@@ -34,6 +34,13 @@ macro_rules! current_round_metrics {
     }};
 }
 
+#[macro_export]
+macro_rules! rejected_proposal_metrics {
+    ($reason:expr) => {{
+        metrics::counter!("consensus_proposals_rejected", "reason" => $reason).increment(1);
+    }};
+}
+
 #[macro_export]
 macro_rules! dkg_start_metrics {
     ($peer_from:expr) => {{
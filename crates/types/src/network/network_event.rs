@@ -6,8 +6,11 @@ use libp2p::{
 };
 use tokio::sync::mpsc;
 
+use crate::audit::AuditEntry;
 use crate::broadcast::BroadcastMessage;
-use crate::intents::{DepositIntent, WithdrawlIntent};
+use crate::consensus::Vote;
+use crate::intents::{DepositIntent, FeeBumpPolicy, WithdrawlIntent};
+use crate::reserves::ReserveAttestation;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct BlockInfo {
@@ -17,6 +20,57 @@ pub struct BlockInfo {
     pub transaction_count: u32,
 }
 
+/// A transaction waiting in the node's mempool, keyed by its transaction id.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct MempoolTransaction {
+    pub hash: String,
+    pub transaction_json: String,
+}
+
+/// Feerate the vault would pay to confirm within `target_blocks` blocks.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FeeEstimate {
+    pub target_blocks: u16,
+    pub sat_per_vb: f64,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SigningSessionInfo {
+    pub sign_id: u64,
+    pub message_hex: String,
+    pub selected_peer_count: u32,
+    pub commitments_received: u32,
+    pub shares_received: u32,
+    pub is_coordinator: bool,
+    pub elapsed_secs: u64,
+}
+
+/// How often a validator has taken part in consensus and signing, as observed by this node.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ValidatorStats {
+    pub peer_id: String,
+    pub proposals: u64,
+    pub votes: u64,
+    pub signing_sessions: u64,
+    /// Unix seconds of the validator's latest recorded activity.
+    pub last_seen: u64,
+}
+
+/// Chain parameters fixed by the genesis block, with keys hex-encoded.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct GenesisInfo {
+    pub genesis_hash: String,
+    pub timestamp: u64,
+    pub vault_pub_key: String,
+    pub min_signers: u16,
+    pub max_signers: u16,
+    pub min_stake: u64,
+    pub block_time_seconds: u64,
+    pub max_block_size: u64,
+    /// Validator public keys and their stake.
+    pub validators: Vec<(String, u64)>,
+}
+
 #[derive(Debug, Clone)]
 pub enum NetworkEvent {
     SelfRequest {
@@ -34,6 +88,13 @@ pub enum NetworkEvent {
     SendBroadcast {
         message: BroadcastMessage,
     },
+    SendPrivateMessage {
+        peer_id: PeerId,
+        message: DirectMessage,
+    },
+    BlockFinalized {
+        height: u64,
+    },
     Unknown,
 }
 
@@ -46,9 +107,16 @@ pub struct PingBody {
 pub enum DirectMessage {
     Ping(PingBody),
     Round2Package(round2::Package),
+    /// Acknowledges a round2 package by echoing the SHA-256 of its serialization.
+    Round2Ack {
+        package_hash: Vec<u8>,
+    },
     SignRequest {
         sign_id: u64,
         message: Vec<u8>,
+        /// Scalar each signer adds to its share before signing, big-endian, for spends of a
+        /// tweaked wallet address; empty to sign with the group key itself.
+        tweak: Vec<u8>,
     },
     SignPackage {
         sign_id: u64,
@@ -63,6 +131,37 @@ pub enum DirectMessage {
         sign_id: u64,
         signature_share: Vec<u8>,
     },
+    /// Sent to every newly connected peer so both sides can check they speak the same
+    /// protocol on the same chain before interoperating.
+    Hello {
+        protocol_version: u32,
+        chain_id: String,
+    },
+    /// A consensus vote sent to the round's aggregator instead of being gossiped, signed
+    /// by the voter's identity key.
+    ConsensusVote {
+        vote: Vote,
+        signature: Vec<u8>,
+    },
+}
+
+impl DirectMessage {
+    /// Short name of the message variant, for logs.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Ping(_) => "ping",
+            Self::Round2Package(_) => "round2_package",
+            Self::Round2Ack { .. } => "round2_ack",
+            Self::SignRequest { .. } => "sign_request",
+            Self::SignPackage { .. } => "sign_package",
+            Self::Pong => "pong",
+            Self::Commitments { .. } => "commitments",
+            Self::SignatureShare { .. } => "signature_share",
+            Self::Hello { .. } => "hello",
+            Self::ConsensusVote { .. } => "consensus_vote",
+        }
+    }
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -70,16 +169,40 @@ pub enum SelfRequest {
     CreateDeposit {
         user_pubkey: String,
         amount_sat: u64,
+        min_confirmations: Option<u32>,
     },
     GetPendingDepositIntents,
+    AddWatchAddress {
+        address: String,
+    },
+    VerifyDepositAddress {
+        deposit_address: String,
+    },
+    CancelDepositIntent {
+        deposit_tracking_id: String,
+    },
     StartSigningSession {
         hex_message: String,
     },
+    GetActiveSigningSessions,
     Spend {
         amount_sat: u64,
         fee: u64,
         address_to: String,
         user_pubkey: String,
+        fee_bump: Option<FeeBumpPolicy>,
+        /// Challenge of the confirmed withdrawal this spend pays out, if any.
+        challenge: Option<String>,
+    },
+    /// The payout for withdrawal `challenge` was signed and broadcast as `txid`.
+    WithdrawalBroadcast {
+        challenge: String,
+        txid: String,
+    },
+    /// A fee bump replaced broadcast withdrawal `replaced_txid` with `txid`.
+    WithdrawalReplaced {
+        replaced_txid: String,
+        txid: String,
     },
     ProposeWithdrawal {
         withdrawal_intent: WithdrawlIntent,
@@ -91,6 +214,8 @@ pub enum SelfRequest {
     CheckBalance {
         address: String,
     },
+    GetVaultBalance,
+    GetFeeEstimates,
     ConfirmDeposit {
         confirmed_tx: Transaction,
     },
@@ -101,7 +226,28 @@ pub enum SelfRequest {
     GetLatestBlocks {
         count: u32,
     },
-    Tick,
+    GetBlock {
+        height: u64,
+    },
+    GetMempool,
+    GetAuditLog {
+        from: u64,
+        to: u64,
+    },
+    GetGenesis,
+    GetValidatorStats,
+    /// Signs the vault's current UTXO set with the group key; answered once signing completes.
+    GetReserveAttestation,
+    /// Signs a fixed test digest and verifies the result against the group key; answered
+    /// once signing completes or fails to start.
+    RunSigningSelfTest,
+    RestartDkg {
+        force: bool,
+    },
+    /// Fires at the consensus cadence: round timeouts, retransmits and liveness checks.
+    ConsensusTick,
+    /// Fires at a slower cadence for garbage collection and other background upkeep.
+    MaintenanceTick,
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -116,9 +262,21 @@ pub enum SelfResponse {
     GetPendingDepositIntentsResponse {
         intents: Vec<DepositIntent>,
     },
+    AddWatchAddressResponse {
+        deposit_tracking_id: String,
+    },
+    VerifyDepositAddressResponse {
+        verified: bool,
+    },
+    CancelDepositIntentResponse {
+        deposit_address: String,
+    },
     StartSigningSessionResponse {
         sign_id: u64,
     },
+    GetActiveSigningSessionsResponse {
+        sessions: Vec<SigningSessionInfo>,
+    },
     SpendRequestSent {
         sighash: String,
     },
@@ -132,6 +290,13 @@ pub enum SelfResponse {
     CheckBalanceResponse {
         balance_satoshis: u64,
     },
+    GetVaultBalanceResponse {
+        spendable_satoshis: u64,
+        total_satoshis: u64,
+    },
+    GetFeeEstimatesResponse {
+        estimates: Vec<FeeEstimate>,
+    },
     NodeError(crate::errors::NodeError),
     GetChainInfoResponse {
         latest_height: u64,
@@ -146,4 +311,31 @@ pub enum SelfResponse {
     GetLatestBlocksResponse {
         blocks: Vec<BlockInfo>,
     },
+    GetBlockResponse {
+        block_json: Option<String>,
+    },
+    GetMempoolResponse {
+        transactions: Vec<MempoolTransaction>,
+    },
+    GetAuditLogResponse {
+        entries: Vec<AuditEntry>,
+    },
+    GetGenesisResponse {
+        genesis: Option<GenesisInfo>,
+    },
+    GetValidatorStatsResponse {
+        validators: Vec<ValidatorStats>,
+    },
+    GetReserveAttestationResponse {
+        attestation: ReserveAttestation,
+    },
+    RunSigningSelfTestResponse {
+        passed: bool,
+        message: String,
+        elapsed_ms: u64,
+    },
+    RestartDkgResponse {
+        success: bool,
+        message: String,
+    },
 }
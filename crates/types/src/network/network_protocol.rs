@@ -12,6 +12,55 @@ use crate::{
     proto::ProtoEncode,
 };
 
+/// Version of the peer-to-peer protocol spoken by this build; peers on another version are
+/// disconnected after the connect handshake.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Gossipsub topics a node publishes on. Consensus traffic is split by message kind, and
+/// every topic is namespaced under the chain id so nodes of distinct networks sharing peers
+/// never see each other's votes, proposals or blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GossipTopic {
+    /// Deposit intents, transactions, signed withdrawals and DKG coordination.
+    Broadcast,
+    /// Leader announcements and round changes.
+    Leader,
+    /// Block proposals and finalized blocks.
+    Block,
+    /// Prevotes and precommits.
+    Vote,
+}
+
+impl GossipTopic {
+    pub const ALL: [Self; 4] = [Self::Broadcast, Self::Leader, Self::Block, Self::Vote];
+
+    #[must_use]
+    pub fn for_chain(self, chain_id: &str) -> IdentTopic {
+        let name = match self {
+            Self::Broadcast => "broadcast",
+            Self::Leader => "consensus/leader",
+            Self::Block => "consensus/block",
+            Self::Vote => "consensus/vote",
+        };
+        IdentTopic::new(format!("{chain_id}/{name}"))
+    }
+}
+
+/// Gossipsub topic carrying the non-consensus broadcasts for `chain_id`.
+#[must_use]
+pub fn broadcast_topic(chain_id: &str) -> IdentTopic {
+    GossipTopic::Broadcast.for_chain(chain_id)
+}
+
+/// Every topic a node on `chain_id` subscribes to.
+#[must_use]
+pub fn chain_topics(chain_id: &str) -> Vec<IdentTopic> {
+    GossipTopic::ALL
+        .iter()
+        .map(|topic| topic.for_chain(chain_id))
+        .collect()
+}
+
 pub type NetworkResponseFuture =
     Pin<Box<dyn Future<Output = Result<SelfResponse, NetworkError>> + Send>>;
 
@@ -20,6 +69,8 @@ pub struct NetworkHandle {
     pub peer_id: PeerId,
     pub tx: mpsc::UnboundedSender<NetworkMessage>,
     pub peers_to_names: std::collections::BTreeMap<PeerId, String>,
+    /// Namespace of the gossip topics broadcasts are published on.
+    pub chain_id: String,
 }
 
 #[derive(Clone, Debug)]
@@ -29,6 +80,7 @@ pub enum NetworkMessage {
         message: Vec<u8>,
     },
     SendPrivateMessage(PeerId, DirectMessage),
+    DisconnectPeer(PeerId),
     SendSelfRequest {
         request: SelfRequest,
         response_channel: Option<mpsc::UnboundedSender<SelfResponse>>,
@@ -49,6 +101,7 @@ pub trait Network: Clone + Debug + Sync + Send {
         sync: bool,
     ) -> Result<Option<NetworkResponseFuture>, NetworkError>;
     fn peer_name(&self, peer_id: &PeerId) -> String;
+    fn disconnect_peer(&self, peer_id: PeerId) -> Result<(), NetworkError>;
 }
 
 impl Network for NetworkHandle {
@@ -58,7 +111,7 @@ impl Network for NetworkHandle {
 
     fn send_broadcast(&self, message: impl ProtoEncode) -> Result<(), NetworkError> {
         let network_message = NetworkMessage::SendBroadcast {
-            topic: IdentTopic::new("broadcast"),
+            topic: message.gossip_topic().for_chain(&self.chain_id),
             message: message.encode().map_err(NetworkError::SendError)?,
         };
         self.tx
@@ -117,6 +170,12 @@ impl Network for NetworkHandle {
             .get(peer_id)
             .map_or_else(|| peer_id.to_string(), Clone::clone)
     }
+
+    fn disconnect_peer(&self, peer_id: PeerId) -> Result<(), NetworkError> {
+        self.tx
+            .send(NetworkMessage::DisconnectPeer(peer_id))
+            .map_err(|e| NetworkError::SendError(e.to_string()))
+    }
 }
 
 impl NetworkHandle {
@@ -125,11 +184,13 @@ impl NetworkHandle {
         peer_id: PeerId,
         tx: mpsc::UnboundedSender<NetworkMessage>,
         peers_to_names: std::collections::BTreeMap<PeerId, String>,
+        chain_id: String,
     ) -> Self {
         Self {
             peer_id,
             tx,
             peers_to_names,
+            chain_id,
         }
     }
 }
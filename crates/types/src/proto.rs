@@ -1,5 +1,5 @@
 #![allow(clippy::all, clippy::pedantic, clippy::nursery)]
-use crate::network::network_event;
+use crate::network::{network_event, network_protocol::GossipTopic};
 
 pub mod p2p_proto {
     tonic::include_proto!("p2p");
@@ -33,9 +33,18 @@ impl From<network_event::DirectMessage> for p2p_proto::DirectMessage {
                     package_data: serialized,
                 })
             }
-            network_event::DirectMessage::SignRequest { sign_id, message } => {
-                Message::SignRequest(p2p_proto::SignRequest { sign_id, message })
+            network_event::DirectMessage::Round2Ack { package_hash } => {
+                Message::Round2Ack(p2p_proto::Round2Ack { package_hash })
             }
+            network_event::DirectMessage::SignRequest {
+                sign_id,
+                message,
+                tweak,
+            } => Message::SignRequest(p2p_proto::SignRequest {
+                sign_id,
+                message,
+                tweak,
+            }),
             network_event::DirectMessage::SignPackage { sign_id, package } => {
                 Message::SignPackage(p2p_proto::SignPackage { sign_id, package })
             }
@@ -53,6 +62,19 @@ impl From<network_event::DirectMessage> for p2p_proto::DirectMessage {
                 sign_id,
                 signature_share,
             }),
+            network_event::DirectMessage::Hello {
+                protocol_version,
+                chain_id,
+            } => Message::Hello(p2p_proto::HelloMessage {
+                protocol_version,
+                chain_id,
+            }),
+            network_event::DirectMessage::ConsensusVote { vote, signature } => {
+                Message::SignedVote(p2p_proto::SignedVote {
+                    vote: Some((&vote).into()),
+                    signature,
+                })
+            }
         };
 
         Self {
@@ -77,9 +99,13 @@ impl TryFrom<p2p_proto::DirectMessage> for network_event::DirectMessage {
                     .map_err(|e| format!("Failed to deserialize round2 package: {e}"))?;
                 Ok(Self::Round2Package(round2_package))
             }
+            Message::Round2Ack(ack) => Ok(Self::Round2Ack {
+                package_hash: ack.package_hash,
+            }),
             Message::SignRequest(req) => Ok(Self::SignRequest {
                 sign_id: req.sign_id,
                 message: req.message,
+                tweak: req.tweak,
             }),
             Message::SignPackage(pkg) => Ok(Self::SignPackage {
                 sign_id: pkg.sign_id,
@@ -93,12 +119,25 @@ impl TryFrom<p2p_proto::DirectMessage> for network_event::DirectMessage {
                 sign_id: share.sign_id,
                 signature_share: share.signature_share,
             }),
+            Message::Hello(hello) => Ok(Self::Hello {
+                protocol_version: hello.protocol_version,
+                chain_id: hello.chain_id,
+            }),
+            Message::SignedVote(signed) => Ok(Self::ConsensusVote {
+                vote: signed.vote.ok_or("Missing vote field")?.try_into()?,
+                signature: signed.signature,
+            }),
         }
     }
 }
 
 pub trait ProtoEncode {
     fn encode(&self) -> Result<Vec<u8>, String>;
+
+    /// Topic the message is gossiped on when broadcast.
+    fn gossip_topic(&self) -> GossipTopic {
+        GossipTopic::Broadcast
+    }
 }
 
 pub trait ProtoDecode {
@@ -0,0 +1,94 @@
+use bitcoin::hashes::{Hash, sha256};
+use bitcoin::{OutPoint, ScriptBuf};
+use frost_secp256k1 as frost;
+
+/// Prefix of the UTXO commitment, so it cannot be mistaken for another hash.
+const UTXO_COMMITMENT_DOMAIN: &[u8] = b"threshold/reserve-utxos/v1";
+/// Prefix of the signed attestation digest.
+const ATTESTATION_DOMAIN: &[u8] = b"threshold/reserve-attestation/v1";
+
+/// A vault output listed in a reserve attestation.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReserveUtxo {
+    pub outpoint: OutPoint,
+    pub value_sat: u64,
+    pub script_pubkey: ScriptBuf,
+}
+
+/// The vault's UTXO set at a chain height, signed by the group key. Anyone holding the group
+/// verifying key can check the signature, recompute the commitment from the listed UTXOs and
+/// look each of them up on Bitcoin.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReserveAttestation {
+    /// Height of the threshold chain when the UTXO set was read.
+    pub height: u64,
+    /// Every tracked vault output, sorted by outpoint.
+    pub utxos: Vec<ReserveUtxo>,
+    pub total_value: u64,
+    /// Serialized FROST signature over [`Self::digest`].
+    pub signature: Vec<u8>,
+}
+
+impl ReserveAttestation {
+    /// An unsigned attestation of `utxos` at `height`.
+    #[must_use]
+    pub fn new(height: u64, mut utxos: Vec<ReserveUtxo>) -> Self {
+        utxos.sort_by_key(|utxo| utxo.outpoint);
+        let total_value = utxos.iter().map(|utxo| utxo.value_sat).sum();
+        Self {
+            height,
+            utxos,
+            total_value,
+            signature: Vec::new(),
+        }
+    }
+
+    /// Hash of every listed UTXO in outpoint order: its consensus-encoded outpoint, value
+    /// and length-prefixed `script_pubkey`.
+    #[must_use]
+    pub fn utxo_commitment(&self) -> [u8; 32] {
+        let mut utxos: Vec<&ReserveUtxo> = self.utxos.iter().collect();
+        utxos.sort_by_key(|utxo| utxo.outpoint);
+
+        let mut bytes = UTXO_COMMITMENT_DOMAIN.to_vec();
+        for utxo in utxos {
+            bytes.extend_from_slice(&bitcoin::consensus::encode::serialize(&utxo.outpoint));
+            bytes.extend_from_slice(&utxo.value_sat.to_be_bytes());
+            bytes.extend_from_slice(&(utxo.script_pubkey.len() as u64).to_be_bytes());
+            bytes.extend_from_slice(utxo.script_pubkey.as_bytes());
+        }
+        sha256::Hash::hash(&bytes).to_byte_array()
+    }
+
+    /// The 32-byte message the group key signs.
+    #[must_use]
+    pub fn digest(&self) -> [u8; 32] {
+        attestation_digest(self.height, self.total_value, &self.utxo_commitment())
+    }
+
+    /// Whether the listed UTXOs add up to `total_value` and `signature` is the group's
+    /// signature over the resulting digest.
+    #[must_use]
+    pub fn verify(&self, verifying_key: &frost::VerifyingKey) -> bool {
+        let listed: Option<u64> = self
+            .utxos
+            .iter()
+            .try_fold(0u64, |acc, utxo| acc.checked_add(utxo.value_sat));
+        if listed != Some(self.total_value) {
+            return false;
+        }
+        frost::Signature::deserialize(&self.signature)
+            .is_ok_and(|signature| verifying_key.verify(&self.digest(), &signature).is_ok())
+    }
+}
+
+/// Digest of `(height, total_value, utxo_commitment)` under the attestation domain, for
+/// verifiers that only hold the commitment rather than the full UTXO list.
+#[must_use]
+pub fn attestation_digest(height: u64, total_value: u64, utxo_commitment: &[u8; 32]) -> [u8; 32] {
+    let mut bytes = ATTESTATION_DOMAIN.to_vec();
+    bytes.extend_from_slice(&height.to_be_bytes());
+    bytes.extend_from_slice(&total_value.to_be_bytes());
+    bytes.extend_from_slice(utxo_commitment);
+    sha256::Hash::hash(&bytes).to_byte_array()
+}
@@ -0,0 +1,257 @@
+#[cfg(test)]
+mod balance_tests {
+    use crate::mocks::network::{MockNodeCluster, MockNodeState};
+    use node::handlers::{Handler, balance::BalanceState};
+    use protocol::transaction::{Operation, Transaction, TransactionType};
+    use tokio::sync::mpsc::unbounded_channel;
+    use types::network::network_event::{NetworkEvent, SelfRequest, SelfResponse};
+    use types::proto::node_proto::GetMempoolRequest;
+
+    const ADDRESS: &str = "balance_cache_test_address";
+
+    fn deposit(amount: u64) -> Transaction {
+        Transaction::new(
+            TransactionType::Deposit,
+            vec![
+                Operation::OpPush {
+                    value: amount.to_be_bytes().to_vec(),
+                },
+                Operation::OpPush {
+                    value: ADDRESS.as_bytes().to_vec(),
+                },
+                Operation::OpIncrementBalance,
+            ],
+            None,
+        )
+    }
+
+    async fn finalize_block(node: &mut MockNodeState, transactions: Vec<Transaction>) {
+        for transaction in transactions {
+            node.chain_interface_tx
+                .send_message_with_response(abci::ChainMessage::AddTransactionToBlock {
+                    transaction,
+                })
+                .await
+                .expect("Failed to add transaction");
+        }
+
+        let abci::ChainResponse::GetProposedBlock { block } = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetProposedBlock {
+                previous_block: None,
+                proposer: vec![1, 2, 3, 4],
+            })
+            .await
+            .expect("Failed to get proposed block")
+        else {
+            panic!("Unexpected response to GetProposedBlock");
+        };
+
+        node.chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::FinalizeBlock { block })
+            .await
+            .expect("Failed to finalize block");
+    }
+
+    async fn credit_account(node: &mut MockNodeState, amount: u64) {
+        finalize_block(node, vec![deposit(amount)]).await;
+    }
+
+    async fn check_balance(state: &mut BalanceState, node: &mut MockNodeState) -> u64 {
+        let (tx, mut rx) = unbounded_channel();
+        state
+            .handle(
+                node,
+                NetworkEvent::SelfRequest {
+                    request: SelfRequest::CheckBalance {
+                        address: ADDRESS.to_string(),
+                    },
+                    response_channel: Some(tx),
+                },
+            )
+            .await
+            .expect("CheckBalance failed");
+
+        match rx.recv().await {
+            Some(SelfResponse::CheckBalanceResponse { balance_satoshis }) => balance_satoshis,
+            other => panic!("Unexpected response: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_balance_queries_are_served_from_cache_until_finalization() {
+        let mut cluster = MockNodeCluster::new(1).await;
+        cluster.setup().await;
+        let peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&peer).unwrap();
+
+        let mut state = BalanceState::new();
+        credit_account(node, 1_000).await;
+        assert_eq!(check_balance(&mut state, node).await, 1_000);
+
+        // The chain moves on, but a second query within the TTL must not reach it.
+        credit_account(node, 500).await;
+        assert_eq!(check_balance(&mut state, node).await, 1_000);
+
+        state
+            .handle(node, NetworkEvent::BlockFinalized { height: 2 })
+            .await
+            .unwrap();
+        assert_eq!(check_balance(&mut state, node).await, 1_500);
+    }
+
+    #[tokio::test]
+    async fn get_block_returns_canonical_json_with_decoded_operations() {
+        let mut cluster = MockNodeCluster::new(1).await;
+        cluster.setup().await;
+        let peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&peer).unwrap();
+
+        credit_account(node, 2_500).await;
+
+        let mut state = BalanceState::new();
+        let mut block_json = Vec::new();
+        for _ in 0..2 {
+            let (tx, mut rx) = unbounded_channel();
+            state
+                .handle(
+                    node,
+                    NetworkEvent::SelfRequest {
+                        request: SelfRequest::GetBlock { height: 1 },
+                        response_channel: Some(tx),
+                    },
+                )
+                .await
+                .expect("GetBlock failed");
+
+            match rx.recv().await {
+                Some(SelfResponse::GetBlockResponse {
+                    block_json: Some(json),
+                }) => block_json.push(json),
+                other => panic!("Unexpected response: {other:?}"),
+            }
+        }
+        assert_eq!(block_json[0], block_json[1]);
+
+        let block: serde_json::Value = serde_json::from_str(&block_json[0]).unwrap();
+        let keys: Vec<_> = block.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys, ["hash", "header", "transactions"]);
+        assert!(block_json[0].find("\"height\"") < block_json[0].find("\"previous_block_hash\""));
+
+        let transaction = &block["transactions"][0];
+        assert_eq!(transaction["type"], "Deposit");
+        let operations = transaction["operations"].as_array().unwrap();
+        assert_eq!(operations.len(), 3);
+        assert_eq!(operations[0]["op"], "OpPush");
+        assert_eq!(operations[0]["number"], 2_500);
+        assert_eq!(operations[1]["text"], ADDRESS);
+        assert_eq!(operations[2]["op"], "OpIncrementBalance");
+    }
+
+    #[tokio::test]
+    async fn get_latest_blocks_reports_each_finalized_block() {
+        let mut cluster = MockNodeCluster::new(1).await;
+        cluster.setup().await;
+        let peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&peer).unwrap();
+
+        for transaction_count in 1..=3 {
+            let transactions = (1..=transaction_count).map(|i| deposit(i * 100)).collect();
+            finalize_block(node, transactions).await;
+        }
+        // Pending transactions belong to no block and must not be counted.
+        node.chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::AddTransactionToBlock {
+                transaction: deposit(1),
+            })
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = unbounded_channel();
+        BalanceState::new()
+            .handle(
+                node,
+                NetworkEvent::SelfRequest {
+                    request: SelfRequest::GetLatestBlocks { count: 3 },
+                    response_channel: Some(tx),
+                },
+            )
+            .await
+            .expect("GetLatestBlocks failed");
+        let Some(SelfResponse::GetLatestBlocksResponse { blocks }) = rx.recv().await else {
+            panic!("Unexpected response to GetLatestBlocks");
+        };
+
+        let heights: Vec<_> = blocks.iter().map(|b| b.height).collect();
+        assert_eq!(heights, [3, 2, 1]);
+        let counts: Vec<_> = blocks.iter().map(|b| b.transaction_count).collect();
+        assert_eq!(counts, [3, 2, 1]);
+
+        for info in &blocks {
+            let abci::ChainResponse::GetBlock { block: Some(block) } = node
+                .chain_interface_tx
+                .send_message_with_response(abci::ChainMessage::GetBlock {
+                    height: info.height,
+                })
+                .await
+                .unwrap()
+            else {
+                panic!("Block {} was not stored", info.height);
+            };
+            assert_eq!(info.hash, hex::encode(block.hash()));
+            assert!(info.timestamp > 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn get_mempool_returns_pending_transactions_with_stable_hashes() {
+        let mut cluster = MockNodeCluster::new(1).await;
+        cluster.setup().await;
+        let peer = *cluster.nodes.keys().next().unwrap();
+
+        let transactions = [
+            Transaction::create_withdrawal_transaction("alice", "destination_a", 1_000).unwrap(),
+            Transaction::create_withdrawal_transaction("bob", "destination_b", 2_000).unwrap(),
+        ];
+        for transaction in &transactions {
+            cluster.nodes[&peer]
+                .chain_interface_tx
+                .clone()
+                .send_message_with_response(abci::ChainMessage::AddTransactionToBlock {
+                    transaction: transaction.clone(),
+                })
+                .await
+                .expect("Failed to add transaction");
+        }
+
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            let network = cluster.networks[&peer].clone();
+            let rpc = tokio::spawn(async move {
+                grpc::grpc_operator::get_mempool(&network, GetMempoolRequest {}).await
+            });
+            tokio::task::yield_now().await;
+
+            let node = cluster.nodes.get_mut(&peer).unwrap();
+            while node.try_poll().await.expect("Failed to poll node") {}
+            responses.push(rpc.await.unwrap().expect("RPC failed"));
+        }
+
+        let hashes: std::collections::BTreeSet<_> = responses[0]
+            .transactions
+            .iter()
+            .map(|tx| tx.hash.clone())
+            .collect();
+        let expected: std::collections::BTreeSet<_> =
+            transactions.iter().map(|tx| hex::encode(tx.id())).collect();
+        assert_eq!(responses[0].transactions.len(), 2);
+        assert_eq!(hashes, expected);
+        assert_eq!(responses[0].transactions, responses[1].transactions);
+
+        for entry in &responses[0].transactions {
+            let json: serde_json::Value = serde_json::from_str(&entry.transaction_json).unwrap();
+            assert_eq!(json["id"], entry.hash);
+            assert_eq!(json["type"], "Withdrawal");
+        }
+    }
+}
@@ -5,6 +5,7 @@ use std::time::{Duration, Instant};
 use bitcoin::Address;
 use bitcoin::secp256k1::{Message, Secp256k1};
 use clap::{Parser, Subcommand};
+use grpc::client::{ConnectRetryPolicy, connect_with_retry};
 use hex::{decode, encode};
 use node::key_manager::generate_keys_from_mnemonic;
 use node::wallet::{TaprootWallet, Wallet};
@@ -99,7 +100,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 || {
                     let monitor_endpoint = monitor_endpoint.clone();
                     async move {
-                        let mut client = NodeControlClient::connect(monitor_endpoint).await?;
+                        let mut client =
+                            connect_with_retry(monitor_endpoint, &ConnectRetryPolicy::default())
+                                .await?;
                         let info = client
                             .get_chain_info(GetChainInfoRequest {})
                             .await?
@@ -167,10 +170,11 @@ async fn run_deposit_test(
     let (sender_address, sender_priv, sender_pub) = generate_keys_from_mnemonic(&mnemonic);
     let public_key = sender_pub.to_string();
 
-    let mut client = NodeControlClient::connect(
+    let mut client = connect_with_retry(
         endpoint
             .clone()
             .unwrap_or_else(|| "http://127.0.0.1:50051".to_string()),
+        &ConnectRetryPolicy::default(),
     )
     .await?;
 
@@ -185,6 +189,7 @@ async fn run_deposit_test(
     let req = CreateDepositIntentRequest {
         public_key: public_key.clone(),
         amount_satoshis: amount,
+        min_confirmations: None,
     };
 
     let resp = client.create_deposit_intent(req).await?.into_inner();
@@ -308,10 +313,11 @@ async fn run_withdrawal_test(
     let public_key = sender_pub.to_string();
 
     // Propose withdrawal --------------------------------------
-    let mut client = NodeControlClient::connect(
+    let mut client = connect_with_retry(
         endpoint
             .clone()
             .unwrap_or_else(|| "http://127.0.0.1:50051".to_string()),
+        &ConnectRetryPolicy::default(),
     )
     .await?;
 
@@ -330,6 +336,8 @@ async fn run_withdrawal_test(
         address_to: deposit_address.clone(),
         public_key: public_key.clone(),
         blocks_to_confirm: None,
+        fee_bump: None,
+        fee_rate_sat_vb: None,
     };
 
     let propose_resp = client.propose_withdrawal(req).await?.into_inner();
@@ -433,7 +441,11 @@ async fn check_if_dkg_keys_exist(port_range: String) -> Result<(), Box<dyn std::
 
     for (index, port) in (start_port..=end_port).enumerate() {
         let node_number = index + 1;
-        let mut client = NodeControlClient::connect(format!("http://127.0.0.1:{port}")).await?;
+        let mut client = connect_with_retry(
+            format!("http://127.0.0.1:{port}"),
+            &ConnectRetryPolicy::default(),
+        )
+        .await?;
 
         let mnemonic = std::env::var("MNEMONIC").expect("MNEMONIC env variable not set");
         let (_, _, sender_pub) = generate_keys_from_mnemonic(&mnemonic);
@@ -442,6 +454,7 @@ async fn check_if_dkg_keys_exist(port_range: String) -> Result<(), Box<dyn std::
         let req = CreateDepositIntentRequest {
             public_key: public_key.clone(),
             amount_satoshis: 1000,
+            min_confirmations: None,
         };
 
         let response = client.create_deposit_intent(req).await;
@@ -493,7 +506,7 @@ async fn run_consensus_test(
 
     let mut clients = Vec::new();
     for endpoint in &endpoints {
-        match NodeControlClient::connect(endpoint.clone()).await {
+        match connect_with_retry(endpoint.clone(), &ConnectRetryPolicy::default()).await {
             Ok(client) => {
                 println!("✅ Connected to node at {}", endpoint);
                 clients.push(client);
@@ -562,6 +575,7 @@ async fn run_consensus_test(
         let req = CreateDepositIntentRequest {
             public_key: public_key.clone(),
             amount_satoshis: amount,
+            min_confirmations: None,
         };
 
         let resp = clients[client_idx]
@@ -1,6 +1,12 @@
 #[cfg(test)]
 mod config_test {
-    use node::NodeConfig;
+    use node::{
+        NodeConfig, NodeConfigBuilder, PeerData,
+        config::Argon2Params,
+        data_dir::{CURRENT_LAYOUT_VERSION, DataDir},
+        key_manager,
+    };
+    use protocol::block::ConsensusQuorum;
 
     #[test]
     fn test_config_deserialization() {
@@ -45,5 +51,347 @@ mod config_test {
         assert_eq!(config.libp2p_tcp_port, 0);
         assert_eq!(config.confirmation_depth, 6);
         assert_eq!(config.monitor_start_block, 0);
+        assert_eq!(
+            config.key_data.encryption_params.argon2,
+            Argon2Params::default()
+        );
+        assert_eq!(config.argon2_params, Argon2Params::default());
+    }
+
+    #[test]
+    fn test_unversioned_key_file_is_migrated_and_newer_format_refused() {
+        use node::config::{CURRENT_KEY_FORMAT_VERSION, KeyStore};
+
+        let root = std::env::temp_dir().join(format!("vault-keys-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let key_file = root.join("key.json");
+        let config_file = root.join("config.yaml");
+        NodeConfigBuilder::new()
+            .key_file_path(key_file.clone())
+            .config_file_path(config_file.clone())
+            .password("test-password")
+            .build()
+            .unwrap()
+            .save_to_file()
+            .unwrap();
+
+        // A version 1 key file is the same record without a version field.
+        let mut key_file_json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&key_file).unwrap()).unwrap();
+        assert_eq!(
+            key_file_json["key_data"]["version"],
+            CURRENT_KEY_FORMAT_VERSION
+        );
+        key_file_json["key_data"]
+            .as_object_mut()
+            .unwrap()
+            .remove("version");
+        key_file_json["key_data"]["encryption_params"]
+            .as_object_mut()
+            .unwrap()
+            .remove("argon2");
+        std::fs::write(&key_file, key_file_json.to_string()).unwrap();
+
+        let config = NodeConfig::get_config(
+            Some(key_file.display().to_string()),
+            Some(config_file.display().to_string()),
+        )
+        .unwrap();
+        assert_eq!(config.key_data.version, CURRENT_KEY_FORMAT_VERSION);
+        assert!(key_manager::decrypt_keypair(&config.key_data, "test-password").is_ok());
+
+        // A record from a newer format is refused by version, before its fields are decoded.
+        key_file_json["key_data"] = serde_json::json!({ "version": 99, "layout": "unknown" });
+        let err = KeyStore::from_json(&key_file_json.to_string())
+            .err()
+            .expect("newer key format must be refused");
+        assert!(
+            err.to_string().contains("format version 99"),
+            "unexpected error: {err}"
+        );
+
+        // A version 1 record missing required fields fails with an error, not a panic.
+        key_file_json["key_data"] = serde_json::json!({ "public_key_b58": "abc" });
+        assert!(KeyStore::from_json(&key_file_json.to_string()).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_argon2_params_are_rejected() {
+        let params = Argon2Params {
+            memory_kib: 1,
+            ..Argon2Params::default()
+        };
+        assert!(params.hasher().is_err());
+    }
+
+    #[test]
+    fn test_consensus_quorum_count_must_fit_the_validator_set() {
+        let builder = || {
+            NodeConfigBuilder::new()
+                .key_file_path(std::path::PathBuf::from("key.json"))
+                .config_file_path(std::path::PathBuf::from("config.yaml"))
+                .password("test-password")
+                .allowed_peers(vec![PeerData {
+                    name: "node-two".to_string(),
+                    public_key: libp2p::PeerId::random().to_base58(),
+                }])
+        };
+
+        assert!(
+            builder()
+                .consensus_quorum(ConsensusQuorum::Count(0))
+                .build()
+                .is_err()
+        );
+        assert!(
+            builder()
+                .consensus_quorum(ConsensusQuorum::Count(3))
+                .build()
+                .is_err()
+        );
+        assert!(
+            builder()
+                .consensus_quorum(ConsensusQuorum::Count(2))
+                .build()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_inverted_withdrawal_bounds_are_rejected() {
+        let root = std::env::temp_dir().join(format!("vault-bounds-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let key_file = root.join("key.json");
+        let config_file = root.join("config.yaml");
+        let builder = || {
+            NodeConfigBuilder::new()
+                .key_file_path(key_file.clone())
+                .config_file_path(config_file.clone())
+                .password("test-password")
+        };
+
+        assert!(
+            builder()
+                .min_withdrawal_sat(10_000)
+                .max_withdrawal_sat(9_999)
+                .build()
+                .is_err()
+        );
+        builder()
+            .min_withdrawal_sat(10_000)
+            .max_withdrawal_sat(10_000)
+            .build()
+            .unwrap()
+            .save_to_file()
+            .unwrap();
+
+        // A config file edited into inverted bounds is refused on load.
+        let config_yaml = std::fs::read_to_string(&config_file).unwrap();
+        assert!(config_yaml.contains("max_withdrawal_sat: 10000"));
+        std::fs::write(
+            &config_file,
+            config_yaml.replace("max_withdrawal_sat: 10000", "max_withdrawal_sat: 9999"),
+        )
+        .unwrap();
+        let err = NodeConfig::get_config(
+            Some(key_file.display().to_string()),
+            Some(config_file.display().to_string()),
+        )
+        .err()
+        .expect("inverted withdrawal bounds must be refused");
+        assert!(err.to_string().contains("exceeds max_withdrawal_sat"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_decrypted_keypair_must_match_stored_public_key() {
+        let mut config = NodeConfigBuilder::new()
+            .key_file_path(std::path::PathBuf::from("key.json"))
+            .config_file_path(std::path::PathBuf::from("config.yaml"))
+            .password("test-password")
+            .build()
+            .unwrap();
+
+        let keypair = key_manager::decrypt_keypair(&config.key_data, "test-password").unwrap();
+        assert_eq!(
+            keypair.public().to_peer_id().to_base58(),
+            config.key_data.public_key_b58
+        );
+
+        config.key_data.public_key_b58 = libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_base58();
+        let err = key_manager::decrypt_keypair(&config.key_data, "test-password").unwrap_err();
+        assert!(err.to_string().contains("Decrypted keypair belongs to"));
+
+        assert!(key_manager::decrypt_keypair(&config.key_data, "wrong-password").is_err());
+
+        // The CLI's key generator records the protobuf-encoded public key instead.
+        config.key_data.public_key_b58 =
+            bs58::encode(keypair.public().encode_protobuf()).into_string();
+        let decrypted = key_manager::decrypt_keypair(&config.key_data, "test-password").unwrap();
+        assert_eq!(decrypted.public(), keypair.public());
+
+        config.key_data.public_key_b58 = bs58::encode(
+            libp2p::identity::Keypair::generate_ed25519()
+                .public()
+                .encode_protobuf(),
+        )
+        .into_string();
+        assert!(key_manager::decrypt_keypair(&config.key_data, "test-password").is_err());
+    }
+
+    #[test]
+    fn test_legacy_data_directory_is_migrated_on_startup() {
+        let root = std::env::temp_dir().join(format!("vault-layout-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        let legacy = NodeConfigBuilder::new()
+            .key_file_path(root.join("config.json"))
+            .config_file_path(root.join("config.yaml"))
+            .log_file_path(Some(root.join("node.log")))
+            .database_directory("nodedb.db")
+            .password("test-password")
+            .build()
+            .unwrap();
+        legacy.save_to_file().unwrap();
+        // Older builds opened their database relative to the working directory.
+        std::fs::create_dir_all("nodedb.db").unwrap();
+        std::fs::write(std::path::Path::new("nodedb.db").join("data"), b"chain").unwrap();
+        std::fs::create_dir_all(root.join("node.log")).unwrap();
+
+        let data_dir = DataDir::new(&root);
+        assert_eq!(data_dir.layout_version().unwrap(), 0);
+
+        let config = NodeConfig::load_from_data_dir(&data_dir).unwrap();
+
+        assert_eq!(data_dir.layout_version().unwrap(), CURRENT_LAYOUT_VERSION);
+        assert!(!std::path::Path::new("nodedb.db").exists());
+        for legacy_file in ["config.json", "config.yaml", "node.log"] {
+            assert!(
+                !root.join(legacy_file).exists(),
+                "{legacy_file} was left behind"
+            );
+        }
+        assert_eq!(config.key_file_path, data_dir.key_file_path());
+        assert_eq!(config.config_file_path, data_dir.config_file_path());
+        assert_eq!(config.database_directory, data_dir.database_directory());
+        assert_eq!(config.log_file_path, Some(data_dir.log_file_path()));
+        assert_eq!(
+            std::fs::read(data_dir.database_directory().join("data")).unwrap(),
+            b"chain"
+        );
+        assert!(key_manager::decrypt_keypair(&config.key_data, "test-password").is_ok());
+
+        // A second start finds the current layout and leaves it alone.
+        assert_eq!(data_dir.migrate().unwrap(), CURRENT_LAYOUT_VERSION);
+        assert!(NodeConfig::load_from_data_dir(&data_dir).is_ok());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_startup_fails_on_mismatched_dkg_key_packages() {
+        use frost_secp256k1 as frost;
+
+        #[allow(clippy::missing_safety_doc)]
+        unsafe {
+            std::env::set_var("KEY_PASSWORD", "test-password");
+        }
+        let dealer = || {
+            frost::keys::generate_with_dealer(
+                2,
+                2,
+                frost::keys::IdentifierList::Default,
+                frost::rand_core::OsRng,
+            )
+            .unwrap()
+        };
+        let (shares, pubkey_package) = dealer();
+        let (_, other_pubkey_package) = dealer();
+        let key_package =
+            frost::keys::KeyPackage::try_from(shares.into_values().next().unwrap()).unwrap();
+        assert!(node::config::verify_key_packages(&key_package, &pubkey_package).is_ok());
+
+        let mut config = NodeConfigBuilder::new()
+            .key_file_path(std::path::PathBuf::from("key.json"))
+            .config_file_path(std::path::PathBuf::from("config.yaml"))
+            .password("test-password")
+            .save_keys(false)
+            .build()
+            .unwrap();
+        config
+            .save_dkg_keys(&key_package, &other_pubkey_package)
+            .unwrap();
+
+        let (pending_events_tx, _pending_events_rx) = tokio::sync::mpsc::unbounded_channel();
+        let Err(err) = crate::mocks::network::create_node_network(
+            libp2p::PeerId::random(),
+            config,
+            pending_events_tx,
+        )
+        .await
+        else {
+            panic!("node started with a key package from another group");
+        };
+        assert!(
+            err.to_string()
+                .contains("does not match the public key package"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_logging_config_filters_per_module() {
+        use node::config::LoggingConfig;
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for Recorder {
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                let metadata = event.metadata();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", metadata.target(), metadata.level()));
+            }
+        }
+
+        let logging = LoggingConfig {
+            level: "info".to_string(),
+            modules: [("consensus".to_string(), "warn".to_string())].into(),
+            ..LoggingConfig::default()
+        };
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry()
+            .with(logging.env_filter().unwrap())
+            .with(Recorder(events.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "consensus::consensus_interface", "suppressed");
+            tracing::warn!(target: "consensus::consensus_interface", "kept");
+            tracing::info!(target: "node::main_loop", "kept");
+            tracing::debug!(target: "node::main_loop", "suppressed");
+        });
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "consensus::consensus_interface WARN".to_string(),
+                "node::main_loop INFO".to_string(),
+            ]
+        );
+
+        let typo = LoggingConfig {
+            level: "loud".to_string(),
+            ..LoggingConfig::default()
+        };
+        assert!(typo.env_filter().is_err());
     }
 }
@@ -3,16 +3,18 @@ mod block_consensus_tests {
     use crate::mocks::network::MockNodeCluster;
     use libp2p::PeerId;
     use protocol::{
-        block::{ChainConfig, ValidatorInfo},
+        block::{ChainConfig, ConsensusQuorum, ValidatorInfo},
         transaction::{Operation, Transaction, TransactionType},
     };
     use tokio::sync::mpsc::unbounded_channel;
     use types::{
         broadcast::BroadcastMessage,
-        consensus::{Vote, VoteType},
+        consensus::{Vote, VoteType, unix_timestamp},
         intents::DepositIntent,
         network::network_protocol::Network,
-        proto::node_proto::{CreateDepositIntentRequest, CreateDepositIntentResponse},
+        proto::node_proto::{
+            CreateDepositIntentRequest, CreateDepositIntentResponse, GetGenesisRequest,
+        },
     };
 
     #[tokio::test]
@@ -88,6 +90,39 @@ mod block_consensus_tests {
         println!("🎉 Block consensus test completed successfully!");
     }
 
+    #[tokio::test]
+    async fn get_genesis_returns_chain_config_and_vault_key() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+        setup_genesis_block(&mut cluster).await;
+
+        let peer_id = cluster.get_peer_ids()[0];
+        let expected_vault_key = hex::encode(
+            cluster.nodes[&peer_id]
+                .pubkey_package
+                .as_ref()
+                .unwrap()
+                .verifying_key()
+                .serialize()
+                .unwrap(),
+        );
+
+        let network = cluster.networks[&peer_id].clone();
+        let rpc = tokio::spawn(async move {
+            grpc::grpc_operator::get_genesis(&network, GetGenesisRequest {}).await
+        });
+        tokio::task::yield_now().await;
+        cluster.run_n_iterations(1).await;
+
+        let genesis = rpc.await.unwrap().expect("GetGenesis failed");
+        assert_eq!(genesis.min_signers, 3);
+        assert_eq!(genesis.max_signers, 3);
+        assert_eq!(genesis.block_time_seconds, 1);
+        assert_eq!(genesis.max_block_size, 1_000_000);
+        assert_eq!(genesis.vault_pub_key, expected_vault_key);
+        assert_eq!(genesis.validators.len(), 3);
+    }
+
     async fn setup_genesis_block(cluster: &mut MockNodeCluster) {
         let peer_ids = cluster.get_peer_ids();
         let first_peer = peer_ids[0];
@@ -117,6 +152,7 @@ mod block_consensus_tests {
             min_stake: 50,
             block_time_seconds: 1,
             max_block_size: 1_000_000,
+            consensus_quorum: ConsensusQuorum::default(),
         };
 
         // Create genesis block on all nodes
@@ -153,6 +189,7 @@ mod block_consensus_tests {
                 CreateDepositIntentRequest {
                     public_key: user_pubkey,
                     amount_satoshis: amount,
+                    min_confirmations: None,
                 },
             )
             .await
@@ -253,6 +290,7 @@ mod block_consensus_tests {
         for &peer_id in peer_ids {
             let prevote = Vote {
                 vote_type: VoteType::Prevote,
+                timestamp: unix_timestamp(),
                 height,
                 round: 0,
                 block_hash: block_hash.clone(),
@@ -269,6 +307,7 @@ mod block_consensus_tests {
         for &peer_id in peer_ids {
             let precommit = Vote {
                 vote_type: VoteType::Precommit,
+                timestamp: unix_timestamp(),
                 height,
                 round: 0,
                 block_hash: block_hash.clone(),
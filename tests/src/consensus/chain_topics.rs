@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod chain_topic_tests {
+    use crate::mocks::network::MockNodeCluster;
+    use consensus::{ConsensusMessage, ConsensusResponse};
+    use libp2p::PeerId;
+    use tokio::sync::mpsc;
+    use types::{
+        broadcast::BroadcastMessage,
+        consensus::{
+            ConsensusMessage as ConsensusNetMessage, LeaderAnnouncement, Vote, VoteType,
+            unix_timestamp,
+        },
+        network::network_event::NetworkEvent,
+        network::network_protocol::{GossipTopic, Network},
+    };
+
+    fn prevote(voter: PeerId) -> BroadcastMessage {
+        BroadcastMessage::Consensus(ConsensusNetMessage::Vote(Vote {
+            round: 1,
+            height: 0,
+            block_hash: vec![7u8; 32],
+            voter: voter.to_bytes(),
+            vote_type: VoteType::Prevote,
+            timestamp: unix_timestamp(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn clusters_on_different_chains_do_not_exchange_votes() {
+        let mut alpha = MockNodeCluster::new_with_chain_id(2, "alpha").await;
+        let mut beta = MockNodeCluster::new_with_chain_id(2, "beta").await;
+        alpha.setup().await;
+        beta.setup().await;
+
+        let alpha_peers = alpha.get_peer_ids();
+        let (receiver, alpha_voter) = (alpha_peers[0], alpha_peers[1]);
+        let beta_voter = beta.get_peer_ids()[0];
+
+        // Record every vote the receiving node hands to consensus.
+        let (consensus_tx, mut consensus_rx) = messenger::channel(100, Some(100));
+        let (votes_tx, mut votes_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((message, reply)) = consensus_rx.recv().await {
+                if let ConsensusMessage::HandleVote { sender, .. } = message {
+                    let _ = votes_tx.send(PeerId::from_bytes(&sender).unwrap());
+                }
+                let _ = reply.send(ConsensusResponse::HandleVote {
+                    accepted: true,
+                    error: None,
+                });
+            }
+        });
+        alpha
+            .nodes
+            .get_mut(&receiver)
+            .unwrap()
+            .consensus_interface_tx = consensus_tx;
+
+        // The beta validator is connected to the alpha node and its gossip reaches it.
+        alpha
+            .senders
+            .get_mut(&receiver)
+            .unwrap()
+            .pending_events
+            .push(NetworkEvent::PeersConnected(vec![(
+                beta_voter,
+                libp2p::Multiaddr::empty(),
+            )]));
+
+        beta.networks[&beta_voter]
+            .send_broadcast(prevote(beta_voter))
+            .unwrap();
+        while let Ok(pending) = beta.pending_events_rx.try_recv() {
+            if let NetworkEvent::GossipsubMessage(message) = pending.event {
+                alpha
+                    .senders
+                    .get_mut(&receiver)
+                    .unwrap()
+                    .pending_events
+                    .push(NetworkEvent::GossipsubMessage(message));
+            }
+        }
+
+        alpha.networks[&alpha_voter]
+            .send_broadcast(prevote(alpha_voter))
+            .unwrap();
+
+        alpha.run_n_iterations(3).await;
+        tokio::task::yield_now().await;
+
+        let mut voters = Vec::new();
+        while let Ok(voter) = votes_rx.try_recv() {
+            voters.push(voter);
+        }
+
+        assert!(
+            voters.contains(&alpha_voter),
+            "vote from the same chain should reach consensus"
+        );
+        assert!(
+            !voters.contains(&beta_voter),
+            "vote from another chain must be ignored"
+        );
+    }
+
+    #[tokio::test]
+    async fn consensus_messages_are_published_on_dedicated_topics() {
+        let mut cluster = MockNodeCluster::new_with_chain_id(1, "alpha").await;
+        let peer = cluster.get_peer_ids()[0];
+        let network = cluster.networks[&peer].clone();
+
+        let messages = [
+            (
+                BroadcastMessage::Consensus(ConsensusNetMessage::LeaderAnnouncement(
+                    LeaderAnnouncement {
+                        leader: peer.to_bytes(),
+                        round: 1,
+                    },
+                )),
+                GossipTopic::Leader,
+            ),
+            (prevote(peer), GossipTopic::Vote),
+            (
+                BroadcastMessage::Consensus(ConsensusNetMessage::BlockProposal {
+                    proposer: peer.to_bytes(),
+                    raw_block: vec![1, 2, 3],
+                    tx_hashes: Vec::new(),
+                    timestamp: unix_timestamp(),
+                }),
+                GossipTopic::Block,
+            ),
+            (
+                BroadcastMessage::Transaction(vec![4, 5, 6]),
+                GossipTopic::Broadcast,
+            ),
+        ];
+
+        for (message, expected) in messages {
+            network.send_broadcast(message).unwrap();
+            let pending = cluster.pending_events_rx.try_recv().unwrap();
+            let NetworkEvent::GossipsubMessage(gossip) = pending.event else {
+                panic!("expected a gossip message");
+            };
+            assert_eq!(gossip.topic, expected.for_chain("alpha").hash());
+        }
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod liveness_tests {
+    use crate::mocks::network::MockNodeCluster;
+    use abci::{ChainMessage, ChainResponse};
+    use consensus::{
+        ConsensusInterface, ConsensusInterfaceImpl, ConsensusMessage, ConsensusResponse,
+    };
+    use node::handlers::{Handler, consensus::ConsensusState};
+    use protocol::transaction::{Operation, Transaction, TransactionType};
+    use std::time::Duration;
+    use types::network::network_event::{NetworkEvent, SelfRequest};
+
+    const STALL_THRESHOLD: Duration = Duration::from_millis(200);
+
+    fn tick() -> NetworkEvent {
+        NetworkEvent::SelfRequest {
+            request: SelfRequest::ConsensusTick,
+            response_channel: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn watchdog_fires_when_consensus_cannot_reach_quorum() {
+        let mut cluster = MockNodeCluster::new(1).await;
+        cluster.setup().await;
+        let peer_id = cluster.get_peer_ids()[0];
+        let node = cluster.nodes.get_mut(&peer_id).unwrap();
+
+        let transaction = Transaction::new(
+            TransactionType::Deposit,
+            vec![Operation::OpPush {
+                value: 1_000u64.to_be_bytes().to_vec(),
+            }],
+            None,
+        );
+        let Ok(ChainResponse::AddTransactionToBlock { error: None }) = node
+            .chain_interface_tx
+            .send_message_with_response(ChainMessage::AddTransactionToBlock { transaction })
+            .await
+        else {
+            panic!("Failed to add transaction to block");
+        };
+
+        // No validators are known, so every round is deferred and the height never moves
+        let (mut consensus, _tx) = ConsensusInterfaceImpl::new();
+        let ConsensusResponse::TriggerConsensusRound { success, .. } = consensus
+            .handle_message(ConsensusMessage::TriggerConsensusRound { force_round: true })
+            .await
+        else {
+            panic!("Unexpected consensus response");
+        };
+        assert!(!success);
+
+        let mut state = ConsensusState::new().with_stall_threshold(STALL_THRESHOLD);
+
+        state.handle(node, tick()).await.unwrap();
+        assert!(state.watchdog.alert.is_none());
+
+        tokio::time::sleep(STALL_THRESHOLD / 2).await;
+        state.handle(node, tick()).await.unwrap();
+        assert!(
+            state.watchdog.alert.is_none(),
+            "watchdog fired before the threshold"
+        );
+
+        tokio::time::sleep(STALL_THRESHOLD).await;
+        state.handle(node, tick()).await.unwrap();
+        let alert = state.watchdog.alert.expect("watchdog did not fire");
+        assert_eq!(alert.height, 0);
+        assert_eq!(alert.pending_transactions, 1);
+        assert!(alert.stalled_for >= STALL_THRESHOLD);
+    }
+}
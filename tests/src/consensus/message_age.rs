@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod message_age_tests {
+    use crate::mocks::network::MockNodeCluster;
+    use consensus::{ConsensusMessage, ConsensusResponse};
+    use libp2p::identity::Keypair;
+    use tokio::sync::mpsc;
+    use types::{
+        broadcast::BroadcastMessage,
+        consensus::{ConsensusMessage as ConsensusNetMessage, Vote, VoteType, unix_timestamp},
+        network::network_event::{DirectMessage, NetworkEvent},
+        network::network_protocol::Network,
+    };
+
+    fn signed_vote(keypair: &Keypair, timestamp: u64) -> DirectMessage {
+        let vote = Vote {
+            round: 1,
+            height: 0,
+            block_hash: vec![7u8; 32],
+            voter: keypair.public().to_peer_id().to_bytes(),
+            vote_type: VoteType::Prevote,
+            timestamp,
+        };
+        let signature = vote.sign(keypair).unwrap();
+        DirectMessage::ConsensusVote { vote, signature }
+    }
+
+    #[tokio::test]
+    async fn stale_signed_vote_is_dropped_and_fresh_one_reaches_consensus() {
+        let mut cluster = MockNodeCluster::new(2).await;
+        cluster.setup().await;
+        let receiver = cluster.get_peer_ids()[0];
+        let node = cluster.nodes.get_mut(&receiver).unwrap();
+        let max_age = node.config.max_consensus_message_age_seconds;
+
+        // Record the timestamp of every signed vote the receiving node hands to consensus.
+        let (consensus_tx, mut consensus_rx) = messenger::channel(100, Some(100));
+        let (votes_tx, mut votes_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((message, reply)) = consensus_rx.recv().await {
+                if let ConsensusMessage::HandleSignedVote { vote, .. } = message {
+                    let _ = votes_tx.send(vote.timestamp);
+                }
+                let _ = reply.send(ConsensusResponse::HandleSignedVote {
+                    accepted: true,
+                    error: None,
+                });
+            }
+        });
+        node.consensus_interface_tx = consensus_tx;
+
+        let voter = Keypair::generate_ed25519();
+        let now = unix_timestamp();
+        let stale = now - max_age - 60;
+        for timestamp in [stale, now] {
+            cluster
+                .senders
+                .get_mut(&receiver)
+                .unwrap()
+                .pending_events
+                .push(NetworkEvent::MessageEvent((
+                    voter.public().to_peer_id(),
+                    signed_vote(&voter, timestamp),
+                )));
+        }
+        cluster.run_n_iterations(5).await;
+
+        let mut delivered = Vec::new();
+        while let Ok(timestamp) = votes_rx.try_recv() {
+            delivered.push(timestamp);
+        }
+        assert_eq!(delivered, vec![now]);
+    }
+
+    #[tokio::test]
+    async fn stale_block_proposal_is_dropped_and_fresh_one_reaches_consensus() {
+        let mut cluster = MockNodeCluster::new(2).await;
+        cluster.setup().await;
+        let peers = cluster.get_peer_ids();
+        let (receiver, proposer) = (peers[0], peers[1]);
+        let node = cluster.nodes.get_mut(&receiver).unwrap();
+        let max_age = node.config.max_consensus_message_age_seconds;
+
+        // Record the block of every proposal the receiving node hands to consensus.
+        let (consensus_tx, mut consensus_rx) = messenger::channel(100, Some(100));
+        let (proposals_tx, mut proposals_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((message, reply)) = consensus_rx.recv().await {
+                if let ConsensusMessage::HandleBlockProposal { raw_block, .. } = message {
+                    let _ = proposals_tx.send(raw_block);
+                }
+                let _ = reply.send(ConsensusResponse::HandleBlockProposal {
+                    accepted: false,
+                    error: None,
+                });
+            }
+        });
+        node.consensus_interface_tx = consensus_tx;
+
+        let now = unix_timestamp();
+        let stale = now - max_age - 60;
+        for (raw_block, timestamp) in [(vec![1u8], stale), (vec![2u8], now)] {
+            cluster.networks[&proposer]
+                .send_broadcast(BroadcastMessage::Consensus(
+                    ConsensusNetMessage::BlockProposal {
+                        proposer: proposer.to_bytes(),
+                        raw_block,
+                        tx_hashes: Vec::new(),
+                        timestamp,
+                    },
+                ))
+                .unwrap();
+        }
+        cluster.run_n_iterations(5).await;
+
+        let mut delivered = Vec::new();
+        while let Ok(raw_block) = proposals_rx.try_recv() {
+            delivered.push(raw_block);
+        }
+        assert_eq!(delivered, vec![vec![2u8]]);
+    }
+}
@@ -1 +1,7 @@
 pub mod block_consensus;
+pub mod chain_topics;
+pub mod liveness;
+pub mod message_age;
+pub mod peer_gate;
+pub mod ticks;
+pub mod validator_stats;
@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod peer_gate_tests {
+    use consensus::{ConsensusInterface, ConsensusInterfaceImpl, ConsensusMessage};
+    use libp2p::{PeerId, gossipsub::IdentTopic, identity::Keypair};
+    use node::{
+        PeerData,
+        swarm_manager::{GateDecision, PeerGate, build_swarm},
+    };
+    use types::{
+        broadcast::BroadcastMessage,
+        consensus::{ConsensusMessage as ConsensusNetMessage, Vote, VoteType, unix_timestamp},
+        network::network_event::NetworkEvent,
+        proto::{ProtoDecode, ProtoEncode},
+    };
+
+    fn vote_from(peer: PeerId) -> NetworkEvent {
+        let vote = Vote {
+            round: 1,
+            height: 0,
+            block_hash: vec![7u8; 32],
+            voter: peer.to_bytes(),
+            vote_type: VoteType::Prevote,
+            timestamp: unix_timestamp(),
+        };
+
+        NetworkEvent::GossipsubMessage(libp2p::gossipsub::Message {
+            source: Some(peer),
+            data: BroadcastMessage::Consensus(ConsensusNetMessage::Vote(vote))
+                .encode()
+                .unwrap(),
+            sequence_number: None,
+            topic: IdentTopic::new("broadcast").hash(),
+        })
+    }
+
+    #[tokio::test]
+    async fn unlisted_peer_vote_is_ignored() {
+        let allowed_peer = PeerId::random();
+        let unlisted_peer = PeerId::random();
+        let peer_data = [PeerData {
+            name: "allowed".to_string(),
+            public_key: allowed_peer.to_base58(),
+        }];
+        let (_network, mut swarm_manager) =
+            build_swarm(Keypair::generate_ed25519(), 0, 0, &peer_data, "test-chain").unwrap();
+        let mut network_events = swarm_manager.network_events.subscribe();
+
+        let topic = IdentTopic::new("broadcast").hash();
+        swarm_manager.emit_from_peer(
+            unlisted_peer,
+            NetworkEvent::Subscribed {
+                peer_id: unlisted_peer,
+                topic: topic.clone(),
+            },
+        );
+        swarm_manager.emit_from_peer(unlisted_peer, vote_from(unlisted_peer));
+        swarm_manager.emit_from_peer(allowed_peer, vote_from(unlisted_peer));
+        swarm_manager.emit_from_peer(
+            allowed_peer,
+            NetworkEvent::Subscribed {
+                peer_id: allowed_peer,
+                topic,
+            },
+        );
+
+        // Feed whatever the swarm let through into consensus
+        let (mut consensus, _tx) = ConsensusInterfaceImpl::new();
+        let mut forwarded = 0;
+        while let Ok(event) = network_events.try_recv() {
+            forwarded += 1;
+            match event {
+                NetworkEvent::Subscribed { peer_id, .. } => {
+                    consensus
+                        .handle_message(ConsensusMessage::AddValidator {
+                            peer_id: peer_id.to_bytes(),
+                        })
+                        .await;
+                }
+                NetworkEvent::GossipsubMessage(message) => {
+                    if let Ok(BroadcastMessage::Consensus(ConsensusNetMessage::Vote(vote))) =
+                        BroadcastMessage::decode(&message.data)
+                    {
+                        consensus
+                            .handle_message(ConsensusMessage::HandleVote {
+                                sender: message.source.unwrap().to_bytes(),
+                                vote,
+                            })
+                            .await;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        assert_eq!(forwarded, 1);
+        assert!(consensus.state.validators.contains(&allowed_peer));
+        assert!(!consensus.state.validators.contains(&unlisted_peer));
+        assert!(consensus.state.prevotes.is_empty());
+    }
+
+    #[test]
+    fn unlisted_author_is_dropped_without_rejecting_the_allowed_relay() {
+        let allowed_relay = PeerId::random();
+        let unlisted_peer = PeerId::random();
+        let gate = PeerGate::new([allowed_relay]);
+
+        assert_eq!(
+            gate.check(&allowed_relay, &vote_from(allowed_relay)),
+            GateDecision::Admit
+        );
+        assert_eq!(
+            gate.check(&allowed_relay, &vote_from(unlisted_peer)),
+            GateDecision::DropFromAuthor(Some(unlisted_peer))
+        );
+
+        let NetworkEvent::GossipsubMessage(mut anonymous) = vote_from(allowed_relay) else {
+            unreachable!();
+        };
+        anonymous.source = None;
+        assert_eq!(
+            gate.check(&allowed_relay, &NetworkEvent::GossipsubMessage(anonymous)),
+            GateDecision::DropFromAuthor(None)
+        );
+
+        assert_eq!(
+            gate.check(&unlisted_peer, &vote_from(allowed_relay)),
+            GateDecision::RejectRelay
+        );
+    }
+}
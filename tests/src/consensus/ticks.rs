@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tick_tests {
+    use node::utils::tick_schedule::TickSchedule;
+    use std::time::Duration;
+    use tokio::time::Instant;
+    use types::network::network_event::SelfRequest;
+
+    #[test]
+    fn maintenance_ticks_fire_at_their_own_slower_cadence() {
+        let start = Instant::now();
+        let mut schedule =
+            TickSchedule::new(Duration::from_secs(10), Duration::from_secs(60), start);
+
+        let (mut consensus, mut maintenance) = (0, 0);
+        for second in 1..=120 {
+            for tick in schedule.due(start + Duration::from_secs(second)) {
+                match tick {
+                    SelfRequest::ConsensusTick => consensus += 1,
+                    SelfRequest::MaintenanceTick => maintenance += 1,
+                    other => panic!("unexpected tick {other:?}"),
+                }
+            }
+        }
+
+        assert_eq!(consensus, 12);
+        assert_eq!(maintenance, 2);
+        assert_eq!(schedule.next_deadline(), start + Duration::from_secs(130));
+    }
+
+    #[test]
+    fn late_ticks_fire_once_instead_of_catching_up() {
+        let start = Instant::now();
+        let mut schedule =
+            TickSchedule::new(Duration::from_secs(10), Duration::from_secs(60), start);
+
+        let ticks = schedule.due(start + Duration::from_secs(300));
+        assert_eq!(ticks.len(), 2);
+        assert!(schedule.due(start + Duration::from_secs(301)).is_empty());
+        assert_eq!(schedule.next_deadline(), start + Duration::from_secs(310));
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod validator_stats_tests {
+    use crate::mocks::network::MockNodeCluster;
+    use libp2p::PeerId;
+    use types::{
+        broadcast::BroadcastMessage,
+        consensus::{ConsensusMessage as ConsensusNetMessage, Vote, VoteType, unix_timestamp},
+        network::network_event::SelfRequest,
+        network::network_protocol::Network,
+        proto::node_proto::{GetValidatorStatsRequest, GetValidatorStatsResponse},
+    };
+
+    fn vote(voter: PeerId, round: u32, vote_type: VoteType) -> BroadcastMessage {
+        BroadcastMessage::Consensus(ConsensusNetMessage::Vote(Vote {
+            round,
+            height: 0,
+            block_hash: vec![7u8; 32],
+            voter: voter.to_bytes(),
+            vote_type,
+            timestamp: unix_timestamp(),
+        }))
+    }
+
+    async fn validator_stats(
+        cluster: &mut MockNodeCluster,
+        peer: PeerId,
+    ) -> GetValidatorStatsResponse {
+        let network = cluster.networks[&peer].clone();
+        let rpc = tokio::spawn(async move {
+            grpc::grpc_operator::get_validator_stats(&network, GetValidatorStatsRequest {}).await
+        });
+        tokio::task::yield_now().await;
+
+        let node = cluster.nodes.get_mut(&peer).unwrap();
+        while node.try_poll().await.expect("Failed to poll node") {}
+        rpc.await.unwrap().expect("RPC failed")
+    }
+
+    #[tokio::test]
+    async fn validator_stats_count_proposals_votes_and_signing_sessions() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let peers = cluster.get_peer_ids();
+        let (observer, leader, follower) = (peers[0], peers[1], peers[2]);
+
+        for round in 1..=2 {
+            cluster.networks[&leader]
+                .send_broadcast(BroadcastMessage::Consensus(
+                    ConsensusNetMessage::BlockProposal {
+                        proposer: leader.to_bytes(),
+                        raw_block: vec![1, 2, 3],
+                        tx_hashes: Vec::new(),
+                        timestamp: unix_timestamp(),
+                    },
+                ))
+                .unwrap();
+            for voter in [leader, follower] {
+                for vote_type in [VoteType::Prevote, VoteType::Precommit] {
+                    cluster.networks[&voter]
+                        .send_broadcast(vote(voter, round, vote_type))
+                        .unwrap();
+                }
+            }
+            cluster.run_n_iterations(2).await;
+        }
+
+        cluster.send_self_request_to_peer(
+            observer,
+            SelfRequest::StartSigningSession {
+                hex_message: hex::encode([9u8; 32]),
+            },
+        );
+        cluster.run_n_iterations(10).await;
+
+        let response = validator_stats(&mut cluster, observer).await;
+        assert_eq!(response.validators.len(), 2);
+        assert!(
+            response
+                .validators
+                .iter()
+                .all(|stats| stats.peer_id != observer.to_string()),
+            "the node must not report its own activity"
+        );
+
+        let stats_of = |peer: PeerId| {
+            response
+                .validators
+                .iter()
+                .find(|stats| stats.peer_id == peer.to_string())
+                .unwrap()
+                .clone()
+        };
+        let leader_stats = stats_of(leader);
+        assert_eq!(leader_stats.proposals, 2);
+        assert_eq!(leader_stats.votes, 4);
+        assert_eq!(leader_stats.signing_sessions, 1);
+        assert!(leader_stats.last_seen > 0);
+
+        let follower_stats = stats_of(follower);
+        assert_eq!(follower_stats.proposals, 0);
+        assert_eq!(follower_stats.votes, 4);
+        assert_eq!(follower_stats.signing_sessions, 1);
+
+        // Participants credit the coordinator that asked them to sign.
+        let response = validator_stats(&mut cluster, follower).await;
+        let coordinator = response
+            .validators
+            .iter()
+            .find(|stats| stats.peer_id == observer.to_string())
+            .unwrap();
+        assert_eq!(coordinator.signing_sessions, 1);
+        assert_eq!(coordinator.votes, 0);
+    }
+}
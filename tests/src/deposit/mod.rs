@@ -2,17 +2,43 @@
 mod deposit_tests {
     use std::str::FromStr;
 
-    use crate::mocks::network::MockNodeCluster;
+    use crate::mocks::network::{MockNodeCluster, MockNodeState};
+    use crate::mocks::pubkey::random_public_key;
     use bitcoin::Address;
     use bitcoin::hashes::Hash;
     use grpc::grpc_operator;
-    use node::{handlers::deposit::DepositIntentState, wallet::Wallet};
+    use node::{
+        handlers::{Handler, deposit::DepositIntentState},
+        wallet::Wallet,
+    };
     use tokio::sync::broadcast;
     use tokio::sync::mpsc::unbounded_channel;
     use types::intents::DepositIntent;
+    use types::network::network_event::{NetworkEvent, SelfRequest, SelfResponse};
     use types::proto::node_proto::{CreateDepositIntentRequest, CreateDepositIntentResponse};
     use uuid::Uuid;
 
+    /// Finalizes a block holding every pending transaction, as consensus would.
+    async fn finalize_pending_transactions(node: &mut MockNodeState) {
+        let Ok(abci::ChainResponse::GetProposedBlock { block }) = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetProposedBlock {
+                previous_block: None,
+                proposer: vec![1, 2, 3, 4],
+            })
+            .await
+        else {
+            panic!("Failed to get proposed block");
+        };
+        let Ok(abci::ChainResponse::FinalizeAndStoreBlock { error: None }) = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::FinalizeBlock { block })
+            .await
+        else {
+            panic!("Failed to finalize block");
+        };
+    }
+
     #[tokio::test]
     async fn deposit_intent_creates_valid_address_and_persists_on_node() {
         let mut cluster = MockNodeCluster::new_with_keys(2).await;
@@ -31,6 +57,7 @@ mod deposit_tests {
                         "020202020202020202020202020202020202020202020202020202020202020202"
                             .to_string(),
                     amount_satoshis: amount_sat,
+                    min_confirmations: None,
                 },
             )
             .await
@@ -91,6 +118,7 @@ mod deposit_tests {
                         "020202020202020202020202020202020202020202020202020202020202020202"
                             .to_string(),
                     amount_satoshis: amount_sat,
+                    min_confirmations: None,
                 },
             )
             .await
@@ -143,6 +171,7 @@ mod deposit_tests {
                 node,
                 "020202020202020202020202020202020202020202020202020202020202020202",
                 amount_sat,
+                None,
             )
             .await
             .expect("create_deposit should succeed");
@@ -194,6 +223,8 @@ mod deposit_tests {
             timestamp: 0,
             user_pubkey: "020202020202020202020202020202020202020202020202020202020202020202"
                 .to_string(),
+            expires_at: 0,
+            min_confirmations: None,
         };
 
         // Act
@@ -222,6 +253,206 @@ mod deposit_tests {
         assert_eq!(notified_addr.deposit_address, deposit_address);
     }
 
+    #[tokio::test]
+    async fn intent_cap_counts_live_intents_and_spares_intents_from_peers() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (tx, _rx) = broadcast::channel::<DepositIntent>(16);
+        let mut state = DepositIntentState::new(tx).with_max_pending_intents(1);
+
+        let peer_intent = |address: &str, expires_at: u64| DepositIntent {
+            amount_sat: 10_000,
+            deposit_tracking_id: Uuid::new_v4().to_string(),
+            deposit_address: address.to_string(),
+            timestamp: 0,
+            user_pubkey: "020202020202020202020202020202020202020202020202020202020202020202"
+                .to_string(),
+            expires_at,
+            min_confirmations: None,
+        };
+
+        // An intent past its expiry does not take up the only slot.
+        state
+            .create_deposit_from_intent(
+                node,
+                peer_intent("tb1q62qxecgfyn7ud6esrxc50xh9hs56dysatwqheh", 1),
+            )
+            .await
+            .unwrap();
+        state
+            .create_deposit(node, "local_user", 10_000, None)
+            .await
+            .expect("an expired intent should not count against the cap");
+
+        let refused = state.create_deposit(node, "local_user", 10_000, None).await;
+        assert!(matches!(
+            refused,
+            Err(types::errors::NodeError::TooManyPendingIntents { limit: 1, .. })
+        ));
+
+        // Intents gossiped by peers are tracked even with the cap reached.
+        state
+            .create_deposit_from_intent(
+                node,
+                peer_intent("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx", 0),
+            )
+            .await
+            .expect("a peer's intent should not be refused by the local cap");
+        assert_eq!(state.deposit_addresses.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn expired_unfunded_deposit_intent_is_removed_through_the_chain() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (tx, mut rx) = broadcast::channel::<DepositIntent>(8);
+        let mut state =
+            DepositIntentState::new(tx).with_intent_ttl(std::time::Duration::from_secs(60));
+        let user_pubkey = "020202020202020202020202020202020202020202020202020202020202020202";
+
+        let (_, abandoned_address) = state
+            .create_deposit(node, user_pubkey, 10_000, None)
+            .await
+            .expect("create_deposit should succeed");
+        let (_, funded_address) = state
+            .create_deposit(node, user_pubkey, 20_000, None)
+            .await
+            .expect("create_deposit should succeed");
+        let abandoned = rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+        assert_ne!(abandoned.expires_at, 0);
+
+        // Funds have reached the second address but are not credited yet
+        let funded = Address::from_str(&funded_address).unwrap().assume_checked();
+        node.wallet.utxos.push(node::wallet::TrackedUtxo {
+            utxo: types::utxo::Utxo {
+                outpoint: bitcoin::OutPoint {
+                    txid: bitcoin::Txid::from_slice(&[3u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: bitcoin::Amount::from_sat(20_000),
+                script_pubkey: funded.script_pubkey(),
+            },
+            address: funded.clone(),
+        });
+
+        // Nothing is due before the expiry
+        let expiring = state
+            .expire_intents(node, abandoned.expires_at - 1)
+            .await
+            .unwrap();
+        assert!(expiring.is_empty());
+
+        // The expiry goes through the chain and takes effect once a block carrying it is final
+        let now = abandoned.expires_at + 1;
+        let expiring = state.expire_intents(node, now).await.unwrap();
+        assert_eq!(expiring.len(), 1);
+        assert_eq!(expiring[0].deposit_address, abandoned_address);
+        assert_eq!(
+            state.get_pending_deposit_intents(node).await.unwrap().len(),
+            2
+        );
+        finalize_pending_transactions(node).await;
+
+        let remaining = state.get_pending_deposit_intents(node).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].deposit_address, funded_address);
+
+        state.settle_expired_intents(node, now).await.unwrap();
+        assert!(!state.deposit_addresses.contains(&abandoned_address));
+        assert!(state.deposit_addresses.contains(&funded_address));
+        assert_eq!(
+            state.expired_addresses.get(&abandoned_address),
+            Some(&(now + 60))
+        );
+
+        // A late payment to the expired address is still seen and falls to the unknown
+        // deposit policy, here the default quarantine
+        let abandoned_addr = Address::from_str(&abandoned_address)
+            .unwrap()
+            .assume_checked();
+        assert!(node.wallet.addresses.contains(&abandoned_addr));
+        let late_payment = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: bitcoin::OutPoint {
+                    txid: bitcoin::Txid::from_slice(&[9u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                ..Default::default()
+            }],
+            output: vec![bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(10_000),
+                script_pubkey: abandoned_addr.script_pubkey(),
+            }],
+        };
+        state
+            .insert_pending_deposit_transaction(node, &late_payment)
+            .await
+            .unwrap();
+        assert_eq!(state.quarantined_deposits.len(), 1);
+        assert_eq!(state.quarantined_deposits[0].address, abandoned_address);
+
+        // Once the window closes the address is no longer watched
+        state.settle_expired_intents(node, now + 60).await.unwrap();
+        assert!(state.expired_addresses.is_empty());
+        assert!(!node.wallet.addresses.contains(&abandoned_addr));
+        assert!(node.wallet.addresses.contains(&funded));
+
+        let unwatched = rx.recv().await.unwrap();
+        assert_eq!(unwatched.deposit_address, abandoned.deposit_address);
+        assert!(unwatched.is_expired(now + 60));
+    }
+
+    #[tokio::test]
+    async fn deposit_intent_reaches_monitor_that_subscribes_late() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        // The monitor is not subscribed, so the intent cannot be handed to it yet
+        let (tx, rx) = broadcast::channel::<DepositIntent>(8);
+        drop(rx);
+        let mut state = DepositIntentState::new(tx.clone());
+        let (_, deposit_address) = state
+            .create_deposit(node, "late_monitor_user", 10_000, None)
+            .await
+            .expect("create_deposit should succeed");
+        assert_eq!(state.unsent_intents.len(), 1);
+
+        // The monitor reconnects and the next maintenance tick hands it the queued address
+        let mut monitor_rx = tx.subscribe();
+        state
+            .handle(
+                node,
+                NetworkEvent::SelfRequest {
+                    request: SelfRequest::MaintenanceTick,
+                    response_channel: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert!(state.unsent_intents.is_empty());
+        let watched = monitor_rx.recv().await.unwrap();
+        assert_eq!(watched.deposit_address, deposit_address);
+
+        // The address stays in the persisted watch list a restarted node reloads
+        let stored = state.get_pending_deposit_intents(node).await.unwrap();
+        assert!(
+            stored
+                .iter()
+                .any(|intent| intent.deposit_address == deposit_address)
+        );
+    }
+
     #[tokio::test]
     async fn update_user_balance_increases_balance_after_confirmation() {
         // Setup cluster
@@ -268,6 +499,8 @@ mod deposit_tests {
                     deposit_tracking_id: Uuid::new_v4().to_string(),
                     deposit_address: deposit_address.to_string(),
                     timestamp: 0,
+                    expires_at: 0,
+                    min_confirmations: None,
                 },
             })
             .await
@@ -305,6 +538,8 @@ mod deposit_tests {
             .insert_pending_deposit_transaction(node, &tx)
             .await
             .expect("balance update failed");
+        // The wallet handler ingests the deposit once the node processes its request
+        while node.try_poll().await.unwrap() {}
 
         let txid = tx.compute_txid();
         let utxo_found = node.wallet.utxos.iter().any(|u| {
@@ -362,6 +597,56 @@ mod deposit_tests {
         }
     }
 
+    #[tokio::test]
+    async fn deposit_utxo_is_tracked_once_credited() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+        let (addr_tx, _addr_rx) = broadcast::channel::<DepositIntent>(4);
+        let mut state = DepositIntentState::new(addr_tx);
+
+        let deposit_address = Address::p2wpkh(
+            &bitcoin::CompressedPublicKey(random_public_key().inner),
+            bitcoin::Network::Testnet,
+        );
+        node.wallet.add_address(deposit_address.clone());
+
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: bitcoin::OutPoint {
+                    txid: bitcoin::Txid::from_slice(&[7u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                ..Default::default()
+            }],
+            output: vec![bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(20_000),
+                script_pubkey: deposit_address.script_pubkey(),
+            }],
+        };
+
+        state
+            .insert_pending_deposit_transaction(node, &tx)
+            .await
+            .expect("deposit processing failed");
+
+        // Ingestion completes before crediting returns, without waiting on the event loop.
+        let outpoint = bitcoin::OutPoint {
+            txid: tx.compute_txid(),
+            vout: 0,
+        };
+        assert!(
+            node.wallet
+                .utxos
+                .iter()
+                .any(|u| u.utxo.outpoint == outpoint && u.address == deposit_address)
+        );
+    }
+
     #[tokio::test]
     async fn update_user_balance_does_not_increase_balance_for_non_deposit_transactions() {
         // Setup cluster
@@ -432,4 +717,1005 @@ mod deposit_tests {
             "wallet should not have ingested any UTXO"
         );
     }
+
+    #[tokio::test]
+    async fn watch_address_deposits_are_detected_and_credited() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (addr_tx, mut addr_rx) = broadcast::channel::<DepositIntent>(4);
+        let (deposit_event_tx, mut deposit_event_rx) = broadcast::channel(16);
+        let mut state = DepositIntentState::new(addr_tx).with_deposit_event_tx(deposit_event_tx);
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (_, watch_pubkey) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let watch_address = Address::p2tr(
+            &secp,
+            watch_pubkey.x_only_public_key().0,
+            None,
+            bitcoin::Network::Testnet,
+        );
+
+        // Addresses for a different network are rejected.
+        let mainnet_address = Address::p2tr(
+            &secp,
+            watch_pubkey.x_only_public_key().0,
+            None,
+            bitcoin::Network::Bitcoin,
+        );
+        assert!(
+            state
+                .add_watch_address(node, &mainnet_address.to_string())
+                .await
+                .is_err()
+        );
+
+        let (response_tx, mut response_rx) = unbounded_channel();
+        state
+            .handle(
+                node,
+                NetworkEvent::SelfRequest {
+                    request: SelfRequest::AddWatchAddress {
+                        address: watch_address.to_string(),
+                    },
+                    response_channel: Some(response_tx),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            response_rx.recv().await,
+            Some(SelfResponse::AddWatchAddressResponse { .. })
+        ));
+
+        // The monitor is told about the address and it is stored, but it is neither a
+        // deposit intent nor one of the vault's addresses.
+        assert_eq!(
+            addr_rx.recv().await.unwrap().deposit_address,
+            watch_address.to_string()
+        );
+        match node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetWatchAddresses)
+            .await
+        {
+            Ok(abci::ChainResponse::GetWatchAddresses { addresses }) => {
+                assert_eq!(addresses, vec![watch_address.to_string()]);
+            }
+            _ => panic!("watch address not persisted"),
+        }
+        match node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetDepositIntentByAddress {
+                address: watch_address.to_string(),
+            })
+            .await
+        {
+            Ok(abci::ChainResponse::GetDepositIntentByAddress { intent: None }) => {}
+            _ => panic!("watch address was stored as a deposit intent"),
+        }
+        assert!(!node.wallet.addresses.contains(&watch_address));
+        assert!(!state.deposit_addresses.contains(&watch_address.to_string()));
+
+        let deposit_amount_sat = 42_000;
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: bitcoin::OutPoint {
+                    txid: bitcoin::Txid::from_slice(&[7u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                script_sig: bitcoin::ScriptBuf::new(),
+                sequence: bitcoin::Sequence::ZERO,
+                witness: bitcoin::witness::Witness::new(),
+            }],
+            output: vec![bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(deposit_amount_sat),
+                script_pubkey: watch_address.script_pubkey(),
+            }],
+        };
+
+        state
+            .insert_pending_deposit_transaction(node, &tx)
+            .await
+            .expect("watch address deposit failed");
+
+        let event = deposit_event_rx.recv().await.unwrap();
+        assert_eq!(event.address, watch_address.to_string());
+        assert_eq!(event.amount_sat, deposit_amount_sat);
+        assert_eq!(event.status, types::intents::DepositStatus::Confirmed);
+
+        let Ok(abci::ChainResponse::GetProposedBlock { block }) = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetProposedBlock {
+                previous_block: None,
+                proposer: vec![1, 2, 3, 4],
+            })
+            .await
+        else {
+            panic!("Failed to get proposed block");
+        };
+        node.chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::FinalizeBlock { block })
+            .await
+            .expect("Failed to finalize block");
+
+        match node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetAccount {
+                address: watch_address.to_string(),
+            })
+            .await
+        {
+            Ok(abci::ChainResponse::GetAccount {
+                account: Some(account),
+            }) => assert_eq!(account.balance, deposit_amount_sat),
+            _ => panic!("watch address account was not credited"),
+        }
+        // The funds stay outside the vault wallet, and the address stays watched.
+        assert!(!node.wallet.addresses.contains(&watch_address));
+        assert!(state.watch_addresses.contains(&watch_address.to_string()));
+    }
+
+    #[tokio::test]
+    async fn subscribe_deposits_streams_confirmed_deposits_for_filtered_addresses() {
+        use futures::StreamExt;
+        use oracle::{mock::MockOracle, oracle::Oracle};
+        use types::proto::node_proto::SubscribeDepositsRequest;
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (intent_tx, _) = broadcast::channel::<DepositIntent>(4);
+        let (deposit_event_tx, _) = broadcast::channel(16);
+        let mut state = DepositIntentState::new(intent_tx.clone())
+            .with_deposit_event_tx(deposit_event_tx.clone());
+
+        // The mock oracle answers every new deposit address with a confirmed transaction.
+        let (oracle_tx, mut oracle_rx) = broadcast::channel::<NetworkEvent>(16);
+        let mut oracle = MockOracle::new(oracle_tx, Some(intent_tx));
+        tokio::spawn(async move { oracle.poll_new_transactions(vec![]).await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let (_, watched_address) = state
+            .create_deposit(node, "watched_user", 25_000, None)
+            .await
+            .unwrap();
+        state
+            .create_deposit(node, "other_user", 40_000, None)
+            .await
+            .unwrap();
+
+        let mut events = grpc_operator::subscribe_deposits(
+            &deposit_event_tx,
+            SubscribeDepositsRequest {
+                address_filter: vec![watched_address.clone()],
+            },
+        );
+
+        // The node's own oracle places every deposit ten blocks below a tip at 110.
+        let (node_oracle_tx, _) = broadcast::channel::<NetworkEvent>(16);
+        let node_oracle = MockOracle::new(node_oracle_tx, None);
+        node_oracle.set_block_height(110);
+        node.oracle = Box::new(node_oracle.clone());
+
+        for _ in 0..2 {
+            let confirm_deposit = oracle_rx.recv().await.unwrap();
+            if let NetworkEvent::SelfRequest {
+                request: SelfRequest::ConfirmDeposit { confirmed_tx },
+                ..
+            } = &confirm_deposit
+            {
+                node_oracle.add_known_transaction(confirmed_tx.clone(), Some(100));
+            }
+            state.handle(node, confirm_deposit).await.unwrap();
+        }
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(1), events.next())
+            .await
+            .expect("no deposit event received")
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.address, watched_address);
+        assert_eq!(event.amount_satoshis, 25_000);
+        assert_eq!(event.status, "confirmed");
+        assert_eq!(event.confirmations, 11);
+        assert!(!event.txid.is_empty());
+
+        // The deposit to the other address is filtered out.
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), events.next())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn deposit_with_min_confirmations_waits_for_deeper_confirmation() {
+        use crate::mocks::network::MockNodeState;
+        use oracle::{mock::MockOracle, oracle::Oracle};
+
+        async fn pending_transactions(node: &mut MockNodeState) -> usize {
+            match node
+                .chain_interface_tx
+                .send_message_with_response(abci::ChainMessage::GetPendingTransactions)
+                .await
+            {
+                Ok(abci::ChainResponse::GetPendingTransactions { transactions }) => {
+                    transactions.len()
+                }
+                _ => panic!("Failed to get pending transactions"),
+            }
+        }
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (chain_oracle_tx, _) = broadcast::channel(4);
+        let chain_oracle = MockOracle::new(chain_oracle_tx, None);
+        chain_oracle.set_block_height(100);
+        node.oracle = Box::new(chain_oracle.clone());
+
+        // The mock oracle reports each new deposit once it has the node's global depth.
+        let (intent_tx, _) = broadcast::channel::<DepositIntent>(4);
+        let (oracle_tx, mut oracle_rx) = broadcast::channel::<NetworkEvent>(16);
+        let mut monitor = MockOracle::new(oracle_tx, Some(intent_tx.clone()));
+        tokio::spawn(async move { monitor.poll_new_transactions(vec![]).await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let mut state = DepositIntentState::new(intent_tx);
+        let min_confirmations = node.config.confirmation_depth + 3;
+        let (_, deposit_address) = state
+            .create_deposit(node, "cautious_user", 500_000, Some(min_confirmations))
+            .await
+            .unwrap();
+
+        let confirm_deposit = oracle_rx.recv().await.unwrap();
+        state.handle(node, confirm_deposit).await.unwrap();
+
+        let tick = || NetworkEvent::SelfRequest {
+            request: SelfRequest::MaintenanceTick,
+            response_channel: None,
+        };
+
+        // Deep enough for the global depth but not for the intent
+        assert_eq!(pending_transactions(node).await, 0);
+        assert_eq!(state.awaiting_depth.len(), 1);
+        assert!(state.deposit_addresses.contains(&deposit_address));
+
+        chain_oracle.set_block_height(102);
+        state.handle(node, tick()).await.unwrap();
+        assert_eq!(pending_transactions(node).await, 0);
+        assert_eq!(state.awaiting_depth.len(), 1);
+
+        chain_oracle.set_block_height(103);
+        state.handle(node, tick()).await.unwrap();
+        assert_eq!(pending_transactions(node).await, 1);
+        assert!(state.awaiting_depth.is_empty());
+        assert!(!state.deposit_addresses.contains(&deposit_address));
+    }
+
+    #[tokio::test]
+    async fn deposit_awaiting_confirmations_counts_from_its_block_and_survives_restart() {
+        use crate::mocks::network::MockNodeState;
+        use oracle::{mock::MockOracle, oracle::Oracle};
+
+        async fn pending_transactions(node: &mut MockNodeState) -> usize {
+            match node
+                .chain_interface_tx
+                .send_message_with_response(abci::ChainMessage::GetPendingTransactions)
+                .await
+            {
+                Ok(abci::ChainResponse::GetPendingTransactions { transactions }) => {
+                    transactions.len()
+                }
+                _ => panic!("Failed to get pending transactions"),
+            }
+        }
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (chain_oracle_tx, _) = broadcast::channel(4);
+        let chain_oracle = MockOracle::new(chain_oracle_tx, None);
+        chain_oracle.set_block_height(100);
+        node.oracle = Box::new(chain_oracle.clone());
+
+        let (intent_tx, _) = broadcast::channel::<DepositIntent>(4);
+        let (oracle_tx, mut oracle_rx) = broadcast::channel::<NetworkEvent>(16);
+        let mut monitor = MockOracle::new(oracle_tx, Some(intent_tx.clone()));
+        tokio::spawn(async move { monitor.poll_new_transactions(vec![]).await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let mut state = DepositIntentState::new(intent_tx.clone());
+        let min_confirmations = node.config.confirmation_depth + 3;
+        let (_, deposit_address) = state
+            .create_deposit(node, "cautious_user", 500_000, Some(min_confirmations))
+            .await
+            .unwrap();
+
+        // The deposit was mined at 94, a block deeper than the global depth at tip 100.
+        let confirm_deposit = oracle_rx.recv().await.unwrap();
+        let NetworkEvent::SelfRequest {
+            request: SelfRequest::ConfirmDeposit { confirmed_tx },
+            ..
+        } = &confirm_deposit
+        else {
+            panic!("expected a confirmed deposit");
+        };
+        let txid = confirmed_tx.compute_txid();
+        chain_oracle.add_known_transaction(confirmed_tx.clone(), Some(94));
+        state.handle(node, confirm_deposit).await.unwrap();
+
+        // Counted from its own block, not from the tip it happened to be reported at.
+        let credit_at = 94 + min_confirmations - 1;
+        assert!(credit_at < 100 + 3);
+        assert_eq!(
+            state.awaiting_depth.get(&txid).map(|(_, at)| *at),
+            Some(credit_at)
+        );
+        assert_eq!(pending_transactions(node).await, 0);
+
+        // A restarted node still holds the deposit back until the same height.
+        let awaiting = DepositIntentState::load_awaiting_deposits(&mut node.chain_interface_tx)
+            .await
+            .unwrap();
+        let mut restarted = DepositIntentState::new(intent_tx).with_awaiting_depth(awaiting);
+        restarted.deposit_addresses.insert(deposit_address.clone());
+        assert_eq!(
+            restarted.awaiting_depth.get(&txid).map(|(_, at)| *at),
+            Some(credit_at)
+        );
+
+        let tick = || NetworkEvent::SelfRequest {
+            request: SelfRequest::MaintenanceTick,
+            response_channel: None,
+        };
+
+        chain_oracle.set_block_height(credit_at - 1);
+        restarted.handle(node, tick()).await.unwrap();
+        assert_eq!(pending_transactions(node).await, 0);
+
+        chain_oracle.set_block_height(credit_at);
+        restarted.handle(node, tick()).await.unwrap();
+        assert_eq!(pending_transactions(node).await, 1);
+        assert!(restarted.awaiting_depth.is_empty());
+        assert!(
+            DepositIntentState::load_awaiting_deposits(&mut node.chain_interface_tx)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn credited_deposit_is_not_credited_again_after_restart() {
+        use oracle::mock::MockOracle;
+
+        async fn pending_transactions(node: &mut crate::mocks::network::MockNodeState) -> usize {
+            match node
+                .chain_interface_tx
+                .send_message_with_response(abci::ChainMessage::GetPendingTransactions)
+                .await
+            {
+                Ok(abci::ChainResponse::GetPendingTransactions { transactions }) => {
+                    transactions.len()
+                }
+                _ => panic!("Failed to get pending transactions"),
+            }
+        }
+
+        // Tracks the intent as a node does at startup, whether or not it was credited.
+        async fn watch(
+            node: &mut crate::mocks::network::MockNodeState,
+            state: &mut DepositIntentState,
+            intent: &DepositIntent,
+        ) {
+            match node
+                .chain_interface_tx
+                .send_message_with_response(abci::ChainMessage::InsertDepositIntent {
+                    intent: intent.clone(),
+                })
+                .await
+            {
+                Ok(abci::ChainResponse::InsertDepositIntent { error: None }) => {}
+                _ => panic!("Failed to insert deposit intent"),
+            }
+            state
+                .deposit_addresses
+                .insert(intent.deposit_address.clone());
+        }
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (oracle_tx, _) = broadcast::channel(4);
+        let oracle = MockOracle::new(oracle_tx, None);
+        oracle.set_block_height(100);
+        node.oracle = Box::new(oracle);
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (_, user_pubkey) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let user_btc_pubkey = bitcoin::PublicKey::from_slice(&user_pubkey.serialize()).unwrap();
+        let user_address = Address::p2pkh(user_btc_pubkey, bitcoin::Network::Testnet);
+        let internal_key = random_public_key().inner.x_only_public_key().0;
+        let deposit_address = Address::p2tr(&secp, internal_key, None, bitcoin::Network::Testnet);
+        let intent = DepositIntent {
+            amount_sat: 15_000,
+            user_pubkey: user_address.to_string(),
+            deposit_tracking_id: Uuid::new_v4().to_string(),
+            deposit_address: deposit_address.to_string(),
+            timestamp: 0,
+            expires_at: 0,
+            min_confirmations: None,
+        };
+        let deposit = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: bitcoin::OutPoint {
+                    txid: bitcoin::Txid::from_slice(&[5u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                script_sig: user_address.script_pubkey(),
+                sequence: bitcoin::Sequence::ZERO,
+                witness: bitcoin::witness::Witness::new(),
+            }],
+            output: vec![bitcoin::TxOut {
+                value: bitcoin::Amount::from_sat(15_000),
+                script_pubkey: deposit_address.script_pubkey(),
+            }],
+        };
+
+        let (addr_tx, _addr_rx) = broadcast::channel::<DepositIntent>(4);
+        let mut state = DepositIntentState::new(addr_tx.clone());
+        watch(node, &mut state, &intent).await;
+        state
+            .insert_pending_deposit_transaction(node, &deposit)
+            .await
+            .unwrap();
+        assert_eq!(pending_transactions(node).await, 1);
+
+        // Restart with the intent still stored, as after a crash before it was removed, and
+        // have the monitor report the same transaction again.
+        let processed = DepositIntentState::load_processed_txids(&mut node.chain_interface_tx)
+            .await
+            .unwrap();
+        assert_eq!(processed.get(&deposit.compute_txid()), Some(&100));
+        let mut restarted = DepositIntentState::new(addr_tx).with_processed_txids(processed);
+        watch(node, &mut restarted, &intent).await;
+        restarted
+            .insert_pending_deposit_transaction(node, &deposit)
+            .await
+            .unwrap();
+
+        assert_eq!(pending_transactions(node).await, 1);
+        assert!(
+            restarted
+                .deposit_addresses
+                .contains(&intent.deposit_address)
+        );
+
+        // Without the persisted txids the same report is credited a second time.
+        let (addr_tx, _addr_rx) = broadcast::channel::<DepositIntent>(4);
+        let mut forgetful = DepositIntentState::new(addr_tx);
+        watch(node, &mut forgetful, &intent).await;
+        forgetful
+            .insert_pending_deposit_transaction(node, &deposit)
+            .await
+            .unwrap();
+        assert!(
+            !forgetful
+                .deposit_addresses
+                .contains(&intent.deposit_address)
+        );
+    }
+
+    #[tokio::test]
+    async fn reorg_deeper_than_limit_halts_deposit_crediting() {
+        use oracle::mock::MockOracle;
+        use types::errors::NodeError;
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (oracle_tx, _) = broadcast::channel(4);
+        let oracle = MockOracle::new(oracle_tx, None);
+        oracle.set_block_height(110);
+        node.oracle = Box::new(oracle.clone());
+
+        let (addr_tx, _addr_rx) = broadcast::channel::<DepositIntent>(4);
+        let mut state = DepositIntentState::new(addr_tx).with_max_reorg_depth(3);
+        let tick = || NetworkEvent::SelfRequest {
+            request: SelfRequest::MaintenanceTick,
+            response_channel: None,
+        };
+
+        state.handle(node, tick()).await.unwrap();
+
+        // A reorg within the limit is followed
+        oracle.reorg_blocks(109..=110, 1);
+        state.handle(node, tick()).await.unwrap();
+        assert!(state.reorg_guard.alert.is_none());
+
+        // Replacing more than three blocks halts crediting and raises the alert
+        oracle.set_block_height(112);
+        oracle.reorg_blocks(106..=112, 2);
+        state.handle(node, tick()).await.unwrap();
+        let alert = state.reorg_guard.alert.expect("deep reorg not reported");
+        assert!(alert.depth > 3);
+
+        let deposit = MockOracle::create_dummy_tx_without_address(15_000);
+        assert!(matches!(
+            state
+                .insert_pending_deposit_transaction(node, &deposit)
+                .await,
+            Err(NodeError::ReorgTooDeep { max_depth: 3, .. })
+        ));
+        assert!(!state.processed_txids.contains_key(&deposit.compute_txid()));
+    }
+
+    #[tokio::test]
+    async fn monitor_skips_malformed_deposit_address_and_keeps_tracking() {
+        use oracle::esplora::EsploraOracle;
+        use std::collections::HashSet;
+        use types::errors::NodeError;
+
+        let monitor = EsploraOracle::new(bitcoin::Network::Testnet, Some(10), None, None, 6, 0);
+        let intent = |deposit_address: &str| DepositIntent {
+            amount_sat: 10_000,
+            user_pubkey: "02".repeat(33),
+            deposit_tracking_id: Uuid::new_v4().to_string(),
+            deposit_address: deposit_address.to_string(),
+            timestamp: 0,
+            expires_at: 0,
+            min_confirmations: None,
+        };
+        let mut addresses = HashSet::new();
+
+        let malformed =
+            monitor.track_deposit_intent(&mut addresses, &intent("tb1q-not-an-address"));
+        assert!(matches!(
+            malformed,
+            Err(NodeError::MalformedDepositAddress { .. })
+        ));
+
+        // A mainnet address gossiped to a testnet monitor is rejected too.
+        let wrong_network = monitor.track_deposit_intent(
+            &mut addresses,
+            &intent("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+        );
+        assert!(matches!(
+            wrong_network,
+            Err(NodeError::DepositAddressWrongNetwork { .. })
+        ));
+        assert!(addresses.is_empty());
+
+        // The monitor carries on with the next valid intent.
+        monitor
+            .track_deposit_intent(
+                &mut addresses,
+                &intent("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"),
+            )
+            .unwrap();
+        assert_eq!(addresses.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stored_deposit_addresses_are_verified_against_the_vault_key() {
+        use crate::mocks::network::MockNodeState;
+
+        async fn verify(
+            state: &mut DepositIntentState,
+            node: &mut MockNodeState,
+            deposit_address: &str,
+        ) -> SelfResponse {
+            let (response_tx, mut response_rx) = unbounded_channel();
+            state
+                .handle(
+                    node,
+                    NetworkEvent::SelfRequest {
+                        request: SelfRequest::VerifyDepositAddress {
+                            deposit_address: deposit_address.to_string(),
+                        },
+                        response_channel: Some(response_tx),
+                    },
+                )
+                .await
+                .unwrap();
+            response_rx.recv().await.unwrap()
+        }
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+        let (intent_tx, _) = broadcast::channel::<DepositIntent>(4);
+        let mut state = DepositIntentState::new(intent_tx);
+
+        let (_, deposit_address) = state
+            .create_deposit(node, &"02".repeat(33), 30_000, None)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            verify(&mut state, node, &deposit_address).await,
+            SelfResponse::VerifyDepositAddressResponse { verified: true }
+        ));
+
+        // An intent whose stored address was swapped for one outside the vault fails.
+        let Ok(abci::ChainResponse::GetDepositIntentByAddress {
+            intent: Some(intent),
+        }) = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetDepositIntentByAddress {
+                address: deposit_address.clone(),
+            })
+            .await
+        else {
+            panic!("intent not stored");
+        };
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (_, foreign_key) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let tampered = DepositIntent {
+            deposit_address: Address::p2tr(
+                &secp,
+                foreign_key.x_only_public_key().0,
+                None,
+                node.wallet.network(),
+            )
+            .to_string(),
+            ..intent.clone()
+        };
+        node.chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::InsertDepositIntent {
+                intent: tampered.clone(),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(
+            verify(&mut state, node, &tampered.deposit_address).await,
+            SelfResponse::VerifyDepositAddressResponse { verified: false }
+        ));
+
+        // So does the genuine address recorded against another intent's tweak.
+        let retagged = DepositIntent {
+            deposit_tracking_id: Uuid::new_v4().to_string(),
+            ..intent
+        };
+        assert!(!node.verify_deposit_address(&retagged).unwrap());
+
+        assert!(matches!(
+            verify(
+                &mut state,
+                node,
+                "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"
+            )
+            .await,
+            SelfResponse::NodeError(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancelled_deposit_intent_is_removed_through_the_chain() {
+        async fn cancel(
+            state: &mut DepositIntentState,
+            node: &mut MockNodeState,
+            deposit_tracking_id: &str,
+        ) -> SelfResponse {
+            let (response_tx, mut response_rx) = unbounded_channel();
+            state
+                .handle(
+                    node,
+                    NetworkEvent::SelfRequest {
+                        request: SelfRequest::CancelDepositIntent {
+                            deposit_tracking_id: deposit_tracking_id.to_string(),
+                        },
+                        response_channel: Some(response_tx),
+                    },
+                )
+                .await
+                .unwrap();
+            response_rx.recv().await.unwrap()
+        }
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let (tx, mut rx) = broadcast::channel::<DepositIntent>(8);
+        let (deposit_event_tx, mut deposit_event_rx) = broadcast::channel(16);
+        let mut state = DepositIntentState::new(tx)
+            .with_intent_ttl(std::time::Duration::from_secs(60))
+            .with_deposit_event_tx(deposit_event_tx);
+        let user_pubkey = "020202020202020202020202020202020202020202020202020202020202020202";
+
+        let (abandoned_id, abandoned_address) = state
+            .create_deposit(node, user_pubkey, 10_000, None)
+            .await
+            .unwrap();
+        let (funded_id, funded_address) = state
+            .create_deposit(node, user_pubkey, 20_000, None)
+            .await
+            .unwrap();
+        rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+
+        let SelfResponse::CancelDepositIntentResponse { deposit_address } =
+            cancel(&mut state, node, &abandoned_id).await
+        else {
+            panic!("expected the intent to be cancelled");
+        };
+        assert_eq!(deposit_address, abandoned_address);
+        let cancelled = loop {
+            let event = deposit_event_rx.recv().await.unwrap();
+            if event.status == types::intents::DepositStatus::Cancelled {
+                break event;
+            }
+        };
+        assert_eq!(cancelled.address, abandoned_address);
+
+        // The cancellation only takes effect once a block carrying it is finalized
+        assert_eq!(
+            state.get_pending_deposit_intents(node).await.unwrap().len(),
+            2
+        );
+        finalize_pending_transactions(node).await;
+        let pending = state.get_pending_deposit_intents(node).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].deposit_tracking_id, funded_id);
+
+        // From there it is settled like an expiry: the address stays watched for one more
+        // intent lifetime, then the monitor is told to drop it.
+        let now = pending[0].timestamp;
+        state.settle_expired_intents(node, now).await.unwrap();
+        assert!(!state.deposit_addresses.contains(&abandoned_address));
+        state.settle_expired_intents(node, now + 60).await.unwrap();
+        let abandoned = Address::from_str(&abandoned_address)
+            .unwrap()
+            .assume_checked();
+        assert!(!node.wallet.addresses.contains(&abandoned));
+        let unwatched = rx.recv().await.unwrap();
+        assert_eq!(unwatched.deposit_address, abandoned_address);
+        assert!(unwatched.is_expired(now + 60));
+
+        // Cancelling again finds nothing to cancel.
+        assert!(matches!(
+            cancel(&mut state, node, &abandoned_id).await,
+            SelfResponse::NodeError(_)
+        ));
+
+        // Once funds have landed the intent can no longer be cancelled.
+        let funded = Address::from_str(&funded_address).unwrap().assume_checked();
+        node.wallet.utxos.push(node::wallet::TrackedUtxo {
+            utxo: types::utxo::Utxo {
+                outpoint: bitcoin::OutPoint {
+                    txid: bitcoin::Txid::from_slice(&[4u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: bitcoin::Amount::from_sat(20_000),
+                script_pubkey: funded.script_pubkey(),
+            },
+            address: funded.clone(),
+        });
+        assert!(matches!(
+            cancel(&mut state, node, &funded_id).await,
+            SelfResponse::NodeError(_)
+        ));
+        assert_eq!(
+            state.get_pending_deposit_intents(node).await.unwrap().len(),
+            1
+        );
+        assert!(state.deposit_addresses.contains(&funded_address));
+        assert!(node.wallet.addresses.contains(&funded));
+    }
+
+    #[tokio::test]
+    async fn deposit_to_vault_address_without_intent_is_quarantined() {
+        use types::audit::AuditEventKind;
+        use types::intents::UnknownDepositPolicy;
+
+        fn pay(address: &Address, amount_sat: u64, funding: u8) -> bitcoin::Transaction {
+            bitcoin::Transaction {
+                version: bitcoin::transaction::Version::TWO,
+                lock_time: bitcoin::absolute::LockTime::ZERO,
+                input: vec![bitcoin::TxIn {
+                    previous_output: bitcoin::OutPoint {
+                        txid: bitcoin::Txid::from_slice(&[funding; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    script_sig: bitcoin::ScriptBuf::new(),
+                    sequence: bitcoin::Sequence::ZERO,
+                    witness: bitcoin::witness::Witness::new(),
+                }],
+                output: vec![bitcoin::TxOut {
+                    value: bitcoin::Amount::from_sat(amount_sat),
+                    script_pubkey: address.script_pubkey(),
+                }],
+            }
+        }
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+        assert_eq!(
+            node.config.unknown_deposit_policy,
+            UnknownDepositPolicy::Quarantine
+        );
+
+        let (tx, _rx) = broadcast::channel::<DepositIntent>(8);
+        let mut state = DepositIntentState::new(tx);
+        let user_pubkey = "020202020202020202020202020202020202020202020202020202020202020202";
+
+        // The vault derived this address, but its intent is gone by the time funds arrive.
+        let (deposit_tracking_id, deposit_address) = state
+            .create_deposit(node, user_pubkey, 10_000, None)
+            .await
+            .unwrap();
+        state
+            .cancel_intent(node, &deposit_tracking_id)
+            .await
+            .unwrap();
+        finalize_pending_transactions(node).await;
+        let address = Address::from_str(&deposit_address)
+            .unwrap()
+            .assume_checked();
+
+        let late = pay(&address, 10_000, 7);
+        state
+            .insert_pending_deposit_transaction(node, &late)
+            .await
+            .unwrap();
+
+        assert_eq!(state.quarantined_deposits.len(), 1);
+        let quarantined = &state.quarantined_deposits[0];
+        assert_eq!(quarantined.txid, late.compute_txid().to_string());
+        assert_eq!(quarantined.vout, 0);
+        assert_eq!(quarantined.address, deposit_address);
+        assert_eq!(quarantined.amount_sat, 10_000);
+
+        let Ok(abci::ChainResponse::GetAuditLog { entries }) = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetAuditLog {
+                from: 0,
+                to: u64::MAX,
+            })
+            .await
+        else {
+            panic!("Failed to get audit log");
+        };
+        let recorded: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.kind == AuditEventKind::DepositQuarantined)
+            .collect();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].address, deposit_address);
+        assert_eq!(recorded[0].amount_sat, 10_000);
+        assert_eq!(recorded[0].reference, format!("{}:0", late.compute_txid()));
+
+        // Nothing was credited.
+        let Ok(abci::ChainResponse::GetPendingTransactions { transactions }) = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetPendingTransactions)
+            .await
+        else {
+            panic!("Failed to get pending transactions");
+        };
+        assert!(transactions.is_empty());
+
+        // Under a fallback account the same kind of payment is credited instead.
+        node.config.unknown_deposit_policy = UnknownDepositPolicy::CreditToFallbackAccount {
+            account: user_pubkey.to_string(),
+        };
+        state
+            .insert_pending_deposit_transaction(node, &pay(&address, 5_000, 8))
+            .await
+            .unwrap();
+        assert_eq!(state.quarantined_deposits.len(), 1);
+        let Ok(abci::ChainResponse::GetPendingTransactions { transactions }) = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetPendingTransactions)
+            .await
+        else {
+            panic!("Failed to get pending transactions");
+        };
+        assert_eq!(transactions.len(), 1);
+    }
+    #[tokio::test]
+    async fn change_from_vault_withdrawal_is_not_treated_as_a_deposit() {
+        use types::intents::UnknownDepositPolicy;
+
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+        let user_pubkey = "020202020202020202020202020202020202020202020202020202020202020202";
+        // The most permissive policy: any unknown payment to the vault would be credited.
+        node.config.unknown_deposit_policy = UnknownDepositPolicy::CreditToFallbackAccount {
+            account: user_pubkey.to_string(),
+        };
+
+        let (tx, _rx) = broadcast::channel::<DepositIntent>(8);
+        let mut state = DepositIntentState::new(tx);
+
+        // A vault address whose deposit has already been handled holds the vault's funds.
+        let (deposit_tracking_id, deposit_address) = state
+            .create_deposit(node, user_pubkey, 50_000, None)
+            .await
+            .unwrap();
+        state
+            .cancel_intent(node, &deposit_tracking_id)
+            .await
+            .unwrap();
+        finalize_pending_transactions(node).await;
+        let vault_address = Address::from_str(&deposit_address)
+            .unwrap()
+            .assume_checked();
+        node.wallet.utxos.push(node::wallet::TrackedUtxo {
+            utxo: types::utxo::Utxo {
+                outpoint: bitcoin::OutPoint {
+                    txid: bitcoin::Txid::from_slice(&[5u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: bitcoin::Amount::from_sat(50_000),
+                script_pubkey: vault_address.script_pubkey(),
+            },
+            address: vault_address.clone(),
+        });
+
+        // A withdrawal is built for real, moving its input out of the UTXO set and sending
+        // its change back to the vault address.
+        let recipient = Address::p2wpkh(
+            &bitcoin::CompressedPublicKey(random_public_key().inner),
+            bitcoin::Network::Testnet,
+        );
+        let (spend, _) = node
+            .wallet
+            .create_spend(20_000, 1_000, &recipient, false)
+            .unwrap();
+        assert!(
+            spend
+                .output
+                .iter()
+                .any(|output| output.script_pubkey == vault_address.script_pubkey())
+        );
+        assert!(node.wallet.is_own_spend(&spend));
+
+        // Seeing the broadcast spend neither credits nor quarantines its change.
+        state
+            .insert_pending_deposit_transaction(node, &spend)
+            .await
+            .unwrap();
+        assert!(state.quarantined_deposits.is_empty());
+        let Ok(abci::ChainResponse::GetPendingTransactions { transactions }) = node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetPendingTransactions)
+            .await
+        else {
+            panic!("Failed to get pending transactions");
+        };
+        assert!(transactions.is_empty());
+    }
 }
@@ -4,17 +4,24 @@ mod dkg_test {
     use crate::mocks::{db::MockDb, network::MockNodeCluster};
     use abci::db::Db;
     use bincode;
+    use frost_secp256k1 as frost;
     use log::info;
-    use protocol::block::{ChainConfig, ValidatorInfo};
+    use node::handlers::Handler;
+    use node::handlers::dkg::key_creation::round2_package_hash;
+    use node::handlers::dkg::{DkgState, MAX_ROUND2_ATTEMPTS, ROUND2_ACK_TIMEOUT};
+    use node::peer_id_to_identifier;
+    use protocol::block::{ChainConfig, ConsensusQuorum, ValidatorInfo};
     use sha2::{Digest, Sha256};
     use tracing_subscriber::EnvFilter;
     use tracing_subscriber::layer::SubscriberExt;
     use tracing_subscriber::util::SubscriberInitExt;
     use types::broadcast::BroadcastMessage;
-    use types::network::network_event::{DirectMessage, NetworkEvent};
+    use types::errors::NodeError;
+    use types::network::network_event::{DirectMessage, NetworkEvent, SelfRequest, SelfResponse};
     use types::proto::ProtoDecode;
     use types::proto::p2p_proto::dkg_message::Message as DkgInner;
     use types::proto::p2p_proto::gossipsub_message::Message as GossipsubMessage;
+    use types::proto::p2p_proto::{DkgMessage, Round1Package};
 
     fn setup() {
         let env_filter =
@@ -133,6 +140,360 @@ mod dkg_test {
         cluster.tear_down().await;
     }
 
+    #[tokio::test]
+    async fn round1_package_with_wrong_commitment_length_is_rejected() {
+        setup();
+        let mut cluster = MockNodeCluster::new(3).await;
+        cluster.setup().await;
+
+        let peers: Vec<_> = cluster.nodes.keys().copied().collect();
+        let (receiver, sender) = (peers[0], peers[1]);
+        let node = cluster.nodes.get_mut(&receiver).unwrap();
+        let min_signers = node.config.min_signers.unwrap();
+
+        // A polynomial of the wrong degree yields one commitment too few.
+        let (_, short_package) = frost::keys::dkg::part1(
+            peer_id_to_identifier(&sender),
+            node.config.max_signers.unwrap(),
+            min_signers - 1,
+            frost::rand_core::OsRng,
+        )
+        .unwrap();
+        let message = DkgMessage {
+            message: Some(DkgInner::Round1Package(Round1Package {
+                package_data: short_package.serialize().unwrap(),
+            })),
+        };
+
+        let mut dkg_state = DkgState::new();
+        let result = dkg_state.handle_round1_payload(node, sender, message);
+
+        assert!(result.is_err(), "Malformed round1 package must be rejected");
+        assert!(
+            dkg_state.round1_peer_packages.is_empty(),
+            "Rejected package must not count toward round1"
+        );
+    }
+
+    async fn start_dkg_with_signers(min_signers: u16, max_signers: u16) -> (DkgState, NodeError) {
+        let mut cluster = MockNodeCluster::new(2).await;
+        cluster.setup().await;
+        let peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&peer).unwrap();
+        node.config.min_signers = Some(min_signers);
+        node.config.max_signers = Some(max_signers);
+
+        let mut dkg_state = DkgState::new();
+        let error = dkg_state.start_dkg(node).unwrap_err();
+        (dkg_state, error)
+    }
+
+    #[tokio::test]
+    async fn dkg_start_rejects_min_signers_above_max_signers() {
+        let (dkg_state, error) = start_dkg_with_signers(3, 2).await;
+
+        assert!(matches!(
+            error,
+            NodeError::InvalidSignerConfig { ref reason }
+                if reason == "min_signers (3) exceeds max_signers (2)"
+        ));
+        assert!(!dkg_state.dkg_started);
+        assert!(dkg_state.r1_secret_package.is_none());
+    }
+
+    #[tokio::test]
+    async fn dkg_start_rejects_zero_max_signers() {
+        let (dkg_state, error) = start_dkg_with_signers(2, 0).await;
+
+        assert!(matches!(
+            error,
+            NodeError::InvalidSignerConfig { ref reason }
+                if reason == "max_signers is 0, FROST requires at least 2"
+        ));
+        assert!(!dkg_state.dkg_started);
+        assert!(dkg_state.r1_secret_package.is_none());
+    }
+
+    fn sent_round2_packages(cluster: &mut MockNodeCluster, to: libp2p::PeerId) -> usize {
+        let mut count = 0;
+        while let Ok(pending) = cluster.pending_events_rx.try_recv() {
+            if pending.target_peers == [to]
+                && matches!(
+                    pending.event,
+                    NetworkEvent::MessageEvent((_, DirectMessage::Round2Package(_)))
+                )
+            {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    #[tokio::test]
+    async fn unacked_round2_package_is_resent_with_backoff_then_given_up() {
+        setup();
+        let mut cluster = MockNodeCluster::new(2).await;
+        cluster.setup().await;
+        cluster.run_n_iterations(0).await;
+        while cluster.pending_events_rx.try_recv().is_ok() {}
+
+        let peers: Vec<_> = cluster.nodes.keys().copied().collect();
+        let (sender, receiver) = (peers[0], peers[1]);
+
+        let (sender_secret, _) = frost::keys::dkg::part1(
+            peer_id_to_identifier(&sender),
+            2,
+            2,
+            frost::rand_core::OsRng,
+        )
+        .unwrap();
+        let (_, receiver_round1) = frost::keys::dkg::part1(
+            peer_id_to_identifier(&receiver),
+            2,
+            2,
+            frost::rand_core::OsRng,
+        )
+        .unwrap();
+        let round1_packages = [(peer_id_to_identifier(&receiver), receiver_round1)]
+            .into_iter()
+            .collect();
+        let (_, round2_packages) =
+            frost::keys::dkg::part2(sender_secret, &round1_packages).unwrap();
+        let package = round2_packages[&peer_id_to_identifier(&receiver)].clone();
+
+        let node = cluster.nodes.get_mut(&sender).unwrap();
+        let mut dkg_state = DkgState::new();
+        dkg_state
+            .send_round2_package(node, receiver, package.clone())
+            .unwrap();
+        assert_eq!(sent_round2_packages(&mut cluster, receiver), 1);
+
+        // The receiver's ack is lost; once the timeout passes the next tick resends.
+        let node = cluster.nodes.get_mut(&sender).unwrap();
+        let tick = || NetworkEvent::SelfRequest {
+            request: SelfRequest::ConsensusTick,
+            response_channel: None,
+        };
+        dkg_state.handle(node, tick()).await.unwrap();
+        assert_eq!(sent_round2_packages(&mut cluster, receiver), 0);
+
+        let node = cluster.nodes.get_mut(&sender).unwrap();
+        dkg_state
+            .pending_round2_acks
+            .get_mut(&receiver)
+            .unwrap()
+            .sent_at -= ROUND2_ACK_TIMEOUT;
+        dkg_state.handle(node, tick()).await.unwrap();
+        assert_eq!(sent_round2_packages(&mut cluster, receiver), 1);
+        assert_eq!(dkg_state.pending_round2_acks[&receiver].attempts, 2);
+
+        // A matching ack settles the delivery.
+        let node = cluster.nodes.get_mut(&sender).unwrap();
+        let package_hash = round2_package_hash(&package).unwrap().to_vec();
+        dkg_state
+            .handle(
+                node,
+                NetworkEvent::MessageEvent((receiver, DirectMessage::Round2Ack { package_hash })),
+            )
+            .await
+            .unwrap();
+        assert!(dkg_state.pending_round2_acks.is_empty());
+
+        // A peer that never acks is resent with backoff, then given up on.
+        let node = cluster.nodes.get_mut(&sender).unwrap();
+        dkg_state
+            .send_round2_package(node, receiver, package)
+            .unwrap();
+        assert_eq!(sent_round2_packages(&mut cluster, receiver), 1);
+        for attempt in 1..MAX_ROUND2_ATTEMPTS {
+            let node = cluster.nodes.get_mut(&sender).unwrap();
+            let pending = dkg_state.pending_round2_acks.get_mut(&receiver).unwrap();
+            assert_eq!(
+                pending.ack_timeout(),
+                ROUND2_ACK_TIMEOUT * 2u32.pow(attempt - 1)
+            );
+            pending.sent_at -= pending.ack_timeout();
+            dkg_state.handle(node, tick()).await.unwrap();
+            assert_eq!(sent_round2_packages(&mut cluster, receiver), 1);
+        }
+
+        let node = cluster.nodes.get_mut(&sender).unwrap();
+        let pending = dkg_state.pending_round2_acks.get_mut(&receiver).unwrap();
+        assert_eq!(pending.attempts, MAX_ROUND2_ATTEMPTS);
+        pending.sent_at -= pending.ack_timeout();
+        assert!(dkg_state.handle(node, tick()).await.is_err());
+        assert_eq!(sent_round2_packages(&mut cluster, receiver), 0);
+        assert!(dkg_state.pending_round2_acks.is_empty());
+    }
+
+    /// Round2 packages from `from` waiting in `to`'s inbox.
+    fn queued_round2_packages(
+        cluster: &MockNodeCluster,
+        from: libp2p::PeerId,
+        to: libp2p::PeerId,
+    ) -> Vec<frost::keys::dkg::round2::Package> {
+        cluster.senders[&to]
+            .pending_events
+            .iter()
+            .filter_map(|event| match event {
+                NetworkEvent::MessageEvent((peer, DirectMessage::Round2Package(package)))
+                    if *peer == from =>
+                {
+                    Some(package.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn round2_package_is_redelivered_when_peer_is_reachable_again() {
+        setup();
+        let mut cluster = MockNodeCluster::new(2).await;
+        cluster.setup().await;
+        cluster.run_n_iterations(0).await;
+        while cluster.pending_events_rx.try_recv().is_ok() {}
+
+        let peers: Vec<_> = cluster.nodes.keys().copied().collect();
+        let (sender, receiver) = (peers[0], peers[1]);
+
+        let (sender_secret, _) = frost::keys::dkg::part1(
+            peer_id_to_identifier(&sender),
+            2,
+            2,
+            frost::rand_core::OsRng,
+        )
+        .unwrap();
+        let (_, receiver_round1) = frost::keys::dkg::part1(
+            peer_id_to_identifier(&receiver),
+            2,
+            2,
+            frost::rand_core::OsRng,
+        )
+        .unwrap();
+        let round1_packages = [(peer_id_to_identifier(&receiver), receiver_round1)]
+            .into_iter()
+            .collect();
+        let (_, round2_packages) =
+            frost::keys::dkg::part2(sender_secret, &round1_packages).unwrap();
+        let package = round2_packages[&peer_id_to_identifier(&receiver)].clone();
+
+        cluster.simulate_peer_unreachable(receiver);
+        let node = cluster.nodes.get_mut(&sender).unwrap();
+        DkgState::new()
+            .send_round2_package(node, receiver, package.clone())
+            .unwrap();
+        cluster.process_network_events().await;
+
+        assert!(queued_round2_packages(&cluster, sender, receiver).is_empty());
+        assert_eq!(cluster.retry_queues[&sender].queued_len(&receiver), 1);
+
+        cluster.simulate_peer_reachable(receiver);
+
+        let redelivered = queued_round2_packages(&cluster, sender, receiver);
+        assert_eq!(redelivered.len(), 1);
+        assert_eq!(
+            round2_package_hash(&redelivered[0]).unwrap(),
+            round2_package_hash(&package).unwrap()
+        );
+        assert_eq!(cluster.retry_queues[&sender].queued_len(&receiver), 0);
+        assert_eq!(cluster.retry_queues[&sender].dead_letters().count(), 0);
+    }
+
+    fn start_dkg_broadcasts(cluster: &mut MockNodeCluster) -> usize {
+        let mut count = 0;
+        while let Ok(pending) = cluster.pending_events_rx.try_recv() {
+            if let NetworkEvent::GossipsubMessage(message) = pending.event {
+                if let Ok(BroadcastMessage::Dkg(gossip)) = BroadcastMessage::decode(&message.data) {
+                    if let Some(GossipsubMessage::Dkg(dkg)) = gossip.message {
+                        if matches!(dkg.message, Some(DkgInner::StartDkg(_))) {
+                            count += 1;
+                        }
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    #[tokio::test]
+    async fn forced_dkg_restart_clears_state_and_rebroadcasts_start() {
+        setup();
+        let mut cluster = MockNodeCluster::new(2).await;
+        cluster.setup().await;
+        cluster.run_n_iterations(0).await;
+        while cluster.pending_events_rx.try_recv().is_ok() {}
+
+        let peers: Vec<_> = cluster.nodes.keys().copied().collect();
+        let (local, remote) = (peers[0], peers[1]);
+
+        // A ceremony stuck after round 1, on a node that already holds group keys.
+        let (shares, pubkey_package) = frost::keys::generate_with_dealer(
+            2,
+            2,
+            frost::keys::IdentifierList::Default,
+            frost::rand_core::OsRng,
+        )
+        .unwrap();
+        let share = shares.into_values().next().unwrap();
+        let node = cluster.nodes.get_mut(&local).unwrap();
+        node.private_key_package = Some(frost::keys::KeyPackage::try_from(share).unwrap());
+        node.pubkey_package = Some(pubkey_package);
+
+        let (_, remote_round1) = frost::keys::dkg::part1(
+            peer_id_to_identifier(&remote),
+            2,
+            2,
+            frost::rand_core::OsRng,
+        )
+        .unwrap();
+        let mut dkg_state = DkgState::new();
+        dkg_state.dkg_started = true;
+        dkg_state
+            .round1_peer_packages
+            .insert(peer_id_to_identifier(&remote), remote_round1);
+
+        let restart = |force| {
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            let event = NetworkEvent::SelfRequest {
+                request: SelfRequest::RestartDkg { force },
+                response_channel: Some(tx),
+            };
+            (event, rx)
+        };
+
+        // Without force the keys in use are protected.
+        let (event, mut response) = restart(false);
+        dkg_state.handle(node, event).await.unwrap();
+        assert!(matches!(
+            response.try_recv().unwrap(),
+            SelfResponse::RestartDkgResponse { success: false, .. }
+        ));
+        assert_eq!(dkg_state.round1_peer_packages.len(), 1);
+        assert!(node.private_key_package.is_some());
+        assert_eq!(start_dkg_broadcasts(&mut cluster), 0);
+
+        let node = cluster.nodes.get_mut(&local).unwrap();
+        let (event, mut response) = restart(true);
+        dkg_state.handle(node, event).await.unwrap();
+        assert!(matches!(
+            response.try_recv().unwrap(),
+            SelfResponse::RestartDkgResponse { success: true, .. }
+        ));
+
+        assert!(dkg_state.round1_peer_packages.is_empty());
+        assert!(dkg_state.round2_peer_packages.is_empty());
+        assert!(dkg_state.r2_secret_package.is_none());
+        assert!(dkg_state.pending_round2_acks.is_empty());
+        // The fresh ceremony is under way with a new round 1 secret.
+        assert!(dkg_state.dkg_started);
+        assert!(dkg_state.r1_secret_package.is_some());
+        let node = &cluster.nodes[&local];
+        assert!(node.private_key_package.is_none());
+        assert!(node.pubkey_package.is_none());
+        assert_eq!(start_dkg_broadcasts(&mut cluster), 1);
+    }
+
     #[tokio::test]
     async fn test_dkg_round2_private_requests() {
         setup();
@@ -427,6 +788,7 @@ mod dkg_test {
                 min_stake: 100,
                 block_time_seconds: 10,
                 max_block_size: 1000,
+                consensus_quorum: ConsensusQuorum::default(),
             };
 
             let expected_initial_state = protocol::block::GenesisState {
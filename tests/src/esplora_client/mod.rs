@@ -1,8 +1,16 @@
 #[cfg(test)]
 mod esplora_client_test {
     use bitcoin::{Address, Network};
-    use oracle::{esplora::EsploraOracle, oracle::Oracle};
+    use oracle::{
+        esplora::EsploraOracle,
+        oracle::Oracle,
+        rate_limit::{RequestLimiter, parse_retry_after},
+    };
     use std::str::FromStr;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::time::{Duration, Instant, sleep};
+    use types::errors::NodeError;
 
     #[tokio::test]
     async fn test_get_confirmed_transactions() {
@@ -30,4 +38,56 @@ mod esplora_client_test {
             assert!(correct_txs.contains(&tx.compute_txid().to_string().as_str()));
         }
     }
+
+    #[tokio::test]
+    async fn test_rate_limited_request_backs_off_within_concurrency_limit() {
+        const LIMIT: usize = 2;
+        let limiter = RequestLimiter::new(LIMIT).with_initial_backoff(Duration::from_millis(20));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let started = Instant::now();
+
+        // The backend answers the very first request with a 429 asking for a 1s wait.
+        let requests = (0..8).map(|i| {
+            let limiter = limiter.clone();
+            let (in_flight, peak, calls) = (in_flight.clone(), peak.clone(), calls.clone());
+            tokio::spawn(async move {
+                limiter
+                    .run("test_request", || {
+                        let (in_flight, peak, calls) =
+                            (in_flight.clone(), peak.clone(), calls.clone());
+                        async move {
+                            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now, Ordering::SeqCst);
+                            sleep(Duration::from_millis(10)).await;
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+                            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                                Err(NodeError::OracleRateLimited {
+                                    retry_after_secs: Some(1),
+                                })
+                            } else {
+                                Ok(i)
+                            }
+                        }
+                    })
+                    .await
+            })
+        });
+        let results = futures::future::join_all(requests).await;
+
+        for (i, result) in results.into_iter().enumerate() {
+            assert_eq!(result.unwrap().unwrap(), i);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 9);
+        assert!(peak.load(Ordering::SeqCst) <= LIMIT);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_after_is_read_in_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(120));
+        assert_eq!(parse_retry_after(" 3 "), Some(3));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2026 07:28:00 GMT"), None);
+    }
 }
@@ -1,10 +1,13 @@
+pub mod balance;
 pub mod config;
 pub mod consensus;
 pub mod deposit;
 pub mod dkg;
 pub mod esplora_client;
 pub mod mocks;
+pub mod peers;
 pub mod protocol;
+pub mod rpc_client;
 pub mod signing;
 pub mod util;
 pub mod wallet;
@@ -9,9 +9,13 @@ use abci::{
 use frost_secp256k1::keys::PublicKeyPackage;
 use protocol::{
     block::{Block, ChainConfig, GenesisBlock, ValidatorInfo},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionId},
+};
+use types::{
+    audit::AuditEntry,
+    errors::NodeError,
+    intents::{DepositIntent, WithdrawalRecord},
 };
-use types::{errors::NodeError, intents::DepositIntent};
 
 use super::db::MockDb;
 
@@ -52,16 +56,8 @@ impl TransactionExecutor for MockTransactionExecutor {
                             transaction.operations.first(),
                             transaction.operations.get(1),
                         ) {
-                            let amount = u64::from_be_bytes([
-                                amount_bytes[0],
-                                amount_bytes[1],
-                                amount_bytes[2],
-                                amount_bytes[3],
-                                amount_bytes[4],
-                                amount_bytes[5],
-                                amount_bytes[6],
-                                amount_bytes[7],
-                            ]);
+                            let amount = protocol::transaction::decode_amount(amount_bytes)
+                                .unwrap_or_default();
                             let address = String::from_utf8(addr_bytes.clone()).unwrap_or_default();
 
                             let current_balance = chain_state
@@ -88,16 +84,8 @@ impl TransactionExecutor for MockTransactionExecutor {
                             transaction.operations.first(),
                             transaction.operations.get(1),
                         ) {
-                            let amount = u64::from_be_bytes([
-                                amount_bytes[0],
-                                amount_bytes[1],
-                                amount_bytes[2],
-                                amount_bytes[3],
-                                amount_bytes[4],
-                                amount_bytes[5],
-                                amount_bytes[6],
-                                amount_bytes[7],
-                            ]);
+                            let amount = protocol::transaction::decode_amount(amount_bytes)
+                                .unwrap_or_default();
                             let address = String::from_utf8(addr_bytes.clone()).unwrap_or_default();
 
                             let current_balance = chain_state
@@ -114,11 +102,49 @@ impl TransactionExecutor for MockTransactionExecutor {
                         }
                     }
                 }
+                protocol::transaction::Operation::OpExpireDepositIntent => {
+                    // Mock implementation - drop the intent the expiry was built from
+                    if let (
+                        Some(protocol::transaction::Operation::OpPush {
+                            value: expiry_bytes,
+                        }),
+                        Some(protocol::transaction::Operation::OpPush { value: addr_bytes }),
+                    ) = (
+                        transaction.operations.first(),
+                        transaction.operations.get(1),
+                    ) {
+                        let expires_at =
+                            protocol::transaction::decode_amount(expiry_bytes).unwrap_or_default();
+                        let address = String::from_utf8(addr_bytes.clone()).unwrap_or_default();
+
+                        if let Some(intent) = chain_state
+                            .get_deposit_intent_by_address(&address)
+                            .filter(|intent| intent.expires_at == expires_at)
+                            .cloned()
+                        {
+                            chain_state.remove_deposit_intent(&intent);
+                        }
+                    }
+                }
             }
         }
 
         Ok(chain_state)
     }
+
+    async fn replay_transaction(
+        &mut self,
+        transaction: Transaction,
+        chain_state: ChainState,
+    ) -> Result<ChainState, NodeError> {
+        // The mock never queries an oracle, so a replay executes the same way
+        self.execute_transaction(transaction, chain_state).await
+    }
+
+    async fn confirm_oracle_checks(&self, _transaction: &Transaction) -> Result<(), NodeError> {
+        // Mock implementation - assume every oracle check passes
+        Ok(())
+    }
 }
 
 pub struct MockChainInterface {
@@ -191,6 +217,23 @@ impl ChainInterface for MockChainInterface {
             .get_proposed_block(previous_block, proposer))
     }
 
+    fn get_block_for_transactions(
+        &self,
+        previous_block: Option<Block>,
+        proposer: Vec<u8>,
+        tx_ids: &[TransactionId],
+    ) -> Result<Block, NodeError> {
+        self.chain_state
+            .get_block_for_transactions(previous_block, proposer, tx_ids)
+    }
+
+    async fn verify_block_deposits(&self, block: &Block) -> Result<(), NodeError> {
+        for transaction in &block.body.transactions {
+            self.executor.confirm_oracle_checks(transaction).await?;
+        }
+        Ok(())
+    }
+
     fn create_genesis_block(
         &mut self,
         validators: Vec<ValidatorInfo>,
@@ -204,6 +247,7 @@ impl ChainInterface for MockChainInterface {
                 .serialize()
                 .map_err(|e| NodeError::Error(format!("Failed to serialize public key: {e}")))?,
         );
+        self.db.insert_genesis(&genesis_block)?;
         self.db.insert_block(genesis_block.to_block())
     }
 
@@ -235,16 +279,13 @@ impl ChainInterface for MockChainInterface {
                 .await?;
         }
 
-        // Store the block in the database
-        self.db.insert_block(block.clone())?;
+        // Only the block's transactions leave the pending set
+        new_chain_state.remove_pending_transactions(&block.body.transactions);
 
-        // Update chain state
-        self.db.flush_state(&new_chain_state)?;
+        // Store the block and the state it produced together
+        self.db.commit_block(block.clone(), &new_chain_state)?;
         self.chain_state = new_chain_state;
 
-        // Clear pending transactions
-        self.chain_state.clear_pending_transactions();
-
         Ok(())
     }
 
@@ -256,6 +297,88 @@ impl ChainInterface for MockChainInterface {
         self.chain_state.clone()
     }
 
+    fn get_block_by_height(&self, height: u64) -> Result<Option<Block>, NodeError> {
+        self.db.get_block_by_height(height)
+    }
+
+    fn get_block_timestamp(&self, height: u64) -> Result<Option<u64>, NodeError> {
+        self.db.get_block_timestamp(height)
+    }
+
+    fn consume_withdrawal_challenge(&mut self, challenge: &str) -> Result<(), NodeError> {
+        if self.db.is_challenge_consumed(challenge)? {
+            return Err(NodeError::WithdrawalChallengeReplayed {
+                challenge: challenge.to_string(),
+            });
+        }
+        self.db.insert_consumed_challenge(challenge)
+    }
+
+    fn append_audit_entry(&mut self, entry: AuditEntry) -> Result<(), NodeError> {
+        self.db.append_audit_entry(entry).map(|_| ())
+    }
+
+    fn get_audit_log(&self, from: u64, to: u64) -> Result<Vec<AuditEntry>, NodeError> {
+        self.db.get_audit_log(from, to)
+    }
+
+    fn get_genesis(&self) -> Result<Option<GenesisBlock>, NodeError> {
+        self.db.get_genesis()
+    }
+
+    fn upsert_withdrawal_record(&mut self, record: &WithdrawalRecord) -> Result<(), NodeError> {
+        self.db.upsert_withdrawal_record(record)
+    }
+
+    fn get_withdrawal_records(&self) -> Result<Vec<WithdrawalRecord>, NodeError> {
+        self.db.get_withdrawal_records()
+    }
+
+    fn allocate_sign_id_counter(&mut self) -> Result<u64, NodeError> {
+        self.db.allocate_sign_id_counter()
+    }
+
+    fn record_processed_deposit(
+        &mut self,
+        txid: &str,
+        bitcoin_height: u32,
+    ) -> Result<(), NodeError> {
+        self.db.insert_processed_deposit(txid, bitcoin_height)
+    }
+
+    fn get_processed_deposits(&self) -> Result<Vec<(String, u32)>, NodeError> {
+        self.db.get_processed_deposits()
+    }
+
+    fn prune_processed_deposits(&mut self, bitcoin_height: u32) -> Result<usize, NodeError> {
+        self.db.prune_processed_deposits(bitcoin_height)
+    }
+
+    fn record_awaiting_deposit(
+        &mut self,
+        txid: &str,
+        credit_at: u32,
+        raw_tx: &[u8],
+    ) -> Result<(), NodeError> {
+        self.db.insert_awaiting_deposit(txid, credit_at, raw_tx)
+    }
+
+    fn get_awaiting_deposits(&self) -> Result<Vec<(String, u32, Vec<u8>)>, NodeError> {
+        self.db.get_awaiting_deposits()
+    }
+
+    fn remove_awaiting_deposit(&mut self, txid: &str) -> Result<(), NodeError> {
+        self.db.remove_awaiting_deposit(txid)
+    }
+
+    fn record_watch_address(&mut self, address: &str) -> Result<(), NodeError> {
+        self.db.insert_watch_address(address)
+    }
+
+    fn get_watch_addresses(&self) -> Result<Vec<String>, NodeError> {
+        self.db.get_watch_addresses()
+    }
+
     fn remove_deposit_intent(&mut self, intent: DepositIntent) -> Result<(), NodeError> {
         self.chain_state.remove_deposit_intent(&intent);
         self.db.remove_deposit_intent(intent)?;
@@ -1,16 +1,33 @@
-use std::{collections::HashMap, sync::RwLock};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::RwLock,
+};
 
 use abci::{chain_state::ChainState, db::Db};
-use protocol::block::{Block, BlockHash};
-use types::{errors::NodeError, intents::DepositIntent, utxo::Utxo};
+use protocol::block::{Block, BlockHash, GenesisBlock};
+use types::{
+    audit::AuditEntry,
+    errors::NodeError,
+    intents::{DepositIntent, WithdrawalRecord},
+    utxo::Utxo,
+};
 
 pub struct MockDb {
     pub blocks: RwLock<HashMap<BlockHash, Block>>,
     pub chain_state: RwLock<ChainState>,
     pub height_map: RwLock<HashMap<u64, BlockHash>>,
+    pub block_timestamps: RwLock<HashMap<u64, u64>>,
     pub tip_block_hash: RwLock<Option<BlockHash>>,
+    pub genesis: RwLock<Option<GenesisBlock>>,
     pub deposit_intents: RwLock<HashMap<String, DepositIntent>>,
     pub utxos: RwLock<HashMap<String, Utxo>>,
+    pub consumed_challenges: RwLock<HashSet<String>>,
+    pub audit_log: RwLock<Vec<AuditEntry>>,
+    pub withdrawal_records: RwLock<BTreeMap<String, WithdrawalRecord>>,
+    pub next_sign_id_counter: RwLock<u64>,
+    pub processed_deposits: RwLock<BTreeMap<String, u32>>,
+    pub awaiting_deposits: RwLock<BTreeMap<String, (u32, Vec<u8>)>>,
+    pub watch_addresses: RwLock<BTreeSet<String>>,
 }
 
 impl Default for MockDb {
@@ -25,9 +42,18 @@ impl MockDb {
             blocks: RwLock::new(HashMap::new()),
             chain_state: RwLock::new(ChainState::new()),
             height_map: RwLock::new(HashMap::new()),
+            block_timestamps: RwLock::new(HashMap::new()),
             tip_block_hash: RwLock::new(None),
+            genesis: RwLock::new(None),
             deposit_intents: RwLock::new(HashMap::new()),
             utxos: RwLock::new(HashMap::new()),
+            consumed_challenges: RwLock::new(HashSet::new()),
+            audit_log: RwLock::new(Vec::new()),
+            withdrawal_records: RwLock::new(BTreeMap::new()),
+            next_sign_id_counter: RwLock::new(0),
+            processed_deposits: RwLock::new(BTreeMap::new()),
+            awaiting_deposits: RwLock::new(BTreeMap::new()),
+            watch_addresses: RwLock::new(BTreeSet::new()),
         }
     }
 }
@@ -49,6 +75,15 @@ impl Db for MockDb {
         Ok(*self.tip_block_hash.read().unwrap())
     }
 
+    fn insert_genesis(&self, genesis: &GenesisBlock) -> Result<(), NodeError> {
+        *self.genesis.write().unwrap() = Some(genesis.clone());
+        Ok(())
+    }
+
+    fn get_genesis(&self) -> Result<Option<GenesisBlock>, NodeError> {
+        Ok(self.genesis.read().unwrap().clone())
+    }
+
     fn insert_chain_state(&self, chain_state: ChainState) -> Result<(), NodeError> {
         *self.chain_state.write().unwrap() = chain_state;
         Ok(())
@@ -66,9 +101,21 @@ impl Db for MockDb {
         blocks.insert(block.hash(), block.clone());
         height_map.insert(block.header.height, block.hash());
         *tip_block_hash = Some(block.hash());
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.block_timestamps
+            .write()
+            .unwrap()
+            .insert(block.header.height, timestamp);
         Ok(())
     }
 
+    fn get_block_timestamp(&self, height: u64) -> Result<Option<u64>, NodeError> {
+        Ok(self.block_timestamps.read().unwrap().get(&height).copied())
+    }
+
     fn insert_deposit_intent(&self, intent: DepositIntent) -> Result<(), NodeError> {
         let mut deposit_intents = self.deposit_intents.write().unwrap();
         deposit_intents.insert(intent.deposit_tracking_id.clone(), intent);
@@ -80,6 +127,11 @@ impl Db for MockDb {
         Ok(())
     }
 
+    fn commit_block(&self, block: Block, chain_state: &ChainState) -> Result<(), NodeError> {
+        self.insert_block(block)?;
+        self.flush_state(chain_state)
+    }
+
     fn get_deposit_intent(&self, tracking_id: &str) -> Result<Option<DepositIntent>, NodeError> {
         let deposit_intents = self.deposit_intents.read().unwrap();
         Ok(deposit_intents.get(tracking_id).cloned())
@@ -119,4 +171,129 @@ impl Db for MockDb {
         deposit_intents.remove(&intent.deposit_tracking_id);
         Ok(())
     }
+
+    fn insert_consumed_challenge(&self, challenge: &str) -> Result<(), NodeError> {
+        self.consumed_challenges
+            .write()
+            .unwrap()
+            .insert(challenge.to_string());
+        Ok(())
+    }
+
+    fn is_challenge_consumed(&self, challenge: &str) -> Result<bool, NodeError> {
+        Ok(self.consumed_challenges.read().unwrap().contains(challenge))
+    }
+
+    fn append_audit_entry(&self, mut entry: AuditEntry) -> Result<u64, NodeError> {
+        let mut audit_log = self.audit_log.write().unwrap();
+        entry.sequence = audit_log.len() as u64;
+        audit_log.push(entry);
+        Ok(audit_log.len() as u64 - 1)
+    }
+
+    fn get_audit_log(&self, from: u64, to: u64) -> Result<Vec<AuditEntry>, NodeError> {
+        Ok(self
+            .audit_log
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|entry| (from..=to).contains(&entry.timestamp))
+            .cloned()
+            .collect())
+    }
+    fn upsert_withdrawal_record(&self, record: &WithdrawalRecord) -> Result<(), NodeError> {
+        self.withdrawal_records
+            .write()
+            .unwrap()
+            .insert(record.challenge.clone(), record.clone());
+        Ok(())
+    }
+
+    fn get_withdrawal_records(&self) -> Result<Vec<WithdrawalRecord>, NodeError> {
+        Ok(self
+            .withdrawal_records
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    fn allocate_sign_id_counter(&self) -> Result<u64, NodeError> {
+        let mut next = self.next_sign_id_counter.write().unwrap();
+        let counter = *next;
+        *next += 1;
+        Ok(counter)
+    }
+
+    fn insert_processed_deposit(&self, txid: &str, bitcoin_height: u32) -> Result<(), NodeError> {
+        self.processed_deposits
+            .write()
+            .unwrap()
+            .insert(txid.to_string(), bitcoin_height);
+        Ok(())
+    }
+
+    fn get_processed_deposits(&self) -> Result<Vec<(String, u32)>, NodeError> {
+        Ok(self
+            .processed_deposits
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(txid, height)| (txid.clone(), *height))
+            .collect())
+    }
+
+    fn prune_processed_deposits(&self, bitcoin_height: u32) -> Result<usize, NodeError> {
+        let mut deposits = self.processed_deposits.write().unwrap();
+        let before = deposits.len();
+        deposits.retain(|_, height| *height >= bitcoin_height);
+        Ok(before - deposits.len())
+    }
+
+    fn insert_awaiting_deposit(
+        &self,
+        txid: &str,
+        credit_at: u32,
+        raw_tx: &[u8],
+    ) -> Result<(), NodeError> {
+        self.awaiting_deposits
+            .write()
+            .unwrap()
+            .insert(txid.to_string(), (credit_at, raw_tx.to_vec()));
+        Ok(())
+    }
+
+    fn get_awaiting_deposits(&self) -> Result<Vec<(String, u32, Vec<u8>)>, NodeError> {
+        Ok(self
+            .awaiting_deposits
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(txid, (credit_at, raw_tx))| (txid.clone(), *credit_at, raw_tx.clone()))
+            .collect())
+    }
+
+    fn remove_awaiting_deposit(&self, txid: &str) -> Result<(), NodeError> {
+        self.awaiting_deposits.write().unwrap().remove(txid);
+        Ok(())
+    }
+
+    fn insert_watch_address(&self, address: &str) -> Result<(), NodeError> {
+        self.watch_addresses
+            .write()
+            .unwrap()
+            .insert(address.to_string());
+        Ok(())
+    }
+
+    fn get_watch_addresses(&self) -> Result<Vec<String>, NodeError> {
+        Ok(self
+            .watch_addresses
+            .read()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect())
+    }
 }
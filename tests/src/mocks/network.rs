@@ -1,7 +1,11 @@
-use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+    time::Duration,
+};
 
 use frost_secp256k1::Identifier;
-use node::{NodeState, wallet::TaprootWallet};
+use node::{NodeState, utils::retry_queue::DirectMessageRetryQueue, wallet::TaprootWallet};
 pub use oracle::mock::MockOracle;
 use tokio::sync::{
     broadcast,
@@ -11,7 +15,7 @@ use types::{
     errors::{self, NetworkError},
     intents::DepositIntent,
     network::network_event::{DirectMessage, NetworkEvent, SelfRequest, SelfResponse},
-    network::network_protocol::{Network, NetworkResponseFuture},
+    network::network_protocol::{Network, NetworkResponseFuture, broadcast_topic},
     proto::ProtoEncode,
 };
 
@@ -58,6 +62,7 @@ pub struct MockNetwork {
     pub peer: libp2p::PeerId,
     pub events_emitter_tx: broadcast::Sender<NetworkEvent>,
     pub pending_events_tx: mpsc::UnboundedSender<PendingNetworkEvent>,
+    pub chain_id: String,
 }
 
 impl MockNetwork {
@@ -65,11 +70,13 @@ impl MockNetwork {
         events_emitter_tx: broadcast::Sender<NetworkEvent>,
         peer: libp2p::PeerId,
         pending_events_tx: mpsc::UnboundedSender<PendingNetworkEvent>,
+        chain_id: String,
     ) -> Self {
         Self {
             events_emitter_tx,
             peer,
             pending_events_tx,
+            chain_id,
         }
     }
 }
@@ -84,7 +91,7 @@ impl Network for MockNetwork {
             source: Some(self.peer),
             data: message.encode().map_err(NetworkError::SendError)?,
             sequence_number: None,
-            topic: libp2p::gossipsub::IdentTopic::new("broadcast").hash(),
+            topic: message.gossip_topic().for_chain(&self.chain_id).hash(),
         };
 
         // Queue the event instead of sending immediately
@@ -156,6 +163,10 @@ impl Network for MockNetwork {
     fn peer_name(&self, _peer_id: &libp2p::PeerId) -> String {
         "test-peer".to_string()
     }
+
+    fn disconnect_peer(&self, _peer_id: libp2p::PeerId) -> Result<(), errors::NetworkError> {
+        Ok(())
+    }
 }
 
 pub struct MockNodeCluster {
@@ -163,10 +174,19 @@ pub struct MockNodeCluster {
     pub senders: BTreeMap<libp2p::PeerId, SenderToNode>,
     pub networks: BTreeMap<libp2p::PeerId, MockNetwork>,
     pub pending_events_rx: mpsc::UnboundedReceiver<PendingNetworkEvent>,
+    /// Peers that direct messages and gossip currently cannot reach or leave.
+    pub unreachable: HashSet<libp2p::PeerId>,
+    /// Each node's undelivered direct messages, as its swarm manager would hold them.
+    pub retry_queues: BTreeMap<libp2p::PeerId, DirectMessageRetryQueue>,
 }
 
 impl MockNodeCluster {
     pub async fn new(peers: u32) -> Self {
+        Self::new_with_chain_id(peers, node::config::DEFAULT_CHAIN_ID).await
+    }
+
+    /// Cluster whose nodes all run on `chain_id`.
+    pub async fn new_with_chain_id(peers: u32, chain_id: &str) -> Self {
         let mut path = PathBuf::new();
         path.push("config.json");
 
@@ -179,12 +199,14 @@ impl MockNodeCluster {
             .password("test-password")
             .min_signers(peers as u16)
             .max_signers(peers as u16)
+            .chain_id(chain_id)
             .build()
             .expect("Failed to create node config");
 
         let mut nodes = BTreeMap::new();
         let mut senders = BTreeMap::new();
         let mut networks = BTreeMap::new();
+        let mut retry_queues = BTreeMap::new();
 
         // Create a single channel for all pending events
         let (pending_events_tx, pending_events_rx) = mpsc::unbounded_channel();
@@ -203,6 +225,10 @@ impl MockNodeCluster {
                 SenderToNode::new(network.events_emitter_tx.clone()),
             );
             networks.insert(peer_id, network);
+            retry_queues.insert(
+                peer_id,
+                DirectMessageRetryQueue::new(node_config.direct_message_retry_budget),
+            );
         }
 
         Self {
@@ -210,6 +236,8 @@ impl MockNodeCluster {
             senders,
             networks,
             pending_events_rx,
+            unreachable: HashSet::new(),
+            retry_queues,
         }
     }
 
@@ -231,13 +259,14 @@ impl MockNodeCluster {
             ));
 
             for peer_id in peers.iter().filter(|peer_id| *peer_id != receipient_peer) {
+                let topic = broadcast_topic(&self.networks[peer_id].chain_id).hash();
                 sender.queue(NetworkEvent::Subscribed {
                     peer_id: *peer_id,
-                    topic: libp2p::gossipsub::IdentTopic::new("broadcast").hash(),
+                    topic: topic.clone(),
                 });
                 sender.queue(NetworkEvent::Subscribed {
                     peer_id: *peer_id,
-                    topic: libp2p::gossipsub::IdentTopic::new("broadcast").hash(),
+                    topic,
                 });
             }
 
@@ -281,7 +310,7 @@ impl MockNodeCluster {
     }
 
     // Process network events that were generated during node polling
-    async fn process_network_events(&mut self) {
+    pub async fn process_network_events(&mut self) {
         // Collect all pending events from the channel
         let mut all_pending_events = Vec::new();
 
@@ -298,12 +327,30 @@ impl MockNodeCluster {
 
     // Forward a single event to the appropriate target peers
     async fn forward_event_to_peers(&mut self, pending_event: PendingNetworkEvent) {
+        if let NetworkEvent::MessageEvent((_, message)) = &pending_event.event {
+            if !pending_event.target_peers.is_empty() {
+                for target_peer in &pending_event.target_peers {
+                    self.deliver_direct_message(
+                        pending_event.from_peer,
+                        *target_peer,
+                        message.clone(),
+                        1,
+                    );
+                }
+                return;
+            }
+        }
+        if self.unreachable.contains(&pending_event.from_peer) {
+            return;
+        }
+
         if pending_event.target_peers.is_empty() {
             // Broadcast to all peers except the sender
             let target_peers: Vec<libp2p::PeerId> = self
                 .senders
                 .keys()
                 .filter(|peer_id| **peer_id != pending_event.from_peer)
+                .filter(|peer_id| !self.unreachable.contains(peer_id))
                 .cloned()
                 .collect();
 
@@ -384,6 +431,26 @@ impl MockNodeCluster {
         }
     }
 
+    /// Delivers the `attempt`-th try of a direct message, handing it to the sender's retry
+    /// queue instead when either end is unreachable.
+    fn deliver_direct_message(
+        &mut self,
+        from_peer: libp2p::PeerId,
+        to_peer: libp2p::PeerId,
+        message: DirectMessage,
+        attempt: u32,
+    ) {
+        if self.unreachable.contains(&from_peer) || self.unreachable.contains(&to_peer) {
+            if let Some(queue) = self.retry_queues.get_mut(&from_peer) {
+                queue.record_failure(to_peer, message, attempt);
+            }
+            return;
+        }
+        if let Some(sender) = self.senders.get_mut(&to_peer) {
+            sender.queue(NetworkEvent::MessageEvent((from_peer, message)));
+        }
+    }
+
     // Methods to send various types of network events for testing
     pub fn send_broadcast_to_all(
         &mut self,
@@ -449,17 +516,51 @@ impl MockNodeCluster {
         }
     }
 
+    /// Cuts `peer_id` off: its peers see it disconnect and messages to or from it fail.
+    pub fn simulate_peer_unreachable(&mut self, peer_id: libp2p::PeerId) {
+        self.unreachable.insert(peer_id);
+        self.simulate_peer_disconnect(peer_id);
+    }
+
+    /// Brings `peer_id` back and redelivers the direct messages queued for it, as the swarm
+    /// manager does when a connection is re-established.
+    pub fn simulate_peer_reachable(&mut self, peer_id: libp2p::PeerId) {
+        self.unreachable.remove(&peer_id);
+        self.simulate_peer_reconnect(peer_id);
+
+        let senders: Vec<libp2p::PeerId> = self.retry_queues.keys().copied().collect();
+        for from_peer in senders {
+            let pending = self
+                .retry_queues
+                .get_mut(&from_peer)
+                .map(|queue| queue.take(&peer_id))
+                .unwrap_or_default();
+            for delivery in pending {
+                self.deliver_direct_message(
+                    from_peer,
+                    peer_id,
+                    delivery.message,
+                    delivery.attempts + 1,
+                );
+            }
+        }
+    }
+
     // Helper method to get peer IDs for testing
     pub fn get_peer_ids(&self) -> Vec<libp2p::PeerId> {
         self.nodes.keys().cloned().collect()
     }
 
     pub async fn new_with_keys(peers: u32) -> Self {
+        Self::new_with_threshold_keys(peers, peers).await
+    }
+
+    /// Cluster whose group key needs only `min_signers` of the `peers` nodes to sign.
+    pub async fn new_with_threshold_keys(peers: u32, min_signers: u32) -> Self {
         let mut cluster = Self::new(peers).await;
 
-        // Set the min_signers and max_signers in the config for all nodes
         for node in cluster.nodes.values_mut() {
-            node.config.min_signers = Some(peers as u16);
+            node.config.min_signers = Some(min_signers as u16);
             node.config.max_signers = Some(peers as u16);
         }
 
@@ -470,9 +571,9 @@ impl MockNodeCluster {
             .collect();
 
         // Run offline DKG once and distribute keys
-        // Use the actual number of peers for both min_signers and max_signers
         let dkg_out =
-            perform_distributed_key_generation(identifiers, peers as u16, peers as u16).unwrap();
+            perform_distributed_key_generation(identifiers, peers as u16, min_signers as u16)
+                .unwrap();
 
         for (peer_id, node) in cluster.nodes.iter_mut() {
             let id = node::peer_id_to_identifier(peer_id);
@@ -497,7 +598,12 @@ pub async fn create_node_network(
     let (events_emitter_tx, _) = broadcast::channel::<NetworkEvent>(256);
     let (deposit_intent_tx, _) = broadcast::channel::<DepositIntent>(100);
 
-    let network = MockNetwork::new(events_emitter_tx.clone(), peer_id, pending_events_tx);
+    let network = MockNetwork::new(
+        events_emitter_tx.clone(),
+        peer_id,
+        pending_events_tx,
+        node_config.chain_id.clone(),
+    );
 
     let executor = Box::new(crate::mocks::abci::MockTransactionExecutor);
     let db = Box::new(crate::mocks::db::MockDb::new());
@@ -515,7 +621,8 @@ pub async fn create_node_network(
         Box::new(oracle.clone()),
         Vec::new(),
         bitcoin::network::Network::Testnet,
-    );
+    )
+    .with_max_utxos_per_address(node_config.max_utxos_per_address);
 
     // Create a mock consensus interface for testing
     let (consensus_interface_tx, _) = messenger::channel(100, Some(100));
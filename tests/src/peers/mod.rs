@@ -0,0 +1,163 @@
+#[cfg(test)]
+mod peer_tests {
+    use crate::mocks::network::MockNodeCluster;
+    use std::sync::Arc;
+    use types::clock::MockClock;
+    use types::network::network_event::{DirectMessage, NetworkEvent, SelfRequest};
+
+    fn tick() -> NetworkEvent {
+        NetworkEvent::SelfRequest {
+            request: SelfRequest::ConsensusTick,
+            response_channel: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn peer_reconnecting_within_grace_period_stays_active() {
+        let mut cluster = MockNodeCluster::new(3).await;
+        cluster.setup().await;
+        cluster.run_n_iterations(1).await;
+
+        let peers = cluster.get_peer_ids();
+        let (observer, flaky) = (peers[0], peers[1]);
+        assert!(cluster.nodes[&observer].peers.contains(&flaky));
+
+        cluster.simulate_peer_disconnect(flaky);
+        cluster.run_n_iterations(1).await;
+
+        let node = cluster.nodes.get_mut(&observer).unwrap();
+        assert!(node.disconnected_peers.contains_key(&flaky));
+        node.handle_message(tick()).await.unwrap();
+        assert!(node.peers.contains(&flaky));
+
+        cluster.simulate_peer_reconnect(flaky);
+        cluster.run_n_iterations(1).await;
+
+        let node = cluster.nodes.get_mut(&observer).unwrap();
+        assert!(node.peers.contains(&flaky));
+        assert!(node.disconnected_peers.is_empty());
+        node.handle_message(tick()).await.unwrap();
+        assert!(node.peers.contains(&flaky));
+    }
+
+    #[tokio::test]
+    async fn peer_is_dropped_once_grace_period_elapses() {
+        let mut cluster = MockNodeCluster::new(3).await;
+        cluster.setup().await;
+        cluster.run_n_iterations(1).await;
+
+        let peers = cluster.get_peer_ids();
+        let (observer, gone) = (peers[0], peers[1]);
+        cluster
+            .nodes
+            .get_mut(&observer)
+            .unwrap()
+            .config
+            .peer_disconnect_grace_seconds = 0;
+
+        cluster.simulate_peer_disconnect(gone);
+        cluster.run_n_iterations(1).await;
+
+        let node = cluster.nodes.get_mut(&observer).unwrap();
+        assert!(node.peers.contains(&gone));
+        node.handle_message(tick()).await.unwrap();
+        assert!(!node.peers.contains(&gone));
+        assert!(node.disconnected_peers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn peers_on_different_protocol_versions_refuse_each_other() {
+        let mut cluster = MockNodeCluster::new(3).await;
+        let peers = cluster.get_peer_ids();
+        let (a, b, outdated) = (peers[0], peers[1], peers[2]);
+        cluster.nodes.get_mut(&outdated).unwrap().protocol_version += 1;
+
+        cluster.setup().await;
+        cluster.run_n_iterations(3).await;
+
+        for (node, other) in [(a, b), (b, a)] {
+            let node = &cluster.nodes[&node];
+            assert!(node.peers.contains(&other));
+            assert!(!node.peers.contains(&outdated));
+            assert!(node.incompatible_peers.contains(&outdated));
+        }
+        let node = &cluster.nodes[&outdated];
+        assert!(node.peers.is_empty());
+        assert_eq!(node.incompatible_peers.len(), 2);
+
+        // Reconnecting does not bring the incompatible peer back
+        cluster.simulate_peer_reconnect(outdated);
+        cluster.run_n_iterations(2).await;
+        assert!(!cluster.nodes[&a].peers.contains(&outdated));
+    }
+
+    #[tokio::test]
+    async fn events_dropped_by_a_lagging_node_are_counted() {
+        let mut cluster = MockNodeCluster::new(1).await;
+        let peer_id = cluster.get_peer_ids()[0];
+        let node = cluster.nodes.get_mut(&peer_id).unwrap();
+
+        // Flood a small channel well past its capacity before the node reads anything
+        let (events_tx, events_rx) = tokio::sync::broadcast::channel(4);
+        node.network_events_stream = events_rx;
+        for _ in 0..20 {
+            events_tx.send(NetworkEvent::Unknown).unwrap();
+        }
+
+        let mut handled = 0;
+        while node.try_poll().await.unwrap() {
+            handled += 1;
+        }
+
+        assert_eq!(node.dropped_network_events, 16);
+        // One poll reports the lag, the rest deliver what the channel still held
+        assert_eq!(handled, 5);
+    }
+
+    #[tokio::test]
+    async fn messages_wait_for_the_handshake_and_silent_peers_are_dropped() {
+        let mut cluster = MockNodeCluster::new(3).await;
+        let peers = cluster.get_peer_ids();
+        let (observer, greeter, silent) = (peers[0], peers[1], peers[2]);
+        let clock = MockClock::new();
+        let node = cluster.nodes.get_mut(&observer).unwrap();
+        node.clock = Arc::new(clock.clone());
+
+        node.handle_message(NetworkEvent::PeersConnected(vec![
+            (greeter, libp2p::Multiaddr::empty()),
+            (silent, libp2p::Multiaddr::empty()),
+        ]))
+        .await
+        .unwrap();
+        for peer in [greeter, silent] {
+            node.handle_message(NetworkEvent::MessageEvent((
+                peer,
+                DirectMessage::Round2Ack {
+                    package_hash: vec![1],
+                },
+            )))
+            .await
+            .unwrap();
+        }
+        assert_eq!(node.pending_handshakes[&greeter].held.len(), 1);
+        assert_eq!(node.pending_handshakes[&silent].held.len(), 1);
+
+        // A compatible Hello releases what the peer sent before it
+        let hello = DirectMessage::Hello {
+            protocol_version: node.protocol_version,
+            chain_id: node.config.chain_id.clone(),
+        };
+        node.handle_message(NetworkEvent::MessageEvent((greeter, hello)))
+            .await
+            .unwrap();
+        assert!(!node.pending_handshakes.contains_key(&greeter));
+        assert_eq!(node.replayed_events.len(), 1);
+
+        // A peer that never says Hello is dropped once the handshake times out
+        clock.advance(node::main_loop::HANDSHAKE_TIMEOUT);
+        node.handle_message(tick()).await.unwrap();
+        assert!(node.pending_handshakes.is_empty());
+        assert!(node.peers.contains(&greeter));
+        assert!(!node.peers.contains(&silent));
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod block_test {
-    use protocol::block::Block;
+    use protocol::block::{Block, ConsensusQuorum};
 
     #[test]
     fn test_block_creation_and_hashing() {
@@ -10,4 +10,12 @@ mod block_test {
         let hash2 = block.hash();
         assert_eq!(hash1, hash2); // Hash should be deterministic
     }
+
+    #[test]
+    fn test_fractional_quorum_rounds_up() {
+        let two_thirds = ConsensusQuorum::default();
+        assert_eq!(two_thirds.required_votes(3), 2);
+        assert_eq!(two_thirds.required_votes(4), 3);
+        assert_eq!(two_thirds.required_votes(1), 1);
+    }
 }
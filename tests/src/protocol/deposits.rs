@@ -487,4 +487,50 @@ mod deposit_test {
             .unwrap();
         assert_eq!(result_state.get_account("1").unwrap().balance, 150);
     }
+    #[tokio::test]
+    async fn test_confirm_oracle_checks_rejects_unconfirmable_deposit() {
+        let confirmed = create_test_tx_hash();
+        let unconfirmed = Txid::from_slice(&[2u8; 32]).unwrap();
+        let mock_oracle = create_mock_oracle(vec![
+            (confirmed, "1".to_string(), 100, true),
+            (unconfirmed, "1".to_string(), 100, false),
+        ]);
+        let executor = TransactionExecutorImpl::new(Box::new(mock_oracle));
+
+        let deposit = |tx_hash: Txid| {
+            Transaction::new(
+                TransactionType::Deposit,
+                vec![
+                    Operation::OpPush {
+                        value: 100u64.to_be_bytes().to_vec(),
+                    },
+                    Operation::OpPush {
+                        value: "1".as_bytes().to_vec(),
+                    },
+                    Operation::OpPush {
+                        value: tx_hash.as_byte_array().to_vec(),
+                    },
+                    Operation::OpCheckOracle,
+                    Operation::OpPush {
+                        value: 100u64.to_be_bytes().to_vec(),
+                    },
+                    Operation::OpPush {
+                        value: "1".as_bytes().to_vec(),
+                    },
+                    Operation::OpIncrementBalance,
+                ],
+                None,
+            )
+        };
+
+        executor
+            .confirm_oracle_checks(&deposit(confirmed))
+            .await
+            .unwrap();
+        let err = executor
+            .confirm_oracle_checks(&deposit(unconfirmed))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("could not be confirmed"));
+    }
 }
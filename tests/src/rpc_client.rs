@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod rpc_client_test {
+    use std::time::Duration;
+
+    use grpc::client::{ConnectRetryPolicy, connect_with_retry};
+    use grpc::grpc_handler::NodeControlService;
+    use types::network::network_protocol::NetworkHandle;
+
+    fn unused_local_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn node_control_service() -> NodeControlService {
+        let (tx, _) = tokio::sync::mpsc::unbounded_channel();
+        let (deposit_events, _) = tokio::sync::broadcast::channel(1);
+        NodeControlService::new(
+            NetworkHandle {
+                peer_id: libp2p::PeerId::random(),
+                tx,
+                peers_to_names: std::collections::BTreeMap::new(),
+                chain_id: "test".to_string(),
+            },
+            deposit_events,
+        )
+    }
+
+    #[tokio::test]
+    async fn connect_with_retry_succeeds_once_server_comes_up() {
+        let port = unused_local_port();
+        let server = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            tonic::transport::Server::builder()
+                .add_service(node_control_service().into_server())
+                .serve(([127, 0, 0, 1], port).into())
+                .await
+        });
+
+        let policy = ConnectRetryPolicy {
+            max_attempts: 20,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_millis(200),
+        };
+        let client = connect_with_retry(format!("http://127.0.0.1:{port}"), &policy).await;
+        assert!(
+            client.is_ok(),
+            "client should connect once the server is up"
+        );
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn connect_with_retry_gives_up_after_max_attempts() {
+        let port = unused_local_port();
+        let policy = ConnectRetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(20),
+        };
+
+        let client = connect_with_retry(format!("http://127.0.0.1:{port}"), &policy).await;
+        assert!(client.is_err());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod threshold_scheme;
+
 #[cfg(test)]
 pub mod signing_tests {
     use std::str::FromStr;
@@ -8,8 +10,17 @@ pub mod signing_tests {
     use types::utxo::Utxo;
 
     use crate::mocks::network::MockNodeCluster;
+    use frost_secp256k1 as frost;
+    use node::config::DEFAULT_NONCE_POOL_SIZE;
+    use node::handlers::signing::{
+        COORDINATOR_SESSION_TIMEOUT, PARTICIPANT_SESSION_TIMEOUT, SigningState,
+    };
     use rand::RngCore;
-    use types::network::network_event::{DirectMessage, NetworkEvent, SelfRequest};
+    use types::network::network_event::{DirectMessage, NetworkEvent, SelfRequest, SelfResponse};
+    use types::proto::node_proto::{
+        GetActiveSigningSessionsRequest, GetReserveAttestationRequest, RunSigningSelfTestRequest,
+    };
+    use types::reserves::{ReserveAttestation, attestation_digest};
 
     #[tokio::test]
     async fn signing_flow_completes_and_produces_shares() {
@@ -74,13 +85,550 @@ pub mod signing_tests {
                 .iter()
                 .find_map(|h| h.downcast_ref::<node::handlers::signing::SigningState>());
             assert!(
-                signing_state.unwrap().active_signing.is_none(),
+                signing_state.unwrap().active_signing.is_empty(),
                 "node still has pending signing {:?}",
                 node.peer_id
             );
         }
     }
 
+    #[tokio::test]
+    async fn active_signing_sessions_report_progress() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator = *cluster.nodes.keys().next().unwrap();
+        let hex_message = hex::encode([7u8; 32]);
+        cluster.send_self_request_to_peer(
+            initiator,
+            SelfRequest::StartSigningSession {
+                hex_message: hex_message.clone(),
+            },
+        );
+        // Only the initiator has acted: its own commitment is in, no peer has answered yet.
+        cluster.run_n_iterations(1).await;
+
+        let network = cluster.networks[&initiator].clone();
+        let rpc = tokio::spawn(async move {
+            grpc::grpc_operator::get_active_signing_sessions(
+                &network,
+                GetActiveSigningSessionsRequest {},
+            )
+            .await
+        });
+        tokio::task::yield_now().await;
+
+        let node = cluster.nodes.get_mut(&initiator).unwrap();
+        while node.try_poll().await.expect("Failed to poll node") {}
+
+        let response = rpc.await.unwrap().expect("RPC failed");
+        assert_eq!(response.sessions.len(), 1);
+        let session = &response.sessions[0];
+        assert_eq!(session.message_hex, hex_message);
+        assert!(session.is_coordinator);
+        assert_eq!(session.selected_peer_count, 2);
+        assert_eq!(session.commitments_received, 1);
+        assert_eq!(session.shares_received, 0);
+    }
+
+    #[tokio::test]
+    async fn stalled_coordinator_session_is_pruned_with_what_awaits_it() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator = *cluster.nodes.keys().next().unwrap();
+        cluster.send_self_request_to_peer(
+            initiator,
+            SelfRequest::StartSigningSession {
+                hex_message: hex::encode([7u8; 32]),
+            },
+        );
+        // The signers never answer: only the coordinator has acted.
+        cluster.run_n_iterations(1).await;
+
+        let node = cluster.nodes.get_mut(&initiator).unwrap();
+        let now = node.clock.now();
+        let signing = node
+            .handlers
+            .iter_mut()
+            .find_map(|h| h.downcast_mut::<SigningState>())
+            .unwrap();
+        let sign_id = *signing.active_signing.keys().next().unwrap();
+        signing
+            .withdrawal_challenges
+            .insert(sign_id, "challenge".to_string());
+        let (response_tx, mut response_rx) = tokio::sync::mpsc::unbounded_channel();
+        signing.pending_attestations.insert(
+            sign_id,
+            (ReserveAttestation::new(0, Vec::new()), response_tx),
+        );
+
+        assert_eq!(
+            signing.prune_stale_sessions(&mut node.wallet, now + PARTICIPANT_SESSION_TIMEOUT),
+            0
+        );
+        assert_eq!(
+            signing.prune_stale_sessions(&mut node.wallet, now + COORDINATOR_SESSION_TIMEOUT),
+            1
+        );
+        assert!(signing.active_signing.is_empty());
+        assert!(signing.withdrawal_challenges.is_empty());
+        assert!(signing.pending_attestations.is_empty());
+        assert!(matches!(
+            response_rx.try_recv(),
+            Ok(SelfResponse::NodeError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn reserve_attestation_is_signed_by_the_group_key() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator = *cluster.nodes.keys().next().unwrap();
+        {
+            let node = cluster.nodes.get_mut(&initiator).unwrap();
+            let address = Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+                .unwrap()
+                .assume_checked();
+            for (i, value) in [(1u8, 25_000), (2, 50_000), (3, 125_000)] {
+                node.wallet.utxos.push(TrackedUtxo {
+                    utxo: Utxo {
+                        outpoint: OutPoint {
+                            txid: Txid::from_slice(&[i; 32]).unwrap(),
+                            vout: u32::from(i),
+                        },
+                        value: Amount::from_sat(value),
+                        script_pubkey: address.script_pubkey(),
+                    },
+                    address: address.clone(),
+                });
+            }
+        }
+
+        let network = cluster.networks[&initiator].clone();
+        let rpc = tokio::spawn(async move {
+            grpc::grpc_operator::get_reserve_attestation(&network, GetReserveAttestationRequest {})
+                .await
+        });
+        for _ in 0..50 {
+            if rpc.is_finished() {
+                break;
+            }
+            cluster.run_n_iterations(1).await;
+        }
+        let response = rpc.await.unwrap().expect("RPC failed");
+
+        let node = &cluster.nodes[&initiator];
+        let tracked_total: u64 = node
+            .wallet
+            .get_utxos()
+            .iter()
+            .map(|u| u.utxo.value.to_sat())
+            .sum();
+        assert_eq!(tracked_total, 200_000);
+        assert_eq!(response.total_value_satoshis, tracked_total);
+        assert_eq!(response.utxos.len(), 3);
+        assert_eq!(
+            response.utxos.iter().map(|u| u.value_satoshis).sum::<u64>(),
+            tracked_total
+        );
+
+        // The digest is recomputable from the attested fields alone.
+        let commitment: [u8; 32] = hex::decode(&response.utxo_commitment)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let digest =
+            attestation_digest(response.height, response.total_value_satoshis, &commitment);
+        assert_eq!(hex::encode(digest), response.digest);
+
+        let signature =
+            frost::Signature::deserialize(&hex::decode(&response.signature).unwrap()).unwrap();
+        let verifying_key = node.pubkey_package.as_ref().unwrap().verifying_key();
+        assert!(verifying_key.verify(&digest, &signature).is_ok());
+
+        // A tampered total no longer matches the signature.
+        let forged = attestation_digest(
+            response.height,
+            response.total_value_satoshis + 1,
+            &commitment,
+        );
+        assert!(verifying_key.verify(&forged, &signature).is_err());
+    }
+
+    #[tokio::test]
+    async fn signing_self_test_passes_and_reports_missing_signers() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+        let initiator = *cluster.nodes.keys().next().unwrap();
+
+        let network = cluster.networks[&initiator].clone();
+        let rpc = tokio::spawn(async move {
+            grpc::grpc_operator::run_signing_self_test(&network, RunSigningSelfTestRequest {}).await
+        });
+        for _ in 0..50 {
+            if rpc.is_finished() {
+                break;
+            }
+            cluster.run_n_iterations(1).await;
+        }
+        let response = rpc.await.unwrap().expect("RPC failed");
+        assert!(response.passed, "self-test failed: {}", response.message);
+
+        // The session signed the test digest only; no spend was set up.
+        let signing = cluster.nodes[&initiator]
+            .handlers
+            .iter()
+            .find_map(|h| h.downcast_ref::<SigningState>())
+            .unwrap();
+        assert!(signing.pending_spends.is_empty());
+        assert!(signing.pending_self_tests.is_empty());
+
+        // Without enough peers to reach the threshold, the self-test reports failure.
+        cluster.nodes.get_mut(&initiator).unwrap().peers.clear();
+        let network = cluster.networks[&initiator].clone();
+        let rpc = tokio::spawn(async move {
+            grpc::grpc_operator::run_signing_self_test(&network, RunSigningSelfTestRequest {}).await
+        });
+        for _ in 0..50 {
+            if rpc.is_finished() {
+                break;
+            }
+            cluster.run_n_iterations(1).await;
+        }
+        let response = rpc.await.unwrap().expect("RPC failed");
+        assert!(!response.passed);
+        assert!(
+            response.message.contains("Not enough peers"),
+            "unexpected failure: {}",
+            response.message
+        );
+    }
+
+    #[tokio::test]
+    async fn signing_completes_with_first_min_signers_to_commit() {
+        let mut cluster = MockNodeCluster::new_with_threshold_keys(4, 2).await;
+        cluster.setup().await;
+        cluster.run_n_iterations(1).await;
+
+        let peers = cluster.get_peer_ids();
+        let coordinator = peers[0];
+        let mut state = SigningState::new();
+        let sign_id = {
+            let node = cluster.nodes.get_mut(&coordinator).unwrap();
+            state
+                .start_signing_session(node, &hex::encode([9u8; 32]), None)
+                .await
+                .unwrap()
+                .unwrap()
+        };
+        // Every other node is asked, although one more signer is enough
+        assert_eq!(state.active_signing[&sign_id].selected_peers.len(), 3);
+
+        // Each peer commits as it would on receiving the sign request
+        let mut nonces = std::collections::BTreeMap::new();
+        let mut commitments = std::collections::BTreeMap::new();
+        for peer in &peers[1..] {
+            let key_package = cluster.nodes[peer].private_key_package.clone().unwrap();
+            let (peer_nonces, peer_commitments) =
+                frost::round1::commit(key_package.signing_share(), &mut frost::rand_core::OsRng);
+            nonces.insert(*peer, (peer_nonces, key_package));
+            commitments.insert(*peer, peer_commitments.serialize().unwrap());
+        }
+
+        let node = cluster.nodes.get_mut(&coordinator).unwrap();
+        let (first, late) = (peers[1], peers[2]);
+        state
+            .handle_commitments_response(node, first, sign_id, &commitments[&first])
+            .unwrap();
+        state
+            .handle_commitments_response(node, late, sign_id, &commitments[&late])
+            .unwrap();
+
+        // The package holds exactly the coordinator and the first peer to commit
+        let active = &state.active_signing[&sign_id];
+        let package = active.signing_package.clone().expect("package built");
+        let signers: Vec<_> = package.signing_commitments().keys().copied().collect();
+        let mut expected = vec![
+            node::peer_id_to_identifier(&coordinator),
+            node::peer_id_to_identifier(&first),
+        ];
+        expected.sort();
+        assert_eq!(signers, expected);
+        assert_eq!(active.signature_shares.len(), 1);
+
+        // A share arriving from a peer outside the subset is ignored
+        let (first_nonces, first_key) = &nonces[&first];
+        let share = frost::round2::sign(&package, first_nonces, first_key).unwrap();
+        state
+            .handle_signature_share(node, late, sign_id, &share.serialize())
+            .await
+            .unwrap();
+        assert_eq!(state.active_signing[&sign_id].signature_shares.len(), 1);
+
+        // The second valid share completes the session without waiting for the others
+        state
+            .handle_signature_share(node, first, sign_id, &share.serialize())
+            .await
+            .unwrap();
+        assert!(state.active_signing.is_empty());
+    }
+
+    #[test]
+    fn tweaked_key_packages_sign_for_the_tweaked_group_key() {
+        use bitcoin::secp256k1::{PublicKey, Scalar, Secp256k1};
+
+        let mut rng = frost::rand_core::OsRng;
+        let (shares, pubkey_package) =
+            frost::keys::generate_with_dealer(3, 2, frost::keys::IdentifierList::Default, &mut rng)
+                .unwrap();
+        let tweak = Scalar::from_be_bytes([5u8; 32]).unwrap();
+        let tweaked_pubkeys =
+            SigningState::tweak_public_key_package(&pubkey_package, &tweak).unwrap();
+
+        // The tweaked group key is the group key plus tweak·G
+        let secp = Secp256k1::new();
+        let expected = PublicKey::from_slice(&pubkey_package.verifying_key().serialize().unwrap())
+            .unwrap()
+            .add_exp_tweak(&secp, &tweak)
+            .unwrap();
+        assert_eq!(
+            tweaked_pubkeys.verifying_key().serialize().unwrap(),
+            expected.serialize().to_vec()
+        );
+
+        // Any two signers' tweaked shares combine into a signature under it
+        let message = [7u8; 32];
+        let key_packages: Vec<_> = shares
+            .into_values()
+            .take(2)
+            .map(|share| {
+                let key_package = frost::keys::KeyPackage::try_from(share).unwrap();
+                SigningState::tweak_key_package(&key_package, &tweak).unwrap()
+            })
+            .collect();
+        let round1: Vec<_> = key_packages
+            .iter()
+            .map(|key_package| frost::round1::commit(key_package.signing_share(), &mut rng))
+            .collect();
+        let signing_package = frost::SigningPackage::new(
+            key_packages
+                .iter()
+                .zip(&round1)
+                .map(|(key_package, (_, commitments))| (*key_package.identifier(), *commitments))
+                .collect(),
+            &message,
+        );
+        let signature_shares = key_packages
+            .iter()
+            .zip(&round1)
+            .map(|(key_package, (nonces, _))| {
+                let share = frost::round2::sign(&signing_package, nonces, key_package).unwrap();
+                (*key_package.identifier(), share)
+            })
+            .collect();
+        let signature =
+            frost::aggregate(&signing_package, &signature_shares, &tweaked_pubkeys).unwrap();
+
+        assert!(
+            tweaked_pubkeys
+                .verifying_key()
+                .verify(&message, &signature)
+                .is_ok()
+        );
+        assert!(
+            pubkey_package
+                .verifying_key()
+                .verify(&message, &signature)
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn sign_ids_do_not_collide_after_restart() {
+        use node::handlers::signing::sign_id;
+
+        let mut cluster = MockNodeCluster::new_with_threshold_keys(3, 2).await;
+        cluster.setup().await;
+        cluster.run_n_iterations(1).await;
+
+        let peers = cluster.get_peer_ids();
+        let coordinator = peers[0];
+        let node = cluster.nodes.get_mut(&coordinator).unwrap();
+
+        let mut state = SigningState::new();
+        let mut before = Vec::new();
+        for byte in 1u8..=3 {
+            let sign_id = state
+                .start_signing_session(node, &hex::encode([byte; 32]), None)
+                .await
+                .unwrap()
+                .unwrap();
+            before.push(sign_id);
+        }
+
+        // A restart loses the in-memory sessions, while the chain interface's store survives
+        let mut restarted = SigningState::new();
+        let mut after = Vec::new();
+        for byte in 1u8..=3 {
+            let sign_id = restarted
+                .start_signing_session(node, &hex::encode([byte; 32]), None)
+                .await
+                .unwrap()
+                .unwrap();
+            after.push(sign_id);
+        }
+
+        for id in &after {
+            assert!(!before.contains(id), "sign id {id} was reused");
+            assert_eq!(
+                id >> sign_id::COUNTER_BITS,
+                sign_id::namespace(&coordinator)
+            );
+        }
+        let last_before = before.iter().map(|id| sign_id::counter(*id)).max().unwrap();
+        let first_after = after.iter().map(|id| sign_id::counter(*id)).min().unwrap();
+        assert!(first_after > last_before);
+
+        // Another coordinator starting from the same counter draws from its own namespace
+        let other = cluster.nodes.get_mut(&peers[1]).unwrap();
+        let other_id = other.allocate_sign_id().await.unwrap();
+        assert_eq!(sign_id::counter(other_id), 0);
+        assert!(!before.contains(&other_id) && !after.contains(&other_id));
+    }
+
+    #[tokio::test]
+    async fn concurrent_signing_sessions_complete_independently() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator = *cluster.nodes.keys().next().unwrap();
+        for byte in [1u8, 2u8] {
+            cluster.send_self_request_to_peer(
+                initiator,
+                SelfRequest::StartSigningSession {
+                    hex_message: hex::encode([byte; 32]),
+                },
+            );
+        }
+        cluster.run_n_iterations(1).await;
+
+        let sign_ids: Vec<u64> = {
+            let state = cluster.nodes[&initiator]
+                .handlers
+                .iter()
+                .find_map(|h| h.downcast_ref::<SigningState>())
+                .unwrap();
+            state.active_signing.keys().copied().collect()
+        };
+        assert_eq!(sign_ids.len(), 2, "both sessions should be in flight");
+
+        let mut shares = std::collections::BTreeMap::<u64, usize>::new();
+        for _ in 0..100 {
+            for sender in cluster.senders.values() {
+                for ev in &sender.pending_events {
+                    if let NetworkEvent::MessageEvent((
+                        _,
+                        DirectMessage::SignatureShare { sign_id, .. },
+                    )) = ev
+                    {
+                        *shares.entry(*sign_id).or_default() += 1;
+                    }
+                }
+            }
+            cluster.run_n_iterations(1).await;
+            if cluster
+                .senders
+                .values()
+                .all(|s| s.pending_events.is_empty())
+            {
+                break;
+            }
+        }
+
+        for sign_id in &sign_ids {
+            assert_eq!(
+                shares.get(sign_id),
+                Some(&2),
+                "session {sign_id} did not collect its shares"
+            );
+        }
+        for node in cluster.nodes.values() {
+            let state = node
+                .handlers
+                .iter()
+                .find_map(|h| h.downcast_ref::<SigningState>())
+                .unwrap();
+            assert!(
+                state.active_signing.is_empty(),
+                "node {:?} still has signing sessions",
+                node.peer_id
+            );
+        }
+    }
+
+    fn signing_state(cluster: &MockNodeCluster, peer: libp2p::PeerId) -> &SigningState {
+        cluster.nodes[&peer]
+            .handlers
+            .iter()
+            .find_map(|h| h.downcast_ref::<SigningState>())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn signing_sessions_draw_single_use_pregenerated_nonces() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator = *cluster.nodes.keys().next().unwrap();
+        let self_identifier = node::peer_id_to_identifier(&initiator);
+
+        // An idle tick fills the pool before any session is started.
+        cluster.send_self_request_to_peer(initiator, SelfRequest::MaintenanceTick);
+        cluster.run_n_iterations(1).await;
+
+        let pool = &signing_state(&cluster, initiator).nonce_pool;
+        assert_eq!(pool.len(), DEFAULT_NONCE_POOL_SIZE);
+        let (first_id, first_commitments) = pool.peek().map(|(id, c)| (id, *c)).unwrap();
+
+        let mut session_commitments = Vec::new();
+        for byte in [1u8, 2u8] {
+            cluster.send_self_request_to_peer(
+                initiator,
+                SelfRequest::StartSigningSession {
+                    hex_message: hex::encode([byte; 32]),
+                },
+            );
+            cluster.run_n_iterations(1).await;
+
+            let state = signing_state(&cluster, initiator);
+            let session = state
+                .active_signing
+                .values()
+                .find(|active| active.is_coordinator && active.message == [byte; 32])
+                .unwrap();
+            session_commitments.push(session.commitments[&self_identifier]);
+        }
+
+        let pool = &signing_state(&cluster, initiator).nonce_pool;
+        assert_eq!(
+            session_commitments[0], first_commitments,
+            "the first session should commit with the oldest pooled nonce"
+        );
+        assert!(pool.is_consumed(first_id));
+        assert!(pool.is_consumed(first_id + 1));
+        assert_ne!(
+            session_commitments[0], session_commitments[1],
+            "a pooled nonce must never be reused"
+        );
+        assert_eq!(pool.len(), DEFAULT_NONCE_POOL_SIZE - 2);
+        assert!(
+            pool.peek()
+                .is_some_and(|(_, commitments)| !session_commitments.contains(commitments))
+        );
+    }
+
     fn create_test_wallet() -> TaprootWallet {
         let (events_emitter, _) = tokio::sync::broadcast::channel(100);
         let (deposits_emitter, _) = tokio::sync::broadcast::channel(100);
@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod threshold_scheme_tests {
+    use std::collections::BTreeMap;
+
+    use frost_secp256k1::{
+        self as frost,
+        rand_core::{CryptoRng, RngCore},
+    };
+    use node::utils::threshold_scheme::{Secp256k1Scheme, ThresholdScheme};
+    use sha2::{Digest, Sha256};
+
+    /// SHA-256 counter stream, so the trait and direct runs draw identical randomness.
+    #[derive(Clone)]
+    struct SeededRng {
+        seed: u64,
+        counter: u64,
+    }
+
+    impl SeededRng {
+        const fn new(seed: u64) -> Self {
+            Self { seed, counter: 0 }
+        }
+    }
+
+    impl RngCore for SeededRng {
+        fn next_u32(&mut self) -> u32 {
+            let mut bytes = [0u8; 4];
+            self.fill_bytes(&mut bytes);
+            u32::from_le_bytes(bytes)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut bytes = [0u8; 8];
+            self.fill_bytes(&mut bytes);
+            u64::from_le_bytes(bytes)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(32) {
+                let block = Sha256::new()
+                    .chain_update(self.seed.to_le_bytes())
+                    .chain_update(self.counter.to_le_bytes())
+                    .finalize();
+                self.counter += 1;
+                chunk.copy_from_slice(&block[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), frost::rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for SeededRng {}
+
+    type Keys = (
+        BTreeMap<frost::Identifier, frost::keys::KeyPackage>,
+        frost::keys::PublicKeyPackage,
+    );
+
+    fn ids() -> Vec<frost::Identifier> {
+        (1u16..=3).map(|i| i.try_into().unwrap()).collect()
+    }
+
+    /// Full DKG for three participants, threshold two, through the trait.
+    fn dkg_with_scheme<S>() -> Keys
+    where
+        S: ThresholdScheme<
+                Identifier = frost::Identifier,
+                Round1Package = frost::keys::dkg::round1::Package,
+                Round2Package = frost::keys::dkg::round2::Package,
+                KeyPackage = frost::keys::KeyPackage,
+                PublicKeyPackage = frost::keys::PublicKeyPackage,
+            >,
+        S::Round1SecretPackage: Clone,
+        S::Error: std::fmt::Debug,
+    {
+        let ids = ids();
+        let mut round1_secrets = BTreeMap::new();
+        let mut round1_packages = BTreeMap::new();
+        for (seed, id) in (0u64..).zip(&ids) {
+            let (secret, package) = S::dkg_part1(*id, 3, 2, SeededRng::new(seed)).unwrap();
+            round1_secrets.insert(*id, secret);
+            round1_packages.insert(*id, package);
+        }
+
+        let received_round1 = |me: &frost::Identifier| -> BTreeMap<_, _> {
+            round1_packages
+                .iter()
+                .filter(|(id, _)| *id != me)
+                .map(|(id, package)| (*id, package.clone()))
+                .collect()
+        };
+
+        let mut round2_secrets = BTreeMap::new();
+        let mut round2_inbox: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        for id in &ids {
+            let (secret, packages) =
+                S::dkg_part2(round1_secrets[id].clone(), &received_round1(id)).unwrap();
+            round2_secrets.insert(*id, secret);
+            for (to, package) in packages {
+                round2_inbox.entry(to).or_default().insert(*id, package);
+            }
+        }
+
+        let mut key_packages = BTreeMap::new();
+        let mut public_key_package = None;
+        for id in &ids {
+            let (key_package, public) =
+                S::dkg_part3(&round2_secrets[id], &received_round1(id), &round2_inbox[id]).unwrap();
+            key_packages.insert(*id, key_package);
+            public_key_package = Some(public);
+        }
+        (key_packages, public_key_package.unwrap())
+    }
+
+    /// The same DKG calling `frost_secp256k1` directly.
+    fn dkg_direct() -> Keys {
+        let ids = ids();
+        let mut round1_secrets = BTreeMap::new();
+        let mut round1_packages = BTreeMap::new();
+        for (seed, id) in (0u64..).zip(&ids) {
+            let (secret, package) =
+                frost::keys::dkg::part1(*id, 3, 2, SeededRng::new(seed)).unwrap();
+            round1_secrets.insert(*id, secret);
+            round1_packages.insert(*id, package);
+        }
+
+        let received_round1 = |me: &frost::Identifier| -> BTreeMap<_, _> {
+            round1_packages
+                .iter()
+                .filter(|(id, _)| *id != me)
+                .map(|(id, package)| (*id, package.clone()))
+                .collect()
+        };
+
+        let mut round2_secrets = BTreeMap::new();
+        let mut round2_inbox: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        for id in &ids {
+            let (secret, packages) =
+                frost::keys::dkg::part2(round1_secrets[id].clone(), &received_round1(id)).unwrap();
+            round2_secrets.insert(*id, secret);
+            for (to, package) in packages {
+                round2_inbox.entry(to).or_default().insert(*id, package);
+            }
+        }
+
+        let mut key_packages = BTreeMap::new();
+        let mut public_key_package = None;
+        for id in &ids {
+            let (key_package, public) = frost::keys::dkg::part3(
+                &round2_secrets[id],
+                &received_round1(id),
+                &round2_inbox[id],
+            )
+            .unwrap();
+            key_packages.insert(*id, key_package);
+            public_key_package = Some(public);
+        }
+        (key_packages, public_key_package.unwrap())
+    }
+
+    #[test]
+    fn secp256k1_scheme_matches_direct_frost_calls() {
+        let (scheme_keys, scheme_public) = dkg_with_scheme::<Secp256k1Scheme>();
+        let (direct_keys, direct_public) = dkg_direct();
+
+        assert_eq!(
+            scheme_public.serialize().unwrap(),
+            direct_public.serialize().unwrap()
+        );
+        for (id, key_package) in &scheme_keys {
+            assert_eq!(
+                key_package.serialize().unwrap(),
+                direct_keys[id].serialize().unwrap()
+            );
+        }
+
+        // Sign with two of the three participants through the trait and directly
+        let message = b"threshold scheme abstraction";
+        let signers = &ids()[..2];
+
+        let mut scheme_nonces = BTreeMap::new();
+        let mut direct_nonces = BTreeMap::new();
+        let mut commitments = BTreeMap::new();
+        for (seed, id) in (100u64..).zip(signers) {
+            let (nonces, scheme_commitments) =
+                Secp256k1Scheme::commit(&scheme_keys[id], &mut SeededRng::new(seed));
+            let (direct, direct_commitments) =
+                frost::round1::commit(direct_keys[id].signing_share(), &mut SeededRng::new(seed));
+            assert_eq!(scheme_commitments, direct_commitments);
+            scheme_nonces.insert(*id, nonces);
+            direct_nonces.insert(*id, direct);
+            commitments.insert(*id, scheme_commitments);
+        }
+        let signing_package = frost::SigningPackage::new(commitments, message);
+
+        let mut scheme_shares = BTreeMap::new();
+        let mut direct_shares = BTreeMap::new();
+        for id in signers {
+            let share =
+                Secp256k1Scheme::sign(&signing_package, &scheme_nonces[id], &scheme_keys[id])
+                    .unwrap();
+            let direct =
+                frost::round2::sign(&signing_package, &direct_nonces[id], &direct_keys[id])
+                    .unwrap();
+            assert_eq!(share, direct);
+            scheme_shares.insert(*id, share);
+            direct_shares.insert(*id, direct);
+        }
+
+        let signature =
+            Secp256k1Scheme::aggregate(&signing_package, &scheme_shares, &scheme_public).unwrap();
+        let direct_signature =
+            frost::aggregate(&signing_package, &direct_shares, &direct_public).unwrap();
+        assert_eq!(signature, direct_signature);
+        scheme_public
+            .verifying_key()
+            .verify(message, &signature)
+            .expect("group signature must verify");
+    }
+}
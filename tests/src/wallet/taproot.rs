@@ -1,16 +1,24 @@
 #[cfg(test)]
 mod taproot_wallet_tests {
     use crate::mocks::pubkey::random_public_key;
+    use bitcoin::hashes::Hash;
     use bitcoin::secp256k1::Scalar;
-    use bitcoin::{Amount, Network};
+    use bitcoin::{Amount, Network, OutPoint, Txid};
     use node::wallet::TaprootWallet;
+    use node::wallet::TrackedUtxo;
     use node::wallet::Wallet;
     use oracle::mock::MockOracle;
+    use oracle::oracle::Oracle;
+    use oracle::timeout::TimeoutOracle;
     use protocol::block::{Block, BlockBody, BlockHeader};
     use protocol::transaction::{Transaction, TransactionType};
     use serde_json::json;
+    use std::str::FromStr;
+    use std::time::Duration;
     use tokio::sync::broadcast;
+    use types::errors::NodeError;
     use types::network::network_event::NetworkEvent;
+    use types::utxo::Utxo;
 
     fn create_test_wallet() -> TaprootWallet {
         let (tx_channel, _) = broadcast::channel::<NetworkEvent>(100);
@@ -304,4 +312,1364 @@ mod taproot_wallet_tests {
             assert!(bitcoin_tx.output[1].value.to_sat() < 40000); // Less than low fee case
         }
     }
+
+    #[tokio::test]
+    async fn test_create_spend_splits_change_across_addresses() {
+        let mut wallet = create_test_wallet().with_change_outputs(3);
+        let pubkey = random_public_key();
+
+        let addresses: Vec<_> = (1u8..=3)
+            .map(|i| wallet.generate_new_address(pubkey, Scalar::from_be_bytes([i; 32]).unwrap()))
+            .collect();
+
+        wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[5u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(100_000),
+                script_pubkey: addresses[0].script_pubkey(),
+            },
+            address: addresses[0].clone(),
+        });
+
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+
+        let (tx, _) = wallet
+            .create_spend(40_000, 1_000, &recipient, false)
+            .expect("create_spend failed");
+
+        assert_eq!(tx.output[0].value, Amount::from_sat(40_000));
+
+        let change = &tx.output[1..];
+        assert_eq!(change.len(), 3);
+        assert_eq!(
+            change.iter().map(|o| o.value.to_sat()).sum::<u64>(),
+            100_000 - 40_000 - 1_000
+        );
+        assert!(change.iter().all(|o| o.value.to_sat() > 546));
+        assert!(change.iter().all(|o| {
+            addresses
+                .iter()
+                .any(|a| a.script_pubkey() == o.script_pubkey)
+        }));
+
+        // Every change output is tracked as a new wallet UTXO
+        assert_eq!(wallet.utxos.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_spendable_balance_excludes_locked_and_unconfirmed_utxos() {
+        let mut wallet = create_test_wallet();
+        let pubkey = random_public_key();
+        let address =
+            wallet.generate_new_address(pubkey, Scalar::from_be_bytes([7u8; 32]).unwrap());
+
+        for (i, value) in [(1u8, 10_000), (2, 20_000), (3, 40_000)] {
+            wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[i; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(value),
+                    script_pubkey: address.script_pubkey(),
+                },
+                address: address.clone(),
+            });
+        }
+        assert_eq!(wallet.spendable_balance(), 70_000);
+
+        let locked = wallet.utxos[2].utxo.outpoint;
+        assert!(wallet.lock_utxo(locked));
+        assert_eq!(wallet.spendable_balance(), 30_000);
+
+        // Spending the 20k UTXO leaves an unconfirmed change output behind
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+        let (tx, _) = wallet
+            .create_spend(15_000, 1_000, &recipient, false)
+            .expect("create_spend failed");
+        assert!(tx.input.iter().all(|i| i.previous_output != locked));
+        assert_eq!(wallet.unconfirmed_utxos.len(), 1);
+        assert_eq!(wallet.spendable_balance(), 10_000);
+
+        assert!(wallet.unlock_utxo(&locked));
+        assert_eq!(wallet.spendable_balance(), 50_000);
+    }
+
+    #[tokio::test]
+    async fn test_utxo_spend_status_links_consumed_outpoint_to_spending_txid() {
+        use node::wallet::UtxoStatus;
+
+        let mut wallet = create_test_wallet();
+        let pubkey = random_public_key();
+        let address =
+            wallet.generate_new_address(pubkey, Scalar::from_be_bytes([7u8; 32]).unwrap());
+
+        for (i, value) in [(1u8, 10_000), (2, 40_000)] {
+            wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[i; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(value),
+                    script_pubkey: address.script_pubkey(),
+                },
+                address: address.clone(),
+            });
+        }
+        let (spent, kept) = (wallet.utxos[0].utxo.outpoint, wallet.utxos[1].utxo.outpoint);
+        let unknown = OutPoint {
+            txid: Txid::from_slice(&[9u8; 32]).unwrap(),
+            vout: 0,
+        };
+        assert_eq!(wallet.get_utxo_spend_status(spent), UtxoStatus::Unspent);
+        assert_eq!(wallet.get_utxo_spend_status(unknown), UtxoStatus::Unknown);
+
+        assert!(wallet.lock_utxo(kept));
+        assert_eq!(wallet.get_utxo_spend_status(kept), UtxoStatus::Locked);
+
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+        let (tx, _) = wallet
+            .create_spend_from(&[spent], &recipient, 1_000)
+            .expect("create_spend_from failed");
+        let txid = tx.compute_txid();
+
+        assert_eq!(
+            wallet.get_utxo_spend_status(spent),
+            UtxoStatus::Spent { txid }
+        );
+        assert_eq!(wallet.get_utxo_spend_status(kept), UtxoStatus::Locked);
+
+        // A dry run consumes nothing.
+        assert!(wallet.unlock_utxo(&kept));
+        wallet
+            .create_spend(5_000, 1_000, &recipient, true)
+            .expect("dry run failed");
+        assert_eq!(wallet.get_utxo_spend_status(kept), UtxoStatus::Unspent);
+    }
+
+    #[tokio::test]
+    async fn test_released_spend_returns_its_inputs_and_drops_its_change() {
+        use node::wallet::UtxoStatus;
+
+        let mut wallet = create_test_wallet();
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([7u8; 32]).unwrap(),
+        );
+        wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[1u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(50_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            address,
+        });
+        let funding = wallet.utxos[0].utxo.outpoint;
+        let balance = wallet.spendable_balance();
+
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+        let (tx, _) = wallet
+            .create_spend(10_000, 1_000, &recipient, false)
+            .expect("create_spend failed");
+        let txid = tx.compute_txid();
+        assert_eq!(
+            wallet.get_utxo_spend_status(funding),
+            UtxoStatus::Spent { txid }
+        );
+        assert!(wallet.utxos.iter().any(|u| u.utxo.outpoint.txid == txid));
+
+        // The spend never reached the network, so its input can be selected again.
+        assert!(wallet.release_spend(txid));
+        assert_eq!(wallet.get_utxo_spend_status(funding), UtxoStatus::Unspent);
+        assert!(wallet.spent_utxos.is_empty());
+        assert!(wallet.utxos.iter().all(|u| u.utxo.outpoint.txid != txid));
+        assert_eq!(wallet.spendable_balance(), balance);
+
+        assert!(!wallet.release_spend(txid));
+    }
+
+    #[tokio::test]
+    async fn test_create_spend_from_uses_exactly_the_given_outpoints() {
+        let mut wallet = create_test_wallet();
+        let pubkey = random_public_key();
+        let address =
+            wallet.generate_new_address(pubkey, Scalar::from_be_bytes([7u8; 32]).unwrap());
+
+        for (i, value) in [(1u8, 10_000), (2, 20_000), (3, 40_000), (4, 80_000)] {
+            wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[i; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(value),
+                    script_pubkey: address.script_pubkey(),
+                },
+                address: address.clone(),
+            });
+        }
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+
+        // Coin selection would prefer the 80k UTXO; coin control spends the two smallest
+        let chosen = [wallet.utxos[1].utxo.outpoint, wallet.utxos[0].utxo.outpoint];
+        let (tx, _) = wallet
+            .create_spend_from(&chosen, &recipient, 1_000)
+            .expect("create_spend_from failed");
+
+        let inputs: Vec<_> = tx.input.iter().map(|i| i.previous_output).collect();
+        assert_eq!(inputs, chosen);
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].value, Amount::from_sat(29_000));
+        assert_eq!(wallet.spendable_balance(), 120_000);
+
+        // Already-spent, unknown and locked outpoints are rejected
+        assert!(
+            wallet
+                .create_spend_from(&chosen, &recipient, 1_000)
+                .is_err()
+        );
+        let unknown = OutPoint {
+            txid: Txid::from_slice(&[9u8; 32]).unwrap(),
+            vout: 0,
+        };
+        assert!(
+            wallet
+                .create_spend_from(&[unknown], &recipient, 1_000)
+                .is_err()
+        );
+        let locked = wallet.utxos[0].utxo.outpoint;
+        assert!(wallet.lock_utxo(locked));
+        assert!(
+            wallet
+                .create_spend_from(&[locked], &recipient, 1_000)
+                .is_err()
+        );
+        assert_eq!(wallet.utxos.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_all_spends_every_utxo_into_one_output() {
+        let mut wallet = create_test_wallet();
+        let pubkey = random_public_key();
+        let address =
+            wallet.generate_new_address(pubkey, Scalar::from_be_bytes([7u8; 32]).unwrap());
+
+        for (i, value) in [(1u8, 10_000), (2, 20_000), (3, 40_000), (4, 80_000)] {
+            wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[i; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(value),
+                    script_pubkey: address.script_pubkey(),
+                },
+                address: address.clone(),
+            });
+        }
+        let outpoints: std::collections::HashSet<_> =
+            wallet.utxos.iter().map(|u| u.utxo.outpoint).collect();
+        let new_vault = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([8u8; 32]).unwrap(),
+        );
+
+        let (tx, _) = wallet
+            .sweep_all(&new_vault, 2_000)
+            .expect("sweep_all failed");
+
+        let inputs: std::collections::HashSet<_> =
+            tx.input.iter().map(|i| i.previous_output).collect();
+        assert_eq!(inputs, outpoints);
+        assert_eq!(tx.input.len(), 4);
+        assert_eq!(tx.output.len(), 1);
+        assert_eq!(tx.output[0].value, Amount::from_sat(148_000));
+        assert_eq!(tx.output[0].script_pubkey, new_vault.script_pubkey());
+        assert_eq!(wallet.spendable_balance(), 0);
+
+        // Nothing is left to sweep a second time.
+        assert!(wallet.sweep_all(&new_vault, 2_000).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_spend_with_height_locktime_and_version() {
+        use bitcoin::absolute::LockTime;
+        use bitcoin::transaction::Version;
+        use node::wallet::SpendOptions;
+
+        let mut wallet = create_test_wallet();
+        let pubkey = random_public_key();
+        let address =
+            wallet.generate_new_address(pubkey, Scalar::from_be_bytes([7u8; 32]).unwrap());
+        wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[1u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(100_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            address: address.clone(),
+        });
+        wallet.tip_height = 850_000;
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+
+        let lock_time = LockTime::from_height(850_144).unwrap();
+        let options = SpendOptions::default()
+            .with_version(Version::ONE)
+            .with_lock_time(lock_time);
+        let (tx, _) = wallet
+            .create_spend_with_options(40_000, 1_000, &recipient, true, options)
+            .expect("create_spend_with_options failed");
+        assert_eq!(tx.lock_time, lock_time);
+        assert!(tx.lock_time.is_block_height());
+        assert_eq!(tx.version, Version::ONE);
+        // The locktime only binds if an input opts out of finality
+        assert!(tx.input.iter().any(|input| !input.sequence.is_final()));
+
+        // Defaults are unchanged
+        let (tx, _) = wallet
+            .create_spend(40_000, 1_000, &recipient, true)
+            .expect("create_spend failed");
+        assert_eq!(tx.lock_time, LockTime::ZERO);
+        assert_eq!(tx.version, Version::TWO);
+
+        let too_far = SpendOptions::default()
+            .with_lock_time(LockTime::from_height(850_000 + 100_000).unwrap());
+        assert!(
+            wallet
+                .create_spend_with_options(40_000, 1_000, &recipient, true, too_far)
+                .is_err()
+        );
+        let non_standard = SpendOptions::default().with_version(Version(7));
+        assert!(
+            wallet
+                .create_spend_with_options(40_000, 1_000, &recipient, true, non_standard)
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_negative_effective_value_utxo_is_not_selected_at_high_feerate() {
+        use node::wallet::SpendOptions;
+
+        let mut wallet = create_test_wallet();
+        let pubkey = random_public_key();
+        let address =
+            wallet.generate_new_address(pubkey, Scalar::from_be_bytes([5u8; 32]).unwrap());
+
+        let tracked = |seed: u8, value: u64| TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[seed; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(value),
+                script_pubkey: address.script_pubkey(),
+            },
+            address: address.clone(),
+        };
+        let large = tracked(1, 20_000);
+        let dust = tracked(2, 1_000);
+        wallet.utxos = vec![large.clone(), dust.clone()];
+
+        // At 100 sat/vB the dust UTXO costs more to spend than it carries
+        assert!(TaprootWallet::effective_value(&dust, 100) < 0);
+        assert!(TaprootWallet::effective_value(&dust, 1) > 0);
+        assert!(TaprootWallet::effective_value(&large, 100) > 0);
+
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+
+        // 20_500 sat is only reachable by adding the dust UTXO to the large one
+        let high_feerate = SpendOptions::default().with_feerate(100);
+        assert!(
+            wallet
+                .create_spend_with_options(19_500, 1_000, &recipient, true, high_feerate)
+                .is_err()
+        );
+
+        let low_feerate = SpendOptions::default().with_feerate(1);
+        let (tx, _) = wallet
+            .create_spend_with_options(19_500, 1_000, &recipient, true, low_feerate)
+            .unwrap();
+        assert!(
+            tx.input
+                .iter()
+                .any(|input| input.previous_output == dust.utxo.outpoint)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_coinbase_utxo_is_not_selected_until_mature() {
+        let mut wallet = create_test_wallet();
+        let pubkey = random_public_key();
+        let address =
+            wallet.generate_new_address(pubkey, Scalar::from_be_bytes([9u8; 32]).unwrap());
+
+        let outpoint = OutPoint {
+            txid: Txid::from_slice(&[8u8; 32]).unwrap(),
+            vout: 0,
+        };
+        wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint,
+                value: Amount::from_sat(50_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            address: address.clone(),
+        });
+        wallet.mark_coinbase(outpoint, 1_000);
+
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+
+        // 99 confirmations: one short of maturity
+        wallet.tip_height = 1_098;
+        assert_eq!(wallet.spendable_balance(), 0);
+        assert!(wallet.create_spend(10_000, 500, &recipient, true).is_err());
+
+        wallet.tip_height = 1_099;
+        assert_eq!(wallet.spendable_balance(), 50_000);
+        let (tx, _) = wallet
+            .create_spend(10_000, 500, &recipient, true)
+            .expect("mature coinbase output should be spendable");
+        assert_eq!(tx.input[0].previous_output, outpoint);
+    }
+
+    #[tokio::test]
+    async fn test_coinbase_utxo_matures_from_the_height_that_mined_it() {
+        use bitcoin::{ScriptBuf, Sequence, TxIn, TxOut};
+
+        let (tx_channel, _) = broadcast::channel::<NetworkEvent>(100);
+        let oracle = MockOracle::new(tx_channel, None);
+        let mut wallet = TaprootWallet::new(Box::new(oracle.clone()), Vec::new(), Network::Testnet);
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([9u8; 32]).unwrap(),
+        );
+
+        let coinbase = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::from_bytes(vec![0x02, 0xe8, 0x03]),
+                sequence: Sequence::MAX,
+                witness: bitcoin::Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(50_000),
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+        let outpoint = OutPoint {
+            txid: coinbase.compute_txid(),
+            vout: 0,
+        };
+        oracle.add_known_transaction(coinbase.clone(), Some(1_000));
+        oracle.set_utxos(vec![Utxo {
+            outpoint,
+            value: Amount::from_sat(50_000),
+            script_pubkey: address.script_pubkey(),
+        }]);
+        oracle.set_block_height(1_050);
+
+        // Seen by the monitor first: the mining height is not known yet.
+        wallet.tip_height = 1_050;
+        wallet.ingest_external_tx(&coinbase).unwrap();
+        assert_eq!(wallet.coinbase_utxos.get(&outpoint), Some(&1_050));
+
+        wallet.refresh_utxos(None).await.unwrap();
+        assert_eq!(wallet.coinbase_utxos.get(&outpoint), Some(&1_000));
+        assert_eq!(wallet.spendable_balance(), 0);
+
+        oracle.set_block_height(1_099);
+        wallet.refresh_utxos(None).await.unwrap();
+        assert_eq!(wallet.spendable_balance(), 50_000);
+
+        // Found by a refresh alone, without the monitor ever ingesting it.
+        let mut fresh = TaprootWallet::new(Box::new(oracle.clone()), Vec::new(), Network::Testnet);
+        fresh.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([9u8; 32]).unwrap(),
+        );
+        oracle.set_block_height(1_050);
+        fresh.refresh_utxos(None).await.unwrap();
+        assert_eq!(fresh.coinbase_utxos.get(&outpoint), Some(&1_000));
+        assert_eq!(fresh.spendable_balance(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refresh_and_spend_neither_reselects_nor_loses_utxos() {
+        let (tx_channel, _) = broadcast::channel::<NetworkEvent>(100);
+        let oracle = MockOracle::new(tx_channel, None);
+        let mut wallet = TaprootWallet::new(Box::new(oracle.clone()), Vec::new(), Network::Testnet);
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([4u8; 32]).unwrap(),
+        );
+
+        let utxos: Vec<Utxo> = [(1u8, 10_000), (2, 20_000), (3, 40_000)]
+            .into_iter()
+            .map(|(i, value)| Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[i; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(value),
+                script_pubkey: address.script_pubkey(),
+            })
+            .collect();
+        oracle.set_utxos(utxos.clone());
+        wallet.refresh_utxos(Some(false)).await.unwrap();
+        assert_eq!(wallet.utxos.len(), 3);
+
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+        let wallet = std::sync::Arc::new(tokio::sync::Mutex::new(wallet));
+
+        let refresh = tokio::spawn({
+            let wallet = wallet.clone();
+            async move { wallet.lock().await.refresh_utxos(Some(false)).await }
+        });
+        let spend = tokio::spawn({
+            let wallet = wallet.clone();
+            let recipient = recipient.clone();
+            async move {
+                wallet
+                    .lock()
+                    .await
+                    .create_spend(30_000, 1_000, &recipient, false)
+            }
+        });
+        refresh.await.unwrap().unwrap();
+        let (tx, _) = spend.await.unwrap().unwrap();
+        let spent = tx.input[0].previous_output;
+        assert_eq!(spent, utxos[2].outpoint);
+
+        // Whichever ran first, the spent input is gone and the others are tracked exactly once
+        let mut wallet = wallet.lock().await;
+        wallet.refresh_utxos(Some(false)).await.unwrap();
+        let tracked: Vec<_> = wallet.utxos.iter().map(|u| u.utxo.outpoint).collect();
+        assert_eq!(tracked, vec![utxos[0].outpoint, utxos[1].outpoint]);
+
+        let (second, _) = wallet
+            .create_spend(25_000, 1_000, &recipient, true)
+            .expect("remaining UTXOs should cover the spend");
+        assert!(second.input.iter().all(|i| i.previous_output != spent));
+
+        // Once the spend confirms the oracle stops reporting its input
+        oracle.set_utxos(utxos[..2].to_vec());
+        wallet.refresh_utxos(Some(false)).await.unwrap();
+        assert!(wallet.spent_utxos.is_empty());
+        assert_eq!(wallet.utxos.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_slow_oracle_times_out_refresh_and_fee_queries() {
+        let (tx_channel, _) = broadcast::channel::<NetworkEvent>(100);
+        let slow = MockOracle::new(tx_channel, None);
+        slow.set_response_delay(Duration::from_secs(30));
+        let oracle = TimeoutOracle::new(Box::new(slow), Duration::from_millis(50));
+
+        let mut wallet = TaprootWallet::new(Box::new(oracle.clone()), Vec::new(), Network::Testnet);
+        wallet.generate_new_address(random_public_key(), Scalar::ZERO);
+
+        // Neither call may wait for the 30s backend
+        let refresh = tokio::time::timeout(Duration::from_secs(5), wallet.refresh_utxos(None))
+            .await
+            .expect("refresh blocked on the oracle");
+        assert!(matches!(
+            refresh,
+            Err(NodeError::OracleTimeout { ref operation, timeout_ms: 50 })
+                if operation == "refresh_utxos"
+        ));
+
+        let fee = tokio::time::timeout(Duration::from_secs(5), oracle.get_current_fee_per_vb(None))
+            .await
+            .expect("fee estimate blocked on the oracle");
+        assert!(matches!(
+            fee,
+            Err(NodeError::OracleTimeout { ref operation, .. })
+                if operation == "get_current_fee_per_vb"
+        ));
+    }
+
+    #[test]
+    fn test_input_sighashes_match_per_input_computation() {
+        use bitcoin::sighash::{Prevouts, SighashCache};
+        use bitcoin::{EcdsaSighashType, ScriptBuf, Sequence, TapSighashType, TxIn, TxOut};
+
+        let mut wallet = create_test_wallet();
+        let mut prevouts: Vec<TxOut> = (1u8..=3)
+            .map(|i| TxOut {
+                value: Amount::from_sat(10_000 * u64::from(i)),
+                script_pubkey: wallet
+                    .generate_new_address(
+                        random_public_key(),
+                        Scalar::from_be_bytes([i; 32]).unwrap(),
+                    )
+                    .script_pubkey(),
+            })
+            .collect();
+        let p2wpkh = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+        prevouts.push(TxOut {
+            value: Amount::from_sat(5_000),
+            script_pubkey: p2wpkh.script_pubkey(),
+        });
+
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: (0u8..4)
+                .map(|i| TxIn {
+                    previous_output: OutPoint {
+                        txid: Txid::from_slice(&[i + 1; 32]).unwrap(),
+                        vout: u32::from(i),
+                    },
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ZERO,
+                    witness: bitcoin::Witness::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(60_000),
+                script_pubkey: p2wpkh.script_pubkey(),
+            }],
+        };
+
+        let cached = wallet.input_sighashes(&tx, &prevouts).unwrap();
+
+        // Naive: a fresh cache per input, recomputing every shared hash
+        let naive: Vec<[u8; 32]> = (0..tx.input.len())
+            .map(|index| {
+                let mut cache = SighashCache::new(&tx);
+                if index < 3 {
+                    cache
+                        .taproot_key_spend_signature_hash(
+                            index,
+                            &Prevouts::All(&prevouts),
+                            TapSighashType::All,
+                        )
+                        .unwrap()
+                        .to_byte_array()
+                } else {
+                    cache
+                        .p2wpkh_signature_hash(
+                            index,
+                            &prevouts[index].script_pubkey,
+                            prevouts[index].value,
+                            EcdsaSighashType::All,
+                        )
+                        .unwrap()
+                        .to_byte_array()
+                }
+            })
+            .collect();
+
+        assert_eq!(cached, naive);
+        assert!(wallet.input_sighashes(&tx, &prevouts[..3]).is_err());
+    }
+
+    #[test]
+    fn test_sign_covers_every_input_of_a_known_spend() {
+        use bitcoin::secp256k1::{Message, Secp256k1, SecretKey, ecdsa::Signature};
+        use bitcoin::{ScriptBuf, Sequence, TxIn, TxOut};
+
+        let secp = Secp256k1::new();
+        let private_key =
+            bitcoin::PrivateKey::new(SecretKey::from_slice(&[7u8; 32]).unwrap(), Network::Testnet);
+        let pubkey = bitcoin::CompressedPublicKey::from_private_key(&secp, &private_key).unwrap();
+        let script_pubkey = ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash());
+
+        let prevouts: Vec<TxOut> = (1u64..=3)
+            .map(|i| TxOut {
+                value: Amount::from_sat(10_000 * i),
+                script_pubkey: script_pubkey.clone(),
+            })
+            .collect();
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: (0u8..3)
+                .map(|i| TxIn {
+                    previous_output: OutPoint {
+                        txid: Txid::from_slice(&[i + 1; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::ZERO,
+                    witness: bitcoin::Witness::new(),
+                })
+                .collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(59_000),
+                script_pubkey: script_pubkey.clone(),
+            }],
+        };
+
+        let mut wallet = create_test_wallet();
+        wallet
+            .wallet_transactions
+            .insert(tx.compute_txid(), (tx.clone(), prevouts.clone()));
+        let sighashes = wallet.input_sighashes(&tx, &prevouts).unwrap();
+
+        let signed = wallet.sign(&tx, &private_key, sighashes[0]);
+
+        for (input, sighash) in signed.input.iter().zip(sighashes) {
+            let der = input.witness.nth(0).expect("every input is signed");
+            let signature = Signature::from_der(&der[..der.len() - 1]).unwrap();
+            secp.verify_ecdsa(&Message::from_digest(sighash), &signature, &pubkey.0)
+                .expect("signature commits to the input's own sighash");
+        }
+    }
+    #[tokio::test]
+    async fn test_p2tr_fee_estimate_applies_witness_discount() {
+        use node::wallet::SpendOptions;
+
+        let mut wallet = create_test_wallet();
+        let pubkey = random_public_key();
+        let address =
+            wallet.generate_new_address(pubkey, Scalar::from_be_bytes([3u8; 32]).unwrap());
+        let prevout = bitcoin::TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: address.script_pubkey(),
+        };
+
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_slice(&[4u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                ..Default::default()
+            }],
+            output: vec![bitcoin::TxOut {
+                value: Amount::from_sat(90_000),
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+
+        // Base: version 4 + input count 1 + input 41 + output count 1 + P2TR output 43 +
+        // locktime 4 = 94 bytes. Witness: marker and flag 2 + item count 1 + length 1 +
+        // signature 65 = 69 units. (94 * 4 + 69) / 4 = 111.25 vB.
+        let expected_vbytes = (94.0 * 4.0 + 69.0) / 4.0;
+        let weight = TaprootWallet::estimated_weight(&tx, std::slice::from_ref(&prevout)).unwrap();
+        assert_eq!(weight.to_wu(), 94 * 4 + 69);
+
+        let feerate = 20;
+        let fee =
+            TaprootWallet::estimate_fee_sat(&tx, std::slice::from_ref(&prevout), feerate).unwrap();
+        #[allow(clippy::cast_precision_loss)]
+        let deviation = (fee as f64 - expected_vbytes * feerate as f64).abs();
+        assert!(
+            deviation <= feerate as f64,
+            "fee {fee} too far from formula"
+        );
+
+        // A flat byte count, counting the witness at full weight, would charge far more.
+        let flat_bytes = 94 + 69;
+        assert!(fee < flat_bytes * feerate);
+
+        // The wallet charges that same weight-based fee when spending at a feerate.
+        wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: tx.input[0].previous_output,
+                value: prevout.value,
+                script_pubkey: prevout.script_pubkey.clone(),
+            },
+            address: address.clone(),
+        });
+        let (spend, _) = wallet
+            .create_spend_with_options(
+                40_000,
+                0,
+                &address,
+                true,
+                SpendOptions::default().with_feerate(feerate),
+            )
+            .unwrap();
+        let paid = 100_000 - spend.output.iter().map(|o| o.value.to_sat()).sum::<u64>();
+        let expected =
+            TaprootWallet::estimate_fee_sat(&spend, std::slice::from_ref(&prevout), feerate)
+                .unwrap();
+        assert_eq!(paid, expected);
+    }
+
+    #[tokio::test]
+    async fn test_utxo_cap_per_address_ignores_dust_but_keeps_larger_utxos() {
+        let (tx_channel, _) = broadcast::channel::<NetworkEvent>(100);
+        let oracle = MockOracle::new(tx_channel, None);
+        let mut wallet = TaprootWallet::new(Box::new(oracle.clone()), Vec::new(), Network::Testnet)
+            .with_max_utxos_per_address(3);
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([5u8; 32]).unwrap(),
+        );
+
+        let values = [600, 600, 50_000, 600, 600, 80_000];
+        let utxos: Vec<Utxo> = (1u8..)
+            .zip(values)
+            .map(|(i, value)| Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[i; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(value),
+                script_pubkey: address.script_pubkey(),
+            })
+            .collect();
+        oracle.set_utxos(utxos);
+        wallet.refresh_utxos(Some(false)).await.unwrap();
+
+        // Dust past the cap is ignored; both larger UTXOs are tracked regardless.
+        let tracked: Vec<u64> = wallet.utxos.iter().map(|u| u.utxo.value.to_sat()).collect();
+        assert_eq!(tracked, vec![600, 600, 50_000, 80_000]);
+
+        // The same holds for outputs ingested from an observed transaction.
+        let tx = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: [700, 30_000]
+                .into_iter()
+                .map(|value| bitcoin::TxOut {
+                    value: Amount::from_sat(value),
+                    script_pubkey: address.script_pubkey(),
+                })
+                .collect(),
+        };
+        wallet.ingest_external_tx(&tx).unwrap();
+        let tracked: Vec<u64> = wallet.utxos.iter().map(|u| u.utxo.value.to_sat()).collect();
+        assert_eq!(tracked, vec![600, 600, 50_000, 80_000, 30_000]);
+    }
+
+    #[tokio::test]
+    async fn test_ingested_p2tr_deposit_records_key_path_spend_metadata() {
+        use bitcoin::secp256k1::Secp256k1;
+        use bitcoin::sighash::{Prevouts, SighashCache};
+        use node::wallet::SpendPath;
+
+        let mut wallet = create_test_wallet();
+        let group_key = random_public_key();
+        let tweak = Scalar::from_be_bytes([9u8; 32]).unwrap();
+        let deposit_address = wallet.generate_new_address(group_key, tweak);
+        let legacy_address = bitcoin::Address::p2pkh(random_public_key(), Network::Testnet);
+        wallet.add_address(legacy_address.clone());
+
+        let deposit = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![
+                bitcoin::TxOut {
+                    value: Amount::from_sat(50_000),
+                    script_pubkey: deposit_address.script_pubkey(),
+                },
+                bitcoin::TxOut {
+                    value: Amount::from_sat(20_000),
+                    script_pubkey: legacy_address.script_pubkey(),
+                },
+            ],
+        };
+        wallet.ingest_external_tx(&deposit).unwrap();
+
+        // The legacy output cannot be signed for by the group key, so it is not tracked
+        assert_eq!(wallet.utxos.len(), 1);
+        let outpoint = wallet.utxos[0].utxo.outpoint;
+        assert_eq!(outpoint.vout, 0);
+
+        let secp = Secp256k1::new();
+        let (output_key, _) = group_key
+            .inner
+            .x_only_public_key()
+            .0
+            .add_tweak(&secp, &tweak)
+            .unwrap();
+        assert_eq!(
+            wallet.spend_path(&outpoint),
+            Some(SpendPath::TaprootKeyPath {
+                output_key,
+                tweak: Some(tweak),
+            })
+        );
+
+        // A spend of the deposit asks the group key for a BIP 341 key-path sighash
+        let recipient = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([3u8; 32]).unwrap(),
+        );
+        let (spend, sighash) = wallet.create_spend(10_000, 500, &recipient, true).unwrap();
+        let prevouts = [deposit.output[0].clone()];
+        let expected = SighashCache::new(&spend)
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&prevouts),
+                bitcoin::TapSighashType::All,
+            )
+            .unwrap();
+        assert_eq!(sighash, expected.to_byte_array());
+
+        // Once spent, the deposit is signed for by the group key plus its tweak
+        let (spend, _) = wallet.create_spend(10_000, 500, &recipient, false).unwrap();
+        assert_eq!(wallet.signing_tweak(&spend), Some(tweak));
+    }
+
+    #[tokio::test]
+    async fn test_suggest_fee_bump_replaces_rbf_signalled_spend() {
+        use node::wallet::FeeBumpPlan;
+
+        let mut wallet = create_test_wallet();
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([6u8; 32]).unwrap(),
+        );
+        let prevout = bitcoin::TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: address.script_pubkey(),
+        };
+        wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[4u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: prevout.value,
+                script_pubkey: prevout.script_pubkey.clone(),
+            },
+            address: address.clone(),
+        });
+        let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            .unwrap()
+            .assume_checked();
+
+        let (spend, _) = wallet
+            .create_spend(40_000, 200, &recipient, false)
+            .expect("create_spend failed");
+        assert!(spend.is_explicitly_rbf());
+
+        let vsize = TaprootWallet::estimated_weight(&spend, &[prevout])
+            .unwrap()
+            .to_vbytes_ceil();
+        assert_eq!(
+            wallet.suggest_fee_bump(spend.compute_txid(), 10).unwrap(),
+            FeeBumpPlan::Rbf {
+                new_fee: 10 * vsize
+            }
+        );
+
+        // A feerate whose fee does not fit in a u64 is an error, not a wrapped fee
+        assert!(
+            wallet
+                .suggest_fee_bump(spend.compute_txid(), u64::MAX)
+                .is_err()
+        );
+
+        // A transaction the wallet never saw cannot be bumped
+        assert!(matches!(
+            wallet
+                .suggest_fee_bump(Txid::from_slice(&[8u8; 32]).unwrap(), 10)
+                .unwrap(),
+            FeeBumpPlan::Impossible { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_suggest_fee_bump_spends_child_of_non_rbf_deposit() {
+        use node::wallet::FeeBumpPlan;
+
+        let mut wallet = create_test_wallet();
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([7u8; 32]).unwrap(),
+        );
+
+        // A deposit spending a confirmed outpoint that opts out of replacement
+        let parent = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_slice(&[3u8; 32]).unwrap(),
+                    vout: 1,
+                },
+                script_sig: bitcoin::ScriptBuf::new(),
+                sequence: bitcoin::Sequence::MAX,
+                witness: bitcoin::Witness::new(),
+            }],
+            output: vec![bitcoin::TxOut {
+                value: Amount::from_sat(50_000),
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+        assert!(!parent.is_explicitly_rbf());
+        wallet.ingest_external_tx(&parent).unwrap();
+
+        let owned = bitcoin::TxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey: address.script_pubkey(),
+        };
+        let child = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: OutPoint {
+                    txid: parent.compute_txid(),
+                    vout: 0,
+                },
+                ..Default::default()
+            }],
+            output: vec![owned.clone()],
+        };
+        let child_vsize = TaprootWallet::estimated_weight(&child, &[owned])
+            .unwrap()
+            .to_vbytes_ceil();
+
+        // The fee the parent pays is unknown, so the child covers the whole package
+        let parent_vsize = parent.vsize() as u64;
+        assert_eq!(
+            wallet.suggest_fee_bump(parent.compute_txid(), 5).unwrap(),
+            FeeBumpPlan::Cpfp {
+                child_fee: 5 * (parent_vsize + child_vsize)
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fee_bump_of_multi_recipient_spend_only_reduces_change() {
+        use bitcoin::{ScriptBuf, Sequence, TxIn, TxOut};
+        use node::wallet::FeeBumpPlan;
+
+        let mut wallet = create_test_wallet();
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([9u8; 32]).unwrap(),
+        );
+        let prevouts = vec![TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: address.script_pubkey(),
+        }];
+        let mut output: Vec<TxOut> = [
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+        ]
+        .iter()
+        .map(|recipient| TxOut {
+            value: Amount::from_sat(30_000),
+            script_pubkey: bitcoin::Address::from_str(recipient)
+                .unwrap()
+                .assume_checked()
+                .script_pubkey(),
+        })
+        .collect();
+        output.push(TxOut {
+            value: Amount::from_sat(39_800),
+            script_pubkey: address.script_pubkey(),
+        });
+        let spend = bitcoin::Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_slice(&[5u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: bitcoin::Witness::new(),
+            }],
+            output,
+        };
+        wallet
+            .wallet_transactions
+            .insert(spend.compute_txid(), (spend.clone(), prevouts.clone()));
+        let change_sat = spend.output[2].value.to_sat();
+
+        // Only the change can give up sats, so the second recipient is not counted as change
+        let FeeBumpPlan::Rbf { new_fee } =
+            wallet.suggest_fee_bump(spend.compute_txid(), 4).unwrap()
+        else {
+            panic!("expected a replacement");
+        };
+        let (replacement, _) = wallet.bump_fee(&spend, &prevouts, new_fee).unwrap();
+
+        assert_eq!(replacement.output[..2], spend.output[..2]);
+        assert!(replacement.output[2].value.to_sat() < change_sat);
+    }
+
+    #[tokio::test]
+    async fn test_spends_never_pay_below_oracle_min_relay_feerate() {
+        use node::wallet::SpendOptions;
+        use node::wallet::taproot::MIN_RELAY_FEERATE_TTL;
+
+        let (tx_channel, _) = broadcast::channel::<NetworkEvent>(100);
+        let oracle = MockOracle::new(tx_channel, None);
+        oracle.set_min_relay_fee(2.0);
+        let mut wallet = TaprootWallet::new(Box::new(oracle.clone()), Vec::new(), Network::Testnet);
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([8u8; 32]).unwrap(),
+        );
+        let prevout = bitcoin::TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: address.script_pubkey(),
+        };
+        wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[8u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: prevout.value,
+                script_pubkey: prevout.script_pubkey.clone(),
+            },
+            address: address.clone(),
+        });
+
+        assert_eq!(wallet.refresh_min_relay_feerate().await, 2);
+
+        // A requested feerate under the floor is raised to it.
+        let (spend, _) = wallet
+            .create_spend_with_options(
+                40_000,
+                0,
+                &address,
+                true,
+                SpendOptions::default().with_feerate(1),
+            )
+            .unwrap();
+        let paid = 100_000 - spend.output.iter().map(|o| o.value.to_sat()).sum::<u64>();
+        let vsize = TaprootWallet::estimated_weight(&spend, std::slice::from_ref(&prevout))
+            .unwrap()
+            .to_vbytes_ceil();
+        assert_eq!(paid, 2 * vsize);
+
+        // A fixed fee under the floor is refused rather than built.
+        assert!(
+            wallet
+                .create_spend(40_000, 2 * vsize - 1, &address, true)
+                .is_err()
+        );
+        assert!(
+            wallet
+                .create_spend(40_000, 2 * vsize, &address, true)
+                .is_ok()
+        );
+
+        // The floor is cached until its TTL runs out.
+        oracle.set_min_relay_fee(5.0);
+        assert_eq!(wallet.refresh_min_relay_feerate().await, 2);
+        wallet.min_relay_feerate_fetched_at =
+            std::time::Instant::now().checked_sub(MIN_RELAY_FEERATE_TTL);
+        assert_eq!(wallet.refresh_min_relay_feerate().await, 5);
+    }
+
+    #[tokio::test]
+    async fn test_selection_prefers_confirmed_utxos_over_unconfirmed() {
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::util::SubscriberInitExt;
+
+        #[derive(Clone, Default)]
+        struct LogCapture(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for LogCapture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (tx_channel, _) = broadcast::channel::<NetworkEvent>(100);
+        let oracle = MockOracle::new(tx_channel, None);
+        let mut wallet = TaprootWallet::new(Box::new(oracle.clone()), Vec::new(), Network::Testnet);
+        let address = wallet.generate_new_address(
+            random_public_key(),
+            Scalar::from_be_bytes([9u8; 32]).unwrap(),
+        );
+        let utxo = |seed: u8, value_sat: u64| Utxo {
+            outpoint: OutPoint {
+                txid: Txid::from_slice(&[seed; 32]).unwrap(),
+                vout: 0,
+            },
+            value: Amount::from_sat(value_sat),
+            script_pubkey: address.script_pubkey(),
+        };
+        let confirmed = [utxo(1, 40_000), utxo(2, 30_000)];
+        // The largest output is still in the mempool.
+        let unconfirmed = utxo(3, 100_000);
+        for u in &confirmed {
+            oracle.confirm_transaction(u.outpoint.txid);
+        }
+        let mut reported = confirmed.to_vec();
+        reported.push(unconfirmed.clone());
+        oracle.set_utxos(reported);
+
+        wallet.refresh_utxos(Some(true)).await.unwrap();
+        assert_eq!(wallet.utxos.len(), 3);
+        assert!(wallet.mempool_utxos.contains(&unconfirmed.outpoint));
+
+        let logs = LogCapture::default();
+        let capture = logs.clone();
+        let _guard = tracing_subscriber::fmt()
+            .with_writer(move || capture.clone())
+            .with_ansi(false)
+            .finish()
+            .set_default();
+
+        // Confirmed outputs cover the spend, so the unconfirmed one is left alone.
+        let (spend, _) = wallet.create_spend(60_000, 1_000, &address, true).unwrap();
+        let inputs: Vec<OutPoint> = spend.input.iter().map(|i| i.previous_output).collect();
+        assert_eq!(inputs.len(), 2);
+        assert!(!inputs.contains(&unconfirmed.outpoint));
+        assert!(logs.0.lock().unwrap().is_empty());
+
+        // Only by adding the unconfirmed output can the spend be paid, with a warning.
+        let (spend, _) = wallet.create_spend(90_000, 1_000, &address, true).unwrap();
+        let inputs: Vec<OutPoint> = spend.input.iter().map(|i| i.previous_output).collect();
+        assert!(inputs.contains(&unconfirmed.outpoint));
+        let logged = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logged.contains("WARN"));
+        assert!(logged.contains("unconfirmed inputs"));
+
+        // A refresh that leaves out unconfirmed outputs never spends them.
+        oracle.set_utxos(confirmed.to_vec());
+        wallet.refresh_utxos(Some(false)).await.unwrap();
+        assert!(wallet.mempool_utxos.is_empty());
+        assert!(wallet.create_spend(90_000, 1_000, &address, true).is_err());
+    }
+
+    /// Wallet holding one UTXO per entry of `values`, spread over three change addresses.
+    fn wallet_with_utxos(values: &[u64]) -> (TaprootWallet, Vec<bitcoin::TxOut>) {
+        let mut wallet = create_test_wallet().with_change_outputs(3);
+        let pubkey = random_public_key();
+        let addresses: Vec<_> = (1u8..=3)
+            .map(|i| wallet.generate_new_address(pubkey, Scalar::from_be_bytes([i; 32]).unwrap()))
+            .collect();
+
+        let mut prevouts = Vec::new();
+        for (i, value) in (0u8..).zip(values) {
+            let address = &addresses[usize::from(i) % addresses.len()];
+            let prevout = bitcoin::TxOut {
+                value: Amount::from_sat(*value),
+                script_pubkey: address.script_pubkey(),
+            };
+            wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[i; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: prevout.value,
+                    script_pubkey: prevout.script_pubkey.clone(),
+                },
+                address: address.clone(),
+            });
+            prevouts.push(prevout);
+        }
+        (wallet, prevouts)
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_change_is_never_negative(
+            input_sat in 0u64..=u64::MAX,
+            amount_sat in 0u64..=u64::MAX,
+            fee_sat in 0u64..=u64::MAX,
+        ) {
+            match TaprootWallet::change_sat(input_sat, amount_sat, fee_sat) {
+                Ok(change) => {
+                    proptest::prop_assert_eq!(
+                        u128::from(input_sat),
+                        u128::from(amount_sat) + u128::from(fee_sat) + u128::from(change)
+                    );
+                }
+                Err(_) => proptest::prop_assert!(
+                    u128::from(input_sat) < u128::from(amount_sat) + u128::from(fee_sat)
+                ),
+            }
+        }
+
+        #[test]
+        fn prop_spend_balances_to_the_sat(
+            values in proptest::collection::vec(1_000u64..2_000_000, 1..6),
+            amount_sat in 1_000u64..5_000_000,
+            fee_sat in 0u64..50_000,
+            feerate_sat_vb in proptest::option::of(1u64..200),
+        ) {
+            use node::wallet::SpendOptions;
+            use node::wallet::taproot::DUST;
+
+            let (mut wallet, prevouts) = wallet_with_utxos(&values);
+            let recipient = bitcoin::Address::from_str("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+                .unwrap()
+                .assume_checked();
+            let options = feerate_sat_vb.map_or_else(SpendOptions::default, |rate| {
+                SpendOptions::default().with_feerate(rate)
+            });
+
+            // Underfunded spends and fees below the relay floor are refused, never built.
+            let Ok((tx, _)) =
+                wallet.create_spend_with_options(amount_sat, fee_sat, &recipient, true, options)
+            else {
+                return Ok(());
+            };
+
+            // Each generated UTXO's txid is filled with its index into `prevouts`.
+            let spent: Vec<bitcoin::TxOut> = tx
+                .input
+                .iter()
+                .map(|input| {
+                    prevouts[usize::from(input.previous_output.txid.to_byte_array()[0])].clone()
+                })
+                .collect();
+            let input_sat: u64 = spent.iter().map(|p| p.value.to_sat()).sum();
+            let output_sat: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+            proptest::prop_assert_eq!(tx.output[0].value.to_sat(), amount_sat);
+            proptest::prop_assert!(input_sat >= output_sat);
+            if let Some(rate) = feerate_sat_vb {
+                // The fee is charged on the rounded-up vsize, never below the caller's floor.
+                let paid = input_sat - output_sat;
+                let needed = TaprootWallet::estimate_fee_sat(&tx, &spent, rate).unwrap();
+                proptest::prop_assert!(paid >= needed.max(fee_sat));
+            } else {
+                // Change too small for its own output is the only thing paid on top of the fee.
+                let change = TaprootWallet::change_sat(input_sat, amount_sat, fee_sat).unwrap();
+                let folded = if change <= DUST { change } else { 0 };
+                proptest::prop_assert_eq!(input_sat, output_sat + fee_sat + folded);
+            }
+            proptest::prop_assert!(tx.output[1..].iter().all(|o| o.value.to_sat() > DUST));
+        }
+    }
 }
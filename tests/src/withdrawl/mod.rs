@@ -38,6 +38,10 @@ mod withdrawl_tests {
             .await
             .expect("Failed to add account setup transaction");
 
+        finalize_pending_block(node).await;
+    }
+
+    async fn finalize_pending_block(node: &mut crate::mocks::network::MockNodeState) {
         let setup_block = node
             .chain_interface_tx
             .send_message_with_response(abci::ChainMessage::GetProposedBlock {
@@ -54,8 +58,26 @@ mod withdrawl_tests {
                 .expect("Failed to finalize setup block");
         }
     }
+
+    async fn account_balance(
+        node: &mut crate::mocks::network::MockNodeState,
+        address: &str,
+    ) -> u64 {
+        match node
+            .chain_interface_tx
+            .send_message_with_response(abci::ChainMessage::GetAccount {
+                address: address.to_string(),
+            })
+            .await
+        {
+            Ok(abci::ChainResponse::GetAccount { account }) => {
+                account.map(|a| a.balance).unwrap_or_default()
+            }
+            _ => panic!("Failed to get account"),
+        }
+    }
     use bitcoin::{Address, Amount, CompressedPublicKey, OutPoint, Txid, hashes::Hash};
-    use node::wallet::TrackedUtxo;
+    use node::wallet::{TrackedUtxo, Wallet};
     use types::proto::node_proto::{
         ConfirmWithdrawalRequest, ProposeWithdrawalRequest, ProposeWithdrawalResponse,
     };
@@ -63,9 +85,8 @@ mod withdrawl_tests {
     use crate::mocks::network::MockNodeCluster;
     use grpc::grpc_operator;
     use node::handlers::withdrawl::SpendIntentState;
-    use std::collections::HashMap;
     use tokio::sync::mpsc::unbounded_channel;
-    use types::intents::WithdrawlIntent;
+    use types::intents::{FeePolicy, PendingSpend, WithdrawlIntent};
     use types::utxo::Utxo;
 
     #[tokio::test]
@@ -115,6 +136,8 @@ mod withdrawl_tests {
                     address_to: address_str,
                     public_key: public_key_hex,
                     blocks_to_confirm: None,
+                    fee_bump: None,
+                    fee_rate_sat_vb: None,
                 },
             )
             .await
@@ -147,15 +170,15 @@ mod withdrawl_tests {
         let address = Address::p2pkh(btc_pubkey, bitcoin::Network::Signet);
 
         // Prepare SpendIntent and state
-        let mut spend_state = SpendIntentState {
-            pending_intents: HashMap::new(),
-        };
+        let mut spend_state = SpendIntentState::new();
 
         let withdrawal_intent = WithdrawlIntent {
             amount_sat: 50_000,
             address_to: address.to_string(),
             public_key: hex::encode(public_key.serialize()),
             blocks_to_confirm: None,
+            fee_bump: None,
+            fee_rate_sat_vb: None,
         };
 
         let result = spend_state
@@ -199,15 +222,15 @@ mod withdrawl_tests {
         });
 
         // SpendIntentState under test
-        let mut spend_state = SpendIntentState {
-            pending_intents: HashMap::new(),
-        };
+        let mut spend_state = SpendIntentState::new();
 
         let withdrawal_intent = WithdrawlIntent {
             amount_sat: 50_000,
             address_to: address.to_string(),
             public_key: hex::encode(public_key.serialize()),
             blocks_to_confirm: None,
+            fee_bump: None,
+            fee_rate_sat_vb: None,
         };
 
         // First propose to obtain challenge
@@ -217,7 +240,9 @@ mod withdrawl_tests {
             .expect("Propose withdrawal should succeed");
 
         // Now attempt to confirm with an obviously invalid signature
-        let result = spend_state.confirm_withdrawal(node, &challenge, "deadbeef");
+        let result = spend_state
+            .confirm_withdrawal(node, &challenge, "deadbeef")
+            .await;
 
         // Expect error
         assert!(result.is_err());
@@ -288,6 +313,8 @@ mod withdrawl_tests {
                     address_to: dest_addr_str,
                     public_key: pubkey_hex_clone,
                     blocks_to_confirm: None,
+                    fee_bump: None,
+                    fee_rate_sat_vb: None,
                 },
             )
             .await
@@ -366,4 +393,906 @@ mod withdrawl_tests {
             assert!(!spent_still_present, "Spent UTXO still present in wallet");
         }
     }
+
+    #[tokio::test]
+    async fn confirm_withdrawal_rejects_expired_quote() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (secret_key, public_key) =
+            secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let address = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        setup_account_with_balance(node, &hex::encode(public_key.serialize()), 100_000).await;
+
+        node.wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[4u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(100_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            address: address.clone(),
+        });
+
+        let withdrawal_intent = WithdrawlIntent {
+            amount_sat: 50_000,
+            address_to: address.to_string(),
+            public_key: hex::encode(public_key.serialize()),
+            blocks_to_confirm: None,
+            fee_bump: None,
+            fee_rate_sat_vb: None,
+        };
+
+        let sign = |challenge: &str| {
+            let msg =
+                bitcoin::secp256k1::Message::from_digest_slice(&hex::decode(challenge).unwrap())
+                    .unwrap();
+            hex::encode(secp.sign_ecdsa(&msg, &secret_key).serialize_der())
+        };
+
+        // Within the quote window the withdrawal is accepted
+        let mut spend_state = SpendIntentState::new();
+        let (_, challenge) = spend_state
+            .propose_withdrawal(node, &withdrawal_intent)
+            .await
+            .expect("Propose withdrawal should succeed");
+        spend_state
+            .confirm_withdrawal(node, &challenge, &sign(&challenge))
+            .await
+            .expect("Confirm within the quote window should succeed");
+
+        // A zero TTL means the quote is already stale when confirmed
+        let mut spend_state = SpendIntentState::with_quote_ttl(0);
+        let (_, challenge) = spend_state
+            .propose_withdrawal(node, &withdrawal_intent)
+            .await
+            .expect("Propose withdrawal should succeed");
+        let result = spend_state
+            .confirm_withdrawal(node, &challenge, &sign(&challenge))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(types::errors::NodeError::WithdrawalQuoteExpired { .. })
+        ));
+        assert!(!spend_state.pending_intents.contains_key(&challenge));
+
+        // Expired quotes are also cleaned up on tick without being confirmed
+        let (_, challenge) = spend_state
+            .propose_withdrawal(node, &withdrawal_intent)
+            .await
+            .expect("Propose withdrawal should succeed");
+        assert_eq!(spend_state.prune_expired_intents(), 1);
+        assert!(!spend_state.pending_intents.contains_key(&challenge));
+    }
+
+    async fn assert_withdrawal_debit(fee_policy: FeePolicy, expected_debit: u64) {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+        node.config.fee_policy = fee_policy;
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (_, public_key) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let public_key_hex = hex::encode(public_key.serialize());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let address = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        let initial_balance = 100_000;
+        setup_account_with_balance(node, &public_key_hex, initial_balance).await;
+
+        node.wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[6u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(initial_balance),
+                script_pubkey: address.script_pubkey(),
+            },
+            address: address.clone(),
+        });
+
+        // The quote only includes the fee when the user pays it
+        let amount_sat = 50_000;
+        let mut spend_state = SpendIntentState::new();
+        let (quote, challenge) = spend_state
+            .propose_withdrawal(
+                node,
+                &WithdrawlIntent {
+                    amount_sat,
+                    address_to: address.to_string(),
+                    public_key: public_key_hex.clone(),
+                    blocks_to_confirm: None,
+                    fee_bump: None,
+                    fee_rate_sat_vb: None,
+                },
+            )
+            .await
+            .expect("Propose withdrawal should succeed");
+        let fee = spend_state.pending_intents[&challenge].fee;
+        assert_eq!(quote, amount_sat + fee_policy.user_fee(fee));
+
+        let (tx, _) = node
+            .wallet
+            .create_spend(amount_sat, 1_000, &address, true)
+            .expect("create_spend failed");
+
+        spend_state
+            .handle_withdrawl_message(
+                node,
+                PendingSpend {
+                    recipient_script: tx.output[0].script_pubkey.clone(),
+                    tx,
+                    user_pubkey: public_key_hex.clone(),
+                    address_to: address.to_string(),
+                    fee: 1_000,
+                },
+            )
+            .await
+            .expect("Failed to apply withdrawal");
+        finalize_pending_block(node).await;
+
+        let balance = account_balance(node, &public_key_hex).await;
+        assert_eq!(initial_balance - balance, expected_debit);
+    }
+
+    #[tokio::test]
+    async fn user_pays_fee_policy_debits_amount_and_fee() {
+        assert_withdrawal_debit(FeePolicy::UserPays, 51_000).await;
+    }
+
+    #[tokio::test]
+    async fn vault_absorbs_fee_policy_debits_amount_only() {
+        assert_withdrawal_debit(FeePolicy::VaultAbsorbs, 50_000).await;
+    }
+
+    #[tokio::test]
+    async fn propose_withdrawal_enforces_pending_intent_cap() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (_, public_key) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let address = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        setup_account_with_balance(node, &hex::encode(public_key.serialize()), 100_000).await;
+
+        node.wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[6u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(100_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            address: address.clone(),
+        });
+
+        let withdrawal_intent = WithdrawlIntent {
+            amount_sat: 10_000,
+            address_to: address.to_string(),
+            public_key: hex::encode(public_key.serialize()),
+            blocks_to_confirm: None,
+            fee_bump: None,
+            fee_rate_sat_vb: None,
+        };
+
+        let mut spend_state = SpendIntentState::new().with_max_pending_intents(3);
+        let mut challenges = Vec::new();
+        for _ in 0..3 {
+            let (_, challenge) = spend_state
+                .propose_withdrawal(node, &withdrawal_intent)
+                .await
+                .expect("Propose withdrawal under the cap should succeed");
+            challenges.push(challenge);
+        }
+
+        // Flooding past the cap with nothing expired is refused
+        for _ in 0..5 {
+            let result = spend_state
+                .propose_withdrawal(node, &withdrawal_intent)
+                .await;
+            assert!(matches!(
+                result,
+                Err(types::errors::NodeError::TooManyPendingIntents { limit: 3, .. })
+            ));
+        }
+        assert_eq!(spend_state.pending_intents.len(), 3);
+
+        // Once quotes expire, the oldest expired one makes room for the next proposal
+        spend_state
+            .pending_intents
+            .get_mut(&challenges[0])
+            .unwrap()
+            .expires_at = 2;
+        spend_state
+            .pending_intents
+            .get_mut(&challenges[1])
+            .unwrap()
+            .expires_at = 1;
+
+        let (_, challenge) = spend_state
+            .propose_withdrawal(node, &withdrawal_intent)
+            .await
+            .expect("Propose withdrawal should evict an expired quote");
+
+        assert_eq!(spend_state.pending_intents.len(), 3);
+        assert!(spend_state.pending_intents.contains_key(&challenge));
+        assert!(spend_state.pending_intents.contains_key(&challenges[0]));
+        assert!(!spend_state.pending_intents.contains_key(&challenges[1]));
+    }
+
+    #[tokio::test]
+    async fn propose_withdrawal_enforces_amount_limits() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (_, public_key) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let address = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        setup_account_with_balance(node, &hex::encode(public_key.serialize()), 100_000).await;
+
+        node.wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[7u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(100_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            address: address.clone(),
+        });
+
+        let mut spend_state = SpendIntentState::new().with_withdrawal_limits(1_000, 50_000);
+        let intent_for = |amount_sat| WithdrawlIntent {
+            amount_sat,
+            address_to: address.to_string(),
+            public_key: hex::encode(public_key.serialize()),
+            blocks_to_confirm: None,
+            fee_bump: None,
+            fee_rate_sat_vb: None,
+        };
+
+        for amount_sat in [999, 50_001] {
+            let result = spend_state
+                .propose_withdrawal(node, &intent_for(amount_sat))
+                .await;
+            assert!(
+                matches!(
+                    result,
+                    Err(types::errors::NodeError::WithdrawalAmountOutOfRange {
+                        amount_sat: rejected,
+                        min_sat: 1_000,
+                        max_sat: 50_000,
+                    }) if rejected == amount_sat
+                ),
+                "{amount_sat} sat should be rejected"
+            );
+        }
+        assert!(spend_state.pending_intents.is_empty());
+
+        spend_state
+            .propose_withdrawal(node, &intent_for(10_000))
+            .await
+            .expect("In-range withdrawal should be quoted");
+        assert_eq!(spend_state.pending_intents.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn confirm_withdrawal_rejects_replayed_challenge() {
+        let mut cluster = MockNodeCluster::new_with_keys(2).await;
+        cluster.setup().await;
+
+        let node_peer = *cluster.nodes.keys().next().unwrap();
+        let node = cluster.nodes.get_mut(&node_peer).unwrap();
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (secret_key, public_key) =
+            secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let address = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        setup_account_with_balance(node, &hex::encode(public_key.serialize()), 100_000).await;
+
+        node.wallet.utxos.push(TrackedUtxo {
+            utxo: Utxo {
+                outpoint: OutPoint {
+                    txid: Txid::from_slice(&[7u8; 32]).unwrap(),
+                    vout: 0,
+                },
+                value: Amount::from_sat(100_000),
+                script_pubkey: address.script_pubkey(),
+            },
+            address: address.clone(),
+        });
+
+        let withdrawal_intent = WithdrawlIntent {
+            amount_sat: 20_000,
+            address_to: address.to_string(),
+            public_key: hex::encode(public_key.serialize()),
+            blocks_to_confirm: None,
+            fee_bump: None,
+            fee_rate_sat_vb: None,
+        };
+
+        let msg = |challenge: &str| {
+            bitcoin::secp256k1::Message::from_digest_slice(&hex::decode(challenge).unwrap())
+                .unwrap()
+        };
+
+        let mut spend_state = SpendIntentState::new();
+        let (_, challenge) = spend_state
+            .propose_withdrawal(node, &withdrawal_intent)
+            .await
+            .expect("Propose withdrawal should succeed");
+        let signature = hex::encode(
+            secp.sign_ecdsa(&msg(&challenge), &secret_key)
+                .serialize_der(),
+        );
+        spend_state
+            .confirm_withdrawal(node, &challenge, &signature)
+            .await
+            .expect("First confirmation should succeed");
+
+        // The same intent is quoted again and ends up filed under the old challenge.
+        let (_, fresh_challenge) = spend_state
+            .propose_withdrawal(node, &withdrawal_intent)
+            .await
+            .expect("Re-proposal should succeed");
+        let pending = spend_state
+            .pending_intents
+            .remove(&fresh_challenge)
+            .unwrap();
+        spend_state
+            .pending_intents
+            .insert(challenge.clone(), pending);
+
+        let result = spend_state
+            .confirm_withdrawal(node, &challenge, &signature)
+            .await;
+        assert!(matches!(
+            result,
+            Err(types::errors::NodeError::WithdrawalChallengeReplayed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn withdrawal_is_recorded_in_audit_log_in_order() {
+        use types::proto::node_proto::GetAuditLogRequest;
+
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator_peer = *cluster.nodes.keys().next().unwrap();
+        let initiator_network = cluster.networks.get(&initiator_peer).unwrap().clone();
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (secret_key, public_key) =
+            secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let public_key_hex = hex::encode(public_key.serialize());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let dest_addr = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        for node in cluster.nodes.values_mut() {
+            setup_account_with_balance(node, &public_key_hex, 100_000).await;
+            node.wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[4u8; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(100_000),
+                    script_pubkey: dest_addr.script_pubkey(),
+                },
+                address: dest_addr.clone(),
+            });
+        }
+
+        let network = initiator_network.clone();
+        let address_to = dest_addr.to_string();
+        let propose = tokio::spawn(async move {
+            grpc_operator::propose_withdrawal(
+                &network,
+                ProposeWithdrawalRequest {
+                    amount_satoshis: 40_000,
+                    address_to,
+                    public_key: public_key_hex,
+                    blocks_to_confirm: None,
+                    fee_bump: None,
+                    fee_rate_sat_vb: None,
+                },
+            )
+            .await
+        });
+        cluster.run_n_iterations(10).await;
+        let challenge = propose.await.unwrap().expect("Propose failed").challenge;
+
+        let msg = bitcoin::secp256k1::Message::from_digest_slice(&hex::decode(&challenge).unwrap())
+            .unwrap();
+        let signature = hex::encode(secp.sign_ecdsa(&msg, &secret_key).serialize_der());
+        let network = initiator_network.clone();
+        let confirm_challenge = challenge.clone();
+        let confirm = tokio::spawn(async move {
+            grpc_operator::confirm_withdrawal(
+                &network,
+                ConfirmWithdrawalRequest {
+                    challenge: confirm_challenge,
+                    signature,
+                },
+            )
+            .await
+        });
+        cluster.run_n_iterations(10).await;
+        confirm.await.unwrap().expect("Confirm failed");
+
+        let network = initiator_network.clone();
+        let audit_log = tokio::spawn(async move {
+            grpc_operator::get_audit_log(&network, GetAuditLogRequest { from: 0, to: 0 }).await
+        });
+        tokio::task::yield_now().await;
+        cluster.run_n_iterations(1).await;
+        let entries = audit_log
+            .await
+            .unwrap()
+            .expect("GetAuditLog failed")
+            .entries;
+
+        let kinds: Vec<_> = entries.iter().map(|entry| entry.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            [
+                "withdrawal_proposed",
+                "withdrawal_confirmed",
+                "signing_started",
+                "transaction_broadcast",
+            ]
+        );
+        assert!(entries.iter().all(|entry| entry.outcome == "accepted"));
+        assert!(
+            entries
+                .windows(2)
+                .all(|pair| pair[0].sequence < pair[1].sequence)
+        );
+        assert_eq!(entries[0].reference, challenge);
+        assert_eq!(entries[0].amount_satoshis, 40_000);
+        assert_eq!(entries[0].address, dest_addr.to_string());
+        assert_eq!(entries[3].amount_satoshis, 40_000);
+    }
+
+    #[tokio::test]
+    async fn explicit_fee_rate_overrides_oracle_estimate() {
+        use crate::mocks::network::MockOracle;
+
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator_peer = *cluster.nodes.keys().next().unwrap();
+        let initiator_network = cluster.networks.get(&initiator_peer).unwrap().clone();
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (secret_key, public_key) =
+            secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let public_key_hex = hex::encode(public_key.serialize());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let dest_addr = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        // The oracle estimates 10 sat/vB; the user asks for 2.
+        let oracle = MockOracle::new(tokio::sync::broadcast::channel(16).0, None);
+        let fee_rate = 2u64;
+        for node in cluster.nodes.values_mut() {
+            setup_account_with_balance(node, &public_key_hex, 100_000).await;
+            node.oracle = Box::new(oracle.clone());
+            node.wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[10u8; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(100_000),
+                    script_pubkey: dest_addr.script_pubkey(),
+                },
+                address: dest_addr.clone(),
+            });
+        }
+
+        let (dry_run, _) = cluster
+            .nodes
+            .get_mut(&initiator_peer)
+            .unwrap()
+            .wallet
+            .create_spend(40_000, fee_rate * 120, &dest_addr, true)
+            .unwrap();
+        let expected_fee = fee_rate * u64::try_from(dry_run.vsize()).unwrap() * 2;
+
+        let network = initiator_network.clone();
+        let address_to = dest_addr.to_string();
+        let propose = tokio::spawn(async move {
+            grpc_operator::propose_withdrawal(
+                &network,
+                ProposeWithdrawalRequest {
+                    amount_satoshis: 40_000,
+                    address_to,
+                    public_key: public_key_hex,
+                    blocks_to_confirm: None,
+                    fee_bump: None,
+                    fee_rate_sat_vb: Some(fee_rate),
+                },
+            )
+            .await
+        });
+        cluster.run_n_iterations(10).await;
+        let quote = propose.await.unwrap().expect("Propose failed");
+        assert_eq!(quote.quote_satoshis, 40_000 + expected_fee);
+
+        let msg =
+            bitcoin::secp256k1::Message::from_digest_slice(&hex::decode(&quote.challenge).unwrap())
+                .unwrap();
+        let signature = hex::encode(secp.sign_ecdsa(&msg, &secret_key).serialize_der());
+        let network = initiator_network.clone();
+        let confirm = tokio::spawn(async move {
+            grpc_operator::confirm_withdrawal(
+                &network,
+                ConfirmWithdrawalRequest {
+                    challenge: quote.challenge,
+                    signature,
+                },
+            )
+            .await
+        });
+        cluster.run_n_iterations(10).await;
+        confirm.await.unwrap().expect("Confirm failed");
+
+        let broadcast = oracle
+            .broadcast_transactions()
+            .first()
+            .cloned()
+            .expect("withdrawal was not broadcast");
+        let paid: u64 = broadcast.output.iter().map(|o| o.value.to_sat()).sum();
+        assert_eq!(100_000 - paid, expected_fee);
+    }
+
+    #[tokio::test]
+    async fn fee_rate_above_configured_maximum_is_rejected() {
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator_peer = *cluster.nodes.keys().next().unwrap();
+        let initiator_network = cluster.networks.get(&initiator_peer).unwrap().clone();
+        let max_fee_rate = cluster.nodes[&initiator_peer]
+            .config
+            .max_withdrawal_fee_rate_sat_vb;
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (_, public_key) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let public_key_hex = hex::encode(public_key.serialize());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let dest_addr = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        for node in cluster.nodes.values_mut() {
+            setup_account_with_balance(node, &public_key_hex, 100_000).await;
+            node.wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[11u8; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(100_000),
+                    script_pubkey: dest_addr.script_pubkey(),
+                },
+                address: dest_addr.clone(),
+            });
+        }
+
+        // Rates past the cap, up to one whose fee would overflow, are refused, not quoted.
+        for fee_rate in [max_fee_rate + 1, u64::MAX] {
+            let network = initiator_network.clone();
+            let address_to = dest_addr.to_string();
+            let public_key = public_key_hex.clone();
+            let propose = tokio::spawn(async move {
+                grpc_operator::propose_withdrawal(
+                    &network,
+                    ProposeWithdrawalRequest {
+                        amount_satoshis: 40_000,
+                        address_to,
+                        public_key,
+                        blocks_to_confirm: None,
+                        fee_bump: None,
+                        fee_rate_sat_vb: Some(fee_rate),
+                    },
+                )
+                .await
+            });
+            cluster.run_n_iterations(10).await;
+            assert!(propose.await.unwrap().is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn stuck_withdrawal_is_replaced_with_higher_fee() {
+        use crate::mocks::network::MockOracle;
+        use types::intents::FeeBumpPolicy;
+        use types::network::network_event::SelfRequest;
+
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+        let initiator = *cluster.nodes.keys().next().unwrap();
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (_, vault_key) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let vault_addr = Address::p2wpkh(
+            &CompressedPublicKey::from_slice(&vault_key.serialize()).unwrap(),
+            bitcoin::Network::Testnet,
+        );
+        let (_, recipient_key) = secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let recipient = Address::p2wpkh(
+            &CompressedPublicKey::from_slice(&recipient_key.serialize()).unwrap(),
+            bitcoin::Network::Testnet,
+        );
+
+        // Every node sees the same chain, on which nothing ever confirms.
+        let oracle = MockOracle::new(tokio::sync::broadcast::channel(16).0, None);
+        let user = "fee_bump_user";
+        for node in cluster.nodes.values_mut() {
+            setup_account_with_balance(node, user, 100_000).await;
+            node.oracle = Box::new(oracle.clone());
+            node.wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[9u8; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(100_000),
+                    script_pubkey: vault_addr.script_pubkey(),
+                },
+                address: vault_addr.clone(),
+            });
+        }
+
+        let policy = FeeBumpPolicy {
+            after_blocks: 3,
+            max_fee_sat: 5_000,
+        };
+        cluster.send_self_request_to_peer(
+            initiator,
+            SelfRequest::Spend {
+                amount_sat: 50_000,
+                fee: 500,
+                address_to: recipient.to_string(),
+                user_pubkey: user.to_string(),
+                fee_bump: Some(policy),
+                challenge: None,
+            },
+        );
+        cluster.run_n_iterations(10).await;
+
+        let original = oracle
+            .broadcast_transactions()
+            .first()
+            .cloned()
+            .expect("withdrawal was not broadcast");
+        let original_txid = original.compute_txid();
+        let replacements = |oracle: &MockOracle| {
+            oracle
+                .broadcast_transactions()
+                .into_iter()
+                .filter(|tx| tx.compute_txid() != original_txid)
+                .collect::<Vec<_>>()
+        };
+
+        // Not yet past the threshold: nothing is replaced.
+        oracle.set_block_height(2);
+        cluster.send_self_request_to_peer(initiator, SelfRequest::MaintenanceTick);
+        cluster.run_n_iterations(10).await;
+        assert!(replacements(&oracle).is_empty());
+
+        oracle.set_block_height(3);
+        cluster.send_self_request_to_peer(initiator, SelfRequest::MaintenanceTick);
+        cluster.run_n_iterations(10).await;
+
+        let replacements = replacements(&oracle);
+        assert_eq!(replacements.len(), 1, "expected one fee bump replacement");
+        let replacement = &replacements[0];
+
+        assert_eq!(
+            replacement.input[0].previous_output,
+            original.input[0].previous_output
+        );
+        assert!(!replacement.input[0].witness.is_empty());
+        assert_eq!(replacement.output[0], original.output[0]);
+
+        let total_out =
+            |tx: &bitcoin::Transaction| -> u64 { tx.output.iter().map(|o| o.value.to_sat()).sum() };
+        let new_fee = 100_000 - total_out(replacement);
+        assert_eq!(100_000 - total_out(&original), 500);
+        assert!(new_fee > 500 && new_fee <= policy.max_fee_sat);
+    }
+
+    #[tokio::test]
+    async fn broadcast_withdrawal_txid_is_recovered_from_db() {
+        use crate::mocks::network::MockOracle;
+        use types::intents::WithdrawalStatus;
+
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let initiator_peer = *cluster.nodes.keys().next().unwrap();
+        let initiator_network = cluster.networks.get(&initiator_peer).unwrap().clone();
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let (secret_key, public_key) =
+            secp.generate_keypair(&mut bitcoin::secp256k1::rand::thread_rng());
+        let public_key_hex = hex::encode(public_key.serialize());
+        let btc_pubkey = CompressedPublicKey::from_slice(&public_key.serialize()).unwrap();
+        let dest_addr = Address::p2wpkh(&btc_pubkey, bitcoin::Network::Signet);
+
+        let oracle = MockOracle::new(tokio::sync::broadcast::channel(16).0, None);
+        oracle.set_block_height(812_000);
+        for node in cluster.nodes.values_mut() {
+            setup_account_with_balance(node, &public_key_hex, 100_000).await;
+            node.oracle = Box::new(oracle.clone());
+            node.wallet.utxos.push(TrackedUtxo {
+                utxo: Utxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_slice(&[11u8; 32]).unwrap(),
+                        vout: 0,
+                    },
+                    value: Amount::from_sat(100_000),
+                    script_pubkey: dest_addr.script_pubkey(),
+                },
+                address: dest_addr.clone(),
+            });
+        }
+
+        let network = initiator_network.clone();
+        let address_to = dest_addr.to_string();
+        let propose = tokio::spawn(async move {
+            grpc_operator::propose_withdrawal(
+                &network,
+                ProposeWithdrawalRequest {
+                    amount_satoshis: 40_000,
+                    address_to,
+                    public_key: public_key_hex,
+                    blocks_to_confirm: None,
+                    fee_bump: None,
+                    fee_rate_sat_vb: None,
+                },
+            )
+            .await
+        });
+        cluster.run_n_iterations(10).await;
+        let challenge = propose.await.unwrap().expect("Propose failed").challenge;
+
+        let msg = bitcoin::secp256k1::Message::from_digest_slice(&hex::decode(&challenge).unwrap())
+            .unwrap();
+        let signature = hex::encode(secp.sign_ecdsa(&msg, &secret_key).serialize_der());
+        let network = initiator_network.clone();
+        let confirm_challenge = challenge.clone();
+        let confirm = tokio::spawn(async move {
+            grpc_operator::confirm_withdrawal(
+                &network,
+                ConfirmWithdrawalRequest {
+                    challenge: confirm_challenge,
+                    signature,
+                },
+            )
+            .await
+        });
+        cluster.run_n_iterations(10).await;
+        confirm.await.unwrap().expect("Confirm failed");
+
+        let broadcast_txid = oracle
+            .broadcast_transactions()
+            .first()
+            .expect("withdrawal was not broadcast")
+            .compute_txid();
+
+        // Rebuild the withdrawal state the way a restarted node would, from its DB alone.
+        let initiator = cluster.nodes.get_mut(&initiator_peer).unwrap();
+        let records = SpendIntentState::load_withdrawal_records(&mut initiator.chain_interface_tx)
+            .await
+            .unwrap();
+        let restored = SpendIntentState::new().with_broadcast_withdrawals(records);
+
+        let record = restored
+            .broadcast_withdrawals
+            .get(&challenge)
+            .expect("withdrawal mapping was not recovered");
+        assert_eq!(record.txid, broadcast_txid.to_string());
+        assert_eq!(record.status, WithdrawalStatus::Broadcast);
+        assert_eq!(record.broadcast_height, 812_000);
+    }
+
+    #[tokio::test]
+    async fn fee_estimates_report_clamped_oracle_rates_per_target() {
+        use crate::mocks::network::MockOracle;
+        use node::handlers::withdrawl::{MAX_FEE_RATE_SAT_VB, MIN_FEE_RATE_SAT_VB};
+        use types::proto::node_proto::GetFeeEstimatesRequest;
+
+        let mut cluster = MockNodeCluster::new_with_keys(3).await;
+        cluster.setup().await;
+
+        let peer = *cluster.nodes.keys().next().unwrap();
+        let network = cluster.networks.get(&peer).unwrap().clone();
+
+        // The next-block rate spikes above the band and the 12-block rate sinks below it.
+        let oracle = MockOracle::new(tokio::sync::broadcast::channel(16).0, None);
+        oracle.set_fee_estimates(
+            [(1, 900.0), (3, 25.0), (6, 12.5), (12, 0.4)]
+                .into_iter()
+                .collect(),
+        );
+        cluster.nodes.get_mut(&peer).unwrap().oracle = Box::new(oracle);
+
+        let request = tokio::spawn(async move {
+            grpc_operator::get_fee_estimates(&network, GetFeeEstimatesRequest {}).await
+        });
+        cluster.run_n_iterations(1).await;
+        let estimates: Vec<(u32, f64)> = request
+            .await
+            .unwrap()
+            .expect("GetFeeEstimates failed")
+            .estimates
+            .into_iter()
+            .map(|estimate| (estimate.target_blocks, estimate.sat_per_vb))
+            .collect();
+
+        assert_eq!(
+            estimates,
+            [
+                (1, MAX_FEE_RATE_SAT_VB),
+                (3, 25.0),
+                (6, 12.5),
+                (12, MIN_FEE_RATE_SAT_VB),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn median_fee_oracle_ignores_outlier_backend() {
+        use crate::mocks::network::MockOracle;
+        use oracle::{median::MedianFeeOracle, oracle::Oracle};
+
+        let backend = |rate: f64| {
+            let oracle = MockOracle::new(tokio::sync::broadcast::channel(16).0, None);
+            oracle.set_fee_estimates([(6, rate)].into_iter().collect());
+            oracle
+        };
+        let outlier = backend(14.0);
+        let median = MedianFeeOracle::new(
+            Box::new(backend(10.0)),
+            vec![
+                Box::new(backend(10.0)),
+                Box::new(backend(12.0)),
+                Box::new(outlier.clone()),
+            ],
+        );
+
+        assert_eq!(median.get_current_fee_per_vb(Some(6)).await.unwrap(), 12.0);
+
+        // A backend quoting far above the others is dropped rather than pulling the rate up
+        outlier.set_fee_estimates([(6, 400.0)].into_iter().collect());
+        assert_eq!(median.get_current_fee_per_vb(Some(6)).await.unwrap(), 11.0);
+    }
 }